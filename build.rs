@@ -0,0 +1,6 @@
+// `sqlx::migrate!` embeds `migrations/` and `graphql_client` embeds the Linear operations at
+// compile time; rebuild when either changes.
+fn main() {
+    println!("cargo:rerun-if-changed=migrations");
+    println!("cargo:rerun-if-changed=src/linear/graphql");
+}
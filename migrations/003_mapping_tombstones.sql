@@ -0,0 +1,13 @@
+-- Mappings removed from sync_mappings (unlinked, thread deleted, ...) are kept here as an
+-- audit record of who removed them and why.
+CREATE TABLE IF NOT EXISTS mapping_tombstones (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    discord_thread_id TEXT NOT NULL,
+    linear_issue_id TEXT NOT NULL,
+    linear_identifier TEXT NOT NULL,
+    channel_type TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    actor_discord_user_id TEXT,
+    mapping_created_at TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
@@ -0,0 +1,7 @@
+-- Threads in channels with an intake form, waiting for the author to fill it in.
+CREATE TABLE IF NOT EXISTS pending_intakes (
+    discord_thread_id TEXT PRIMARY KEY,
+    author_discord_user_id TEXT NOT NULL,
+    prompt_message_id TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
@@ -0,0 +1,6 @@
+-- Threads whose issue closed and that should be archived once the grace period elapses.
+CREATE TABLE IF NOT EXISTS pending_archives (
+    discord_thread_id TEXT PRIMARY KEY,
+    archive_at TEXT NOT NULL,
+    lock_thread INTEGER NOT NULL DEFAULT 0
+);
@@ -0,0 +1,8 @@
+-- Last known Linear title per tracked issue, and the title last applied to the Discord thread.
+-- The two differ while a rename is waiting out Discord's thread rename rate limit.
+CREATE TABLE IF NOT EXISTS issue_titles (
+    linear_issue_id TEXT PRIMARY KEY,
+    title TEXT NOT NULL,
+    applied_title TEXT NOT NULL,
+    renamed_at TEXT
+);
@@ -0,0 +1,8 @@
+-- Discord user ↔ Linear user identity links, created with `/linear connect`.
+CREATE TABLE IF NOT EXISTS user_links (
+    discord_user_id TEXT PRIMARY KEY,
+    linear_user_id TEXT NOT NULL UNIQUE,
+    linear_name TEXT NOT NULL,
+    linear_email TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
@@ -0,0 +1,7 @@
+-- Webhooks the bot created in forum channels to relay Linear comments under the author's name.
+-- `webhook` is the serialized Discord webhook object, including its execution token.
+CREATE TABLE IF NOT EXISTS channel_webhooks (
+    discord_channel_id TEXT PRIMARY KEY,
+    webhook TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
@@ -0,0 +1,6 @@
+-- Last assignee announced in Discord per tracked issue (NULL = unassigned).
+CREATE TABLE IF NOT EXISTS issue_assignees (
+    linear_issue_id TEXT PRIMARY KEY,
+    assignee_id TEXT,
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
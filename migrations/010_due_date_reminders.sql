@@ -0,0 +1,9 @@
+-- Due date reminders already posted, so each (issue, due date, kind) is announced once.
+-- Moving the due date re-arms its reminders.
+CREATE TABLE IF NOT EXISTS due_date_reminders (
+    linear_issue_id TEXT NOT NULL,
+    due_date TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    sent_at TEXT NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (linear_issue_id, due_date, kind)
+);
@@ -0,0 +1,6 @@
+-- Pinned status embed kept up to date in each tracked thread (channels with status_embed).
+CREATE TABLE IF NOT EXISTS status_embeds (
+    linear_issue_id TEXT PRIMARY KEY,
+    discord_message_id TEXT NOT NULL,
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
@@ -0,0 +1,10 @@
+-- Thread notifications held back so bursts of Linear updates go out as one digest message.
+CREATE TABLE IF NOT EXISTS pending_notifications (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    discord_thread_id TEXT NOT NULL,
+    body TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_pending_notifications_thread
+    ON pending_notifications (discord_thread_id);
@@ -0,0 +1,6 @@
+-- Newest Linear comment handled per tracked issue, so comment polls only fetch what's new.
+CREATE TABLE IF NOT EXISTS comment_cursors (
+    linear_issue_id TEXT PRIMARY KEY,
+    last_created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
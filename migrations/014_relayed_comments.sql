@@ -0,0 +1,12 @@
+-- Linear comments relayed into Discord as a single message, whose edits and deletions are
+-- mirrored onto that message.
+CREATE TABLE IF NOT EXISTS relayed_comments (
+    linear_comment_id TEXT PRIMARY KEY,
+    linear_issue_id TEXT NOT NULL,
+    discord_message_id TEXT NOT NULL,
+    via_webhook INTEGER NOT NULL DEFAULT 0,
+    linear_updated_at TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_relayed_comments_issue ON relayed_comments (linear_issue_id);
@@ -0,0 +1,12 @@
+-- Sync operations that failed and are retried by the poller until they succeed. Each payload
+-- is a serialized `sync::outbox::Operation`.
+CREATE TABLE IF NOT EXISTS pending_operations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    -- At most one queued operation per target (thread, Discord message, issue).
+    dedupe_key TEXT NOT NULL UNIQUE,
+    payload TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT NOT NULL,
+    next_attempt_at TEXT NOT NULL DEFAULT (datetime('now')),
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
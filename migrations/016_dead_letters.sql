@@ -0,0 +1,12 @@
+-- Queued sync operations that failed `OUTBOX_MAX_ATTEMPTS` times. They stay here until an admin
+-- requeues them with `/linear retry-failed`.
+CREATE TABLE IF NOT EXISTS dead_letters (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    dedupe_key TEXT NOT NULL UNIQUE,
+    payload TEXT NOT NULL,
+    attempts INTEGER NOT NULL,
+    last_error TEXT NOT NULL,
+    -- When the operation was first queued.
+    created_at TEXT NOT NULL,
+    failed_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
@@ -0,0 +1,7 @@
+-- Per-team timestamp the status poller last fetched updates from, so a restart resumes where the
+-- previous run left off instead of skipping what changed while the bot was down.
+CREATE TABLE IF NOT EXISTS poll_cursors (
+    linear_team_id TEXT PRIMARY KEY,
+    last_polled_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
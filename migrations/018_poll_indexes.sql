@@ -0,0 +1,12 @@
+-- Indexes for the scans the poller runs every pass. Per-issue lookups on sync_mappings,
+-- synced_comments, and linear_status_cache already go through their UNIQUE / PRIMARY KEY
+-- indexes; these cover the queue and rename scans, which otherwise read every row.
+CREATE INDEX IF NOT EXISTS idx_pending_operations_due
+    ON pending_operations (next_attempt_at);
+
+CREATE INDEX IF NOT EXISTS idx_pending_archives_due
+    ON pending_archives (archive_at);
+
+-- Only issues whose thread name is behind the Linear title, usually none.
+CREATE INDEX IF NOT EXISTS idx_issue_titles_pending_rename
+    ON issue_titles (linear_issue_id) WHERE title != applied_title;
@@ -0,0 +1,10 @@
+-- Forum channels managed with `/linear admin`. Rows supplement `CHANNELS` and take precedence over
+-- an entry for the same channel there; a NULL config removes that entry instead.
+CREATE TABLE IF NOT EXISTS channel_configs (
+    discord_channel_id TEXT PRIMARY KEY,
+    guild_id TEXT NOT NULL,
+    -- A `CHANNELS` entry as JSON.
+    config TEXT,
+    updated_by TEXT NOT NULL,
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
@@ -0,0 +1,14 @@
+-- Linear API keys added with `/linear admin add-credential`, for teams in other workspaces. Keys
+-- are encrypted with AES-256-GCM under `CREDENTIAL_KEY`; see `src/credentials.rs`.
+CREATE TABLE IF NOT EXISTS linear_credentials (
+    name TEXT PRIMARY KEY,
+    -- The guild whose admins manage the key; only its channels may use it.
+    guild_id TEXT NOT NULL,
+    -- First 8 hex characters of the SHA-256 of the encryption key that sealed the row.
+    key_id TEXT NOT NULL,
+    nonce BLOB NOT NULL,
+    -- The API key and its authentication tag.
+    ciphertext BLOB NOT NULL,
+    updated_by TEXT NOT NULL,
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
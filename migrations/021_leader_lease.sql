@@ -0,0 +1,8 @@
+-- The lease that decides which of several replicas sharing this database runs the sync; the
+-- others stand by until it expires. See `src/leader.rs`.
+CREATE TABLE IF NOT EXISTS leader_lease (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    holder TEXT NOT NULL,
+    -- Unix seconds, by the database's clock; another replica may take the lease after this.
+    expires_at INTEGER NOT NULL
+);
@@ -0,0 +1,11 @@
+-- Linear and Discord writes skipped in shadow mode (`SHADOW_MODE`), kept when
+-- `SHADOW_RECORD` is set so a staging run can be compared with what production did.
+CREATE TABLE IF NOT EXISTS shadow_actions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    -- "linear" or "discord".
+    service TEXT NOT NULL,
+    action TEXT NOT NULL,
+    -- JSON: the call's arguments, as the API would have received them.
+    detail TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
@@ -0,0 +1,16 @@
+-- Significant bot actions on synced threads (issues filed or linked, comments posted or
+-- relayed, status announcements, archiving, unlinking), listed by `/linear audit`.
+CREATE TABLE IF NOT EXISTS audit_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    discord_thread_id TEXT NOT NULL,
+    linear_identifier TEXT NOT NULL,
+    -- "issue_created", "comment_relayed", "thread_archived", ...
+    action TEXT NOT NULL,
+    -- Discord user who triggered the action; NULL for the bot's own syncs from Linear.
+    actor_discord_user_id TEXT,
+    -- Action-specific summary: the issue title, new status, Linear comment author, ...
+    detail TEXT NOT NULL DEFAULT '',
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_thread ON audit_log (discord_thread_id, id);
@@ -0,0 +1,11 @@
+-- New threads in channels with `suggest_duplicates`, checked against existing Linear issues
+-- before one is created, and how the author answered the prompt.
+CREATE TABLE IF NOT EXISTS duplicate_checks (
+    discord_thread_id TEXT PRIMARY KEY,
+    author_discord_user_id TEXT NOT NULL,
+    -- NULL when no similar issues were found, so no prompt was posted.
+    prompt_message_id TEXT,
+    -- NULL while the prompt waits; then "create", "link", "timeout", or "none".
+    outcome TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
@@ -0,0 +1,19 @@
+-- Hash of the title and body of each post an issue was created for, so a post that is deleted
+-- and posted again goes back to its issue instead of getting a second one.
+CREATE TABLE IF NOT EXISTS content_hashes (
+    discord_channel_id TEXT NOT NULL,
+    content_hash TEXT NOT NULL,
+    linear_issue_id TEXT NOT NULL,
+    linear_identifier TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (discord_channel_id, content_hash)
+);
+
+-- Reposts of a post whose thread still exists. The author is pointed to the original once and
+-- no issue is created.
+CREATE TABLE IF NOT EXISTS reposts (
+    discord_thread_id TEXT PRIMARY KEY,
+    original_discord_thread_id TEXT NOT NULL,
+    linear_identifier TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
@@ -0,0 +1,10 @@
+-- Threads in channels with an `approval_role_id`, held until a member with the role approves
+-- them. Approved rows are kept so the issue is created once, when the thread is next synced.
+CREATE TABLE IF NOT EXISTS thread_approvals (
+    discord_thread_id TEXT PRIMARY KEY,
+    prompt_message_id TEXT NOT NULL,
+    -- NULL until the thread is approved.
+    approved_by_discord_user_id TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    approved_at TEXT
+);
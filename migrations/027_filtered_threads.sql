@@ -0,0 +1,12 @@
+-- Posts the spam filter kept from being filed (too short, over the author's rate limit, or
+-- blocklisted). The author is told once and the thread is skipped from then on.
+CREATE TABLE IF NOT EXISTS filtered_threads (
+    discord_thread_id TEXT PRIMARY KEY,
+    -- "too_short", "rate_limited", "blocked_user", or "blocked_keyword".
+    reason TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+-- For counting the issues recently created from each author's posts.
+CREATE INDEX IF NOT EXISTS idx_audit_log_actor
+    ON audit_log (actor_discord_user_id, action, created_at);
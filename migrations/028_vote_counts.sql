@@ -0,0 +1,8 @@
+-- Vote reactions last written to each issue's description, so unchanged counts aren't
+-- rewritten, and whether the vote label has been added.
+CREATE TABLE IF NOT EXISTS vote_counts (
+    linear_issue_id TEXT PRIMARY KEY,
+    vote_count INTEGER NOT NULL,
+    label_added INTEGER NOT NULL DEFAULT 0,
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
@@ -0,0 +1,8 @@
+-- Discord users who get a DM when a synced thread's issue changes status or gets a comment in
+-- Linear. Added with `/linear subscribe` or a 🔔 reaction on the post.
+CREATE TABLE IF NOT EXISTS subscriptions (
+    discord_thread_id TEXT NOT NULL,
+    discord_user_id TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (discord_thread_id, discord_user_id)
+);
@@ -0,0 +1,12 @@
+-- Per-user notification settings, changed with `/linear prefs`. Users without a row get the
+-- defaults: no assignment DMs, subscription DMs for status changes and comments, not muted.
+CREATE TABLE IF NOT EXISTS notification_prefs (
+    discord_user_id TEXT PRIMARY KEY,
+    -- DM the user when a Linear issue is assigned to them (needs `/linear connect`).
+    dm_on_assignment INTEGER NOT NULL DEFAULT 0,
+    -- Only DM subscribed users about status changes, not comments.
+    status_changes_only INTEGER NOT NULL DEFAULT 0,
+    -- No DMs at all, and assignment announcements name the user instead of mentioning them.
+    muted INTEGER NOT NULL DEFAULT 0,
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
@@ -0,0 +1,12 @@
+-- Every Linear status change announced for a tracked issue, oldest first, for the time-in-status
+-- report in `/linear cycle-time`. `linear_status_cache` only keeps the latest status.
+CREATE TABLE IF NOT EXISTS status_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    linear_issue_id TEXT NOT NULL,
+    status_name TEXT NOT NULL,
+    -- Linear's workflow state type: triage, backlog, unstarted, started, completed or canceled.
+    status_type TEXT NOT NULL,
+    changed_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_status_history_issue ON status_history (linear_issue_id, id);
@@ -0,0 +1,7 @@
+-- Escalation pings posted for issues that have had no Linear activity within their channel's
+-- `sla_hours`. `pings` drives the backoff before the next one.
+CREATE TABLE IF NOT EXISTS sla_escalations (
+    linear_issue_id TEXT PRIMARY KEY,
+    pings INTEGER NOT NULL,
+    last_pinged_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
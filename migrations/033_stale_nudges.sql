@@ -0,0 +1,6 @@
+-- Last "still tracked" nudge posted in an issue's thread, so a stale issue is nudged at most once
+-- per `stale_nudge_days`.
+CREATE TABLE IF NOT EXISTS stale_nudges (
+    linear_issue_id TEXT PRIMARY KEY,
+    nudged_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
@@ -0,0 +1,3 @@
+-- Set while a form submit, skip, or timeout is filing the intake's issue. The row itself is only
+-- deleted once the issue exists, so a failed attempt can be retried.
+ALTER TABLE pending_intakes ADD COLUMN claimed_at TEXT;
@@ -0,0 +1,8 @@
+-- One-time codes for `/linear connect`. A link is only made once the code shows up in a comment
+-- written by the Linear user, which proves the Discord user controls that account.
+CREATE TABLE user_link_codes (
+    discord_user_id TEXT PRIMARY KEY,
+    linear_user_id TEXT NOT NULL,
+    code TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
@@ -0,0 +1,6 @@
+-- Held while a new post is being filed: across the wait for the author's follow-up messages
+-- and the issue creation, so the thread reconcile doesn't file the same post meanwhile.
+CREATE TABLE thread_claims (
+    discord_thread_id TEXT PRIMARY KEY,
+    claimed_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
@@ -0,0 +1,3 @@
+-- Guild of each mapped thread's forum, so per-server commands only see that server's mappings.
+-- Mappings made before this are left NULL.
+ALTER TABLE sync_mappings ADD COLUMN guild_id TEXT;
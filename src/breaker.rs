@@ -0,0 +1,99 @@
+//! Circuit breaker for calls to Linear and Discord.
+//!
+//! After `threshold` consecutive transient failures (timeouts, 5xx, rate limiting) the breaker
+//! opens: one error is logged and callers are expected to skip outbound calls until the cool-down
+//! passes. The next call after that is a trial. If it succeeds the breaker closes; if it fails
+//! the breaker opens for another cool-down. Clones share state.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tracing::{error, info};
+
+/// Consecutive failures that open a breaker unless configured otherwise.
+pub const DEFAULT_THRESHOLD: u32 = 5;
+
+/// How long an open breaker pauses calls unless configured otherwise.
+pub const DEFAULT_COOLDOWN_SECS: u64 = 60;
+
+#[derive(Debug, Default)]
+struct State {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+    /// Calls turned away since the breaker last opened.
+    rejected: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    service: &'static str,
+    threshold: u32,
+    cooldown: Duration,
+    state: Arc<Mutex<State>>,
+}
+
+impl CircuitBreaker {
+    pub fn new(service: &'static str, threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            service,
+            threshold: threshold.max(1),
+            cooldown,
+            state: Arc::default(),
+        }
+    }
+
+    /// A closed breaker with the same settings that doesn't share this one's state.
+    pub fn reset(&self) -> Self {
+        Self::new(self.service, self.threshold, self.cooldown)
+    }
+
+    /// Whether calls may go out: the breaker is closed, or its cool-down has passed.
+    pub fn is_available(&self) -> bool {
+        self.state
+            .lock()
+            .unwrap()
+            .open_until
+            .is_none_or(|until| Instant::now() >= until)
+    }
+
+    /// Like [`Self::is_available`], but counts a refusal towards the total reported on recovery.
+    pub fn allow(&self) -> bool {
+        let available = self.is_available();
+        if !available {
+            self.state.lock().unwrap().rejected += 1;
+        }
+        available
+    }
+
+    pub fn record_success(&self) {
+        let mut state = self.state.lock().unwrap();
+        if state.consecutive_failures >= self.threshold {
+            info!(
+                service = self.service,
+                skipped_calls = state.rejected,
+                "Service recovered, resuming calls"
+            );
+        }
+        *state = State::default();
+    }
+
+    pub fn record_failure(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.consecutive_failures += 1;
+        if state.consecutive_failures < self.threshold {
+            return;
+        }
+        // Past the threshold, any failure is either the one that trips the breaker or a failed
+        // trial after a cool-down; both (re)open it.
+        let already_open = state.open_until.is_some_and(|until| Instant::now() < until);
+        if !already_open {
+            state.open_until = Some(Instant::now() + self.cooldown);
+            error!(
+                service = self.service,
+                failures = state.consecutive_failures,
+                cooldown_secs = self.cooldown.as_secs(),
+                "Service failing repeatedly, pausing calls"
+            );
+        }
+    }
+}
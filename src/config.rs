@@ -1,7 +1,18 @@
 use std::collections::HashMap;
 use std::env;
+use std::sync::{Arc, PoisonError, RwLock};
 
 use serde::Deserialize;
+use sqlx::sqlite::{SqliteJournalMode, SqliteSynchronous};
+use sqlx::SqlitePool;
+use tracing::warn;
+
+use crate::breaker;
+use crate::credentials::CredentialKeys;
+use crate::db;
+use crate::discord::commands;
+use crate::linear::client::DEFAULT_MAX_ATTEMPTS;
+use crate::templates::{self, Notification};
 
 #[derive(Debug, thiserror::Error)]
 pub enum ConfigError {
@@ -30,41 +41,457 @@ pub struct ChannelConfig {
     pub linear_label_id: String,
     /// Linear project ID to assign issues to
     pub linear_project_id: String,
-    /// Optional: map Discord forum tag IDs to additional Linear label IDs
+    /// Optional: map Discord forum tag IDs to additional Linear label IDs. Label changes in
+    /// Linear are mirrored back onto the mapped tags.
     #[serde(default)]
     pub tag_label_map: HashMap<String, String>,
+    /// Optional: map keywords to Linear label IDs added to new issues whose title or body
+    /// contains them (ignoring case). Alternatives are separated by `|`, e.g. `"android|apk"`.
+    #[serde(default)]
+    pub keyword_label_map: HashMap<String, String>,
+    /// Optional: map Discord forum tag IDs to Linear project IDs that override
+    /// `linear_project_id`. The first applied tag with a mapping wins.
+    #[serde(default)]
+    pub tag_project_map: HashMap<String, String>,
+    /// Optional: map Discord forum tag IDs to the Linear priority (1 = urgent to 4 = low) of new
+    /// issues. The most urgent of the applied tags' priorities wins.
+    #[serde(default)]
+    pub tag_priority_map: HashMap<String, i64>,
+    /// Optional: map Discord forum tag IDs to the estimate of new issues (must match the team's
+    /// estimation scale). The largest of the applied tags' estimates wins.
+    #[serde(default)]
+    pub tag_estimate_map: HashMap<String, i64>,
+    /// Rules checked in order against each new post; the first that matches picks the issue's
+    /// team, project, labels, and priority in place of the channel's. Posts no rule matches use
+    /// the channel's settings.
+    #[serde(default)]
+    pub routing_rules: Vec<RoutingRule>,
+    /// Most of the author's opening messages in the post that make up the issue description,
+    /// for reporters who split their report across several messages. Only edits to the first
+    /// message are synced, and they replace the whole body.
+    #[serde(default = "default_description_message_limit")]
+    pub description_message_limit: usize,
+    /// Wait up to this long after the post for the author's follow-up messages (unless
+    /// `description_message_limit` is reached first), and only include those sent within it
+    #[serde(default)]
+    pub description_window_secs: i64,
+    /// Linear label that opens a post in this channel for an issue in one of its teams that
+    /// isn't tracked yet, so a discussion can be started from Linear
+    #[serde(default)]
+    pub discuss_label_id: Option<String>,
+    /// Support-style channels: also file each new post as a Linear customer need on its issue,
+    /// with the post's author as the customer (found by their Discord user ID)
+    #[serde(default)]
+    pub customer_needs: bool,
+    /// Handlebars template for new issue descriptions, replacing the built-in layout. Variables:
+    /// `title`, `message_body`, `author`, `thread_url`, `tags`, `intake`, `attachments`.
+    /// Starter message edits are only synced if the template keeps the Discord thread footer.
+    #[serde(default)]
+    pub description_template: Option<String>,
+    /// Overrides for the messages written for this channel's threads, keyed by message name
+    /// (see `templates::Notification`). Unset messages use `MESSAGE_TEMPLATES`, then the defaults.
+    #[serde(default)]
+    pub message_templates: HashMap<String, String>,
+    /// Map Linear workflow state names (or categories, e.g. "started") to forum tag IDs that
+    /// are swapped onto the thread as the issue moves through the workflow
+    #[serde(default)]
+    pub state_tag_map: HashMap<String, String>,
+    /// What to do in the thread when its issue moves to a workflow state, keyed by state name or
+    /// category (a name takes precedence). Unset actions keep the channel's usual behavior.
+    #[serde(default)]
+    pub status_actions: HashMap<String, StatusAction>,
+    /// Archive the thread when its issue is completed or canceled
+    #[serde(default = "default_true")]
+    pub archive_on_close: bool,
+    /// Also lock the thread when archiving it, so only moderators can reopen it
+    #[serde(default)]
+    pub lock_on_close: bool,
+    /// Delay between the issue closing and the thread being archived
+    #[serde(default)]
+    pub archive_grace_secs: u64,
+    /// Mirror replies in tracked threads (and their attachments) as Linear comments
+    #[serde(default)]
+    pub sync_replies: bool,
+    /// Relay Linear comments through a channel webhook under the author's name and avatar
+    /// (requires the Manage Webhooks permission)
+    #[serde(default)]
+    pub comment_webhooks: bool,
+    /// Keep one pinned embed per thread (status, assignee, priority, last update) up to date
+    /// instead of posting a message for every status change
+    #[serde(default)]
+    pub status_embed: bool,
+    /// Post reminders in the thread as the issue's due date approaches and once it's missed
+    #[serde(default)]
+    pub due_date_reminders: bool,
+    /// How many days before the due date the "due soon" reminder is posted
+    #[serde(default = "default_due_reminder_lead_days")]
+    pub due_reminder_lead_days: i64,
+    /// Let the thread know its issue is still tracked once the open issue has gone this many
+    /// days without an update in Linear, and again every as many days while it stays quiet
+    #[serde(default)]
+    pub stale_nudge_days: Option<i64>,
+    /// Count reactions on the post and write the vote count into the issue description
+    #[serde(default)]
+    pub vote_sync: bool,
+    /// Reaction counted as a vote: a Unicode emoji or a custom emoji's name
+    #[serde(default = "default_vote_emoji")]
+    pub vote_emoji: String,
+    /// Linear label added to the issue once it has `vote_label_threshold` votes
+    #[serde(default)]
+    pub vote_label_id: Option<String>,
+    #[serde(default = "default_vote_label_threshold")]
+    pub vote_label_threshold: u64,
+    /// Escalate issues filed from this channel that have had no status change or comment in
+    /// Linear this many hours after they were filed
+    #[serde(default)]
+    pub sla_hours: Option<u64>,
+    /// Discord role mentioned in the escalation
+    #[serde(default)]
+    pub sla_role_id: Option<u64>,
+    /// Most escalations per issue; each repeat waits twice as long as the previous gap
+    #[serde(default = "default_sla_max_pings")]
+    pub sla_max_pings: u32,
+    /// What to do with the Linear issue when its thread is deleted in Discord
+    #[serde(default)]
+    pub on_thread_delete: ThreadDeleteAction,
+    /// Ask the thread author for repro steps, severity, and version before creating the issue
+    #[serde(default)]
+    pub intake_form: bool,
+    /// How long to wait for the intake form before filing the issue without it
+    #[serde(default = "default_intake_timeout_secs")]
+    pub intake_timeout_secs: i64,
+    /// Discord role whose members must approve new threads (with a button or a ✅ reaction)
+    /// before their issue is created
+    #[serde(default)]
+    pub approval_role_id: Option<u64>,
+    /// Search Linear for the new thread's title before creating an issue, and let the author
+    /// link one of the matches instead
+    #[serde(default)]
+    pub suggest_duplicates: bool,
+    /// How long to wait for an answer to the duplicate prompt before creating the issue anyway
+    #[serde(default = "default_intake_timeout_secs")]
+    pub duplicate_timeout_secs: i64,
+    /// If non-empty, only forum posts by members with one of these Discord roles get an issue
+    /// automatically; other posts can still be filed or linked with `/linear` commands
+    #[serde(default)]
+    pub poster_role_ids: Vec<u64>,
+    /// Posts whose body is shorter than this many characters aren't filed
+    #[serde(default)]
+    pub min_body_length: usize,
+    /// Most issues created from one author's posts within `user_issue_window_secs`; later posts
+    /// aren't filed. Counts issues from every channel.
+    #[serde(default)]
+    pub user_issue_limit: Option<i64>,
+    #[serde(default = "default_user_issue_window_secs")]
+    pub user_issue_window_secs: i64,
+    /// Discord users whose posts are never filed
+    #[serde(default)]
+    pub blocked_user_ids: Vec<u64>,
+    /// Posts whose title or body contains one of these (ignoring case) aren't filed
+    #[serde(default)]
+    pub blocked_keywords: Vec<String>,
+    /// Discord role allowed to set priority/estimate from the triage menu posted on new issues
+    #[serde(default)]
+    pub triage_role_id: Option<u64>,
+    /// Estimate values offered in the triage menu (must match the team's estimation scale)
+    #[serde(default = "default_triage_estimates")]
+    pub triage_estimates: Vec<i64>,
+    /// Overrides `POLL_INTERVAL_SECS` for this channel's Linear team; if several channels share a
+    /// team, the shortest interval wins
+    #[serde(default)]
+    pub poll_interval_secs: Option<u64>,
+    /// Name of a key in `LINEAR_API_KEYS` to use instead of `LINEAR_API_KEY`, for a team in
+    /// another Linear workspace. Channels sharing a team must use the same key.
+    #[serde(default)]
+    pub linear_credential: Option<String>,
+}
+
+impl ChannelConfig {
+    /// The Linear teams this channel files issues in: its own and any its routing rules pick.
+    pub fn team_ids(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.linear_team_id.as_str()).chain(
+            self.routing_rules
+                .iter()
+                .filter_map(|r| r.linear_team_id.as_deref()),
+        )
+    }
+}
+
+/// A channel routing rule. A rule matches a post when every condition it sets holds (a rule
+/// without conditions matches every post); the rule's settings then replace the channel's.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RoutingRule {
+    /// Matches posts with one of these forum tags applied
+    #[serde(default)]
+    pub tag_ids: Vec<u64>,
+    /// Matches posts whose title or body contains one of these (ignoring case)
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    /// Matches posts whose author has one of these Discord roles
+    #[serde(default)]
+    pub author_role_ids: Vec<u64>,
+    /// Team to create the issue in. Labels and projects belong to a team, so a rule that sets
+    /// one must also set `linear_project_id`, and tag-mapped labels and projects are dropped.
+    #[serde(default)]
+    pub linear_team_id: Option<String>,
+    /// Project to assign the issue to, instead of the channel's or the tag-mapped one
+    #[serde(default)]
+    pub linear_project_id: Option<String>,
+    /// Labels to apply instead of the channel's `linear_label_id`
+    #[serde(default)]
+    pub linear_label_ids: Vec<String>,
+    /// Priority (1 = urgent to 4 = low), instead of the tag-mapped one
+    #[serde(default)]
+    pub priority: Option<i64>,
+}
+
+/// Actions for one entry of a channel's `status_actions`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StatusAction {
+    /// Handlebars template for the status notice, with the `status_changed` variables. Also
+    /// posted in channels with `status_embed`.
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Forum tag swapped onto the thread, in place of the one from `state_tag_map`
+    #[serde(default)]
+    pub tag_id: Option<u64>,
+    /// Archive (or, if false, reopen) the thread, overriding `archive_on_close`. Archiving waits
+    /// for `archive_grace_secs`.
+    #[serde(default)]
+    pub archive: Option<bool>,
+    /// Lock (or, if false, unlock) the thread, overriding `lock_on_close`
+    #[serde(default)]
+    pub lock: Option<bool>,
+    /// Discord role mentioned in the status notice (also posted in channels with `status_embed`)
+    #[serde(default)]
+    pub ping_role_id: Option<u64>,
+}
+
+/// Action taken on a mapped issue when its Discord thread is deleted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThreadDeleteAction {
+    /// Leave the issue open and note the deletion in a comment
+    #[default]
+    Comment,
+    /// Move the issue to the team's canceled state (and comment)
+    Cancel,
+}
+
+fn default_description_message_limit() -> usize {
+    1
+}
+
+fn default_triage_estimates() -> Vec<i64> {
+    vec![1, 2, 3, 5, 8]
+}
+
+fn default_due_reminder_lead_days() -> i64 {
+    1
+}
+
+fn default_vote_emoji() -> String {
+    "👍".to_string()
+}
+
+fn default_vote_label_threshold() -> u64 {
+    20
+}
+
+fn default_sla_max_pings() -> u32 {
+    3
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_intake_timeout_secs() -> i64 {
+    86400
+}
+
+fn default_user_issue_window_secs() -> i64 {
+    3600
 }
 
 #[derive(Debug, Clone)]
 pub struct Config {
     pub discord_token: String,
     pub linear_api_key: String,
+    /// API keys for further Linear workspaces by name, referenced by `linear_credential`.
+    pub linear_api_keys: HashMap<String, String>,
+    /// Hex AES-256 key that API keys stored in the database are encrypted with.
+    pub credential_key: Option<String>,
+    /// Retired `credential_key`s, still accepted for decryption until `rotate-credentials` runs.
+    pub credential_previous_keys: Vec<String>,
     pub channels: Vec<ChannelConfig>,
+    /// Let any server that adds the bot connect its own Linear workspace with `/linear setup`.
+    /// `CHANNELS` may then be empty.
+    pub multi_tenant: bool,
     pub database_url: String,
+    /// Connections in the SQLite pool.
+    pub database_max_connections: u32,
+    /// WAL by default, so the poller and the event handler can read while the other writes.
+    pub sqlite_journal_mode: SqliteJournalMode,
+    /// NORMAL by default: in WAL mode the database can't be corrupted by a crash, though a power
+    /// loss may drop the last few commits. FULL trades write speed for those too.
+    pub sqlite_synchronous: SqliteSynchronous,
+    /// How long a write waits for another connection's lock before failing with
+    /// "database is locked".
+    pub sqlite_busy_timeout_ms: u64,
     pub poll_interval_secs: u64,
+    /// Longest a quiet team's poll interval grows to; see `linear::poll_interval`.
+    pub poll_interval_max_secs: u64,
+    /// How far each status poll reaches back before its cursor, to catch updates that landed
+    /// while the previous poll was running. Issues seen twice are deduplicated by the sync caches.
+    pub poll_cursor_overlap_secs: u64,
     pub comment_poll_interval_secs: u64,
     pub thread_reconcile_interval_secs: u64,
+    /// How often tracked issues are checked for approaching or missed due dates.
+    pub due_reminder_interval_secs: u64,
+    /// How often open issues in channels with `stale_nudge_days` are checked for staleness.
+    pub stale_nudge_interval_secs: u64,
+    /// How often vote counts on posts in channels with `vote_sync` are written to Linear.
+    pub vote_sync_interval_secs: u64,
+    /// How often issues in channels with `sla_hours` are checked for missed SLAs.
+    pub sla_check_interval_secs: u64,
+    /// How often the teams, labels, projects, and members offered by autocomplete are refetched.
+    pub metadata_refresh_secs: u64,
+    /// How often mappings are cross-checked against Linear and Discord for drift; 0 disables the
+    /// check. See `sync::drift`.
+    pub drift_check_interval_secs: u64,
+    /// Repair drift the check finds instead of only reporting it.
+    pub drift_auto_repair: bool,
+    /// Quiet period before queued thread notifications are posted as one digest message;
+    /// 0 posts each notification immediately.
+    pub digest_window_secs: u64,
+    /// Channel whose team/label/project config is used for issues created from messages
+    /// outside monitored forums (the "Create Linear issue from message" command).
+    pub context_menu_channel_id: Option<u64>,
+    /// Address for the Linear webhook receiver; the receiver is disabled when unset.
+    pub webhook_listen_addr: Option<String>,
+    /// Signing secret shown on the Linear webhook settings page.
+    pub linear_webhook_secret: Option<String>,
+    /// Maximum age of a webhook delivery before it is rejected as a replay.
+    pub webhook_max_age_secs: u64,
+    /// Linear user IDs or emails whose comments are not relayed to Discord.
+    pub comment_author_denylist: Vec<String>,
+    /// If non-empty, only comments from these Linear user IDs or emails are relayed.
+    pub comment_author_allowlist: Vec<String>,
+    /// Skip comments posted by integrations and bot accounts rather than a Linear user.
+    pub skip_bot_comments: bool,
+    /// The Linear keys are OAuth app tokens with `actor=app`, so comments mirrored from Discord
+    /// can be posted under their author's name and avatar. Linear rejects that for API keys.
+    pub linear_comment_as_author: bool,
+    /// Discord roles allowed to run each `/linear` subcommand, keyed by its name ("unlink",
+    /// "admin prune"). A listed subcommand needs one of the roles instead of its usual Discord
+    /// permission; administrators are always allowed.
+    pub command_roles: HashMap<String, Vec<u64>>,
+    /// Bot-wide overrides for thread notifications, keyed by message name. Channel overrides
+    /// are merged over these at load time.
+    pub message_templates: HashMap<String, String>,
+    /// Attempts per Linear API request before a transient failure (timeout, 5xx, 429) is
+    /// returned as an error.
+    pub linear_max_attempts: u32,
+    /// Consecutive transient failures of Linear or Discord before calls to it are paused.
+    pub breaker_threshold: u32,
+    /// How long calls stay paused once a breaker opens.
+    pub breaker_cooldown_secs: u64,
+    /// Retries of a queued sync operation before it is moved to the dead-letter table.
+    pub outbox_max_attempts: i64,
+    /// Channel alerted when a sync operation is dead-lettered or the poller is restarted; alerts
+    /// are only logged when unset.
+    pub ops_channel_id: Option<u64>,
+    /// How long a shutdown waits for in-flight syncs before exiting anyway.
+    pub shutdown_timeout_secs: u64,
+    /// Run as one of several replicas sharing the database; only the one holding the leader
+    /// lease syncs. See `leader`.
+    pub leader_election: bool,
+    /// How long the leader lease lasts without renewal, and so how long a standby waits to take
+    /// over from a leader that died.
+    pub leader_lease_secs: u64,
+    /// Log Linear and Discord writes instead of making them. See `shadow`.
+    pub shadow_mode: bool,
+    /// In shadow mode, also store the skipped writes in the `shadow_actions` table.
+    pub shadow_record: bool,
 }
 
 impl Config {
     pub fn from_env() -> Result<Self, ConfigError> {
-        let channels_json = required("CHANNELS")?;
-        let channels: Vec<ChannelConfig> = serde_json::from_str(&channels_json)
-            .map_err(|e| ConfigError::Invalid("CHANNELS".into(), e.to_string()))?;
+        let linear_api_keys: HashMap<String, String> = match env::var("LINEAR_API_KEYS") {
+            Ok(json) => serde_json::from_str(&json)
+                .map_err(|e| ConfigError::Invalid("LINEAR_API_KEYS".into(), e.to_string()))?,
+            Err(_) => HashMap::new(),
+        };
+        let command_roles: HashMap<String, Vec<u64>> = match env::var("COMMAND_ROLES") {
+            Ok(json) => serde_json::from_str(&json)
+                .map_err(|e| ConfigError::Invalid("COMMAND_ROLES".into(), e.to_string()))?,
+            Err(_) => HashMap::new(),
+        };
+        if let Some(name) = command_roles.keys().find(|n| !commands::is_subcommand(n)) {
+            return Err(ConfigError::Invalid(
+                "COMMAND_ROLES".into(),
+                format!("unknown subcommand `{name}`"),
+            ));
+        }
+        let multi_tenant =
+            env::var("MULTI_TENANT").is_ok_and(|v| matches!(v.as_str(), "1" | "true"));
+        let (channels, message_templates) =
+            load_channels(|name| env::var(name).ok(), &linear_api_keys, multi_tenant)?;
 
-        if channels.is_empty() {
-            return Err(ConfigError::NoChannels);
+        let credential_key = env::var("CREDENTIAL_KEY").ok();
+        let credential_previous_keys = list("CREDENTIAL_PREVIOUS_KEYS");
+        if let Some(key) = &credential_key {
+            CredentialKeys::new(key, &credential_previous_keys)
+                .map_err(|e| ConfigError::Invalid("CREDENTIAL_KEY".into(), e.to_string()))?;
+        }
+
+        // An unauthenticated webhook endpoint would let anyone inject fake status changes.
+        if env::var("WEBHOOK_LISTEN_ADDR").is_ok() && env::var("LINEAR_WEBHOOK_SECRET").is_err() {
+            return Err(ConfigError::Missing("LINEAR_WEBHOOK_SECRET".into()));
         }
 
         Ok(Config {
             discord_token: required("DISCORD_TOKEN")?,
             linear_api_key: required("LINEAR_API_KEY")?,
+            linear_api_keys,
+            credential_key,
+            credential_previous_keys,
             channels,
+            multi_tenant,
             database_url: env::var("DATABASE_URL").unwrap_or_else(|_| "sqlite:bot.db".into()),
+            database_max_connections: env::var("DATABASE_MAX_CONNECTIONS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(5),
+            sqlite_journal_mode: match env::var("SQLITE_JOURNAL_MODE") {
+                Ok(v) => v
+                    .parse()
+                    .map_err(|_| ConfigError::Invalid("SQLITE_JOURNAL_MODE".into(), v.clone()))?,
+                Err(_) => SqliteJournalMode::Wal,
+            },
+            sqlite_synchronous: match env::var("SQLITE_SYNCHRONOUS") {
+                Ok(v) => v
+                    .parse()
+                    .map_err(|_| ConfigError::Invalid("SQLITE_SYNCHRONOUS".into(), v.clone()))?,
+                Err(_) => SqliteSynchronous::Normal,
+            },
+            sqlite_busy_timeout_ms: env::var("SQLITE_BUSY_TIMEOUT_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(5000),
             poll_interval_secs: env::var("POLL_INTERVAL_SECS")
                 .ok()
                 .and_then(|v| v.parse().ok())
                 .unwrap_or(30),
+            poll_interval_max_secs: env::var("POLL_INTERVAL_MAX_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(300),
+            poll_cursor_overlap_secs: env::var("POLL_CURSOR_OVERLAP_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(10),
             comment_poll_interval_secs: env::var("COMMENT_POLL_INTERVAL_SECS")
                 .ok()
                 .and_then(|v| v.parse().ok())
@@ -73,6 +500,92 @@ impl Config {
                 .ok()
                 .and_then(|v| v.parse().ok())
                 .unwrap_or(600),
+            due_reminder_interval_secs: env::var("DUE_REMINDER_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(3600),
+            stale_nudge_interval_secs: env::var("STALE_NUDGE_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(3600),
+            vote_sync_interval_secs: env::var("VOTE_SYNC_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(900),
+            sla_check_interval_secs: env::var("SLA_CHECK_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(900),
+            metadata_refresh_secs: env::var("METADATA_REFRESH_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(300),
+            drift_check_interval_secs: env::var("DRIFT_CHECK_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(86400),
+            drift_auto_repair: env::var("DRIFT_AUTO_REPAIR")
+                .is_ok_and(|v| matches!(v.as_str(), "1" | "true")),
+            digest_window_secs: env::var("DIGEST_WINDOW_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0),
+            context_menu_channel_id: match env::var("CONTEXT_MENU_CHANNEL_ID") {
+                Ok(v) => Some(v.parse().map_err(|_| {
+                    ConfigError::Invalid("CONTEXT_MENU_CHANNEL_ID".into(), v.clone())
+                })?),
+                Err(_) => None,
+            },
+            webhook_listen_addr: env::var("WEBHOOK_LISTEN_ADDR").ok(),
+            linear_webhook_secret: env::var("LINEAR_WEBHOOK_SECRET").ok(),
+            webhook_max_age_secs: env::var("WEBHOOK_MAX_AGE_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(60),
+            comment_author_denylist: list("LINEAR_COMMENT_AUTHOR_DENYLIST"),
+            comment_author_allowlist: list("LINEAR_COMMENT_AUTHOR_ALLOWLIST"),
+            skip_bot_comments: env::var("SKIP_LINEAR_BOT_COMMENTS")
+                .is_ok_and(|v| matches!(v.as_str(), "1" | "true")),
+            linear_comment_as_author: env::var("LINEAR_COMMENT_AS_AUTHOR")
+                .is_ok_and(|v| matches!(v.as_str(), "1" | "true")),
+            command_roles,
+            message_templates,
+            linear_max_attempts: env::var("LINEAR_MAX_ATTEMPTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_MAX_ATTEMPTS),
+            breaker_threshold: env::var("CIRCUIT_BREAKER_THRESHOLD")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(breaker::DEFAULT_THRESHOLD),
+            breaker_cooldown_secs: env::var("CIRCUIT_BREAKER_COOLDOWN_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(breaker::DEFAULT_COOLDOWN_SECS),
+            outbox_max_attempts: env::var("OUTBOX_MAX_ATTEMPTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(10),
+            ops_channel_id: match env::var("OPS_CHANNEL_ID") {
+                Ok(v) => Some(
+                    v.parse()
+                        .map_err(|_| ConfigError::Invalid("OPS_CHANNEL_ID".into(), v.clone()))?,
+                ),
+                Err(_) => None,
+            },
+            shutdown_timeout_secs: env::var("SHUTDOWN_TIMEOUT_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(25),
+            leader_election: env::var("LEADER_ELECTION")
+                .is_ok_and(|v| matches!(v.as_str(), "1" | "true")),
+            leader_lease_secs: env::var("LEADER_LEASE_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(30),
+            shadow_mode: env::var("SHADOW_MODE").is_ok_and(|v| matches!(v.as_str(), "1" | "true")),
+            shadow_record: env::var("SHADOW_RECORD")
+                .is_ok_and(|v| matches!(v.as_str(), "1" | "true")),
         })
     }
 
@@ -93,13 +606,25 @@ impl Config {
         let mut ids: Vec<String> = self
             .channels
             .iter()
-            .map(|c| c.linear_team_id.clone())
+            .flat_map(ChannelConfig::team_ids)
+            .map(str::to_string)
             .collect();
         ids.sort();
         ids.dedup();
         ids
     }
 
+    /// How often a Linear team is polled for updated issues: the shortest `poll_interval_secs`
+    /// among its channels, or `POLL_INTERVAL_SECS`.
+    pub fn team_poll_interval_secs(&self, team_id: &str) -> u64 {
+        self.channels
+            .iter()
+            .filter(|c| c.team_ids().any(|t| t == team_id))
+            .filter_map(|c| c.poll_interval_secs)
+            .min()
+            .unwrap_or(self.poll_interval_secs)
+    }
+
     /// All unique guild IDs across all channels.
     pub fn unique_guild_ids(&self) -> Vec<u64> {
         let mut ids: Vec<u64> = self.channels.iter().map(|c| c.guild_id).collect();
@@ -109,6 +634,305 @@ impl Config {
     }
 }
 
+/// The running configuration, shared by the event handler and the background tasks. Channels are
+/// `CHANNELS` overlaid with the ones managed through `/linear admin` (stored in the database), and
+/// can change without a restart, as can `MESSAGE_TEMPLATES`; everything else is fixed at startup.
+/// Readers take a `current` snapshot per event or pass instead of holding on to one.
+#[derive(Debug, Clone)]
+pub struct SharedConfig(Arc<RwLock<ConfigState>>);
+
+#[derive(Debug)]
+struct ConfigState {
+    config: Arc<Config>,
+    /// `CHANNELS`, with `MESSAGE_TEMPLATES` merged in.
+    env_channels: Vec<ChannelConfig>,
+    /// Channels from the database by Discord channel ID, as stored; `None` removes the channel's
+    /// `CHANNELS` entry.
+    stored_channels: Vec<(u64, Option<ChannelConfig>)>,
+    /// Names of the Linear API keys stored in the database, with the guild that may use each.
+    stored_credentials: HashMap<String, u64>,
+}
+
+impl ConfigState {
+    /// Recompute the effective channel list. A stored channel that no longer validates (say, after
+    /// `MESSAGE_TEMPLATES` changed) is left out.
+    fn rebuild(&mut self) {
+        let stored_ids: Vec<u64> = self.stored_channels.iter().map(|(id, _)| *id).collect();
+        let mut channels: Vec<ChannelConfig> = self
+            .env_channels
+            .iter()
+            .filter(|c| !stored_ids.contains(&c.discord_channel_id))
+            .cloned()
+            .collect();
+        for channel in self.stored_channels.iter().filter_map(|(_, c)| c.as_ref()) {
+            let mut channel = channel.clone();
+            let config = &self.config;
+            let guild_id = channel.guild_id;
+            let known_credential = |name: &str| {
+                config.linear_api_keys.contains_key(name)
+                    || self.stored_credentials.get(name) == Some(&guild_id)
+            };
+            match prepare_channel(&mut channel, &config.message_templates, known_credential) {
+                Ok(()) => channels.push(channel),
+                Err(e) => warn!(
+                    channel_id = channel.discord_channel_id,
+                    error = %e,
+                    "Ignoring invalid stored channel config"
+                ),
+            }
+        }
+        self.config = Arc::new(Config {
+            channels,
+            ..Config::clone(&self.config)
+        });
+    }
+}
+
+impl SharedConfig {
+    /// Start from `config`, whose channels are taken to be `CHANNELS`.
+    pub fn new(config: Config) -> Self {
+        Self(Arc::new(RwLock::new(ConfigState {
+            env_channels: config.channels.clone(),
+            config: Arc::new(config),
+            stored_channels: Vec::new(),
+            stored_credentials: HashMap::new(),
+        })))
+    }
+
+    pub fn current(&self) -> Arc<Config> {
+        self.0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .config
+            .clone()
+    }
+
+    /// Whether `CHANNELS` has an entry for the channel, whether or not the database overrides it.
+    pub fn in_env(&self, discord_channel_id: u64) -> bool {
+        self.0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .env_channels
+            .iter()
+            .any(|c| c.discord_channel_id == discord_channel_id)
+    }
+
+    /// Whether any `CHANNELS` entry is in the guild.
+    pub fn in_env_guild(&self, guild_id: u64) -> bool {
+        self.0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .env_channels
+            .iter()
+            .any(|c| c.guild_id == guild_id)
+    }
+
+    /// Reload `CHANNELS` and `MESSAGE_TEMPLATES` from `.env`, falling back to the process
+    /// environment for variables the file doesn't set (or when there is no `.env`).
+    pub fn reload(&self) -> Result<Arc<Config>, ConfigError> {
+        let file: HashMap<String, String> = match dotenvy::dotenv_iter() {
+            Ok(vars) => vars
+                .collect::<Result<_, _>>()
+                .map_err(|e| ConfigError::Invalid(".env".into(), e.to_string()))?,
+            Err(_) => HashMap::new(),
+        };
+        self.reload_from(|name| file.get(name).cloned().or_else(|| env::var(name).ok()))
+    }
+
+    /// Reload `CHANNELS` and `MESSAGE_TEMPLATES` using `var` to look up variables. On error the
+    /// running configuration is left as it was.
+    pub fn reload_from(
+        &self,
+        var: impl Fn(&str) -> Option<String>,
+    ) -> Result<Arc<Config>, ConfigError> {
+        let mut state = self.0.write().unwrap_or_else(PoisonError::into_inner);
+        let config = &state.config;
+        let (channels, message_templates) =
+            load_channels(var, &config.linear_api_keys, config.multi_tenant)?;
+        state.env_channels = channels;
+        state.config = Arc::new(Config {
+            message_templates,
+            ..Config::clone(&state.config)
+        });
+        state.rebuild();
+        Ok(state.config.clone())
+    }
+
+    /// Re-read the channels and Linear API keys managed through `/linear admin` from the
+    /// database. Channel rows that can't be parsed are logged and skipped.
+    pub async fn load_stored_channels(
+        &self,
+        pool: &SqlitePool,
+    ) -> Result<Arc<Config>, sqlx::Error> {
+        let mut stored_channels = Vec::new();
+        for row in db::get_channel_configs(pool).await? {
+            let Ok(channel_id) = row.discord_channel_id.parse() else {
+                warn!(channel_id = %row.discord_channel_id, "Ignoring stored channel config");
+                continue;
+            };
+            let config = match row.config.as_deref().map(serde_json::from_str).transpose() {
+                Ok(config) => config,
+                Err(e) => {
+                    warn!(channel_id, error = %e, "Ignoring unreadable stored channel config");
+                    continue;
+                }
+            };
+            stored_channels.push((channel_id, config));
+        }
+        let stored_credentials = db::get_linear_credentials(pool)
+            .await?
+            .into_iter()
+            .filter_map(|row| Some((row.name, row.guild_id.parse().ok()?)))
+            .collect();
+
+        let mut state = self.0.write().unwrap_or_else(PoisonError::into_inner);
+        state.stored_channels = stored_channels;
+        state.stored_credentials = stored_credentials;
+        state.rebuild();
+        Ok(state.config.clone())
+    }
+}
+
+/// `CHANNELS`, validated, with the bot-wide `MESSAGE_TEMPLATES` merged into each channel's
+/// overrides. Also returns the bot-wide templates. `var` looks up a variable by name. In
+/// multi-tenant mode `CHANNELS` may be unset or empty.
+fn load_channels(
+    var: impl Fn(&str) -> Option<String>,
+    linear_api_keys: &HashMap<String, String>,
+    multi_tenant: bool,
+) -> Result<(Vec<ChannelConfig>, HashMap<String, String>), ConfigError> {
+    let channels_json = match var("CHANNELS") {
+        Some(json) => json,
+        None if multi_tenant => "[]".to_string(),
+        None => return Err(ConfigError::Missing("CHANNELS".into())),
+    };
+    let mut channels: Vec<ChannelConfig> = serde_json::from_str(&channels_json)
+        .map_err(|e| ConfigError::Invalid("CHANNELS".into(), e.to_string()))?;
+
+    if channels.is_empty() && !multi_tenant {
+        return Err(ConfigError::NoChannels);
+    }
+
+    let message_templates: HashMap<String, String> = match var("MESSAGE_TEMPLATES") {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| ConfigError::Invalid("MESSAGE_TEMPLATES".into(), e.to_string()))?,
+        None => HashMap::new(),
+    };
+    templates::validate_messages(&message_templates)
+        .map_err(|e| ConfigError::Invalid("MESSAGE_TEMPLATES".into(), e))?;
+
+    for channel in &mut channels {
+        prepare_channel(channel, &message_templates, |name| {
+            linear_api_keys.contains_key(name)
+        })?;
+    }
+
+    Ok((channels, message_templates))
+}
+
+/// Validate a channel's templates, tag priorities, routing rules, and credential, and merge the
+/// bot-wide `message_templates` under its own. `known_credential` says whether the channel may
+/// use a credential name.
+fn prepare_channel(
+    channel: &mut ChannelConfig,
+    message_templates: &HashMap<String, String>,
+    known_credential: impl Fn(&str) -> bool,
+) -> Result<(), ConfigError> {
+    if let Some(priority) = channel
+        .tag_priority_map
+        .values()
+        .find(|p| !(1..=4).contains(*p))
+    {
+        return Err(ConfigError::Invalid(
+            "CHANNELS".into(),
+            format!(
+                "channel {}: tag_priority_map priority {priority} is not between 1 and 4",
+                channel.discord_channel_id
+            ),
+        ));
+    }
+    for (i, rule) in channel.routing_rules.iter().enumerate() {
+        let problem = if rule.priority.is_some_and(|p| !(1..=4).contains(&p)) {
+            Some("priority is not between 1 and 4")
+        } else if rule.linear_team_id.is_some() && rule.linear_project_id.is_none() {
+            Some("sets linear_team_id without linear_project_id")
+        } else {
+            None
+        };
+        if let Some(problem) = problem {
+            return Err(ConfigError::Invalid(
+                "CHANNELS".into(),
+                format!(
+                    "channel {}: routing rule {} {problem}",
+                    channel.discord_channel_id,
+                    i + 1
+                ),
+            ));
+        }
+    }
+    if let Some(name) = &channel.linear_credential {
+        if !known_credential(name) {
+            return Err(ConfigError::Invalid(
+                "CHANNELS".into(),
+                format!(
+                    "channel {}: linear_credential {name:?} is not a configured Linear API key",
+                    channel.discord_channel_id
+                ),
+            ));
+        }
+    }
+    templates::validate_messages(&channel.message_templates).map_err(|e| {
+        ConfigError::Invalid(
+            "CHANNELS".into(),
+            format!("channel {}: {e}", channel.discord_channel_id),
+        )
+    })?;
+    for (key, template) in message_templates {
+        channel
+            .message_templates
+            .entry(key.clone())
+            .or_insert_with(|| template.clone());
+    }
+    for (state, action) in &channel.status_actions {
+        if let Some(message) = &action.message {
+            templates::validate_message(Notification::StatusChanged, message).map_err(|e| {
+                ConfigError::Invalid(
+                    "CHANNELS".into(),
+                    format!(
+                        "channel {}: status action message for \"{state}\": {e}",
+                        channel.discord_channel_id
+                    ),
+                )
+            })?;
+        }
+    }
+    if let Some(template) = &channel.description_template {
+        templates::validate(template).map_err(|e| {
+            ConfigError::Invalid(
+                "CHANNELS".into(),
+                format!(
+                    "description_template for channel {}: {e}",
+                    channel.discord_channel_id
+                ),
+            )
+        })?;
+    }
+    Ok(())
+}
+
 fn required(name: &str) -> Result<String, ConfigError> {
     env::var(name).map_err(|_| ConfigError::Missing(name.into()))
 }
+
+/// Comma-separated list from an optional environment variable; empty entries are dropped.
+fn list(name: &str) -> Vec<String> {
+    env::var(name)
+        .map(|v| {
+            v.split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
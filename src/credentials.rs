@@ -0,0 +1,198 @@
+//! Encryption of the Linear API keys stored in the database, so a copy of the SQLite file doesn't
+//! expose them.
+//!
+//! Keys are sealed with AES-256-GCM under `CREDENTIAL_KEY`, with the credential's name as
+//! associated data so a row's ciphertext can't be passed off as another's. Each row records which
+//! encryption key sealed it. To rotate, set a new `CREDENTIAL_KEY`, move the old one to
+//! `CREDENTIAL_PREVIOUS_KEYS` so existing rows still open, and run `rotate-credentials` to reseal
+//! them under the new key; the old key can then be dropped.
+
+use std::fmt;
+
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use tracing::info;
+
+use crate::config::Config;
+use crate::db::{self, StoredCredential};
+use crate::error::AppError;
+
+#[derive(Debug, thiserror::Error)]
+pub enum CredentialError {
+    #[error("{0} must be 64 hex characters (a 32-byte key)")]
+    InvalidKey(&'static str),
+
+    #[error("Linear API keys are stored in the database, but CREDENTIAL_KEY is not set")]
+    NoKey,
+
+    #[error("credential {0:?} was encrypted with key {1}, which is not configured")]
+    UnknownKey(String, String),
+
+    #[error("credential {0:?} can't be decrypted; the row or the key for it is corrupt")]
+    Corrupt(String),
+}
+
+/// An API key encrypted for storage.
+#[derive(Debug)]
+pub struct Sealed {
+    pub key_id: String,
+    pub nonce: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+}
+
+/// A stored Linear API key, decrypted.
+pub struct Credential {
+    pub name: String,
+    pub guild_id: String,
+    pub api_key: String,
+}
+
+impl fmt::Debug for Credential {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credential")
+            .field("name", &self.name)
+            .field("guild_id", &self.guild_id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The encryption key new credentials are sealed with, and the retired ones still accepted.
+pub struct CredentialKeys {
+    current: EncryptionKey,
+    previous: Vec<EncryptionKey>,
+}
+
+struct EncryptionKey {
+    /// First 8 hex characters of the key's SHA-256, stored with each row it seals.
+    id: String,
+    key: LessSafeKey,
+}
+
+impl EncryptionKey {
+    fn from_hex(hex_key: &str, var: &'static str) -> Result<Self, CredentialError> {
+        let bytes = hex::decode(hex_key.trim()).map_err(|_| CredentialError::InvalidKey(var))?;
+        let key =
+            UnboundKey::new(&AES_256_GCM, &bytes).map_err(|_| CredentialError::InvalidKey(var))?;
+        Ok(Self {
+            id: hex::encode(&Sha256::digest(&bytes)[..4]),
+            key: LessSafeKey::new(key),
+        })
+    }
+}
+
+impl fmt::Debug for CredentialKeys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let previous: Vec<&str> = self.previous.iter().map(|k| k.id.as_str()).collect();
+        f.debug_struct("CredentialKeys")
+            .field("current", &self.current.id)
+            .field("previous", &previous)
+            .finish()
+    }
+}
+
+impl CredentialKeys {
+    /// Keys from hex: `current` for `CREDENTIAL_KEY` and `previous` for
+    /// `CREDENTIAL_PREVIOUS_KEYS`.
+    pub fn new(current: &str, previous: &[String]) -> Result<Self, CredentialError> {
+        Ok(Self {
+            current: EncryptionKey::from_hex(current, "CREDENTIAL_KEY")?,
+            previous: previous
+                .iter()
+                .map(|key| EncryptionKey::from_hex(key, "CREDENTIAL_PREVIOUS_KEYS"))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// The configured keys, or `None` if `CREDENTIAL_KEY` isn't set.
+    pub fn from_config(config: &Config) -> Result<Option<Self>, CredentialError> {
+        config
+            .credential_key
+            .as_deref()
+            .map(|key| Self::new(key, &config.credential_previous_keys))
+            .transpose()
+    }
+
+    pub fn current_key_id(&self) -> &str {
+        &self.current.id
+    }
+
+    /// Encrypt `api_key` for the credential `name` under the current key.
+    pub fn seal(&self, name: &str, api_key: &str) -> Sealed {
+        let nonce: [u8; NONCE_LEN] = rand::random();
+        let mut ciphertext = api_key.as_bytes().to_vec();
+        self.current
+            .key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(name.as_bytes()),
+                &mut ciphertext,
+            )
+            .expect("API keys are far below AES-GCM's length limit");
+        Sealed {
+            key_id: self.current.id.clone(),
+            nonce: nonce.to_vec(),
+            ciphertext,
+        }
+    }
+
+    /// Decrypt a stored credential with whichever configured key sealed it.
+    pub fn open(&self, row: &StoredCredential) -> Result<String, CredentialError> {
+        let key = std::iter::once(&self.current)
+            .chain(&self.previous)
+            .find(|k| k.id == row.key_id)
+            .ok_or_else(|| CredentialError::UnknownKey(row.name.clone(), row.key_id.clone()))?;
+        let corrupt = || CredentialError::Corrupt(row.name.clone());
+        let nonce = Nonce::try_assume_unique_for_key(&row.nonce).map_err(|_| corrupt())?;
+        let mut buf = row.ciphertext.clone();
+        let plaintext = key
+            .key
+            .open_in_place(nonce, Aad::from(row.name.as_bytes()), &mut buf)
+            .map_err(|_| corrupt())?;
+        String::from_utf8(plaintext.to_vec()).map_err(|_| corrupt())
+    }
+}
+
+/// Every stored credential, decrypted. Fails if any can't be, so a missing or wrong key is caught
+/// at startup rather than on a channel's first post.
+pub async fn load(
+    pool: &SqlitePool,
+    keys: Option<&CredentialKeys>,
+) -> Result<Vec<Credential>, AppError> {
+    let rows = db::get_linear_credentials(pool).await?;
+    if rows.is_empty() {
+        return Ok(Vec::new());
+    }
+    let keys = keys.ok_or(CredentialError::NoKey)?;
+    let credentials = rows
+        .into_iter()
+        .map(|row| {
+            Ok::<_, CredentialError>(Credential {
+                api_key: keys.open(&row)?,
+                name: row.name,
+                guild_id: row.guild_id,
+            })
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(credentials)
+}
+
+/// Reseal every credential not already under the current key. Returns how many were.
+pub async fn rotate(pool: &SqlitePool, keys: &CredentialKeys) -> Result<usize, AppError> {
+    let mut rotated = 0;
+    for row in db::get_linear_credentials(pool).await? {
+        if row.key_id == keys.current_key_id() {
+            continue;
+        }
+        let api_key = keys.open(&row)?;
+        db::reseal_linear_credential(pool, &row.name, &keys.seal(&row.name, &api_key)).await?;
+        info!(
+            name = %row.name,
+            from = %row.key_id,
+            to = keys.current_key_id(),
+            "Credential re-encrypted"
+        );
+        rotated += 1;
+    }
+    Ok(rotated)
+}
@@ -1,7 +1,12 @@
+use serde::Serialize;
+use sqlx::migrate::{MigrateError, Migrator};
 use sqlx::sqlite::SqlitePool;
 use sqlx::FromRow;
 
-#[derive(Debug, FromRow)]
+use crate::credentials::Sealed;
+use crate::transfer::ExportedMapping;
+
+#[derive(Debug, FromRow, Serialize)]
 pub struct SyncMapping {
     pub id: i64,
     pub discord_thread_id: String,
@@ -9,6 +14,8 @@ pub struct SyncMapping {
     pub linear_identifier: String,
     pub channel_type: String,
     pub created_at: String,
+    /// Guild of the thread's forum; `None` for mappings made before it was recorded.
+    pub guild_id: Option<String>,
 }
 
 #[derive(Debug, FromRow)]
@@ -26,12 +33,75 @@ pub struct BackfillState {
     pub updated_at: String,
 }
 
+/// A write skipped in shadow mode.
+#[derive(Debug, FromRow, Serialize)]
+pub struct ShadowAction {
+    pub id: i64,
+    pub service: String,
+    pub action: String,
+    pub detail: String,
+    pub created_at: String,
+}
+
+/// A bot action on a synced thread, as listed by `/linear audit`.
+#[derive(Debug, FromRow)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub discord_thread_id: String,
+    pub linear_identifier: String,
+    pub action: String,
+    pub actor_discord_user_id: Option<String>,
+    pub detail: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, FromRow)]
+pub struct PendingIntake {
+    pub author_discord_user_id: String,
+    pub prompt_message_id: String,
+    /// Seconds since the prompt was posted.
+    pub age_secs: i64,
+}
+
+#[derive(Debug, FromRow)]
+pub struct DuplicateCheck {
+    pub author_discord_user_id: String,
+    /// `None` when no similar issues were found.
+    pub prompt_message_id: Option<String>,
+    /// How the prompt was answered; `None` while it waits.
+    pub outcome: Option<String>,
+    /// Seconds since the check ran.
+    pub age_secs: i64,
+}
+
+/// Schema migrations from `migrations/`, embedded at build time. Applied versions and their
+/// checksums are recorded in `_sqlx_migrations`, so each runs once; a migration that failed
+/// partway or was edited after being applied stops startup instead of running again.
+static MIGRATOR: Migrator = sqlx::migrate!();
+
+/// Apply pending migrations. Databases created before migrations were tracked replay the full
+/// history on first run, which is safe because 001–017 only create tables if they don't exist.
+pub async fn run_migrations(pool: &SqlitePool) -> Result<(), MigrateError> {
+    MIGRATOR.run(pool).await
+}
+
+/// Problems found by SQLite's `quick_check` (corrupt pages, rows missing from indexes, broken
+/// constraints); empty when the database is sound.
+pub async fn check_integrity(pool: &SqlitePool) -> Result<Vec<String>, sqlx::Error> {
+    let mut problems: Vec<String> = sqlx::query_scalar("PRAGMA quick_check")
+        .fetch_all(pool)
+        .await?;
+    problems.retain(|p| p != "ok");
+    Ok(problems)
+}
+
 pub async fn get_mapping_by_discord_thread(
     pool: &SqlitePool,
     discord_thread_id: &str,
 ) -> Result<Option<SyncMapping>, sqlx::Error> {
     sqlx::query_as::<_, SyncMapping>(
-        "SELECT id, discord_thread_id, linear_issue_id, linear_identifier, channel_type, created_at
+        "SELECT id, discord_thread_id, linear_issue_id, linear_identifier, channel_type, created_at,
+                guild_id
          FROM sync_mappings WHERE discord_thread_id = ?",
     )
     .bind(discord_thread_id)
@@ -44,7 +114,8 @@ pub async fn get_mapping_by_linear_issue(
     linear_issue_id: &str,
 ) -> Result<Option<SyncMapping>, sqlx::Error> {
     sqlx::query_as::<_, SyncMapping>(
-        "SELECT id, discord_thread_id, linear_issue_id, linear_identifier, channel_type, created_at
+        "SELECT id, discord_thread_id, linear_issue_id, linear_identifier, channel_type, created_at,
+                guild_id
          FROM sync_mappings WHERE linear_issue_id = ?",
     )
     .bind(linear_issue_id)
@@ -58,30 +129,124 @@ pub async fn create_mapping(
     linear_issue_id: &str,
     linear_identifier: &str,
     channel_type: &str,
+    guild_id: &str,
 ) -> Result<(), sqlx::Error> {
     sqlx::query(
-        "INSERT INTO sync_mappings (discord_thread_id, linear_issue_id, linear_identifier, channel_type)
-         VALUES (?, ?, ?, ?)",
+        "INSERT INTO sync_mappings
+           (discord_thread_id, linear_issue_id, linear_identifier, channel_type, guild_id)
+         VALUES (?, ?, ?, ?, ?)",
     )
     .bind(discord_thread_id)
     .bind(linear_issue_id)
     .bind(linear_identifier)
     .bind(channel_type)
+    .bind(guild_id)
     .execute(pool)
     .await?;
     Ok(())
 }
 
+/// Remove a mapping so status and comment sync stop, recording why and by whom in
+/// `mapping_tombstones` and the audit log. The status cache entry is dropped so a later re-link
+/// starts fresh.
+pub async fn tombstone_mapping(
+    pool: &SqlitePool,
+    mapping: &SyncMapping,
+    reason: &str,
+    actor_discord_user_id: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        "INSERT INTO mapping_tombstones
+           (discord_thread_id, linear_issue_id, linear_identifier, channel_type, reason,
+            actor_discord_user_id, mapping_created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&mapping.discord_thread_id)
+    .bind(&mapping.linear_issue_id)
+    .bind(&mapping.linear_identifier)
+    .bind(&mapping.channel_type)
+    .bind(reason)
+    .bind(actor_discord_user_id)
+    .bind(&mapping.created_at)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "INSERT INTO audit_log
+           (discord_thread_id, linear_identifier, action, actor_discord_user_id, detail)
+         VALUES (?, ?, 'mapping_removed', ?, ?)",
+    )
+    .bind(&mapping.discord_thread_id)
+    .bind(&mapping.linear_identifier)
+    .bind(actor_discord_user_id)
+    .bind(reason)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query("DELETE FROM sync_mappings WHERE id = ?")
+        .bind(mapping.id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query("DELETE FROM linear_status_cache WHERE linear_issue_id = ?")
+        .bind(&mapping.linear_issue_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query("DELETE FROM status_history WHERE linear_issue_id = ?")
+        .bind(&mapping.linear_issue_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query("DELETE FROM sla_escalations WHERE linear_issue_id = ?")
+        .bind(&mapping.linear_issue_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query("DELETE FROM stale_nudges WHERE linear_issue_id = ?")
+        .bind(&mapping.linear_issue_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query("DELETE FROM issue_titles WHERE linear_issue_id = ?")
+        .bind(&mapping.linear_issue_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query("DELETE FROM issue_assignees WHERE linear_issue_id = ?")
+        .bind(&mapping.linear_issue_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query("DELETE FROM status_embeds WHERE linear_issue_id = ?")
+        .bind(&mapping.linear_issue_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query("DELETE FROM comment_cursors WHERE linear_issue_id = ?")
+        .bind(&mapping.linear_issue_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query("DELETE FROM relayed_comments WHERE linear_issue_id = ?")
+        .bind(&mapping.linear_issue_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await
+}
+
 pub async fn get_cached_status(
     pool: &SqlitePool,
     linear_issue_id: &str,
 ) -> Result<Option<String>, sqlx::Error> {
-    let row: Option<(String,)> = sqlx::query_as(
-        "SELECT status_name FROM linear_status_cache WHERE linear_issue_id = ?",
-    )
-    .bind(linear_issue_id)
-    .fetch_optional(pool)
-    .await?;
+    let row: Option<(String,)> =
+        sqlx::query_as("SELECT status_name FROM linear_status_cache WHERE linear_issue_id = ?")
+            .bind(linear_issue_id)
+            .fetch_optional(pool)
+            .await?;
     Ok(row.map(|r| r.0))
 }
 
@@ -102,16 +267,110 @@ pub async fn upsert_cached_status(
     Ok(())
 }
 
+/// Append a status change to the issue's history.
+pub async fn insert_status_history(
+    pool: &SqlitePool,
+    linear_issue_id: &str,
+    status_name: &str,
+    status_type: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO status_history (linear_issue_id, status_name, status_type) VALUES (?, ?, ?)",
+    )
+    .bind(linear_issue_id)
+    .bind(status_name)
+    .bind(status_type)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct TrackedStatusChange {
+    pub discord_thread_id: String,
+    pub linear_issue_id: String,
+    pub status_name: String,
+    pub status_type: String,
+    pub changed_at: String,
+}
+
+/// The status history of every tracked issue, only those in `guild_id` if given, grouped by
+/// issue and oldest first.
+pub async fn get_tracked_status_history(
+    pool: &SqlitePool,
+    guild_id: Option<&str>,
+) -> Result<Vec<TrackedStatusChange>, sqlx::Error> {
+    sqlx::query_as::<_, TrackedStatusChange>(
+        "SELECT m.discord_thread_id, h.linear_issue_id, h.status_name, h.status_type, h.changed_at
+         FROM status_history h
+         JOIN sync_mappings m ON m.linear_issue_id = h.linear_issue_id
+         WHERE ?1 IS NULL OR m.guild_id = ?1
+         ORDER BY h.linear_issue_id, h.id",
+    )
+    .bind(guild_id)
+    .fetch_all(pool)
+    .await
+}
+
+#[derive(Debug, FromRow)]
+pub struct SlaCandidate {
+    pub discord_thread_id: String,
+    pub linear_issue_id: String,
+    pub linear_identifier: String,
+    /// Seconds since the issue was filed.
+    pub age_secs: i64,
+    /// Escalation pings already posted.
+    pub pings: i64,
+}
+
+/// Issues the bot filed that haven't had a status change or comment in Linear since, with how
+/// many escalation pings each has had.
+pub async fn get_sla_candidates(pool: &SqlitePool) -> Result<Vec<SlaCandidate>, sqlx::Error> {
+    sqlx::query_as::<_, SlaCandidate>(
+        "SELECT m.discord_thread_id, m.linear_issue_id, m.linear_identifier,
+                CAST((julianday('now') - julianday(m.created_at)) * 86400 AS INTEGER) AS age_secs,
+                COALESCE(e.pings, 0) AS pings
+         FROM sync_mappings m
+         LEFT JOIN sla_escalations e ON e.linear_issue_id = m.linear_issue_id
+         WHERE EXISTS (
+             SELECT 1 FROM audit_log a
+             WHERE a.discord_thread_id = m.discord_thread_id AND a.action = 'issue_created'
+           )
+           AND NOT EXISTS (
+             SELECT 1 FROM audit_log a
+             WHERE a.discord_thread_id = m.discord_thread_id
+               AND a.action IN ('status_announced', 'comment_relayed')
+               AND a.created_at >= m.created_at
+           )
+         ORDER BY m.id",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Record another escalation ping for the issue.
+pub async fn record_sla_ping(pool: &SqlitePool, linear_issue_id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO sla_escalations (linear_issue_id, pings) VALUES (?, 1)
+         ON CONFLICT(linear_issue_id) DO UPDATE SET
+           pings = pings + 1,
+           last_pinged_at = datetime('now')",
+    )
+    .bind(linear_issue_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
 pub async fn is_comment_synced(
     pool: &SqlitePool,
     linear_comment_id: &str,
 ) -> Result<bool, sqlx::Error> {
-    let row: Option<(i32,)> = sqlx::query_as(
-        "SELECT 1 FROM synced_comments WHERE linear_comment_id = ?",
-    )
-    .bind(linear_comment_id)
-    .fetch_optional(pool)
-    .await?;
+    let row: Option<(i32,)> =
+        sqlx::query_as("SELECT 1 FROM synced_comments WHERE linear_comment_id = ?")
+            .bind(linear_comment_id)
+            .fetch_optional(pool)
+            .await?;
     Ok(row.is_some())
 }
 
@@ -133,17 +392,95 @@ pub async fn insert_synced_comment(
     Ok(())
 }
 
-pub async fn get_all_tracked_issues(
+pub async fn delete_synced_comment(
+    pool: &SqlitePool,
+    linear_comment_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM synced_comments WHERE linear_comment_id = ?")
+        .bind(linear_comment_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Tracked issues, only those in `guild_id` if given.
+pub async fn count_mappings(pool: &SqlitePool, guild_id: Option<&str>) -> Result<i64, sqlx::Error> {
+    let (count,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM sync_mappings WHERE ?1 IS NULL OR guild_id = ?1")
+            .bind(guild_id)
+            .fetch_one(pool)
+            .await?;
+    Ok(count)
+}
+
+/// Tracked issues per cached Linear status, most common first, only those in `guild_id` if
+/// given. Issues whose status hasn't been seen yet are counted under `None`.
+pub async fn count_mappings_by_status(
     pool: &SqlitePool,
-) -> Result<Vec<SyncMapping>, sqlx::Error> {
+    guild_id: Option<&str>,
+) -> Result<Vec<(Option<String>, i64)>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT c.status_name, COUNT(*) AS issues
+         FROM sync_mappings m
+         LEFT JOIN linear_status_cache c ON c.linear_issue_id = m.linear_issue_id
+         WHERE ?1 IS NULL OR m.guild_id = ?1
+         GROUP BY c.status_name
+         ORDER BY issues DESC, c.status_name",
+    )
+    .bind(guild_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn get_all_tracked_issues(pool: &SqlitePool) -> Result<Vec<SyncMapping>, sqlx::Error> {
     sqlx::query_as::<_, SyncMapping>(
-        "SELECT id, discord_thread_id, linear_issue_id, linear_identifier, channel_type, created_at
+        "SELECT id, discord_thread_id, linear_issue_id, linear_identifier, channel_type, created_at,
+                guild_id
          FROM sync_mappings",
     )
     .fetch_all(pool)
     .await
 }
 
+#[derive(Debug, FromRow)]
+pub struct VoteCount {
+    pub vote_count: i64,
+    pub label_added: bool,
+}
+
+pub async fn get_vote_count(
+    pool: &SqlitePool,
+    linear_issue_id: &str,
+) -> Result<Option<VoteCount>, sqlx::Error> {
+    sqlx::query_as::<_, VoteCount>(
+        "SELECT vote_count, label_added FROM vote_counts WHERE linear_issue_id = ?",
+    )
+    .bind(linear_issue_id)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn upsert_vote_count(
+    pool: &SqlitePool,
+    linear_issue_id: &str,
+    vote_count: i64,
+    label_added: bool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO vote_counts (linear_issue_id, vote_count, label_added) VALUES (?, ?, ?)
+         ON CONFLICT(linear_issue_id) DO UPDATE SET
+           vote_count = excluded.vote_count,
+           label_added = excluded.label_added,
+           updated_at = datetime('now')",
+    )
+    .bind(linear_issue_id)
+    .bind(vote_count)
+    .bind(label_added)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
 pub async fn get_backfill_state(
     pool: &SqlitePool,
     channel_id: &str,
@@ -179,3 +516,1567 @@ pub async fn upsert_backfill_state(
     Ok(())
 }
 
+pub async fn get_pending_intake(
+    pool: &SqlitePool,
+    discord_thread_id: &str,
+) -> Result<Option<PendingIntake>, sqlx::Error> {
+    sqlx::query_as::<_, PendingIntake>(
+        "SELECT author_discord_user_id, prompt_message_id,
+                CAST((julianday('now') - julianday(created_at)) * 86400 AS INTEGER) AS age_secs
+         FROM pending_intakes WHERE discord_thread_id = ?",
+    )
+    .bind(discord_thread_id)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn insert_pending_intake(
+    pool: &SqlitePool,
+    discord_thread_id: &str,
+    author_discord_user_id: &str,
+    prompt_message_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO pending_intakes (discord_thread_id, author_discord_user_id, prompt_message_id)
+         VALUES (?, ?, ?)",
+    )
+    .bind(discord_thread_id)
+    .bind(author_discord_user_id)
+    .bind(prompt_message_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// How long a claim holds. A claim older than this belongs to a process that died mid-filing.
+const INTAKE_CLAIM_TIMEOUT_SECS: i64 = 300;
+
+/// Claim a pending intake, returning whether it was unclaimed (or its claim had expired). Only
+/// one caller (form submit, skip, or timeout) goes on to create the issue.
+pub async fn claim_pending_intake(
+    pool: &SqlitePool,
+    discord_thread_id: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE pending_intakes SET claimed_at = datetime('now')
+         WHERE discord_thread_id = ?
+           AND (claimed_at IS NULL
+                OR claimed_at <= datetime('now', '-' || ? || ' seconds'))",
+    )
+    .bind(discord_thread_id)
+    .bind(INTAKE_CLAIM_TIMEOUT_SECS)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Give up a claim after filing failed, so the next attempt can claim the intake again.
+pub async fn release_pending_intake(
+    pool: &SqlitePool,
+    discord_thread_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE pending_intakes SET claimed_at = NULL WHERE discord_thread_id = ?")
+        .bind(discord_thread_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Remove a pending intake once its issue exists.
+pub async fn delete_pending_intake(
+    pool: &SqlitePool,
+    discord_thread_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM pending_intakes WHERE discord_thread_id = ?")
+        .bind(discord_thread_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Claim a new post for filing, returning whether it was unclaimed (or its claim, older than
+/// `timeout_secs`, belongs to a process that died). Only the claimant creates the issue.
+pub async fn claim_thread(
+    pool: &SqlitePool,
+    discord_thread_id: &str,
+    timeout_secs: i64,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO thread_claims (discord_thread_id) VALUES (?)
+         ON CONFLICT(discord_thread_id) DO UPDATE SET claimed_at = datetime('now')
+         WHERE thread_claims.claimed_at <= datetime('now', '-' || ? || ' seconds')",
+    )
+    .bind(discord_thread_id)
+    .bind(timeout_secs)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Give up a post's claim once it's filed, or left waiting on a prompt.
+pub async fn release_thread_claim(
+    pool: &SqlitePool,
+    discord_thread_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM thread_claims WHERE discord_thread_id = ?")
+        .bind(discord_thread_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn get_duplicate_check(
+    pool: &SqlitePool,
+    discord_thread_id: &str,
+) -> Result<Option<DuplicateCheck>, sqlx::Error> {
+    sqlx::query_as::<_, DuplicateCheck>(
+        "SELECT author_discord_user_id, prompt_message_id, outcome,
+                CAST((julianday('now') - julianday(created_at)) * 86400 AS INTEGER) AS age_secs
+         FROM duplicate_checks WHERE discord_thread_id = ?",
+    )
+    .bind(discord_thread_id)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn insert_duplicate_check(
+    pool: &SqlitePool,
+    discord_thread_id: &str,
+    author_discord_user_id: &str,
+    prompt_message_id: Option<&str>,
+    outcome: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO duplicate_checks
+           (discord_thread_id, author_discord_user_id, prompt_message_id, outcome)
+         VALUES (?, ?, ?, ?)",
+    )
+    .bind(discord_thread_id)
+    .bind(author_discord_user_id)
+    .bind(prompt_message_id)
+    .bind(outcome)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Record how a waiting duplicate prompt was answered, returning whether it was still waiting.
+/// Used as a claim so only one answer (a button or the timeout) is acted on.
+pub async fn resolve_duplicate_check(
+    pool: &SqlitePool,
+    discord_thread_id: &str,
+    outcome: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE duplicate_checks SET outcome = ?
+         WHERE discord_thread_id = ? AND outcome IS NULL",
+    )
+    .bind(outcome)
+    .bind(discord_thread_id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+#[derive(Debug, FromRow)]
+pub struct ThreadApproval {
+    pub prompt_message_id: String,
+    /// `None` while the thread waits for approval.
+    pub approved_by_discord_user_id: Option<String>,
+}
+
+pub async fn get_thread_approval(
+    pool: &SqlitePool,
+    discord_thread_id: &str,
+) -> Result<Option<ThreadApproval>, sqlx::Error> {
+    sqlx::query_as::<_, ThreadApproval>(
+        "SELECT prompt_message_id, approved_by_discord_user_id
+         FROM thread_approvals WHERE discord_thread_id = ?",
+    )
+    .bind(discord_thread_id)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn insert_thread_approval(
+    pool: &SqlitePool,
+    discord_thread_id: &str,
+    prompt_message_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO thread_approvals (discord_thread_id, prompt_message_id) VALUES (?, ?)",
+    )
+    .bind(discord_thread_id)
+    .bind(prompt_message_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Mark a waiting thread approved, returning whether it was still waiting. Used as a claim so a
+/// button click and a reaction don't both create the issue.
+pub async fn approve_thread(
+    pool: &SqlitePool,
+    discord_thread_id: &str,
+    approved_by_discord_user_id: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE thread_approvals
+         SET approved_by_discord_user_id = ?, approved_at = datetime('now')
+         WHERE discord_thread_id = ? AND approved_by_discord_user_id IS NULL",
+    )
+    .bind(approved_by_discord_user_id)
+    .bind(discord_thread_id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Subscribe a user to a thread's issue, returning whether they weren't already subscribed.
+pub async fn insert_subscription(
+    pool: &SqlitePool,
+    discord_thread_id: &str,
+    discord_user_id: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO subscriptions (discord_thread_id, discord_user_id) VALUES (?, ?)
+         ON CONFLICT(discord_thread_id, discord_user_id) DO NOTHING",
+    )
+    .bind(discord_thread_id)
+    .bind(discord_user_id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Unsubscribe a user from a thread's issue, returning whether they were subscribed.
+pub async fn delete_subscription(
+    pool: &SqlitePool,
+    discord_thread_id: &str,
+    discord_user_id: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "DELETE FROM subscriptions WHERE discord_thread_id = ? AND discord_user_id = ?",
+    )
+    .bind(discord_thread_id)
+    .bind(discord_user_id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Discord user IDs subscribed to a thread's issue, oldest subscription first.
+pub async fn get_subscribers(
+    pool: &SqlitePool,
+    discord_thread_id: &str,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT discord_user_id FROM subscriptions WHERE discord_thread_id = ?
+         ORDER BY created_at, discord_user_id",
+    )
+    .bind(discord_thread_id)
+    .fetch_all(pool)
+    .await
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, FromRow)]
+pub struct NotificationPrefs {
+    pub dm_on_assignment: bool,
+    pub status_changes_only: bool,
+    pub muted: bool,
+}
+
+/// A user's notification settings, or the defaults if they haven't changed any.
+pub async fn get_notification_prefs(
+    pool: &SqlitePool,
+    discord_user_id: &str,
+) -> Result<NotificationPrefs, sqlx::Error> {
+    let prefs = sqlx::query_as::<_, NotificationPrefs>(
+        "SELECT dm_on_assignment, status_changes_only, muted
+         FROM notification_prefs WHERE discord_user_id = ?",
+    )
+    .bind(discord_user_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(prefs.unwrap_or_default())
+}
+
+pub async fn upsert_notification_prefs(
+    pool: &SqlitePool,
+    discord_user_id: &str,
+    prefs: &NotificationPrefs,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO notification_prefs
+           (discord_user_id, dm_on_assignment, status_changes_only, muted)
+         VALUES (?, ?, ?, ?)
+         ON CONFLICT(discord_user_id) DO UPDATE SET
+           dm_on_assignment = excluded.dm_on_assignment,
+           status_changes_only = excluded.status_changes_only,
+           muted = excluded.muted,
+           updated_at = datetime('now')",
+    )
+    .bind(discord_user_id)
+    .bind(prefs.dm_on_assignment)
+    .bind(prefs.status_changes_only)
+    .bind(prefs.muted)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+#[derive(Debug, FromRow)]
+pub struct PendingArchive {
+    pub discord_thread_id: String,
+    pub lock_thread: bool,
+}
+
+pub async fn upsert_pending_archive(
+    pool: &SqlitePool,
+    discord_thread_id: &str,
+    delay_secs: u64,
+    lock_thread: bool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO pending_archives (discord_thread_id, archive_at, lock_thread)
+         VALUES (?, datetime('now', '+' || ? || ' seconds'), ?)
+         ON CONFLICT(discord_thread_id) DO UPDATE SET
+           archive_at = excluded.archive_at,
+           lock_thread = excluded.lock_thread",
+    )
+    .bind(discord_thread_id)
+    .bind(delay_secs as i64)
+    .bind(lock_thread)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn delete_pending_archive(
+    pool: &SqlitePool,
+    discord_thread_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM pending_archives WHERE discord_thread_id = ?")
+        .bind(discord_thread_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn get_due_pending_archives(
+    pool: &SqlitePool,
+) -> Result<Vec<PendingArchive>, sqlx::Error> {
+    sqlx::query_as::<_, PendingArchive>(
+        "SELECT discord_thread_id, lock_thread FROM pending_archives
+         WHERE archive_at <= datetime('now')",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+#[derive(Debug, FromRow)]
+pub struct PendingRename {
+    pub linear_issue_id: String,
+    pub discord_thread_id: String,
+    pub title: String,
+}
+
+/// Record the current Linear title. The first sighting of an issue is taken as already applied
+/// so existing threads aren't renamed on startup.
+pub async fn record_issue_title(
+    pool: &SqlitePool,
+    linear_issue_id: &str,
+    title: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO issue_titles (linear_issue_id, title, applied_title)
+         VALUES (?, ?, ?)
+         ON CONFLICT(linear_issue_id) DO UPDATE SET title = excluded.title",
+    )
+    .bind(linear_issue_id)
+    .bind(title)
+    .bind(title)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+#[derive(Debug, FromRow)]
+pub struct ContentHash {
+    pub linear_issue_id: String,
+    pub linear_identifier: String,
+}
+
+/// Record the content hash of a post an issue was created for. A later issue for the same
+/// content replaces the earlier one.
+pub async fn record_content_hash(
+    pool: &SqlitePool,
+    discord_channel_id: &str,
+    content_hash: &str,
+    linear_issue_id: &str,
+    linear_identifier: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO content_hashes
+           (discord_channel_id, content_hash, linear_issue_id, linear_identifier)
+         VALUES (?, ?, ?, ?)
+         ON CONFLICT(discord_channel_id, content_hash) DO UPDATE SET
+           linear_issue_id = excluded.linear_issue_id,
+           linear_identifier = excluded.linear_identifier,
+           created_at = datetime('now')",
+    )
+    .bind(discord_channel_id)
+    .bind(content_hash)
+    .bind(linear_issue_id)
+    .bind(linear_identifier)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn get_content_hash(
+    pool: &SqlitePool,
+    discord_channel_id: &str,
+    content_hash: &str,
+) -> Result<Option<ContentHash>, sqlx::Error> {
+    sqlx::query_as::<_, ContentHash>(
+        "SELECT linear_issue_id, linear_identifier FROM content_hashes
+         WHERE discord_channel_id = ? AND content_hash = ?",
+    )
+    .bind(discord_channel_id)
+    .bind(content_hash)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Record a repost of a live thread, returning whether it was new. Used as a claim so the
+/// author is only pointed to the original once.
+pub async fn insert_repost(
+    pool: &SqlitePool,
+    discord_thread_id: &str,
+    original_discord_thread_id: &str,
+    linear_identifier: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO reposts (discord_thread_id, original_discord_thread_id, linear_identifier)
+         VALUES (?, ?, ?)
+         ON CONFLICT(discord_thread_id) DO NOTHING",
+    )
+    .bind(discord_thread_id)
+    .bind(original_discord_thread_id)
+    .bind(linear_identifier)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn is_thread_filtered(
+    pool: &SqlitePool,
+    discord_thread_id: &str,
+) -> Result<bool, sqlx::Error> {
+    let row: Option<(i64,)> =
+        sqlx::query_as("SELECT 1 FROM filtered_threads WHERE discord_thread_id = ?")
+            .bind(discord_thread_id)
+            .fetch_optional(pool)
+            .await?;
+    Ok(row.is_some())
+}
+
+/// Record a post the spam filter kept from being filed, returning whether it was new. Used as
+/// a claim so the author is only told once.
+pub async fn insert_filtered_thread(
+    pool: &SqlitePool,
+    discord_thread_id: &str,
+    reason: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO filtered_threads (discord_thread_id, reason) VALUES (?, ?)
+         ON CONFLICT(discord_thread_id) DO NOTHING",
+    )
+    .bind(discord_thread_id)
+    .bind(reason)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Issues created from a Discord user's posts within the last `window_secs`.
+pub async fn count_recent_issues_by(
+    pool: &SqlitePool,
+    actor_discord_user_id: &str,
+    window_secs: i64,
+) -> Result<i64, sqlx::Error> {
+    let (count,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM audit_log
+         WHERE actor_discord_user_id = ? AND action = 'issue_created'
+           AND created_at > datetime('now', '-' || ? || ' seconds')",
+    )
+    .bind(actor_discord_user_id)
+    .bind(window_secs)
+    .fetch_one(pool)
+    .await?;
+    Ok(count)
+}
+
+/// Tracked issues whose thread name is behind the Linear title and that haven't been renamed
+/// within the last `min_interval_secs`.
+pub async fn get_pending_renames(
+    pool: &SqlitePool,
+    min_interval_secs: i64,
+) -> Result<Vec<PendingRename>, sqlx::Error> {
+    sqlx::query_as::<_, PendingRename>(
+        "SELECT t.linear_issue_id, m.discord_thread_id, t.title
+         FROM issue_titles t
+         JOIN sync_mappings m ON m.linear_issue_id = t.linear_issue_id
+         WHERE t.title != t.applied_title
+           AND (t.renamed_at IS NULL
+                OR t.renamed_at <= datetime('now', '-' || ? || ' seconds'))",
+    )
+    .bind(min_interval_secs)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn get_applied_title(
+    pool: &SqlitePool,
+    linear_issue_id: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    let row: Option<(String,)> =
+        sqlx::query_as("SELECT applied_title FROM issue_titles WHERE linear_issue_id = ?")
+            .bind(linear_issue_id)
+            .fetch_optional(pool)
+            .await?;
+    Ok(row.map(|r| r.0))
+}
+
+/// Store a title that now matches on both sides, e.g. after a Discord rename was pushed to
+/// Linear. The poller then sees no difference and won't rename the thread back.
+pub async fn set_synced_title(
+    pool: &SqlitePool,
+    linear_issue_id: &str,
+    title: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO issue_titles (linear_issue_id, title, applied_title)
+         VALUES (?, ?, ?)
+         ON CONFLICT(linear_issue_id) DO UPDATE SET
+           title = excluded.title,
+           applied_title = excluded.applied_title",
+    )
+    .bind(linear_issue_id)
+    .bind(title)
+    .bind(title)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn mark_title_applied(
+    pool: &SqlitePool,
+    linear_issue_id: &str,
+    title: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE issue_titles SET applied_title = ?, renamed_at = datetime('now')
+         WHERE linear_issue_id = ?",
+    )
+    .bind(title)
+    .bind(linear_issue_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+#[derive(Debug, FromRow)]
+pub struct UserLink {
+    pub discord_user_id: String,
+    pub linear_user_id: String,
+    pub linear_name: String,
+}
+
+pub async fn upsert_user_link(
+    pool: &SqlitePool,
+    discord_user_id: &str,
+    linear_user_id: &str,
+    linear_name: &str,
+    linear_email: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO user_links (discord_user_id, linear_user_id, linear_name, linear_email)
+         VALUES (?, ?, ?, ?)
+         ON CONFLICT(discord_user_id) DO UPDATE SET
+           linear_user_id = excluded.linear_user_id,
+           linear_name = excluded.linear_name,
+           linear_email = excluded.linear_email,
+           created_at = datetime('now')",
+    )
+    .bind(discord_user_id)
+    .bind(linear_user_id)
+    .bind(linear_name)
+    .bind(linear_email)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn delete_user_link(
+    pool: &SqlitePool,
+    discord_user_id: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM user_links WHERE discord_user_id = ?")
+        .bind(discord_user_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn get_user_link_by_discord_user(
+    pool: &SqlitePool,
+    discord_user_id: &str,
+) -> Result<Option<UserLink>, sqlx::Error> {
+    sqlx::query_as::<_, UserLink>(
+        "SELECT discord_user_id, linear_user_id, linear_name FROM user_links
+         WHERE discord_user_id = ?",
+    )
+    .bind(discord_user_id)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn get_user_link_by_linear_user(
+    pool: &SqlitePool,
+    linear_user_id: &str,
+) -> Result<Option<UserLink>, sqlx::Error> {
+    sqlx::query_as::<_, UserLink>(
+        "SELECT discord_user_id, linear_user_id, linear_name FROM user_links
+         WHERE linear_user_id = ?",
+    )
+    .bind(linear_user_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// How long a `/linear connect` code stays valid, in minutes.
+pub const USER_LINK_CODE_TTL_MINUTES: i64 = 30;
+
+#[derive(Debug, FromRow)]
+pub struct UserLinkCode {
+    pub linear_user_id: String,
+    pub code: String,
+}
+
+/// Issue `code` for linking `discord_user_id` to `linear_user_id`, replacing any earlier one.
+pub async fn upsert_user_link_code(
+    pool: &SqlitePool,
+    discord_user_id: &str,
+    linear_user_id: &str,
+    code: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO user_link_codes (discord_user_id, linear_user_id, code)
+         VALUES (?, ?, ?)
+         ON CONFLICT(discord_user_id) DO UPDATE SET
+           linear_user_id = excluded.linear_user_id,
+           code = excluded.code,
+           created_at = datetime('now')",
+    )
+    .bind(discord_user_id)
+    .bind(linear_user_id)
+    .bind(code)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// The unexpired code issued to `discord_user_id`, if any.
+pub async fn get_user_link_code(
+    pool: &SqlitePool,
+    discord_user_id: &str,
+) -> Result<Option<UserLinkCode>, sqlx::Error> {
+    sqlx::query_as::<_, UserLinkCode>(
+        "SELECT linear_user_id, code FROM user_link_codes
+         WHERE discord_user_id = ?
+           AND created_at > datetime('now', '-' || ? || ' minutes')",
+    )
+    .bind(discord_user_id)
+    .bind(USER_LINK_CODE_TTL_MINUTES)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn delete_user_link_code(
+    pool: &SqlitePool,
+    discord_user_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM user_link_codes WHERE discord_user_id = ?")
+        .bind(discord_user_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn get_channel_webhook(
+    pool: &SqlitePool,
+    discord_channel_id: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    let row: Option<(String,)> =
+        sqlx::query_as("SELECT webhook FROM channel_webhooks WHERE discord_channel_id = ?")
+            .bind(discord_channel_id)
+            .fetch_optional(pool)
+            .await?;
+    Ok(row.map(|r| r.0))
+}
+
+pub async fn upsert_channel_webhook(
+    pool: &SqlitePool,
+    discord_channel_id: &str,
+    webhook: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO channel_webhooks (discord_channel_id, webhook) VALUES (?, ?)
+         ON CONFLICT(discord_channel_id) DO UPDATE SET
+           webhook = excluded.webhook,
+           created_at = datetime('now')",
+    )
+    .bind(discord_channel_id)
+    .bind(webhook)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn delete_channel_webhook(
+    pool: &SqlitePool,
+    discord_channel_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM channel_webhooks WHERE discord_channel_id = ?")
+        .bind(discord_channel_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Last announced assignee: `None` if the issue hasn't been seen yet, `Some(None)` if it was
+/// unassigned.
+pub async fn get_cached_assignee(
+    pool: &SqlitePool,
+    linear_issue_id: &str,
+) -> Result<Option<Option<String>>, sqlx::Error> {
+    let row: Option<(Option<String>,)> =
+        sqlx::query_as("SELECT assignee_id FROM issue_assignees WHERE linear_issue_id = ?")
+            .bind(linear_issue_id)
+            .fetch_optional(pool)
+            .await?;
+    Ok(row.map(|r| r.0))
+}
+
+pub async fn upsert_cached_assignee(
+    pool: &SqlitePool,
+    linear_issue_id: &str,
+    assignee_id: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO issue_assignees (linear_issue_id, assignee_id, updated_at)
+         VALUES (?, ?, datetime('now'))
+         ON CONFLICT(linear_issue_id) DO UPDATE SET
+           assignee_id = excluded.assignee_id,
+           updated_at = excluded.updated_at",
+    )
+    .bind(linear_issue_id)
+    .bind(assignee_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn is_reminder_sent(
+    pool: &SqlitePool,
+    linear_issue_id: &str,
+    due_date: &str,
+    kind: &str,
+) -> Result<bool, sqlx::Error> {
+    let row: Option<(i32,)> = sqlx::query_as(
+        "SELECT 1 FROM due_date_reminders
+         WHERE linear_issue_id = ? AND due_date = ? AND kind = ?",
+    )
+    .bind(linear_issue_id)
+    .bind(due_date)
+    .bind(kind)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.is_some())
+}
+
+pub async fn insert_reminder_sent(
+    pool: &SqlitePool,
+    linear_issue_id: &str,
+    due_date: &str,
+    kind: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT OR IGNORE INTO due_date_reminders (linear_issue_id, due_date, kind)
+         VALUES (?, ?, ?)",
+    )
+    .bind(linear_issue_id)
+    .bind(due_date)
+    .bind(kind)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Whether the issue's thread was nudged within the last `period_days`.
+pub async fn was_nudged_within(
+    pool: &SqlitePool,
+    linear_issue_id: &str,
+    period_days: i64,
+) -> Result<bool, sqlx::Error> {
+    let row: Option<(i32,)> = sqlx::query_as(
+        "SELECT 1 FROM stale_nudges
+         WHERE linear_issue_id = ? AND nudged_at > datetime('now', '-' || ? || ' days')",
+    )
+    .bind(linear_issue_id)
+    .bind(period_days)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.is_some())
+}
+
+pub async fn upsert_stale_nudge(
+    pool: &SqlitePool,
+    linear_issue_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO stale_nudges (linear_issue_id) VALUES (?)
+         ON CONFLICT(linear_issue_id) DO UPDATE SET nudged_at = datetime('now')",
+    )
+    .bind(linear_issue_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Message ID of the pinned status embed in the issue's thread, if one has been posted.
+pub async fn get_status_embed(
+    pool: &SqlitePool,
+    linear_issue_id: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    let row: Option<(String,)> =
+        sqlx::query_as("SELECT discord_message_id FROM status_embeds WHERE linear_issue_id = ?")
+            .bind(linear_issue_id)
+            .fetch_optional(pool)
+            .await?;
+    Ok(row.map(|r| r.0))
+}
+
+pub async fn upsert_status_embed(
+    pool: &SqlitePool,
+    linear_issue_id: &str,
+    discord_message_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO status_embeds (linear_issue_id, discord_message_id)
+         VALUES (?, ?)
+         ON CONFLICT(linear_issue_id) DO UPDATE SET
+           discord_message_id = excluded.discord_message_id,
+           updated_at = datetime('now')",
+    )
+    .bind(linear_issue_id)
+    .bind(discord_message_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn insert_pending_notification(
+    pool: &SqlitePool,
+    discord_thread_id: &str,
+    body: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO pending_notifications (discord_thread_id, body) VALUES (?, ?)")
+        .bind(discord_thread_id)
+        .bind(body)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Threads whose most recent pending notification is at least `window_secs` old.
+pub async fn get_settled_notification_threads(
+    pool: &SqlitePool,
+    window_secs: u64,
+) -> Result<Vec<String>, sqlx::Error> {
+    let rows: Vec<(String,)> = sqlx::query_as(
+        "SELECT discord_thread_id FROM pending_notifications
+         GROUP BY discord_thread_id
+         HAVING MAX(created_at) <= datetime('now', '-' || ? || ' seconds')",
+    )
+    .bind(window_secs as i64)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(|r| r.0).collect())
+}
+
+/// Pending notifications for a thread, oldest first, as `(id, body)` pairs.
+pub async fn get_pending_notifications(
+    pool: &SqlitePool,
+    discord_thread_id: &str,
+) -> Result<Vec<(i64, String)>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT id, body FROM pending_notifications WHERE discord_thread_id = ? ORDER BY id",
+    )
+    .bind(discord_thread_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Remove a thread's pending notifications up to and including `max_id`; anything queued while
+/// the digest was being posted is kept for the next one.
+pub async fn delete_pending_notifications(
+    pool: &SqlitePool,
+    discord_thread_id: &str,
+    max_id: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM pending_notifications WHERE discord_thread_id = ? AND id <= ?")
+        .bind(discord_thread_id)
+        .bind(max_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// `createdAt` of the newest comment already handled for the issue.
+pub async fn get_comment_cursor(
+    pool: &SqlitePool,
+    linear_issue_id: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    let row: Option<(String,)> =
+        sqlx::query_as("SELECT last_created_at FROM comment_cursors WHERE linear_issue_id = ?")
+            .bind(linear_issue_id)
+            .fetch_optional(pool)
+            .await?;
+    Ok(row.map(|r| r.0))
+}
+
+pub async fn upsert_comment_cursor(
+    pool: &SqlitePool,
+    linear_issue_id: &str,
+    last_created_at: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO comment_cursors (linear_issue_id, last_created_at)
+         VALUES (?, ?)
+         ON CONFLICT(linear_issue_id) DO UPDATE SET
+           last_created_at = excluded.last_created_at,
+           updated_at = datetime('now')",
+    )
+    .bind(linear_issue_id)
+    .bind(last_created_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn get_poll_cursor(
+    pool: &SqlitePool,
+    linear_team_id: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    let row: Option<(String,)> =
+        sqlx::query_as("SELECT last_polled_at FROM poll_cursors WHERE linear_team_id = ?")
+            .bind(linear_team_id)
+            .fetch_optional(pool)
+            .await?;
+    Ok(row.map(|r| r.0))
+}
+
+pub async fn upsert_poll_cursor(
+    pool: &SqlitePool,
+    linear_team_id: &str,
+    last_polled_at: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO poll_cursors (linear_team_id, last_polled_at)
+         VALUES (?, ?)
+         ON CONFLICT(linear_team_id) DO UPDATE SET
+           last_polled_at = excluded.last_polled_at,
+           updated_at = datetime('now')",
+    )
+    .bind(linear_team_id)
+    .bind(last_polled_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+#[derive(Debug, FromRow)]
+pub struct PollCursor {
+    pub linear_team_id: String,
+    pub last_polled_at: String,
+    /// When the cursor was last saved, i.e. the team's last completed poll.
+    pub updated_at: String,
+}
+
+pub async fn get_poll_cursors(pool: &SqlitePool) -> Result<Vec<PollCursor>, sqlx::Error> {
+    sqlx::query_as::<_, PollCursor>(
+        "SELECT linear_team_id, last_polled_at, updated_at FROM poll_cursors
+         ORDER BY linear_team_id",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+#[derive(Debug, FromRow)]
+pub struct RelayedComment {
+    pub linear_comment_id: String,
+    pub discord_message_id: String,
+    pub via_webhook: bool,
+    pub linear_updated_at: String,
+}
+
+pub async fn insert_relayed_comment(
+    pool: &SqlitePool,
+    linear_comment_id: &str,
+    linear_issue_id: &str,
+    discord_message_id: &str,
+    via_webhook: bool,
+    linear_updated_at: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO relayed_comments
+           (linear_comment_id, linear_issue_id, discord_message_id, via_webhook, linear_updated_at)
+         VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(linear_comment_id)
+    .bind(linear_issue_id)
+    .bind(discord_message_id)
+    .bind(via_webhook)
+    .bind(linear_updated_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// A Linear comment mirrored to or from a Discord message, from `synced_comments`.
+#[derive(Debug, FromRow)]
+pub struct SyncedComment {
+    pub linear_comment_id: String,
+    pub discord_message_id: String,
+}
+
+pub async fn get_synced_comments(
+    pool: &SqlitePool,
+    linear_issue_id: &str,
+) -> Result<Vec<SyncedComment>, sqlx::Error> {
+    sqlx::query_as::<_, SyncedComment>(
+        "SELECT linear_comment_id, discord_message_id FROM synced_comments
+         WHERE linear_issue_id = ? ORDER BY created_at, linear_comment_id",
+    )
+    .bind(linear_issue_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn get_relayed_comments(
+    pool: &SqlitePool,
+    linear_issue_id: &str,
+) -> Result<Vec<RelayedComment>, sqlx::Error> {
+    sqlx::query_as::<_, RelayedComment>(
+        "SELECT linear_comment_id, discord_message_id, via_webhook, linear_updated_at
+         FROM relayed_comments WHERE linear_issue_id = ?",
+    )
+    .bind(linear_issue_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn set_relayed_comment_updated_at(
+    pool: &SqlitePool,
+    linear_comment_id: &str,
+    linear_updated_at: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE relayed_comments SET linear_updated_at = ? WHERE linear_comment_id = ?")
+        .bind(linear_updated_at)
+        .bind(linear_comment_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn delete_relayed_comment(
+    pool: &SqlitePool,
+    linear_comment_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM relayed_comments WHERE linear_comment_id = ?")
+        .bind(linear_comment_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+#[derive(Debug, FromRow)]
+pub struct PendingOperation {
+    pub id: i64,
+    pub payload: String,
+    pub attempts: i64,
+}
+
+/// Queue a failed operation for retry on the next poll. An operation already queued under
+/// `dedupe_key` is kept as is, along with its retry schedule.
+pub async fn insert_pending_operation(
+    pool: &SqlitePool,
+    dedupe_key: &str,
+    payload: &str,
+    last_error: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO pending_operations (dedupe_key, payload, last_error) VALUES (?, ?, ?)
+         ON CONFLICT(dedupe_key) DO NOTHING",
+    )
+    .bind(dedupe_key)
+    .bind(payload)
+    .bind(last_error)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Queued operations whose next attempt is due, oldest first.
+pub async fn get_due_pending_operations(
+    pool: &SqlitePool,
+) -> Result<Vec<PendingOperation>, sqlx::Error> {
+    sqlx::query_as::<_, PendingOperation>(
+        "SELECT id, payload, attempts FROM pending_operations
+         WHERE next_attempt_at <= datetime('now')
+         ORDER BY id",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Record a failed retry and push the next attempt `delay_secs` out.
+pub async fn reschedule_pending_operation(
+    pool: &SqlitePool,
+    id: i64,
+    delay_secs: u64,
+    last_error: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE pending_operations SET
+           attempts = attempts + 1,
+           last_error = ?,
+           next_attempt_at = datetime('now', '+' || ? || ' seconds')
+         WHERE id = ?",
+    )
+    .bind(last_error)
+    .bind(delay_secs as i64)
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn delete_pending_operation(pool: &SqlitePool, id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM pending_operations WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Move a queued operation that has run out of retries to `dead_letters`, recording the error
+/// from its final attempt.
+pub async fn dead_letter_pending_operation(
+    pool: &SqlitePool,
+    id: i64,
+    last_error: &str,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        "INSERT OR REPLACE INTO dead_letters (dedupe_key, payload, attempts, last_error, created_at)
+         SELECT dedupe_key, payload, attempts + 1, ?, created_at
+         FROM pending_operations WHERE id = ?",
+    )
+    .bind(last_error)
+    .bind(id)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query("DELETE FROM pending_operations WHERE id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Put every dead-lettered operation back in the retry queue with a fresh attempt count, due
+/// immediately. Returns how many were requeued.
+pub async fn requeue_dead_letters(pool: &SqlitePool) -> Result<u64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    // An operation queued again for the same target since is already due for retry; keep it.
+    sqlx::query(
+        "INSERT OR IGNORE INTO pending_operations (dedupe_key, payload, last_error)
+         SELECT dedupe_key, payload, last_error FROM dead_letters",
+    )
+    .execute(&mut *tx)
+    .await?;
+    let requeued = sqlx::query("DELETE FROM dead_letters")
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    tx.commit().await?;
+    Ok(requeued)
+}
+
+/// Sync operations that first failed within the last `window_secs`, whether they are still
+/// being retried or have given up.
+pub async fn count_sync_failures_since(
+    pool: &SqlitePool,
+    window_secs: i64,
+) -> Result<i64, sqlx::Error> {
+    let (count,): (i64,) = sqlx::query_as(
+        "SELECT
+           (SELECT COUNT(*) FROM pending_operations
+            WHERE created_at > datetime('now', '-' || ?1 || ' seconds'))
+         + (SELECT COUNT(*) FROM dead_letters
+            WHERE created_at > datetime('now', '-' || ?1 || ' seconds'))",
+    )
+    .bind(window_secs)
+    .fetch_one(pool)
+    .await?;
+    Ok(count)
+}
+
+#[derive(Debug, FromRow)]
+pub struct StoredChannelConfig {
+    pub discord_channel_id: String,
+    pub guild_id: String,
+    /// `None` removes the channel's `CHANNELS` entry.
+    pub config: Option<String>,
+}
+
+pub async fn get_channel_configs(
+    pool: &SqlitePool,
+) -> Result<Vec<StoredChannelConfig>, sqlx::Error> {
+    sqlx::query_as::<_, StoredChannelConfig>(
+        "SELECT discord_channel_id, guild_id, config FROM channel_configs
+         ORDER BY discord_channel_id",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn upsert_channel_config(
+    pool: &SqlitePool,
+    discord_channel_id: &str,
+    guild_id: &str,
+    config: Option<&str>,
+    updated_by: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO channel_configs (discord_channel_id, guild_id, config, updated_by)
+         VALUES (?, ?, ?, ?)
+         ON CONFLICT(discord_channel_id) DO UPDATE SET
+           guild_id = excluded.guild_id,
+           config = excluded.config,
+           updated_by = excluded.updated_by,
+           updated_at = datetime('now')",
+    )
+    .bind(discord_channel_id)
+    .bind(guild_id)
+    .bind(config)
+    .bind(updated_by)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn delete_channel_config(
+    pool: &SqlitePool,
+    discord_channel_id: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM channel_configs WHERE discord_channel_id = ?")
+        .bind(discord_channel_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+#[derive(Debug, FromRow)]
+pub struct StoredCredential {
+    pub name: String,
+    pub guild_id: String,
+    /// Which encryption key sealed the row; see `credentials::CredentialKeys`.
+    pub key_id: String,
+    pub nonce: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+}
+
+pub async fn get_linear_credentials(
+    pool: &SqlitePool,
+) -> Result<Vec<StoredCredential>, sqlx::Error> {
+    sqlx::query_as::<_, StoredCredential>(
+        "SELECT name, guild_id, key_id, nonce, ciphertext FROM linear_credentials ORDER BY name",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Store a credential for `guild_id`, replacing its key if the guild already has one by that
+/// name. Returns `false`, storing nothing, if another guild owns the name.
+pub async fn upsert_linear_credential(
+    pool: &SqlitePool,
+    name: &str,
+    guild_id: &str,
+    sealed: &Sealed,
+    updated_by: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO linear_credentials (name, guild_id, key_id, nonce, ciphertext, updated_by)
+         VALUES (?, ?, ?, ?, ?, ?)
+         ON CONFLICT(name) DO UPDATE SET
+           key_id = excluded.key_id,
+           nonce = excluded.nonce,
+           ciphertext = excluded.ciphertext,
+           updated_by = excluded.updated_by,
+           updated_at = datetime('now')
+         WHERE linear_credentials.guild_id = excluded.guild_id",
+    )
+    .bind(name)
+    .bind(guild_id)
+    .bind(&sealed.key_id)
+    .bind(&sealed.nonce)
+    .bind(&sealed.ciphertext)
+    .bind(updated_by)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Replace a credential's encryption after a key rotation, leaving who set it untouched.
+pub async fn reseal_linear_credential(
+    pool: &SqlitePool,
+    name: &str,
+    sealed: &Sealed,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE linear_credentials SET key_id = ?, nonce = ?, ciphertext = ? WHERE name = ?",
+    )
+    .bind(&sealed.key_id)
+    .bind(&sealed.nonce)
+    .bind(&sealed.ciphertext)
+    .bind(name)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn delete_linear_credential(pool: &SqlitePool, name: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM linear_credentials WHERE name = ?")
+        .bind(name)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Take or renew the leader lease for `holder` for `ttl_secs`, unless another replica's lease is
+/// still live. Returns whether `holder` now holds it.
+pub async fn acquire_leader_lease(
+    pool: &SqlitePool,
+    holder: &str,
+    ttl_secs: i64,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO leader_lease (id, holder, expires_at)
+         VALUES (1, ?, CAST(strftime('%s', 'now') AS INTEGER) + ?)
+         ON CONFLICT(id) DO UPDATE SET
+           holder = excluded.holder,
+           expires_at = excluded.expires_at
+         WHERE leader_lease.holder = excluded.holder
+            OR leader_lease.expires_at <= CAST(strftime('%s', 'now') AS INTEGER)",
+    )
+    .bind(holder)
+    .bind(ttl_secs)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Give up the leader lease if `holder` has it, so a standby can take over straight away.
+pub async fn release_leader_lease(pool: &SqlitePool, holder: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM leader_lease WHERE holder = ?")
+        .bind(holder)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn insert_shadow_action(
+    pool: &SqlitePool,
+    service: &str,
+    action: &str,
+    detail: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO shadow_actions (service, action, detail) VALUES (?, ?, ?)")
+        .bind(service)
+        .bind(action)
+        .bind(detail)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Recorded shadow actions, oldest first.
+pub async fn get_shadow_actions(pool: &SqlitePool) -> Result<Vec<ShadowAction>, sqlx::Error> {
+    sqlx::query_as::<_, ShadowAction>(
+        "SELECT id, service, action, detail, created_at FROM shadow_actions ORDER BY id",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Record a bot action on a synced thread. `actor_discord_user_id` is the Discord user who
+/// triggered it, if any; `detail` is a short action-specific summary.
+pub async fn insert_audit_entry(
+    pool: &SqlitePool,
+    discord_thread_id: &str,
+    linear_identifier: &str,
+    action: &str,
+    actor_discord_user_id: Option<&str>,
+    detail: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO audit_log
+           (discord_thread_id, linear_identifier, action, actor_discord_user_id, detail)
+         VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(discord_thread_id)
+    .bind(linear_identifier)
+    .bind(action)
+    .bind(actor_discord_user_id)
+    .bind(detail)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Audit entries recorded with `action`, across all threads, or only the tracked threads in
+/// `guild_id` if given.
+pub async fn count_audit_actions(
+    pool: &SqlitePool,
+    action: &str,
+    guild_id: Option<&str>,
+) -> Result<i64, sqlx::Error> {
+    let (count,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM audit_log
+         WHERE action = ?1
+           AND (?2 IS NULL OR discord_thread_id IN
+                (SELECT discord_thread_id FROM sync_mappings WHERE guild_id = ?2))",
+    )
+    .bind(action)
+    .bind(guild_id)
+    .fetch_one(pool)
+    .await?;
+    Ok(count)
+}
+
+/// The thread's most recent audit entries, newest first.
+pub async fn get_audit_entries(
+    pool: &SqlitePool,
+    discord_thread_id: &str,
+    limit: i64,
+) -> Result<Vec<AuditEntry>, sqlx::Error> {
+    sqlx::query_as::<_, AuditEntry>(
+        "SELECT id, discord_thread_id, linear_identifier, action, actor_discord_user_id, detail,
+                created_at
+         FROM audit_log WHERE discord_thread_id = ? ORDER BY id DESC LIMIT ?",
+    )
+    .bind(discord_thread_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+/// Insert an exported mapping with its status and comment sync state. Returns false, writing
+/// nothing, if its thread or issue is already mapped here.
+pub async fn import_mapping(
+    pool: &SqlitePool,
+    mapping: &ExportedMapping,
+) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let inserted = sqlx::query(
+        "INSERT INTO sync_mappings
+           (discord_thread_id, linear_issue_id, linear_identifier, channel_type, created_at,
+            guild_id)
+         VALUES (?, ?, ?, ?, ?, ?)
+         ON CONFLICT DO NOTHING",
+    )
+    .bind(&mapping.discord_thread_id)
+    .bind(&mapping.linear_issue_id)
+    .bind(&mapping.linear_identifier)
+    .bind(&mapping.channel_type)
+    .bind(&mapping.created_at)
+    .bind(&mapping.guild_id)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected()
+        > 0;
+    if !inserted {
+        return Ok(false);
+    }
+
+    if let Some(status) = &mapping.status {
+        sqlx::query(
+            "INSERT INTO linear_status_cache (linear_issue_id, status_name) VALUES (?, ?)
+             ON CONFLICT(linear_issue_id) DO UPDATE SET status_name = excluded.status_name",
+        )
+        .bind(&mapping.linear_issue_id)
+        .bind(status)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    if let Some(cursor) = &mapping.comment_cursor {
+        sqlx::query(
+            "INSERT INTO comment_cursors (linear_issue_id, last_created_at) VALUES (?, ?)
+             ON CONFLICT(linear_issue_id) DO UPDATE SET last_created_at = excluded.last_created_at",
+        )
+        .bind(&mapping.linear_issue_id)
+        .bind(cursor)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    for comment in &mapping.synced_comments {
+        sqlx::query(
+            "INSERT INTO synced_comments (linear_comment_id, linear_issue_id, discord_message_id)
+             VALUES (?, ?, ?)
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(&comment.linear_comment_id)
+        .bind(&mapping.linear_issue_id)
+        .bind(&comment.discord_message_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    for comment in &mapping.relayed_comments {
+        sqlx::query(
+            "INSERT INTO relayed_comments
+               (linear_comment_id, linear_issue_id, discord_message_id, via_webhook,
+                linear_updated_at)
+             VALUES (?, ?, ?, ?, ?)
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(&comment.linear_comment_id)
+        .bind(&mapping.linear_issue_id)
+        .bind(&comment.discord_message_id)
+        .bind(comment.via_webhook)
+        .bind(&comment.linear_updated_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(true)
+}
@@ -0,0 +1,207 @@
+//! Moderator approval before an issue is created.
+//!
+//! For channels with an `approval_role_id`, new threads get a "pending triage" message with an
+//! "Approve" button instead of an issue. A member with the role approves the thread with the
+//! button or a ✅ reaction on the post or the message; the thread is then synced as usual
+//! (duplicate suggestions and the intake form still apply). Waiting threads are persisted in
+//! `thread_approvals`, so approval works across restarts. There is no timeout.
+
+use serenity::all::{
+    ButtonStyle, ChannelId, ComponentInteraction, Context, CreateActionRow, CreateButton,
+    CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage, EditMessage,
+    GuildChannel, Member, MessageId, Reaction, ReactionType, RoleId, UserId,
+};
+use sqlx::SqlitePool;
+use tracing::{error, info, warn};
+
+use crate::config::ChannelConfig;
+use crate::db;
+use crate::discord::handler::AppState;
+use crate::discord::port::DiscordPort;
+use crate::error::AppError;
+use crate::linear::api::LinearApi;
+use crate::sync::discord_to_linear::sync_discord_to_linear;
+
+pub const CUSTOM_ID_PREFIX: &str = "approval:";
+const APPROVE: &str = "approval:approve:";
+const APPROVE_EMOJI: &str = "✅";
+
+/// Post the pending triage message in a new thread and record it as waiting.
+pub async fn prompt(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    role_id: u64,
+    thread: &GuildChannel,
+) -> Result<(), AppError> {
+    let button = CreateButton::new(format!("{APPROVE}{}", thread.id))
+        .label("Approve")
+        .style(ButtonStyle::Success);
+    let message = CreateMessage::new()
+        .content(format!(
+            "Pending triage: this post will be filed in Linear once a <@&{role_id}> member \
+             approves it with the button below or a {APPROVE_EMOJI} reaction."
+        ))
+        .components(vec![CreateActionRow::Buttons(vec![button])]);
+
+    let sent = discord.send_message(thread.id, message).await?;
+    db::insert_thread_approval(pool, &thread.id.to_string(), &sent.id.to_string()).await?;
+
+    info!(thread_id = %thread.id, "Holding thread for approval");
+    Ok(())
+}
+
+/// Approve a waiting thread and sync it. Returns whether it was still waiting; a second
+/// approval is ignored.
+pub async fn approve(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    channel_config: &ChannelConfig,
+    linear: &impl LinearApi,
+    thread: &GuildChannel,
+    approver: UserId,
+) -> Result<bool, AppError> {
+    let thread_id = thread.id.to_string();
+    let Some(approval) = db::get_thread_approval(pool, &thread_id).await? else {
+        return Ok(false);
+    };
+    if !db::approve_thread(pool, &thread_id, &approver.to_string()).await? {
+        return Ok(false);
+    }
+    info!(thread_id, approver = %approver, "Thread approved");
+
+    // Replace the button so the message shows who approved the thread.
+    if let Ok(prompt_id) = approval.prompt_message_id.parse::<u64>() {
+        let edit = EditMessage::new()
+            .content(format!("Approved by <@{approver}>."))
+            .components(Vec::new());
+        if let Err(e) = discord
+            .edit_message(thread.id, MessageId::new(prompt_id), edit)
+            .await
+        {
+            warn!(thread_id, error = %e, "Failed to update approval message");
+        }
+    }
+
+    sync_discord_to_linear(discord, pool, channel_config, linear, thread).await?;
+    Ok(true)
+}
+
+pub async fn handle_component(ctx: &Context, state: &AppState, component: &ComponentInteraction) {
+    let Some(thread_id) = component.data.custom_id.strip_prefix(APPROVE) else {
+        return;
+    };
+    let Ok(thread_id) = thread_id.parse::<u64>() else {
+        return;
+    };
+
+    let discord = state.discord(&ctx.http);
+    let thread = match discord.channel(ChannelId::new(thread_id)).await {
+        Ok(thread) => thread,
+        Err(e) => {
+            warn!(thread_id, error = %e, "Failed to fetch thread for approval");
+            return;
+        }
+    };
+    let config = state.config.current();
+    let Some(channel_config) = thread
+        .parent_id
+        .and_then(|p| config.channel_config(p.get()))
+    else {
+        return;
+    };
+    if !may_approve(channel_config, component.member.as_ref()) {
+        let reply = CreateInteractionResponseMessage::new()
+            .content("You don't have the approval role for this channel.")
+            .ephemeral(true);
+        if let Err(e) = component
+            .create_response(&ctx.http, CreateInteractionResponse::Message(reply))
+            .await
+        {
+            warn!(error = %e, "Failed to send approval reply");
+        }
+        return;
+    }
+
+    if let Err(e) = component
+        .create_response(&ctx.http, CreateInteractionResponse::Acknowledge)
+        .await
+    {
+        warn!(thread_id, error = %e, "Failed to acknowledge approval");
+    }
+    if let Err(e) = approve(
+        &discord,
+        &state.pool,
+        channel_config,
+        &state.linear_client,
+        &thread,
+        component.user.id,
+    )
+    .await
+    {
+        error!(thread_id, error = %e, "Failed to sync approved thread");
+    }
+}
+
+/// Approve a waiting thread when a member with the role reacts ✅ to the post or the pending
+/// triage message.
+pub async fn handle_reaction(ctx: &Context, state: &AppState, reaction: &Reaction) {
+    if !matches!(&reaction.emoji, ReactionType::Unicode(emoji) if emoji == APPROVE_EMOJI) {
+        return;
+    }
+    let thread_id = reaction.channel_id.to_string();
+    let approval = match db::get_thread_approval(&state.pool, &thread_id).await {
+        Ok(Some(approval)) if approval.approved_by_discord_user_id.is_none() => approval,
+        Ok(_) => return,
+        Err(e) => {
+            error!(thread_id, error = %e, "Failed to load thread approval");
+            return;
+        }
+    };
+    // A forum post's starter message shares the thread's ID.
+    let on_post = reaction.message_id.get() == reaction.channel_id.get();
+    if !on_post && reaction.message_id.to_string() != approval.prompt_message_id {
+        return;
+    }
+    let Some(approver) = reaction.user_id else {
+        return;
+    };
+
+    let discord = state.discord(&ctx.http);
+    let thread = match discord.channel(reaction.channel_id).await {
+        Ok(thread) => thread,
+        Err(e) => {
+            warn!(thread_id, error = %e, "Failed to fetch thread for approval");
+            return;
+        }
+    };
+    let config = state.config.current();
+    let Some(channel_config) = thread
+        .parent_id
+        .and_then(|p| config.channel_config(p.get()))
+    else {
+        return;
+    };
+    if !may_approve(channel_config, reaction.member.as_ref()) {
+        return;
+    }
+
+    if let Err(e) = approve(
+        &discord,
+        &state.pool,
+        channel_config,
+        &state.linear_client,
+        &thread,
+        approver,
+    )
+    .await
+    {
+        error!(thread_id, error = %e, "Failed to sync approved thread");
+    }
+}
+
+fn may_approve(channel_config: &ChannelConfig, member: Option<&Member>) -> bool {
+    match (channel_config.approval_role_id, member) {
+        (Some(role), Some(member)) => member.roles.contains(&RoleId::new(role)),
+        _ => false,
+    }
+}
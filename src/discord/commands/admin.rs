@@ -0,0 +1,548 @@
+//! `/linear admin`: manage which forum channels are synced without editing `CHANNELS`.
+//!
+//! Channels are stored in `channel_configs` and take effect immediately. A stored channel replaces
+//! a `CHANNELS` entry for the same forum, and removing a `CHANNELS` channel stores a tombstone
+//! that hides it until it is added again.
+//!
+//! Linear API keys for teams in other workspaces can be stored too, encrypted (see
+//! `crate::credentials`). A stored key can only be used by channels in the server that added it.
+//!
+//! `prune` removes mappings whose Linear issue or Discord thread was deleted (see
+//! `crate::sync::drift`), so the poller stops failing on them.
+
+use serde_json::{json, Value};
+use serenity::all::{
+    ChannelType, CommandInteraction, CommandOptionType, Context, CreateCommandOption,
+    EditInteractionResponse, ResolvedOption,
+};
+use tracing::{error, info};
+
+use crate::config::ChannelConfig;
+use crate::db;
+use crate::discord::commands::setup::GUILD_CREDENTIAL_PREFIX;
+use crate::discord::commands::{channel_option, string_option, text_response, CommandError};
+use crate::discord::handler::AppState;
+use crate::error::AppError;
+use crate::linear::api::LinearApi;
+use crate::sync::backfill::run_backfill;
+use crate::sync::drift::prune_orphans;
+
+/// Pruned mappings listed in the reply; the rest are only counted.
+const MAX_PRUNED_LISTED: usize = 20;
+
+pub fn register() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::SubCommandGroup,
+        "admin",
+        "Manage the forum channels synced with Linear",
+    )
+    .add_sub_option(
+        CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "add-channel",
+            "Sync a forum channel with Linear, or change the team, label, or project it uses",
+        )
+        .add_sub_option(channel_argument())
+        .add_sub_option(
+            CreateCommandOption::new(CommandOptionType::String, "type", "Kind of posts")
+                .required(true)
+                .add_string_choice("Bug reports", "bug")
+                .add_string_choice("Feature requests", "feature"),
+        )
+        .add_sub_option(
+            CreateCommandOption::new(CommandOptionType::String, "team", "Linear team ID")
+                .required(true),
+        )
+        .add_sub_option(
+            CreateCommandOption::new(CommandOptionType::String, "label", "Linear label ID")
+                .required(true),
+        )
+        .add_sub_option(
+            CreateCommandOption::new(CommandOptionType::String, "project", "Linear project ID")
+                .required(true),
+        )
+        .add_sub_option(CreateCommandOption::new(
+            CommandOptionType::String,
+            "credential",
+            "Linear API key to use (see add-credential), if the team is in another workspace",
+        )),
+    )
+    .add_sub_option(
+        CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "remove-channel",
+            "Stop creating Linear issues for a forum channel's posts",
+        )
+        .add_sub_option(channel_argument()),
+    )
+    .add_sub_option(CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        "list-channels",
+        "List the forum channels in this server that are synced with Linear",
+    ))
+    .add_sub_option(
+        CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "add-credential",
+            "Store a Linear API key for channels whose team is in another Linear workspace",
+        )
+        .add_sub_option(credential_argument())
+        .add_sub_option(
+            CreateCommandOption::new(CommandOptionType::String, "api-key", "Linear API key")
+                .required(true),
+        ),
+    )
+    .add_sub_option(
+        CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "remove-credential",
+            "Delete a stored Linear API key that no channel uses",
+        )
+        .add_sub_option(credential_argument()),
+    )
+    .add_sub_option(CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        "prune",
+        "Unlink threads whose Linear issue or Discord thread was deleted",
+    ))
+}
+
+fn credential_argument() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::String,
+        "name",
+        "Name channels refer to the key by",
+    )
+    .required(true)
+}
+
+fn channel_argument() -> CreateCommandOption {
+    CreateCommandOption::new(CommandOptionType::Channel, "channel", "Forum channel")
+        .required(true)
+        .channel_types(vec![ChannelType::Forum])
+}
+
+pub async fn add_channel(
+    ctx: &Context,
+    state: &AppState,
+    cmd: &CommandInteraction,
+    options: &[ResolvedOption<'_>],
+) -> Result<EditInteractionResponse, CommandError> {
+    let guild_id = cmd
+        .guild_id
+        .ok_or_else(|| CommandError::User("Use this command in a server.".into()))?;
+    let channel = channel_option(options, "channel")
+        .ok_or_else(|| CommandError::User("Missing channel.".into()))?;
+    if channel.kind != ChannelType::Forum {
+        return Err(CommandError::User(format!(
+            "<#{}> isn't a forum channel.",
+            channel.id
+        )));
+    }
+    let argument = |name: &str| {
+        string_option(options, name)
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .ok_or_else(|| CommandError::User(format!("Missing {name}.")))
+    };
+    let channel_type = argument("type")?;
+    let team_id = argument("team")?;
+    let label_id = argument("label")?;
+    let project_id = argument("project")?;
+    let credential = string_option(options, "credential")
+        .map(str::trim)
+        .filter(|v| !v.is_empty());
+    if let Some(name) = credential {
+        if !credential_available(state, guild_id.get(), name).await? {
+            return Err(CommandError::User(format!(
+                "There's no Linear API key named `{name}` for this server."
+            )));
+        }
+    } else if state.config.current().multi_tenant && !state.config.in_env_guild(guild_id.get()) {
+        // Other servers' channels mustn't file into the operator's workspace.
+        return Err(CommandError::User(
+            "Connect this server's Linear workspace with `/linear setup` first.".into(),
+        ));
+    }
+    let linear = state
+        .linear_client
+        .workspace(credential)
+        .ok_or_else(|| CommandError::User("That Linear API key isn't loaded.".into()))?;
+
+    // Catch mistyped IDs now rather than on the first post.
+    let team = linear_name(linear.team_name(team_id).await, "team", team_id)?;
+    let label = linear_name(linear.label_name(label_id).await, "label", label_id)?;
+    let project = linear_name(linear.project_name(project_id).await, "project", project_id)?;
+
+    let mut stored = json!({
+        "discord_channel_id": channel.id.get(),
+        "guild_id": guild_id.get(),
+        "channel_type": channel_type,
+        "linear_team_id": team_id,
+        "linear_label_id": label_id,
+        "linear_project_id": project_id,
+    });
+    if let Some(credential) = credential {
+        stored["linear_credential"] = credential.into();
+    }
+    save_channel(ctx, state, cmd, stored).await?;
+
+    Ok(text_response(format!(
+        "<#{}> now files {channel_type} posts in **{team}** with label **{label}** \
+         in project **{project}**. Existing posts are being backfilled.",
+        channel.id
+    )))
+}
+
+pub async fn remove_channel(
+    _ctx: &Context,
+    state: &AppState,
+    cmd: &CommandInteraction,
+    options: &[ResolvedOption<'_>],
+) -> Result<EditInteractionResponse, CommandError> {
+    let guild_id = cmd
+        .guild_id
+        .ok_or_else(|| CommandError::User("Use this command in a server.".into()))?;
+    let channel = channel_option(options, "channel")
+        .ok_or_else(|| CommandError::User("Missing channel.".into()))?;
+    let channel_id = channel.id.get();
+    if state.config.current().channel_config(channel_id).is_none() {
+        return Err(CommandError::User(format!(
+            "<#{channel_id}> isn't synced with Linear."
+        )));
+    }
+
+    let key = channel_id.to_string();
+    if state.config.in_env(channel_id) {
+        db::upsert_channel_config(
+            &state.pool,
+            &key,
+            &guild_id.to_string(),
+            None,
+            &cmd.user.id.to_string(),
+        )
+        .await?;
+    } else {
+        db::delete_channel_config(&state.pool, &key).await?;
+    }
+    state.config.load_stored_channels(&state.pool).await?;
+
+    info!(channel_id, user = %cmd.user.id, "Channel removed with /linear admin");
+
+    Ok(text_response(format!(
+        "<#{channel_id}> is no longer synced with Linear. New posts won't create issues."
+    )))
+}
+
+pub async fn list_channels(
+    _ctx: &Context,
+    state: &AppState,
+    cmd: &CommandInteraction,
+    _options: &[ResolvedOption<'_>],
+) -> Result<EditInteractionResponse, CommandError> {
+    let guild_id = cmd
+        .guild_id
+        .ok_or_else(|| CommandError::User("Use this command in a server.".into()))?;
+    let stored: Vec<db::StoredChannelConfig> = db::get_channel_configs(&state.pool)
+        .await?
+        .into_iter()
+        .filter(|row| row.guild_id == guild_id.to_string())
+        .collect();
+
+    let config = state.config.current();
+    let mut lines: Vec<String> = config
+        .channels
+        .iter()
+        .filter(|c| c.guild_id == guild_id.get())
+        .map(|c| {
+            let key = c.discord_channel_id.to_string();
+            let source = if stored.iter().any(|row| row.discord_channel_id == key) {
+                "added with /linear admin"
+            } else {
+                "from CHANNELS"
+            };
+            let workspace = c
+                .linear_credential
+                .as_ref()
+                .map(|name| format!(", key `{name}`"))
+                .unwrap_or_default();
+            format!(
+                "<#{}> ({}): team `{}`, label `{}`, project `{}`{workspace}, {source}",
+                c.discord_channel_id,
+                c.channel_type,
+                c.linear_team_id,
+                c.linear_label_id,
+                c.linear_project_id
+            )
+        })
+        .collect();
+    lines.extend(
+        stored
+            .iter()
+            .filter(|row| row.config.is_none())
+            .map(|row| format!("<#{}>: removed from CHANNELS", row.discord_channel_id)),
+    );
+
+    if lines.is_empty() {
+        return Err(CommandError::User(
+            "No channels in this server are synced with Linear.".into(),
+        ));
+    }
+    let credentials: Vec<String> = db::get_linear_credentials(&state.pool)
+        .await?
+        .into_iter()
+        .filter(|row| row.guild_id == guild_id.to_string())
+        .map(|row| format!("`{}`", row.name))
+        .collect();
+    if !credentials.is_empty() {
+        lines.push(format!(
+            "Stored Linear API keys: {}",
+            credentials.join(", ")
+        ));
+    }
+    Ok(text_response(lines.join("\n")))
+}
+
+pub async fn add_credential(
+    _ctx: &Context,
+    state: &AppState,
+    cmd: &CommandInteraction,
+    options: &[ResolvedOption<'_>],
+) -> Result<EditInteractionResponse, CommandError> {
+    let guild_id = cmd
+        .guild_id
+        .ok_or_else(|| CommandError::User("Use this command in a server.".into()))?;
+    let keys = state.credential_keys.as_ref().ok_or_else(|| {
+        CommandError::User("Storing Linear API keys needs CREDENTIAL_KEY to be set.".into())
+    })?;
+    let argument = |name: &str| {
+        string_option(options, name)
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .ok_or_else(|| CommandError::User(format!("Missing {name}.")))
+    };
+    let name = argument("name")?;
+    let api_key = argument("api-key")?;
+    if state.config.current().linear_api_keys.contains_key(name) {
+        return Err(CommandError::User(format!(
+            "`{name}` is set in LINEAR_API_KEYS; pick another name."
+        )));
+    }
+    if name.starts_with(GUILD_CREDENTIAL_PREFIX) {
+        return Err(CommandError::User(format!(
+            "Names starting with `{GUILD_CREDENTIAL_PREFIX}` are reserved for `/linear setup`; \
+             pick another name."
+        )));
+    }
+    let existing = db::get_linear_credentials(&state.pool).await?;
+    if existing
+        .iter()
+        .any(|row| row.name == name && row.guild_id != guild_id.to_string())
+    {
+        return Err(CommandError::User(format!(
+            "Another server already has a Linear API key named `{name}`."
+        )));
+    }
+
+    // Only store keys that work.
+    let client = state.linear_client.client_for_key(api_key);
+    let viewer = match client.viewer().await {
+        Ok(viewer) => viewer,
+        Err(AppError::LinearApi(e)) => {
+            return Err(CommandError::User(format!(
+                "Linear rejected the API key: {e}"
+            )))
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let stored = db::upsert_linear_credential(
+        &state.pool,
+        name,
+        &guild_id.to_string(),
+        &keys.seal(name, api_key),
+        &cmd.user.id.to_string(),
+    )
+    .await?;
+    if !stored {
+        return Err(CommandError::User(format!(
+            "Another server already has a Linear API key named `{name}`."
+        )));
+    }
+    state.linear_client.set_workspace(name, Some(client));
+    state.config.load_stored_channels(&state.pool).await?;
+
+    info!(name, user = %cmd.user.id, "Linear API key stored with /linear admin");
+
+    Ok(text_response(format!(
+        "Stored Linear API key `{name}` for {}. Use it with \
+         `/linear admin add-channel credential:{name}`.",
+        viewer.display_name
+    )))
+}
+
+pub async fn remove_credential(
+    _ctx: &Context,
+    state: &AppState,
+    cmd: &CommandInteraction,
+    options: &[ResolvedOption<'_>],
+) -> Result<EditInteractionResponse, CommandError> {
+    let guild_id = cmd
+        .guild_id
+        .ok_or_else(|| CommandError::User("Use this command in a server.".into()))?;
+    let name = string_option(options, "name")
+        .map(str::trim)
+        .ok_or_else(|| CommandError::User("Missing name.".into()))?;
+    let stored = db::get_linear_credentials(&state.pool)
+        .await?
+        .into_iter()
+        .any(|row| row.name == name && row.guild_id == guild_id.to_string());
+    if !stored {
+        return Err(CommandError::User(format!(
+            "There's no stored Linear API key named `{name}` for this server."
+        )));
+    }
+    let users: Vec<String> = state
+        .config
+        .current()
+        .channels
+        .iter()
+        .filter(|c| c.linear_credential.as_deref() == Some(name))
+        .map(|c| format!("<#{}>", c.discord_channel_id))
+        .collect();
+    if !users.is_empty() {
+        return Err(CommandError::User(format!(
+            "`{name}` is still used by {}. Remove those channels first.",
+            users.join(", ")
+        )));
+    }
+
+    db::delete_linear_credential(&state.pool, name).await?;
+    state.linear_client.set_workspace(name, None);
+    state.config.load_stored_channels(&state.pool).await?;
+
+    info!(name, user = %cmd.user.id, "Linear API key removed with /linear admin");
+
+    Ok(text_response(format!("Deleted Linear API key `{name}`.")))
+}
+
+pub async fn prune(
+    ctx: &Context,
+    state: &AppState,
+    cmd: &CommandInteraction,
+    _options: &[ResolvedOption<'_>],
+) -> Result<EditInteractionResponse, CommandError> {
+    let discord = state.discord(&ctx.http);
+    let actor = cmd.user.id.to_string();
+    let guild_id = cmd
+        .guild_id
+        .ok_or_else(|| CommandError::User("Use this command in a server.".into()))?
+        .to_string();
+    // Other servers' mappings aren't this server's admins' to remove.
+    let scope = state
+        .config
+        .current()
+        .multi_tenant
+        .then_some(guild_id.as_str());
+    let pruned = prune_orphans(
+        &discord,
+        &state.pool,
+        &state.linear_client,
+        scope,
+        Some(&actor),
+    )
+    .await?;
+
+    info!(pruned = pruned.len(), user = %cmd.user.id, "Orphaned mappings pruned");
+
+    if pruned.is_empty() {
+        return Ok(text_response(
+            "Every linked thread and issue still exists; nothing to prune.",
+        ));
+    }
+    let mut lines = vec![format!("Unlinked {} orphaned threads:", pruned.len())];
+    lines.extend(
+        pruned
+            .iter()
+            .take(MAX_PRUNED_LISTED)
+            .map(|orphan| format!("- {orphan}")),
+    );
+    if pruned.len() > MAX_PRUNED_LISTED {
+        lines.push(format!("…and {} more", pruned.len() - MAX_PRUNED_LISTED));
+    }
+    Ok(text_response(lines.join("\n")))
+}
+
+/// Whether a channel in the guild may use the credential: the guild stored it, or it's in
+/// `LINEAR_API_KEYS`. In multi-tenant mode those keys are the operator's, so only guilds with
+/// `CHANNELS` entries get them.
+async fn credential_available(
+    state: &AppState,
+    guild_id: u64,
+    name: &str,
+) -> Result<bool, CommandError> {
+    let config = state.config.current();
+    if config.linear_api_keys.contains_key(name)
+        && (!config.multi_tenant || state.config.in_env_guild(guild_id))
+    {
+        return Ok(true);
+    }
+    Ok(db::get_linear_credentials(&state.pool)
+        .await?
+        .iter()
+        .any(|row| row.name == name && row.guild_id == guild_id.to_string()))
+}
+
+/// Store a channel's `CHANNELS`-style entry, apply it, and backfill the forum's existing posts.
+pub(super) async fn save_channel(
+    ctx: &Context,
+    state: &AppState,
+    cmd: &CommandInteraction,
+    stored: Value,
+) -> Result<(), CommandError> {
+    let channel: ChannelConfig = serde_json::from_value(stored.clone()).map_err(AppError::from)?;
+    db::upsert_channel_config(
+        &state.pool,
+        &channel.discord_channel_id.to_string(),
+        &channel.guild_id.to_string(),
+        Some(&stored.to_string()),
+        &cmd.user.id.to_string(),
+    )
+    .await?;
+    let config = state.config.load_stored_channels(&state.pool).await?;
+
+    info!(
+        channel_id = channel.discord_channel_id,
+        team_id = %channel.linear_team_id,
+        user = %cmd.user.id,
+        "Channel added from Discord"
+    );
+
+    // Channels that were backfilled before are skipped.
+    tokio::spawn({
+        let discord = state.discord(&ctx.http);
+        let pool = state.pool.clone();
+        let linear = state.linear_client.clone();
+        async move {
+            if let Err(e) = run_backfill(&discord, &pool, &config, &linear).await {
+                error!(error = %e, "Backfill of added channel failed");
+            }
+        }
+    });
+    Ok(())
+}
+
+/// The looked-up name, or a user error if Linear doesn't know the ID.
+fn linear_name(
+    result: Result<String, AppError>,
+    what: &str,
+    id: &str,
+) -> Result<String, CommandError> {
+    match result {
+        Ok(name) => Ok(name),
+        Err(AppError::LinearApi(e)) => Err(CommandError::User(format!(
+            "Couldn't find Linear {what} `{id}`: {e}"
+        ))),
+        Err(e) => Err(e.into()),
+    }
+}
@@ -0,0 +1,132 @@
+use serenity::all::{
+    AutocompleteChoice, CommandInteraction, CommandOptionType, Context, CreateCommandOption,
+    EditInteractionResponse, ResolvedOption,
+};
+use tracing::info;
+
+use crate::db;
+use crate::discord::commands::{
+    string_option, text_response, thread_team_id, CommandError, MAX_CHOICES,
+};
+use crate::discord::handler::AppState;
+use crate::linear::api::LinearApi;
+use crate::linear::client::LinearUser;
+
+/// Autocomplete value meaning "clear the assignee".
+const UNASSIGN: &str = "none";
+
+pub fn register() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        "assign",
+        "Assign the linked Linear issue",
+    )
+    .add_sub_option(
+        CreateCommandOption::new(CommandOptionType::String, "user", "Linear team member")
+            .required(true)
+            .set_autocomplete(true),
+    )
+}
+
+pub async fn run(
+    _ctx: &Context,
+    state: &AppState,
+    cmd: &CommandInteraction,
+    options: &[ResolvedOption<'_>],
+) -> Result<EditInteractionResponse, CommandError> {
+    let input = string_option(options, "user")
+        .map(str::trim)
+        .ok_or_else(|| CommandError::User("Pick a team member to assign.".into()))?;
+
+    let mapping = db::get_mapping_by_discord_thread(&state.pool, &cmd.channel_id.to_string())
+        .await?
+        .ok_or_else(|| CommandError::User("This thread isn't linked to a Linear issue.".into()))?;
+
+    let (assignee_id, assignee_name) = if input.eq_ignore_ascii_case(UNASSIGN) {
+        (None, None)
+    } else {
+        let team_id = thread_team_id(state, cmd)?;
+        let metadata = state.metadata.team(&state.linear_client, &team_id).await?;
+        let member = resolve_member(&metadata.members, input).ok_or_else(|| {
+            CommandError::User(format!("No Linear team member matches \"{input}\"."))
+        })?;
+        (Some(member.id.clone()), Some(member.display_name.clone()))
+    };
+
+    state
+        .linear_client
+        .update_issue_assignee(&mapping.linear_issue_id, assignee_id.as_deref())
+        .await?;
+    // The reply below announces the change; keep the poller from announcing it again.
+    db::upsert_cached_assignee(
+        &state.pool,
+        &mapping.linear_issue_id,
+        assignee_id.as_deref(),
+    )
+    .await?;
+    db::insert_audit_entry(
+        &state.pool,
+        &mapping.discord_thread_id,
+        &mapping.linear_identifier,
+        "assignee_changed",
+        Some(&cmd.user.id.to_string()),
+        assignee_name.as_deref().unwrap_or_default(),
+    )
+    .await?;
+
+    info!(
+        identifier = %mapping.linear_identifier,
+        assignee = ?assignee_name,
+        user = %cmd.user.id,
+        "Updated Linear assignee from Discord"
+    );
+
+    Ok(text_response(match assignee_name {
+        Some(name) => format!(
+            "**{}** assigned to **{name}** by <@{}>",
+            mapping.linear_identifier, cmd.user.id
+        ),
+        None => format!(
+            "**{}** unassigned by <@{}>",
+            mapping.linear_identifier, cmd.user.id
+        ),
+    }))
+}
+
+pub async fn autocomplete(
+    state: &AppState,
+    cmd: &CommandInteraction,
+    partial: &str,
+) -> Result<Vec<AutocompleteChoice>, CommandError> {
+    let team_id = thread_team_id(state, cmd)?;
+    let metadata = state.metadata.team(&state.linear_client, &team_id).await?;
+
+    let needle = partial.to_lowercase();
+    let mut choices = vec![AutocompleteChoice::new("Unassigned", UNASSIGN)];
+    choices.extend(
+        metadata
+            .members
+            .iter()
+            .filter(|m| {
+                m.display_name.to_lowercase().contains(&needle)
+                    || m.name.to_lowercase().contains(&needle)
+                    || m.email.to_lowercase().contains(&needle)
+            })
+            .take(MAX_CHOICES - 1)
+            .map(|m| {
+                AutocompleteChoice::new(format!("{} ({})", m.display_name, m.name), m.id.clone())
+            }),
+    );
+    Ok(choices)
+}
+
+/// Match an autocomplete-selected user ID, or free text against name/display name/email.
+fn resolve_member<'a>(members: &'a [LinearUser], input: &str) -> Option<&'a LinearUser> {
+    members.iter().find(|m| m.id == input).or_else(|| {
+        members.iter().find(|m| {
+            m.display_name.eq_ignore_ascii_case(input)
+                || m.name.eq_ignore_ascii_case(input)
+                || m.email.eq_ignore_ascii_case(input)
+        })
+    })
+}
@@ -0,0 +1,89 @@
+use chrono::NaiveDateTime;
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommandOption, CreateEmbed,
+    EditInteractionResponse, ResolvedOption,
+};
+
+use crate::db::{self, AuditEntry};
+use crate::discord::commands::CommandError;
+use crate::discord::handler::AppState;
+
+const MAX_ENTRIES: i64 = 15;
+
+/// Longest `detail` shown per entry; issue titles and comment authors are cut to this.
+const MAX_DETAIL_CHARS: usize = 80;
+
+pub fn register() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        "audit",
+        "Show what the bot has recently done in this thread",
+    )
+}
+
+pub async fn run(
+    _ctx: &Context,
+    state: &AppState,
+    cmd: &CommandInteraction,
+    _options: &[ResolvedOption<'_>],
+) -> Result<EditInteractionResponse, CommandError> {
+    // Entries outlive the mapping, so an unlinked thread still shows its history.
+    let entries =
+        db::get_audit_entries(&state.pool, &cmd.channel_id.to_string(), MAX_ENTRIES).await?;
+    if entries.is_empty() {
+        return Err(CommandError::User(
+            "The bot hasn't recorded any actions in this thread.".into(),
+        ));
+    }
+
+    let description = entries
+        .iter()
+        .map(audit_line)
+        .collect::<Vec<_>>()
+        .join("\n");
+    let embed = CreateEmbed::new()
+        .title("Recent bot actions in this thread")
+        .description(description);
+
+    Ok(EditInteractionResponse::new().embed(embed))
+}
+
+/// One entry as "<time> **ENG-12** <what happened> by <who>: <detail>".
+fn audit_line(entry: &AuditEntry) -> String {
+    let when = NaiveDateTime::parse_from_str(&entry.created_at, "%Y-%m-%d %H:%M:%S")
+        .map(|t| format!("<t:{}:R>", t.and_utc().timestamp()))
+        .unwrap_or_else(|_| entry.created_at.clone());
+    let who = match &entry.actor_discord_user_id {
+        Some(user_id) => format!("<@{user_id}>"),
+        None => "the bot".to_string(),
+    };
+    let mut line = format!(
+        "{when} **{}** {} by {who}",
+        entry.linear_identifier,
+        describe(&entry.action)
+    );
+    if !entry.detail.is_empty() {
+        let detail: String = entry.detail.chars().take(MAX_DETAIL_CHARS).collect();
+        line.push_str(&format!(": {detail}"));
+    }
+    line
+}
+
+/// Past-tense description of an `audit_log.action`.
+fn describe(action: &str) -> &str {
+    match action {
+        "issue_created" => "filed",
+        "issue_linked" => "linked",
+        "thread_opened" => "thread opened from Linear",
+        "comment_posted" => "comment posted to Linear",
+        "comment_relayed" => "comment relayed from Linear",
+        "status_announced" => "status announced",
+        "assignee_changed" => "assignee changed",
+        "priority_changed" => "priority changed",
+        "label_added" => "label added",
+        "project_changed" => "project changed",
+        "thread_archived" => "thread archived",
+        "mapping_removed" => "unlinked",
+        other => other,
+    }
+}
@@ -0,0 +1,191 @@
+//! `/linear backfill`: file issues for a synced forum's existing posts now, rather than waiting
+//! for a restart, with the reply kept up to date as posts are processed.
+
+use std::time::Duration;
+
+use serenity::all::{
+    ChannelId, ChannelType, CommandInteraction, CommandOptionType, Context, CreateCommandOption,
+    EditInteractionResponse, Http, ResolvedOption,
+};
+use tokio::sync::watch;
+use tracing::{error, info, warn};
+
+use crate::discord::commands::{channel_option, text_response, CommandError};
+use crate::discord::handler::AppState;
+use crate::sync::backfill::{rerun_channel_backfill, BackfillProgress};
+
+/// How often the reply is updated while the backfill runs.
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(3);
+
+pub fn register() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        "backfill",
+        "File Linear issues for a synced forum's existing posts",
+    )
+    .add_sub_option(
+        CreateCommandOption::new(
+            CommandOptionType::Channel,
+            "channel",
+            "Synced forum channel",
+        )
+        .required(true)
+        .channel_types(vec![ChannelType::Forum]),
+    )
+}
+
+pub async fn run(
+    ctx: &Context,
+    state: &AppState,
+    cmd: &CommandInteraction,
+    options: &[ResolvedOption<'_>],
+) -> Result<EditInteractionResponse, CommandError> {
+    let guild_id = cmd
+        .guild_id
+        .ok_or_else(|| CommandError::User("Use this command in a server.".into()))?;
+    let channel = channel_option(options, "channel")
+        .ok_or_else(|| CommandError::User("Missing channel.".into()))?;
+    let config = state.config.current();
+    let synced_here = config
+        .channel_config(channel.id.get())
+        .is_some_and(|c| c.guild_id == guild_id.get());
+    if !synced_here {
+        return Err(CommandError::User(format!(
+            "<#{}> isn't synced with Linear.",
+            channel.id
+        )));
+    }
+    let running = state.backfills.start(channel.id.get()).ok_or_else(|| {
+        CommandError::User(format!("<#{}> is already being backfilled.", channel.id))
+    })?;
+
+    info!(channel_id = %channel.id, user = %cmd.user.id, "Backfill started from Discord");
+
+    // Runs past the command, editing the reply as it goes; Discord allows edits for 15 minutes.
+    tokio::spawn({
+        let http = ctx.http.clone();
+        let discord = state.discord(&ctx.http);
+        let pool = state.pool.clone();
+        let linear = state.linear_client.clone();
+        let shutdown = state.shutdown.clone();
+        let cmd = cmd.clone();
+        let channel_id = channel.id;
+        async move {
+            let _running = running;
+            let (progress, mut updates) = watch::channel(BackfillProgress::default());
+            let backfill = rerun_channel_backfill(
+                &discord,
+                &pool,
+                &config,
+                &linear,
+                channel_id.get(),
+                &progress,
+                &shutdown,
+            );
+            tokio::pin!(backfill);
+
+            let mut reply = Reply::new(&http, &cmd);
+            let start = tokio::time::Instant::now() + PROGRESS_INTERVAL;
+            let mut ticks = tokio::time::interval_at(start, PROGRESS_INTERVAL);
+            let result = loop {
+                tokio::select! {
+                    result = &mut backfill => break result,
+                    _ = ticks.tick() => {
+                        if updates.has_changed().unwrap_or(false) {
+                            let counts = *updates.borrow_and_update();
+                            reply.update(progress_message(channel_id, &counts)).await;
+                        }
+                    }
+                }
+            };
+
+            let message = match result {
+                Ok(counts) if counts.processed < counts.total => format!(
+                    "Backfill of <#{channel_id}> was interrupted by a bot restart after {}/{} \
+                     posts; it picks up where it left off when the bot starts again.",
+                    counts.processed, counts.total
+                ),
+                Ok(counts) => finished_message(channel_id, &counts),
+                Err(e) => {
+                    error!(channel_id = %channel_id, error = %e, "Backfill from Discord failed");
+                    format!("Backfill of <#{channel_id}> failed; check the bot logs.")
+                }
+            };
+            reply.update(message).await;
+        }
+    });
+
+    Ok(text_response(format!(
+        "Backfilling <#{}>: listing posts…",
+        channel.id
+    )))
+}
+
+fn progress_message(channel_id: ChannelId, counts: &BackfillProgress) -> String {
+    format!(
+        "Backfilling <#{channel_id}>: {}/{} posts checked, {} issue{} filed, {} failed.",
+        counts.processed,
+        counts.total,
+        counts.synced,
+        plural(counts.synced),
+        counts.failed
+    )
+}
+
+fn finished_message(channel_id: ChannelId, counts: &BackfillProgress) -> String {
+    let mut message = format!(
+        "Backfill of <#{channel_id}> finished: {} issue{} filed, {} post{} already linked.",
+        counts.synced,
+        plural(counts.synced),
+        counts.skipped,
+        plural(counts.skipped)
+    );
+    if counts.failed > 0 {
+        message.push_str(&format!(
+            " {} post{} failed and will be retried by the periodic thread reconcile.",
+            counts.failed,
+            plural(counts.failed)
+        ));
+    }
+    message
+}
+
+fn plural(count: usize) -> &'static str {
+    if count == 1 {
+        ""
+    } else {
+        "s"
+    }
+}
+
+/// The command's reply, edited until an edit fails (the interaction token expires after 15
+/// minutes); after that the backfill carries on and only logs.
+struct Reply<'a> {
+    http: &'a Http,
+    cmd: &'a CommandInteraction,
+    editable: bool,
+}
+
+impl<'a> Reply<'a> {
+    fn new(http: &'a Http, cmd: &'a CommandInteraction) -> Self {
+        Self {
+            http,
+            cmd,
+            editable: true,
+        }
+    }
+
+    async fn update(&mut self, content: String) {
+        if !self.editable {
+            return;
+        }
+        if let Err(e) = self
+            .cmd
+            .edit_response(self.http, text_response(content))
+            .await
+        {
+            warn!(error = %e, "Failed to update backfill progress, no longer reporting it");
+            self.editable = false;
+        }
+    }
+}
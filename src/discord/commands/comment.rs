@@ -0,0 +1,98 @@
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommandOption, EditInteractionResponse,
+    ResolvedOption,
+};
+use tracing::{info, warn};
+
+use crate::db;
+use crate::discord::commands::{string_option, text_response, thread_parent_id, CommandError};
+use crate::discord::handler::AppState;
+use crate::sync::discord_to_linear::{create_linear_comment, reply_author, reply_text};
+use crate::sync::linear_to_discord::bot_comment_text;
+
+pub fn register() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        "comment",
+        "Add a comment to the linked Linear issue",
+    )
+    .add_sub_option(
+        CreateCommandOption::new(CommandOptionType::String, "text", "Comment text")
+            .required(true)
+            .max_length(4000),
+    )
+}
+
+pub async fn run(
+    ctx: &Context,
+    state: &AppState,
+    cmd: &CommandInteraction,
+    options: &[ResolvedOption<'_>],
+) -> Result<EditInteractionResponse, CommandError> {
+    let text = string_option(options, "text")
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .ok_or_else(|| CommandError::User("Comment text can't be empty.".into()))?;
+
+    let mapping = db::get_mapping_by_discord_thread(&state.pool, &cmd.channel_id.to_string())
+        .await?
+        .ok_or_else(|| CommandError::User("This thread isn't linked to a Linear issue.".into()))?;
+
+    let author = cmd
+        .member
+        .as_ref()
+        .map(|m| m.display_name().to_string())
+        .unwrap_or_else(|| cmd.user.display_name().to_string());
+
+    let config = state.config.current();
+    let overrides = thread_parent_id(cmd)
+        .and_then(|parent| config.channel_config(parent))
+        .map_or(&config.message_templates, |c| &c.message_templates);
+    let body = reply_text(&state.pool, overrides, cmd.user.id, &author, text).await?;
+    let comment_author =
+        reply_author(&state.pool, &config, cmd.user.id, &author, cmd.user.face()).await?;
+
+    // Echo into the thread first so the comment can be recorded against the echo before it
+    // exists; the comment poller then never relays our own comment back.
+    let echo = bot_comment_text(overrides, &mapping.linear_identifier, &author, text);
+    let sent = cmd.channel_id.say(&ctx.http, echo).await?;
+    let comment_id = match create_linear_comment(
+        &state.pool,
+        &state.linear_client,
+        &mapping.linear_issue_id,
+        &body,
+        comment_author.as_ref(),
+        &sent.id.to_string(),
+    )
+    .await
+    {
+        Ok(id) => id,
+        Err(e) => {
+            if let Err(e) = sent.delete(&ctx.http).await {
+                warn!(error = %e, "Failed to remove comment echo after Linear error");
+            }
+            return Err(e.into());
+        }
+    };
+    db::insert_audit_entry(
+        &state.pool,
+        &mapping.discord_thread_id,
+        &mapping.linear_identifier,
+        "comment_posted",
+        Some(&cmd.user.id.to_string()),
+        &comment_id,
+    )
+    .await?;
+
+    info!(
+        identifier = %mapping.linear_identifier,
+        comment_id,
+        user = %cmd.user.id,
+        "Posted Discord comment to Linear"
+    );
+
+    Ok(text_response(format!(
+        "Comment added to **{}**.",
+        mapping.linear_identifier
+    )))
+}
@@ -0,0 +1,139 @@
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommandOption, EditInteractionResponse,
+    ResolvedOption,
+};
+use tracing::info;
+
+use crate::db;
+use crate::discord::commands::{string_option, text_response, CommandError};
+use crate::discord::handler::AppState;
+use crate::linear::api::LinearApi;
+
+pub fn register() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        "connect",
+        "Link your Discord account to your Linear account",
+    )
+    .add_sub_option(
+        CreateCommandOption::new(
+            CommandOptionType::String,
+            "email",
+            "Email address of your Linear account",
+        )
+        .required(true),
+    )
+}
+
+pub async fn run(
+    _ctx: &Context,
+    state: &AppState,
+    cmd: &CommandInteraction,
+    options: &[ResolvedOption<'_>],
+) -> Result<EditInteractionResponse, CommandError> {
+    let email = string_option(options, "email")
+        .map(str::trim)
+        .filter(|e| e.contains('@'))
+        .ok_or_else(|| CommandError::User("Enter the email of your Linear account.".into()))?;
+
+    // In multi-tenant mode, only look in the workspaces this server's channels use, and check
+    // the code in the workspace the user was found in.
+    let config = state.config.current();
+    let (user, workspace) = if config.multi_tenant {
+        let mut credentials: Vec<Option<&str>> = config
+            .channels
+            .iter()
+            .filter(|c| Some(c.guild_id) == cmd.guild_id.map(|g| g.get()))
+            .map(|c| c.linear_credential.as_deref())
+            .collect();
+        credentials.sort();
+        credentials.dedup();
+        let mut found = (None, None);
+        for client in credentials
+            .into_iter()
+            .filter_map(|c| state.linear_client.workspace(c))
+        {
+            if let Some(user) = client.find_user_by_email(email).await? {
+                found = (Some(user), Some(client));
+                break;
+            }
+        }
+        found
+    } else {
+        (state.linear_client.find_user_by_email(email).await?, None)
+    };
+    let user = user
+        .ok_or_else(|| CommandError::User(format!("No active Linear user has email {email}.")))?;
+
+    let discord_user_id = cmd.user.id.to_string();
+    if let Some(current) = db::get_user_link_by_discord_user(&state.pool, &discord_user_id).await? {
+        if current.linear_user_id == user.id {
+            return Ok(text_response(format!(
+                "You're already connected to Linear as **{}**.",
+                current.linear_name
+            )));
+        }
+    }
+    if let Some(existing) = db::get_user_link_by_linear_user(&state.pool, &user.id).await? {
+        if existing.discord_user_id != discord_user_id {
+            return Err(CommandError::User(format!(
+                "**{}** is already connected to another Discord account.",
+                user.name
+            )));
+        }
+    }
+
+    // Anyone can type an email, so the link waits until the code we hand out appears in a
+    // comment written by that Linear user.
+    let pending = db::get_user_link_code(&state.pool, &discord_user_id)
+        .await?
+        .filter(|pending| pending.linear_user_id == user.id);
+    let Some(pending) = pending else {
+        let code = format!("link-{}", &uuid::Uuid::new_v4().simple().to_string()[..12]);
+        db::upsert_user_link_code(&state.pool, &discord_user_id, &user.id, &code).await?;
+        return Ok(text_response(format!(
+            "To confirm **{}** is you, comment `{code}` on any Linear issue while signed in \
+             as them, then run `/linear connect` with the same email within {} minutes. You \
+             can delete the comment afterwards.",
+            user.name,
+            db::USER_LINK_CODE_TTL_MINUTES
+        )));
+    };
+    let confirmed = match &workspace {
+        Some(client) => client.user_commented(&user.id, &pending.code).await?,
+        None => {
+            state
+                .linear_client
+                .user_commented(&user.id, &pending.code)
+                .await?
+        }
+    };
+    if !confirmed {
+        return Ok(text_response(format!(
+            "No comment from **{}** contains `{}` yet. Comment it on any Linear issue, then run \
+             `/linear connect` again.",
+            user.name, pending.code
+        )));
+    }
+
+    db::upsert_user_link(
+        &state.pool,
+        &discord_user_id,
+        &user.id,
+        &user.name,
+        &user.email,
+    )
+    .await?;
+    db::delete_user_link_code(&state.pool, &discord_user_id).await?;
+
+    info!(
+        user = %cmd.user.id,
+        linear_user_id = %user.id,
+        "Connected Discord user to Linear user"
+    );
+
+    Ok(text_response(format!(
+        "Connected to Linear as **{}** ({}).",
+        user.name, user.display_name
+    )))
+}
@@ -0,0 +1,290 @@
+//! `/linear create`: file an issue from anywhere in the server through a modal, in a synced
+//! forum's team and project, optionally opening a post in that forum to follow it.
+
+use serenity::all::{
+    ActionRowComponent, ChannelId, ChannelType, CommandInteraction, CommandOptionType, Context,
+    CreateActionRow, CreateCommandOption, CreateForumPost, CreateInputText,
+    CreateInteractionResponse, CreateMessage, CreateModal, EditInteractionResponse, InputTextStyle,
+    ModalInteraction, ResolvedOption, User,
+};
+use sqlx::SqlitePool;
+use tracing::{error, info, warn};
+
+use crate::config::{ChannelConfig, Config};
+use crate::db;
+use crate::discord::commands::{bool_option, channel_option, thread_parent_id, CommandError};
+use crate::discord::handler::AppState;
+use crate::discord::port::DiscordPort;
+use crate::error::AppError;
+use crate::linear::api::LinearApi;
+use crate::linear::client::LinearIssue;
+use crate::sync::discord_to_linear::{link_thread_url, thread_url, tracked_message};
+use crate::sync::linear_to_discord::split_for_discord;
+use crate::sync::thread::thread_name_for_title;
+
+pub const CUSTOM_ID_PREFIX: &str = "create:";
+
+/// Linear's limit on issue titles.
+const MAX_TITLE_CHARS: u16 = 255;
+
+pub fn register() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        "create",
+        "File a Linear issue from anywhere",
+    )
+    .add_sub_option(
+        CreateCommandOption::new(
+            CommandOptionType::Channel,
+            "forum",
+            "Synced forum whose team and project the issue goes to",
+        )
+        .channel_types(vec![ChannelType::Forum]),
+    )
+    .add_sub_option(CreateCommandOption::new(
+        CommandOptionType::Boolean,
+        "thread",
+        "Also open a post in the forum to follow the issue",
+    ))
+}
+
+/// Show the modal asking for the issue's title and description. Runs instead of the usual
+/// deferred response, since a modal has to be the interaction's first response.
+pub async fn open_modal(
+    ctx: &Context,
+    state: &AppState,
+    cmd: &CommandInteraction,
+    options: &[ResolvedOption<'_>],
+) -> Result<(), CommandError> {
+    let guild_id = cmd
+        .guild_id
+        .ok_or_else(|| CommandError::User("Use this command in a server.".into()))?;
+    let config = state.config.current();
+    let forum = channel_option(options, "forum").map(|c| c.id.get());
+    let channel_config = resolve_config(&config, guild_id.get(), forum, thread_parent_id(cmd))?;
+    let open_thread = bool_option(options, "thread").unwrap_or(false);
+
+    let custom_id = format!(
+        "{CUSTOM_ID_PREFIX}{}:{}",
+        channel_config.discord_channel_id,
+        u8::from(open_thread)
+    );
+    let modal = CreateModal::new(custom_id, "New Linear issue").components(vec![
+        CreateActionRow::InputText(
+            CreateInputText::new(InputTextStyle::Short, "Title", "title")
+                .max_length(MAX_TITLE_CHARS)
+                .required(true),
+        ),
+        CreateActionRow::InputText(
+            CreateInputText::new(InputTextStyle::Paragraph, "Description", "description")
+                .required(false),
+        ),
+    ]);
+    cmd.create_response(&ctx.http, CreateInteractionResponse::Modal(modal))
+        .await?;
+    Ok(())
+}
+
+/// The forum named in the command, else the forum of the thread it was used in, else the
+/// server's only synced forum.
+fn resolve_config(
+    config: &Config,
+    guild_id: u64,
+    forum: Option<u64>,
+    parent_id: Option<u64>,
+) -> Result<&ChannelConfig, CommandError> {
+    let in_guild = |id: u64| config.channel_config(id).filter(|c| c.guild_id == guild_id);
+    if let Some(forum) = forum {
+        return in_guild(forum)
+            .ok_or_else(|| CommandError::User(format!("<#{forum}> isn't synced with Linear.")));
+    }
+    if let Some(channel_config) = parent_id.and_then(in_guild) {
+        return Ok(channel_config);
+    }
+    let mut synced = config.channels.iter().filter(|c| c.guild_id == guild_id);
+    match (synced.next(), synced.next()) {
+        (Some(channel_config), None) => Ok(channel_config),
+        (None, _) => Err(CommandError::User(
+            "No forum in this server is synced with Linear.".into(),
+        )),
+        (Some(_), Some(_)) => Err(CommandError::User(
+            "Choose the forum whose Linear team the issue goes to.".into(),
+        )),
+    }
+}
+
+pub async fn handle_modal(ctx: &Context, state: &AppState, modal: &ModalInteraction) {
+    let Some((forum_id, open_thread)) = modal
+        .data
+        .custom_id
+        .strip_prefix(CUSTOM_ID_PREFIX)
+        .and_then(|rest| rest.split_once(':'))
+    else {
+        return;
+    };
+
+    let mut title = String::new();
+    let mut description = String::new();
+    for row in &modal.data.components {
+        for component in &row.components {
+            if let ActionRowComponent::InputText(input) = component {
+                let value = input.value.clone().unwrap_or_default();
+                match input.custom_id.as_str() {
+                    "title" => title = value,
+                    "description" => description = value,
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    if let Err(e) = modal.defer_ephemeral(&ctx.http).await {
+        warn!(error = %e, "Failed to defer create modal");
+        return;
+    }
+
+    let config = state.config.current();
+    let channel_config = forum_id
+        .parse::<u64>()
+        .ok()
+        .and_then(|id| config.channel_config(id));
+    let content = match channel_config {
+        None => "That forum is no longer synced with Linear.".to_string(),
+        Some(channel_config) => {
+            let filed = file_issue(
+                &state.discord(&ctx.http),
+                &state.pool,
+                channel_config,
+                &state.linear_client,
+                NewIssue {
+                    author: &modal.user,
+                    title: title.trim(),
+                    description: description.trim(),
+                    open_thread: open_thread == "1",
+                },
+            )
+            .await;
+            match filed {
+                Ok((issue, None)) => {
+                    format!("Created **[{}]({})**.", issue.identifier, issue.url)
+                }
+                Ok((issue, Some(thread_id))) => format!(
+                    "Created **[{}]({})**; follow it in <#{thread_id}>.",
+                    issue.identifier, issue.url
+                ),
+                Err(e) => {
+                    error!(user = %modal.user.id, error = %e, "Failed to create issue from modal");
+                    "Something went wrong creating the issue; check the bot logs.".to_string()
+                }
+            }
+        }
+    };
+    let response = EditInteractionResponse::new().content(content);
+    if let Err(e) = modal.edit_response(&ctx.http, response).await {
+        warn!(error = %e, "Failed to send create response");
+    }
+}
+
+/// An issue submitted through the modal.
+#[derive(Debug, Clone, Copy)]
+pub struct NewIssue<'a> {
+    pub author: &'a User,
+    pub title: &'a str,
+    /// Markdown; may be empty.
+    pub description: &'a str,
+    /// Also open a post in the channel's forum to follow the issue.
+    pub open_thread: bool,
+}
+
+/// File `new` in the channel's team and project with the channel's label, crediting its author,
+/// and open a post mapped to the issue if asked. Returns the issue and the post, if one was
+/// opened.
+pub async fn file_issue(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    channel_config: &ChannelConfig,
+    linear: &impl LinearApi,
+    new: NewIssue<'_>,
+) -> Result<(LinearIssue, Option<ChannelId>), AppError> {
+    let NewIssue {
+        author,
+        title,
+        description,
+        open_thread,
+    } = new;
+    let footer = format!(
+        "Filed from Discord by {} with `/linear create`.",
+        author.display_name()
+    );
+    let issue_description = if description.is_empty() {
+        footer
+    } else {
+        format!("{description}\n\n---\n{footer}")
+    };
+    let issue = linear
+        .create_issue(
+            &channel_config.linear_team_id,
+            title,
+            &issue_description,
+            std::slice::from_ref(&channel_config.linear_label_id),
+            &channel_config.linear_project_id,
+        )
+        .await?;
+    info!(
+        identifier = %issue.identifier,
+        user = %author.id,
+        team_id = %channel_config.linear_team_id,
+        "Created Linear issue from /linear create"
+    );
+    if !open_thread {
+        return Ok((issue, None));
+    }
+
+    let text = if description.is_empty() {
+        format!("Filed by <@{}>.", author.id)
+    } else {
+        format!("{description}\n\n— <@{}>", author.id)
+    };
+    let mut chunks = split_for_discord(&text).into_iter();
+    let forum_id = ChannelId::new(channel_config.discord_channel_id);
+    let post = CreateForumPost::new(
+        thread_name_for_title(title),
+        CreateMessage::new().content(chunks.next().unwrap_or_default()),
+    );
+    let thread = discord.create_forum_post(forum_id, post).await?;
+    let thread_id = thread.id.to_string();
+
+    db::create_mapping(
+        pool,
+        &thread_id,
+        &issue.id,
+        &issue.identifier,
+        &channel_config.channel_type,
+        &channel_config.guild_id.to_string(),
+    )
+    .await?;
+    db::record_issue_title(pool, &issue.id, &issue.title).await?;
+    db::insert_audit_entry(
+        pool,
+        &thread_id,
+        &issue.identifier,
+        "issue_created",
+        Some(&author.id.to_string()),
+        &issue.title,
+    )
+    .await?;
+
+    for chunk in chunks {
+        discord
+            .send_message(thread.id, CreateMessage::new().content(chunk))
+            .await?;
+    }
+    let url = thread_url(channel_config.guild_id, forum_id, thread.id);
+    link_thread_url(linear, &issue.id, &url).await;
+    let reply = tracked_message(channel_config, &issue.identifier, &issue.url);
+    discord
+        .send_message(thread.id, CreateMessage::new().content(reply))
+        .await?;
+
+    Ok((issue, Some(thread.id)))
+}
@@ -0,0 +1,151 @@
+use serenity::all::{
+    Channel, ChannelType, CommandInteraction, CommandType, Context, CreateCommand, CreateThread,
+    EditInteractionResponse, Message, ResolvedTarget,
+};
+use tracing::info;
+
+use crate::config::{ChannelConfig, Config};
+use crate::db;
+use crate::discord::commands::{text_response, CommandError};
+use crate::discord::handler::AppState;
+use crate::sync::discord_to_linear::create_issue_for_thread;
+
+pub const COMMAND_NAME: &str = "Create Linear issue from message";
+
+/// Discord's limit on thread names.
+const MAX_THREAD_NAME_CHARS: usize = 100;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(COMMAND_NAME)
+        .kind(CommandType::Message)
+        .dm_permission(false)
+}
+
+pub async fn run(
+    ctx: &Context,
+    state: &AppState,
+    cmd: &CommandInteraction,
+) -> Result<EditInteractionResponse, CommandError> {
+    let message = match cmd.data.target() {
+        Some(ResolvedTarget::Message(m)) => m,
+        _ => {
+            return Err(CommandError::User(
+                "Couldn't read the target message.".into(),
+            ))
+        }
+    };
+
+    let in_thread = matches!(
+        cmd.channel.as_ref().map(|c| c.kind),
+        Some(ChannelType::PublicThread | ChannelType::PrivateThread)
+    );
+
+    let guild_id = cmd
+        .guild_id
+        .ok_or_else(|| CommandError::User("Use this command in a server.".into()))?;
+
+    // A message inside an existing thread reuses that thread; anywhere else gets a new one.
+    let config = state.config.current();
+    let (thread, channel_config) = if in_thread {
+        let thread = match message.channel_id.to_channel(&ctx.http).await? {
+            Channel::Guild(gc) => gc,
+            _ => return Err(CommandError::User("Unsupported channel.".into())),
+        };
+        let channel_config =
+            resolve_config(&config, guild_id.get(), thread.parent_id.map(|p| p.get()))?;
+        ensure_untracked(state, &thread.id.to_string()).await?;
+        (thread, channel_config)
+    } else {
+        let channel_config = resolve_config(&config, guild_id.get(), None)?;
+        // A thread started from a message shares its id, so a second run finds the first one's.
+        let thread = match &message.thread {
+            Some(thread) => {
+                ensure_untracked(state, &thread.id.to_string()).await?;
+                thread.clone()
+            }
+            None => {
+                ensure_untracked(state, &message.id.to_string()).await?;
+                message
+                    .channel_id
+                    .create_thread_from_message(
+                        &ctx.http,
+                        message.id,
+                        CreateThread::new(thread_name(message)),
+                    )
+                    .await?
+            }
+        };
+        (thread, channel_config)
+    };
+
+    let issue = create_issue_for_thread(
+        ctx.http.as_ref(),
+        &state.pool,
+        channel_config,
+        &state.linear_client,
+        &thread,
+        Some(message),
+        None,
+    )
+    .await?;
+    db::insert_audit_entry(
+        &state.pool,
+        &thread.id.to_string(),
+        &issue.identifier,
+        "issue_created",
+        Some(&cmd.user.id.to_string()),
+        &issue.title,
+    )
+    .await?;
+
+    info!(
+        thread_id = %thread.id,
+        message_id = %message.id,
+        identifier = %issue.identifier,
+        user = %cmd.user.id,
+        "Created Linear issue from Discord message"
+    );
+
+    Ok(text_response(format!(
+        "Created **{}** in <#{}>.",
+        issue.identifier, thread.id
+    )))
+}
+
+/// Refuse a thread that is already linked to an issue.
+async fn ensure_untracked(state: &AppState, thread_id: &str) -> Result<(), CommandError> {
+    match db::get_mapping_by_discord_thread(&state.pool, thread_id).await? {
+        Some(existing) => Err(CommandError::User(format!(
+            "This thread is already tracked as **{}**.",
+            existing.linear_identifier
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Monitored forum config for the thread's parent, falling back to `CONTEXT_MENU_CHANNEL_ID`.
+/// The menu is registered in every server, so only `guild_id`'s own channels qualify.
+fn resolve_config(
+    config: &Config,
+    guild_id: u64,
+    parent_id: Option<u64>,
+) -> Result<&ChannelConfig, CommandError> {
+    let in_guild = |id: u64| config.channel_config(id).filter(|c| c.guild_id == guild_id);
+    parent_id
+        .and_then(in_guild)
+        .or_else(|| config.context_menu_channel_id.and_then(in_guild))
+        .ok_or_else(|| {
+            CommandError::User("No Linear team is configured for messages in this channel.".into())
+        })
+}
+
+/// First line of the message, trimmed to Discord's thread name limit.
+fn thread_name(message: &Message) -> String {
+    let first_line = message.content.lines().next().unwrap_or_default().trim();
+    let name: String = first_line.chars().take(MAX_THREAD_NAME_CHARS).collect();
+    if name.is_empty() {
+        format!("Report from {}", message.author.name)
+    } else {
+        name
+    }
+}
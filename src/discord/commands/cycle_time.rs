@@ -0,0 +1,164 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDateTime;
+use serenity::all::{
+    ChannelId, CommandInteraction, CommandOptionType, Context, CreateCommandOption, CreateEmbed,
+    EditInteractionResponse, ResolvedOption,
+};
+
+use crate::db::{self, TrackedStatusChange};
+use crate::discord::commands::CommandError;
+use crate::discord::handler::AppState;
+use crate::linear::client::StateCategory;
+
+/// Average of a set of durations.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Average {
+    pub total_secs: i64,
+    pub count: usize,
+}
+
+impl Average {
+    fn add(&mut self, secs: i64) {
+        self.total_secs += secs;
+        self.count += 1;
+    }
+
+    pub fn mean_secs(&self) -> Option<i64> {
+        (self.count > 0).then(|| self.total_secs / self.count as i64)
+    }
+}
+
+/// Time-in-status figures over the tracked issues' status history.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CycleTimes {
+    /// From the Discord post to the issue's first `started` state.
+    pub to_started: Average,
+    /// From the Discord post to the issue's first `completed` state.
+    pub to_completed: Average,
+    /// Time spent in each status before moving on, by status name. The current status of each
+    /// issue isn't counted since it hasn't ended yet.
+    pub in_status: BTreeMap<String, Average>,
+}
+
+pub fn register() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        "cycle-time",
+        "Show how long tracked issues take to be started and completed in Linear",
+    )
+}
+
+pub async fn run(
+    _ctx: &Context,
+    state: &AppState,
+    cmd: &CommandInteraction,
+    _options: &[ResolvedOption<'_>],
+) -> Result<EditInteractionResponse, CommandError> {
+    let guild_id = cmd
+        .guild_id
+        .ok_or_else(|| CommandError::User("Use this command in a server.".into()))?;
+    // In multi-tenant mode a server only sees its own issues' statuses.
+    let guild = guild_id.to_string();
+    let scope = state
+        .config
+        .current()
+        .multi_tenant
+        .then_some(guild.as_str());
+    let history = db::get_tracked_status_history(&state.pool, scope).await?;
+    if history.is_empty() {
+        return Err(CommandError::User(
+            "No status changes have been recorded for tracked issues yet.".into(),
+        ));
+    }
+    let times = summarize(&history);
+
+    let mut in_status = times
+        .in_status
+        .iter()
+        .map(|(status, average)| format!("{status}: {}", describe(average)))
+        .collect::<Vec<_>>()
+        .join("\n");
+    if in_status.is_empty() {
+        in_status = "No issue has left a status yet".to_string();
+    }
+    let embed = CreateEmbed::new()
+        .title("Time in status for tracked issues")
+        .field("Posted → In Progress", describe(&times.to_started), false)
+        .field("Posted → Done", describe(&times.to_completed), false)
+        .field("Average time in status", in_status, false);
+
+    Ok(EditInteractionResponse::new().embed(embed))
+}
+
+/// Summarize `history`, which is grouped by issue and oldest first. "In Progress" and "Done" are
+/// Linear's `started` and `completed` state types, so custom state names count too.
+pub fn summarize(history: &[TrackedStatusChange]) -> CycleTimes {
+    let mut times = CycleTimes::default();
+    for issue in history.chunk_by(|a, b| a.linear_issue_id == b.linear_issue_id) {
+        let posted_at = issue[0]
+            .discord_thread_id
+            .parse::<u64>()
+            .ok()
+            .filter(|&id| id > 0)
+            .map(|id| ChannelId::new(id).created_at().unix_timestamp());
+        let changes: Vec<(&TrackedStatusChange, i64)> = issue
+            .iter()
+            .filter_map(|change| Some((change, unix_time(&change.changed_at)?)))
+            .collect();
+
+        if let Some(posted_at) = posted_at {
+            for (category, average) in [
+                (StateCategory::Started, &mut times.to_started),
+                (StateCategory::Completed, &mut times.to_completed),
+            ] {
+                let reached = changes
+                    .iter()
+                    .find(|(c, _)| StateCategory::from_type(&c.status_type) == category);
+                if let Some((_, at)) = reached {
+                    average.add((at - posted_at).max(0));
+                }
+            }
+        }
+        for pair in changes.windows(2) {
+            let ((change, start), (_, end)) = (pair[0], pair[1]);
+            times
+                .in_status
+                .entry(change.status_name.clone())
+                .or_default()
+                .add((end - start).max(0));
+        }
+    }
+    times
+}
+
+/// "2d 4h (12 issues)", or a note that no issue got there.
+fn describe(average: &Average) -> String {
+    match average.mean_secs() {
+        Some(secs) => format!(
+            "{} ({} issue{})",
+            format_duration(secs),
+            average.count,
+            if average.count == 1 { "" } else { "s" }
+        ),
+        None => "No issues yet".to_string(),
+    }
+}
+
+/// The two largest units of `secs`, e.g. "3d 4h" or "12m".
+fn format_duration(secs: i64) -> String {
+    let (days, hours, minutes) = (secs / 86_400, secs % 86_400 / 3_600, secs % 3_600 / 60);
+    match (days, hours, minutes) {
+        (0, 0, 0) => "under a minute".to_string(),
+        (0, 0, m) => format!("{m}m"),
+        (0, h, m) => format!("{h}h {m}m"),
+        (d, h, _) => format!("{d}d {h}h"),
+    }
+}
+
+/// A SQLite `datetime('now')` timestamp as Unix seconds.
+fn unix_time(sqlite_time: &str) -> Option<i64> {
+    NaiveDateTime::parse_from_str(sqlite_time, "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .map(|t| t.and_utc().timestamp())
+}
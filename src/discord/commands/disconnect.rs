@@ -0,0 +1,34 @@
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommandOption, EditInteractionResponse,
+    ResolvedOption,
+};
+use tracing::info;
+
+use crate::db;
+use crate::discord::commands::{text_response, CommandError};
+use crate::discord::handler::AppState;
+
+pub fn register() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        "disconnect",
+        "Unlink your Discord account from Linear",
+    )
+}
+
+pub async fn run(
+    _ctx: &Context,
+    state: &AppState,
+    cmd: &CommandInteraction,
+    _options: &[ResolvedOption<'_>],
+) -> Result<EditInteractionResponse, CommandError> {
+    if !db::delete_user_link(&state.pool, &cmd.user.id.to_string()).await? {
+        return Err(CommandError::User(
+            "Your Discord account isn't connected to Linear.".into(),
+        ));
+    }
+
+    info!(user = %cmd.user.id, "Disconnected Discord user from Linear");
+
+    Ok(text_response("Disconnected your Linear account."))
+}
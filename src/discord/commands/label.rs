@@ -0,0 +1,81 @@
+use serenity::all::{
+    AutocompleteChoice, CommandInteraction, CommandOptionType, Context, CreateCommandOption,
+    EditInteractionResponse, ResolvedOption,
+};
+use tracing::info;
+
+use crate::db;
+use crate::discord::commands::{
+    name_choices, resolve_name, string_option, text_response, thread_team_id, CommandError,
+};
+use crate::discord::handler::AppState;
+use crate::linear::api::LinearApi;
+
+pub fn register() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        "label",
+        "Add a label to the linked Linear issue",
+    )
+    .add_sub_option(
+        CreateCommandOption::new(CommandOptionType::String, "label", "Linear label")
+            .required(true)
+            .set_autocomplete(true),
+    )
+}
+
+pub async fn run(
+    _ctx: &Context,
+    state: &AppState,
+    cmd: &CommandInteraction,
+    options: &[ResolvedOption<'_>],
+) -> Result<EditInteractionResponse, CommandError> {
+    let input = string_option(options, "label")
+        .map(str::trim)
+        .ok_or_else(|| CommandError::User("Pick a label to add.".into()))?;
+
+    let mapping = db::get_mapping_by_discord_thread(&state.pool, &cmd.channel_id.to_string())
+        .await?
+        .ok_or_else(|| CommandError::User("This thread isn't linked to a Linear issue.".into()))?;
+
+    let team_id = thread_team_id(state, cmd)?;
+    let metadata = state.metadata.team(&state.linear_client, &team_id).await?;
+    let (label_id, label_name) = resolve_name(&metadata.labels, input)
+        .ok_or_else(|| CommandError::User(format!("No Linear label matches \"{input}\".")))?;
+
+    state
+        .linear_client
+        .add_issue_label(&mapping.linear_issue_id, label_id)
+        .await?;
+    db::insert_audit_entry(
+        &state.pool,
+        &mapping.discord_thread_id,
+        &mapping.linear_identifier,
+        "label_added",
+        Some(&cmd.user.id.to_string()),
+        label_name,
+    )
+    .await?;
+
+    info!(
+        identifier = %mapping.linear_identifier,
+        label = %label_name,
+        user = %cmd.user.id,
+        "Added Linear label from Discord"
+    );
+
+    Ok(text_response(format!(
+        "**{}** labeled **{label_name}** by <@{}>",
+        mapping.linear_identifier, cmd.user.id
+    )))
+}
+
+pub async fn autocomplete(
+    state: &AppState,
+    cmd: &CommandInteraction,
+    partial: &str,
+) -> Result<Vec<AutocompleteChoice>, CommandError> {
+    let team_id = thread_team_id(state, cmd)?;
+    let metadata = state.metadata.team(&state.linear_client, &team_id).await?;
+    Ok(name_choices(&metadata.labels, partial))
+}
@@ -0,0 +1,176 @@
+use serenity::all::{
+    ChannelId, CommandInteraction, CommandOptionType, Context, CreateCommandOption,
+    EditInteractionResponse, ResolvedOption, UserId,
+};
+use tracing::{info, warn};
+
+use crate::config::ChannelConfig;
+use crate::db;
+use crate::discord::commands::{string_option, text_response, thread_parent_id, CommandError};
+use crate::discord::handler::AppState;
+use crate::error::AppError;
+use crate::linear::api::LinearApi;
+use crate::linear::client::LinearIssueDetail;
+use crate::sync::discord_to_linear::{link_thread_url, thread_url, tracked_message};
+use crate::sync::linear_to_discord::sync_linear_comments_to_discord;
+use crate::sync::status_embed::post_status_embed;
+
+pub fn register() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        "link",
+        "Link this thread to an existing Linear issue",
+    )
+    .add_sub_option(
+        CreateCommandOption::new(
+            CommandOptionType::String,
+            "issue",
+            "Issue identifier, e.g. ABC-123",
+        )
+        .required(true),
+    )
+}
+
+pub async fn run(
+    ctx: &Context,
+    state: &AppState,
+    cmd: &CommandInteraction,
+    options: &[ResolvedOption<'_>],
+) -> Result<EditInteractionResponse, CommandError> {
+    let identifier = string_option(options, "issue")
+        .map(|s| s.trim().to_uppercase())
+        .ok_or_else(|| CommandError::User("Missing issue identifier.".into()))?;
+
+    let config = state.config.current();
+    let channel_config = thread_parent_id(cmd)
+        .and_then(|parent| config.channel_config(parent))
+        .ok_or_else(|| {
+            CommandError::User("Use this command inside a thread in a monitored forum.".into())
+        })?;
+
+    let thread_id = cmd.channel_id.to_string();
+    if let Some(existing) = db::get_mapping_by_discord_thread(&state.pool, &thread_id).await? {
+        return Err(CommandError::User(format!(
+            "This thread is already linked to **{}**.",
+            existing.linear_identifier
+        )));
+    }
+
+    // Identifiers are only unique within a workspace, so look in the forum's.
+    let linear = state.linear_client.for_channel(channel_config);
+    let issue = match linear.get_issue(&identifier).await {
+        Ok(issue) => issue,
+        Err(AppError::LinearApi(e)) => {
+            warn!(identifier, error = %e, "Issue lookup failed for /linear link");
+            return Err(CommandError::User(format!(
+                "Couldn't find **{identifier}** in Linear."
+            )));
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    if let Some(existing) = db::get_mapping_by_linear_issue(&state.pool, &issue.id).await? {
+        return Err(CommandError::User(format!(
+            "**{}** is already linked to <#{}>.",
+            issue.identifier, existing.discord_thread_id
+        )));
+    }
+
+    link_thread(
+        ctx,
+        state,
+        channel_config,
+        cmd.channel_id,
+        &issue,
+        cmd.user.id,
+    )
+    .await?;
+
+    Ok(text_response(format!(
+        "Linked this thread to **{}**: {}",
+        issue.identifier, issue.title
+    )))
+}
+
+/// Map a thread to an existing issue, announce it in the thread, and bring over the issue's
+/// comments. Callers check that neither is mapped yet. Also used by the duplicate prompt.
+pub async fn link_thread(
+    ctx: &Context,
+    state: &AppState,
+    channel_config: &ChannelConfig,
+    thread: ChannelId,
+    issue: &LinearIssueDetail,
+    actor: UserId,
+) -> Result<(), CommandError> {
+    let thread_id = thread.to_string();
+    db::create_mapping(
+        &state.pool,
+        &thread_id,
+        &issue.id,
+        &issue.identifier,
+        &channel_config.channel_type,
+        &channel_config.guild_id.to_string(),
+    )
+    .await?;
+
+    // Prime the cache so the poller doesn't announce the current state as a change.
+    db::upsert_cached_status(&state.pool, &issue.id, &issue.status_name).await?;
+    let parent_id = ChannelId::new(channel_config.discord_channel_id);
+    let url = thread_url(channel_config.guild_id, parent_id, thread);
+    link_thread_url(&state.linear_client, &issue.id, &url).await;
+    db::insert_audit_entry(
+        &state.pool,
+        &thread_id,
+        &issue.identifier,
+        "issue_linked",
+        Some(&actor.to_string()),
+        &issue.title,
+    )
+    .await?;
+
+    info!(
+        thread_id,
+        identifier = %issue.identifier,
+        status = %issue.status_name,
+        status_type = %issue.status_type,
+        user = %actor,
+        "Linked Discord thread to existing Linear issue"
+    );
+
+    thread
+        .say(
+            &ctx.http,
+            tracked_message(channel_config, &issue.identifier, &issue.url),
+        )
+        .await?;
+
+    if channel_config.status_embed {
+        if let Err(e) = post_status_embed(
+            ctx.http.as_ref(),
+            &state.pool,
+            &state.linear_client,
+            thread,
+            &issue.id,
+        )
+        .await
+        {
+            warn!(identifier = %issue.identifier, error = %e, "Failed to post status embed");
+        }
+    }
+
+    // Bring over the existing discussion so the thread has context.
+    if let Err(e) = sync_linear_comments_to_discord(
+        ctx.http.as_ref(),
+        &state.pool,
+        &state.config.current(),
+        &state.linear_client,
+        &issue.id,
+        &issue.identifier,
+    )
+    .await
+    {
+        warn!(identifier = %issue.identifier, error = %e, "Failed to backfill comments after link");
+    }
+
+    Ok(())
+}
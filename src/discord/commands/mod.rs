@@ -0,0 +1,598 @@
+//! `/linear` application command group.
+//!
+//! Each subcommand lives in its own module exposing `register()` (its option definition) and
+//! `run()`; the `admin` group's module has a function per subcommand. Dispatch checks the
+//! subcommand's required permissions, defers the response (Linear calls can exceed Discord's 3s
+//! acknowledgement window), then edits in the result; `create` answers with a modal instead.
+//! Errors are always delivered as ephemeral follow-ups so only the invoking user sees them.
+
+pub mod admin;
+pub mod assign;
+pub mod audit;
+pub mod backfill;
+pub mod comment;
+pub mod connect;
+pub mod create;
+pub mod create_from_message;
+pub mod cycle_time;
+pub mod disconnect;
+pub mod label;
+pub mod link;
+pub mod prefs;
+pub mod priority;
+pub mod project;
+pub mod retry_failed;
+pub mod search;
+pub mod setup;
+pub mod stats;
+pub mod status;
+pub mod subscribe;
+pub mod unlink;
+pub mod unsubscribe;
+
+use serenity::all::{
+    AutocompleteChoice, CommandInteraction, Context, CreateAutocompleteResponse, CreateCommand,
+    CreateInteractionResponse, CreateInteractionResponseFollowup, CreateInteractionResponseMessage,
+    EditInteractionResponse, PartialChannel, Permissions, ResolvedOption, ResolvedValue, RoleId,
+};
+use tracing::{error, warn};
+
+use crate::discord::handler::AppState;
+use crate::error::AppError;
+
+pub const COMMAND_NAME: &str = "linear";
+
+/// Discord caps autocomplete responses at 25 choices.
+pub const MAX_CHOICES: usize = 25;
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    /// A problem the user can fix; the message is shown verbatim.
+    #[error("{0}")]
+    User(String),
+
+    #[error("You need the {0} permission to use this command.")]
+    MissingPermission(Permissions),
+
+    #[error("You need one of these roles to use this command: {}.", role_mentions(.0))]
+    MissingRole(Vec<u64>),
+
+    /// Boxed because serenity's error type makes `AppError` large.
+    #[error(transparent)]
+    App(Box<AppError>),
+}
+
+fn role_mentions(roles: &[u64]) -> String {
+    roles
+        .iter()
+        .map(|r| format!("<@&{r}>"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl From<AppError> for CommandError {
+    fn from(e: AppError) -> Self {
+        CommandError::App(Box::new(e))
+    }
+}
+
+impl From<sqlx::Error> for CommandError {
+    fn from(e: sqlx::Error) -> Self {
+        AppError::from(e).into()
+    }
+}
+
+impl From<serenity::Error> for CommandError {
+    fn from(e: serenity::Error) -> Self {
+        AppError::from(e).into()
+    }
+}
+
+impl CommandError {
+    /// Text shown to the invoking user. Internal errors are logged, not echoed.
+    fn user_message(&self) -> String {
+        match self {
+            CommandError::App(_) => {
+                "Something went wrong handling that command; check the bot logs.".into()
+            }
+            other => other.to_string(),
+        }
+    }
+}
+
+/// Static metadata for a subcommand, consulted before it runs.
+struct Subcommand {
+    /// Prefixed with the group for grouped subcommands, e.g. "admin add-channel".
+    name: &'static str,
+    /// Discord permissions the invoking member must hold (empty = anyone).
+    permissions: Permissions,
+    /// Whether successful replies are only visible to the invoking user.
+    ephemeral: bool,
+}
+
+const SUBCOMMANDS: &[Subcommand] = &[
+    Subcommand {
+        name: "status",
+        permissions: Permissions::empty(),
+        ephemeral: false,
+    },
+    Subcommand {
+        name: "link",
+        permissions: Permissions::MANAGE_THREADS,
+        ephemeral: true,
+    },
+    Subcommand {
+        name: "unlink",
+        permissions: Permissions::MANAGE_THREADS,
+        ephemeral: false,
+    },
+    Subcommand {
+        name: "create",
+        permissions: Permissions::MANAGE_THREADS,
+        ephemeral: true,
+    },
+    Subcommand {
+        name: "comment",
+        permissions: Permissions::empty(),
+        ephemeral: true,
+    },
+    Subcommand {
+        name: "subscribe",
+        permissions: Permissions::empty(),
+        ephemeral: true,
+    },
+    Subcommand {
+        name: "unsubscribe",
+        permissions: Permissions::empty(),
+        ephemeral: true,
+    },
+    Subcommand {
+        name: "prefs",
+        permissions: Permissions::empty(),
+        ephemeral: true,
+    },
+    Subcommand {
+        name: "assign",
+        permissions: Permissions::MANAGE_THREADS,
+        ephemeral: false,
+    },
+    Subcommand {
+        name: "priority",
+        permissions: Permissions::MANAGE_THREADS,
+        ephemeral: false,
+    },
+    Subcommand {
+        name: "label",
+        permissions: Permissions::MANAGE_THREADS,
+        ephemeral: false,
+    },
+    Subcommand {
+        name: "project",
+        permissions: Permissions::MANAGE_THREADS,
+        ephemeral: false,
+    },
+    Subcommand {
+        name: "search",
+        permissions: Permissions::empty(),
+        ephemeral: true,
+    },
+    Subcommand {
+        name: "connect",
+        permissions: Permissions::empty(),
+        ephemeral: true,
+    },
+    Subcommand {
+        name: "disconnect",
+        permissions: Permissions::empty(),
+        ephemeral: true,
+    },
+    Subcommand {
+        name: "audit",
+        permissions: Permissions::MANAGE_THREADS,
+        ephemeral: true,
+    },
+    Subcommand {
+        name: "cycle-time",
+        permissions: Permissions::MANAGE_THREADS,
+        ephemeral: true,
+    },
+    Subcommand {
+        name: "stats",
+        permissions: Permissions::MANAGE_GUILD,
+        ephemeral: true,
+    },
+    Subcommand {
+        name: "retry-failed",
+        permissions: Permissions::MANAGE_GUILD,
+        ephemeral: true,
+    },
+    Subcommand {
+        name: "backfill",
+        permissions: Permissions::MANAGE_GUILD,
+        ephemeral: true,
+    },
+    Subcommand {
+        name: "setup",
+        permissions: Permissions::MANAGE_GUILD,
+        ephemeral: true,
+    },
+    Subcommand {
+        name: "admin add-channel",
+        permissions: Permissions::MANAGE_GUILD,
+        ephemeral: true,
+    },
+    Subcommand {
+        name: "admin remove-channel",
+        permissions: Permissions::MANAGE_GUILD,
+        ephemeral: true,
+    },
+    Subcommand {
+        name: "admin list-channels",
+        permissions: Permissions::MANAGE_GUILD,
+        ephemeral: true,
+    },
+    Subcommand {
+        name: "admin add-credential",
+        permissions: Permissions::MANAGE_GUILD,
+        ephemeral: true,
+    },
+    Subcommand {
+        name: "admin remove-credential",
+        permissions: Permissions::MANAGE_GUILD,
+        ephemeral: true,
+    },
+    Subcommand {
+        name: "admin prune",
+        permissions: Permissions::MANAGE_GUILD,
+        ephemeral: true,
+    },
+];
+
+/// Whether `name` is a `/linear` subcommand, prefixed with its group if it has one.
+pub fn is_subcommand(name: &str) -> bool {
+    SUBCOMMANDS.iter().any(|s| s.name == name)
+}
+
+/// The `/linear` command definition registered in every configured guild.
+pub fn register() -> CreateCommand {
+    CreateCommand::new(COMMAND_NAME)
+        .description("Work with the Linear issue linked to this thread")
+        .dm_permission(false)
+        .add_option(status::register())
+        .add_option(link::register())
+        .add_option(unlink::register())
+        .add_option(comment::register())
+        .add_option(create::register())
+        .add_option(subscribe::register())
+        .add_option(unsubscribe::register())
+        .add_option(prefs::register())
+        .add_option(assign::register())
+        .add_option(priority::register())
+        .add_option(label::register())
+        .add_option(project::register())
+        .add_option(search::register())
+        .add_option(connect::register())
+        .add_option(disconnect::register())
+        .add_option(audit::register())
+        .add_option(cycle_time::register())
+        .add_option(stats::register())
+        .add_option(retry_failed::register())
+        .add_option(backfill::register())
+        .add_option(setup::register())
+        .add_option(admin::register())
+}
+
+pub async fn handle_command(ctx: &Context, state: &AppState, cmd: &CommandInteraction) {
+    let (name, sub_options) = match subcommand(cmd.data.options()) {
+        Some(found) => found,
+        None => {
+            reply_error(ctx, cmd, &CommandError::User("Unknown command.".into())).await;
+            return;
+        }
+    };
+    let name = name.as_str();
+
+    let spec = match SUBCOMMANDS.iter().find(|s| s.name == name) {
+        Some(s) => s,
+        None => {
+            reply_error(ctx, cmd, &CommandError::User("Unknown command.".into())).await;
+            return;
+        }
+    };
+
+    let config = state.config.current();
+    let result = match config.command_roles.get(name) {
+        Some(roles) => check_roles(cmd, roles),
+        None => check_permissions(cmd, spec.permissions),
+    };
+    if let Err(e) = result {
+        reply_error(ctx, cmd, &e).await;
+        return;
+    }
+
+    // A modal has to be the first response, so this one can't be deferred.
+    if name == "create" {
+        if let Err(e) = create::open_modal(ctx, state, cmd, &sub_options).await {
+            if let CommandError::App(inner) = &e {
+                error!(command = name, user = %cmd.user.id, error = %inner, "Command failed");
+            }
+            reply_error(ctx, cmd, &e).await;
+        }
+        return;
+    }
+
+    if !defer(ctx, cmd, name, spec.ephemeral).await {
+        return;
+    }
+
+    let result = match name {
+        "status" => status::run(ctx, state, cmd, &sub_options).await,
+        "link" => link::run(ctx, state, cmd, &sub_options).await,
+        "unlink" => unlink::run(ctx, state, cmd, &sub_options).await,
+        "comment" => comment::run(ctx, state, cmd, &sub_options).await,
+        "subscribe" => subscribe::run(ctx, state, cmd, &sub_options).await,
+        "unsubscribe" => unsubscribe::run(ctx, state, cmd, &sub_options).await,
+        "prefs" => prefs::run(ctx, state, cmd, &sub_options).await,
+        "assign" => assign::run(ctx, state, cmd, &sub_options).await,
+        "priority" => priority::run(ctx, state, cmd, &sub_options).await,
+        "label" => label::run(ctx, state, cmd, &sub_options).await,
+        "project" => project::run(ctx, state, cmd, &sub_options).await,
+        "search" => search::run(ctx, state, cmd, &sub_options).await,
+        "connect" => connect::run(ctx, state, cmd, &sub_options).await,
+        "disconnect" => disconnect::run(ctx, state, cmd, &sub_options).await,
+        "audit" => audit::run(ctx, state, cmd, &sub_options).await,
+        "cycle-time" => cycle_time::run(ctx, state, cmd, &sub_options).await,
+        "stats" => stats::run(ctx, state, cmd, &sub_options).await,
+        "retry-failed" => retry_failed::run(ctx, state, cmd, &sub_options).await,
+        "backfill" => backfill::run(ctx, state, cmd, &sub_options).await,
+        "setup" => setup::run(ctx, state, cmd, &sub_options).await,
+        "admin add-channel" => admin::add_channel(ctx, state, cmd, &sub_options).await,
+        "admin remove-channel" => admin::remove_channel(ctx, state, cmd, &sub_options).await,
+        "admin list-channels" => admin::list_channels(ctx, state, cmd, &sub_options).await,
+        "admin add-credential" => admin::add_credential(ctx, state, cmd, &sub_options).await,
+        "admin remove-credential" => admin::remove_credential(ctx, state, cmd, &sub_options).await,
+        "admin prune" => admin::prune(ctx, state, cmd, &sub_options).await,
+        _ => Err(CommandError::User("Unknown command.".into())),
+    };
+
+    deliver(ctx, cmd, name, result).await;
+}
+
+/// The invoked subcommand's name, prefixed with its group if it has one, and its options.
+fn subcommand(options: Vec<ResolvedOption<'_>>) -> Option<(String, Vec<ResolvedOption<'_>>)> {
+    match options.into_iter().next()? {
+        ResolvedOption {
+            name,
+            value: ResolvedValue::SubCommand(sub_options),
+            ..
+        } => Some((name.to_string(), sub_options)),
+        ResolvedOption {
+            name: group,
+            value: ResolvedValue::SubCommandGroup(group_options),
+            ..
+        } => match group_options.into_iter().next()? {
+            ResolvedOption {
+                name,
+                value: ResolvedValue::SubCommand(sub_options),
+                ..
+            } => Some((format!("{group} {name}"), sub_options)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Handle the "Create Linear issue from message" context-menu command.
+pub async fn handle_message_command(ctx: &Context, state: &AppState, cmd: &CommandInteraction) {
+    let name = create_from_message::COMMAND_NAME;
+
+    if let Err(e) = check_permissions(cmd, Permissions::MANAGE_THREADS) {
+        reply_error(ctx, cmd, &e).await;
+        return;
+    }
+
+    if !defer(ctx, cmd, name, true).await {
+        return;
+    }
+
+    let result = create_from_message::run(ctx, state, cmd).await;
+    deliver(ctx, cmd, name, result).await;
+}
+
+/// Acknowledge the interaction; returns false if Discord rejected the acknowledgement.
+async fn defer(ctx: &Context, cmd: &CommandInteraction, name: &str, ephemeral: bool) -> bool {
+    let deferred = if ephemeral {
+        cmd.defer_ephemeral(&ctx.http).await
+    } else {
+        cmd.defer(&ctx.http).await
+    };
+    if let Err(e) = deferred {
+        warn!(command = name, error = %e, "Failed to defer interaction");
+        return false;
+    }
+    true
+}
+
+/// Edit the deferred response with the result, or replace it with an ephemeral error.
+async fn deliver(
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    name: &str,
+    result: Result<EditInteractionResponse, CommandError>,
+) {
+    match result {
+        Ok(response) => {
+            if let Err(e) = cmd.edit_response(&ctx.http, response).await {
+                warn!(command = name, error = %e, "Failed to send command response");
+            }
+        }
+        Err(e) => {
+            if let CommandError::App(inner) = &e {
+                error!(command = name, user = %cmd.user.id, error = %inner, "Command failed");
+            }
+            // The deferred placeholder may be public; drop it so the error stays private.
+            let _ = cmd.delete_response(&ctx.http).await;
+            let followup = CreateInteractionResponseFollowup::new()
+                .content(e.user_message())
+                .ephemeral(true);
+            if let Err(e) = cmd.create_followup(&ctx.http, followup).await {
+                warn!(command = name, error = %e, "Failed to send command error");
+            }
+        }
+    }
+}
+
+/// Answer an autocomplete request for whichever subcommand option is focused.
+pub async fn handle_autocomplete(ctx: &Context, state: &AppState, cmd: &CommandInteraction) {
+    let subcommand = match cmd.data.options.first() {
+        Some(o) => o.name.as_str(),
+        None => return,
+    };
+    let focused = match cmd.data.autocomplete() {
+        Some(f) => f,
+        None => return,
+    };
+
+    let result = match (subcommand, focused.name) {
+        ("assign", "user") => assign::autocomplete(state, cmd, focused.value).await,
+        ("label", "label") => label::autocomplete(state, cmd, focused.value).await,
+        ("project", "project") => project::autocomplete(state, cmd, focused.value).await,
+        _ => Ok(Vec::new()),
+    };
+
+    let choices = match result {
+        Ok(choices) => choices,
+        Err(e) => {
+            // Autocomplete has no error channel; an empty list is the best we can do.
+            warn!(subcommand, error = %e, "Autocomplete failed");
+            Vec::new()
+        }
+    };
+
+    let response = CreateAutocompleteResponse::new().set_choices(choices);
+    if let Err(e) = cmd
+        .create_response(&ctx.http, CreateInteractionResponse::Autocomplete(response))
+        .await
+    {
+        warn!(subcommand, error = %e, "Failed to send autocomplete response");
+    }
+}
+
+fn check_permissions(cmd: &CommandInteraction, required: Permissions) -> Result<(), CommandError> {
+    if required.is_empty() {
+        return Ok(());
+    }
+    let granted = cmd
+        .member
+        .as_ref()
+        .and_then(|m| m.permissions)
+        .unwrap_or_else(Permissions::empty);
+    if granted.contains(required) || granted.administrator() {
+        Ok(())
+    } else {
+        Err(CommandError::MissingPermission(required))
+    }
+}
+
+/// Require one of `roles`, for subcommands restricted with `COMMAND_ROLES`.
+fn check_roles(cmd: &CommandInteraction, roles: &[u64]) -> Result<(), CommandError> {
+    let Some(member) = cmd.member.as_ref() else {
+        return Err(CommandError::MissingRole(roles.to_vec()));
+    };
+    let administrator = member.permissions.is_some_and(|p| p.administrator());
+    let has_role = roles
+        .iter()
+        .any(|r| member.roles.contains(&RoleId::new(*r)));
+    if administrator || has_role {
+        Ok(())
+    } else {
+        Err(CommandError::MissingRole(roles.to_vec()))
+    }
+}
+
+/// Immediate ephemeral error reply, for failures detected before the response is deferred.
+async fn reply_error(ctx: &Context, cmd: &CommandInteraction, e: &CommandError) {
+    let message = CreateInteractionResponseMessage::new()
+        .content(e.user_message())
+        .ephemeral(true);
+    if let Err(e) = cmd
+        .create_response(&ctx.http, CreateInteractionResponse::Message(message))
+        .await
+    {
+        warn!(error = %e, "Failed to send command error");
+    }
+}
+
+/// Plain-text response body for a deferred interaction.
+pub fn text_response(content: impl Into<String>) -> EditInteractionResponse {
+    EditInteractionResponse::new().content(content)
+}
+
+/// Value of a boolean option, if present.
+pub fn bool_option(options: &[ResolvedOption<'_>], name: &str) -> Option<bool> {
+    options.iter().find_map(|o| match o.value {
+        ResolvedValue::Boolean(v) if o.name == name => Some(v),
+        _ => None,
+    })
+}
+
+/// Value of a string option, if present.
+pub fn string_option<'a>(options: &'a [ResolvedOption<'a>], name: &str) -> Option<&'a str> {
+    options.iter().find_map(|o| match o.value {
+        ResolvedValue::String(v) if o.name == name => Some(v),
+        _ => None,
+    })
+}
+
+/// Value of a channel option, if present.
+pub fn channel_option<'a>(
+    options: &'a [ResolvedOption<'a>],
+    name: &str,
+) -> Option<&'a PartialChannel> {
+    options.iter().find_map(|o| match o.value {
+        ResolvedValue::Channel(v) if o.name == name => Some(v),
+        _ => None,
+    })
+}
+
+/// Parent channel of the thread the command was invoked in.
+pub fn thread_parent_id(cmd: &CommandInteraction) -> Option<u64> {
+    cmd.channel
+        .as_ref()
+        .and_then(|c| c.parent_id)
+        .map(|id| id.get())
+}
+
+/// Linear team of the forum channel the command's thread lives in.
+pub fn thread_team_id(state: &AppState, cmd: &CommandInteraction) -> Result<String, CommandError> {
+    let config = state.config.current();
+    thread_parent_id(cmd)
+        .and_then(|parent| config.channel_config(parent))
+        .map(|c| c.linear_team_id.clone())
+        .ok_or_else(|| {
+            CommandError::User("Use this command inside a thread in a monitored forum.".into())
+        })
+}
+
+/// Autocomplete choices for the `(id, name)` pairs whose name contains `partial`.
+pub fn name_choices(pairs: &[(String, String)], partial: &str) -> Vec<AutocompleteChoice> {
+    let needle = partial.to_lowercase();
+    pairs
+        .iter()
+        .filter(|(_, name)| name.to_lowercase().contains(&needle))
+        .take(MAX_CHOICES)
+        .map(|(id, name)| AutocompleteChoice::new(name.clone(), id.clone()))
+        .collect()
+}
+
+/// The pair an autocomplete-selected ID names, or else the one named `input`, ignoring case.
+pub fn resolve_name<'a>(
+    pairs: &'a [(String, String)],
+    input: &str,
+) -> Option<&'a (String, String)> {
+    pairs.iter().find(|(id, _)| id == input).or_else(|| {
+        pairs
+            .iter()
+            .find(|(_, name)| name.eq_ignore_ascii_case(input))
+    })
+}
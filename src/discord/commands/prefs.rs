@@ -0,0 +1,77 @@
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommandOption, EditInteractionResponse,
+    ResolvedOption,
+};
+use tracing::info;
+
+use crate::db::{self, NotificationPrefs};
+use crate::discord::commands::{bool_option, text_response, CommandError};
+use crate::discord::handler::AppState;
+
+pub fn register() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        "prefs",
+        "Show or change how the bot notifies you",
+    )
+    .add_sub_option(CreateCommandOption::new(
+        CommandOptionType::Boolean,
+        "dm-on-assignment",
+        "DM me when a Linear issue is assigned to me (needs /linear connect)",
+    ))
+    .add_sub_option(CreateCommandOption::new(
+        CommandOptionType::Boolean,
+        "status-changes-only",
+        "Only DM me about status changes on issues I'm subscribed to, not comments",
+    ))
+    .add_sub_option(CreateCommandOption::new(
+        CommandOptionType::Boolean,
+        "mute",
+        "No DMs at all, and don't mention me when I'm assigned an issue",
+    ))
+}
+
+pub async fn run(
+    _ctx: &Context,
+    state: &AppState,
+    cmd: &CommandInteraction,
+    options: &[ResolvedOption<'_>],
+) -> Result<EditInteractionResponse, CommandError> {
+    let user_id = cmd.user.id.to_string();
+    let current = db::get_notification_prefs(&state.pool, &user_id).await?;
+    let prefs = NotificationPrefs {
+        dm_on_assignment: bool_option(options, "dm-on-assignment")
+            .unwrap_or(current.dm_on_assignment),
+        status_changes_only: bool_option(options, "status-changes-only")
+            .unwrap_or(current.status_changes_only),
+        muted: bool_option(options, "mute").unwrap_or(current.muted),
+    };
+
+    let heading = if prefs == current {
+        "Your notification settings:"
+    } else {
+        db::upsert_notification_prefs(&state.pool, &user_id, &prefs).await?;
+        info!(user = %cmd.user.id, ?prefs, "Updated notification preferences");
+        "Updated your notification settings:"
+    };
+
+    Ok(text_response(format!("{heading}\n{}", describe(&prefs))))
+}
+
+fn describe(prefs: &NotificationPrefs) -> String {
+    if prefs.muted {
+        return "- Muted: no DMs, and you're named rather than mentioned when assigned an issue"
+            .to_string();
+    }
+    let assignment = if prefs.dm_on_assignment {
+        "- DM when you're assigned an issue"
+    } else {
+        "- No DM when you're assigned an issue"
+    };
+    let subscriptions = if prefs.status_changes_only {
+        "- Subscribed issues: status changes only"
+    } else {
+        "- Subscribed issues: status changes and comments"
+    };
+    format!("{assignment}\n{subscriptions}")
+}
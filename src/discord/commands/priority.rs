@@ -0,0 +1,76 @@
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommandOption, EditInteractionResponse,
+    ResolvedOption, ResolvedValue,
+};
+use tracing::info;
+
+use crate::db;
+use crate::discord::commands::{text_response, CommandError};
+use crate::discord::handler::AppState;
+use crate::linear::api::LinearApi;
+use crate::linear::client::priority_label;
+
+pub fn register() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        "priority",
+        "Set the priority of the linked Linear issue",
+    )
+    .add_sub_option(
+        CreateCommandOption::new(CommandOptionType::Integer, "level", "Priority level")
+            .required(true)
+            .add_int_choice("Urgent", 1)
+            .add_int_choice("High", 2)
+            .add_int_choice("Medium", 3)
+            .add_int_choice("Low", 4)
+            .add_int_choice("None", 0),
+    )
+}
+
+pub async fn run(
+    _ctx: &Context,
+    state: &AppState,
+    cmd: &CommandInteraction,
+    options: &[ResolvedOption<'_>],
+) -> Result<EditInteractionResponse, CommandError> {
+    let priority = options
+        .iter()
+        .find_map(|o| match o.value {
+            ResolvedValue::Integer(v) if o.name == "level" => Some(v),
+            _ => None,
+        })
+        .filter(|p| (0..=4).contains(p))
+        .ok_or_else(|| CommandError::User("Pick a priority level.".into()))?;
+
+    let mapping = db::get_mapping_by_discord_thread(&state.pool, &cmd.channel_id.to_string())
+        .await?
+        .ok_or_else(|| CommandError::User("This thread isn't linked to a Linear issue.".into()))?;
+
+    state
+        .linear_client
+        .update_issue_priority(&mapping.linear_issue_id, priority)
+        .await?;
+    db::insert_audit_entry(
+        &state.pool,
+        &mapping.discord_thread_id,
+        &mapping.linear_identifier,
+        "priority_changed",
+        Some(&cmd.user.id.to_string()),
+        priority_label(priority),
+    )
+    .await?;
+
+    info!(
+        identifier = %mapping.linear_identifier,
+        priority,
+        user = %cmd.user.id,
+        "Updated Linear priority from Discord"
+    );
+
+    Ok(text_response(format!(
+        "**{}** priority set to **{}** by <@{}>",
+        mapping.linear_identifier,
+        priority_label(priority),
+        cmd.user.id
+    )))
+}
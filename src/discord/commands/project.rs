@@ -0,0 +1,81 @@
+use serenity::all::{
+    AutocompleteChoice, CommandInteraction, CommandOptionType, Context, CreateCommandOption,
+    EditInteractionResponse, ResolvedOption,
+};
+use tracing::info;
+
+use crate::db;
+use crate::discord::commands::{
+    name_choices, resolve_name, string_option, text_response, thread_team_id, CommandError,
+};
+use crate::discord::handler::AppState;
+use crate::linear::api::LinearApi;
+
+pub fn register() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        "project",
+        "Move the linked Linear issue to another project",
+    )
+    .add_sub_option(
+        CreateCommandOption::new(CommandOptionType::String, "project", "Linear project")
+            .required(true)
+            .set_autocomplete(true),
+    )
+}
+
+pub async fn run(
+    _ctx: &Context,
+    state: &AppState,
+    cmd: &CommandInteraction,
+    options: &[ResolvedOption<'_>],
+) -> Result<EditInteractionResponse, CommandError> {
+    let input = string_option(options, "project")
+        .map(str::trim)
+        .ok_or_else(|| CommandError::User("Pick a project.".into()))?;
+
+    let mapping = db::get_mapping_by_discord_thread(&state.pool, &cmd.channel_id.to_string())
+        .await?
+        .ok_or_else(|| CommandError::User("This thread isn't linked to a Linear issue.".into()))?;
+
+    let team_id = thread_team_id(state, cmd)?;
+    let metadata = state.metadata.team(&state.linear_client, &team_id).await?;
+    let (project_id, project_name) = resolve_name(&metadata.projects, input)
+        .ok_or_else(|| CommandError::User(format!("No Linear project matches \"{input}\".")))?;
+
+    state
+        .linear_client
+        .update_issue_project(&mapping.linear_issue_id, project_id)
+        .await?;
+    db::insert_audit_entry(
+        &state.pool,
+        &mapping.discord_thread_id,
+        &mapping.linear_identifier,
+        "project_changed",
+        Some(&cmd.user.id.to_string()),
+        project_name,
+    )
+    .await?;
+
+    info!(
+        identifier = %mapping.linear_identifier,
+        project = %project_name,
+        user = %cmd.user.id,
+        "Updated Linear project from Discord"
+    );
+
+    Ok(text_response(format!(
+        "**{}** moved to **{project_name}** by <@{}>",
+        mapping.linear_identifier, cmd.user.id
+    )))
+}
+
+pub async fn autocomplete(
+    state: &AppState,
+    cmd: &CommandInteraction,
+    partial: &str,
+) -> Result<Vec<AutocompleteChoice>, CommandError> {
+    let team_id = thread_team_id(state, cmd)?;
+    let metadata = state.metadata.team(&state.linear_client, &team_id).await?;
+    Ok(name_choices(&metadata.projects, partial))
+}
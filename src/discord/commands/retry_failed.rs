@@ -0,0 +1,49 @@
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommandOption, EditInteractionResponse,
+    ResolvedOption,
+};
+use tracing::info;
+
+use crate::db;
+use crate::discord::commands::{text_response, CommandError};
+use crate::discord::handler::AppState;
+
+pub fn register() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        "retry-failed",
+        "Queue sync operations that gave up for another round of retries",
+    )
+}
+
+pub async fn run(
+    _ctx: &Context,
+    state: &AppState,
+    cmd: &CommandInteraction,
+    _options: &[ResolvedOption<'_>],
+) -> Result<EditInteractionResponse, CommandError> {
+    // The dead letters are every server's; in multi-tenant mode only the operator's servers (those
+    // with `CHANNELS` entries) may retry them.
+    let operator = cmd
+        .guild_id
+        .is_some_and(|g| state.config.in_env_guild(g.get()));
+    if state.config.current().multi_tenant && !operator {
+        return Err(CommandError::User(
+            "Failed sync operations can only be retried by the bot's operator.".into(),
+        ));
+    }
+
+    let requeued = db::requeue_dead_letters(&state.pool).await?;
+    if requeued == 0 {
+        return Err(CommandError::User(
+            "There are no failed sync operations to retry.".into(),
+        ));
+    }
+
+    info!(user = %cmd.user.id, requeued, "Requeued dead-lettered sync operations");
+
+    Ok(text_response(format!(
+        "Queued {requeued} failed sync operation{} for retry on the next poll.",
+        if requeued == 1 { "" } else { "s" }
+    )))
+}
@@ -0,0 +1,81 @@
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommandOption, CreateEmbed,
+    EditInteractionResponse, ResolvedOption,
+};
+
+use crate::discord::commands::{string_option, thread_parent_id, CommandError};
+use crate::discord::handler::AppState;
+use crate::linear::api::LinearApi;
+
+const MAX_RESULTS: usize = 10;
+
+pub fn register() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        "search",
+        "Search Linear for existing issues",
+    )
+    .add_sub_option(
+        CreateCommandOption::new(CommandOptionType::String, "query", "Text to search for")
+            .required(true)
+            .min_length(2),
+    )
+}
+
+pub async fn run(
+    _ctx: &Context,
+    state: &AppState,
+    cmd: &CommandInteraction,
+    options: &[ResolvedOption<'_>],
+) -> Result<EditInteractionResponse, CommandError> {
+    let term = string_option(options, "query")
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .ok_or_else(|| CommandError::User("Search text can't be empty.".into()))?;
+
+    // Scope to the forum's team inside a monitored thread, otherwise every tracked team (only
+    // this server's in multi-tenant mode).
+    let config = state.config.current();
+    let team_ids = match thread_parent_id(cmd).and_then(|p| config.channel_config(p)) {
+        Some(c) => vec![c.linear_team_id.clone()],
+        None if config.multi_tenant => {
+            let guild_id = cmd.guild_id.map(|g| g.get());
+            let mut ids: Vec<String> = config
+                .channels
+                .iter()
+                .filter(|c| Some(c.guild_id) == guild_id)
+                .map(|c| c.linear_team_id.clone())
+                .collect();
+            ids.sort();
+            ids.dedup();
+            ids
+        }
+        None => config.unique_team_ids(),
+    };
+
+    let results = state
+        .linear_client
+        .search_issues(term, &team_ids, MAX_RESULTS)
+        .await?;
+
+    let description = if results.is_empty() {
+        "No matching issues.".to_string()
+    } else {
+        results
+            .iter()
+            .map(|r| {
+                format!(
+                    "[{}]({}) {} · *{}*",
+                    r.identifier, r.url, r.title, r.status_name
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let embed = CreateEmbed::new()
+        .title(format!("Linear issues matching \"{term}\""))
+        .description(description);
+
+    Ok(EditInteractionResponse::new().embed(embed))
+}
@@ -0,0 +1,216 @@
+//! `/linear setup`: connect a server's own Linear workspace and sync a forum channel with it.
+//!
+//! The API key is stored encrypted as the server's credential (`guild-<id>`), so later runs can
+//! add more channels without it. Teams, projects, and labels are looked up by name, so admins
+//! don't need to dig up Linear IDs.
+
+use serde_json::json;
+use serenity::all::{
+    ChannelType, CommandInteraction, CommandOptionType, Context, CreateCommandOption,
+    EditInteractionResponse, ResolvedOption,
+};
+use tracing::info;
+
+use crate::db;
+use crate::discord::commands::admin::save_channel;
+use crate::discord::commands::{channel_option, string_option, text_response, CommandError};
+use crate::discord::handler::AppState;
+use crate::error::AppError;
+use crate::linear::api::LinearApi;
+
+/// Credential names starting with this are reserved for the key `/linear setup` stores, followed
+/// by the server's ID.
+pub const GUILD_CREDENTIAL_PREFIX: &str = "guild-";
+
+pub fn register() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        "setup",
+        "Connect this server's Linear workspace and sync a forum channel with it",
+    )
+    .add_sub_option(
+        CreateCommandOption::new(CommandOptionType::Channel, "channel", "Forum channel")
+            .required(true)
+            .channel_types(vec![ChannelType::Forum]),
+    )
+    .add_sub_option(
+        CreateCommandOption::new(CommandOptionType::String, "type", "Kind of posts")
+            .required(true)
+            .add_string_choice("Bug reports", "bug")
+            .add_string_choice("Feature requests", "feature"),
+    )
+    .add_sub_option(
+        CreateCommandOption::new(
+            CommandOptionType::String,
+            "team",
+            "Linear team to file issues in: its key (e.g. ENG), name, or ID",
+        )
+        .required(true),
+    )
+    .add_sub_option(
+        CreateCommandOption::new(
+            CommandOptionType::String,
+            "project",
+            "Project in that team: its name or ID",
+        )
+        .required(true),
+    )
+    .add_sub_option(CreateCommandOption::new(
+        CommandOptionType::String,
+        "label",
+        "Label for new issues: its name or ID (default: the label named like the type)",
+    ))
+    .add_sub_option(CreateCommandOption::new(
+        CommandOptionType::String,
+        "api-key",
+        "Linear API key (Settings → API). Needed the first time, or to replace the key",
+    ))
+}
+
+pub async fn run(
+    ctx: &Context,
+    state: &AppState,
+    cmd: &CommandInteraction,
+    options: &[ResolvedOption<'_>],
+) -> Result<EditInteractionResponse, CommandError> {
+    let guild_id = cmd
+        .guild_id
+        .ok_or_else(|| CommandError::User("Use this command in a server.".into()))?;
+    let keys = state.credential_keys.as_ref().ok_or_else(|| {
+        CommandError::User("This bot isn't set up to store Linear API keys.".into())
+    })?;
+    let channel = channel_option(options, "channel")
+        .ok_or_else(|| CommandError::User("Missing channel.".into()))?;
+    let argument = |name: &str| {
+        string_option(options, name)
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+    };
+    let required =
+        |name: &str| argument(name).ok_or_else(|| CommandError::User(format!("Missing {name}.")));
+    let channel_type = required("type")?;
+    let team_wanted = required("team")?;
+    let project_wanted = required("project")?;
+    let credential = format!("{GUILD_CREDENTIAL_PREFIX}{guild_id}");
+    let owner = db::get_linear_credentials(&state.pool)
+        .await?
+        .into_iter()
+        .find(|row| row.name == credential)
+        .map(|row| row.guild_id);
+    if owner.is_some_and(|owner| owner != guild_id.to_string()) {
+        return Err(CommandError::User(format!(
+            "Another server already has a Linear API key named `{credential}`; ask the bot's \
+             operator to remove it."
+        )));
+    }
+
+    let api_key = argument("api-key");
+    let linear = match api_key {
+        Some(api_key) => {
+            let client = state.linear_client.client_for_key(api_key);
+            user_error(client.viewer().await, "Linear rejected the API key")?;
+            client
+        }
+        None => state
+            .linear_client
+            .workspace(Some(&credential))
+            .ok_or_else(|| {
+                CommandError::User(
+                    "Pass your Linear API key as `api-key` the first time you run this.".into(),
+                )
+            })?,
+    };
+
+    let teams = user_error(linear.teams().await, "Couldn't list your Linear teams")?;
+    let team = teams
+        .iter()
+        .find(|t| {
+            t.id == team_wanted
+                || t.key.eq_ignore_ascii_case(team_wanted)
+                || t.name.eq_ignore_ascii_case(team_wanted)
+        })
+        .ok_or_else(|| {
+            let keys: Vec<&str> = teams.iter().map(|t| t.key.as_str()).collect();
+            CommandError::User(format!(
+                "There's no Linear team `{team_wanted}`. Your teams: {}",
+                keys.join(", ")
+            ))
+        })?;
+    let choices = user_error(
+        linear.team_choices(&team.id).await,
+        "Couldn't list the team's projects and labels",
+    )?;
+    let (project_id, project) = pick(&choices.projects, project_wanted).ok_or_else(|| {
+        CommandError::User(format!(
+            "**{}** has no project `{project_wanted}`.",
+            team.name
+        ))
+    })?;
+    let label_wanted = argument("label").unwrap_or(channel_type);
+    let (label_id, label) = pick(&choices.labels, label_wanted).ok_or_else(|| {
+        CommandError::User(format!(
+            "There's no label `{label_wanted}` for **{}**; pass one as `label`.",
+            team.name
+        ))
+    })?;
+
+    if let Some(api_key) = api_key {
+        let stored = db::upsert_linear_credential(
+            &state.pool,
+            &credential,
+            &guild_id.to_string(),
+            &keys.seal(&credential, api_key),
+            &cmd.user.id.to_string(),
+        )
+        .await?;
+        if !stored {
+            return Err(CommandError::User(format!(
+                "Another server already has a Linear API key named `{credential}`."
+            )));
+        }
+        state
+            .linear_client
+            .set_workspace(&credential, Some(linear.clone()));
+        info!(guild_id = %guild_id, user = %cmd.user.id, "Linear workspace connected");
+    }
+    save_channel(
+        ctx,
+        state,
+        cmd,
+        json!({
+            "discord_channel_id": channel.id.get(),
+            "guild_id": guild_id.get(),
+            "channel_type": channel_type,
+            "linear_team_id": team.id,
+            "linear_label_id": label_id,
+            "linear_project_id": project_id,
+            "linear_credential": credential,
+        }),
+    )
+    .await?;
+
+    Ok(text_response(format!(
+        "<#{}> now files {channel_type} posts in **{}** with label **{label}** in project \
+         **{project}**. Existing posts are being backfilled. Run `/linear setup` again to \
+         connect more forums.",
+        channel.id, team.name
+    )))
+}
+
+/// The choice whose ID is `wanted`, or else whose name matches it ignoring case.
+fn pick<'a>(choices: &'a [(String, String)], wanted: &str) -> Option<&'a (String, String)> {
+    choices.iter().find(|(id, _)| id == wanted).or_else(|| {
+        choices
+            .iter()
+            .find(|(_, name)| name.eq_ignore_ascii_case(wanted))
+    })
+}
+
+/// `result`, with a Linear API error turned into a user error prefixed with `context`.
+fn user_error<T>(result: Result<T, AppError>, context: &str) -> Result<T, CommandError> {
+    match result {
+        Ok(value) => Ok(value),
+        Err(AppError::LinearApi(e)) => Err(CommandError::User(format!("{context}: {e}"))),
+        Err(e) => Err(e.into()),
+    }
+}
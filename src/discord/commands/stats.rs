@@ -0,0 +1,131 @@
+use std::sync::atomic::Ordering;
+
+use chrono::NaiveDateTime;
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommandOption, CreateEmbed,
+    EditInteractionResponse, ResolvedOption,
+};
+
+use crate::db;
+use crate::discord::commands::CommandError;
+use crate::discord::handler::AppState;
+
+/// Window for the sync failure count.
+const FAILURE_WINDOW_SECS: i64 = 24 * 60 * 60;
+
+pub fn register() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        "stats",
+        "Show sync statistics for the bot",
+    )
+}
+
+pub async fn run(
+    _ctx: &Context,
+    state: &AppState,
+    cmd: &CommandInteraction,
+    _options: &[ResolvedOption<'_>],
+) -> Result<EditInteractionResponse, CommandError> {
+    let guild_id = cmd
+        .guild_id
+        .ok_or_else(|| CommandError::User("Use this command in a server.".into()))?;
+    // In multi-tenant mode a server only sees its own issues and teams. Failures and webhooks
+    // aren't tracked per server, so only the operator's servers see those.
+    let config = state.config.current();
+    let scoped = config.multi_tenant;
+    let operator = !scoped || state.config.in_env_guild(guild_id.get());
+    let guild = guild_id.to_string();
+    let scope = scoped.then_some(guild.as_str());
+
+    let pool = &state.pool;
+    let mappings = db::count_mappings(pool, scope).await?;
+    let by_status = db::count_mappings_by_status(pool, scope).await?;
+    let posted = db::count_audit_actions(pool, "comment_posted", scope).await?;
+    let relayed = db::count_audit_actions(pool, "comment_relayed", scope).await?;
+    let cursors = db::get_poll_cursors(pool).await?;
+
+    let statuses = if by_status.is_empty() {
+        "None".to_string()
+    } else {
+        by_status
+            .iter()
+            .map(|(status, count)| format!("{}: {count}", status.as_deref().unwrap_or("Unknown")))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    // Teams that were configured but haven't completed a poll yet are listed too.
+    let team_ids = if scoped {
+        let mut team_ids: Vec<String> = config
+            .channels
+            .iter()
+            .filter(|c| c.guild_id == guild_id.get())
+            .map(|c| c.linear_team_id.clone())
+            .collect();
+        team_ids.sort();
+        team_ids.dedup();
+        team_ids
+    } else {
+        config.unique_team_ids()
+    };
+    let polls = team_ids
+        .iter()
+        .map(|team_id| {
+            let last = cursors
+                .iter()
+                .find(|c| &c.linear_team_id == team_id)
+                .map_or_else(|| "never".to_string(), |c| relative_time(&c.updated_at));
+            format!("`{team_id}`: {last}")
+        })
+        .collect::<Vec<_>>();
+    let polls = if polls.is_empty() {
+        "No teams configured".to_string()
+    } else {
+        polls.join("\n")
+    };
+
+    let mut embed = CreateEmbed::new().title("Sync statistics").field(
+        "Tracked issues",
+        mappings.to_string(),
+        true,
+    );
+    if operator {
+        let failures = db::count_sync_failures_since(pool, FAILURE_WINDOW_SECS).await?;
+        embed = embed.field("Sync failures (24h)", failures.to_string(), true);
+    }
+    embed = embed
+        .field(
+            "Up since",
+            format!("<t:{}:R>", state.started_at.timestamp()),
+            true,
+        )
+        .field("Issues by status", statuses, false)
+        .field(
+            "Comments synced",
+            format!("Discord → Linear: {posted}\nLinear → Discord: {relayed}"),
+            false,
+        )
+        .field("Last poll per team", polls, false);
+    if operator && config.webhook_listen_addr.is_some() {
+        let stats = &state.webhook_stats;
+        embed = embed.field(
+            "Webhooks since startup",
+            format!(
+                "{} accepted, {} rejected",
+                stats.accepted.load(Ordering::Relaxed),
+                stats.rejected.load(Ordering::Relaxed)
+            ),
+            false,
+        );
+    }
+
+    Ok(EditInteractionResponse::new().embed(embed))
+}
+
+/// A SQLite `datetime('now')` timestamp as a Discord relative timestamp.
+fn relative_time(sqlite_time: &str) -> String {
+    NaiveDateTime::parse_from_str(sqlite_time, "%Y-%m-%d %H:%M:%S")
+        .map(|t| format!("<t:{}:R>", t.and_utc().timestamp()))
+        .unwrap_or_else(|_| sqlite_time.to_string())
+}
@@ -0,0 +1,36 @@
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommandOption, EditInteractionResponse,
+    ResolvedOption,
+};
+
+use crate::db;
+use crate::discord::commands::CommandError;
+use crate::discord::embeds::issue_embed;
+use crate::discord::handler::AppState;
+use crate::linear::api::LinearApi;
+
+pub fn register() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        "status",
+        "Show the Linear issue linked to this thread",
+    )
+}
+
+pub async fn run(
+    _ctx: &Context,
+    state: &AppState,
+    cmd: &CommandInteraction,
+    _options: &[ResolvedOption<'_>],
+) -> Result<EditInteractionResponse, CommandError> {
+    let mapping = db::get_mapping_by_discord_thread(&state.pool, &cmd.channel_id.to_string())
+        .await?
+        .ok_or_else(|| CommandError::User("This thread isn't linked to a Linear issue.".into()))?;
+
+    let issue = state
+        .linear_client
+        .get_issue(&mapping.linear_issue_id)
+        .await?;
+
+    Ok(EditInteractionResponse::new().embed(issue_embed(&issue)))
+}
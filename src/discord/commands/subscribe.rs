@@ -0,0 +1,50 @@
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommandOption, EditInteractionResponse,
+    ResolvedOption,
+};
+use tracing::info;
+
+use crate::db;
+use crate::discord::commands::{text_response, CommandError};
+use crate::discord::handler::AppState;
+
+pub fn register() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        "subscribe",
+        "Get a DM when this thread's Linear issue changes status or gets a comment",
+    )
+}
+
+pub async fn run(
+    _ctx: &Context,
+    state: &AppState,
+    cmd: &CommandInteraction,
+    _options: &[ResolvedOption<'_>],
+) -> Result<EditInteractionResponse, CommandError> {
+    let thread_id = cmd.channel_id.to_string();
+    let mapping = db::get_mapping_by_discord_thread(&state.pool, &thread_id)
+        .await?
+        .ok_or_else(|| CommandError::User("This thread isn't linked to a Linear issue.".into()))?;
+
+    let user_id = cmd.user.id.to_string();
+    if !db::insert_subscription(&state.pool, &thread_id, &user_id).await? {
+        return Err(CommandError::User(format!(
+            "You're already subscribed to **{}**.",
+            mapping.linear_identifier
+        )));
+    }
+
+    info!(
+        thread_id,
+        identifier = %mapping.linear_identifier,
+        user = %cmd.user.id,
+        "Subscribed to issue"
+    );
+
+    Ok(text_response(format!(
+        "You'll get a DM when **{}** changes status or gets a comment. Use `/linear unsubscribe` \
+         to stop. Make sure you allow DMs from members of this server.",
+        mapping.linear_identifier
+    )))
+}
@@ -0,0 +1,48 @@
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommandOption, EditInteractionResponse,
+    ResolvedOption,
+};
+use tracing::info;
+
+use crate::db;
+use crate::discord::commands::{text_response, CommandError};
+use crate::discord::handler::AppState;
+
+pub fn register() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        "unlink",
+        "Stop syncing this thread with its Linear issue",
+    )
+}
+
+pub async fn run(
+    _ctx: &Context,
+    state: &AppState,
+    cmd: &CommandInteraction,
+    _options: &[ResolvedOption<'_>],
+) -> Result<EditInteractionResponse, CommandError> {
+    let mapping = db::get_mapping_by_discord_thread(&state.pool, &cmd.channel_id.to_string())
+        .await?
+        .ok_or_else(|| CommandError::User("This thread isn't linked to a Linear issue.".into()))?;
+
+    db::tombstone_mapping(
+        &state.pool,
+        &mapping,
+        "unlinked",
+        Some(&cmd.user.id.to_string()),
+    )
+    .await?;
+
+    info!(
+        thread_id = %mapping.discord_thread_id,
+        identifier = %mapping.linear_identifier,
+        user = %cmd.user.id,
+        "Unlinked Discord thread from Linear issue"
+    );
+
+    Ok(text_response(format!(
+        "<@{}> unlinked this thread from **{}**. Status changes and comments will no longer sync.",
+        cmd.user.id, mapping.linear_identifier
+    )))
+}
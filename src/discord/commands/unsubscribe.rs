@@ -0,0 +1,37 @@
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommandOption, EditInteractionResponse,
+    ResolvedOption,
+};
+use tracing::info;
+
+use crate::db;
+use crate::discord::commands::{text_response, CommandError};
+use crate::discord::handler::AppState;
+
+pub fn register() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::SubCommand,
+        "unsubscribe",
+        "Stop DMs about this thread's Linear issue",
+    )
+}
+
+pub async fn run(
+    _ctx: &Context,
+    state: &AppState,
+    cmd: &CommandInteraction,
+    _options: &[ResolvedOption<'_>],
+) -> Result<EditInteractionResponse, CommandError> {
+    let thread_id = cmd.channel_id.to_string();
+    if !db::delete_subscription(&state.pool, &thread_id, &cmd.user.id.to_string()).await? {
+        return Err(CommandError::User(
+            "You aren't subscribed to this thread's issue.".into(),
+        ));
+    }
+
+    info!(thread_id, user = %cmd.user.id, "Unsubscribed from issue");
+
+    Ok(text_response(
+        "You'll no longer get DMs about this thread's issue.",
+    ))
+}
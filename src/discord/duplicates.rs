@@ -0,0 +1,276 @@
+//! Duplicate suggestions shown before an issue is created.
+//!
+//! For channels with `suggest_duplicates` enabled, a new thread's title is searched in the
+//! channel's Linear team first. If similar issues exist, the thread gets a prompt listing them,
+//! with a "Link to ABC-123" button for each and a "Create anyway" button; otherwise the issue is
+//! created as usual. The check is persisted in `duplicate_checks`, so buttons keep working across
+//! restarts and the reconcile pass creates the issue once `duplicate_timeout_secs` passes.
+//! Channels with an intake form show it after "Create anyway".
+
+use serenity::all::{
+    ButtonStyle, Channel, ChannelId, ComponentInteraction, Context, CreateActionRow, CreateButton,
+    CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage,
+    EditMessage, GuildChannel, MessageId, UserId,
+};
+use sqlx::SqlitePool;
+use tracing::{error, info, warn};
+
+use crate::config::ChannelConfig;
+use crate::db;
+use crate::discord::commands::link::link_thread;
+use crate::discord::commands::CommandError;
+use crate::discord::handler::AppState;
+use crate::discord::port::DiscordPort;
+use crate::error::AppError;
+use crate::linear::api::LinearApi;
+use crate::sync::discord_to_linear::sync_discord_to_linear;
+
+pub const CUSTOM_ID_PREFIX: &str = "duplicate:";
+const CREATE: &str = "duplicate:create:";
+const LINK: &str = "duplicate:link:";
+
+/// Matches offered; with "Create anyway" their buttons fill one row.
+const MAX_SUGGESTIONS: usize = 4;
+
+/// Search Linear for issues like the new thread and, if there are any, post the prompt and record
+/// it as waiting. Returns whether the prompt was posted; if not, the issue should be created.
+pub async fn suggest(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    channel_config: &ChannelConfig,
+    linear: &impl LinearApi,
+    thread: &GuildChannel,
+) -> Result<bool, AppError> {
+    let thread_id = thread.id.to_string();
+    let author = thread
+        .owner_id
+        .ok_or_else(|| AppError::Internal("Thread has no owner".into()))?;
+
+    // A failed search shouldn't hold up the report; it's filed as if nothing matched.
+    let team_ids = [channel_config.linear_team_id.clone()];
+    let matches = match linear
+        .search_issues(&thread.name, &team_ids, MAX_SUGGESTIONS)
+        .await
+    {
+        Ok(matches) => matches,
+        Err(e) => {
+            warn!(thread_id, error = %e, "Duplicate search failed, creating the issue");
+            Vec::new()
+        }
+    };
+    if matches.is_empty() {
+        db::insert_duplicate_check(pool, &thread_id, &author.to_string(), None, Some("none"))
+            .await?;
+        return Ok(false);
+    }
+
+    let listed = matches
+        .iter()
+        .map(|m| {
+            format!(
+                "[{}]({}) {} · *{}*",
+                m.identifier, m.url, m.title, m.status_name
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut buttons: Vec<CreateButton> = matches
+        .iter()
+        .map(|m| {
+            CreateButton::new(format!("{LINK}{thread_id}:{}", m.identifier))
+                .label(format!("Link to {}", m.identifier))
+                .style(ButtonStyle::Secondary)
+        })
+        .collect();
+    buttons.push(
+        CreateButton::new(format!("{CREATE}{thread_id}"))
+            .label("Create anyway")
+            .style(ButtonStyle::Primary),
+    );
+
+    let message = CreateMessage::new()
+        .content(format!(
+            "<@{author}> this may already be tracked in Linear. Link your post to one of these \
+             issues to follow it there, or create a new one."
+        ))
+        .embed(
+            CreateEmbed::new()
+                .title("Similar issues")
+                .description(listed),
+        )
+        .components(vec![CreateActionRow::Buttons(buttons)]);
+
+    let sent = discord.send_message(thread.id, message).await?;
+    db::insert_duplicate_check(
+        pool,
+        &thread_id,
+        &author.to_string(),
+        Some(&sent.id.to_string()),
+        None,
+    )
+    .await?;
+
+    info!(
+        thread_id,
+        matches = matches.len(),
+        "Posted duplicate suggestions"
+    );
+    Ok(true)
+}
+
+pub async fn handle_component(ctx: &Context, state: &AppState, component: &ComponentInteraction) {
+    let custom_id = component.data.custom_id.as_str();
+    let (thread_id, identifier) = if let Some(id) = custom_id.strip_prefix(CREATE) {
+        (id, None)
+    } else if let Some((id, identifier)) = custom_id
+        .strip_prefix(LINK)
+        .and_then(|rest| rest.split_once(':'))
+    {
+        (id, Some(identifier))
+    } else {
+        return;
+    };
+
+    if let Err(message) = authorize(state, thread_id, component).await {
+        let reply = CreateInteractionResponseMessage::new()
+            .content(message)
+            .ephemeral(true);
+        if let Err(e) = component
+            .create_response(&ctx.http, CreateInteractionResponse::Message(reply))
+            .await
+        {
+            warn!(error = %e, "Failed to send duplicate prompt reply");
+        }
+        return;
+    }
+
+    if let Err(e) = component
+        .create_response(&ctx.http, CreateInteractionResponse::Acknowledge)
+        .await
+    {
+        warn!(thread_id, error = %e, "Failed to acknowledge duplicate prompt");
+    }
+    if let Err(e) = answer(ctx, state, thread_id, identifier, component.user.id).await {
+        error!(thread_id, error = %e, "Failed to act on duplicate prompt");
+    }
+}
+
+/// Only the thread author or members who can manage threads may answer the prompt.
+async fn authorize(
+    state: &AppState,
+    thread_id: &str,
+    component: &ComponentInteraction,
+) -> Result<(), String> {
+    let check = match db::get_duplicate_check(&state.pool, thread_id).await {
+        Ok(Some(check)) if check.outcome.is_none() => check,
+        Ok(_) => return Err("This post has already been filed.".into()),
+        Err(e) => {
+            error!(thread_id, error = %e, "Failed to load duplicate check");
+            return Err("Something went wrong; try again later.".into());
+        }
+    };
+
+    let is_moderator = component
+        .member
+        .as_ref()
+        .and_then(|m| m.permissions)
+        .map(|p| p.manage_threads() || p.administrator())
+        .unwrap_or(false);
+
+    if check.author_discord_user_id == component.user.id.to_string() || is_moderator {
+        Ok(())
+    } else {
+        Err("Only the author of this post can choose.".into())
+    }
+}
+
+/// Link the thread to the chosen issue, or with no `identifier` create one.
+async fn answer(
+    ctx: &Context,
+    state: &AppState,
+    thread_id: &str,
+    identifier: Option<&str>,
+    user: UserId,
+) -> Result<(), CommandError> {
+    let Some(check) = db::get_duplicate_check(&state.pool, thread_id).await? else {
+        return Ok(());
+    };
+    // Claim the prompt; a double click or a concurrent timeout loses the race here.
+    let outcome = if identifier.is_some() {
+        "link"
+    } else {
+        "create"
+    };
+    if !db::resolve_duplicate_check(&state.pool, thread_id, outcome).await? {
+        return Ok(());
+    }
+
+    let thread_channel_id: ChannelId = thread_id
+        .parse::<u64>()
+        .map(ChannelId::new)
+        .map_err(|_| AppError::Internal("Invalid thread id in duplicate custom_id".into()))?;
+
+    // Strip the buttons so the prompt can't be used again.
+    if let Some(prompt_id) = check
+        .prompt_message_id
+        .and_then(|id| id.parse::<u64>().ok())
+    {
+        let edit = EditMessage::new().components(Vec::new());
+        if let Err(e) = thread_channel_id
+            .edit_message(&ctx.http, MessageId::new(prompt_id), edit)
+            .await
+        {
+            warn!(thread_id, error = %e, "Failed to remove duplicate prompt buttons");
+        }
+    }
+
+    let thread = match thread_channel_id.to_channel(&ctx.http).await? {
+        Channel::Guild(gc) => gc,
+        _ => {
+            return Err(
+                AppError::Internal("Duplicate prompt thread is not a guild channel".into()).into(),
+            )
+        }
+    };
+    let config = state.config.current();
+    let channel_config = thread
+        .parent_id
+        .and_then(|p| config.channel_config(p.get()))
+        .ok_or_else(|| {
+            AppError::Internal("Duplicate prompt thread is not in a monitored forum".into())
+        })?;
+
+    let Some(identifier) = identifier else {
+        sync_discord_to_linear(
+            &state.discord(&ctx.http),
+            &state.pool,
+            channel_config,
+            &state.linear_client,
+            &thread,
+        )
+        .await?;
+        return Ok(());
+    };
+
+    // Identifiers are only unique within a workspace, so look in the forum's.
+    let issue = state
+        .linear_client
+        .for_channel(channel_config)
+        .get_issue(identifier)
+        .await?;
+    if let Some(existing) = db::get_mapping_by_linear_issue(&state.pool, &issue.id).await? {
+        // Already discussed in another thread; point there rather than create a second issue.
+        thread_channel_id
+            .say(
+                &ctx.http,
+                format!(
+                    "**{}** is already being discussed in <#{}>; follow along there.",
+                    issue.identifier, existing.discord_thread_id
+                ),
+            )
+            .await?;
+        info!(thread_id, identifier = %issue.identifier, "Post marked as a duplicate");
+        return Ok(());
+    }
+    link_thread(ctx, state, channel_config, thread_channel_id, &issue, user).await
+}
@@ -0,0 +1,40 @@
+use serenity::all::{Colour, CreateEmbed, Timestamp};
+
+use crate::linear::client::LinearIssueDetail;
+
+/// Rich summary of a Linear issue: state, assignee, priority, labels, project, last update.
+pub fn issue_embed(issue: &LinearIssueDetail) -> CreateEmbed {
+    let mut embed = CreateEmbed::new()
+        .title(format!("{}: {}", issue.identifier, issue.title))
+        .url(&issue.url)
+        .colour(state_colour(&issue.status_color))
+        .field("Status", &issue.status_name, true)
+        .field(
+            "Assignee",
+            issue.assignee_name.as_deref().unwrap_or("Unassigned"),
+            true,
+        )
+        .field("Priority", &issue.priority_label, true)
+        .field(
+            "Project",
+            issue.project_name.as_deref().unwrap_or("None"),
+            true,
+        );
+
+    if !issue.labels.is_empty() {
+        embed = embed.field("Labels", issue.labels.join(", "), true);
+    }
+
+    if let Ok(updated) = Timestamp::parse(&issue.updated_at) {
+        embed = embed.timestamp(updated);
+    }
+
+    embed
+}
+
+/// Parse Linear's `#rrggbb` state color, falling back to Discord's default embed color.
+fn state_colour(hex: &str) -> Colour {
+    u32::from_str_radix(hex.trim_start_matches('#'), 16)
+        .map(Colour::new)
+        .unwrap_or_default()
+}
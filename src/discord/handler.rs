@@ -1,16 +1,55 @@
-use serenity::all::{Context, EventHandler, GuildChannel, Ready};
+use chrono::{DateTime, Utc};
+use serenity::all::{
+    Context, EventHandler, Guild, GuildChannel, GuildId, Interaction, Message, MessageUpdateEvent,
+    PartialGuildChannel, Reaction, Ready,
+};
+use std::sync::Arc;
+
 use serenity::async_trait;
+use serenity::http::Http;
 use sqlx::SqlitePool;
-use tracing::{error, info};
+use tracing::{error, info, info_span, warn, Instrument};
 
-use crate::config::Config;
-use crate::linear::client::LinearClient;
-use crate::sync::discord_to_linear::sync_discord_to_linear;
+use crate::config::SharedConfig;
+use crate::credentials::CredentialKeys;
+use crate::discord::{approval, commands, duplicates, intake, subscriptions, triage};
+use crate::linear::cache::MetadataCache;
+use crate::linear::webhook::WebhookStats;
+use crate::linear::workspaces::LinearWorkspaces;
+use crate::shadow::{ShadowLog, Shadowed};
+use crate::shutdown::Shutdown;
+use crate::sync::backfill::RunningBackfills;
+use crate::sync::discord_to_linear::{
+    sync_discord_to_linear, sync_reply_to_linear, sync_starter_message_edit, sync_thread_delete,
+    sync_thread_rename,
+};
+use crate::sync::outbox::{self, Operation};
 
 pub struct AppState {
-    pub config: Config,
+    pub config: SharedConfig,
     pub pool: SqlitePool,
-    pub linear_client: LinearClient,
+    pub linear_client: Shadowed<LinearWorkspaces>,
+    /// Team labels, projects, and members offered by autocomplete.
+    pub metadata: MetadataCache,
+    /// Encrypts the Linear API keys added with `/linear admin`; `None` without `CREDENTIAL_KEY`.
+    pub credential_keys: Option<CredentialKeys>,
+    /// Events are ignored once shutdown starts; the ones being handled are waited for.
+    pub shutdown: Shutdown,
+    /// Backfills started with `/linear backfill`.
+    pub backfills: RunningBackfills,
+    /// Set in shadow mode; see `shadow`.
+    pub shadow: Option<ShadowLog>,
+    /// When the bot started, for the uptime in `/linear stats`.
+    pub started_at: DateTime<Utc>,
+    /// Deliveries to the Linear webhook receiver since startup.
+    pub webhook_stats: WebhookStats,
+}
+
+impl AppState {
+    /// Discord through `http`, with writes skipped in shadow mode.
+    pub fn discord(&self, http: &Arc<Http>) -> Shadowed<Http> {
+        Shadowed::new(http.clone(), self.shadow.clone())
+    }
 }
 
 pub struct Handler;
@@ -38,6 +77,9 @@ impl EventHandler for Handler {
                 return;
             }
         };
+        let Some(_task) = state.shutdown.start_task() else {
+            return;
+        };
 
         // Check if thread is in a monitored forum channel
         let parent_id = match thread.parent_id {
@@ -45,7 +87,8 @@ impl EventHandler for Handler {
             None => return,
         };
 
-        let channel_config = match state.config.channel_config(parent_id) {
+        let config = state.config.current();
+        let channel_config = match config.channel_config(parent_id) {
             Some(c) => c,
             None => return,
         };
@@ -59,12 +102,13 @@ impl EventHandler for Handler {
         );
 
         if let Err(e) = sync_discord_to_linear(
-            &ctx.http,
+            &state.discord(&ctx.http),
             &state.pool,
             channel_config,
             &state.linear_client,
             &thread,
         )
+        .instrument(info_span!("thread_create", thread_id = %thread.id, parent_id))
         .await
         {
             error!(
@@ -72,10 +116,307 @@ impl EventHandler for Handler {
                 error = %e,
                 "Failed to sync thread to Linear"
             );
+            let operation = Operation::CreateIssue {
+                thread_id: thread.id.get(),
+            };
+            outbox::enqueue(&state.pool, &operation, &e).await;
+        }
+    }
+
+    async fn thread_update(&self, ctx: Context, _old: Option<GuildChannel>, thread: GuildChannel) {
+        let state = match Self::get_state(&ctx).await {
+            Some(s) => s,
+            None => {
+                error!("AppState not found in TypeMap");
+                return;
+            }
+        };
+        let Some(_task) = state.shutdown.start_task() else {
+            return;
+        };
+
+        if let Err(e) = sync_thread_rename(&state.pool, &state.linear_client, &thread).await {
+            error!(
+                thread_id = %thread.id,
+                error = %e,
+                "Failed to sync thread rename to Linear"
+            );
+        }
+    }
+
+    async fn thread_delete(
+        &self,
+        ctx: Context,
+        thread: PartialGuildChannel,
+        _full_thread_data: Option<GuildChannel>,
+    ) {
+        let state = match Self::get_state(&ctx).await {
+            Some(s) => s,
+            None => {
+                error!("AppState not found in TypeMap");
+                return;
+            }
+        };
+        let Some(_task) = state.shutdown.start_task() else {
+            return;
+        };
+
+        if let Err(e) = sync_thread_delete(
+            &state.pool,
+            &state.config.current(),
+            &state.linear_client,
+            thread.id,
+            thread.parent_id,
+        )
+        .await
+        {
+            error!(
+                thread_id = %thread.id,
+                error = %e,
+                "Failed to handle thread deletion"
+            );
+        }
+    }
+
+    async fn message(&self, ctx: Context, msg: Message) {
+        if msg.author.bot {
+            return;
+        }
+
+        let state = match Self::get_state(&ctx).await {
+            Some(s) => s,
+            None => {
+                error!("AppState not found in TypeMap");
+                return;
+            }
+        };
+        let Some(_task) = state.shutdown.start_task() else {
+            return;
+        };
+
+        if let Err(e) = sync_reply_to_linear(
+            &state.discord(&ctx.http),
+            &state.pool,
+            &state.config.current(),
+            &state.linear_client,
+            &msg,
+        )
+        .await
+        {
+            error!(
+                message_id = %msg.id,
+                thread_id = %msg.channel_id,
+                error = %e,
+                "Failed to sync reply to Linear"
+            );
+        }
+    }
+
+    async fn reaction_add(&self, ctx: Context, reaction: Reaction) {
+        let state = match Self::get_state(&ctx).await {
+            Some(s) => s,
+            None => {
+                error!("AppState not found in TypeMap");
+                return;
+            }
+        };
+        let Some(_task) = state.shutdown.start_task() else {
+            return;
+        };
+
+        approval::handle_reaction(&ctx, &state, &reaction).await;
+        subscriptions::handle_reaction_add(&ctx, &state, &reaction).await;
+    }
+
+    async fn reaction_remove(&self, ctx: Context, reaction: Reaction) {
+        let state = match Self::get_state(&ctx).await {
+            Some(s) => s,
+            None => {
+                error!("AppState not found in TypeMap");
+                return;
+            }
+        };
+        let Some(_task) = state.shutdown.start_task() else {
+            return;
+        };
+
+        subscriptions::handle_reaction_remove(&state, &reaction).await;
+    }
+
+    async fn message_update(
+        &self,
+        ctx: Context,
+        _old_if_available: Option<Message>,
+        _new: Option<Message>,
+        event: MessageUpdateEvent,
+    ) {
+        // Only content edits matter; embed unfurls also arrive as updates without content.
+        let content = match &event.content {
+            Some(c) => c,
+            None => return,
+        };
+
+        let state = match Self::get_state(&ctx).await {
+            Some(s) => s,
+            None => {
+                error!("AppState not found in TypeMap");
+                return;
+            }
+        };
+        let Some(_task) = state.shutdown.start_task() else {
+            return;
+        };
+
+        if let Err(e) = sync_starter_message_edit(
+            &ctx.http,
+            &state.pool,
+            &state.linear_client,
+            &event,
+            content,
+        )
+        .await
+        {
+            error!(
+                message_id = %event.id,
+                error = %e,
+                "Failed to sync message edit to Linear"
+            );
         }
     }
 
-    async fn ready(&self, _ctx: Context, ready: Ready) {
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        let state = match Self::get_state(&ctx).await {
+            Some(s) => s,
+            None => {
+                error!("AppState not found in TypeMap");
+                return;
+            }
+        };
+        let Some(_task) = state.shutdown.start_task() else {
+            return;
+        };
+        // Answering would race the production bot, which gets the same interactions.
+        if state.shadow.is_some() {
+            info!(id = %interaction.id(), "Shadow mode: ignoring interaction");
+            return;
+        }
+
+        match interaction {
+            Interaction::Command(cmd) if cmd.data.name == commands::COMMAND_NAME => {
+                commands::handle_command(&ctx, &state, &cmd).await;
+            }
+            Interaction::Command(cmd)
+                if cmd.data.name == commands::create_from_message::COMMAND_NAME =>
+            {
+                commands::handle_message_command(&ctx, &state, &cmd).await;
+            }
+            Interaction::Autocomplete(cmd) if cmd.data.name == commands::COMMAND_NAME => {
+                commands::handle_autocomplete(&ctx, &state, &cmd).await;
+            }
+            Interaction::Component(component)
+                if component
+                    .data
+                    .custom_id
+                    .starts_with(intake::CUSTOM_ID_PREFIX) =>
+            {
+                intake::handle_component(&ctx, &state, &component).await;
+            }
+            Interaction::Component(component)
+                if component
+                    .data
+                    .custom_id
+                    .starts_with(approval::CUSTOM_ID_PREFIX) =>
+            {
+                approval::handle_component(&ctx, &state, &component).await;
+            }
+            Interaction::Component(component)
+                if component
+                    .data
+                    .custom_id
+                    .starts_with(duplicates::CUSTOM_ID_PREFIX) =>
+            {
+                duplicates::handle_component(&ctx, &state, &component).await;
+            }
+            Interaction::Component(component)
+                if component
+                    .data
+                    .custom_id
+                    .starts_with(triage::CUSTOM_ID_PREFIX) =>
+            {
+                triage::handle_component(&ctx, &state, &component).await;
+            }
+            Interaction::Modal(modal)
+                if modal.data.custom_id.starts_with(intake::CUSTOM_ID_PREFIX) =>
+            {
+                intake::handle_modal(&ctx, &state, &modal).await;
+            }
+            Interaction::Modal(modal)
+                if modal
+                    .data
+                    .custom_id
+                    .starts_with(commands::create::CUSTOM_ID_PREFIX) =>
+            {
+                commands::create::handle_modal(&ctx, &state, &modal).await;
+            }
+            _ => {}
+        }
+    }
+
+    async fn ready(&self, ctx: Context, ready: Ready) {
         info!(user = %ready.user.name, "Discord bot connected");
+
+        let state = match Self::get_state(&ctx).await {
+            Some(s) => s,
+            None => {
+                error!("AppState not found in TypeMap");
+                return;
+            }
+        };
+
+        if state.shadow.is_some() {
+            return;
+        }
+        for guild_id in state.config.current().unique_guild_ids() {
+            register_commands(&ctx.http, guild_id).await;
+        }
+    }
+
+    /// Sent for each guild the bot is in once connected, and when it joins one. In multi-tenant
+    /// mode every guild gets the commands, so a new server can run `/linear setup`.
+    async fn guild_create(&self, ctx: Context, guild: Guild, _is_new: Option<bool>) {
+        let state = match Self::get_state(&ctx).await {
+            Some(s) => s,
+            None => {
+                error!("AppState not found in TypeMap");
+                return;
+            }
+        };
+
+        let config = state.config.current();
+        // Configured guilds were registered on ready.
+        if config.multi_tenant
+            && state.shadow.is_none()
+            && !config.unique_guild_ids().contains(&guild.id.get())
+        {
+            info!(guild_id = %guild.id, name = %guild.name, "Registering commands in guild");
+            register_commands(&ctx.http, guild.id.get()).await;
+        }
+    }
+}
+
+/// Register the bot's commands in a guild. Guild-scoped registration propagates immediately,
+/// unlike global commands.
+pub async fn register_commands(http: &Http, guild_id: u64) {
+    if let Err(e) = GuildId::new(guild_id)
+        .set_commands(
+            http,
+            vec![
+                commands::register(),
+                commands::create_from_message::register(),
+            ],
+        )
+        .await
+    {
+        warn!(guild_id, error = %e, "Failed to register slash commands");
     }
 }
@@ -0,0 +1,329 @@
+//! Optional intake form shown before an issue is created.
+//!
+//! For channels with `intake_form` enabled, new threads get a prompt with "Add details" and
+//! "File as-is" buttons. "Add details" opens a modal for repro steps, severity, and affected
+//! version; submitting it (or skipping) creates the issue. The prompt is persisted in
+//! `pending_intakes`, so buttons keep working across restarts and the reconcile pass files the
+//! issue without details once `intake_timeout_secs` passes.
+
+use serenity::all::{
+    ActionRowComponent, ButtonStyle, Channel, ChannelId, ComponentInteraction, Context,
+    CreateActionRow, CreateButton, CreateInputText, CreateInteractionResponse,
+    CreateInteractionResponseMessage, CreateMessage, CreateModal, EditMessage, GuildChannel,
+    InputTextStyle, Member, MessageId, ModalInteraction, UserId,
+};
+use sqlx::SqlitePool;
+use tracing::{error, info, warn};
+
+use crate::config::ChannelConfig;
+use crate::db::{self, PendingIntake};
+use crate::discord::handler::AppState;
+use crate::discord::port::DiscordPort;
+use crate::error::AppError;
+use crate::linear::api::LinearApi;
+use crate::sync::discord_to_linear::{
+    create_issue_for_thread, fetch_first_message_with_retry, IntakeAnswers,
+};
+use crate::sync::outbox::{self, Operation};
+
+pub const CUSTOM_ID_PREFIX: &str = "intake:";
+const OPEN: &str = "intake:open:";
+const SKIP: &str = "intake:skip:";
+const SUBMIT: &str = "intake:submit:";
+
+/// Post the intake prompt in a new thread and record it as pending.
+pub async fn prompt(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    thread: &GuildChannel,
+) -> Result<(), AppError> {
+    let author = thread
+        .owner_id
+        .ok_or_else(|| AppError::Internal("Thread has no owner".into()))?;
+
+    let buttons = CreateActionRow::Buttons(vec![
+        CreateButton::new(format!("{OPEN}{}", thread.id))
+            .label("Add details")
+            .style(ButtonStyle::Primary),
+        CreateButton::new(format!("{SKIP}{}", thread.id))
+            .label("File as-is")
+            .style(ButtonStyle::Secondary),
+    ]);
+
+    let message = CreateMessage::new()
+        .content(format!(
+            "<@{author}> thanks for the report! Add repro steps, severity, and the affected \
+             version before this is filed in Linear, or file it as-is."
+        ))
+        .components(vec![buttons]);
+
+    let sent = discord.send_message(thread.id, message).await?;
+    db::insert_pending_intake(
+        pool,
+        &thread.id.to_string(),
+        &author.to_string(),
+        &sent.id.to_string(),
+    )
+    .await?;
+
+    info!(thread_id = %thread.id, "Posted intake form prompt");
+    Ok(())
+}
+
+pub async fn handle_component(ctx: &Context, state: &AppState, component: &ComponentInteraction) {
+    let custom_id = component.data.custom_id.as_str();
+    let (thread_id, open) = if let Some(id) = custom_id.strip_prefix(OPEN) {
+        (id, true)
+    } else if let Some(id) = custom_id.strip_prefix(SKIP) {
+        (id, false)
+    } else {
+        return;
+    };
+
+    if let Err(message) = authorize(
+        state,
+        thread_id,
+        component.user.id,
+        component.member.as_ref(),
+    )
+    .await
+    {
+        reply_ephemeral(ctx, component, &message).await;
+        return;
+    }
+
+    if open {
+        let modal =
+            CreateModal::new(format!("{SUBMIT}{thread_id}"), "Issue details").components(vec![
+                CreateActionRow::InputText(
+                    CreateInputText::new(InputTextStyle::Paragraph, "Steps to reproduce", "repro")
+                        .required(false)
+                        .max_length(2000),
+                ),
+                CreateActionRow::InputText(
+                    CreateInputText::new(InputTextStyle::Short, "Severity", "severity")
+                        .placeholder("e.g. crash, blocker, cosmetic")
+                        .required(false)
+                        .max_length(100),
+                ),
+                CreateActionRow::InputText(
+                    CreateInputText::new(InputTextStyle::Short, "Affected version", "version")
+                        .required(false)
+                        .max_length(100),
+                ),
+            ]);
+        if let Err(e) = component
+            .create_response(&ctx.http, CreateInteractionResponse::Modal(modal))
+            .await
+        {
+            warn!(thread_id, error = %e, "Failed to open intake modal");
+        }
+        return;
+    }
+
+    if let Err(e) = component
+        .create_response(&ctx.http, CreateInteractionResponse::Acknowledge)
+        .await
+    {
+        warn!(thread_id, error = %e, "Failed to acknowledge intake skip");
+    }
+    file_issue(ctx, state, thread_id, None).await;
+}
+
+pub async fn handle_modal(ctx: &Context, state: &AppState, modal: &ModalInteraction) {
+    let thread_id = match modal.data.custom_id.strip_prefix(SUBMIT) {
+        Some(id) => id,
+        None => return,
+    };
+
+    let mut answers = IntakeAnswers::default();
+    for row in &modal.data.components {
+        for component in &row.components {
+            if let ActionRowComponent::InputText(input) = component {
+                let value = input.value.clone().unwrap_or_default();
+                match input.custom_id.as_str() {
+                    "repro" => answers.repro_steps = value,
+                    "severity" => answers.severity = value,
+                    "version" => answers.affected_version = value,
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    if let Err(e) = modal
+        .create_response(&ctx.http, CreateInteractionResponse::Acknowledge)
+        .await
+    {
+        warn!(thread_id, error = %e, "Failed to acknowledge intake modal");
+    }
+    file_issue(ctx, state, thread_id, Some(&answers)).await;
+}
+
+/// Only the thread author or members who can manage threads may answer the form.
+async fn authorize(
+    state: &AppState,
+    thread_id: &str,
+    user_id: UserId,
+    member: Option<&Member>,
+) -> Result<(), String> {
+    let pending = match db::get_pending_intake(&state.pool, thread_id).await {
+        Ok(Some(p)) => p,
+        Ok(None) => return Err("This report has already been filed.".into()),
+        Err(e) => {
+            error!(thread_id, error = %e, "Failed to load pending intake");
+            return Err("Something went wrong; try again later.".into());
+        }
+    };
+
+    let is_moderator = member
+        .and_then(|m| m.permissions)
+        .map(|p| p.manage_threads() || p.administrator())
+        .unwrap_or(false);
+
+    if pending.author_discord_user_id == user_id.to_string() || is_moderator {
+        Ok(())
+    } else {
+        Err("Only the author of this post can fill in the form.".into())
+    }
+}
+
+async fn file_issue(
+    ctx: &Context,
+    state: &AppState,
+    thread_id: &str,
+    answers: Option<&IntakeAnswers>,
+) {
+    if let Err(e) = try_file_issue(ctx, state, thread_id, answers).await {
+        error!(thread_id, error = %e, "Failed to file issue from intake form");
+    }
+}
+
+async fn try_file_issue(
+    ctx: &Context,
+    state: &AppState,
+    thread_id: &str,
+    answers: Option<&IntakeAnswers>,
+) -> Result<(), AppError> {
+    let pending = match db::get_pending_intake(&state.pool, thread_id).await? {
+        Some(p) => p,
+        None => return Ok(()),
+    };
+
+    let thread_channel_id: ChannelId = thread_id
+        .parse::<u64>()
+        .map(ChannelId::new)
+        .map_err(|_| AppError::Internal("Invalid thread id in intake custom_id".into()))?;
+
+    // Claim the intake; a double click or a concurrent timeout loses the race here.
+    if !db::claim_pending_intake(&state.pool, thread_id).await? {
+        return Ok(());
+    }
+
+    // The claim stays with the queued retry, so the timeout can't file it without the answers.
+    if let Err(e) = file_claimed_issue(ctx, state, thread_channel_id, &pending, answers).await {
+        let operation = Operation::FileIntake {
+            thread_id: thread_channel_id.get(),
+            answers: answers.cloned(),
+        };
+        outbox::enqueue(&state.pool, &operation, &e).await;
+        return Err(e);
+    }
+    Ok(())
+}
+
+async fn file_claimed_issue(
+    ctx: &Context,
+    state: &AppState,
+    thread_channel_id: ChannelId,
+    pending: &PendingIntake,
+    answers: Option<&IntakeAnswers>,
+) -> Result<(), AppError> {
+    let thread_id = thread_channel_id.to_string();
+    let thread = match thread_channel_id.to_channel(&ctx.http).await? {
+        Channel::Guild(gc) => gc,
+        _ => {
+            return Err(AppError::Internal(
+                "Intake thread is not a guild channel".into(),
+            ))
+        }
+    };
+    let config = state.config.current();
+    let channel_config = thread
+        .parent_id
+        .and_then(|p| config.channel_config(p.get()))
+        .ok_or_else(|| AppError::Internal("Intake thread is not in a monitored forum".into()))?;
+
+    // Strip the buttons so the prompt can't be used again.
+    if let Ok(prompt_id) = pending.prompt_message_id.parse::<u64>() {
+        let edit = EditMessage::new().components(Vec::new());
+        if let Err(e) = thread_channel_id
+            .edit_message(&ctx.http, MessageId::new(prompt_id), edit)
+            .await
+        {
+            warn!(thread_id, error = %e, "Failed to remove intake buttons");
+        }
+    }
+
+    file_intake(
+        ctx.http.as_ref(),
+        &state.pool,
+        channel_config,
+        &state.linear_client,
+        &thread,
+        answers,
+    )
+    .await
+}
+
+/// Create the issue for a claimed intake, then drop the intake. Also run by the outbox when
+/// filing from the form failed.
+pub async fn file_intake(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    channel_config: &ChannelConfig,
+    linear: &impl LinearApi,
+    thread: &GuildChannel,
+    answers: Option<&IntakeAnswers>,
+) -> Result<(), AppError> {
+    let thread_id = thread.id.to_string();
+    let author = db::get_pending_intake(pool, &thread_id)
+        .await?
+        .map(|p| p.author_discord_user_id);
+
+    let first_message = fetch_first_message_with_retry(discord, thread.id).await;
+    let issue = create_issue_for_thread(
+        discord,
+        pool,
+        channel_config,
+        linear,
+        thread,
+        first_message.as_ref(),
+        answers,
+    )
+    .await?;
+    db::delete_pending_intake(pool, &thread_id).await?;
+    db::insert_audit_entry(
+        pool,
+        &thread_id,
+        &issue.identifier,
+        "issue_created",
+        author.as_deref(),
+        &issue.title,
+    )
+    .await?;
+
+    Ok(())
+}
+
+async fn reply_ephemeral(ctx: &Context, component: &ComponentInteraction, content: &str) {
+    let message = CreateInteractionResponseMessage::new()
+        .content(content)
+        .ephemeral(true);
+    if let Err(e) = component
+        .create_response(&ctx.http, CreateInteractionResponse::Message(message))
+        .await
+    {
+        warn!(error = %e, "Failed to send intake reply");
+    }
+}
@@ -1 +1,9 @@
+pub mod approval;
+pub mod commands;
+pub mod duplicates;
+pub mod embeds;
 pub mod handler;
+pub mod intake;
+pub mod port;
+pub mod subscriptions;
+pub mod triage;
@@ -0,0 +1,298 @@
+//! The Discord operations the sync paths depend on.
+//!
+//! Issue creation, status sync, and comment relay in both directions (and the helpers they
+//! share) take `&impl DiscordPort` instead of serenity's `Http`, so tests can run them against
+//! an in-memory transport. `Http` is the production implementation.
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use serenity::all::{
+    ApplicationId, Channel, ChannelId, CreateForumPost, CreateMessage, CreateWebhook, EditMessage,
+    EditThread, EditWebhookMessage, ExecuteWebhook, GetMessages, GuildChannel, GuildId, Http,
+    Member, Message, MessageId, Role, RoleId, UserId, Webhook,
+};
+use serenity::http::HttpError;
+
+use crate::error::AppError;
+
+#[async_trait]
+pub trait DiscordPort: Send + Sync {
+    /// Fetch a guild channel, forum, or thread.
+    async fn channel(&self, channel_id: ChannelId) -> Result<GuildChannel, AppError>;
+
+    /// Roles defined in a guild.
+    async fn roles(&self, guild_id: GuildId) -> Result<HashMap<RoleId, Role>, AppError>;
+
+    /// A guild member, with their roles.
+    async fn member(&self, guild_id: GuildId, user_id: UserId) -> Result<Member, AppError>;
+
+    /// Threads in a guild that aren't archived.
+    async fn active_threads(&self, guild_id: GuildId) -> Result<Vec<GuildChannel>, AppError>;
+
+    /// The oldest message in a channel (a forum post's body), if there is one yet.
+    async fn first_message(&self, channel_id: ChannelId) -> Result<Option<Message>, AppError>;
+
+    /// The oldest messages in a channel (up to 100), oldest first.
+    async fn opening_messages(&self, channel_id: ChannelId) -> Result<Vec<Message>, AppError>;
+
+    async fn message(
+        &self,
+        channel_id: ChannelId,
+        message_id: MessageId,
+    ) -> Result<Message, AppError>;
+
+    async fn send_message(
+        &self,
+        channel_id: ChannelId,
+        message: CreateMessage,
+    ) -> Result<Message, AppError>;
+
+    async fn edit_message(
+        &self,
+        channel_id: ChannelId,
+        message_id: MessageId,
+        edit: EditMessage,
+    ) -> Result<Message, AppError>;
+
+    /// Send a direct message, opening the DM channel if needed.
+    async fn send_dm(&self, user_id: UserId, message: CreateMessage) -> Result<(), AppError>;
+
+    async fn delete_message(
+        &self,
+        channel_id: ChannelId,
+        message_id: MessageId,
+    ) -> Result<(), AppError>;
+
+    async fn pin_message(
+        &self,
+        channel_id: ChannelId,
+        message_id: MessageId,
+    ) -> Result<(), AppError>;
+
+    async fn edit_thread(
+        &self,
+        channel_id: ChannelId,
+        edit: EditThread<'_>,
+    ) -> Result<GuildChannel, AppError>;
+
+    /// Open a post in a forum channel, returning its thread.
+    async fn create_forum_post(
+        &self,
+        forum_id: ChannelId,
+        post: CreateForumPost<'_>,
+    ) -> Result<GuildChannel, AppError>;
+
+    /// The bot's own application, used to recognise webhooks it created.
+    fn application_id(&self) -> Option<ApplicationId>;
+
+    async fn webhooks(&self, channel_id: ChannelId) -> Result<Vec<Webhook>, AppError>;
+
+    async fn create_webhook(
+        &self,
+        channel_id: ChannelId,
+        builder: CreateWebhook<'_>,
+    ) -> Result<Webhook, AppError>;
+
+    /// Post through `webhook` into `thread` and wait for the created message.
+    async fn execute_webhook(
+        &self,
+        webhook: &Webhook,
+        thread: ChannelId,
+        builder: ExecuteWebhook,
+    ) -> Result<Message, AppError>;
+
+    /// Edit a message `webhook` posted in `thread`.
+    async fn edit_webhook_message(
+        &self,
+        webhook: &Webhook,
+        thread: ChannelId,
+        message_id: MessageId,
+        edit: EditWebhookMessage,
+    ) -> Result<Message, AppError>;
+
+    /// Delete a message `webhook` posted in `thread`.
+    async fn delete_webhook_message(
+        &self,
+        webhook: &Webhook,
+        thread: ChannelId,
+        message_id: MessageId,
+    ) -> Result<(), AppError>;
+}
+
+/// Whether `error` is Discord reporting that the target no longer exists.
+pub fn is_not_found(error: &AppError) -> bool {
+    match error {
+        AppError::Discord(serenity::Error::Http(e)) => {
+            e.status_code() == Some(StatusCode::NOT_FOUND)
+        }
+        _ => false,
+    }
+}
+
+/// Whether `error` is Discord (or the network to it) failing, as opposed to Discord rejecting the
+/// request.
+pub fn is_transient(error: &AppError) -> bool {
+    match error {
+        AppError::Discord(serenity::Error::Http(HttpError::Request(_))) => true,
+        AppError::Discord(serenity::Error::Http(e)) => e
+            .status_code()
+            .is_some_and(|s| s.is_server_error() || s == StatusCode::TOO_MANY_REQUESTS),
+        _ => false,
+    }
+}
+
+#[async_trait]
+impl DiscordPort for Http {
+    async fn channel(&self, channel_id: ChannelId) -> Result<GuildChannel, AppError> {
+        match channel_id.to_channel(self).await? {
+            Channel::Guild(gc) => Ok(gc),
+            _ => Err(AppError::Internal(format!(
+                "Channel {channel_id} is not a guild channel"
+            ))),
+        }
+    }
+
+    async fn roles(&self, guild_id: GuildId) -> Result<HashMap<RoleId, Role>, AppError> {
+        Ok(guild_id.roles(self).await?)
+    }
+
+    async fn member(&self, guild_id: GuildId, user_id: UserId) -> Result<Member, AppError> {
+        Ok(guild_id.member(self, user_id).await?)
+    }
+
+    async fn active_threads(&self, guild_id: GuildId) -> Result<Vec<GuildChannel>, AppError> {
+        Ok(guild_id.get_active_threads(self).await?.threads)
+    }
+
+    async fn first_message(&self, channel_id: ChannelId) -> Result<Option<Message>, AppError> {
+        // Paging `after` the smallest possible ID returns the start of the history rather than
+        // the latest reply.
+        let messages = channel_id
+            .messages(self, GetMessages::new().after(MessageId::new(1)).limit(1))
+            .await?;
+        Ok(messages.into_iter().next())
+    }
+
+    async fn opening_messages(&self, channel_id: ChannelId) -> Result<Vec<Message>, AppError> {
+        let mut messages = channel_id
+            .messages(self, GetMessages::new().after(MessageId::new(1)).limit(100))
+            .await?;
+        messages.sort_by_key(|m| m.id);
+        Ok(messages)
+    }
+
+    async fn message(
+        &self,
+        channel_id: ChannelId,
+        message_id: MessageId,
+    ) -> Result<Message, AppError> {
+        Ok(channel_id.message(self, message_id).await?)
+    }
+
+    async fn send_message(
+        &self,
+        channel_id: ChannelId,
+        message: CreateMessage,
+    ) -> Result<Message, AppError> {
+        Ok(channel_id.send_message(self, message).await?)
+    }
+
+    async fn edit_message(
+        &self,
+        channel_id: ChannelId,
+        message_id: MessageId,
+        edit: EditMessage,
+    ) -> Result<Message, AppError> {
+        Ok(channel_id.edit_message(self, message_id, edit).await?)
+    }
+
+    async fn send_dm(&self, user_id: UserId, message: CreateMessage) -> Result<(), AppError> {
+        user_id.direct_message(self, message).await?;
+        Ok(())
+    }
+
+    async fn delete_message(
+        &self,
+        channel_id: ChannelId,
+        message_id: MessageId,
+    ) -> Result<(), AppError> {
+        Ok(channel_id.delete_message(self, message_id).await?)
+    }
+
+    async fn pin_message(
+        &self,
+        channel_id: ChannelId,
+        message_id: MessageId,
+    ) -> Result<(), AppError> {
+        Ok(channel_id.pin(self, message_id).await?)
+    }
+
+    async fn edit_thread(
+        &self,
+        channel_id: ChannelId,
+        edit: EditThread<'_>,
+    ) -> Result<GuildChannel, AppError> {
+        Ok(channel_id.edit_thread(self, edit).await?)
+    }
+
+    async fn create_forum_post(
+        &self,
+        forum_id: ChannelId,
+        post: CreateForumPost<'_>,
+    ) -> Result<GuildChannel, AppError> {
+        Ok(forum_id.create_forum_post(self, post).await?)
+    }
+
+    fn application_id(&self) -> Option<ApplicationId> {
+        Http::application_id(self)
+    }
+
+    async fn webhooks(&self, channel_id: ChannelId) -> Result<Vec<Webhook>, AppError> {
+        Ok(channel_id.webhooks(self).await?)
+    }
+
+    async fn create_webhook(
+        &self,
+        channel_id: ChannelId,
+        builder: CreateWebhook<'_>,
+    ) -> Result<Webhook, AppError> {
+        Ok(channel_id.create_webhook(self, builder).await?)
+    }
+
+    async fn execute_webhook(
+        &self,
+        webhook: &Webhook,
+        thread: ChannelId,
+        builder: ExecuteWebhook,
+    ) -> Result<Message, AppError> {
+        webhook
+            .execute(self, true, builder.in_thread(thread))
+            .await?
+            .ok_or_else(|| AppError::Internal("Webhook returned no message".into()))
+    }
+
+    async fn edit_webhook_message(
+        &self,
+        webhook: &Webhook,
+        thread: ChannelId,
+        message_id: MessageId,
+        edit: EditWebhookMessage,
+    ) -> Result<Message, AppError> {
+        Ok(webhook
+            .edit_message(self, message_id, edit.in_thread(thread))
+            .await?)
+    }
+
+    async fn delete_webhook_message(
+        &self,
+        webhook: &Webhook,
+        thread: ChannelId,
+        message_id: MessageId,
+    ) -> Result<(), AppError> {
+        Ok(webhook
+            .delete_message(self, Some(thread), message_id)
+            .await?)
+    }
+}
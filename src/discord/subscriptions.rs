@@ -0,0 +1,213 @@
+//! Per-user subscriptions to a synced thread's issue.
+//!
+//! Members subscribe with `/linear subscribe` in the thread or a 🔔 reaction on the post, and get
+//! a DM when the issue changes status or gets new comments in Linear. Removing the reaction or
+//! `/linear unsubscribe` stops them. A DM that can't be delivered (the member doesn't accept DMs
+//! from the server) is logged and skipped; it doesn't hold up the thread's own sync.
+//!
+//! Every DM consults the recipient's `/linear prefs`: muted users get none, users who chose
+//! status changes only don't hear about comments, and assignment DMs are opt-in.
+
+use std::collections::HashMap;
+
+use serde_json::json;
+use serenity::all::{Context, CreateMessage, Reaction, ReactionType, UserId};
+use sqlx::SqlitePool;
+use tracing::{error, info, warn};
+
+use crate::db::{self, NotificationPrefs};
+use crate::discord::handler::AppState;
+use crate::discord::port::DiscordPort;
+use crate::error::AppError;
+use crate::linear::client::LinearComment;
+use crate::templates::{self, Notification};
+
+const SUBSCRIBE_EMOJI: &str = "🔔";
+
+/// Longest excerpt of a comment quoted in a DM.
+const MAX_EXCERPT_CHARS: usize = 300;
+
+/// What a subscription DM is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Update {
+    StatusChanged,
+    Comments,
+}
+
+fn wants(prefs: &NotificationPrefs, update: Update) -> bool {
+    match update {
+        Update::StatusChanged => !prefs.muted,
+        Update::Comments => !prefs.muted && !prefs.status_changes_only,
+    }
+}
+
+/// DM everyone subscribed to the thread who wants this kind of update. Returns how many DMs
+/// were delivered.
+pub async fn notify_subscribers(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    discord_thread_id: &str,
+    update: Update,
+    content: &str,
+) -> Result<usize, AppError> {
+    let mut delivered = 0usize;
+    for subscriber in db::get_subscribers(pool, discord_thread_id).await? {
+        let Ok(user_id) = subscriber.parse::<u64>() else {
+            continue;
+        };
+        let prefs = db::get_notification_prefs(pool, &subscriber).await?;
+        if !wants(&prefs, update) {
+            continue;
+        }
+        let message = CreateMessage::new().content(content);
+        match discord.send_dm(UserId::new(user_id), message).await {
+            Ok(()) => delivered += 1,
+            Err(e) => warn!(
+                thread_id = discord_thread_id,
+                user_id,
+                error = %e,
+                "Failed to DM subscriber"
+            ),
+        }
+    }
+    Ok(delivered)
+}
+
+/// DM a user who was just assigned an issue, if they opted in. Returns whether it was delivered.
+pub async fn notify_assignee(
+    discord: &impl DiscordPort,
+    overrides: &HashMap<String, String>,
+    prefs: &NotificationPrefs,
+    discord_user_id: &str,
+    discord_thread_id: &str,
+    identifier: &str,
+) -> bool {
+    if !prefs.dm_on_assignment || prefs.muted {
+        return false;
+    }
+    let Ok(user_id) = discord_user_id.parse::<u64>() else {
+        return false;
+    };
+    let content = templates::notification(
+        overrides,
+        Notification::DmAssigned,
+        &json!({ "identifier": identifier, "thread": format!("<#{discord_thread_id}>") }),
+    );
+    match discord
+        .send_dm(UserId::new(user_id), CreateMessage::new().content(content))
+        .await
+    {
+        Ok(()) => true,
+        Err(e) => {
+            warn!(identifier, user_id, error = %e, "Failed to DM assignee");
+            false
+        }
+    }
+}
+
+/// DM for a status change.
+pub fn status_message(
+    overrides: &HashMap<String, String>,
+    discord_thread_id: &str,
+    identifier: &str,
+    status: &str,
+) -> String {
+    templates::notification(
+        overrides,
+        Notification::DmStatusChanged,
+        &json!({
+            "identifier": identifier,
+            "status": status,
+            "thread": format!("<#{discord_thread_id}>"),
+        }),
+    )
+}
+
+/// DM for comments relayed from Linear: the comment itself if there's one, else a count.
+pub fn comments_message(
+    overrides: &HashMap<String, String>,
+    discord_thread_id: &str,
+    identifier: &str,
+    comments: &[&LinearComment],
+) -> Option<String> {
+    let thread = format!("<#{discord_thread_id}>");
+    match comments {
+        [] => None,
+        [comment] => {
+            let mut excerpt: String = comment.body.chars().take(MAX_EXCERPT_CHARS).collect();
+            if comment.body.chars().count() > MAX_EXCERPT_CHARS {
+                excerpt.push('…');
+            }
+            Some(templates::notification(
+                overrides,
+                Notification::DmComment,
+                &json!({
+                    "identifier": identifier,
+                    "author": comment.author_name,
+                    "excerpt": excerpt,
+                    "thread": thread,
+                }),
+            ))
+        }
+        _ => Some(templates::notification(
+            overrides,
+            Notification::DmComments,
+            &json!({ "identifier": identifier, "count": comments.len(), "thread": thread }),
+        )),
+    }
+}
+
+/// Subscribe a member who reacts 🔔 to a synced thread's post.
+pub async fn handle_reaction_add(ctx: &Context, state: &AppState, reaction: &Reaction) {
+    let Some((thread_id, user_id)) = subscription_reaction(state, reaction).await else {
+        return;
+    };
+    match db::insert_subscription(&state.pool, &thread_id, &user_id.to_string()).await {
+        Ok(true) => {
+            info!(thread_id, user_id = %user_id, "Subscribed to issue by reaction");
+            let confirmation = format!(
+                "You'll get a DM when the issue for <#{thread_id}> changes status or gets a \
+                 comment. Remove your {SUBSCRIBE_EMOJI} reaction to stop."
+            );
+            let message = CreateMessage::new().content(confirmation);
+            if let Err(e) = state.discord(&ctx.http).send_dm(user_id, message).await {
+                warn!(thread_id, user_id = %user_id, error = %e, "Failed to confirm subscription");
+            }
+        }
+        Ok(false) => {}
+        Err(e) => error!(thread_id, error = %e, "Failed to save subscription"),
+    }
+}
+
+/// Unsubscribe a member who removes their 🔔 reaction.
+pub async fn handle_reaction_remove(state: &AppState, reaction: &Reaction) {
+    let Some((thread_id, user_id)) = subscription_reaction(state, reaction).await else {
+        return;
+    };
+    match db::delete_subscription(&state.pool, &thread_id, &user_id.to_string()).await {
+        Ok(true) => info!(thread_id, user_id = %user_id, "Unsubscribed from issue by reaction"),
+        Ok(false) => {}
+        Err(e) => error!(thread_id, error = %e, "Failed to remove subscription"),
+    }
+}
+
+/// The thread and member for a 🔔 reaction on the post of a synced thread.
+async fn subscription_reaction(state: &AppState, reaction: &Reaction) -> Option<(String, UserId)> {
+    if !matches!(&reaction.emoji, ReactionType::Unicode(emoji) if emoji == SUBSCRIBE_EMOJI) {
+        return None;
+    }
+    // A forum post's starter message shares the thread's ID.
+    if reaction.message_id.get() != reaction.channel_id.get() {
+        return None;
+    }
+    let user_id = reaction.user_id?;
+    let thread_id = reaction.channel_id.to_string();
+    match db::get_mapping_by_discord_thread(&state.pool, &thread_id).await {
+        Ok(Some(_)) => Some((thread_id, user_id)),
+        Ok(None) => None,
+        Err(e) => {
+            error!(thread_id, error = %e, "Failed to look up mapping for reaction");
+            None
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Priority/estimate select menus posted on newly created issues, so members with the
+//! channel's `triage_role_id` can triage without leaving Discord.
+
+use serenity::all::{
+    ChannelId, ComponentInteraction, ComponentInteractionDataKind, Context, CreateActionRow,
+    CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage, CreateSelectMenu,
+    CreateSelectMenuKind, CreateSelectMenuOption, RoleId,
+};
+use tracing::{info, warn};
+
+use crate::config::ChannelConfig;
+use crate::discord::handler::AppState;
+use crate::discord::port::DiscordPort;
+use crate::error::AppError;
+use crate::linear::api::LinearApi;
+use crate::linear::client::{priority_label, LinearIssue};
+
+pub const CUSTOM_ID_PREFIX: &str = "triage:";
+const PRIORITY: &str = "triage:priority:";
+const ESTIMATE: &str = "triage:estimate:";
+
+pub async fn post_menu(
+    discord: &impl DiscordPort,
+    thread_id: ChannelId,
+    issue: &LinearIssue,
+    channel_config: &ChannelConfig,
+) -> Result<(), AppError> {
+    let priorities = [1, 2, 3, 4, 0]
+        .iter()
+        .map(|p| CreateSelectMenuOption::new(priority_label(*p), p.to_string()))
+        .collect();
+    let estimates = channel_config
+        .triage_estimates
+        .iter()
+        .map(|e| CreateSelectMenuOption::new(format!("{e} points"), e.to_string()))
+        .collect();
+
+    let components = vec![
+        CreateActionRow::SelectMenu(
+            CreateSelectMenu::new(
+                format!("{PRIORITY}{}", issue.id),
+                CreateSelectMenuKind::String {
+                    options: priorities,
+                },
+            )
+            .placeholder("Set priority"),
+        ),
+        CreateActionRow::SelectMenu(
+            CreateSelectMenu::new(
+                format!("{ESTIMATE}{}", issue.id),
+                CreateSelectMenuKind::String { options: estimates },
+            )
+            .placeholder("Set estimate"),
+        ),
+    ];
+
+    discord
+        .send_message(
+            thread_id,
+            CreateMessage::new()
+                .content(format!("Triage **{}**:", issue.identifier))
+                .components(components),
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub async fn handle_component(ctx: &Context, state: &AppState, component: &ComponentInteraction) {
+    let custom_id = component.data.custom_id.as_str();
+    let value = match &component.data.kind {
+        ComponentInteractionDataKind::StringSelect { values } => values.first(),
+        _ => None,
+    };
+    let value: i64 = match value.and_then(|v| v.parse().ok()) {
+        Some(v) => v,
+        None => return,
+    };
+
+    let config = state.config.current();
+    let role = component
+        .channel
+        .as_ref()
+        .and_then(|c| c.parent_id)
+        .and_then(|p| config.channel_config(p.get()))
+        .and_then(|c| c.triage_role_id);
+    let allowed = match (role, component.member.as_ref()) {
+        (Some(role), Some(member)) => member.roles.contains(&RoleId::new(role)),
+        _ => false,
+    };
+    if !allowed {
+        respond(
+            ctx,
+            component,
+            "You don't have the triage role for this channel.",
+            true,
+        )
+        .await;
+        return;
+    }
+
+    let (result, summary) = if let Some(issue_id) = custom_id.strip_prefix(PRIORITY) {
+        (
+            state
+                .linear_client
+                .update_issue_priority(issue_id, value)
+                .await,
+            format!("priority set to **{}**", priority_label(value)),
+        )
+    } else if let Some(issue_id) = custom_id.strip_prefix(ESTIMATE) {
+        (
+            state
+                .linear_client
+                .update_issue_estimate(issue_id, value)
+                .await,
+            format!("estimate set to **{value} points**"),
+        )
+    } else {
+        return;
+    };
+
+    match result {
+        Ok(()) => {
+            info!(custom_id, value, user = %component.user.id, "Applied triage selection");
+            let message = format!("<@{}> {summary}", component.user.id);
+            respond(ctx, component, &message, false).await;
+        }
+        Err(e) => {
+            warn!(custom_id, error = %e, "Failed to apply triage selection");
+            respond(ctx, component, "Couldn't update the issue in Linear.", true).await;
+        }
+    }
+}
+
+async fn respond(ctx: &Context, component: &ComponentInteraction, content: &str, ephemeral: bool) {
+    let message = CreateInteractionResponseMessage::new()
+        .content(content)
+        .ephemeral(ephemeral);
+    if let Err(e) = component
+        .create_response(&ctx.http, CreateInteractionResponse::Message(message))
+        .await
+    {
+        warn!(error = %e, "Failed to respond to triage selection");
+    }
+}
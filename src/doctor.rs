@@ -0,0 +1,219 @@
+//! Live checks of the configuration against Discord and Linear, run by the `doctor` command.
+//!
+//! Every configured channel (`CHANNELS` plus those added with `/linear admin`) is checked on both
+//! sides: the forum exists in the configured guild and the bot holds the permissions the channel's
+//! features need, and the Linear team, project, and labels it files issues with resolve. Checks
+//! never stop at the first failure, so one run reports everything that needs fixing.
+
+use std::fmt;
+
+use serenity::all::{ChannelId, ChannelType, GuildChannel, GuildId, Permissions, UserId};
+use serenity::http::Http;
+
+use crate::config::{ChannelConfig, Config};
+use crate::linear::api::LinearApi;
+use crate::linear::workspaces::LinearWorkspaces;
+
+/// The outcome of one check.
+#[derive(Debug)]
+pub struct Check {
+    /// What was checked, e.g. "channel 123: forum".
+    pub subject: String,
+    pub passed: bool,
+    pub detail: String,
+}
+
+impl Check {
+    fn new(subject: impl Into<String>, result: Result<String, String>) -> Self {
+        let (passed, detail) = match result {
+            Ok(detail) => (true, detail),
+            Err(detail) => (false, detail),
+        };
+        Self {
+            subject: subject.into(),
+            passed,
+            detail,
+        }
+    }
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mark = if self.passed { "PASS" } else { "FAIL" };
+        write!(f, "[{mark}] {}: {}", self.subject, self.detail)
+    }
+}
+
+/// Run every check. The order follows `CHANNELS`, account-level checks first.
+pub async fn run_checks(http: &Http, linear: &LinearWorkspaces, config: &Config) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    let bot = http.get_current_user().await;
+    checks.push(Check::new(
+        "Discord token",
+        bot.as_ref()
+            .map(|bot| format!("signed in as {}", bot.name))
+            .map_err(|e| e.to_string()),
+    ));
+    for (credential, client) in linear.workspaces() {
+        let key = match credential {
+            Some(name) => format!("Linear API key {name}"),
+            None => "Linear API key".to_string(),
+        };
+        checks.push(Check::new(
+            key,
+            client
+                .viewer()
+                .await
+                .map(|viewer| format!("signed in as {} <{}>", viewer.display_name, viewer.email))
+                .map_err(|e| e.to_string()),
+        ));
+    }
+
+    for channel in &config.channels {
+        let subject = |what: &str| format!("channel {}: {what}", channel.discord_channel_id);
+        let linear = linear.for_channel(channel);
+
+        match forum(http, channel).await {
+            Ok(forum) => {
+                checks.push(Check::new(subject("forum"), Ok(format!("#{}", forum.name))));
+                if let Ok(bot) = &bot {
+                    checks.push(Check::new(
+                        subject("permissions"),
+                        check_permissions(http, channel, &forum, bot.id).await,
+                    ));
+                }
+            }
+            Err(detail) => checks.push(Check::new(subject("forum"), Err(detail))),
+        }
+
+        let team = linear.team_name(&channel.linear_team_id).await;
+        checks.push(Check::new(
+            subject("Linear team"),
+            team.map_err(|e| e.to_string()),
+        ));
+        for team_id in channel
+            .routing_rules
+            .iter()
+            .filter_map(|r| r.linear_team_id.as_ref())
+        {
+            let team = linear.team_name(team_id).await;
+            checks.push(Check::new(
+                subject(&format!("Linear team {team_id}")),
+                team.map_err(|e| e.to_string()),
+            ));
+        }
+        let project = linear.project_name(&channel.linear_project_id).await;
+        checks.push(Check::new(
+            subject("Linear project"),
+            project.map_err(|e| e.to_string()),
+        ));
+        for label_id in label_ids(channel) {
+            let label = linear.label_name(label_id).await;
+            checks.push(Check::new(
+                subject(&format!("Linear label {label_id}")),
+                label.map_err(|e| e.to_string()),
+            ));
+        }
+        let rule_projects = channel
+            .routing_rules
+            .iter()
+            .filter_map(|r| r.linear_project_id.as_ref());
+        for project_id in channel.tag_project_map.values().chain(rule_projects) {
+            let project = linear.project_name(project_id).await;
+            checks.push(Check::new(
+                subject(&format!("Linear project {project_id}")),
+                project.map_err(|e| e.to_string()),
+            ));
+        }
+    }
+
+    checks
+}
+
+/// The configured channel, if it's a forum in the configured guild.
+async fn forum(http: &Http, channel: &ChannelConfig) -> Result<GuildChannel, String> {
+    let found = http
+        .get_channel(ChannelId::new(channel.discord_channel_id))
+        .await
+        .map_err(|e| format!("can't be fetched: {e}"))?;
+    let Some(forum) = found.guild() else {
+        return Err("not a guild channel".into());
+    };
+    if forum.guild_id.get() != channel.guild_id {
+        return Err(format!(
+            "belongs to guild {}, not {}",
+            forum.guild_id, channel.guild_id
+        ));
+    }
+    if forum.kind != ChannelType::Forum {
+        return Err(format!(
+            "#{} is a {:?} channel, not a forum",
+            forum.name, forum.kind
+        ));
+    }
+    Ok(forum)
+}
+
+/// Permissions the bot needs in a forum, given the channel's features.
+fn required_permissions(channel: &ChannelConfig) -> Permissions {
+    let mut required = Permissions::VIEW_CHANNEL
+        | Permissions::READ_MESSAGE_HISTORY
+        | Permissions::SEND_MESSAGES
+        | Permissions::SEND_MESSAGES_IN_THREADS
+        | Permissions::CREATE_PUBLIC_THREADS;
+    if channel.archive_on_close || !channel.state_tag_map.is_empty() {
+        required |= Permissions::MANAGE_THREADS;
+    }
+    if channel.comment_webhooks {
+        required |= Permissions::MANAGE_WEBHOOKS;
+    }
+    if channel.status_embed {
+        required |= Permissions::MANAGE_MESSAGES;
+    }
+    required
+}
+
+async fn check_permissions(
+    http: &Http,
+    channel: &ChannelConfig,
+    forum: &GuildChannel,
+    bot_id: UserId,
+) -> Result<String, String> {
+    let guild_id = GuildId::new(channel.guild_id);
+    let guild = http
+        .get_guild(guild_id)
+        .await
+        .map_err(|e| format!("can't fetch guild {guild_id}: {e}"))?;
+    let member = http
+        .get_member(guild_id, bot_id)
+        .await
+        .map_err(|e| format!("bot isn't a member of guild {guild_id}: {e}"))?;
+
+    let required = required_permissions(channel);
+    let missing = required - guild.user_permissions_in(forum, &member);
+    if missing.is_empty() {
+        Ok(format!("has {required}"))
+    } else {
+        Err(format!("missing {missing}"))
+    }
+}
+
+/// Every Linear label the channel uses: its primary label, the tag- and keyword-mapped ones,
+/// those its routing rules apply, and the one that opens posts from Linear.
+fn label_ids(channel: &ChannelConfig) -> Vec<&str> {
+    let mut ids: Vec<&str> = std::iter::once(channel.linear_label_id.as_str())
+        .chain(channel.tag_label_map.values().map(String::as_str))
+        .chain(channel.keyword_label_map.values().map(String::as_str))
+        .chain(channel.discuss_label_id.as_deref())
+        .chain(
+            channel
+                .routing_rules
+                .iter()
+                .flat_map(|r| r.linear_label_ids.iter().map(String::as_str)),
+        )
+        .collect();
+    ids.sort();
+    ids.dedup();
+    ids
+}
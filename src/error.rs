@@ -9,15 +9,27 @@ pub enum AppError {
     #[error("Database error: {0}")]
     Database(#[from] sqlx::Error),
 
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
 
     #[error("Linear API error: {0}")]
     LinearApi(String),
 
+    #[error("Template error: {0}")]
+    Template(String),
+
     #[error("Attachment upload failed: {0}")]
     AttachmentUpload(String),
 
+    #[error("Credential error: {0}")]
+    Credential(#[from] crate::credentials::CredentialError),
+
+    #[error("{0} is failing repeatedly; calls are paused")]
+    Unavailable(&'static str),
+
     #[error("{0}")]
     Internal(String),
 }
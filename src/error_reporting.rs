@@ -0,0 +1,61 @@
+//! Reporting of errors and panics to Sentry, built with the `sentry` feature and enabled by
+//! `SENTRY_DSN`.
+//!
+//! Error events from this crate become Sentry issues, and so do warnings that carry an `error`
+//! field, since most sync failures are logged that way and otherwise go unnoticed. Each report
+//! includes the fields of the spans it happened in (the thread, team, and issue being synced).
+//! Other log lines are attached as breadcrumbs. Panics are reported by the Sentry panic hook.
+
+use std::env;
+
+use sentry::integrations::tracing::{EventFilter, SentryLayer};
+use sentry::types::{Dsn, ParseDsnError};
+use sentry::{ClientInitGuard, ClientOptions};
+use tracing::{Level, Metadata, Subscriber};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Start the Sentry client if `SENTRY_DSN` is set. Keep the guard until exit; dropping it sends
+/// queued reports. `SENTRY_ENVIRONMENT` and `SENTRY_RELEASE` are read by the client.
+pub fn init() -> Result<Option<ClientInitGuard>, ParseDsnError> {
+    let Some(dsn) = env::var("SENTRY_DSN").ok().filter(|dsn| !dsn.is_empty()) else {
+        return Ok(None);
+    };
+    let dsn: Dsn = dsn.parse()?;
+    Ok(Some(sentry::init(ClientOptions {
+        dsn: Some(dsn),
+        release: sentry::release_name!(),
+        attach_stacktrace: true,
+        ..Default::default()
+    })))
+}
+
+/// The layer that turns log events into Sentry reports and breadcrumbs.
+pub fn layer<S>() -> SentryLayer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    sentry::integrations::tracing::layer()
+        .event_filter(event_filter)
+        .span_filter(|metadata| own_crate(metadata) && *metadata.level() <= Level::INFO)
+        .enable_span_attributes()
+}
+
+/// How a log event is reported: errors, and warnings with an `error` field, from this crate are
+/// reported; other warnings and info lines are breadcrumbs.
+pub fn event_filter(metadata: &Metadata<'_>) -> EventFilter {
+    let failure = match *metadata.level() {
+        Level::ERROR => true,
+        Level::WARN => metadata.fields().field("error").is_some(),
+        Level::INFO => false,
+        _ => return EventFilter::Ignore,
+    };
+    if failure && own_crate(metadata) {
+        EventFilter::Event
+    } else {
+        EventFilter::Breadcrumb
+    }
+}
+
+fn own_crate(metadata: &Metadata<'_>) -> bool {
+    metadata.target().starts_with(env!("CARGO_CRATE_NAME"))
+}
@@ -0,0 +1,356 @@
+//! Markdown conversion between Discord and Linear.
+//!
+//! Discord content carries markup Linear can't render: `<@id>`-style mentions, custom emoji,
+//! `||spoilers||`, and `~~double-tilde~~` strikethrough. In the other direction, Linear's
+//! headings, tables, and login-only links read poorly inside Discord blockquotes. Code spans and
+//! fences are passed through untouched.
+
+use std::collections::HashMap;
+
+use serenity::all::{ChannelId, GuildId, User};
+
+use crate::discord::port::DiscordPort;
+
+const LINEAR_APP_URL: &str = "https://linear.app/";
+
+/// Display names for the mentions in a piece of Discord content.
+#[derive(Debug, Default)]
+pub struct MentionNames {
+    users: HashMap<u64, String>,
+    roles: HashMap<u64, String>,
+    channels: HashMap<u64, String>,
+}
+
+impl MentionNames {
+    /// Collect names for the mentions in `content`. Users come from the message's resolved
+    /// mentions; roles and channels are looked up in the guild. Lookups are best-effort —
+    /// anything unresolved falls back to a generic placeholder.
+    pub async fn resolve(
+        discord: &impl DiscordPort,
+        content: &str,
+        guild_id: Option<GuildId>,
+        mentioned_users: &[User],
+    ) -> Self {
+        let mut names = MentionNames {
+            users: mentioned_users
+                .iter()
+                .map(|u| (u.id.get(), u.display_name().to_string()))
+                .collect(),
+            ..Default::default()
+        };
+
+        let mentions = mentions(content);
+
+        if mentions.iter().any(|m| matches!(m, Mention::Role(_))) {
+            if let Some(guild_id) = guild_id {
+                if let Ok(roles) = discord.roles(guild_id).await {
+                    names.roles = roles
+                        .into_iter()
+                        .map(|(id, role)| (id.get(), role.name))
+                        .collect();
+                }
+            }
+        }
+
+        for mention in &mentions {
+            if let Mention::Channel(id) = mention {
+                if names.channels.contains_key(id) {
+                    continue;
+                }
+                if let Ok(channel) = discord.channel(ChannelId::new(*id)).await {
+                    names.channels.insert(*id, channel.name);
+                }
+            }
+        }
+
+        names
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Mention<'a> {
+    User(u64),
+    Role(u64),
+    Channel(u64),
+    Emoji(&'a str),
+}
+
+/// Convert Discord message content to Linear-flavoured markdown.
+pub fn discord_to_linear(content: &str, names: &MentionNames) -> String {
+    let mut out = String::with_capacity(content.len());
+    for segment in segments(content) {
+        match segment {
+            Segment::Code(code) => out.push_str(code),
+            Segment::Text(text) => out.push_str(&convert_text(text, names)),
+        }
+    }
+    out
+}
+
+enum Segment<'a> {
+    Text(&'a str),
+    Code(&'a str),
+}
+
+/// Split content into code (fences and inline spans, delimiters included) and everything else.
+/// An unterminated delimiter is treated as text, matching Discord's rendering.
+fn segments(content: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut rest = content;
+
+    while let Some(start) = rest.find('`') {
+        let delimiter = if rest[start..].starts_with("```") {
+            "```"
+        } else {
+            "`"
+        };
+        let body_start = start + delimiter.len();
+        match rest[body_start..].find(delimiter) {
+            Some(len) => {
+                let end = body_start + len + delimiter.len();
+                if start > 0 {
+                    segments.push(Segment::Text(&rest[..start]));
+                }
+                segments.push(Segment::Code(&rest[start..end]));
+                rest = &rest[end..];
+            }
+            None => break,
+        }
+    }
+
+    if !rest.is_empty() {
+        segments.push(Segment::Text(rest));
+    }
+    segments
+}
+
+fn convert_text(text: &str, names: &MentionNames) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('<') {
+        out.push_str(&rest[..start]);
+        match parse_mention(&rest[start..]) {
+            Some((mention, len)) => {
+                out.push_str(&render_mention(&mention, names));
+                rest = &rest[start + len..];
+            }
+            None => {
+                out.push('<');
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+    out.push_str(rest);
+
+    convert_paired(
+        &convert_paired(&out, "||", "*[spoiler]* ", ""),
+        "~~",
+        "~",
+        "~",
+    )
+}
+
+fn render_mention(mention: &Mention<'_>, names: &MentionNames) -> String {
+    let lookup = |map: &HashMap<u64, String>, id: &u64, fallback: &str| {
+        map.get(id).cloned().unwrap_or_else(|| fallback.to_string())
+    };
+    match mention {
+        Mention::User(id) => format!("@{}", lookup(&names.users, id, "unknown-user")),
+        Mention::Role(id) => format!("@{}", lookup(&names.roles, id, "unknown-role")),
+        Mention::Channel(id) => format!("#{}", lookup(&names.channels, id, "unknown-channel")),
+        Mention::Emoji(name) => format!(":{name}:"),
+    }
+}
+
+/// Parse a mention at the start of `s` (which begins with `<`), returning it and its length.
+fn parse_mention(s: &str) -> Option<(Mention<'_>, usize)> {
+    let end = s.find('>')?;
+    let inner = &s[1..end];
+    let len = end + 1;
+
+    let mention = if let Some(id) = inner.strip_prefix("@&") {
+        Mention::Role(id.parse().ok()?)
+    } else if let Some(id) = inner.strip_prefix("@!").or_else(|| inner.strip_prefix('@')) {
+        Mention::User(id.parse().ok()?)
+    } else if let Some(id) = inner.strip_prefix('#') {
+        Mention::Channel(id.parse().ok()?)
+    } else {
+        let emoji = inner
+            .strip_prefix("a:")
+            .or_else(|| inner.strip_prefix(':'))?;
+        let (name, id) = emoji.split_once(':')?;
+        id.parse::<u64>().ok()?;
+        if name.is_empty() {
+            return None;
+        }
+        Mention::Emoji(name)
+    };
+    Some((mention, len))
+}
+
+/// Every mention in the non-code parts of `content`.
+fn mentions(content: &str) -> Vec<Mention<'_>> {
+    let mut found = Vec::new();
+    for segment in segments(content) {
+        let Segment::Text(mut rest) = segment else {
+            continue;
+        };
+        while let Some(start) = rest.find('<') {
+            match parse_mention(&rest[start..]) {
+                Some((mention, len)) => {
+                    found.push(mention);
+                    rest = &rest[start + len..];
+                }
+                None => rest = &rest[start + 1..],
+            }
+        }
+    }
+    found
+}
+
+/// Rewrite `delimiter`-wrapped spans as `open`…`close`. An unpaired delimiter is left as is.
+fn convert_paired(text: &str, delimiter: &str, open: &str, close: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find(delimiter) {
+        let body_start = start + delimiter.len();
+        match rest[body_start..].find(delimiter) {
+            Some(len) => {
+                out.push_str(&rest[..start]);
+                out.push_str(open);
+                out.push_str(&rest[body_start..body_start + len]);
+                out.push_str(close);
+                rest = &rest[body_start + len + delimiter.len()..];
+            }
+            None => break,
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Convert Linear markdown to something that reads well in a Discord message: headings become
+/// bold lines, tables are flattened to one line per row, profile mentions lose their (login-only)
+/// links, and bare issue URLs are shortened to their identifier. Fenced code is left alone.
+pub fn linear_to_discord(body: &str) -> String {
+    let mut lines = Vec::new();
+    let mut in_fence = false;
+
+    for line in body.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") {
+            in_fence = !in_fence;
+            lines.push(line.to_string());
+            continue;
+        }
+        if in_fence {
+            lines.push(line.to_string());
+            continue;
+        }
+
+        if let Some(heading) = heading_text(trimmed) {
+            lines.push(format!("**{}**", convert_links(heading)));
+        } else if is_table_row(trimmed) {
+            if !is_table_separator(trimmed) {
+                lines.push(convert_links(&flatten_table_row(trimmed)));
+            }
+        } else {
+            lines.push(convert_links(line));
+        }
+    }
+
+    lines.join("\n")
+}
+
+fn heading_text(line: &str) -> Option<&str> {
+    let hashes = line.chars().take_while(|&c| c == '#').count();
+    if !(1..=6).contains(&hashes) {
+        return None;
+    }
+    line[hashes..]
+        .strip_prefix(' ')
+        .map(|t| t.trim().trim_end_matches('#').trim_end())
+}
+
+fn is_table_row(line: &str) -> bool {
+    line.starts_with('|') && line.trim_end().ends_with('|') && line.trim_end().len() > 1
+}
+
+fn is_table_separator(line: &str) -> bool {
+    line.chars()
+        .all(|c| matches!(c, '|' | '-' | ':' | ' ' | '\t'))
+}
+
+fn flatten_table_row(line: &str) -> String {
+    line.trim()
+        .trim_matches('|')
+        .split('|')
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .join(" · ")
+}
+
+/// Rewrite Linear links within a line: `[@Name](…/profiles/…)` becomes `@Name`, and a bare
+/// `https://linear.app/<org>/issue/ABC-123/…` URL becomes a masked link labelled `ABC-123`.
+fn convert_links(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+
+    while let Some(start) = rest.find(['[', 'h']) {
+        let candidate = &rest[start..];
+        if let Some((text, len)) = profile_link(candidate) {
+            out.push_str(&rest[..start]);
+            out.push_str(text);
+            rest = &candidate[len..];
+        } else if let Some((identifier, url)) = issue_url(candidate) {
+            // Skip URLs that are already the target of a markdown link.
+            if rest[..start].ends_with("](") || rest[..start].ends_with("](<") {
+                out.push_str(&rest[..start + url.len()]);
+            } else {
+                out.push_str(&rest[..start]);
+                out.push_str(&format!("[{identifier}](<{url}>)"));
+            }
+            rest = &candidate[url.len()..];
+        } else {
+            let next = start + candidate.chars().next().map_or(1, char::len_utf8);
+            out.push_str(&rest[..next]);
+            rest = &rest[next..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// `[@Name](https://linear.app/<org>/profiles/<user>)` at the start of `s`.
+fn profile_link(s: &str) -> Option<(&str, usize)> {
+    let text_end = s.find("](")?;
+    let text = &s[1..text_end];
+    if !s.starts_with("[@") || text.contains(']') {
+        return None;
+    }
+    let url_start = text_end + 2;
+    let url_len = s[url_start..].find(')')?;
+    let url = &s[url_start..url_start + url_len];
+    (url.starts_with(LINEAR_APP_URL) && url.contains("/profiles/"))
+        .then_some((text, url_start + url_len + 1))
+}
+
+/// A bare `https://linear.app/<org>/issue/<identifier>/…` URL at the start of `s`.
+fn issue_url(s: &str) -> Option<(&str, &str)> {
+    if !s.starts_with(LINEAR_APP_URL) {
+        return None;
+    }
+    let end = s
+        .find(|c: char| c.is_whitespace() || matches!(c, ')' | '>' | ']'))
+        .unwrap_or(s.len());
+    let url = &s[..end];
+    let mut parts = url[LINEAR_APP_URL.len()..].split('/');
+    let _org = parts.next()?;
+    if parts.next()? != "issue" {
+        return None;
+    }
+    let identifier = parts.next().filter(|id| id.contains('-'))?;
+    Some((identifier, url))
+}
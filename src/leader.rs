@@ -0,0 +1,106 @@
+//! Leader election for running several replicas against one database.
+//!
+//! Replicas compete for a lease in the `leader_lease` table. The holder connects to Discord and
+//! runs the sync, renewing the lease every third of its lifetime; the others finish starting up
+//! and stand by, trying to take the lease as often. A leader that loses the lease, or can't renew
+//! it before it runs out, exits without waiting for in-flight syncs rather than write alongside
+//! its successor, and a leader that shuts down releases it so a standby takes over at once.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use sqlx::SqlitePool;
+use tracing::{error, info, warn};
+
+use crate::db;
+use crate::shutdown::Shutdown;
+
+pub struct LeaderLease {
+    pool: SqlitePool,
+    /// This replica's identity in the lease table, unique per process.
+    holder: String,
+    ttl: Duration,
+    lost: AtomicBool,
+}
+
+impl LeaderLease {
+    pub fn new(pool: SqlitePool, ttl: Duration) -> Self {
+        Self {
+            pool,
+            holder: uuid::Uuid::new_v4().to_string(),
+            ttl,
+            lost: AtomicBool::new(false),
+        }
+    }
+
+    pub fn holder(&self) -> &str {
+        &self.holder
+    }
+
+    /// Whether `hold` gave up the lease, in which case a standby may already be syncing.
+    pub fn is_lost(&self) -> bool {
+        self.lost.load(Ordering::SeqCst)
+    }
+
+    /// Take the lease, or renew it if this replica already holds it. Returns whether it does.
+    pub async fn try_acquire(&self) -> Result<bool, sqlx::Error> {
+        db::acquire_leader_lease(&self.pool, &self.holder, self.ttl.as_secs() as i64).await
+    }
+
+    /// Wait until this replica holds the lease. Returns `false` if shutdown is requested first.
+    pub async fn acquire(&self, shutdown: &Shutdown) -> bool {
+        let mut standing_by = false;
+        loop {
+            match self.try_acquire().await {
+                Ok(true) => {
+                    info!(holder = %self.holder, "Acquired the leader lease");
+                    return true;
+                }
+                Ok(false) if !standing_by => {
+                    info!("Another replica holds the leader lease, standing by");
+                    standing_by = true;
+                }
+                Ok(false) => {}
+                Err(e) => warn!(error = %e, "Failed to check the leader lease"),
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(self.renew_interval()) => {}
+                _ = shutdown.requested() => return false,
+            }
+        }
+    }
+
+    /// Keep renewing the lease. Returns after requesting shutdown once the lease is lost, or is
+    /// about to run out because renewals keep failing; otherwise runs until dropped.
+    pub async fn hold(&self, shutdown: &Shutdown) {
+        let mut expires = Instant::now() + self.ttl;
+        loop {
+            tokio::time::sleep(self.renew_interval()).await;
+            let attempted = Instant::now();
+            match self.try_acquire().await {
+                Ok(true) => expires = attempted + self.ttl,
+                Ok(false) => {
+                    error!("Another replica took the leader lease, shutting down");
+                    break;
+                }
+                // The next attempt would come too late: a standby may take over before it.
+                Err(e) if Instant::now() + self.renew_interval() >= expires => {
+                    error!(error = %e, "Couldn't renew the leader lease in time, shutting down");
+                    break;
+                }
+                Err(e) => warn!(error = %e, "Failed to renew the leader lease, retrying"),
+            }
+        }
+        self.lost.store(true, Ordering::SeqCst);
+        shutdown.request();
+    }
+
+    /// Give up the lease so a standby can take over without waiting for it to expire.
+    pub async fn release(&self) -> Result<(), sqlx::Error> {
+        db::release_leader_lease(&self.pool, &self.holder).await
+    }
+
+    fn renew_interval(&self) -> Duration {
+        (self.ttl / 3).max(Duration::from_secs(1))
+    }
+}
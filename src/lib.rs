@@ -0,0 +1,19 @@
+pub mod breaker;
+pub mod config;
+pub mod credentials;
+pub mod db;
+pub mod discord;
+pub mod doctor;
+pub mod error;
+#[cfg(feature = "sentry")]
+pub mod error_reporting;
+pub mod format;
+pub mod leader;
+pub mod linear;
+pub mod shadow;
+pub mod shutdown;
+pub mod supervisor;
+pub mod sync;
+pub mod telemetry;
+pub mod templates;
+pub mod transfer;
@@ -0,0 +1,176 @@
+//! The Linear operations the bot depends on.
+//!
+//! Sync code takes `&impl LinearApi` rather than a concrete client so it can be driven against
+//! a fake in tests; [`LinearClient`](super::client::LinearClient) is the production
+//! implementation.
+
+use async_trait::async_trait;
+
+use super::client::{
+    AttachmentDownload, CommentAuthor, Customer, LinearComment, LinearIssue, LinearIssueDetail,
+    LinearIssueStatus, LinearSearchResult, LinearUser, TeamMetadata, UploadFile,
+};
+use crate::error::AppError;
+
+#[async_trait]
+pub trait LinearApi: Send + Sync {
+    /// Whether calls are currently going out. `false` while the client's circuit breaker is open
+    /// after repeated failures; background jobs skip their pass rather than fail every call.
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    /// Create an issue in `team_id`.
+    async fn create_issue(
+        &self,
+        team_id: &str,
+        title: &str,
+        description: &str,
+        label_ids: &[String],
+        project_id: &str,
+    ) -> Result<LinearIssue, AppError>;
+
+    /// Fetch a single issue by UUID or human identifier (`ABC-123`).
+    async fn get_issue(&self, id_or_identifier: &str) -> Result<LinearIssueDetail, AppError>;
+
+    /// Fetch issues updated since `since` (ISO 8601 timestamp) for a specific team.
+    async fn get_updated_issues(
+        &self,
+        team_id: &str,
+        since: &str,
+    ) -> Result<Vec<LinearIssueStatus>, AppError>;
+
+    /// Fetch current state for a specific set of issue IDs in a single query.
+    /// Issues that no longer exist or aren't visible are silently omitted from the result.
+    async fn get_issues_by_ids(&self, ids: &[String]) -> Result<Vec<LinearIssueStatus>, AppError>;
+
+    /// Comments on an issue in creation order, limited to those created after `since` when
+    /// given.
+    async fn get_issue_comments(
+        &self,
+        issue_id: &str,
+        since: Option<&str>,
+    ) -> Result<Vec<LinearComment>, AppError>;
+
+    /// Fetch comments by ID. Comments that have been deleted are omitted from the result.
+    async fn get_comments_by_ids(
+        &self,
+        comment_ids: &[String],
+    ) -> Result<Vec<LinearComment>, AppError>;
+
+    /// Post a comment on an issue, returning the new comment's ID. Passing `comment_id` (a UUID)
+    /// lets the caller record the comment before it exists; otherwise the client picks one.
+    /// `author` shows it as posted by someone other than the API key's user.
+    async fn create_comment(
+        &self,
+        comment_id: Option<&str>,
+        issue_id: &str,
+        body: &str,
+        author: Option<&CommentAuthor>,
+    ) -> Result<String, AppError>;
+
+    /// Full-text search for issues within the given teams, best matches first.
+    async fn search_issues(
+        &self,
+        term: &str,
+        team_ids: &[String],
+        limit: usize,
+    ) -> Result<Vec<LinearSearchResult>, AppError>;
+
+    /// Fetch active members of a team.
+    async fn get_team_members(&self, team_id: &str) -> Result<Vec<LinearUser>, AppError>;
+
+    /// A team's workflow states, labels, projects, and active members, in one request.
+    async fn get_team_metadata(&self, team_id: &str) -> Result<TeamMetadata, AppError>;
+
+    /// Active workspace user with the given email, if any.
+    async fn find_user_by_email(&self, email: &str) -> Result<Option<LinearUser>, AppError>;
+
+    /// Whether `user_id` has written a comment containing `text`.
+    async fn user_commented(&self, user_id: &str, text: &str) -> Result<bool, AppError>;
+
+    /// The user the API key belongs to.
+    async fn viewer(&self) -> Result<LinearUser, AppError>;
+
+    /// Set (or clear, with `None`) an issue's assignee.
+    async fn update_issue_assignee(
+        &self,
+        issue_id: &str,
+        assignee_id: Option<&str>,
+    ) -> Result<(), AppError>;
+
+    /// Set an issue's priority (0 = none, 1 = urgent ... 4 = low).
+    async fn update_issue_priority(&self, issue_id: &str, priority: i64) -> Result<(), AppError>;
+
+    /// Move an issue to another project.
+    async fn update_issue_project(&self, issue_id: &str, project_id: &str) -> Result<(), AppError>;
+
+    /// ID of the team's first workflow state in the canceled category, if it has one.
+    async fn get_canceled_state_id(&self, team_id: &str) -> Result<Option<String>, AppError>;
+
+    /// Move an issue to another workflow state.
+    async fn update_issue_state(&self, issue_id: &str, state_id: &str) -> Result<(), AppError>;
+
+    /// Current markdown description of an issue.
+    async fn get_issue_description(&self, issue_id: &str) -> Result<String, AppError>;
+
+    /// Replace an issue's description.
+    async fn update_issue_description(
+        &self,
+        issue_id: &str,
+        description: &str,
+    ) -> Result<(), AppError>;
+
+    /// Set an issue's title.
+    async fn update_issue_title(&self, issue_id: &str, title: &str) -> Result<(), AppError>;
+
+    /// Set an issue's estimate in the team's estimation scale.
+    async fn update_issue_estimate(&self, issue_id: &str, estimate: i64) -> Result<(), AppError>;
+
+    /// Add a label to an issue, keeping its other labels.
+    async fn add_issue_label(&self, issue_id: &str, label_id: &str) -> Result<(), AppError>;
+
+    /// Record a customer need on an issue: `body` is the request, `url` where it was made.
+    /// `customer` is created in Linear on first use and found by its external ID after that.
+    async fn create_customer_need(
+        &self,
+        issue_id: &str,
+        customer: &Customer,
+        body: &str,
+        url: &str,
+    ) -> Result<(), AppError>;
+
+    /// Attach a link to `url` to an issue. Linking a URL the issue already has updates the
+    /// existing attachment rather than adding another.
+    async fn link_url(&self, issue_id: &str, url: &str, title: &str) -> Result<(), AppError>;
+
+    /// Reserve an upload slot for an attachment to an issue in `team_or_issue_id`'s workspace.
+    async fn request_file_upload(
+        &self,
+        team_or_issue_id: &str,
+        filename: &str,
+        content_type: &str,
+        size: u64,
+    ) -> Result<UploadFile, AppError>;
+
+    /// Upload `size` bytes of `body` to a slot from [`LinearApi::request_file_upload`],
+    /// returning the asset URL.
+    async fn upload_file_to_url(
+        &self,
+        upload: &UploadFile,
+        body: reqwest::Body,
+        size: u64,
+        content_type: &str,
+    ) -> Result<String, AppError>;
+
+    /// Start downloading a publicly reachable file (e.g. a Discord attachment).
+    async fn download_attachment(&self, url: &str) -> Result<AttachmentDownload, AppError>;
+
+    /// Download a file hosted on `uploads.linear.app`, which requires the API key of the
+    /// workspace `issue_id` is in.
+    async fn download_linear_upload(
+        &self,
+        issue_id: &str,
+        url: &str,
+    ) -> Result<(Vec<u8>, String), AppError>;
+}
@@ -0,0 +1,88 @@
+//! Per-team Linear metadata (workflow states, labels, projects, members), kept in memory so
+//! slash command autocomplete answers instantly instead of hitting Linear on every keystroke.
+//!
+//! `run_metadata_refresh` refetches every configured team on `METADATA_REFRESH_SECS`; a team
+//! that isn't cached yet (say, one added since the last pass) is fetched on first lookup.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+
+use crate::config::SharedConfig;
+use crate::error::AppError;
+use crate::linear::api::LinearApi;
+use crate::linear::client::TeamMetadata;
+
+#[derive(Clone, Default)]
+pub struct MetadataCache {
+    teams: Arc<RwLock<HashMap<String, Arc<TeamMetadata>>>>,
+}
+
+impl MetadataCache {
+    /// The team's cached metadata, fetched now if it isn't cached yet.
+    pub async fn team(
+        &self,
+        linear: &impl LinearApi,
+        team_id: &str,
+    ) -> Result<Arc<TeamMetadata>, AppError> {
+        if let Some(metadata) = self.teams.read().await.get(team_id) {
+            return Ok(metadata.clone());
+        }
+        self.fetch(linear, team_id).await
+    }
+
+    /// Refetch each of `team_ids` and drop teams no longer in the list. A team that fails to
+    /// refresh keeps its previous metadata. Returns how many teams were refreshed.
+    pub async fn refresh(&self, linear: &impl LinearApi, team_ids: &[String]) -> usize {
+        self.teams
+            .write()
+            .await
+            .retain(|team_id, _| team_ids.contains(team_id));
+
+        let mut refreshed = 0;
+        for team_id in team_ids {
+            match self.fetch(linear, team_id).await {
+                Ok(_) => refreshed += 1,
+                Err(e) => warn!(team_id, error = %e, "Failed to refresh Linear metadata"),
+            }
+        }
+        refreshed
+    }
+
+    async fn fetch(
+        &self,
+        linear: &impl LinearApi,
+        team_id: &str,
+    ) -> Result<Arc<TeamMetadata>, AppError> {
+        let metadata = Arc::new(linear.get_team_metadata(team_id).await?);
+        self.teams
+            .write()
+            .await
+            .insert(team_id.to_string(), metadata.clone());
+        Ok(metadata)
+    }
+}
+
+/// Keep `cache` filled for every configured team, refreshing every `metadata_refresh_secs`.
+pub async fn run_metadata_refresh(
+    linear: impl LinearApi,
+    config: SharedConfig,
+    cache: MetadataCache,
+) {
+    let interval_secs = config.current().metadata_refresh_secs;
+    info!(interval_secs, "Starting Linear metadata refresh task");
+
+    loop {
+        let team_ids = config.current().unique_team_ids();
+        let refreshed = cache.refresh(&linear, &team_ids).await;
+        debug!(
+            refreshed,
+            teams = team_ids.len(),
+            "Refreshed Linear metadata"
+        );
+
+        tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
+    }
+}
@@ -1,17 +1,49 @@
+use async_trait::async_trait;
+use graphql_client::GraphQLQuery;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use serde_json::{json, Value};
-use tracing::{debug, warn};
+use serde_json::Value;
+use tracing::{debug, instrument, warn};
 
+use super::api::LinearApi;
+use super::rate_limit::RateLimiter;
+use super::schema::{
+    self, add_issue_label, attachment_link_url, canceled_state, comments_by_ids, create_comment,
+    create_customer_need, create_issue, file_upload, issue, issue_comments, issue_description,
+    issues_by_ids, label_name, project_name, search_issues, team_choices, team_members,
+    team_metadata, team_name, teams, unassign_issue, update_issue, updated_issues, upsert_customer,
+    user_by_email, user_comment, viewer, AddIssueLabel, AttachmentLinkUrl, CanceledState,
+    CommentsByIds, CreateComment, CreateCustomerNeed, CreateIssue, FileUpload, Issue,
+    IssueComments, IssueDescription, IssuesByIds, LabelName, ProjectName, SearchIssues,
+    TeamMembers, TeamName, Teams, UnassignIssue, UpdateIssue, UpdatedIssues, UpsertCustomer,
+    UserByEmail, UserComment, Viewer,
+};
+use crate::breaker::{self, CircuitBreaker};
 use crate::error::AppError;
 
 #[derive(Debug, Clone)]
 pub struct LinearClient {
     client: Client,
     api_key: String,
+    endpoint: String,
+    max_attempts: u32,
+    rate_limit: RateLimiter,
+    breaker: CircuitBreaker,
 }
 
-#[derive(Debug, Deserialize)]
+/// Linear's GraphQL endpoint.
+const LINEAR_API_URL: &str = "https://api.linear.app/graphql";
+
+/// Attempts per GraphQL request unless overridden with [`LinearClient::with_max_attempts`].
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
+/// A GraphQL request that takes longer than this is abandoned and retried.
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Longest wait between retries, however many attempts are configured.
+const MAX_BACKOFF: std::time::Duration = std::time::Duration::from_secs(30);
+
+#[derive(Debug)]
 pub struct LinearIssue {
     pub id: String,
     pub identifier: String,
@@ -19,45 +51,293 @@ pub struct LinearIssue {
     pub url: String,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LinearComment {
     pub id: String,
     pub body: String,
     pub created_at: String,
+    pub updated_at: String,
     pub author_name: String,
+    pub author_avatar_url: Option<String>,
+    /// Linear user ID; `None` for comments posted by integrations and bots.
+    pub author_id: Option<String>,
+    pub author_email: Option<String>,
+}
+
+/// Workflow state category (Linear's `WorkflowState.type`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateCategory {
+    Triage,
+    Backlog,
+    Unstarted,
+    Started,
+    Completed,
+    Canceled,
+    Unknown,
+}
+
+impl StateCategory {
+    pub fn from_type(state_type: &str) -> Self {
+        match state_type {
+            "triage" => StateCategory::Triage,
+            "backlog" => StateCategory::Backlog,
+            "unstarted" => StateCategory::Unstarted,
+            "started" => StateCategory::Started,
+            "completed" => StateCategory::Completed,
+            "canceled" => StateCategory::Canceled,
+            _ => StateCategory::Unknown,
+        }
+    }
+
+    /// Linear's name for the category, as used in `state_tag_map` keys.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            StateCategory::Triage => "triage",
+            StateCategory::Backlog => "backlog",
+            StateCategory::Unstarted => "unstarted",
+            StateCategory::Started => "started",
+            StateCategory::Completed => "completed",
+            StateCategory::Canceled => "canceled",
+            StateCategory::Unknown => "unknown",
+        }
+    }
+
+    /// Whether the issue is closed (completed or canceled).
+    pub fn is_terminal(self) -> bool {
+        matches!(self, StateCategory::Completed | StateCategory::Canceled)
+    }
 }
 
 #[derive(Debug)]
 pub struct LinearIssueStatus {
     pub id: String,
     pub identifier: String,
+    pub title: String,
+    pub url: String,
+    pub assignee: Option<LinearAssignee>,
+    /// Due date as `YYYY-MM-DD`, if set.
+    pub due_date: Option<String>,
+    pub status_name: String,
+    pub status_type: String,
+    /// Workflow state color as a `#rrggbb` hex string.
+    pub status_color: String,
+    /// Human-readable priority ("Urgent", "High", ..., "No priority").
+    pub priority_label: String,
+    pub label_ids: Vec<String>,
+    /// Label names, in the same order as `label_ids`.
+    pub labels: Vec<String>,
+    pub project_name: Option<String>,
+    pub updated_at: String,
+}
+
+/// The user an issue is assigned to.
+#[derive(Debug)]
+pub struct LinearAssignee {
+    pub id: String,
+    pub name: String,
+}
+
+/// A single issue looked up by ID or identifier (e.g. `ABC-123`).
+#[derive(Debug)]
+pub struct LinearIssueDetail {
+    pub id: String,
+    pub identifier: String,
+    pub title: String,
+    pub url: String,
     pub status_name: String,
     pub status_type: String,
+    /// Workflow state color as a `#rrggbb` hex string.
+    pub status_color: String,
+    pub assignee_name: Option<String>,
+    /// Human-readable priority ("Urgent", "High", ..., "No priority").
+    pub priority_label: String,
+    pub labels: Vec<String>,
+    pub project_name: Option<String>,
     pub updated_at: String,
 }
 
-#[derive(Debug, Deserialize)]
+/// A compact issue reference returned by search.
+#[derive(Debug)]
+pub struct LinearSearchResult {
+    pub identifier: String,
+    pub title: String,
+    pub url: String,
+    pub status_name: String,
+}
+
+/// A member of a Linear team.
+#[derive(Debug, Clone)]
+pub struct LinearUser {
+    pub id: String,
+    pub name: String,
+    pub display_name: String,
+    pub email: String,
+}
+
+/// Someone outside the team asking for an issue, as a Linear customer. `external_id` identifies
+/// them across requests.
+#[derive(Debug, Clone)]
+pub struct Customer {
+    pub external_id: String,
+    pub name: String,
+}
+
+/// Who a comment is shown as posted by, in place of the API key's user.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommentAuthor {
+    pub name: String,
+    pub avatar_url: Option<String>,
+}
+
+/// A team the API key can see.
+#[derive(Debug)]
+pub struct LinearTeam {
+    pub id: String,
+    /// Prefix of the team's issue identifiers, e.g. `ENG`.
+    pub key: String,
+    pub name: String,
+}
+
+/// The projects and labels a channel filing into a team can use.
+#[derive(Debug, Default)]
+pub struct TeamChoices {
+    /// `(id, name)` of each of the team's projects.
+    pub projects: Vec<(String, String)>,
+    /// `(id, name)` of the team's labels and the workspace-wide ones.
+    pub labels: Vec<(String, String)>,
+}
+
+/// A team's workflow states, labels, projects, and members, as offered by autocomplete.
+#[derive(Debug, Clone, Default)]
+pub struct TeamMetadata {
+    pub key: String,
+    pub name: String,
+    pub states: Vec<LinearWorkflowState>,
+    /// `(id, name)` of the team's labels and the workspace-wide ones.
+    pub labels: Vec<(String, String)>,
+    /// `(id, name)` of each of the team's projects.
+    pub projects: Vec<(String, String)>,
+    /// Active members only.
+    pub members: Vec<LinearUser>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LinearWorkflowState {
+    pub id: String,
+    pub name: String,
+    /// Linear's `WorkflowState.type`; see [`StateCategory`].
+    pub state_type: String,
+}
+
+impl LinearIssueStatus {
+    pub fn category(&self) -> StateCategory {
+        StateCategory::from_type(&self.status_type)
+    }
+
+    /// The fields shown in the issue embed, without fetching the issue again.
+    pub fn detail(&self) -> LinearIssueDetail {
+        LinearIssueDetail {
+            id: self.id.clone(),
+            identifier: self.identifier.clone(),
+            title: self.title.clone(),
+            url: self.url.clone(),
+            status_name: self.status_name.clone(),
+            status_type: self.status_type.clone(),
+            status_color: self.status_color.clone(),
+            assignee_name: self.assignee.as_ref().map(|a| a.name.clone()),
+            priority_label: self.priority_label.clone(),
+            labels: self.labels.clone(),
+            project_name: self.project_name.clone(),
+            updated_at: self.updated_at.clone(),
+        }
+    }
+}
+
+impl LinearIssueDetail {
+    pub fn category(&self) -> StateCategory {
+        StateCategory::from_type(&self.status_type)
+    }
+}
+
+#[derive(Debug)]
 pub struct UploadFile {
     pub upload_url: String,
     pub asset_url: String,
     pub headers: Vec<UploadHeader>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug)]
 pub struct UploadHeader {
     pub key: String,
     pub value: String,
 }
 
+/// A file being downloaded for upload to Linear. `body` reads the response as it is consumed, so
+/// passing it to [`LinearApi::upload_file_to_url`] streams the file through a chunk at a time
+/// instead of holding all of it in memory.
+#[derive(Debug)]
+pub struct AttachmentDownload {
+    pub content_type: String,
+    /// Length in bytes from the response's `Content-Length`, if it sent one.
+    pub size: Option<u64>,
+    pub body: reqwest::Body,
+}
+
 impl LinearClient {
     pub fn new(api_key: String) -> Self {
+        Self::with_endpoint(api_key, LINEAR_API_URL.to_string())
+    }
+
+    /// A client that sends GraphQL requests to `endpoint` instead of Linear, e.g. a local mock
+    /// server in tests.
+    pub fn with_endpoint(api_key: String, endpoint: String) -> Self {
         Self {
             client: Client::new(),
             api_key,
+            endpoint,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            rate_limit: RateLimiter::default(),
+            breaker: CircuitBreaker::new(
+                "Linear",
+                breaker::DEFAULT_THRESHOLD,
+                std::time::Duration::from_secs(breaker::DEFAULT_COOLDOWN_SECS),
+            ),
         }
     }
 
-    pub async fn create_issue(
+    /// Try each GraphQL request up to `max_attempts` times (at least once) before giving up on
+    /// a transient failure.
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Trip after `threshold` requests in a row fail transiently (each after its retries), and
+    /// then refuse requests for `cooldown`.
+    pub fn with_circuit_breaker(mut self, threshold: u32, cooldown: std::time::Duration) -> Self {
+        self.breaker = CircuitBreaker::new("Linear", threshold, cooldown);
+        self
+    }
+
+    /// A client with these settings for another API key. Its rate limit and breaker are its own,
+    /// since Linear meters each key separately.
+    pub fn with_api_key(&self, api_key: &str) -> Self {
+        Self {
+            api_key: api_key.to_string(),
+            rate_limit: RateLimiter::default(),
+            breaker: self.breaker.reset(),
+            ..self.clone()
+        }
+    }
+}
+
+#[async_trait]
+impl LinearApi for LinearClient {
+    fn is_available(&self) -> bool {
+        self.breaker.is_available()
+    }
+
+    async fn create_issue(
         &self,
         team_id: &str,
         title: &str,
@@ -65,287 +345,496 @@ impl LinearClient {
         label_ids: &[String],
         project_id: &str,
     ) -> Result<LinearIssue, AppError> {
-        let query = r#"
-            mutation CreateIssue($input: IssueCreateInput!) {
-                issueCreate(input: $input) {
-                    success
-                    issue {
-                        id
-                        identifier
-                        title
-                        url
-                    }
-                }
-            }
-        "#;
-
-        let variables = json!({
-            "input": {
-                "teamId": team_id,
-                "title": title,
-                "description": description,
-                "labelIds": label_ids,
-                "projectId": project_id,
+        // Our own ID makes the mutation safe to retry, and lets us find the issue if an attempt
+        // landed but its response was lost.
+        let id = uuid::Uuid::new_v4().to_string();
+        let input = create_issue::IssueCreateInput {
+            id: Some(id.clone()),
+            team_id: team_id.to_string(),
+            title: Some(title.to_string()),
+            description: Some(description.to_string()),
+            label_ids: Some(label_ids.to_vec()),
+            project_id: Some(project_id.to_string()),
+            ..Default::default()
+        };
+
+        let variables = create_issue::Variables { input };
+        match self
+            .create::<CreateIssue, _>(variables, self.get_issue(&id))
+            .await?
+        {
+            Ok(data) => {
+                let issue = data
+                    .issue_create
+                    .issue
+                    .ok_or_else(|| AppError::LinearApi("issueCreate returned no issue".into()))?;
+                Ok(LinearIssue {
+                    id: issue.id,
+                    identifier: issue.identifier,
+                    title: issue.title,
+                    url: issue.url,
+                })
             }
-        });
+            Err(earlier) => Ok(LinearIssue {
+                id: earlier.id,
+                identifier: earlier.identifier,
+                title: earlier.title,
+                url: earlier.url,
+            }),
+        }
+    }
 
-        let data = self.execute(query, variables).await?;
-        let issue_data = &data["issueCreate"]["issue"];
-
-        Ok(LinearIssue {
-            id: issue_data["id"]
-                .as_str()
-                .ok_or_else(|| AppError::LinearApi("Missing issue id".into()))?
-                .to_string(),
-            identifier: issue_data["identifier"]
-                .as_str()
-                .ok_or_else(|| AppError::LinearApi("Missing issue identifier".into()))?
-                .to_string(),
-            title: issue_data["title"]
-                .as_str()
-                .ok_or_else(|| AppError::LinearApi("Missing issue title".into()))?
-                .to_string(),
-            url: issue_data["url"]
-                .as_str()
-                .ok_or_else(|| AppError::LinearApi("Missing issue url".into()))?
-                .to_string(),
-        })
+    async fn get_issue(&self, id_or_identifier: &str) -> Result<LinearIssueDetail, AppError> {
+        let variables = issue::Variables {
+            id: id_or_identifier.to_string(),
+        };
+        let data = self.execute::<Issue>(variables).await?;
+        Ok(data.issue.into())
     }
 
-    /// Fetch issues updated since `since` (ISO 8601 timestamp) for a specific team.
-    pub async fn get_updated_issues(
+    async fn get_updated_issues(
         &self,
         team_id: &str,
         since: &str,
     ) -> Result<Vec<LinearIssueStatus>, AppError> {
-        let query = r#"
-            query UpdatedIssues($teamId: ID!, $since: DateTimeOrDuration!) {
-                issues(
-                    filter: {
-                        team: { id: { eq: $teamId } }
-                        updatedAt: { gt: $since }
-                    }
-                    first: 100
-                ) {
-                    nodes {
-                        id
-                        identifier
-                        state {
-                            name
-                            type
-                        }
-                        updatedAt
-                    }
-                }
-            }
-        "#;
-
-        let variables = json!({
-            "teamId": team_id,
-            "since": since,
-        });
+        // Page through everything updated since the last poll; after downtime a busy team can
+        // have far more than one page.
+        let mut results = Vec::new();
+        let mut after: Option<String> = None;
+        loop {
+            let variables = updated_issues::Variables {
+                team_id: team_id.to_string(),
+                since: since.to_string(),
+                after: after.take(),
+            };
 
-        let data = self.execute(query, variables).await?;
-        let nodes = data["issues"]["nodes"]
-            .as_array()
-            .ok_or_else(|| AppError::LinearApi("Missing issues.nodes".into()))?;
+            let data = self.execute::<UpdatedIssues>(variables).await?;
+            results.extend(data.issues.nodes.into_iter().map(Into::into));
 
-        let mut results = Vec::new();
-        for node in nodes {
-            let id = node["id"].as_str().unwrap_or_default().to_string();
-            let identifier = node["identifier"].as_str().unwrap_or_default().to_string();
-            let status_name = node["state"]["name"]
-                .as_str()
-                .unwrap_or_default()
-                .to_string();
-            let status_type = node["state"]["type"]
-                .as_str()
-                .unwrap_or_default()
-                .to_string();
-            let updated_at = node["updatedAt"].as_str().unwrap_or_default().to_string();
-
-            results.push(LinearIssueStatus {
-                id,
-                identifier,
-                status_name,
-                status_type,
-                updated_at,
-            });
+            let page = data.issues.page_info;
+            match page.end_cursor {
+                Some(cursor) if page.has_next_page => after = Some(cursor),
+                _ => break,
+            }
         }
 
         Ok(results)
     }
 
-    /// Fetch current state for a specific set of issue IDs in a single query.
-    /// Issues that no longer exist or aren't visible are silently omitted from the result.
-    pub async fn get_issues_by_ids(
-        &self,
-        ids: &[String],
-    ) -> Result<Vec<LinearIssueStatus>, AppError> {
+    async fn get_issues_by_ids(&self, ids: &[String]) -> Result<Vec<LinearIssueStatus>, AppError> {
         if ids.is_empty() {
             return Ok(Vec::new());
         }
 
-        let query = r#"
-            query IssuesByIds($ids: [ID!]!) {
-                issues(filter: { id: { in: $ids } }, first: 250) {
-                    nodes {
-                        id
-                        identifier
-                        state {
-                            name
-                            type
-                        }
-                        updatedAt
-                    }
-                }
-            }
-        "#;
+        let variables = issues_by_ids::Variables { ids: ids.to_vec() };
+        let data = self.execute::<IssuesByIds>(variables).await?;
+        Ok(data.issues.nodes.into_iter().map(Into::into).collect())
+    }
 
-        let variables = json!({ "ids": ids });
-        let data = self.execute(query, variables).await?;
-        let nodes = data["issues"]["nodes"]
-            .as_array()
-            .ok_or_else(|| AppError::LinearApi("Missing issues.nodes".into()))?;
+    async fn get_issue_comments(
+        &self,
+        issue_id: &str,
+        since: Option<&str>,
+    ) -> Result<Vec<LinearComment>, AppError> {
+        let filter = since.map(|since| issue_comments::CommentFilter {
+            created_at: Some(issue_comments::DateComparator {
+                gt: Some(since.to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        let variables = issue_comments::Variables {
+            issue_id: issue_id.to_string(),
+            filter,
+        };
+
+        let data = self.execute::<IssueComments>(variables).await?;
+        Ok(data
+            .issue
+            .comments
+            .nodes
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
 
+    async fn get_comments_by_ids(
+        &self,
+        comment_ids: &[String],
+    ) -> Result<Vec<LinearComment>, AppError> {
         let mut results = Vec::new();
-        for node in nodes {
-            let id = node["id"].as_str().unwrap_or_default().to_string();
-            let identifier = node["identifier"].as_str().unwrap_or_default().to_string();
-            let status_name = node["state"]["name"]
-                .as_str()
-                .unwrap_or_default()
-                .to_string();
-            let status_type = node["state"]["type"]
-                .as_str()
-                .unwrap_or_default()
-                .to_string();
-            let updated_at = node["updatedAt"].as_str().unwrap_or_default().to_string();
-
-            results.push(LinearIssueStatus {
-                id,
-                identifier,
-                status_name,
-                status_type,
-                updated_at,
-            });
+        for ids in comment_ids.chunks(100) {
+            let variables = comments_by_ids::Variables {
+                ids: ids.to_vec(),
+                first: ids.len() as i64,
+            };
+            let data = self.execute::<CommentsByIds>(variables).await?;
+            results.extend(data.comments.nodes.into_iter().map(Into::into));
         }
 
         Ok(results)
     }
 
-    /// Fetch comments for a specific issue, sorted by creation time.
-    pub async fn get_issue_comments(&self, issue_id: &str) -> Result<Vec<LinearComment>, AppError> {
-        let query = r#"
-            query IssueComments($issueId: String!) {
-                issue(id: $issueId) {
-                    comments(first: 100, orderBy: createdAt) {
-                        nodes {
-                            id
-                            body
-                            createdAt
-                            user {
-                                displayName
-                            }
-                        }
-                    }
+    async fn create_comment(
+        &self,
+        comment_id: Option<&str>,
+        issue_id: &str,
+        body: &str,
+        author: Option<&CommentAuthor>,
+    ) -> Result<String, AppError> {
+        // As with issues, an ID of our own makes retrying safe.
+        let id = comment_id.map_or_else(|| uuid::Uuid::new_v4().to_string(), str::to_string);
+        let input = create_comment::CommentCreateInput {
+            id: Some(id.clone()),
+            issue_id: Some(issue_id.to_string()),
+            body: Some(body.to_string()),
+            create_as_user: author.map(|a| a.name.clone()),
+            display_icon_url: author.and_then(|a| a.avatar_url.clone()),
+            ..Default::default()
+        };
+
+        let variables = create_comment::Variables { input };
+        let lookup = async {
+            self.get_comments_by_ids(std::slice::from_ref(&id))
+                .await?
+                .into_iter()
+                .next()
+                .map(|comment| comment.id)
+                .ok_or_else(|| AppError::LinearApi(format!("Comment {id} not found")))
+        };
+        match self.create::<CreateComment, _>(variables, lookup).await? {
+            Ok(data) => Ok(data.comment_create.comment.id),
+            Err(earlier) => Ok(earlier),
+        }
+    }
+
+    async fn search_issues(
+        &self,
+        term: &str,
+        team_ids: &[String],
+        limit: usize,
+    ) -> Result<Vec<LinearSearchResult>, AppError> {
+        let variables = search_issues::Variables {
+            term: term.to_string(),
+            team_ids: team_ids.to_vec(),
+            first: limit as i64,
+        };
+
+        let data = self.execute::<SearchIssues>(variables).await?;
+        Ok(data
+            .search_issues
+            .nodes
+            .into_iter()
+            .map(|node| LinearSearchResult {
+                identifier: node.identifier,
+                title: node.title,
+                url: node.url,
+                status_name: node.state.name,
+            })
+            .collect())
+    }
+
+    async fn get_team_members(&self, team_id: &str) -> Result<Vec<LinearUser>, AppError> {
+        let variables = team_members::Variables {
+            team_id: team_id.to_string(),
+        };
+        let data = self.execute::<TeamMembers>(variables).await?;
+        Ok(data
+            .team
+            .members
+            .nodes
+            .into_iter()
+            .filter(|member| member.active)
+            .map(Into::into)
+            .collect())
+    }
+
+    async fn get_team_metadata(&self, team_id: &str) -> Result<TeamMetadata, AppError> {
+        let variables = team_metadata::Variables {
+            id: team_id.to_string(),
+            team_id: team_id.to_string(),
+        };
+        let data = self.execute::<schema::TeamMetadata>(variables).await?;
+        let team = data.team;
+        Ok(TeamMetadata {
+            key: team.key,
+            name: team.name,
+            states: team
+                .states
+                .nodes
+                .into_iter()
+                .map(|state| LinearWorkflowState {
+                    id: state.id,
+                    name: state.name,
+                    state_type: state.type_,
+                })
+                .collect(),
+            labels: data
+                .issue_labels
+                .nodes
+                .into_iter()
+                .map(|n| (n.id, n.name))
+                .collect(),
+            projects: team
+                .projects
+                .nodes
+                .into_iter()
+                .map(|n| (n.id, n.name))
+                .collect(),
+            members: team
+                .members
+                .nodes
+                .into_iter()
+                .filter(|member| member.active)
+                .map(Into::into)
+                .collect(),
+        })
+    }
+
+    async fn find_user_by_email(&self, email: &str) -> Result<Option<LinearUser>, AppError> {
+        let variables = user_by_email::Variables {
+            email: email.to_string(),
+        };
+        let data = self.execute::<UserByEmail>(variables).await?;
+        Ok(data
+            .users
+            .nodes
+            .into_iter()
+            .next()
+            .filter(|user| user.active)
+            .map(Into::into))
+    }
+
+    async fn user_commented(&self, user_id: &str, text: &str) -> Result<bool, AppError> {
+        let variables = user_comment::Variables {
+            user_id: user_id.to_string(),
+            text: text.to_string(),
+        };
+        let data = self.execute::<UserComment>(variables).await?;
+        Ok(!data.comments.nodes.is_empty())
+    }
+
+    async fn viewer(&self) -> Result<LinearUser, AppError> {
+        let data = self.execute::<Viewer>(viewer::Variables).await?;
+        Ok(data.viewer.into())
+    }
+
+    async fn update_issue_assignee(
+        &self,
+        issue_id: &str,
+        assignee_id: Option<&str>,
+    ) -> Result<(), AppError> {
+        match assignee_id {
+            Some(assignee_id) => {
+                let input = update_issue::IssueUpdateInput {
+                    assignee_id: Some(assignee_id.to_string()),
+                    ..Default::default()
+                };
+                self.update_issue(issue_id, input).await
+            }
+            // Unassigning needs an explicit null, which the generated input omits.
+            None => {
+                let variables = unassign_issue::Variables {
+                    id: issue_id.to_string(),
+                };
+                let data = self.execute::<UnassignIssue>(variables).await?;
+                if !data.issue_update.success {
+                    return Err(AppError::LinearApi("issueUpdate reported failure".into()));
                 }
+                Ok(())
             }
-        "#;
+        }
+    }
 
-        let variables = json!({
-            "issueId": issue_id,
-        });
+    async fn update_issue_priority(&self, issue_id: &str, priority: i64) -> Result<(), AppError> {
+        let input = update_issue::IssueUpdateInput {
+            priority: Some(priority),
+            ..Default::default()
+        };
+        self.update_issue(issue_id, input).await
+    }
 
-        let data = self.execute(query, variables).await?;
-        let nodes = data["issue"]["comments"]["nodes"]
-            .as_array()
-            .ok_or_else(|| AppError::LinearApi("Missing issue.comments.nodes".into()))?;
+    async fn update_issue_project(&self, issue_id: &str, project_id: &str) -> Result<(), AppError> {
+        let input = update_issue::IssueUpdateInput {
+            project_id: Some(project_id.to_string()),
+            ..Default::default()
+        };
+        self.update_issue(issue_id, input).await
+    }
 
-        let mut results = Vec::new();
-        for node in nodes {
-            let id = node["id"].as_str().unwrap_or_default().to_string();
-            let body = node["body"].as_str().unwrap_or_default().to_string();
-            let created_at = node["createdAt"].as_str().unwrap_or_default().to_string();
-            let author_name = node["user"]["displayName"]
-                .as_str()
-                .unwrap_or("Unknown")
-                .to_string();
-
-            results.push(LinearComment {
-                id,
-                body,
-                created_at,
-                author_name,
-            });
+    async fn get_canceled_state_id(&self, team_id: &str) -> Result<Option<String>, AppError> {
+        let variables = canceled_state::Variables {
+            team_id: team_id.to_string(),
+        };
+        let data = self.execute::<CanceledState>(variables).await?;
+        Ok(data
+            .workflow_states
+            .nodes
+            .into_iter()
+            .next()
+            .map(|state| state.id))
+    }
+
+    async fn update_issue_state(&self, issue_id: &str, state_id: &str) -> Result<(), AppError> {
+        let input = update_issue::IssueUpdateInput {
+            state_id: Some(state_id.to_string()),
+            ..Default::default()
+        };
+        self.update_issue(issue_id, input).await
+    }
+
+    async fn get_issue_description(&self, issue_id: &str) -> Result<String, AppError> {
+        let variables = issue_description::Variables {
+            id: issue_id.to_string(),
+        };
+        let data = self.execute::<IssueDescription>(variables).await?;
+        Ok(data.issue.description.unwrap_or_default())
+    }
+
+    async fn update_issue_description(
+        &self,
+        issue_id: &str,
+        description: &str,
+    ) -> Result<(), AppError> {
+        let input = update_issue::IssueUpdateInput {
+            description: Some(description.to_string()),
+            ..Default::default()
+        };
+        self.update_issue(issue_id, input).await
+    }
+
+    async fn update_issue_title(&self, issue_id: &str, title: &str) -> Result<(), AppError> {
+        let input = update_issue::IssueUpdateInput {
+            title: Some(title.to_string()),
+            ..Default::default()
+        };
+        self.update_issue(issue_id, input).await
+    }
+
+    async fn update_issue_estimate(&self, issue_id: &str, estimate: i64) -> Result<(), AppError> {
+        let input = update_issue::IssueUpdateInput {
+            estimate: Some(estimate),
+            ..Default::default()
+        };
+        self.update_issue(issue_id, input).await
+    }
+
+    async fn add_issue_label(&self, issue_id: &str, label_id: &str) -> Result<(), AppError> {
+        let variables = add_issue_label::Variables {
+            id: issue_id.to_string(),
+            label_id: label_id.to_string(),
+        };
+
+        let data = self.execute::<AddIssueLabel>(variables).await?;
+        if !data.issue_add_label.success {
+            return Err(AppError::LinearApi("issueAddLabel reported failure".into()));
         }
+        Ok(())
+    }
 
-        Ok(results)
+    async fn create_customer_need(
+        &self,
+        issue_id: &str,
+        customer: &Customer,
+        body: &str,
+        url: &str,
+    ) -> Result<(), AppError> {
+        let input = upsert_customer::CustomerUpsertInput {
+            external_id: Some(customer.external_id.clone()),
+            name: Some(customer.name.clone()),
+            ..Default::default()
+        };
+        let data = self
+            .execute::<UpsertCustomer>(upsert_customer::Variables { input })
+            .await?;
+        let customer_id = data.customer_upsert.customer.id;
+
+        let input = create_customer_need::CustomerNeedCreateInput {
+            issue_id: Some(issue_id.to_string()),
+            customer_id: Some(customer_id),
+            body: Some(body.to_string()),
+            attachment_url: Some(url.to_string()),
+            ..Default::default()
+        };
+        let data = self
+            .execute_once::<CreateCustomerNeed>(create_customer_need::Variables { input })
+            .await?;
+        if !data.customer_need_create.success {
+            return Err(AppError::LinearApi(
+                "customerNeedCreate reported failure".into(),
+            ));
+        }
+        Ok(())
     }
 
-    pub async fn request_file_upload(
+    async fn link_url(&self, issue_id: &str, url: &str, title: &str) -> Result<(), AppError> {
+        let variables = attachment_link_url::Variables {
+            issue_id: issue_id.to_string(),
+            url: url.to_string(),
+            title: Some(title.to_string()),
+        };
+
+        let data = self.execute::<AttachmentLinkUrl>(variables).await?;
+        if !data.attachment_link_url.success {
+            return Err(AppError::LinearApi(
+                "attachmentLinkURL reported failure".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    async fn request_file_upload(
         &self,
+        _team_or_issue_id: &str,
         filename: &str,
         content_type: &str,
         size: u64,
     ) -> Result<UploadFile, AppError> {
-        let query = r#"
-            mutation FileUpload($contentType: String!, $filename: String!, $size: Int!) {
-                fileUpload(contentType: $contentType, filename: $filename, size: $size) {
-                    uploadFile {
-                        uploadUrl
-                        assetUrl
-                        headers {
-                            key
-                            value
-                        }
-                    }
-                }
-            }
-        "#;
-
-        let variables = json!({
-            "contentType": content_type,
-            "filename": filename,
-            "size": size,
-        });
-
-        let data = self.execute(query, variables).await?;
-        let upload_data = &data["fileUpload"]["uploadFile"];
-
-        let headers: Vec<UploadHeader> = serde_json::from_value(upload_data["headers"].clone())
-            .map_err(|e| AppError::LinearApi(format!("Failed to parse upload headers: {e}")))?;
+        let variables = file_upload::Variables {
+            content_type: content_type.to_string(),
+            filename: filename.to_string(),
+            size: size as i64,
+        };
 
+        let data = self.execute::<FileUpload>(variables).await?;
+        let upload = data
+            .file_upload
+            .upload_file
+            .ok_or_else(|| AppError::LinearApi("fileUpload returned no upload target".into()))?;
         Ok(UploadFile {
-            upload_url: upload_data["uploadUrl"]
-                .as_str()
-                .ok_or_else(|| AppError::LinearApi("Missing uploadUrl".into()))?
-                .to_string(),
-            asset_url: upload_data["assetUrl"]
-                .as_str()
-                .ok_or_else(|| AppError::LinearApi("Missing assetUrl".into()))?
-                .to_string(),
-            headers,
+            upload_url: upload.upload_url,
+            asset_url: upload.asset_url,
+            headers: upload
+                .headers
+                .into_iter()
+                .map(|h| UploadHeader {
+                    key: h.key,
+                    value: h.value,
+                })
+                .collect(),
         })
     }
 
-    pub async fn upload_file_to_url(
+    async fn upload_file_to_url(
         &self,
         upload: &UploadFile,
-        data: Vec<u8>,
+        body: reqwest::Body,
+        size: u64,
         content_type: &str,
     ) -> Result<String, AppError> {
+        // A streamed body would otherwise go out chunked, which the signed upload URL rejects.
         let mut request = self
             .client
             .put(&upload.upload_url)
-            .header("Content-Type", content_type);
+            .header("Content-Type", content_type)
+            .header("Content-Length", size);
 
         for header in &upload.headers {
             request = request.header(&header.key, &header.value);
         }
 
         let response = request
-            .body(data)
+            .body(body)
             .send()
             .await
             .map_err(|e| AppError::AttachmentUpload(e.to_string()))?;
@@ -362,14 +851,49 @@ impl LinearClient {
         Ok(upload.asset_url.clone())
     }
 
-    pub async fn download_attachment(&self, url: &str) -> Result<(Vec<u8>, String), AppError> {
+    async fn download_attachment(&self, url: &str) -> Result<AttachmentDownload, AppError> {
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| AppError::AttachmentUpload(format!("Download failed: {e}")))?;
+
+        let content_type = response
+            .headers()
+            .get("content-type")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("application/octet-stream")
+            .to_string();
+
+        Ok(AttachmentDownload {
+            content_type,
+            size: response.content_length(),
+            body: reqwest::Body::wrap_stream(response.bytes_stream()),
+        })
+    }
+
+    async fn download_linear_upload(
+        &self,
+        _issue_id: &str,
+        url: &str,
+    ) -> Result<(Vec<u8>, String), AppError> {
         let response = self
             .client
             .get(url)
+            .header("Authorization", &self.api_key)
             .send()
             .await
             .map_err(|e| AppError::AttachmentUpload(format!("Download failed: {e}")))?;
 
+        if !response.status().is_success() {
+            return Err(AppError::AttachmentUpload(format!(
+                "Download failed with status {}",
+                response.status()
+            )));
+        }
+
         let content_type = response
             .headers()
             .get("content-type")
@@ -384,14 +908,147 @@ impl LinearClient {
 
         Ok((bytes.to_vec(), content_type))
     }
+}
+
+impl LinearClient {
+    /// Name of the team with ID `team_id`. Used to check configuration; an error if the team
+    /// doesn't exist or the API key can't see it.
+    pub async fn team_name(&self, team_id: &str) -> Result<String, AppError> {
+        let variables = team_name::Variables {
+            id: team_id.to_string(),
+        };
+        Ok(self.execute::<TeamName>(variables).await?.team.name)
+    }
+
+    pub async fn project_name(&self, project_id: &str) -> Result<String, AppError> {
+        let variables = project_name::Variables {
+            id: project_id.to_string(),
+        };
+        Ok(self.execute::<ProjectName>(variables).await?.project.name)
+    }
+
+    pub async fn label_name(&self, label_id: &str) -> Result<String, AppError> {
+        let variables = label_name::Variables {
+            id: label_id.to_string(),
+        };
+        Ok(self.execute::<LabelName>(variables).await?.issue_label.name)
+    }
+
+    /// Every team the API key can see.
+    pub async fn teams(&self) -> Result<Vec<LinearTeam>, AppError> {
+        let data = self.execute::<Teams>(teams::Variables).await?;
+        Ok(data
+            .teams
+            .nodes
+            .into_iter()
+            .map(|team| LinearTeam {
+                id: team.id,
+                key: team.key,
+                name: team.name,
+            })
+            .collect())
+    }
+
+    /// The projects and labels available to issues in a team.
+    pub async fn team_choices(&self, team_id: &str) -> Result<TeamChoices, AppError> {
+        let variables = team_choices::Variables {
+            id: team_id.to_string(),
+            team_id: team_id.to_string(),
+        };
+        let data = self.execute::<schema::TeamChoices>(variables).await?;
+        Ok(TeamChoices {
+            projects: data
+                .team
+                .projects
+                .nodes
+                .into_iter()
+                .map(|n| (n.id, n.name))
+                .collect(),
+            labels: data
+                .issue_labels
+                .nodes
+                .into_iter()
+                .map(|n| (n.id, n.name))
+                .collect(),
+        })
+    }
+
+    /// Apply an `IssueUpdateInput` to an issue.
+    async fn update_issue(
+        &self,
+        issue_id: &str,
+        input: update_issue::IssueUpdateInput,
+    ) -> Result<(), AppError> {
+        let variables = update_issue::Variables {
+            id: issue_id.to_string(),
+            input,
+        };
+
+        let data = self.execute::<UpdateIssue>(variables).await?;
+        if !data.issue_update.success {
+            return Err(AppError::LinearApi("issueUpdate reported failure".into()));
+        }
+        Ok(())
+    }
+
+    /// Run a generated operation (see [`super::schema`]) and deserialize its `data`. For
+    /// queries, and for mutations that are idempotent or carry a client-generated `id`.
+    async fn execute<Q: GraphQLQuery>(
+        &self,
+        variables: Q::Variables,
+    ) -> Result<Q::ResponseData, AppError> {
+        self.send::<Q>(variables, Retry::Always)
+            .await
+            .map_err(|failed| failed.error)
+    }
 
-    async fn execute(&self, query: &str, variables: Value) -> Result<Value, AppError> {
-        #[derive(Serialize)]
-        struct GraphQLRequest<'a> {
-            query: &'a str,
-            variables: &'a Value,
+    /// Run a mutation that would apply twice if sent twice. After a timeout or a 5xx it may
+    /// have landed, so it's only retried when it can't have reached Linear.
+    async fn execute_once<Q: GraphQLQuery>(
+        &self,
+        variables: Q::Variables,
+    ) -> Result<Q::ResponseData, AppError> {
+        self.send::<Q>(variables, Retry::UnlessSent)
+            .await
+            .map_err(|failed| failed.error)
+    }
+
+    /// Run a create that carries our own `id`, and if the outcome of an attempt is unknown,
+    /// `lookup` what it may have created: a retry of a create that landed is rejected as a
+    /// duplicate, and every attempt can time out after the first one went through.
+    async fn create<Q: GraphQLQuery, T>(
+        &self,
+        variables: Q::Variables,
+        lookup: impl std::future::Future<Output = Result<T, AppError>>,
+    ) -> Result<Result<Q::ResponseData, T>, AppError> {
+        match self.send::<Q>(variables, Retry::Always).await {
+            Ok(data) => Ok(Ok(data)),
+            Err(failed) if failed.maybe_applied => lookup.await.map(Err).map_err(|_| failed.error),
+            Err(failed) => Err(failed.error),
         }
+    }
 
+    async fn send<Q: GraphQLQuery>(
+        &self,
+        variables: Q::Variables,
+        retry: Retry,
+    ) -> Result<Q::ResponseData, Failed> {
+        let mut maybe_applied = false;
+        self.send_attempts::<Q>(variables, retry, &mut maybe_applied)
+            .await
+            .map_err(|error| Failed {
+                error,
+                maybe_applied,
+            })
+    }
+
+    #[instrument(name = "linear_graphql", skip_all, fields(operation = tracing::field::Empty))]
+    async fn send_attempts<Q: GraphQLQuery>(
+        &self,
+        variables: Q::Variables,
+        retry: Retry,
+        maybe_applied: &mut bool,
+    ) -> Result<Q::ResponseData, AppError> {
         #[derive(Deserialize)]
         struct GraphQLResponse {
             data: Option<Value>,
@@ -403,32 +1060,39 @@ impl LinearClient {
             message: String,
         }
 
-        // Transient failures (host DNS blips, Linear edge 5xx, rate limits) are
-        // retried with exponential backoff. Non-retryable errors (4xx other than
-        // 429, GraphQL-level errors) fail immediately.
-        const MAX_ATTEMPTS: u32 = 3;
-        let body = GraphQLRequest {
-            query,
-            variables: &variables,
-        };
+        // Transient failures (host DNS blips, timeouts, Linear edge 5xx, rate limits)
+        // are retried with jittered exponential backoff, except that a timeout or 5xx
+        // doesn't say whether a mutation went through, so `Retry::UnlessSent` gives up on
+        // those. Non-retryable errors (4xx other than 429, GraphQL-level errors) fail
+        // immediately.
+        let body = Q::build_query(variables);
+        tracing::Span::current().record("operation", body.operation_name);
+
+        if !self.breaker.allow() {
+            return Err(AppError::Unavailable("Linear"));
+        }
 
         let mut attempt = 0;
         let text = loop {
             attempt += 1;
+            self.rate_limit.wait().await;
 
             let send_result = self
                 .client
-                .post("https://api.linear.app/graphql")
+                .post(&self.endpoint)
                 .header("Authorization", &self.api_key)
                 .header("Content-Type", "application/json")
                 .json(&body)
+                .timeout(REQUEST_TIMEOUT)
                 .send()
                 .await;
 
             let response = match send_result {
                 Ok(response) => response,
                 Err(e) => {
-                    if attempt < MAX_ATTEMPTS {
+                    let unsent = e.is_connect();
+                    *maybe_applied |= !unsent;
+                    if attempt < self.max_attempts && (retry == Retry::Always || unsent) {
                         let delay = backoff(attempt);
                         warn!(
                             attempt,
@@ -439,17 +1103,24 @@ impl LinearClient {
                         tokio::time::sleep(delay).await;
                         continue;
                     }
+                    self.breaker.record_failure();
                     return Err(e.into());
                 }
             };
 
             let status = response.status();
+            self.rate_limit.record(response.headers());
 
-            // 5xx and 429 are worth retrying; everything else is decided now.
-            if (status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS)
-                && attempt < MAX_ATTEMPTS
-            {
-                let delay = backoff(attempt);
+            // 5xx and 429 are worth retrying; everything else is decided now. A 429 waits for
+            // the rate limit window to reset when Linear says when that is.
+            let rate_limited = status == reqwest::StatusCode::TOO_MANY_REQUESTS;
+            *maybe_applied |= status.is_server_error();
+            let retryable = rate_limited || (status.is_server_error() && retry == Retry::Always);
+            if retryable && attempt < self.max_attempts {
+                let delay = rate_limited
+                    .then(|| self.rate_limit.until_reset())
+                    .flatten()
+                    .unwrap_or_else(|| backoff(attempt));
                 warn!(
                     attempt,
                     %status,
@@ -460,6 +1131,13 @@ impl LinearClient {
                 continue;
             }
 
+            // Anything but a 5xx or 429 means Linear is up, even if it rejected this request.
+            if status.is_server_error() || rate_limited {
+                self.breaker.record_failure();
+            } else {
+                self.breaker.record_success();
+            }
+
             let text = response.text().await?;
 
             if !status.is_success() {
@@ -484,13 +1162,48 @@ impl LinearClient {
             return Err(AppError::LinearApi(combined));
         }
 
-        response
+        let data = response
             .data
-            .ok_or_else(|| AppError::LinearApi("No data in response".into()))
+            .ok_or_else(|| AppError::LinearApi("No data in response".into()))?;
+        serde_json::from_value(data)
+            .map_err(|e| AppError::LinearApi(format!("Unexpected response shape: {e}")))
+    }
+}
+
+/// Whether a request may be sent again after a failure that leaves its outcome unknown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Retry {
+    /// Queries, and mutations that are idempotent or carry a client-generated `id`.
+    Always,
+    /// Mutations that would apply twice: retried only when Linear certainly didn't get them
+    /// (the connection failed, or the request was rate limited).
+    UnlessSent,
+}
+
+/// A request that failed. `maybe_applied` is set when an attempt timed out or got a 5xx after
+/// reaching Linear, so a mutation may have gone through despite the error.
+struct Failed {
+    error: AppError,
+    maybe_applied: bool,
+}
+
+/// Display name for Linear's numeric priority scale.
+pub fn priority_label(priority: i64) -> &'static str {
+    match priority {
+        1 => "Urgent",
+        2 => "High",
+        3 => "Medium",
+        4 => "Low",
+        _ => "No priority",
     }
 }
 
-/// Exponential backoff for retry attempt N (1-indexed): 1s, 2s, 4s, ...
+/// Delay before retrying after attempt N (1-indexed): exponential (1s, 2s, 4s, ... capped at
+/// [`MAX_BACKOFF`]) with the upper half randomized, so instances that failed together don't
+/// retry in lockstep.
 fn backoff(attempt: u32) -> std::time::Duration {
-    std::time::Duration::from_secs(2u64.pow(attempt.saturating_sub(1)))
+    let exponential = 1000u64.saturating_mul(2u64.saturating_pow(attempt.saturating_sub(1)));
+    let capped = exponential.min(MAX_BACKOFF.as_millis() as u64);
+    let half = capped / 2;
+    std::time::Duration::from_millis(half + rand::random_range(0..=half))
 }
@@ -1,2 +1,9 @@
+pub mod api;
+pub mod cache;
 pub mod client;
+pub mod poll_interval;
 pub mod poller;
+pub mod rate_limit;
+pub mod schema;
+pub mod webhook;
+pub mod workspaces;
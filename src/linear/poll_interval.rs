@@ -0,0 +1,50 @@
+//! Adaptive interval for the per-team status poll.
+//!
+//! A team with recent updates is polled at its configured interval. Once several polls in a row
+//! come back empty the interval doubles, up to `POLL_INTERVAL_MAX_SECS`, and the first update
+//! seen snaps it back. Quiet workspaces spend far less of the API budget on polling, at the cost
+//! of noticing the first change after a lull a little later.
+
+use std::time::Duration;
+
+/// Empty polls in a row before the interval is lengthened (again).
+const QUIET_POLLS_BEFORE_BACKOFF: u32 = 3;
+
+#[derive(Debug, Clone)]
+pub struct PollInterval {
+    min: Duration,
+    max: Duration,
+    current: Duration,
+    quiet_polls: u32,
+}
+
+impl PollInterval {
+    /// Starts at `min`. A `max` below `min` disables the back-off.
+    pub fn new(min: Duration, max: Duration) -> Self {
+        Self {
+            min,
+            max: max.max(min),
+            current: min,
+            quiet_polls: 0,
+        }
+    }
+
+    /// How long to wait before the next poll.
+    pub fn current(&self) -> Duration {
+        self.current
+    }
+
+    /// Record whether a poll found updated issues.
+    pub fn record(&mut self, active: bool) {
+        if active {
+            self.current = self.min;
+            self.quiet_polls = 0;
+            return;
+        }
+        self.quiet_polls += 1;
+        if self.quiet_polls >= QUIET_POLLS_BEFORE_BACKOFF && self.current < self.max {
+            self.quiet_polls = 0;
+            self.current = (self.current * 2).min(self.max);
+        }
+    }
+}
@@ -1,135 +1,177 @@
-use std::sync::Arc;
+use std::collections::HashMap;
 use std::time::Instant;
 
-use serenity::http::Http;
+use serenity::all::ChannelId;
 use sqlx::SqlitePool;
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, instrument, warn};
 
-use crate::config::Config;
+use crate::breaker::CircuitBreaker;
+use crate::config::{Config, SharedConfig};
 use crate::db;
-use crate::linear::client::LinearClient;
-use crate::sync::linear_to_discord::{sync_linear_comments_to_discord, sync_linear_to_discord};
+use crate::discord::port::{is_transient, DiscordPort};
+use crate::linear::api::LinearApi;
+use crate::linear::client::LinearIssueStatus;
+use crate::linear::poll_interval::PollInterval;
+use crate::shutdown::Shutdown;
+use crate::sync::discuss::open_thread_for_issue;
+use crate::sync::linear_to_discord::{
+    archive_due_threads, sync_assignee_to_discord, sync_labels_to_tags,
+    sync_linear_comment_changes, sync_linear_comments_to_discord, sync_linear_to_discord,
+    sync_title_renames,
+};
+use crate::sync::outbox::{self, process_outbox, Operation};
 use crate::sync::reconcile::reconcile_discord_to_linear;
+use crate::sync::status_embed::update_status_embed;
 
-pub async fn run_poller(http: Arc<Http>, pool: SqlitePool, linear: LinearClient, config: Config) {
-    let team_ids = config.unique_team_ids();
+/// Spawn a status poller per Linear team, then run the shared sync passes (queued retries,
+/// renames, archiving, comments, thread reconcile) on `POLL_INTERVAL_SECS`. Teams added to
+/// `CHANNELS` by a reload get a poller on the next pass. Returns once shutdown is requested, after
+/// finishing the pass under way.
+pub async fn run_poller(
+    discord: impl DiscordPort + Clone + 'static,
+    pool: SqlitePool,
+    linear: impl LinearApi + Clone + 'static,
+    shared_config: SharedConfig,
+    shutdown: Shutdown,
+) {
+    let config = shared_config.current();
     let interval_secs = config.poll_interval_secs;
     let comment_interval_secs = config.comment_poll_interval_secs;
     let thread_reconcile_interval_secs = config.thread_reconcile_interval_secs;
 
-    let mut last_poll = chrono::Utc::now().to_rfc3339();
     let mut last_comment_sync = Instant::now();
     let mut last_thread_reconcile = Instant::now();
     let comment_interval = std::time::Duration::from_secs(comment_interval_secs);
     let thread_reconcile_interval = std::time::Duration::from_secs(thread_reconcile_interval_secs);
+    // Discord outcomes are counted per pass: a pass with any transient Discord error is one
+    // failure. Linear's breaker lives in its client and counts individual requests. Clones
+    // share state, so the team pollers and the shared passes all feed and honour the same one.
+    let discord_breaker = CircuitBreaker::new(
+        "Discord",
+        config.breaker_threshold,
+        std::time::Duration::from_secs(config.breaker_cooldown_secs),
+    );
 
     info!(
         interval_secs,
         comment_interval_secs,
         thread_reconcile_interval_secs,
-        teams = team_ids.len(),
+        teams = config.unique_team_ids().len(),
         "Starting Linear status poller"
     );
 
-    loop {
-        tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
+    let mut team_pollers: HashMap<String, TeamPoller> = HashMap::new();
 
-        let now = chrono::Utc::now().to_rfc3339();
-        let mut any_success = false;
-
-        for team_id in &team_ids {
-            match linear.get_updated_issues(team_id, &last_poll).await {
-                Ok(issues) => {
-                    any_success = true;
-
-                    if !issues.is_empty() {
-                        info!(
-                            count = issues.len(),
-                            team_id, "Polled updated issues from Linear"
-                        );
-                    }
+    // The first pass runs immediately and includes the comment sync and thread reconcile, so
+    // whatever changed while the bot was offline reaches Discord without waiting out the
+    // intervals.
+    let mut first_pass = true;
 
-                    for issue in &issues {
-                        // Only process issues we're tracking
-                        match db::get_mapping_by_linear_issue(&pool, &issue.id).await {
-                            Ok(Some(_)) => {}
-                            Ok(None) => continue,
-                            Err(e) => {
-                                warn!(issue_id = %issue.id, error = %e, "DB lookup failed");
-                                continue;
-                            }
-                        };
-
-                        // Check if status actually changed from what we last posted
-                        let status_changed = match db::get_cached_status(&pool, &issue.id).await {
-                            Ok(Some(cached)) if cached == issue.status_name => false,
-                            Ok(_) => true,
-                            Err(e) => {
-                                warn!(
-                                    issue_id = %issue.id,
-                                    error = %e,
-                                    "Failed to check status cache"
-                                );
-                                false
-                            }
-                        };
-
-                        if status_changed {
-                            info!(
-                                identifier = %issue.identifier,
-                                status = %issue.status_name,
-                                "Status change detected"
-                            );
+    loop {
+        let catch_up = std::mem::take(&mut first_pass);
+        if !catch_up {
+            tokio::select! {
+                _ = tokio::time::sleep(std::time::Duration::from_secs(interval_secs)) => {}
+                _ = shutdown.requested() => {}
+            }
+        }
+        let Some(_task) = shutdown.start_task() else {
+            // Dropping the team pollers would abort them; let them save their cursors first.
+            for poller in team_pollers.values_mut() {
+                let _ = (&mut poller.0).await;
+            }
+            info!("Linear poller stopped");
+            return;
+        };
 
-                            if let Err(e) = sync_linear_to_discord(
-                                &http,
-                                &pool,
-                                &issue.id,
-                                &issue.identifier,
-                                &issue.status_name,
-                                &issue.status_type,
-                            )
-                            .await
-                            {
-                                error!(
-                                    identifier = %issue.identifier,
-                                    error = %e,
-                                    "Failed to sync status to Discord"
-                                );
-                            }
-                        }
-                    }
+        let config = shared_config.current();
+        // Pollers of teams removed from the config stop themselves.
+        for team_id in config.unique_team_ids() {
+            if let Some(poller) = team_pollers.get_mut(&team_id) {
+                if !poller.0.is_finished() {
+                    continue;
                 }
-                Err(e) => {
-                    error!(team_id, error = %e, "Failed to poll Linear for updates");
+                if let Err(e) = (&mut poller.0).await {
+                    error!(team_id, error = %e, "Team poller died, restarting it");
                 }
             }
+            let poller = tokio::spawn(poll_team(
+                discord.clone(),
+                pool.clone(),
+                linear.clone(),
+                shared_config.clone(),
+                discord_breaker.clone(),
+                shutdown.clone(),
+                team_id.clone(),
+            ));
+            team_pollers.insert(team_id, TeamPoller(poller));
+        }
+
+        // The breakers logged the outage when they opened.
+        if !linear.is_available() || !discord_breaker.is_available() {
+            continue;
+        }
+
+        let mut discord_failed = false;
+
+        if let Err(e) = process_outbox(&discord, &pool, &config, &linear).await {
+            discord_failed |= is_transient(&e);
+            error!(error = %e, "Failed to retry queued operations");
+        }
+
+        if let Err(e) = sync_title_renames(&discord, &pool).await {
+            discord_failed |= is_transient(&e);
+            error!(error = %e, "Failed to sync thread titles");
+        }
+
+        if let Err(e) = archive_due_threads(&discord, &pool).await {
+            discord_failed |= is_transient(&e);
+            error!(error = %e, "Failed to archive closed threads");
         }
 
         // Sync comments on a separate, longer interval to avoid rate limits.
         // Adding a comment in Linear may not bump the issue's updatedAt field,
         // so we check all tracked issues, but less frequently.
-        if last_comment_sync.elapsed() >= comment_interval {
+        if catch_up || last_comment_sync.elapsed() >= comment_interval {
             last_comment_sync = Instant::now();
 
             match db::get_all_tracked_issues(&pool).await {
                 Ok(mappings) => {
                     for mapping in &mappings {
                         if let Err(e) = sync_linear_comments_to_discord(
-                            &http,
+                            &discord,
                             &pool,
+                            &config,
                             &linear,
                             &mapping.linear_issue_id,
                             &mapping.linear_identifier,
                         )
                         .await
                         {
+                            discord_failed |= is_transient(&e);
                             error!(
                                 identifier = %mapping.linear_identifier,
                                 error = %e,
                                 "Failed to sync comments to Discord"
                             );
                         }
+                        if let Err(e) = sync_linear_comment_changes(
+                            &discord,
+                            &pool,
+                            &config,
+                            &linear,
+                            &mapping.linear_issue_id,
+                            &mapping.linear_identifier,
+                        )
+                        .await
+                        {
+                            discord_failed |= is_transient(&e);
+                            error!(
+                                identifier = %mapping.linear_identifier,
+                                error = %e,
+                                "Failed to sync comment edits to Discord"
+                            );
+                        }
                     }
                 }
                 Err(e) => {
@@ -141,17 +183,308 @@ pub async fn run_poller(http: Arc<Http>, pool: SqlitePool, linear: LinearClient,
         // Safety net: periodically create Linear issues for monitored forum threads that have
         // no mapping yet. Catches posts whose `thread_create` create failed (transient error,
         // rate limit, the Free-plan issue cap) or whose gateway event was missed entirely.
-        if last_thread_reconcile.elapsed() >= thread_reconcile_interval {
+        if catch_up || last_thread_reconcile.elapsed() >= thread_reconcile_interval {
             last_thread_reconcile = Instant::now();
 
-            if let Err(e) = reconcile_discord_to_linear(&http, &pool, &config, &linear).await {
+            if let Err(e) = reconcile_discord_to_linear(&discord, &pool, &config, &linear).await {
+                discord_failed |= is_transient(&e);
                 error!(error = %e, "Discord→Linear thread reconcile failed");
             }
         }
 
-        // Only advance the cursor if at least one team succeeded
-        if any_success {
-            last_poll = now;
+        if discord_failed {
+            discord_breaker.record_failure();
+        } else {
+            discord_breaker.record_success();
+        }
+    }
+}
+
+/// A team poller's task, aborted when dropped, so that when the supervisor restarts a poller that
+/// died, the old team pollers don't keep running alongside the new ones.
+struct TeamPoller(tokio::task::JoinHandle<()>);
+
+impl Drop for TeamPoller {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Poll one team for updated issues on its own interval and cursor, so a team that keeps failing
+/// (a wrong ID, revoked access) only stalls itself. The first pass runs immediately to catch up
+/// from the saved cursor. Returns once a reload removes the team's last channel, or on shutdown
+/// once the pass under way has saved its cursor.
+async fn poll_team(
+    discord: impl DiscordPort,
+    pool: SqlitePool,
+    linear: impl LinearApi,
+    shared_config: SharedConfig,
+    discord_breaker: CircuitBreaker,
+    shutdown: Shutdown,
+    team_id: String,
+) {
+    let config = shared_config.current();
+    let mut interval_secs = config.team_poll_interval_secs(&team_id);
+    let mut interval = team_poll_interval(&config, interval_secs);
+    let mut cursor = load_poll_cursor(&pool, &team_id).await;
+    let mut consecutive_failures: u32 = 0;
+    let mut first_pass = true;
+
+    info!(team_id, interval_secs, since = %cursor, "Starting team poller");
+
+    loop {
+        if !std::mem::take(&mut first_pass) {
+            tokio::select! {
+                _ = tokio::time::sleep(interval.current()) => {}
+                _ = shutdown.requested() => {}
+            }
+        }
+        let Some(_task) = shutdown.start_task() else {
+            return;
+        };
+
+        let config = shared_config.current();
+        if !config.unique_team_ids().contains(&team_id) {
+            info!(team_id, "Team is no longer configured, stopping its poller");
+            return;
+        }
+        if config.team_poll_interval_secs(&team_id) != interval_secs {
+            interval_secs = config.team_poll_interval_secs(&team_id);
+            interval = team_poll_interval(&config, interval_secs);
+            info!(team_id, interval_secs, "Team poll interval changed");
+        }
+
+        // The breaker logged the outage when it opened. Skipping the pass leaves the cursor
+        // where it is, so nothing updated in the meantime is missed.
+        if !linear.is_available() || !discord_breaker.is_available() {
+            continue;
+        }
+
+        let now = chrono::Utc::now().to_rfc3339();
+        let since = reach_back(&cursor, config.poll_cursor_overlap_secs);
+        let issues = match linear.get_updated_issues(&team_id, &since).await {
+            Ok(issues) => issues,
+            Err(e) => {
+                consecutive_failures += 1;
+                error!(
+                    team_id,
+                    consecutive_failures,
+                    error = %e,
+                    "Failed to poll Linear for updates"
+                );
+                continue;
+            }
+        };
+        if consecutive_failures > 0 {
+            info!(team_id, consecutive_failures, "Polling team recovered");
+            consecutive_failures = 0;
+        }
+
+        if !issues.is_empty() {
+            info!(
+                count = issues.len(),
+                team_id, "Polled updated issues from Linear"
+            );
+        }
+        let previous = interval.current();
+        interval.record(!issues.is_empty());
+        if interval.current() > previous {
+            debug!(
+                team_id,
+                interval_secs = interval.current().as_secs(),
+                "No recent updates, polling less often"
+            );
+        }
+
+        let mut discord_failed = false;
+        for issue in &issues {
+            discord_failed |=
+                sync_updated_issue(&discord, &pool, &config, &linear, &team_id, issue).await;
+        }
+        if discord_failed {
+            discord_breaker.record_failure();
+        } else {
+            discord_breaker.record_success();
+        }
+
+        // Saved so that after a restart the next poll picks up from here.
+        if let Err(e) = db::upsert_poll_cursor(&pool, &team_id, &now).await {
+            warn!(team_id, error = %e, "Failed to save poll cursor");
+        }
+        cursor = now;
+    }
+}
+
+fn team_poll_interval(config: &Config, interval_secs: u64) -> PollInterval {
+    PollInterval::new(
+        std::time::Duration::from_secs(interval_secs),
+        std::time::Duration::from_secs(config.poll_interval_max_secs),
+    )
+}
+
+/// Sync one updated issue's assignee, status, and status embed to its thread, if it's tracked,
+/// or open a post for it if it was labeled for a discussion in Discord.
+/// Returns whether a Discord call failed transiently.
+#[instrument(skip_all, fields(identifier = %issue.identifier, status = %issue.status_name))]
+async fn sync_updated_issue(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    config: &Config,
+    linear: &impl LinearApi,
+    team_id: &str,
+    issue: &LinearIssueStatus,
+) -> bool {
+    let mut discord_failed = false;
+
+    // Only process issues we're tracking, or that were labeled for a discussion in Discord
+    let mapping = match db::get_mapping_by_linear_issue(pool, &issue.id).await {
+        Ok(Some(m)) => m,
+        Ok(None) => {
+            let opened = open_thread_for_issue(
+                discord,
+                pool,
+                config,
+                linear,
+                team_id,
+                &issue.id,
+                &issue.label_ids,
+            )
+            .await;
+            if let Err(e) = &opened {
+                error!(
+                    identifier = %issue.identifier,
+                    error = %e,
+                    "Failed to open forum post for labeled issue"
+                );
+            }
+            return opened.is_err_and(|e| is_transient(&e));
+        }
+        Err(e) => {
+            warn!(issue_id = %issue.id, error = %e, "DB lookup failed");
+            return false;
+        }
+    };
+
+    if let Err(e) = db::record_issue_title(pool, &issue.id, &issue.title).await {
+        warn!(issue_id = %issue.id, error = %e, "Failed to record issue title");
+    }
+
+    if let Err(e) = sync_assignee_to_discord(
+        discord,
+        pool,
+        config,
+        &issue.id,
+        &issue.identifier,
+        issue.assignee.as_ref(),
+    )
+    .await
+    {
+        discord_failed |= is_transient(&e);
+        error!(
+            identifier = %issue.identifier,
+            error = %e,
+            "Failed to sync assignee to Discord"
+        );
+    }
+
+    if let Err(e) = sync_labels_to_tags(
+        discord,
+        config,
+        &mapping.discord_thread_id,
+        &issue.identifier,
+        &issue.label_ids,
+    )
+    .await
+    {
+        discord_failed |= is_transient(&e);
+        error!(
+            identifier = %issue.identifier,
+            error = %e,
+            "Failed to sync labels to forum tags"
+        );
+    }
+
+    // Check if status actually changed from what we last posted
+    let status_changed = match db::get_cached_status(pool, &issue.id).await {
+        Ok(Some(cached)) if cached == issue.status_name => false,
+        Ok(_) => true,
+        Err(e) => {
+            warn!(
+                issue_id = %issue.id,
+                error = %e,
+                "Failed to check status cache"
+            );
+            false
+        }
+    };
+
+    if status_changed {
+        info!(
+            identifier = %issue.identifier,
+            status = %issue.status_name,
+            "Status change detected"
+        );
+
+        if let Err(e) = sync_linear_to_discord(discord, pool, config, linear, &issue.detail()).await
+        {
+            discord_failed |= is_transient(&e);
+            error!(
+                identifier = %issue.identifier,
+                error = %e,
+                "Failed to sync status to Discord"
+            );
+            let operation = Operation::SyncStatus {
+                issue_id: issue.id.clone(),
+            };
+            outbox::enqueue(pool, &operation, &e).await;
+        }
+    } else if let Ok(thread_id) = mapping.discord_thread_id.parse() {
+        // Other changes (assignee, priority, ...) only touch the status
+        // embed, in channels that have one.
+        if let Err(e) =
+            update_status_embed(discord, pool, linear, ChannelId::new(thread_id), &issue.id).await
+        {
+            discord_failed |= is_transient(&e);
+            error!(
+                identifier = %issue.identifier,
+                error = %e,
+                "Failed to update status embed"
+            );
+        }
+    }
+
+    discord_failed
+}
+
+/// `cursor` moved back by `overlap_secs`. The cursor is taken before the poll's query runs, and
+/// an update committed in Linear around that moment can carry an `updatedAt` just before it yet
+/// not be visible to the query; overlapping polls pick it up next time. Status, assignee, and
+/// comment syncs all skip what they've already posted, so re-reading an issue is harmless.
+fn reach_back(cursor: &str, overlap_secs: u64) -> String {
+    match chrono::DateTime::parse_from_rfc3339(cursor) {
+        Ok(at) => (at - chrono::Duration::seconds(overlap_secs as i64)).to_rfc3339(),
+        Err(_) => cursor.to_string(),
+    }
+}
+
+/// A team's stored poll cursor. A team polled for the first time starts from now; its cursor is
+/// saved straight away so a restart before the first pass doesn't lose the gap.
+async fn load_poll_cursor(pool: &SqlitePool, team_id: &str) -> String {
+    let now = chrono::Utc::now().to_rfc3339();
+    match db::get_poll_cursor(pool, team_id).await {
+        Ok(Some(cursor)) => {
+            info!(team_id, since = %cursor, "Resuming Linear poll from saved cursor");
+            cursor
+        }
+        Ok(None) => {
+            if let Err(e) = db::upsert_poll_cursor(pool, team_id, &now).await {
+                warn!(team_id, error = %e, "Failed to save poll cursor");
+            }
+            now
+        }
+        Err(e) => {
+            warn!(team_id, error = %e, "Failed to load poll cursor, starting from now");
+            now
         }
     }
 }
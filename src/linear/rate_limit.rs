@@ -0,0 +1,76 @@
+//! Tracking Linear's request budget from its rate limit headers.
+//!
+//! Every API response carries `X-RateLimit-Requests-Remaining` and `X-RateLimit-Requests-Reset`
+//! (epoch milliseconds). [`RateLimiter`] remembers the latest values and makes requests wait for
+//! the reset once the budget is nearly spent, so a large backfill or a busy poll slows down
+//! instead of running into 429s. The state is shared between clones of the client.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use reqwest::header::HeaderMap;
+use tracing::{debug, warn};
+
+const REMAINING_HEADER: &str = "x-ratelimit-requests-remaining";
+const RESET_HEADER: &str = "x-ratelimit-requests-reset";
+
+/// Requests held back for when the budget is low; once only this many are left, callers wait for
+/// the window to reset.
+const RESERVE: u64 = 10;
+
+/// Longest a request waits on the rate limit. Linear's window is an hour; anything beyond that
+/// means a bad header or clock.
+const MAX_WAIT: Duration = Duration::from_secs(3600);
+
+#[derive(Debug, Clone, Copy)]
+struct Budget {
+    remaining: u64,
+    reset_at_ms: i64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RateLimiter {
+    budget: Arc<Mutex<Option<Budget>>>,
+}
+
+impl RateLimiter {
+    /// Remember the budget reported by a response. Responses without the headers are ignored.
+    pub fn record(&self, headers: &HeaderMap) {
+        let header =
+            |name: &str| -> Option<i64> { headers.get(name)?.to_str().ok()?.trim().parse().ok() };
+        let (Some(remaining), Some(reset_at_ms)) = (header(REMAINING_HEADER), header(RESET_HEADER))
+        else {
+            return;
+        };
+        let remaining = remaining.max(0) as u64;
+        debug!(remaining, reset_at_ms, "Linear rate limit budget");
+        *self.budget.lock().unwrap() = Some(Budget {
+            remaining,
+            reset_at_ms,
+        });
+    }
+
+    /// Time until the current window resets, if it's known and still ahead.
+    pub fn until_reset(&self) -> Option<Duration> {
+        let budget = (*self.budget.lock().unwrap())?;
+        let ms = budget.reset_at_ms - chrono::Utc::now().timestamp_millis();
+        (ms > 0).then(|| Duration::from_millis(ms as u64).min(MAX_WAIT))
+    }
+
+    /// Wait for the window to reset if the budget is down to the reserve.
+    pub async fn wait(&self) {
+        let remaining = match *self.budget.lock().unwrap() {
+            Some(budget) if budget.remaining <= RESERVE => budget.remaining,
+            _ => return,
+        };
+        let Some(delay) = self.until_reset() else {
+            return;
+        };
+        warn!(
+            remaining,
+            delay_secs = delay.as_secs(),
+            "Linear rate limit nearly exhausted, pausing until it resets"
+        );
+        tokio::time::sleep(delay).await;
+    }
+}
@@ -0,0 +1,392 @@
+//! Typed Linear operations, generated by `graphql_client` from the `.graphql` files in
+//! `graphql/`.
+//!
+//! Every operation is checked against the checked-in `graphql/schema.graphql` at compile time,
+//! so a misspelled field, a variable of the wrong type, or a selection Linear doesn't offer is a
+//! build error rather than a failed request. Nullable fields in the schema are `Option`s in the
+//! generated types. Operations that share a selection (issue status, comment, user) spell it as
+//! a fragment of the same name, and the conversions into the client's types are written once
+//! for each fragment below.
+
+use graphql_client::GraphQLQuery;
+
+use super::client::{
+    LinearAssignee, LinearComment, LinearIssueDetail, LinearIssueStatus, LinearUser,
+};
+
+/// Custom scalars, as the strings Linear sends them as.
+type DateTime = String;
+type DateTimeOrDuration = String;
+type TimelessDate = String;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/linear/graphql/schema.graphql",
+    query_path = "src/linear/graphql/create_issue.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug, Default",
+    skip_serializing_none
+)]
+pub struct CreateIssue;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/linear/graphql/schema.graphql",
+    query_path = "src/linear/graphql/issue.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug, Default",
+    skip_serializing_none
+)]
+pub struct Issue;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/linear/graphql/schema.graphql",
+    query_path = "src/linear/graphql/updated_issues.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug, Default",
+    skip_serializing_none
+)]
+pub struct UpdatedIssues;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/linear/graphql/schema.graphql",
+    query_path = "src/linear/graphql/issues_by_ids.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug, Default",
+    skip_serializing_none
+)]
+pub struct IssuesByIds;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/linear/graphql/schema.graphql",
+    query_path = "src/linear/graphql/issue_comments.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug, Default",
+    skip_serializing_none
+)]
+pub struct IssueComments;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/linear/graphql/schema.graphql",
+    query_path = "src/linear/graphql/comments_by_ids.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug, Default",
+    skip_serializing_none
+)]
+pub struct CommentsByIds;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/linear/graphql/schema.graphql",
+    query_path = "src/linear/graphql/create_comment.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug, Default",
+    skip_serializing_none
+)]
+pub struct CreateComment;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/linear/graphql/schema.graphql",
+    query_path = "src/linear/graphql/search_issues.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug, Default",
+    skip_serializing_none
+)]
+pub struct SearchIssues;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/linear/graphql/schema.graphql",
+    query_path = "src/linear/graphql/team_members.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug, Default",
+    skip_serializing_none
+)]
+pub struct TeamMembers;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/linear/graphql/schema.graphql",
+    query_path = "src/linear/graphql/team_metadata.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug, Default",
+    skip_serializing_none
+)]
+pub struct TeamMetadata;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/linear/graphql/schema.graphql",
+    query_path = "src/linear/graphql/user_by_email.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug, Default",
+    skip_serializing_none
+)]
+pub struct UserByEmail;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/linear/graphql/schema.graphql",
+    query_path = "src/linear/graphql/user_comment.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug, Default",
+    skip_serializing_none
+)]
+pub struct UserComment;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/linear/graphql/schema.graphql",
+    query_path = "src/linear/graphql/viewer.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug, Default",
+    skip_serializing_none
+)]
+pub struct Viewer;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/linear/graphql/schema.graphql",
+    query_path = "src/linear/graphql/canceled_state.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug, Default",
+    skip_serializing_none
+)]
+pub struct CanceledState;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/linear/graphql/schema.graphql",
+    query_path = "src/linear/graphql/issue_description.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug, Default",
+    skip_serializing_none
+)]
+pub struct IssueDescription;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/linear/graphql/schema.graphql",
+    query_path = "src/linear/graphql/update_issue.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug, Default",
+    skip_serializing_none
+)]
+pub struct UpdateIssue;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/linear/graphql/schema.graphql",
+    query_path = "src/linear/graphql/unassign_issue.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug, Default",
+    skip_serializing_none
+)]
+pub struct UnassignIssue;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/linear/graphql/schema.graphql",
+    query_path = "src/linear/graphql/add_issue_label.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug, Default",
+    skip_serializing_none
+)]
+pub struct AddIssueLabel;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/linear/graphql/schema.graphql",
+    query_path = "src/linear/graphql/upsert_customer.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug, Default",
+    skip_serializing_none
+)]
+pub struct UpsertCustomer;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/linear/graphql/schema.graphql",
+    query_path = "src/linear/graphql/create_customer_need.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug, Default",
+    skip_serializing_none
+)]
+pub struct CreateCustomerNeed;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/linear/graphql/schema.graphql",
+    query_path = "src/linear/graphql/attachment_link_url.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug, Default",
+    skip_serializing_none
+)]
+pub struct AttachmentLinkUrl;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/linear/graphql/schema.graphql",
+    query_path = "src/linear/graphql/file_upload.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug, Default",
+    skip_serializing_none
+)]
+pub struct FileUpload;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/linear/graphql/schema.graphql",
+    query_path = "src/linear/graphql/teams.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug, Default",
+    skip_serializing_none
+)]
+pub struct Teams;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/linear/graphql/schema.graphql",
+    query_path = "src/linear/graphql/team_choices.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug, Default",
+    skip_serializing_none
+)]
+pub struct TeamChoices;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/linear/graphql/schema.graphql",
+    query_path = "src/linear/graphql/team_name.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug, Default",
+    skip_serializing_none
+)]
+pub struct TeamName;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/linear/graphql/schema.graphql",
+    query_path = "src/linear/graphql/project_name.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug, Default",
+    skip_serializing_none
+)]
+pub struct ProjectName;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/linear/graphql/schema.graphql",
+    query_path = "src/linear/graphql/label_name.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug, Default",
+    skip_serializing_none
+)]
+pub struct LabelName;
+
+impl From<issue::IssueIssue> for LinearIssueDetail {
+    fn from(node: issue::IssueIssue) -> Self {
+        LinearIssueDetail {
+            id: node.id,
+            identifier: node.identifier,
+            title: node.title,
+            url: node.url,
+            status_name: node.state.name,
+            status_type: node.state.type_,
+            status_color: node.state.color,
+            assignee_name: node.assignee.map(|a| a.display_name),
+            priority_label: node.priority_label,
+            labels: node.labels.nodes.into_iter().map(|l| l.name).collect(),
+            project_name: node.project.map(|p| p.name),
+            updated_at: node.updated_at,
+        }
+    }
+}
+
+/// `From` impls for the `IssueStatusFields` fragment of each operation that selects it.
+macro_rules! issue_status_fields {
+    ($($module:ident),+) => {$(
+        impl From<$module::IssueStatusFields> for LinearIssueStatus {
+            fn from(node: $module::IssueStatusFields) -> Self {
+                let (label_ids, labels) = node
+                    .labels
+                    .nodes
+                    .into_iter()
+                    .map(|l| (l.id, l.name))
+                    .unzip();
+                LinearIssueStatus {
+                    id: node.id,
+                    identifier: node.identifier,
+                    title: node.title,
+                    assignee: node.assignee.map(|a| LinearAssignee {
+                        id: a.id,
+                        name: a.display_name,
+                    }),
+                    url: node.url,
+                    due_date: node.due_date,
+                    status_name: node.state.name,
+                    status_type: node.state.type_,
+                    status_color: node.state.color,
+                    priority_label: node.priority_label,
+                    label_ids,
+                    labels,
+                    project_name: node.project.map(|p| p.name),
+                    updated_at: node.updated_at,
+                }
+            }
+        }
+    )+};
+}
+
+issue_status_fields!(updated_issues, issues_by_ids);
+
+/// `From` impls for the `CommentFields` fragment. `user` is null for integration and bot
+/// comments, which carry a `botActor` instead.
+macro_rules! comment_fields {
+    ($($module:ident),+) => {$(
+        impl From<$module::CommentFields> for LinearComment {
+            fn from(node: $module::CommentFields) -> Self {
+                let author_name = match (&node.user, node.bot_actor) {
+                    (Some(user), _) => user.display_name.clone(),
+                    (None, Some($module::CommentFieldsBotActor { name: Some(name) })) => name,
+                    _ => "Unknown".to_string(),
+                };
+                LinearComment {
+                    id: node.id,
+                    body: node.body,
+                    created_at: node.created_at,
+                    updated_at: node.updated_at,
+                    author_name,
+                    author_avatar_url: node.user.as_ref().and_then(|u| u.avatar_url.clone()),
+                    author_id: node.user.as_ref().map(|u| u.id.clone()),
+                    author_email: node.user.map(|u| u.email),
+                }
+            }
+        }
+    )+};
+}
+
+comment_fields!(issue_comments, comments_by_ids);
+
+/// `From` impls for the `UserFields` fragment.
+macro_rules! user_fields {
+    ($($module:ident),+) => {$(
+        impl From<$module::UserFields> for LinearUser {
+            fn from(node: $module::UserFields) -> Self {
+                LinearUser {
+                    id: node.id,
+                    name: node.name,
+                    display_name: node.display_name,
+                    email: node.email,
+                }
+            }
+        }
+    )+};
+}
+
+user_fields!(team_members, team_metadata, user_by_email, viewer);
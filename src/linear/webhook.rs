@@ -0,0 +1,448 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::Router;
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use serde_json::Value;
+use serenity::all::ChannelId;
+use serenity::http::Http;
+use sha2::Sha256;
+use tracing::{debug, error, info, warn};
+
+use crate::db;
+use crate::discord::handler::AppState;
+use crate::error::AppError;
+use crate::linear::api::LinearApi;
+use crate::linear::client::LinearAssignee;
+use crate::shadow::Shadowed;
+use crate::sync::discuss::open_thread_for_issue;
+use crate::sync::linear_to_discord::{
+    sync_assignee_to_discord, sync_labels_to_tags, sync_linear_comment_changes,
+    sync_linear_comments_to_discord, sync_linear_to_discord,
+};
+use crate::sync::outbox::{self, Operation};
+use crate::sync::status_embed::update_status_embed;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const SIGNATURE_HEADER: &str = "linear-signature";
+
+#[derive(Debug, thiserror::Error)]
+pub enum WebhookRejection {
+    #[error("missing Linear-Signature header")]
+    MissingSignature,
+
+    #[error("Linear-Signature header is not valid hex")]
+    MalformedSignature,
+
+    #[error("signature mismatch")]
+    BadSignature,
+
+    #[error("payload could not be decoded: {0}")]
+    MalformedPayload(String),
+
+    #[error("webhook timestamp is {age_secs}s away from local time")]
+    Stale { age_secs: i64 },
+}
+
+/// Counters for webhook deliveries, logged alongside each rejection and shown by `/linear stats`.
+#[derive(Debug, Default)]
+pub struct WebhookStats {
+    pub accepted: AtomicU64,
+    pub rejected: AtomicU64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WebhookPayload {
+    action: String,
+    #[serde(rename = "type")]
+    kind: String,
+    data: Value,
+    #[serde(default)]
+    updated_from: Option<Value>,
+    webhook_timestamp: i64,
+}
+
+struct WebhookState {
+    app: Arc<AppState>,
+    discord: Shadowed<Http>,
+    secret: String,
+    max_age_secs: u64,
+}
+
+/// Verify the hex-encoded HMAC-SHA256 of the raw request body. `verify_slice` compares
+/// in constant time, so the signature can't be recovered byte-by-byte via timing.
+pub fn verify_signature(
+    secret: &str,
+    body: &[u8],
+    signature_hex: &str,
+) -> Result<(), WebhookRejection> {
+    let expected =
+        hex::decode(signature_hex.trim()).map_err(|_| WebhookRejection::MalformedSignature)?;
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    mac.verify_slice(&expected)
+        .map_err(|_| WebhookRejection::BadSignature)
+}
+
+/// Reject deliveries whose `webhookTimestamp` (milliseconds) is too far from now, so a
+/// captured request can't be replayed later.
+pub fn check_timestamp(
+    webhook_timestamp_ms: i64,
+    now_ms: i64,
+    max_age_secs: u64,
+) -> Result<(), WebhookRejection> {
+    let age_secs = (now_ms - webhook_timestamp_ms).abs() / 1000;
+    if age_secs > max_age_secs as i64 {
+        return Err(WebhookRejection::Stale { age_secs });
+    }
+    Ok(())
+}
+
+/// Serve the Linear webhook endpoint at `POST /webhooks/linear`. Verified events trigger the
+/// same status/comment sync the poller performs, so updates reach Discord without waiting for
+/// the next poll; the poller remains the fallback and the dedup tables absorb double delivery.
+pub async fn run_webhook_server(
+    addr: String,
+    secret: String,
+    max_age_secs: u64,
+    discord: Shadowed<Http>,
+    app: Arc<AppState>,
+) -> Result<(), AppError> {
+    let shutdown = app.shutdown.clone();
+    let state = Arc::new(WebhookState {
+        app,
+        discord,
+        secret,
+        max_age_secs,
+    });
+
+    let router = Router::new()
+        .route("/webhooks/linear", post(handle_linear_webhook))
+        .with_state(state);
+
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    info!(addr, "Linear webhook receiver listening");
+    axum::serve(listener, router)
+        .with_graceful_shutdown(async move { shutdown.requested().await })
+        .await?;
+
+    Ok(())
+}
+
+async fn handle_linear_webhook(
+    State(state): State<Arc<WebhookState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> StatusCode {
+    let stats = &state.app.webhook_stats;
+    let payload = match authenticate(&state, &headers, &body) {
+        Ok(p) => p,
+        Err(rejection) => {
+            let rejected = stats.rejected.fetch_add(1, Ordering::Relaxed) + 1;
+            warn!(reason = %rejection, rejected_total = rejected, "Rejected Linear webhook");
+            return match rejection {
+                WebhookRejection::MalformedPayload(_) => StatusCode::BAD_REQUEST,
+                _ => StatusCode::UNAUTHORIZED,
+            };
+        }
+    };
+
+    // Linear redelivers refused webhooks, so after a restart nothing is lost.
+    let Some(task) = state.app.shutdown.start_task() else {
+        return StatusCode::SERVICE_UNAVAILABLE;
+    };
+
+    let accepted = stats.accepted.fetch_add(1, Ordering::Relaxed) + 1;
+    debug!(
+        kind = %payload.kind,
+        action = %payload.action,
+        accepted_total = accepted,
+        "Accepted Linear webhook"
+    );
+
+    // Acknowledge immediately; Linear retries deliveries that take too long to answer.
+    tokio::spawn(async move {
+        if let Err(e) = dispatch(&state, payload).await {
+            error!(error = %e, "Failed to process Linear webhook");
+        }
+        drop(task);
+    });
+
+    StatusCode::OK
+}
+
+fn authenticate(
+    state: &WebhookState,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> Result<WebhookPayload, WebhookRejection> {
+    let signature = headers
+        .get(SIGNATURE_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .ok_or(WebhookRejection::MissingSignature)?;
+
+    verify_signature(&state.secret, body, signature)?;
+
+    // Only parse after the signature checks out, so unauthenticated input is never decoded.
+    let payload: WebhookPayload = serde_json::from_slice(body)
+        .map_err(|e| WebhookRejection::MalformedPayload(e.to_string()))?;
+
+    check_timestamp(
+        payload.webhook_timestamp,
+        chrono::Utc::now().timestamp_millis(),
+        state.max_age_secs,
+    )?;
+
+    Ok(payload)
+}
+
+async fn dispatch(state: &WebhookState, payload: WebhookPayload) -> Result<(), AppError> {
+    let pool = &state.app.pool;
+    let config = state.app.config.current();
+
+    match (payload.kind.as_str(), payload.action.as_str()) {
+        ("Issue", "update") => {
+            // Only state, assignee, and label changes matter here; other field edits are ignored.
+            let changed = |field: &str| {
+                payload
+                    .updated_from
+                    .as_ref()
+                    .is_some_and(|u| u.get(field).is_some())
+            };
+            let state_changed = changed("stateId");
+            let assignee_changed = changed("assigneeId");
+            let labels_changed = changed("labelIds");
+            if !state_changed && !assignee_changed && !labels_changed {
+                return Ok(());
+            }
+
+            let issue_id = payload.data["id"].as_str().unwrap_or_default();
+            let identifier = payload.data["identifier"].as_str().unwrap_or_default();
+
+            let label_ids: Vec<String> = payload.data["labelIds"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|id| id.as_str().map(str::to_string))
+                .collect();
+
+            let mapping = match db::get_mapping_by_linear_issue(pool, issue_id).await? {
+                Some(m) => m,
+                None if labels_changed => {
+                    let team_id = payload.data["teamId"].as_str().unwrap_or_default();
+                    open_thread_for_issue(
+                        &state.discord,
+                        pool,
+                        &config,
+                        &state.app.linear_client,
+                        team_id,
+                        issue_id,
+                        &label_ids,
+                    )
+                    .await?;
+                    return Ok(());
+                }
+                None => return Ok(()),
+            };
+
+            if assignee_changed {
+                let assignee = payload.data["assignee"]["id"]
+                    .as_str()
+                    .map(|id| LinearAssignee {
+                        id: id.to_string(),
+                        name: payload.data["assignee"]["name"]
+                            .as_str()
+                            .unwrap_or_default()
+                            .to_string(),
+                    });
+                sync_assignee_to_discord(
+                    &state.discord,
+                    pool,
+                    &config,
+                    issue_id,
+                    identifier,
+                    assignee.as_ref(),
+                )
+                .await?;
+            }
+
+            if labels_changed {
+                sync_labels_to_tags(
+                    &state.discord,
+                    &config,
+                    &mapping.discord_thread_id,
+                    identifier,
+                    &label_ids,
+                )
+                .await?;
+            }
+
+            let status_name = payload.data["state"]["name"].as_str().unwrap_or_default();
+            if !state_changed
+                || db::get_cached_status(pool, issue_id).await?.as_deref() == Some(status_name)
+            {
+                if let Ok(thread_id) = mapping.discord_thread_id.parse() {
+                    update_status_embed(
+                        &state.discord,
+                        pool,
+                        &state.app.linear_client,
+                        ChannelId::new(thread_id),
+                        issue_id,
+                    )
+                    .await?;
+                }
+                return Ok(());
+            }
+
+            info!(
+                identifier,
+                status = status_name,
+                "Status change received via webhook"
+            );
+            // The payload's state lacks the fields shown in the embed.
+            let result = match state.app.linear_client.get_issue(issue_id).await {
+                Ok(issue) => {
+                    sync_linear_to_discord(
+                        &state.discord,
+                        pool,
+                        &config,
+                        &state.app.linear_client,
+                        &issue,
+                    )
+                    .await
+                }
+                Err(e) => Err(e),
+            };
+            if let Err(e) = &result {
+                let operation = Operation::SyncStatus {
+                    issue_id: issue_id.to_string(),
+                };
+                outbox::enqueue(pool, &operation, e).await;
+            }
+            result
+        }
+        ("Comment", "create") => {
+            let issue_id = payload.data["issueId"].as_str().unwrap_or_default();
+            let mapping = match db::get_mapping_by_linear_issue(pool, issue_id).await? {
+                Some(m) => m,
+                None => return Ok(()),
+            };
+
+            sync_linear_comments_to_discord(
+                &state.discord,
+                pool,
+                &config,
+                &state.app.linear_client,
+                &mapping.linear_issue_id,
+                &mapping.linear_identifier,
+            )
+            .await
+        }
+        ("Comment", "update" | "remove") => {
+            let issue_id = payload.data["issueId"].as_str().unwrap_or_default();
+            let mapping = match db::get_mapping_by_linear_issue(pool, issue_id).await? {
+                Some(m) => m,
+                None => return Ok(()),
+            };
+
+            sync_linear_comment_changes(
+                &state.discord,
+                pool,
+                &config,
+                &state.app.linear_client,
+                &mapping.linear_issue_id,
+                &mapping.linear_identifier,
+            )
+            .await
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "whsec_test";
+    const BODY: &[u8] = br#"{"action":"update","type":"Issue","data":{},"webhookTimestamp":0}"#;
+
+    fn sign(body: &[u8]) -> String {
+        let mut mac = HmacSha256::new_from_slice(SECRET.as_bytes()).unwrap();
+        mac.update(body);
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    #[test]
+    fn valid_signature_is_accepted() {
+        assert!(verify_signature(SECRET, BODY, &sign(BODY)).is_ok());
+        // Header values can carry stray whitespace.
+        assert!(verify_signature(SECRET, BODY, &format!(" {}\n", sign(BODY))).is_ok());
+    }
+
+    #[test]
+    fn tampered_body_is_rejected() {
+        let signature = sign(BODY);
+        let tampered = br#"{"action":"remove","type":"Issue","data":{},"webhookTimestamp":0}"#;
+
+        assert!(matches!(
+            verify_signature(SECRET, tampered, &signature),
+            Err(WebhookRejection::BadSignature)
+        ));
+        assert!(matches!(
+            verify_signature("other-secret", BODY, &signature),
+            Err(WebhookRejection::BadSignature)
+        ));
+    }
+
+    #[test]
+    fn non_hex_signature_is_malformed() {
+        assert!(matches!(
+            verify_signature(SECRET, BODY, "not-hex"),
+            Err(WebhookRejection::MalformedSignature)
+        ));
+        assert!(matches!(
+            verify_signature(SECRET, BODY, &sign(BODY)[1..]),
+            Err(WebhookRejection::MalformedSignature)
+        ));
+    }
+
+    #[test]
+    fn timestamps_within_the_window_are_accepted() {
+        let now = 1_700_000_000_000;
+
+        assert!(check_timestamp(now, now, 60).is_ok());
+        assert!(check_timestamp(now - 60_000, now, 60).is_ok());
+        // Clock skew runs both ways.
+        assert!(check_timestamp(now + 30_000, now, 60).is_ok());
+    }
+
+    #[test]
+    fn skewed_timestamps_are_stale() {
+        let now = 1_700_000_000_000;
+
+        assert!(matches!(
+            check_timestamp(now - 61_000, now, 60),
+            Err(WebhookRejection::Stale { age_secs: 61 })
+        ));
+        assert!(matches!(
+            check_timestamp(now + 120_000, now, 60),
+            Err(WebhookRejection::Stale { age_secs: 120 })
+        ));
+    }
+
+    #[test]
+    fn missing_timestamp_is_a_malformed_payload() {
+        let body = br#"{"action":"update","type":"Issue","data":{}}"#;
+
+        assert!(serde_json::from_slice::<WebhookPayload>(body).is_err());
+        assert!(serde_json::from_slice::<WebhookPayload>(BODY).is_ok());
+    }
+}
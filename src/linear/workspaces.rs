@@ -0,0 +1,500 @@
+//! Routes Linear calls to the right workspace when channels use more than one API key.
+//!
+//! A channel's team lives in the workspace of its `linear_credential`, or `LINEAR_API_KEY`'s when
+//! it has none. Team-scoped calls go straight to that workspace. Issue-scoped calls go to the
+//! workspace the issue was last seen in; an issue not seen yet is looked up in each workspace in
+//! turn. With a single key, every call goes to it directly.
+//!
+//! Workspaces whose keys are stored in the database (see `crate::credentials`) can be added and
+//! removed while the bot runs, so clients are handed out by value; they share their connection
+//! pool, rate limit, and breaker with the original.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, PoisonError, RwLock, RwLockReadGuard};
+
+use async_trait::async_trait;
+use tracing::error;
+
+use super::api::LinearApi;
+use super::client::{
+    AttachmentDownload, CommentAuthor, Customer, LinearClient, LinearComment, LinearIssue,
+    LinearIssueDetail, LinearIssueStatus, LinearSearchResult, LinearUser, TeamMetadata, UploadFile,
+};
+use crate::config::{ChannelConfig, SharedConfig};
+use crate::error::AppError;
+
+#[derive(Debug, Clone)]
+pub struct LinearWorkspaces {
+    default: LinearClient,
+    named: Arc<RwLock<BTreeMap<String, LinearClient>>>,
+    config: SharedConfig,
+    /// Workspace of each issue seen so far, by issue ID or identifier: the credential name, or
+    /// `None` for `LINEAR_API_KEY`.
+    issues: Arc<Mutex<HashMap<String, Option<String>>>>,
+}
+
+impl LinearWorkspaces {
+    /// `default` uses `LINEAR_API_KEY`; `named` holds a client per `LINEAR_API_KEYS` entry.
+    pub fn new(
+        default: LinearClient,
+        named: BTreeMap<String, LinearClient>,
+        config: SharedConfig,
+    ) -> Self {
+        Self {
+            default,
+            named: Arc::new(RwLock::new(named)),
+            config,
+            issues: Arc::default(),
+        }
+    }
+
+    /// The client for a credential name, or `LINEAR_API_KEY`'s for `None`.
+    pub fn workspace(&self, credential: Option<&str>) -> Option<LinearClient> {
+        match credential {
+            Some(name) => self.named().get(name).cloned(),
+            None => Some(self.default.clone()),
+        }
+    }
+
+    /// Every workspace's client with its credential name, `LINEAR_API_KEY`'s first.
+    pub fn workspaces(&self) -> Vec<(Option<String>, LinearClient)> {
+        std::iter::once((None, self.default.clone()))
+            .chain(
+                self.named()
+                    .iter()
+                    .map(|(name, c)| (Some(name.clone()), c.clone())),
+            )
+            .collect()
+    }
+
+    /// The client for the workspace a channel's team is in.
+    pub fn for_channel(&self, channel: &ChannelConfig) -> LinearClient {
+        // Config validation guarantees the credential exists.
+        self.workspace(channel.linear_credential.as_deref())
+            .unwrap_or_else(|| self.default.clone())
+    }
+
+    /// A client for another workspace's API key, with the same settings as `LINEAR_API_KEY`'s.
+    pub fn client_for_key(&self, api_key: &str) -> LinearClient {
+        self.default.with_api_key(api_key)
+    }
+
+    /// Add, replace, or (with `None`) remove the workspace for a credential name.
+    pub fn set_workspace(&self, name: &str, client: Option<LinearClient>) {
+        let mut named = self.named.write().unwrap_or_else(PoisonError::into_inner);
+        match client {
+            Some(client) => named.insert(name.to_string(), client),
+            None => named.remove(name),
+        };
+    }
+
+    fn named(&self) -> RwLockReadGuard<'_, BTreeMap<String, LinearClient>> {
+        self.named.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Whether `LINEAR_API_KEY`'s is the only workspace.
+    fn is_single(&self) -> bool {
+        self.named().is_empty()
+    }
+
+    /// The credential of the first channel using `team_id`, or `None` if no channel does.
+    fn team_credential(&self, team_id: &str) -> Option<Option<String>> {
+        self.config
+            .current()
+            .channels
+            .iter()
+            .find(|c| c.team_ids().any(|t| t == team_id))
+            .map(|c| c.linear_credential.clone())
+    }
+
+    /// The client for a team's workspace. Teams not in the config are taken to be in
+    /// `LINEAR_API_KEY`'s.
+    fn for_team(&self, team_id: &str) -> (Option<String>, LinearClient) {
+        let credential = self.team_credential(team_id).flatten();
+        let client = self
+            .workspace(credential.as_deref())
+            .unwrap_or_else(|| self.default.clone());
+        (credential, client)
+    }
+
+    fn remember<'a>(
+        &self,
+        credential: &Option<String>,
+        issue_ids: impl IntoIterator<Item = &'a str>,
+    ) {
+        if self.is_single() {
+            return;
+        }
+        let mut issues = self.issues.lock().unwrap_or_else(PoisonError::into_inner);
+        for id in issue_ids {
+            issues.insert(id.to_string(), credential.clone());
+        }
+    }
+
+    /// The client for the workspace an issue is in.
+    async fn for_issue(&self, issue_id: &str) -> Result<LinearClient, AppError> {
+        if self.is_single() {
+            return Ok(self.default.clone());
+        }
+        let known = self
+            .issues
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(issue_id)
+            .cloned();
+        if let Some(client) = known.and_then(|credential| self.workspace(credential.as_deref())) {
+            return Ok(client);
+        }
+        self.find_issue(issue_id).await.map(|(client, _)| client)
+    }
+
+    /// Look an issue up in each workspace until one has it.
+    async fn find_issue(
+        &self,
+        issue_id: &str,
+    ) -> Result<(LinearClient, LinearIssueDetail), AppError> {
+        for (credential, client) in self.workspaces() {
+            match client.get_issue(issue_id).await {
+                Ok(issue) => {
+                    self.remember(&credential, [issue_id, issue.id.as_str()]);
+                    return Ok((client, issue));
+                }
+                Err(e) if is_not_found(&e) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Err(AppError::LinearApi(format!(
+            "Issue {issue_id} not found in any workspace"
+        )))
+    }
+}
+
+fn is_not_found(e: &AppError) -> bool {
+    matches!(e, AppError::LinearApi(message) if message.to_lowercase().contains("not found"))
+}
+
+#[async_trait]
+impl LinearApi for LinearWorkspaces {
+    /// Available while any workspace is; calls to one whose breaker is open fail fast.
+    fn is_available(&self) -> bool {
+        self.workspaces().iter().any(|(_, c)| c.is_available())
+    }
+
+    async fn create_issue(
+        &self,
+        team_id: &str,
+        title: &str,
+        description: &str,
+        label_ids: &[String],
+        project_id: &str,
+    ) -> Result<LinearIssue, AppError> {
+        let (credential, client) = self.for_team(team_id);
+        let issue = client
+            .create_issue(team_id, title, description, label_ids, project_id)
+            .await?;
+        self.remember(&credential, [issue.id.as_str(), issue.identifier.as_str()]);
+        Ok(issue)
+    }
+
+    async fn get_issue(&self, id_or_identifier: &str) -> Result<LinearIssueDetail, AppError> {
+        if self.is_single() {
+            return self.default.get_issue(id_or_identifier).await;
+        }
+        let known = self
+            .issues
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains_key(id_or_identifier);
+        if known {
+            return self
+                .for_issue(id_or_identifier)
+                .await?
+                .get_issue(id_or_identifier)
+                .await;
+        }
+        self.find_issue(id_or_identifier)
+            .await
+            .map(|(_, issue)| issue)
+    }
+
+    async fn get_updated_issues(
+        &self,
+        team_id: &str,
+        since: &str,
+    ) -> Result<Vec<LinearIssueStatus>, AppError> {
+        let (credential, client) = self.for_team(team_id);
+        let issues = client.get_updated_issues(team_id, since).await?;
+        self.remember(&credential, issues.iter().map(|i| i.id.as_str()));
+        Ok(issues)
+    }
+
+    async fn get_issues_by_ids(&self, ids: &[String]) -> Result<Vec<LinearIssueStatus>, AppError> {
+        if self.is_single() {
+            return self.default.get_issues_by_ids(ids).await;
+        }
+        // Each workspace omits the issues it doesn't have. One that fails (a revoked key, an open
+        // circuit breaker) only loses its own issues; the call fails only if every one does.
+        let mut found = Vec::new();
+        let mut failure = None;
+        let mut answered = false;
+        for (credential, client) in self.workspaces() {
+            match client.get_issues_by_ids(ids).await {
+                Ok(issues) => {
+                    self.remember(&credential, issues.iter().map(|i| i.id.as_str()));
+                    found.extend(issues);
+                    answered = true;
+                }
+                Err(e) => {
+                    error!(
+                        credential = credential.as_deref().unwrap_or("default"),
+                        error = %e,
+                        "Failed to look up issues in a Linear workspace"
+                    );
+                    failure = Some(e);
+                }
+            }
+        }
+        match failure {
+            Some(e) if !answered => Err(e),
+            _ => Ok(found),
+        }
+    }
+
+    async fn get_issue_comments(
+        &self,
+        issue_id: &str,
+        since: Option<&str>,
+    ) -> Result<Vec<LinearComment>, AppError> {
+        self.for_issue(issue_id)
+            .await?
+            .get_issue_comments(issue_id, since)
+            .await
+    }
+
+    async fn get_comments_by_ids(
+        &self,
+        comment_ids: &[String],
+    ) -> Result<Vec<LinearComment>, AppError> {
+        let mut found = Vec::new();
+        for (_, client) in self.workspaces() {
+            found.extend(client.get_comments_by_ids(comment_ids).await?);
+        }
+        Ok(found)
+    }
+
+    async fn create_comment(
+        &self,
+        comment_id: Option<&str>,
+        issue_id: &str,
+        body: &str,
+        author: Option<&CommentAuthor>,
+    ) -> Result<String, AppError> {
+        self.for_issue(issue_id)
+            .await?
+            .create_comment(comment_id, issue_id, body, author)
+            .await
+    }
+
+    async fn search_issues(
+        &self,
+        term: &str,
+        team_ids: &[String],
+        limit: usize,
+    ) -> Result<Vec<LinearSearchResult>, AppError> {
+        if self.is_single() {
+            return self.default.search_issues(term, team_ids, limit).await;
+        }
+        let mut by_workspace: BTreeMap<Option<String>, Vec<String>> = BTreeMap::new();
+        for team_id in team_ids {
+            let (credential, _) = self.for_team(team_id);
+            by_workspace
+                .entry(credential)
+                .or_default()
+                .push(team_id.clone());
+        }
+        let mut results = Vec::new();
+        for (credential, team_ids) in by_workspace {
+            let client = self
+                .workspace(credential.as_deref())
+                .unwrap_or_else(|| self.default.clone());
+            results.extend(client.search_issues(term, &team_ids, limit).await?);
+        }
+        results.truncate(limit);
+        Ok(results)
+    }
+
+    async fn get_team_members(&self, team_id: &str) -> Result<Vec<LinearUser>, AppError> {
+        self.for_team(team_id).1.get_team_members(team_id).await
+    }
+
+    async fn get_team_metadata(&self, team_id: &str) -> Result<TeamMetadata, AppError> {
+        self.for_team(team_id).1.get_team_metadata(team_id).await
+    }
+
+    async fn find_user_by_email(&self, email: &str) -> Result<Option<LinearUser>, AppError> {
+        for (_, client) in self.workspaces() {
+            if let Some(user) = client.find_user_by_email(email).await? {
+                return Ok(Some(user));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn user_commented(&self, user_id: &str, text: &str) -> Result<bool, AppError> {
+        for (_, client) in self.workspaces() {
+            if client.user_commented(user_id, text).await? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// The user `LINEAR_API_KEY` belongs to.
+    async fn viewer(&self) -> Result<LinearUser, AppError> {
+        self.default.viewer().await
+    }
+
+    async fn update_issue_assignee(
+        &self,
+        issue_id: &str,
+        assignee_id: Option<&str>,
+    ) -> Result<(), AppError> {
+        self.for_issue(issue_id)
+            .await?
+            .update_issue_assignee(issue_id, assignee_id)
+            .await
+    }
+
+    async fn update_issue_priority(&self, issue_id: &str, priority: i64) -> Result<(), AppError> {
+        self.for_issue(issue_id)
+            .await?
+            .update_issue_priority(issue_id, priority)
+            .await
+    }
+
+    async fn update_issue_project(&self, issue_id: &str, project_id: &str) -> Result<(), AppError> {
+        self.for_issue(issue_id)
+            .await?
+            .update_issue_project(issue_id, project_id)
+            .await
+    }
+
+    async fn get_canceled_state_id(&self, team_id: &str) -> Result<Option<String>, AppError> {
+        self.for_team(team_id)
+            .1
+            .get_canceled_state_id(team_id)
+            .await
+    }
+
+    async fn update_issue_state(&self, issue_id: &str, state_id: &str) -> Result<(), AppError> {
+        self.for_issue(issue_id)
+            .await?
+            .update_issue_state(issue_id, state_id)
+            .await
+    }
+
+    async fn get_issue_description(&self, issue_id: &str) -> Result<String, AppError> {
+        self.for_issue(issue_id)
+            .await?
+            .get_issue_description(issue_id)
+            .await
+    }
+
+    async fn update_issue_description(
+        &self,
+        issue_id: &str,
+        description: &str,
+    ) -> Result<(), AppError> {
+        self.for_issue(issue_id)
+            .await?
+            .update_issue_description(issue_id, description)
+            .await
+    }
+
+    async fn update_issue_title(&self, issue_id: &str, title: &str) -> Result<(), AppError> {
+        self.for_issue(issue_id)
+            .await?
+            .update_issue_title(issue_id, title)
+            .await
+    }
+
+    async fn update_issue_estimate(&self, issue_id: &str, estimate: i64) -> Result<(), AppError> {
+        self.for_issue(issue_id)
+            .await?
+            .update_issue_estimate(issue_id, estimate)
+            .await
+    }
+
+    async fn add_issue_label(&self, issue_id: &str, label_id: &str) -> Result<(), AppError> {
+        self.for_issue(issue_id)
+            .await?
+            .add_issue_label(issue_id, label_id)
+            .await
+    }
+
+    async fn create_customer_need(
+        &self,
+        issue_id: &str,
+        customer: &Customer,
+        body: &str,
+        url: &str,
+    ) -> Result<(), AppError> {
+        self.for_issue(issue_id)
+            .await?
+            .create_customer_need(issue_id, customer, body, url)
+            .await
+    }
+
+    async fn link_url(&self, issue_id: &str, url: &str, title: &str) -> Result<(), AppError> {
+        self.for_issue(issue_id)
+            .await?
+            .link_url(issue_id, url, title)
+            .await
+    }
+
+    async fn request_file_upload(
+        &self,
+        team_or_issue_id: &str,
+        filename: &str,
+        content_type: &str,
+        size: u64,
+    ) -> Result<UploadFile, AppError> {
+        let client = match self.team_credential(team_or_issue_id) {
+            Some(credential) => self
+                .workspace(credential.as_deref())
+                .unwrap_or_else(|| self.default.clone()),
+            None => self.for_issue(team_or_issue_id).await?,
+        };
+        client
+            .request_file_upload(team_or_issue_id, filename, content_type, size)
+            .await
+    }
+
+    async fn upload_file_to_url(
+        &self,
+        upload: &UploadFile,
+        body: reqwest::Body,
+        size: u64,
+        content_type: &str,
+    ) -> Result<String, AppError> {
+        // The slot's URL is pre-signed, so any client can upload to it.
+        self.default
+            .upload_file_to_url(upload, body, size, content_type)
+            .await
+    }
+
+    async fn download_attachment(&self, url: &str) -> Result<AttachmentDownload, AppError> {
+        self.default.download_attachment(url).await
+    }
+
+    async fn download_linear_upload(
+        &self,
+        issue_id: &str,
+        url: &str,
+    ) -> Result<(Vec<u8>, String), AppError> {
+        self.for_issue(issue_id)
+            .await?
+            .download_linear_upload(issue_id, url)
+            .await
+    }
+}
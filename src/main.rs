@@ -1,33 +1,132 @@
-mod config;
-mod db;
-mod discord;
-mod error;
-mod linear;
-mod sync;
-
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+use anyhow::Context;
+use clap::{Parser, Subcommand, ValueEnum};
 use serenity::all::GatewayIntents;
+use serenity::http::Http;
 use serenity::Client;
-use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
 use std::str::FromStr;
-use tracing::{error, info};
+use tracing::{error, info, warn};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+use discord_linear_bot::config::{Config, SharedConfig};
+use discord_linear_bot::credentials::{self, CredentialKeys};
+use discord_linear_bot::discord::handler::{self, AppState, AppStateKey, Handler};
+use discord_linear_bot::error::AppError;
+use discord_linear_bot::leader::LeaderLease;
+use discord_linear_bot::linear::api::LinearApi;
+use discord_linear_bot::linear::cache::{self, MetadataCache};
+use discord_linear_bot::linear::client::LinearClient;
+use discord_linear_bot::linear::webhook::WebhookStats;
+use discord_linear_bot::linear::workspaces::LinearWorkspaces;
+use discord_linear_bot::shadow::{ShadowLog, Shadowed};
+use discord_linear_bot::shutdown::{self, Shutdown};
+use discord_linear_bot::supervisor::supervise;
+use discord_linear_bot::sync::backfill::RunningBackfills;
+use discord_linear_bot::{db, doctor, linear, sync, telemetry, transfer};
 
-use crate::config::Config;
-use crate::discord::handler::{AppState, AppStateKey, Handler};
-use crate::linear::client::LinearClient;
+/// Syncs Discord forum threads with Linear issues. Configuration is read from the environment
+/// (and `.env`); see `.env.example`.
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the bot (the default)
+    Run,
+    /// Backfill existing forum threads into Linear, then exit
+    Backfill {
+        /// Only backfill this forum channel (by default, every configured channel)
+        #[arg(long)]
+        channel: Option<u64>,
+        /// Backfill again even if an earlier backfill of the channel completed
+        #[arg(long)]
+        force: bool,
+        /// List the issues the backfill would create, without writing to Linear, Discord, or
+        /// the database
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Check the configuration and that the Discord token and Linear API key work, then exit
+    Validate,
+    /// Check every configured channel against Discord and Linear and print a pass/fail report
+    Doctor,
+    /// Print every thread ↔ issue mapping, with its status and comment sync state
+    ExportMappings {
+        /// CSV has one row per mapping and omits the per-comment sync state
+        #[arg(long, value_enum, default_value_t = MappingFormat::Json)]
+        format: MappingFormat,
+    },
+    /// Add the mappings in a file written by export-mappings, skipping any whose thread or issue
+    /// is already mapped, then exit
+    ImportMappings {
+        file: PathBuf,
+        #[arg(long, value_enum, default_value_t = MappingFormat::Json)]
+        format: MappingFormat,
+    },
+    /// Re-encrypt the stored Linear API keys with CREDENTIAL_KEY, then exit
+    RotateCredentials,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum MappingFormat {
+    Json,
+    Csv,
+}
+
+impl From<MappingFormat> for transfer::Format {
+    fn from(format: MappingFormat) -> Self {
+        match format {
+            MappingFormat::Json => transfer::Format::Json,
+            MappingFormat::Csv => transfer::Format::Csv,
+        }
+    }
+}
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    tracing_subscriber::fmt()
-        .with_env_filter(
+    let cli = Cli::parse();
+    dotenvy::dotenv().ok();
+
+    #[cfg(feature = "sentry")]
+    let _sentry = discord_linear_bot::error_reporting::init()?;
+    let (otlp_layer, exporter) = telemetry::OtlpSettings::from_env()
+        .map(telemetry::init)
+        .transpose()?
+        .unzip();
+    // Logs go to stderr so commands that print results (export-mappings) can be piped.
+    let subscriber = tracing_subscriber::registry()
+        .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| "discord_linear_bot=info".into()),
         )
-        .init();
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .with(otlp_layer);
+    #[cfg(feature = "sentry")]
+    let subscriber = subscriber.with(discord_linear_bot::error_reporting::layer());
+    subscriber.init();
 
-    dotenvy::dotenv().ok();
+    let result = run_command(cli).await;
+    #[cfg(feature = "sentry")]
+    if let Err(e) = &result {
+        sentry::integrations::anyhow::capture_anyhow(e);
+    }
+    if let Some(exporter) = exporter {
+        exporter.shutdown().await;
+    }
+    result
+}
 
+async fn run_command(cli: Cli) -> anyhow::Result<()> {
     let config = Config::from_env()?;
     info!(
         channels = config.channels.len(),
@@ -36,33 +135,152 @@ async fn main() -> anyhow::Result<()> {
         "Configuration loaded"
     );
 
-    // SQLite pool + migrations
-    let connect_options =
-        SqliteConnectOptions::from_str(&config.database_url)?.create_if_missing(true);
+    match cli.command.unwrap_or(Command::Run) {
+        Command::Run => run(config).await,
+        Command::Backfill {
+            channel,
+            force,
+            dry_run,
+        } => backfill(config, channel, force, dry_run).await,
+        Command::Validate => validate(config).await,
+        Command::Doctor => doctor(config).await,
+        Command::ExportMappings { format } => export_mappings(config, format.into()).await,
+        Command::ImportMappings { file, format } => {
+            import_mappings(config, &file, format.into()).await
+        }
+        Command::RotateCredentials => rotate_credentials(config).await,
+    }
+}
+
+/// Open the database, apply pending migrations, and check its integrity.
+async fn open_database(config: &Config) -> anyhow::Result<SqlitePool> {
+    let connect_options = SqliteConnectOptions::from_str(&config.database_url)?
+        .create_if_missing(true)
+        .journal_mode(config.sqlite_journal_mode)
+        .synchronous(config.sqlite_synchronous)
+        .busy_timeout(std::time::Duration::from_millis(
+            config.sqlite_busy_timeout_ms,
+        ));
     let pool = SqlitePoolOptions::new()
-        .max_connections(5)
+        .max_connections(config.database_max_connections)
         .connect_with(connect_options)
         .await?;
 
-    sqlx::raw_sql(include_str!("../migrations/001_initial_schema.sql"))
-        .execute(&pool)
-        .await?;
-    sqlx::raw_sql(include_str!("../migrations/002_comment_sync.sql"))
-        .execute(&pool)
-        .await?;
+    db::run_migrations(&pool).await?;
+    let problems = db::check_integrity(&pool).await?;
+    if !problems.is_empty() {
+        for problem in &problems {
+            error!(problem = %problem, "Database integrity check failed");
+        }
+        anyhow::bail!(
+            "{} failed its integrity check; restore it from a backup",
+            config.database_url
+        );
+    }
 
     info!("Database initialized");
+    Ok(pool)
+}
+
+/// Where writes go instead of Linear and Discord in shadow mode; `None` otherwise.
+fn shadow_log(config: &Config, pool: &SqlitePool) -> Option<ShadowLog> {
+    if !config.shadow_mode {
+        return None;
+    }
+    warn!(
+        record = config.shadow_record,
+        "Shadow mode: Linear and Discord writes are logged, not made"
+    );
+    Some(ShadowLog::new(config.shadow_record.then(|| pool.clone())))
+}
+
+/// `config` with the channels managed through `/linear admin` applied.
+async fn with_stored_channels(config: Config, pool: &SqlitePool) -> anyhow::Result<Config> {
+    let config = SharedConfig::new(config).load_stored_channels(pool).await?;
+    Ok(Config::clone(&config))
+}
+
+/// A Linear client per workspace: `LINEAR_API_KEY`'s and one per `LINEAR_API_KEYS` entry.
+/// Channels (and so teams) are looked up in `shared_config`.
+fn linear_workspaces(config: &Config, shared_config: SharedConfig) -> LinearWorkspaces {
+    let named = config
+        .linear_api_keys
+        .iter()
+        .map(|(name, api_key)| (name.clone(), linear_client(config, api_key)))
+        .collect();
+    LinearWorkspaces::new(
+        linear_client(config, &config.linear_api_key),
+        named,
+        shared_config,
+    )
+}
+
+/// Add a workspace for each Linear API key stored with `/linear admin add-credential`.
+async fn load_stored_workspaces(
+    linear: &LinearWorkspaces,
+    pool: &SqlitePool,
+    keys: Option<&CredentialKeys>,
+) -> anyhow::Result<()> {
+    for credential in credentials::load(pool, keys).await? {
+        let client = linear.client_for_key(&credential.api_key);
+        linear.set_workspace(&credential.name, Some(client));
+    }
+    Ok(())
+}
 
-    let linear_client = LinearClient::new(config.linear_api_key.clone());
+fn linear_client(config: &Config, api_key: &str) -> LinearClient {
+    LinearClient::new(api_key.to_string())
+        .with_max_attempts(config.linear_max_attempts)
+        .with_circuit_breaker(
+            config.breaker_threshold,
+            std::time::Duration::from_secs(config.breaker_cooldown_secs),
+        )
+}
+
+async fn run(config: Config) -> anyhow::Result<()> {
+    let pool = open_database(&config).await?;
+    let credential_keys = CredentialKeys::from_config(&config)?;
+    let shared_config = SharedConfig::new(config);
+    let config = shared_config.load_stored_channels(&pool).await?;
+    let shadow = shadow_log(&config, &pool);
+    let linear_client = Shadowed::new(
+        Arc::new(linear_workspaces(&config, shared_config.clone())),
+        shadow.clone(),
+    );
+    load_stored_workspaces(&linear_client, &pool, credential_keys.as_ref()).await?;
+
+    // Listening from the start means a signal during the startup backfill lets it finish too.
+    let shutdown = Shutdown::new();
+    tokio::spawn({
+        let shutdown = shutdown.clone();
+        async move {
+            if let Err(e) = shutdown::signalled().await {
+                error!(error = %e, "Failed to listen for shutdown signals");
+                return;
+            }
+            info!("Shutdown requested, finishing in-flight syncs");
+            shutdown.request();
+        }
+    });
 
     let app_state = Arc::new(AppState {
-        config: config.clone(),
+        config: shared_config.clone(),
         pool: pool.clone(),
         linear_client: linear_client.clone(),
+        metadata: MetadataCache::default(),
+        credential_keys,
+        shutdown: shutdown.clone(),
+        backfills: RunningBackfills::default(),
+        shadow: shadow.clone(),
+        started_at: chrono::Utc::now(),
+        webhook_stats: WebhookStats::default(),
     });
 
     // Build Discord client
-    let intents = GatewayIntents::GUILDS | GatewayIntents::MESSAGE_CONTENT;
+    let intents = GatewayIntents::GUILDS
+        | GatewayIntents::GUILD_MESSAGES
+        | GatewayIntents::GUILD_MESSAGE_REACTIONS
+        | GatewayIntents::MESSAGE_CONTENT;
     let mut discord_client = Client::builder(&config.discord_token, intents)
         .event_handler(Handler)
         .await?;
@@ -73,13 +291,58 @@ async fn main() -> anyhow::Result<()> {
         data.insert::<AppStateKey>(app_state.clone());
     }
 
-    let discord_http = discord_client.http.clone();
+    let discord = Shadowed::new(discord_client.http.clone(), shadow);
+
+    // With several replicas, only the one holding the lease connects to Discord and syncs; the
+    // rest wait here, ready to take over.
+    let mut lease_holder = None;
+    if config.leader_election {
+        let lease = Arc::new(LeaderLease::new(
+            pool.clone(),
+            std::time::Duration::from_secs(config.leader_lease_secs),
+        ));
+        if !lease.acquire(&shutdown).await {
+            pool.close().await;
+            info!("Shut down while standing by");
+            return Ok(());
+        }
+        let renewal = tokio::spawn({
+            let lease = lease.clone();
+            let shutdown = shutdown.clone();
+            async move { lease.hold(&shutdown).await }
+        });
+        lease_holder = Some((lease, renewal));
+    }
+
+    // Linear webhook receiver (optional). Config validation guarantees a secret is set
+    // whenever a listen address is.
+    if let (Some(addr), Some(secret)) = (
+        config.webhook_listen_addr.clone(),
+        config.linear_webhook_secret.clone(),
+    ) {
+        tokio::spawn({
+            let discord = discord.clone();
+            let app_state = app_state.clone();
+            let max_age_secs = config.webhook_max_age_secs;
+            async move {
+                if let Err(e) = linear::webhook::run_webhook_server(
+                    addr,
+                    secret,
+                    max_age_secs,
+                    discord,
+                    app_state,
+                )
+                .await
+                {
+                    error!(error = %e, "Linear webhook receiver stopped");
+                }
+            }
+        });
+    }
 
     // Run backfill before starting live sync
     info!("Running backfill...");
-    if let Err(e) =
-        sync::backfill::run_backfill(&discord_http, &pool, &config, &linear_client).await
-    {
+    if let Err(e) = sync::backfill::run_backfill(&discord, &pool, &config, &linear_client).await {
         error!(error = %e, "Backfill failed, continuing with live sync");
     }
 
@@ -87,31 +350,378 @@ async fn main() -> anyhow::Result<()> {
     // issues that completed before this feature existed and self-heals on every restart.
     info!("Reconciling Discord thread archive state...");
     if let Err(e) =
-        sync::reconcile::reconcile_archive_state(&discord_http, &pool, &linear_client).await
+        sync::reconcile::reconcile_archive_state(&discord, &pool, &config, &linear_client).await
     {
         error!(error = %e, "Reconcile pass failed, continuing with live sync");
     }
 
-    // Spawn Linear status poller (handles status sync, comment sync, and the periodic
-    // Discord→Linear thread reconcile for posts whose issue creation was missed or failed).
-    let poller_handle = tokio::spawn(linear::poller::run_poller(
-        discord_http,
-        pool,
-        linear_client,
-        config,
+    if config.digest_window_secs > 0 {
+        tokio::spawn(sync::digest::run_digests(
+            discord.clone(),
+            pool.clone(),
+            Config::clone(&config),
+        ));
+    }
+
+    // Due date reminders run on their own schedule; they only need read access to Linear.
+    tokio::spawn(sync::reminders::run_reminders(
+        discord.clone(),
+        pool.clone(),
+        linear_client.clone(),
+        shared_config.clone(),
     ));
 
+    tokio::spawn(sync::stale::run_stale_nudges(
+        discord.clone(),
+        pool.clone(),
+        linear_client.clone(),
+        shared_config.clone(),
+    ));
+
+    tokio::spawn(sync::votes::run_vote_sync(
+        discord.clone(),
+        pool.clone(),
+        linear_client.clone(),
+        shared_config.clone(),
+    ));
+
+    tokio::spawn(cache::run_metadata_refresh(
+        linear_client.clone(),
+        shared_config.clone(),
+        app_state.metadata.clone(),
+    ));
+
+    tokio::spawn(sync::sla::run_sla_checks(
+        discord.clone(),
+        pool.clone(),
+        shared_config.clone(),
+    ));
+
+    if config.drift_check_interval_secs > 0 {
+        tokio::spawn(sync::drift::run_drift_checks(
+            discord.clone(),
+            pool.clone(),
+            linear_client.clone(),
+            shared_config.clone(),
+        ));
+    }
+
+    #[cfg(unix)]
+    tokio::spawn({
+        let discord = discord.clone();
+        let pool = pool.clone();
+        let linear_client = linear_client.clone();
+        let shared_config = shared_config.clone();
+        async move {
+            if let Err(e) = reload_on_hangup(discord, pool, linear_client, shared_config).await {
+                error!(error = %e, "Config reload handler stopped");
+            }
+        }
+    });
+
+    // Spawn Linear status poller (handles status sync, comment sync, and the periodic
+    // Discord→Linear thread reconcile for posts whose issue creation was missed or failed),
+    // restarted with backoff if it dies.
+    let ops_channel_id = config.ops_channel_id;
+    let poller_handle = tokio::spawn({
+        let pool = pool.clone();
+        let shutdown = shutdown.clone();
+        async move {
+            supervise("Linear poller", &discord, ops_channel_id, &shutdown, || {
+                linear::poller::run_poller(
+                    discord.clone(),
+                    pool.clone(),
+                    linear_client.clone(),
+                    shared_config.clone(),
+                    shutdown.clone(),
+                )
+            })
+            .await
+        }
+    });
+
     // Run Discord gateway + poller concurrently
+    let shard_manager = discord_client.shard_manager.clone();
     tokio::select! {
+        // Checked first: the poller also ends on shutdown.
+        biased;
+        _ = shutdown.requested() => {}
         result = discord_client.start() => {
             if let Err(e) = result {
                 error!(error = %e, "Discord client error");
             }
         }
         _ = poller_handle => {
-            error!("Linear poller unexpectedly ended");
+            error!("Linear poller supervisor unexpectedly ended");
+        }
+    }
+
+    // A standby may already be syncing, so nothing in flight may write another update to Linear
+    // or Discord. Anything cut short is picked up by the new leader's reconcile.
+    if let Some((lease, _)) = &lease_holder {
+        if lease.is_lost() {
+            error!("Lost the leader lease, exiting without draining in-flight syncs");
+            std::process::exit(1);
+        }
+    }
+
+    // However the run ended, stop taking new work and let what's under way finish, so an issue
+    // isn't left created in Linear without its mapping.
+    shutdown.request();
+    let timeout = std::time::Duration::from_secs(config.shutdown_timeout_secs);
+    if !shutdown.drain(timeout).await {
+        warn!(
+            in_flight = shutdown.in_flight(),
+            "Shutdown timed out with syncs still in flight"
+        );
+    }
+    shard_manager.shutdown_all().await;
+    if let Some((lease, renewal)) = lease_holder {
+        renewal.abort();
+        if let Err(e) = lease.release().await {
+            warn!(error = %e, "Failed to release the leader lease");
         }
     }
+    pool.close().await;
+    info!("Shut down");
+
+    Ok(())
+}
+
+/// Reload `CHANNELS` and `MESSAGE_TEMPLATES` on SIGHUP. Guilds new to the config get the slash
+/// commands, and new channels are backfilled; an invalid config is logged and not applied.
+#[cfg(unix)]
+async fn reload_on_hangup(
+    discord: Shadowed<Http>,
+    pool: SqlitePool,
+    linear_client: Shadowed<LinearWorkspaces>,
+    shared_config: SharedConfig,
+) -> anyhow::Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangups = signal(SignalKind::hangup())?;
+    while hangups.recv().await.is_some() {
+        let previous = shared_config.current();
+        let config = match shared_config.reload() {
+            Ok(config) => config,
+            Err(e) => {
+                error!(error = %e, "Config reload failed, keeping the running configuration");
+                continue;
+            }
+        };
+        info!(
+            channels = config.channels.len(),
+            teams = config.unique_team_ids().len(),
+            guilds = config.unique_guild_ids().len(),
+            "Configuration reloaded"
+        );
+
+        let known_guilds = previous.unique_guild_ids();
+        for guild_id in config.unique_guild_ids() {
+            if !known_guilds.contains(&guild_id) && !discord.is_shadow() {
+                handler::register_commands(&discord, guild_id).await;
+            }
+        }
+        // Channels that were backfilled before are skipped.
+        if let Err(e) = sync::backfill::run_backfill(&discord, &pool, &config, &linear_client).await
+        {
+            error!(error = %e, "Backfill of reloaded channels failed");
+        }
+    }
+    Ok(())
+}
+
+async fn backfill(
+    config: Config,
+    channel: Option<u64>,
+    force: bool,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    let pool = open_database(&config).await?;
+    let mut config = with_stored_channels(config, &pool).await?;
+    if let Some(channel_id) = channel {
+        config
+            .channels
+            .retain(|c| c.discord_channel_id == channel_id);
+        if config.channels.is_empty() {
+            anyhow::bail!("channel {channel_id} is not configured");
+        }
+    }
+    if force && !dry_run {
+        for channel in &config.channels {
+            let channel_id = channel.discord_channel_id.to_string();
+            db::upsert_backfill_state(&pool, &channel_id, false, None).await?;
+        }
+    }
+
+    let shadow = shadow_log(&config, &pool);
+    let discord = Shadowed::new(Arc::new(Http::new(&config.discord_token)), shadow.clone());
+    let linear = Shadowed::new(
+        Arc::new(linear_workspaces(
+            &config,
+            SharedConfig::new(config.clone()),
+        )),
+        shadow,
+    );
+    let keys = CredentialKeys::from_config(&config)?;
+    load_stored_workspaces(&linear, &pool, keys.as_ref()).await?;
+    if dry_run {
+        return print_backfill_plan(&discord, &pool, &config, &linear, force).await;
+    }
+    sync::backfill::run_backfill(&discord, &pool, &config, &linear).await?;
+    Ok(())
+}
 
+/// Print the issues a backfill would create, with their Linear team, project, and labels by name.
+async fn print_backfill_plan(
+    http: &Http,
+    pool: &SqlitePool,
+    config: &Config,
+    linear: &LinearWorkspaces,
+    force: bool,
+) -> anyhow::Result<()> {
+    let planned = sync::backfill::plan_backfill(http, pool, config, force).await?;
+    let mut names = HashMap::new();
+    for issue in &planned {
+        let channel = config
+            .channel_config(issue.channel_id)
+            .context("planned issue for an unconfigured channel")?;
+        let client = linear.for_channel(channel);
+        let team = cached_name(&mut names, &issue.team_id, client.team_name(&issue.team_id)).await;
+        let project = cached_name(
+            &mut names,
+            &issue.project_id,
+            client.project_name(&issue.project_id),
+        )
+        .await;
+        let mut labels = Vec::new();
+        for label_id in &issue.label_ids {
+            labels.push(cached_name(&mut names, label_id, client.label_name(label_id)).await);
+        }
+
+        println!(
+            "channel {} thread {}: {:?}",
+            issue.channel_id, issue.thread_id, issue.title
+        );
+        println!(
+            "    team {team}, project {project}, labels {}",
+            labels.join(", ")
+        );
+        if issue.intake_form {
+            println!("    (filed after the post's intake form is answered or times out)");
+        }
+    }
+
+    println!(
+        "Dry run: {} issues would be created; nothing was written",
+        planned.len()
+    );
+    Ok(())
+}
+
+/// Name of the Linear entity `id`, looked up once per ID. An ID that doesn't resolve is shown
+/// with the error, so a bad config stands out in the dry run.
+async fn cached_name(
+    names: &mut HashMap<String, String>,
+    id: &str,
+    lookup: impl Future<Output = Result<String, AppError>>,
+) -> String {
+    if let Some(name) = names.get(id) {
+        return name.clone();
+    }
+    let name = match lookup.await {
+        Ok(name) => format!("{name:?}"),
+        Err(e) => format!("{id} (lookup failed: {e})"),
+    };
+    names.insert(id.to_string(), name.clone());
+    name
+}
+
+async fn validate(config: Config) -> anyhow::Result<()> {
+    // Loading the config already checked CHANNELS and the message templates.
+    let bot = Http::new(&config.discord_token)
+        .get_current_user()
+        .await
+        .context("Discord rejected DISCORD_TOKEN")?;
+    println!("Discord: signed in as {}", bot.name);
+
+    let linear = linear_workspaces(&config, SharedConfig::new(config.clone()));
+    for (credential, client) in linear.workspaces() {
+        let name = match credential {
+            Some(name) => format!("LINEAR_API_KEYS[{name}]"),
+            None => "LINEAR_API_KEY".to_string(),
+        };
+        let viewer = client
+            .viewer()
+            .await
+            .with_context(|| format!("Linear rejected {name}"))?;
+        println!(
+            "Linear ({name}): signed in as {} <{}>",
+            viewer.display_name, viewer.email
+        );
+    }
+
+    println!(
+        "Configuration OK: {} channels, {} teams",
+        config.channels.len(),
+        config.unique_team_ids().len()
+    );
+    Ok(())
+}
+
+async fn doctor(config: Config) -> anyhow::Result<()> {
+    let pool = open_database(&config).await?;
+    let config = with_stored_channels(config, &pool).await?;
+    let http = Http::new(&config.discord_token);
+    let linear = linear_workspaces(&config, SharedConfig::new(config.clone()));
+    let keys = CredentialKeys::from_config(&config)?;
+    load_stored_workspaces(&linear, &pool, keys.as_ref()).await?;
+    let checks = doctor::run_checks(&http, &linear, &config).await;
+    for check in &checks {
+        println!("{check}");
+    }
+
+    let failed = checks.iter().filter(|c| !c.passed).count();
+    if failed > 0 {
+        anyhow::bail!("{failed} of {} checks failed", checks.len());
+    }
+    println!("All {} checks passed", checks.len());
+    Ok(())
+}
+
+async fn export_mappings(config: Config, format: transfer::Format) -> anyhow::Result<()> {
+    let pool = open_database(&config).await?;
+    let mappings = transfer::export(&pool).await?;
+    transfer::write(format, &mappings, std::io::stdout().lock())?;
+    Ok(())
+}
+
+async fn import_mappings(
+    config: Config,
+    file: &Path,
+    format: transfer::Format,
+) -> anyhow::Result<()> {
+    let reader =
+        std::fs::File::open(file).with_context(|| format!("Failed to open {}", file.display()))?;
+    let mappings = transfer::read(format, std::io::BufReader::new(reader))
+        .with_context(|| format!("Failed to parse {}", file.display()))?;
+    let pool = open_database(&config).await?;
+    let summary = transfer::import(&pool, &mappings).await?;
+    println!(
+        "Imported {} mappings; skipped {} already mapped here",
+        summary.imported, summary.skipped
+    );
+    Ok(())
+}
+
+async fn rotate_credentials(config: Config) -> anyhow::Result<()> {
+    let keys = CredentialKeys::from_config(&config)?
+        .context("CREDENTIAL_KEY must be set to rotate credentials")?;
+    let pool = open_database(&config).await?;
+    let rotated = credentials::rotate(&pool, &keys).await?;
+    println!(
+        "Re-encrypted {rotated} stored Linear API keys with key {}",
+        keys.current_key_id()
+    );
     Ok(())
 }
@@ -0,0 +1,672 @@
+//! Shadow mode: run the bot against live traffic without acting on it, to stage a new config or
+//! build against production.
+//!
+//! With `SHADOW_MODE` set, Linear and Discord are wrapped in [`Shadowed`]. Reads go through, but
+//! every write is logged instead of sent (and stored in `shadow_actions` with `SHADOW_RECORD`),
+//! and answered with a stand-in result so the sync carries on as if it had succeeded. What the
+//! bot would otherwise write through serenity directly (command replies and registration, the
+//! startup backfill) is skipped. A shadow instance needs a database of its own, since the
+//! mappings it stores point at issues that were never created.
+
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use serenity::all::{
+    ApplicationId, ChannelId, ChannelType, CreateForumPost, CreateMessage, CreateWebhook,
+    EditMessage, EditThread, EditWebhookMessage, ExecuteWebhook, GuildChannel, GuildId, Member,
+    Message, MessageId, Role, RoleId, User, UserId, Webhook,
+};
+use sqlx::SqlitePool;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::db;
+use crate::discord::port::DiscordPort;
+use crate::error::AppError;
+use crate::linear::api::LinearApi;
+use crate::linear::client::{
+    AttachmentDownload, CommentAuthor, Customer, LinearComment, LinearIssue, LinearIssueDetail,
+    LinearIssueStatus, LinearSearchResult, LinearUser, TeamMetadata, UploadFile,
+};
+
+/// Prefix of the IDs given to issues created in shadow mode, so reads that would ask Linear
+/// about them can be answered here.
+const SHADOW_ISSUE_PREFIX: &str = "shadow-";
+
+/// Discord snowflakes count milliseconds from the start of 2015.
+const DISCORD_EPOCH_MS: u64 = 1_420_070_400_000;
+
+/// Where skipped writes go: the log, and the `shadow_actions` table when given a pool.
+#[derive(Clone)]
+pub struct ShadowLog {
+    pool: Option<SqlitePool>,
+    sequence: Arc<AtomicU64>,
+}
+
+impl ShadowLog {
+    pub fn new(pool: Option<SqlitePool>) -> Self {
+        Self {
+            pool,
+            sequence: Arc::default(),
+        }
+    }
+
+    async fn record(&self, service: &'static str, action: &'static str, detail: Value) {
+        info!(service, action, %detail, "Shadow mode: skipped write");
+        if let Some(pool) = &self.pool {
+            let detail = detail.to_string();
+            if let Err(e) = db::insert_shadow_action(pool, service, action, &detail).await {
+                warn!(service, action, error = %e, "Failed to record shadow action");
+            }
+        }
+    }
+
+    /// A Discord ID for a stand-in message or webhook, shaped like a real one from now.
+    fn snowflake(&self) -> u64 {
+        let elapsed =
+            (chrono::Utc::now().timestamp_millis() as u64).saturating_sub(DISCORD_EPOCH_MS);
+        let sequence = self.sequence.fetch_add(1, Ordering::Relaxed) & 0xfff;
+        (elapsed << 22) | sequence
+    }
+
+    /// The message Discord would have returned for `body` posted in `channel_id`.
+    fn stand_in_message(&self, channel_id: ChannelId, id: MessageId, body: &Value) -> Message {
+        let mut message = Message::default();
+        message.id = id;
+        message.channel_id = channel_id;
+        message.content = body["content"].as_str().unwrap_or_default().to_string();
+        message.author = User::default();
+        message.author.bot = true;
+        if let Some(username) = body["username"].as_str() {
+            message.author.name = username.to_string();
+        }
+        message
+    }
+}
+
+/// Linear or Discord, with writes diverted to a [`ShadowLog`] when one is set and passed
+/// through otherwise. Derefs to the wrapped client for what isn't part of its API trait.
+pub struct Shadowed<T> {
+    inner: Arc<T>,
+    log: Option<ShadowLog>,
+}
+
+impl<T> Shadowed<T> {
+    pub fn new(inner: Arc<T>, log: Option<ShadowLog>) -> Self {
+        Self { inner, log }
+    }
+
+    pub fn is_shadow(&self) -> bool {
+        self.log.is_some()
+    }
+}
+
+impl<T> Clone for Shadowed<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            log: self.log.clone(),
+        }
+    }
+}
+
+impl<T> Deref for Shadowed<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+fn is_shadow_issue(id: &str) -> bool {
+    id.starts_with(SHADOW_ISSUE_PREFIX)
+}
+
+#[async_trait]
+impl<T: LinearApi> LinearApi for Shadowed<T> {
+    fn is_available(&self) -> bool {
+        self.inner.is_available()
+    }
+
+    async fn create_issue(
+        &self,
+        team_id: &str,
+        title: &str,
+        description: &str,
+        label_ids: &[String],
+        project_id: &str,
+    ) -> Result<LinearIssue, AppError> {
+        let Some(log) = &self.log else {
+            return self
+                .inner
+                .create_issue(team_id, title, description, label_ids, project_id)
+                .await;
+        };
+        let detail = json!({
+            "team_id": team_id,
+            "title": title,
+            "description": description,
+            "label_ids": label_ids,
+            "project_id": project_id,
+        });
+        log.record("linear", "create_issue", detail).await;
+        let uuid = Uuid::new_v4().simple().to_string();
+        let identifier = format!("SHADOW-{}", &uuid[..8]);
+        Ok(LinearIssue {
+            id: format!("{SHADOW_ISSUE_PREFIX}{uuid}"),
+            url: format!("https://linear.app/issue/{identifier}"),
+            identifier,
+            title: title.to_string(),
+        })
+    }
+
+    async fn get_issue(&self, id_or_identifier: &str) -> Result<LinearIssueDetail, AppError> {
+        self.inner.get_issue(id_or_identifier).await
+    }
+
+    async fn get_updated_issues(
+        &self,
+        team_id: &str,
+        since: &str,
+    ) -> Result<Vec<LinearIssueStatus>, AppError> {
+        self.inner.get_updated_issues(team_id, since).await
+    }
+
+    async fn get_issues_by_ids(&self, ids: &[String]) -> Result<Vec<LinearIssueStatus>, AppError> {
+        let real: Vec<String> = ids
+            .iter()
+            .filter(|id| !is_shadow_issue(id))
+            .cloned()
+            .collect();
+        if real.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.inner.get_issues_by_ids(&real).await
+    }
+
+    async fn get_issue_comments(
+        &self,
+        issue_id: &str,
+        since: Option<&str>,
+    ) -> Result<Vec<LinearComment>, AppError> {
+        if is_shadow_issue(issue_id) {
+            return Ok(Vec::new());
+        }
+        self.inner.get_issue_comments(issue_id, since).await
+    }
+
+    async fn get_comments_by_ids(
+        &self,
+        comment_ids: &[String],
+    ) -> Result<Vec<LinearComment>, AppError> {
+        self.inner.get_comments_by_ids(comment_ids).await
+    }
+
+    async fn create_comment(
+        &self,
+        comment_id: Option<&str>,
+        issue_id: &str,
+        body: &str,
+        author: Option<&CommentAuthor>,
+    ) -> Result<String, AppError> {
+        let Some(log) = &self.log else {
+            return self
+                .inner
+                .create_comment(comment_id, issue_id, body, author)
+                .await;
+        };
+        let comment_id = comment_id.map_or_else(|| Uuid::new_v4().to_string(), str::to_string);
+        let detail = json!({
+            "comment_id": comment_id,
+            "issue_id": issue_id,
+            "body": body,
+            "author": author.map(|a| &a.name),
+        });
+        log.record("linear", "create_comment", detail).await;
+        Ok(comment_id)
+    }
+
+    async fn search_issues(
+        &self,
+        term: &str,
+        team_ids: &[String],
+        limit: usize,
+    ) -> Result<Vec<LinearSearchResult>, AppError> {
+        self.inner.search_issues(term, team_ids, limit).await
+    }
+
+    async fn get_team_members(&self, team_id: &str) -> Result<Vec<LinearUser>, AppError> {
+        self.inner.get_team_members(team_id).await
+    }
+
+    async fn get_team_metadata(&self, team_id: &str) -> Result<TeamMetadata, AppError> {
+        self.inner.get_team_metadata(team_id).await
+    }
+
+    async fn find_user_by_email(&self, email: &str) -> Result<Option<LinearUser>, AppError> {
+        self.inner.find_user_by_email(email).await
+    }
+
+    async fn user_commented(&self, user_id: &str, text: &str) -> Result<bool, AppError> {
+        self.inner.user_commented(user_id, text).await
+    }
+
+    async fn viewer(&self) -> Result<LinearUser, AppError> {
+        self.inner.viewer().await
+    }
+
+    async fn update_issue_assignee(
+        &self,
+        issue_id: &str,
+        assignee_id: Option<&str>,
+    ) -> Result<(), AppError> {
+        let Some(log) = &self.log else {
+            return self
+                .inner
+                .update_issue_assignee(issue_id, assignee_id)
+                .await;
+        };
+        let detail = json!({ "issue_id": issue_id, "assignee_id": assignee_id });
+        log.record("linear", "update_issue_assignee", detail).await;
+        Ok(())
+    }
+
+    async fn update_issue_priority(&self, issue_id: &str, priority: i64) -> Result<(), AppError> {
+        let Some(log) = &self.log else {
+            return self.inner.update_issue_priority(issue_id, priority).await;
+        };
+        let detail = json!({ "issue_id": issue_id, "priority": priority });
+        log.record("linear", "update_issue_priority", detail).await;
+        Ok(())
+    }
+
+    async fn update_issue_project(&self, issue_id: &str, project_id: &str) -> Result<(), AppError> {
+        let Some(log) = &self.log else {
+            return self.inner.update_issue_project(issue_id, project_id).await;
+        };
+        let detail = json!({ "issue_id": issue_id, "project_id": project_id });
+        log.record("linear", "update_issue_project", detail).await;
+        Ok(())
+    }
+
+    async fn get_canceled_state_id(&self, team_id: &str) -> Result<Option<String>, AppError> {
+        self.inner.get_canceled_state_id(team_id).await
+    }
+
+    async fn update_issue_state(&self, issue_id: &str, state_id: &str) -> Result<(), AppError> {
+        let Some(log) = &self.log else {
+            return self.inner.update_issue_state(issue_id, state_id).await;
+        };
+        let detail = json!({ "issue_id": issue_id, "state_id": state_id });
+        log.record("linear", "update_issue_state", detail).await;
+        Ok(())
+    }
+
+    async fn get_issue_description(&self, issue_id: &str) -> Result<String, AppError> {
+        self.inner.get_issue_description(issue_id).await
+    }
+
+    async fn update_issue_description(
+        &self,
+        issue_id: &str,
+        description: &str,
+    ) -> Result<(), AppError> {
+        let Some(log) = &self.log else {
+            return self
+                .inner
+                .update_issue_description(issue_id, description)
+                .await;
+        };
+        let detail = json!({ "issue_id": issue_id, "description": description });
+        log.record("linear", "update_issue_description", detail)
+            .await;
+        Ok(())
+    }
+
+    async fn update_issue_title(&self, issue_id: &str, title: &str) -> Result<(), AppError> {
+        let Some(log) = &self.log else {
+            return self.inner.update_issue_title(issue_id, title).await;
+        };
+        let detail = json!({ "issue_id": issue_id, "title": title });
+        log.record("linear", "update_issue_title", detail).await;
+        Ok(())
+    }
+
+    async fn update_issue_estimate(&self, issue_id: &str, estimate: i64) -> Result<(), AppError> {
+        let Some(log) = &self.log else {
+            return self.inner.update_issue_estimate(issue_id, estimate).await;
+        };
+        let detail = json!({ "issue_id": issue_id, "estimate": estimate });
+        log.record("linear", "update_issue_estimate", detail).await;
+        Ok(())
+    }
+
+    async fn add_issue_label(&self, issue_id: &str, label_id: &str) -> Result<(), AppError> {
+        let Some(log) = &self.log else {
+            return self.inner.add_issue_label(issue_id, label_id).await;
+        };
+        let detail = json!({ "issue_id": issue_id, "label_id": label_id });
+        log.record("linear", "add_issue_label", detail).await;
+        Ok(())
+    }
+
+    async fn create_customer_need(
+        &self,
+        issue_id: &str,
+        customer: &Customer,
+        body: &str,
+        url: &str,
+    ) -> Result<(), AppError> {
+        let Some(log) = &self.log else {
+            return self
+                .inner
+                .create_customer_need(issue_id, customer, body, url)
+                .await;
+        };
+        let detail = json!({
+            "issue_id": issue_id,
+            "customer_external_id": customer.external_id,
+            "customer_name": customer.name,
+            "body": body,
+            "url": url,
+        });
+        log.record("linear", "create_customer_need", detail).await;
+        Ok(())
+    }
+
+    async fn link_url(&self, issue_id: &str, url: &str, title: &str) -> Result<(), AppError> {
+        let Some(log) = &self.log else {
+            return self.inner.link_url(issue_id, url, title).await;
+        };
+        let detail = json!({ "issue_id": issue_id, "url": url, "title": title });
+        log.record("linear", "link_url", detail).await;
+        Ok(())
+    }
+
+    async fn request_file_upload(
+        &self,
+        team_or_issue_id: &str,
+        filename: &str,
+        content_type: &str,
+        size: u64,
+    ) -> Result<UploadFile, AppError> {
+        let Some(log) = &self.log else {
+            return self
+                .inner
+                .request_file_upload(team_or_issue_id, filename, content_type, size)
+                .await;
+        };
+        let detail = json!({
+            "team_or_issue_id": team_or_issue_id,
+            "filename": filename,
+            "content_type": content_type,
+            "size": size,
+        });
+        log.record("linear", "upload_file", detail).await;
+        Ok(UploadFile {
+            upload_url: String::new(),
+            asset_url: format!("https://uploads.linear.app/shadow/{filename}"),
+            headers: Vec::new(),
+        })
+    }
+
+    async fn upload_file_to_url(
+        &self,
+        upload: &UploadFile,
+        body: reqwest::Body,
+        size: u64,
+        content_type: &str,
+    ) -> Result<String, AppError> {
+        // Recorded when the upload was requested.
+        if self.log.is_some() {
+            return Ok(upload.asset_url.clone());
+        }
+        self.inner
+            .upload_file_to_url(upload, body, size, content_type)
+            .await
+    }
+
+    async fn download_attachment(&self, url: &str) -> Result<AttachmentDownload, AppError> {
+        self.inner.download_attachment(url).await
+    }
+
+    async fn download_linear_upload(
+        &self,
+        issue_id: &str,
+        url: &str,
+    ) -> Result<(Vec<u8>, String), AppError> {
+        self.inner.download_linear_upload(issue_id, url).await
+    }
+}
+
+#[async_trait]
+impl<T: DiscordPort> DiscordPort for Shadowed<T> {
+    async fn channel(&self, channel_id: ChannelId) -> Result<GuildChannel, AppError> {
+        self.inner.channel(channel_id).await
+    }
+
+    async fn roles(&self, guild_id: GuildId) -> Result<HashMap<RoleId, Role>, AppError> {
+        self.inner.roles(guild_id).await
+    }
+
+    async fn member(&self, guild_id: GuildId, user_id: UserId) -> Result<Member, AppError> {
+        self.inner.member(guild_id, user_id).await
+    }
+
+    async fn active_threads(&self, guild_id: GuildId) -> Result<Vec<GuildChannel>, AppError> {
+        self.inner.active_threads(guild_id).await
+    }
+
+    async fn first_message(&self, channel_id: ChannelId) -> Result<Option<Message>, AppError> {
+        self.inner.first_message(channel_id).await
+    }
+
+    async fn opening_messages(&self, channel_id: ChannelId) -> Result<Vec<Message>, AppError> {
+        self.inner.opening_messages(channel_id).await
+    }
+
+    async fn message(
+        &self,
+        channel_id: ChannelId,
+        message_id: MessageId,
+    ) -> Result<Message, AppError> {
+        self.inner.message(channel_id, message_id).await
+    }
+
+    async fn send_message(
+        &self,
+        channel_id: ChannelId,
+        message: CreateMessage,
+    ) -> Result<Message, AppError> {
+        let Some(log) = &self.log else {
+            return self.inner.send_message(channel_id, message).await;
+        };
+        let body = serde_json::to_value(&message)?;
+        let detail = json!({ "channel_id": channel_id, "message": body });
+        log.record("discord", "send_message", detail).await;
+        let id = MessageId::new(log.snowflake());
+        Ok(log.stand_in_message(channel_id, id, &body))
+    }
+
+    async fn send_dm(&self, user_id: UserId, message: CreateMessage) -> Result<(), AppError> {
+        let Some(log) = &self.log else {
+            return self.inner.send_dm(user_id, message).await;
+        };
+        let body = serde_json::to_value(&message)?;
+        let detail = json!({ "user_id": user_id, "message": body });
+        log.record("discord", "send_dm", detail).await;
+        Ok(())
+    }
+
+    async fn edit_message(
+        &self,
+        channel_id: ChannelId,
+        message_id: MessageId,
+        edit: EditMessage,
+    ) -> Result<Message, AppError> {
+        let Some(log) = &self.log else {
+            return self.inner.edit_message(channel_id, message_id, edit).await;
+        };
+        let body = serde_json::to_value(&edit)?;
+        let detail = json!({ "channel_id": channel_id, "message_id": message_id, "edit": body });
+        log.record("discord", "edit_message", detail).await;
+        Ok(log.stand_in_message(channel_id, message_id, &body))
+    }
+
+    async fn delete_message(
+        &self,
+        channel_id: ChannelId,
+        message_id: MessageId,
+    ) -> Result<(), AppError> {
+        let Some(log) = &self.log else {
+            return self.inner.delete_message(channel_id, message_id).await;
+        };
+        let detail = json!({ "channel_id": channel_id, "message_id": message_id });
+        log.record("discord", "delete_message", detail).await;
+        Ok(())
+    }
+
+    async fn pin_message(
+        &self,
+        channel_id: ChannelId,
+        message_id: MessageId,
+    ) -> Result<(), AppError> {
+        let Some(log) = &self.log else {
+            return self.inner.pin_message(channel_id, message_id).await;
+        };
+        let detail = json!({ "channel_id": channel_id, "message_id": message_id });
+        log.record("discord", "pin_message", detail).await;
+        Ok(())
+    }
+
+    /// In shadow mode, returns the thread as it is.
+    async fn edit_thread(
+        &self,
+        channel_id: ChannelId,
+        edit: EditThread<'_>,
+    ) -> Result<GuildChannel, AppError> {
+        let Some(log) = &self.log else {
+            return self.inner.edit_thread(channel_id, edit).await;
+        };
+        let detail = json!({ "channel_id": channel_id, "edit": serde_json::to_value(&edit)? });
+        log.record("discord", "edit_thread", detail).await;
+        self.inner.channel(channel_id).await
+    }
+
+    async fn create_forum_post(
+        &self,
+        forum_id: ChannelId,
+        post: CreateForumPost<'_>,
+    ) -> Result<GuildChannel, AppError> {
+        let Some(log) = &self.log else {
+            return self.inner.create_forum_post(forum_id, post).await;
+        };
+        let body = serde_json::to_value(&post)?;
+        let detail = json!({ "forum_id": forum_id, "post": body });
+        log.record("discord", "create_forum_post", detail).await;
+        let forum = self.inner.channel(forum_id).await?;
+        let mut thread = GuildChannel::default();
+        thread.id = ChannelId::new(log.snowflake());
+        thread.guild_id = forum.guild_id;
+        thread.parent_id = Some(forum_id);
+        thread.kind = ChannelType::PublicThread;
+        thread.name = body["name"].as_str().unwrap_or_default().to_string();
+        Ok(thread)
+    }
+
+    fn application_id(&self) -> Option<ApplicationId> {
+        self.inner.application_id()
+    }
+
+    async fn webhooks(&self, channel_id: ChannelId) -> Result<Vec<Webhook>, AppError> {
+        self.inner.webhooks(channel_id).await
+    }
+
+    async fn create_webhook(
+        &self,
+        channel_id: ChannelId,
+        builder: CreateWebhook<'_>,
+    ) -> Result<Webhook, AppError> {
+        let Some(log) = &self.log else {
+            return self.inner.create_webhook(channel_id, builder).await;
+        };
+        let body = serde_json::to_value(&builder)?;
+        let detail = json!({ "channel_id": channel_id, "webhook": body });
+        log.record("discord", "create_webhook", detail).await;
+        Ok(serde_json::from_value(json!({
+            "id": log.snowflake().to_string(),
+            "type": 1,
+            "channel_id": channel_id,
+            "name": body["name"],
+            "avatar": null,
+            "token": "shadow",
+            "application_id": self.inner.application_id(),
+        }))?)
+    }
+
+    async fn execute_webhook(
+        &self,
+        webhook: &Webhook,
+        thread: ChannelId,
+        builder: ExecuteWebhook,
+    ) -> Result<Message, AppError> {
+        let Some(log) = &self.log else {
+            return self.inner.execute_webhook(webhook, thread, builder).await;
+        };
+        let body = serde_json::to_value(&builder)?;
+        let detail = json!({ "webhook_id": webhook.id, "thread_id": thread, "message": body });
+        log.record("discord", "execute_webhook", detail).await;
+        let id = MessageId::new(log.snowflake());
+        Ok(log.stand_in_message(thread, id, &body))
+    }
+
+    async fn edit_webhook_message(
+        &self,
+        webhook: &Webhook,
+        thread: ChannelId,
+        message_id: MessageId,
+        edit: EditWebhookMessage,
+    ) -> Result<Message, AppError> {
+        let Some(log) = &self.log else {
+            return self
+                .inner
+                .edit_webhook_message(webhook, thread, message_id, edit)
+                .await;
+        };
+        let body = serde_json::to_value(&edit)?;
+        let detail = json!({
+            "webhook_id": webhook.id,
+            "thread_id": thread,
+            "message_id": message_id,
+            "edit": body,
+        });
+        log.record("discord", "edit_webhook_message", detail).await;
+        Ok(log.stand_in_message(thread, message_id, &body))
+    }
+
+    async fn delete_webhook_message(
+        &self,
+        webhook: &Webhook,
+        thread: ChannelId,
+        message_id: MessageId,
+    ) -> Result<(), AppError> {
+        let Some(log) = &self.log else {
+            return self
+                .inner
+                .delete_webhook_message(webhook, thread, message_id)
+                .await;
+        };
+        let detail = json!({
+            "webhook_id": webhook.id,
+            "thread_id": thread,
+            "message_id": message_id,
+        });
+        log.record("discord", "delete_webhook_message", detail)
+            .await;
+        Ok(())
+    }
+}
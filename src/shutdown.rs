@@ -0,0 +1,105 @@
+//! Graceful shutdown. Once a shutdown is requested (SIGTERM or SIGINT), new Discord events and
+//! webhook deliveries are turned away, while work already under way (an issue creation and its
+//! mapping, a poll pass and its cursor) runs to completion. Missed events are picked up after the
+//! restart by the thread reconcile and the saved poll cursors.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::Notify;
+
+#[derive(Clone, Default)]
+pub struct Shutdown(Arc<Inner>);
+
+#[derive(Default)]
+struct Inner {
+    requested: AtomicBool,
+    in_flight: AtomicUsize,
+    /// Woken when shutdown is requested.
+    on_request: Notify,
+    /// Woken when in-flight work finishes.
+    on_finish: Notify,
+}
+
+/// Marks a unit of work as in flight until dropped.
+#[must_use]
+pub struct Task(Arc<Inner>);
+
+impl Drop for Task {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::SeqCst);
+        self.0.on_finish.notify_waiters();
+    }
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn request(&self) {
+        if !self.0.requested.swap(true, Ordering::SeqCst) {
+            self.0.on_request.notify_waiters();
+        }
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.0.requested.load(Ordering::SeqCst)
+    }
+
+    /// Resolves once shutdown is requested, immediately if it already was.
+    pub async fn requested(&self) {
+        wait_until(&self.0.on_request, || self.is_requested()).await;
+    }
+
+    /// Start a unit of work that shutdown waits for, or `None` once shutdown is requested.
+    pub fn start_task(&self) -> Option<Task> {
+        // Counted before checking, so `drain` can't miss a task that starts as it's requested.
+        self.0.in_flight.fetch_add(1, Ordering::SeqCst);
+        let task = Task(self.0.clone());
+        (!self.is_requested()).then_some(task)
+    }
+
+    /// Tasks still in flight.
+    pub fn in_flight(&self) -> usize {
+        self.0.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Wait up to `timeout` for in-flight tasks to finish. Returns whether they all did.
+    pub async fn drain(&self, timeout: Duration) -> bool {
+        let finished = wait_until(&self.0.on_finish, || self.in_flight() == 0);
+        tokio::time::timeout(timeout, finished).await.is_ok()
+    }
+}
+
+async fn wait_until(notify: &Notify, done: impl Fn() -> bool) {
+    loop {
+        let notified = notify.notified();
+        tokio::pin!(notified);
+        // Registered before checking, so a wake-up between the check and the await isn't lost.
+        notified.as_mut().enable();
+        if done() {
+            return;
+        }
+        notified.await;
+    }
+}
+
+/// Resolves on the first SIGTERM or Ctrl-C.
+pub async fn signalled() -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut terminate = signal(SignalKind::terminate())?;
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => result,
+            _ = terminate.recv() => Ok(()),
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        tokio::signal::ctrl_c().await
+    }
+}
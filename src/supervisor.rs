@@ -0,0 +1,96 @@
+//! Restarting long-running background tasks that die. A task that panics or returns before
+//! shutdown is restarted after a delay that doubles with each consecutive restart, and each
+//! restart is logged and posted to the ops channel.
+
+use std::any::Any;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use serenity::all::{ChannelId, CreateMessage};
+use tracing::{error, info, warn};
+
+use crate::discord::port::DiscordPort;
+use crate::shutdown::Shutdown;
+
+/// Delay before the first restart; it doubles with each consecutive one.
+const MIN_RESTART_DELAY: Duration = Duration::from_secs(1);
+
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(300);
+
+/// A task that ran this long before dying is restarted after the shortest delay again.
+const HEALTHY_RUN: Duration = Duration::from_secs(600);
+
+/// Run the task made by `start` until shutdown, restarting it whenever it panics or returns
+/// early. Restarts are alerted in `ops_channel_id` when one is set.
+pub async fn supervise<F, Fut>(
+    name: &'static str,
+    discord: &impl DiscordPort,
+    ops_channel_id: Option<u64>,
+    shutdown: &Shutdown,
+    mut start: F,
+) where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let mut restarts: u32 = 0;
+    loop {
+        let started = Instant::now();
+        let outcome = tokio::spawn(start()).await;
+        if shutdown.is_requested() {
+            return;
+        }
+
+        if started.elapsed() >= HEALTHY_RUN {
+            restarts = 0;
+        }
+        restarts += 1;
+        let delay = restart_delay(restarts);
+        let reason = match outcome {
+            Ok(()) => "it returned unexpectedly".to_string(),
+            Err(e) if e.is_panic() => format!("it panicked: {}", panic_message(e.into_panic())),
+            Err(e) => e.to_string(),
+        };
+        error!(
+            task = name,
+            restarts,
+            delay_secs = delay.as_secs(),
+            reason,
+            "Background task stopped, restarting"
+        );
+        if let Some(channel_id) = ops_channel_id {
+            let content = format!(
+                "The {name} stopped ({reason}); restarting it in {}s (restart {restarts}).",
+                delay.as_secs()
+            );
+            let message = CreateMessage::new().content(content);
+            if let Err(e) = discord
+                .send_message(ChannelId::new(channel_id), message)
+                .await
+            {
+                warn!(task = name, error = %e, "Failed to alert ops channel");
+            }
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = shutdown.requested() => return,
+        }
+        info!(task = name, restarts, "Restarting background task");
+    }
+}
+
+/// Delay before the `restarts`th consecutive restart.
+fn restart_delay(restarts: u32) -> Duration {
+    let doublings = restarts.clamp(1, 10) - 1;
+    (MIN_RESTART_DELAY * 2u32.pow(doublings)).min(MAX_RESTART_DELAY)
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&'static str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "no message".to_string(),
+        },
+    }
+}
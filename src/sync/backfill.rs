@@ -1,18 +1,90 @@
-use serenity::all::{GuildId, Http};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+use serenity::all::{
+    ChannelId, EditThread, GuildChannel, GuildId, Http, LightMethod, Request, Route, ThreadsData,
+    Timestamp,
+};
 use sqlx::SqlitePool;
+use tokio::sync::watch;
 use tracing::{info, warn};
 
-use crate::config::Config;
+use crate::config::{ChannelConfig, Config};
 use crate::db;
+use crate::discord::port::DiscordPort;
 use crate::error::AppError;
-use crate::linear::client::LinearClient;
+use crate::linear::api::LinearApi;
+use crate::shadow::Shadowed;
+use crate::shutdown::Shutdown;
 use crate::sync::discord_to_linear::sync_discord_to_linear;
+use crate::sync::routing;
+
+/// Archived posts fetched per request; Discord's maximum.
+const ARCHIVED_PAGE_SIZE: u64 = 100;
+
+/// Channels being backfilled on demand, so a second request for one is turned away rather than
+/// racing the first to create the same issues.
+#[derive(Clone, Default)]
+pub struct RunningBackfills(Arc<Mutex<HashSet<u64>>>);
+
+/// Marks a channel's backfill as running until dropped.
+pub struct RunningBackfill {
+    running: RunningBackfills,
+    channel_id: u64,
+}
+
+impl RunningBackfills {
+    /// `None` if the channel is already being backfilled.
+    pub fn start(&self, channel_id: u64) -> Option<RunningBackfill> {
+        self.0
+            .lock()
+            .unwrap()
+            .insert(channel_id)
+            .then(|| RunningBackfill {
+                running: self.clone(),
+                channel_id,
+            })
+    }
+}
+
+impl Drop for RunningBackfill {
+    fn drop(&mut self) {
+        self.running.0.lock().unwrap().remove(&self.channel_id);
+    }
+}
+
+/// How far a channel's backfill has got.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BackfillProgress {
+    /// Posts to go through; zero until they've been listed.
+    pub total: usize,
+    pub processed: usize,
+    /// Posts an issue was created for.
+    pub synced: usize,
+    /// Posts that already had an issue.
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+/// An issue a backfill would create.
+#[derive(Debug, Clone)]
+pub struct PlannedIssue {
+    pub channel_id: u64,
+    pub thread_id: u64,
+    pub title: String,
+    pub team_id: String,
+    pub project_id: String,
+    pub label_ids: Vec<String>,
+    /// The channel asks for intake form answers first, so the backfill would prompt in the
+    /// post and the issue would be filed once the form is answered or times out.
+    pub intake_form: bool,
+}
 
 pub async fn run_backfill(
-    http: &Http,
+    discord: &Shadowed<Http>,
     pool: &SqlitePool,
     config: &Config,
-    linear: &LinearClient,
+    linear: &impl LinearApi,
 ) -> Result<(), AppError> {
     for channel_config in &config.channels {
         let channel_str = channel_config.discord_channel_id.to_string();
@@ -35,12 +107,22 @@ pub async fn run_backfill(
             "Starting backfill"
         );
 
-        match backfill_channel(http, pool, config, linear, channel_config.discord_channel_id, channel_config.guild_id).await {
-            Ok(count) => {
+        match backfill_channel(
+            discord,
+            pool,
+            config,
+            linear,
+            channel_config.discord_channel_id,
+            None,
+            None,
+        )
+        .await
+        {
+            Ok(progress) => {
                 db::upsert_backfill_state(pool, &channel_str, true, None).await?;
                 info!(
                     channel_id = %channel_str,
-                    count,
+                    count = progress.synced,
                     "Backfill completed"
                 );
             }
@@ -57,15 +139,102 @@ pub async fn run_backfill(
     Ok(())
 }
 
-async fn backfill_channel(
+/// Walk the posts `run_backfill` would go through and list the issues it would create, without
+/// writing to Linear, Discord, or the database. With `force`, channels whose backfill completed
+/// are walked from the start, as after `backfill --force`.
+pub async fn plan_backfill(
     http: &Http,
     pool: &SqlitePool,
     config: &Config,
-    linear: &LinearClient,
+    force: bool,
+) -> Result<Vec<PlannedIssue>, AppError> {
+    let mut planned = Vec::new();
+    for channel_config in &config.channels {
+        let channel_str = channel_config.discord_channel_id.to_string();
+        let state = if force {
+            None
+        } else {
+            db::get_backfill_state(pool, &channel_str).await?
+        };
+        if state.as_ref().is_some_and(|s| s.completed) {
+            info!(channel_id = %channel_str, "Backfill already completed, skipping");
+            continue;
+        }
+        let resume_after = state.and_then(|s| s.last_thread_id);
+
+        let threads = pending_threads(http, channel_config, resume_after.as_deref()).await?;
+        for thread in &threads {
+            if db::get_mapping_by_discord_thread(pool, &thread.id.to_string())
+                .await?
+                .is_some()
+            {
+                continue;
+            }
+            // Rules on the post's body or author's roles aren't checked for the plan.
+            let route = routing::route(channel_config, thread, "", &[]);
+            planned.push(PlannedIssue {
+                channel_id: channel_config.discord_channel_id,
+                thread_id: thread.id.get(),
+                title: thread.name.clone(),
+                team_id: route.team_id.to_string(),
+                project_id: route.project_id.to_string(),
+                label_ids: route.label_ids,
+                intake_form: channel_config.intake_form,
+            });
+        }
+    }
+    Ok(planned)
+}
+
+/// Backfill one channel from the start, whether or not it was backfilled before, publishing
+/// progress as it goes. Posts that already have issues are skipped. Stops between posts once
+/// shutdown is requested; the channel is then left incomplete, to be resumed on the next start.
+pub async fn rerun_channel_backfill(
+    discord: &Shadowed<Http>,
+    pool: &SqlitePool,
+    config: &Config,
+    linear: &impl LinearApi,
+    channel_id: u64,
+    progress: &watch::Sender<BackfillProgress>,
+    shutdown: &Shutdown,
+) -> Result<BackfillProgress, AppError> {
+    let channel_str = channel_id.to_string();
+    db::upsert_backfill_state(pool, &channel_str, false, None).await?;
+
+    let done = backfill_channel(
+        discord,
+        pool,
+        config,
+        linear,
+        channel_id,
+        Some(progress),
+        Some(shutdown),
+    )
+    .await?;
+    if done.processed == done.total {
+        db::upsert_backfill_state(pool, &channel_str, true, None).await?;
+    }
+    info!(
+        channel_id = %channel_str,
+        count = done.synced,
+        failed = done.failed,
+        "Backfill rerun finished"
+    );
+    Ok(done)
+}
+
+async fn backfill_channel(
+    discord: &Shadowed<Http>,
+    pool: &SqlitePool,
+    config: &Config,
+    linear: &impl LinearApi,
     channel_id: u64,
-    guild_id: u64,
-) -> Result<usize, AppError> {
-    let guild = GuildId::new(guild_id);
+    progress: Option<&watch::Sender<BackfillProgress>>,
+    shutdown: Option<&Shutdown>,
+) -> Result<BackfillProgress, AppError> {
+    let channel_config = config
+        .channel_config(channel_id)
+        .ok_or_else(|| AppError::Internal(format!("No config for channel {channel_id}")))?;
     let channel_str = channel_id.to_string();
 
     // Get resume cursor if we crashed mid-backfill
@@ -75,53 +244,56 @@ async fn backfill_channel(
         None
     };
 
-    // Fetch active threads in the guild
-    let active_threads = guild.get_active_threads(http).await?;
-
-    // Filter to threads in the target forum channel
-    let mut threads: Vec<_> = active_threads
-        .threads
-        .into_iter()
-        .filter(|t| {
-            t.parent_id
-                .map(|p| p.get() == channel_id)
-                .unwrap_or(false)
-        })
-        .collect();
-
-    // Sort by ID (chronological order)
-    threads.sort_by_key(|t| t.id);
+    let threads = pending_threads(discord, channel_config, resume_after.as_deref()).await?;
 
-    // Skip past resume cursor
-    if let Some(ref cursor) = resume_after {
-        let cursor_id: u64 = cursor.parse().unwrap_or(0);
-        threads.retain(|t| t.id.get() > cursor_id);
-    }
-
-    let channel_config = config
-        .channel_config(channel_id)
-        .ok_or_else(|| AppError::Internal(format!("No config for channel {channel_id}")))?;
-
-    let mut synced = 0;
+    let mut counts = BackfillProgress {
+        total: threads.len(),
+        ..Default::default()
+    };
+    let publish = |counts: &BackfillProgress| {
+        if let Some(progress) = progress {
+            progress.send_replace(*counts);
+        }
+    };
+    publish(&counts);
 
     for thread in &threads {
         let thread_id = thread.id.to_string();
+        // Held while the post is synced, so a shutdown doesn't cut an issue off from its mapping.
+        let _task = match shutdown.map(Shutdown::start_task) {
+            Some(None) => break,
+            Some(task) => task,
+            None => None,
+        };
+        counts.processed += 1;
 
         // Skip already-synced threads
         if db::get_mapping_by_discord_thread(pool, &thread_id)
             .await?
             .is_some()
         {
+            counts.skipped += 1;
+            publish(&counts);
             continue;
         }
 
-        match sync_discord_to_linear(http, pool, channel_config, linear, thread).await {
+        match sync_discord_to_linear(discord, pool, channel_config, linear, thread).await {
             Ok(()) => {
-                synced += 1;
+                counts.synced += 1;
                 // Persist cursor for crash resilience
                 db::upsert_backfill_state(pool, &channel_str, false, Some(&thread_id)).await?;
+                // Posting the issue link reopened the thread; put it back.
+                if thread.thread_metadata.is_some_and(|m| m.archived) {
+                    if let Err(e) = discord
+                        .edit_thread(thread.id, EditThread::new().archived(true))
+                        .await
+                    {
+                        warn!(thread_id, error = %e, "Failed to re-archive backfilled thread");
+                    }
+                }
             }
             Err(e) => {
+                counts.failed += 1;
                 warn!(
                     thread_id,
                     thread_name = %thread.name,
@@ -130,10 +302,87 @@ async fn backfill_channel(
                 );
             }
         }
+        publish(&counts);
 
         // Rate limit: wait between syncs to avoid Discord rate limits
         tokio::time::sleep(std::time::Duration::from_millis(500)).await;
     }
 
-    Ok(synced)
+    Ok(counts)
+}
+
+/// Posts in the channel's forum in chronological order, after `resume_after` if given.
+async fn pending_threads(
+    http: &Http,
+    channel_config: &ChannelConfig,
+    resume_after: Option<&str>,
+) -> Result<Vec<GuildChannel>, AppError> {
+    let guild = GuildId::new(channel_config.guild_id);
+    let channel_id = ChannelId::new(channel_config.discord_channel_id);
+    let mut threads = forum_threads(http, guild, channel_id).await?;
+
+    // Sort by ID (chronological order)
+    threads.sort_by_key(|t| t.id);
+    threads.dedup_by_key(|t| t.id);
+
+    // Skip past resume cursor
+    if let Some(cursor) = resume_after {
+        let cursor_id: u64 = cursor.parse().unwrap_or(0);
+        threads.retain(|t| t.id.get() > cursor_id);
+    }
+
+    Ok(threads)
+}
+
+/// Every post in a forum: the guild's active threads in it, then its archived ones.
+async fn forum_threads(
+    http: &Http,
+    guild: GuildId,
+    channel_id: ChannelId,
+) -> Result<Vec<GuildChannel>, AppError> {
+    let mut threads: Vec<_> = guild
+        .get_active_threads(http)
+        .await?
+        .threads
+        .into_iter()
+        .filter(|t| t.parent_id == Some(channel_id))
+        .collect();
+
+    // Pages run from the most recently archived back, each continuing before the last one's
+    // archive time.
+    let mut before = None;
+    loop {
+        let page = archived_threads_page(http, channel_id, before).await?;
+        before = page
+            .threads
+            .last()
+            .and_then(|t| t.thread_metadata)
+            .and_then(|m| m.archive_timestamp);
+        threads.extend(page.threads);
+        if !page.has_more || before.is_none() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    }
+
+    Ok(threads)
+}
+
+/// One page of a channel's public archived threads. serenity's `get_archived_public_threads`
+/// sends `before` as a number, but Discord expects an ISO 8601 timestamp.
+async fn archived_threads_page(
+    http: &Http,
+    channel_id: ChannelId,
+    before: Option<Timestamp>,
+) -> Result<ThreadsData, AppError> {
+    let mut params = vec![("limit", ARCHIVED_PAGE_SIZE.to_string())];
+    if let Some(before) = before {
+        params.push(("before", before.to_string()));
+    }
+    let request = Request::new(
+        Route::ChannelArchivedPublicThreads { channel_id },
+        LightMethod::Get,
+    )
+    .params(Some(params));
+    Ok(http.fire(request).await?)
 }
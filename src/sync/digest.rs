@@ -0,0 +1,146 @@
+//! Debounced thread notifications.
+//!
+//! With `DIGEST_WINDOW_SECS` set, status, assignee, and reminder notices are queued instead of
+//! posted. Once a thread has gone quiet for the window, everything queued for it goes out as a
+//! single message, so an issue bouncing between states doesn't post one message per hop.
+
+use serde_json::json;
+use serenity::all::{ChannelId, CreateEmbed, CreateMessage};
+use sqlx::SqlitePool;
+use tracing::{error, info, warn};
+
+use crate::config::Config;
+use crate::db;
+use crate::discord::port::{is_not_found, DiscordPort};
+use crate::error::AppError;
+use crate::sync::linear_to_discord::split_for_discord;
+use crate::sync::thread::channel_config_for_thread;
+use crate::templates::{self, Notification};
+
+/// How often queued notifications are checked for threads that have settled.
+const DIGEST_FLUSH_INTERVAL_SECS: u64 = 5;
+
+/// Post `body` in `thread`, or queue it for the thread's next digest when debouncing is on.
+pub async fn notify(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    config: &Config,
+    thread: ChannelId,
+    body: &str,
+) -> Result<(), AppError> {
+    if config.digest_window_secs == 0 {
+        discord
+            .send_message(thread, CreateMessage::new().content(body))
+            .await?;
+    } else {
+        db::insert_pending_notification(pool, &thread.to_string(), body).await?;
+    }
+    Ok(())
+}
+
+/// Like [`notify`], with `embed` under `body` when it's posted right away. Digests are plain
+/// text, so a queued notice goes without it.
+pub async fn notify_with_embed(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    config: &Config,
+    thread: ChannelId,
+    body: &str,
+    embed: CreateEmbed,
+) -> Result<(), AppError> {
+    if config.digest_window_secs == 0 {
+        discord
+            .send_message(thread, CreateMessage::new().content(body).embed(embed))
+            .await?;
+        Ok(())
+    } else {
+        notify(discord, pool, config, thread, body).await
+    }
+}
+
+/// Post digests for threads whose notifications have settled. Only runs when
+/// `digest_window_secs` is non-zero.
+pub async fn run_digests(discord: impl DiscordPort, pool: SqlitePool, config: Config) {
+    let window_secs = config.digest_window_secs;
+    info!(window_secs, "Starting notification digest task");
+
+    loop {
+        tokio::time::sleep(std::time::Duration::from_secs(DIGEST_FLUSH_INTERVAL_SECS)).await;
+
+        if let Err(e) = flush_digests(&discord, &pool, &config).await {
+            error!(error = %e, "Notification digest pass failed");
+        }
+    }
+}
+
+async fn flush_digests(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    config: &Config,
+) -> Result<(), AppError> {
+    let window_secs = config.digest_window_secs;
+    for thread_id in db::get_settled_notification_threads(pool, window_secs).await? {
+        let pending = db::get_pending_notifications(pool, &thread_id).await?;
+        let Some(&(max_id, _)) = pending.last() else {
+            continue;
+        };
+        let channel = match thread_id.parse() {
+            Ok(id) => ChannelId::new(id),
+            Err(_) => {
+                db::delete_pending_notifications(pool, &thread_id, max_id).await?;
+                continue;
+            }
+        };
+
+        let notices = pending
+            .iter()
+            .map(|(_, body)| body.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let overrides = match channel_config_for_thread(discord, config, channel).await {
+            Some(c) => &c.message_templates,
+            None => &config.message_templates,
+        };
+        let digest = templates::notification(
+            overrides,
+            Notification::Digest,
+            &json!({ "notices": notices, "count": pending.len() }),
+        );
+        match post_digest(discord, channel, &digest).await {
+            Ok(()) => {
+                info!(
+                    thread_id,
+                    notifications = pending.len(),
+                    "Posted notification digest"
+                );
+            }
+            // The thread is gone; there is nowhere left to post these.
+            Err(e) if is_not_found(&e) => {
+                warn!(
+                    thread_id,
+                    "Thread no longer exists, dropping queued notifications"
+                );
+            }
+            Err(e) => {
+                // Left queued; the next pass retries the digest.
+                warn!(thread_id, error = %e, "Failed to post notification digest");
+                continue;
+            }
+        }
+        db::delete_pending_notifications(pool, &thread_id, max_id).await?;
+    }
+    Ok(())
+}
+
+async fn post_digest(
+    discord: &impl DiscordPort,
+    channel: ChannelId,
+    digest: &str,
+) -> Result<(), AppError> {
+    for chunk in split_for_discord(digest) {
+        discord
+            .send_message(channel, CreateMessage::new().content(chunk))
+            .await?;
+    }
+    Ok(())
+}
@@ -1,17 +1,35 @@
-use serenity::all::{ChannelId, GuildChannel, Http};
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use serenity::all::{
+    Attachment, ChannelId, CreateMessage, GuildChannel, Http, Message, MessageUpdateEvent, RoleId,
+    UserId,
+};
 use sqlx::SqlitePool;
-use tracing::{info, warn};
+use tracing::{info, info_span, instrument, warn, Instrument, Span};
+use uuid::Uuid;
 
-use crate::config::ChannelConfig;
+use crate::config::{ChannelConfig, Config, ThreadDeleteAction};
 use crate::db;
+use crate::discord::port::{self, DiscordPort};
+use crate::discord::{approval, duplicates, intake, triage};
 use crate::error::AppError;
-use crate::linear::client::LinearClient;
+use crate::format::{self, MentionNames};
+use crate::linear::api::LinearApi;
+use crate::linear::client::{CommentAuthor, Customer, LinearIssue};
+use crate::sync::outbox::{self, Operation};
+use crate::sync::routing::{self, IssueRoute};
+use crate::sync::status_embed::post_status_embed;
+use crate::sync::thread::{fetch_thread, thread_name_for_title};
+use crate::sync::{filter, repost};
+use crate::templates::{self, DescriptionVars, Notification};
 
 pub async fn sync_discord_to_linear(
-    http: &Http,
+    discord: &impl DiscordPort,
     pool: &SqlitePool,
     channel_config: &ChannelConfig,
-    linear: &LinearClient,
+    linear: &impl LinearApi,
     thread: &GuildChannel,
 ) -> Result<(), AppError> {
     let thread_id = thread.id.to_string();
@@ -25,109 +43,943 @@ pub async fn sync_discord_to_linear(
         return Ok(());
     }
 
-    let parent_id = thread
-        .parent_id
-        .ok_or_else(|| AppError::Internal("Thread has no parent channel".into()))?;
+    // Posts the bot opens for issues labeled in Linear are mapped as soon as they're created.
+    let bot_id = discord.application_id().map(|id| id.get());
+    if bot_id.is_some() && thread.owner_id.map(|id| id.get()) == bot_id {
+        info!(thread_id, "Thread was opened by the bot, skipping");
+        return Ok(());
+    }
+
+    // Channels restricted to certain posters leave other posts for moderators to file by hand.
+    if !channel_config.poster_role_ids.is_empty()
+        && !poster_allowed(discord, channel_config, thread).await?
+    {
+        info!(thread_id, "Thread author lacks a poster role, skipping");
+        return Ok(());
+    }
 
     // Fetch first message with retry — race condition where message isn't available yet
-    let first_message = fetch_first_message_with_retry(http, thread.id).await;
+    let first_message = fetch_first_message_with_retry(discord, thread.id).await;
 
-    let message_body = match &first_message {
-        Some(msg) => msg.content.clone(),
-        None => "(No message content available)".to_string(),
-    };
+    // A post that was deleted and posted again goes back to its issue.
+    if let Some(message) = &first_message {
+        if repost::handle_repost(discord, pool, channel_config, linear, thread, message).await? {
+            return Ok(());
+        }
+    }
 
-    // Build label list: primary label + any mapped forum tags
-    let mut label_ids = vec![channel_config.linear_label_id.clone()];
+    // Claimed before waiting for follow-ups, so the thread reconcile can't file the post in the
+    // meantime. Checked for a mapping again in case a claimant just finished.
+    let timeout_secs = channel_config.description_window_secs.max(0) + THREAD_CLAIM_TIMEOUT_SECS;
+    if !db::claim_thread(pool, &thread_id, timeout_secs).await? {
+        info!(thread_id, "Thread is already being filed, skipping");
+        return Ok(());
+    }
+    let result = file_claimed_thread(
+        discord,
+        pool,
+        channel_config,
+        linear,
+        thread,
+        first_message.as_ref(),
+    )
+    .await;
+    db::release_thread_claim(pool, &thread_id).await?;
+    result
+}
 
-    for tag_id in &thread.applied_tags {
-        let tag_str = tag_id.to_string();
-        if let Some(linear_label_id) = channel_config.tag_label_map.get(&tag_str) {
-            label_ids.push(linear_label_id.clone());
+/// The rest of [`sync_discord_to_linear`], run while holding the thread's claim.
+async fn file_claimed_thread(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    channel_config: &ChannelConfig,
+    linear: &impl LinearApi,
+    thread: &GuildChannel,
+    first_message: Option<&Message>,
+) -> Result<(), AppError> {
+    let thread_id = thread.id.to_string();
+    if db::get_mapping_by_discord_thread(pool, &thread_id)
+        .await?
+        .is_some()
+    {
+        return Ok(());
+    }
+
+    // Reporters who split their post across several messages get a moment to finish.
+    wait_for_follow_ups(discord, channel_config, thread, first_message).await;
+
+    // Spam and low-quality posts get a reply instead of an issue. Judged on everything the
+    // author opened with, not just the first message.
+    let messages = opening_messages(discord, channel_config, thread, first_message).await;
+    if filter::filter_post(discord, pool, channel_config, thread, &messages).await? {
+        return Ok(());
+    }
+
+    // Channels with an approval role hold new posts until a member with the role approves them.
+    if let Some(role_id) = channel_config.approval_role_id {
+        match db::get_thread_approval(pool, &thread_id).await? {
+            None => {
+                approval::prompt(discord, pool, role_id, thread).await?;
+                return Ok(());
+            }
+            Some(pending) if pending.approved_by_discord_user_id.is_none() => return Ok(()),
+            Some(_) => {}
         }
     }
 
-    // Upload attachments (best-effort)
-    let mut attachment_links = Vec::new();
-    if let Some(msg) = &first_message {
-        for attachment in &msg.attachments {
-            match upload_attachment(linear, &attachment.url, &attachment.filename).await {
-                Ok(asset_url) => {
-                    attachment_links.push(format!("![{}]({})", attachment.filename, asset_url));
+    // Channels suggesting duplicates wait for the author to pick a similar issue or create
+    // anyway; the issue is created on "Create anyway", or here once the prompt times out.
+    if channel_config.suggest_duplicates {
+        match db::get_duplicate_check(pool, &thread_id).await? {
+            None => {
+                if duplicates::suggest(discord, pool, channel_config, linear, thread).await? {
+                    return Ok(());
+                }
+            }
+            Some(check) => match check.outcome.as_deref() {
+                // Being linked to the chosen issue instead.
+                Some("link") => return Ok(()),
+                Some(_) => {}
+                None if check.age_secs < channel_config.duplicate_timeout_secs => {
+                    return Ok(());
                 }
-                Err(e) => {
-                    warn!(
-                        filename = %attachment.filename,
-                        error = %e,
-                        "Failed to upload attachment, skipping"
-                    );
+                None => {
+                    if !db::resolve_duplicate_check(pool, &thread_id, "timeout").await? {
+                        return Ok(());
+                    }
+                    info!(thread_id, "Duplicate prompt timed out, creating the issue");
+                }
+            },
+        }
+    }
+
+    // Channels with an intake form wait for the author's answers; the issue is created when
+    // the form is submitted or skipped, or here once the prompt times out.
+    if channel_config.intake_form {
+        match db::get_pending_intake(pool, &thread_id).await? {
+            None => {
+                intake::prompt(discord, pool, thread).await?;
+                return Ok(());
+            }
+            Some(pending) if pending.age_secs < channel_config.intake_timeout_secs => {
+                return Ok(());
+            }
+            Some(_) => {
+                if !db::claim_pending_intake(pool, &thread_id).await? {
+                    return Ok(());
                 }
+                info!(thread_id, "Intake form timed out, filing without details");
             }
         }
     }
 
-    // Build description
-    let thread_url = format!(
-        "https://discord.com/channels/{}/{}/{}",
-        channel_config.guild_id, parent_id, thread.id
+    let issue = match create_issue_for_thread(
+        discord,
+        pool,
+        channel_config,
+        linear,
+        thread,
+        first_message,
+        None,
+    )
+    .await
+    {
+        Ok(issue) => issue,
+        Err(e) => {
+            // Let the retry claim the timed-out intake again.
+            if channel_config.intake_form {
+                db::release_pending_intake(pool, &thread_id).await?;
+            }
+            return Err(e);
+        }
+    };
+    if channel_config.intake_form {
+        db::delete_pending_intake(pool, &thread_id).await?;
+    }
+    let author = thread.owner_id.map(|id| id.to_string());
+    db::insert_audit_entry(
+        pool,
+        &thread_id,
+        &issue.identifier,
+        "issue_created",
+        author.as_deref(),
+        &issue.title,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Push a mapped thread's new name to the Linear issue title. Thread updates whose name still
+/// matches the last title we applied (our own renames, archive or tag changes) are ignored.
+pub async fn sync_thread_rename(
+    pool: &SqlitePool,
+    linear: &impl LinearApi,
+    thread: &GuildChannel,
+) -> Result<(), AppError> {
+    let mapping = match db::get_mapping_by_discord_thread(pool, &thread.id.to_string()).await? {
+        Some(m) => m,
+        None => return Ok(()),
+    };
+
+    let applied = match db::get_applied_title(pool, &mapping.linear_issue_id).await? {
+        Some(t) => t,
+        None => {
+            // No title recorded for this mapping yet; adopt the current name as the baseline.
+            db::set_synced_title(pool, &mapping.linear_issue_id, &thread.name).await?;
+            return Ok(());
+        }
+    };
+    if thread.name == thread_name_for_title(&applied) {
+        return Ok(());
+    }
+
+    linear
+        .update_issue_title(&mapping.linear_issue_id, &thread.name)
+        .await?;
+    db::set_synced_title(pool, &mapping.linear_issue_id, &thread.name).await?;
+
+    info!(
+        thread_id = %thread.id,
+        identifier = %mapping.linear_identifier,
+        title = %thread.name,
+        "Synced thread rename to Linear title"
+    );
+
+    Ok(())
+}
+
+/// Handle deletion of a mapped thread: comment on (or cancel, per channel config) the Linear
+/// issue, then tombstone the mapping so the poller stops trying to post into the thread.
+pub async fn sync_thread_delete(
+    pool: &SqlitePool,
+    config: &Config,
+    linear: &impl LinearApi,
+    thread_id: ChannelId,
+    parent_id: ChannelId,
+) -> Result<(), AppError> {
+    let mapping = match db::get_mapping_by_discord_thread(pool, &thread_id.to_string()).await? {
+        Some(m) => m,
+        None => return Ok(()),
+    };
+
+    let channel_config = config.channel_config(parent_id.get());
+    let action = channel_config
+        .map(|c| c.on_thread_delete)
+        .unwrap_or_default();
+
+    let note = "The Discord thread for this issue was deleted.";
+    if let Err(e) = linear
+        .create_comment(None, &mapping.linear_issue_id, note, None)
+        .await
+    {
+        warn!(
+            identifier = %mapping.linear_identifier,
+            error = %e,
+            "Failed to comment on deleted thread's issue"
+        );
+    }
+
+    // Tombstone first: the comment is already posted, so a failed cancel must not leave the
+    // mapping pointing at the deleted thread for the next attempt to comment again.
+    db::tombstone_mapping(pool, &mapping, "thread_deleted", None).await?;
+
+    if let (ThreadDeleteAction::Cancel, Some(c)) = (action, channel_config) {
+        if let Err(e) = cancel_issue(linear, &c.linear_team_id, &mapping.linear_issue_id).await {
+            warn!(
+                identifier = %mapping.linear_identifier,
+                error = %e,
+                "Failed to cancel deleted thread's issue"
+            );
+        }
+    }
+
+    info!(
+        thread_id = %thread_id,
+        identifier = %mapping.linear_identifier,
+        action = ?action,
+        "Handled deletion of mapped thread"
     );
 
-    let mut description = format!("{message_body}\n\n---\n[Discord Thread]({thread_url})");
+    Ok(())
+}
+
+/// Move an issue to its team's canceled state, if the team has one.
+async fn cancel_issue(
+    linear: &impl LinearApi,
+    team_id: &str,
+    issue_id: &str,
+) -> Result<(), AppError> {
+    match linear.get_canceled_state_id(team_id).await? {
+        Some(state_id) => linear.update_issue_state(issue_id, &state_id).await,
+        None => {
+            warn!(team_id, "Team has no canceled state; leaving issue open");
+            Ok(())
+        }
+    }
+}
+
+/// Mirror a reply in a mapped thread as a Linear comment, including its attachments, when the
+/// thread's channel has `sync_replies` enabled. The comment is recorded as synced so the comment
+/// poller doesn't post it back into the thread.
+pub async fn sync_reply_to_linear(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    config: &Config,
+    linear: &impl LinearApi,
+    msg: &Message,
+) -> Result<(), AppError> {
+    // The starter message is the issue description, not a comment; webhook posts are our own
+    // relayed Linear comments.
+    if msg.author.bot || msg.webhook_id.is_some() || msg.id.get() == msg.channel_id.get() {
+        return Ok(());
+    }
+
+    let thread_id = msg.channel_id.to_string();
+    let mapping = match db::get_mapping_by_discord_thread(pool, &thread_id).await? {
+        Some(m) => m,
+        None => return Ok(()),
+    };
+
+    let thread = fetch_thread(discord, msg.channel_id).await?;
+    let Some(channel_config) = thread
+        .parent_id
+        .and_then(|p| config.channel_config(p.get()))
+        .filter(|c| c.sync_replies)
+    else {
+        return Ok(());
+    };
+
+    let attachment_links =
+        upload_attachments(linear, &mapping.linear_issue_id, &msg.attachments).await;
+    if msg.content.trim().is_empty() && attachment_links.is_empty() {
+        return Ok(());
+    }
+
+    let display_name = msg
+        .member
+        .as_ref()
+        .and_then(|m| m.nick.clone())
+        .unwrap_or_else(|| msg.author.display_name().to_string());
+    let names =
+        MentionNames::resolve(discord, &msg.content, Some(thread.guild_id), &msg.mentions).await;
+    let content = format::discord_to_linear(&msg.content, &names);
+    let overrides = &channel_config.message_templates;
+    let mut body = reply_text(pool, overrides, msg.author.id, &display_name, &content).await?;
+    let author = reply_author(
+        pool,
+        config,
+        msg.author.id,
+        &display_name,
+        msg.author.face(),
+    )
+    .await?;
     if !attachment_links.is_empty() {
-        description.push_str("\n\n**Attachments:**\n");
-        description.push_str(&attachment_links.join("\n"));
+        body.push_str("\n\n");
+        body.push_str(&attachment_links.join("\n"));
     }
 
-    // Create Linear issue in the configured team
+    let comment_id = match create_linear_comment(
+        pool,
+        linear,
+        &mapping.linear_issue_id,
+        &body,
+        author.as_ref(),
+        &msg.id.to_string(),
+    )
+    .await
+    {
+        Ok(id) => id,
+        Err(e) => {
+            let operation = Operation::CreateComment {
+                issue_id: mapping.linear_issue_id.clone(),
+                body,
+                author,
+                discord_message_id: msg.id.to_string(),
+            };
+            outbox::enqueue(pool, &operation, &e).await;
+            return Err(e);
+        }
+    };
+    db::insert_audit_entry(
+        pool,
+        &thread_id,
+        &mapping.linear_identifier,
+        "comment_posted",
+        Some(&msg.author.id.to_string()),
+        &comment_id,
+    )
+    .await?;
+
+    info!(
+        thread_id = %msg.channel_id,
+        identifier = %mapping.linear_identifier,
+        comment_id,
+        attachments = attachment_links.len(),
+        "Synced Discord reply to Linear comment"
+    );
+
+    Ok(())
+}
+
+/// Create a Linear comment mirroring `discord_message_id` without it being relayed back by the
+/// comment poller. The comment's ID is generated here and recorded in `synced_comments` before
+/// the comment exists, so a poll that lands mid-request already sees it as synced.
+pub async fn create_linear_comment(
+    pool: &SqlitePool,
+    linear: &impl LinearApi,
+    linear_issue_id: &str,
+    body: &str,
+    author: Option<&CommentAuthor>,
+    discord_message_id: &str,
+) -> Result<String, AppError> {
+    let comment_id = Uuid::new_v4().to_string();
+    db::insert_synced_comment(pool, &comment_id, linear_issue_id, discord_message_id).await?;
+    if let Err(e) = linear
+        .create_comment(Some(&comment_id), linear_issue_id, body, author)
+        .await
+    {
+        db::delete_synced_comment(pool, &comment_id).await?;
+        return Err(e);
+    }
+    Ok(comment_id)
+}
+
+/// The Linear comment for a reply written in Discord, under an attribution line. Users who ran
+/// `/linear connect` are named by their Linear account, since comments are posted with the bot's
+/// API key unless [`reply_author`] names someone.
+pub async fn reply_text(
+    pool: &SqlitePool,
+    overrides: &HashMap<String, String>,
+    user_id: UserId,
+    display_name: &str,
+    content: &str,
+) -> Result<String, AppError> {
+    let linear_name = db::get_user_link_by_discord_user(pool, &user_id.to_string())
+        .await?
+        .map(|link| link.linear_name)
+        .unwrap_or_default();
+    Ok(templates::notification(
+        overrides,
+        Notification::Reply,
+        &json!({ "author": display_name, "linear_name": linear_name, "content": content }),
+    ))
+}
+
+/// Who a reply written in Discord is posted as in Linear, with `LINEAR_COMMENT_AS_AUTHOR`: the
+/// Linear name of users who ran `/linear connect`, or else their Discord name, with their
+/// Discord avatar. `None` posts as the API key's user.
+pub async fn reply_author(
+    pool: &SqlitePool,
+    config: &Config,
+    user_id: UserId,
+    display_name: &str,
+    avatar_url: String,
+) -> Result<Option<CommentAuthor>, AppError> {
+    if !config.linear_comment_as_author {
+        return Ok(None);
+    }
+    let name = db::get_user_link_by_discord_user(pool, &user_id.to_string())
+        .await?
+        .map_or_else(|| display_name.to_string(), |link| link.linear_name);
+    Ok(Some(CommentAuthor {
+        name,
+        avatar_url: Some(avatar_url),
+    }))
+}
+
+/// Answers collected by the intake form.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct IntakeAnswers {
+    pub repro_steps: String,
+    pub severity: String,
+    pub affected_version: String,
+}
+
+const INTAKE_LABELS: [&str; 3] = ["Steps to reproduce", "Severity", "Affected version"];
+
+/// Start of the footer appended after the message body in every issue description.
+const DESCRIPTION_FOOTER: &str = "\n\n---\n[Discord Thread](";
+
+impl IntakeAnswers {
+    /// Markdown section appended to the issue description; empty answers are omitted.
+    fn render(&self) -> String {
+        INTAKE_LABELS
+            .iter()
+            .zip([&self.repro_steps, &self.severity, &self.affected_version])
+            .filter(|(_, value)| !value.trim().is_empty())
+            .map(|(label, value)| format!("**{label}:**\n{}", value.trim()))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+/// Split an issue description into the Discord message body and everything the bot appended
+/// after it (intake answers, thread link, attachments). `None` if the footer is missing, e.g.
+/// because the description was rewritten in Linear.
+fn split_description(description: &str) -> Option<(&str, &str)> {
+    let footer = description.find(DESCRIPTION_FOOTER)?;
+    let start = INTAKE_LABELS
+        .iter()
+        .filter_map(|label| description[..footer].find(&format!("\n\n**{label}:**\n")))
+        .min()
+        .unwrap_or(footer);
+    Some(description.split_at(start))
+}
+
+/// Push an edited thread starter message into the Linear issue description, keeping the
+/// sections the bot appended after the body. The starter message shares its ID with the thread,
+/// both for forum posts and for threads started from a channel message.
+pub async fn sync_starter_message_edit(
+    http: &Http,
+    pool: &SqlitePool,
+    linear: &impl LinearApi,
+    event: &MessageUpdateEvent,
+    content: &str,
+) -> Result<(), AppError> {
+    let mapping = match db::get_mapping_by_discord_thread(pool, &event.id.to_string()).await? {
+        Some(m) => m,
+        None => return Ok(()),
+    };
+
+    let mentioned = event.mentions.as_deref().unwrap_or_default();
+    let names = MentionNames::resolve(http, content, event.guild_id, mentioned).await;
+    let content = format::discord_to_linear(content, &names);
+
+    let description = linear
+        .get_issue_description(&mapping.linear_issue_id)
+        .await?;
+    let (body, rest) = match split_description(&description) {
+        Some(parts) => parts,
+        None => {
+            warn!(
+                identifier = %mapping.linear_identifier,
+                "Issue description has no Discord footer; not syncing message edit"
+            );
+            return Ok(());
+        }
+    };
+    if body == content {
+        return Ok(());
+    }
+
+    let updated = format!("{content}{rest}");
+    linear
+        .update_issue_description(&mapping.linear_issue_id, &updated)
+        .await?;
+
+    info!(
+        thread_id = %mapping.discord_thread_id,
+        identifier = %mapping.linear_identifier,
+        "Synced starter message edit to Linear description"
+    );
+
+    Ok(())
+}
+
+/// Names of the forum tags applied to `thread`, looked up on its parent forum. Best-effort: an
+/// unreadable parent yields no names.
+async fn applied_tag_names(
+    discord: &impl DiscordPort,
+    thread: &GuildChannel,
+    parent_id: ChannelId,
+) -> Vec<String> {
+    if thread.applied_tags.is_empty() {
+        return Vec::new();
+    }
+    let forum = match discord.channel(parent_id).await {
+        Ok(forum) => forum,
+        Err(e) => {
+            warn!(thread_id = %thread.id, error = %e, "Failed to fetch forum for tag names");
+            return Vec::new();
+        }
+    };
+    forum
+        .available_tags
+        .iter()
+        .filter(|tag| thread.applied_tags.contains(&tag.id))
+        .map(|tag| tag.name.clone())
+        .collect()
+}
+
+/// Labels and project for `thread`'s issue: the channel's label plus those mapped from the
+/// post's forum tags, and the first tag-mapped project or else the channel's.
+pub fn issue_labels_and_project<'a>(
+    channel_config: &'a ChannelConfig,
+    thread: &GuildChannel,
+) -> (Vec<String>, &'a str) {
+    let mut label_ids = vec![channel_config.linear_label_id.clone()];
+    for tag_id in &thread.applied_tags {
+        if let Some(linear_label_id) = channel_config.tag_label_map.get(&tag_id.to_string()) {
+            label_ids.push(linear_label_id.clone());
+        }
+    }
+
+    let project_id = thread
+        .applied_tags
+        .iter()
+        .find_map(|tag_id| channel_config.tag_project_map.get(&tag_id.to_string()))
+        .unwrap_or(&channel_config.linear_project_id);
+
+    (label_ids, project_id)
+}
+
+/// Priority and estimate for `thread`'s issue from the post's forum tags: the most urgent mapped
+/// priority and the largest mapped estimate, if any tag is mapped.
+pub fn issue_priority_and_estimate(
+    channel_config: &ChannelConfig,
+    thread: &GuildChannel,
+) -> (Option<i64>, Option<i64>) {
+    let mapped = |map: &HashMap<String, i64>| -> Vec<i64> {
+        thread
+            .applied_tags
+            .iter()
+            .filter_map(|tag_id| map.get(&tag_id.to_string()).copied())
+            .collect()
+    };
+    let priority = mapped(&channel_config.tag_priority_map).into_iter().min();
+    let estimate = mapped(&channel_config.tag_estimate_map).into_iter().max();
+    (priority, estimate)
+}
+
+/// Create the Linear issue for `thread` from `first_message`, store the mapping, and post the
+/// confirmation. Callers are responsible for checking the thread isn't already mapped.
+#[instrument(
+    skip_all,
+    fields(thread_id = %thread.id, team_id, identifier)
+)]
+pub async fn create_issue_for_thread(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    channel_config: &ChannelConfig,
+    linear: &impl LinearApi,
+    thread: &GuildChannel,
+    first_message: Option<&Message>,
+    intake: Option<&IntakeAnswers>,
+) -> Result<LinearIssue, AppError> {
+    let thread_id = thread.id.to_string();
+
+    let parent_id = thread
+        .parent_id
+        .ok_or_else(|| AppError::Internal("Thread has no parent channel".into()))?;
+
+    let messages = opening_messages(discord, channel_config, thread, first_message).await;
+    let mut bodies = Vec::new();
+    for msg in &messages {
+        let names =
+            MentionNames::resolve(discord, &msg.content, Some(thread.guild_id), &msg.mentions)
+                .await;
+        bodies.push(format::discord_to_linear(&msg.content, &names));
+    }
+    let message_body = if bodies.is_empty() {
+        "(No message content available)".to_string()
+    } else {
+        bodies.join("\n\n")
+    };
+
+    let author_roles = routing::author_roles(discord, channel_config, thread).await;
+    let content: Vec<&str> = messages.iter().map(|m| m.content.as_str()).collect();
+    let route = routing::route(channel_config, thread, &content.join("\n"), &author_roles);
+    Span::current().record("team_id", route.team_id);
+
+    // Upload attachments (best-effort)
+    let attachments: Vec<Attachment> = messages
+        .iter()
+        .flat_map(|m| m.attachments.iter().cloned())
+        .collect();
+    let attachment_links = upload_attachments(linear, route.team_id, &attachments).await;
+
+    // Build description
+    let thread_url = thread_url(channel_config.guild_id, parent_id, thread.id);
+
     let title = thread.name.clone();
+    let need_body = channel_config.customer_needs.then(|| message_body.clone());
+    let intake_answers = intake.map(IntakeAnswers::render).unwrap_or_default();
+    let description = match &channel_config.description_template {
+        Some(template) => {
+            let vars = DescriptionVars {
+                title: &title,
+                message_body: &message_body,
+                author: first_message.map_or("unknown", |m| m.author.display_name()),
+                thread_url: &thread_url,
+                tags: applied_tag_names(discord, thread, parent_id)
+                    .await
+                    .join(", "),
+                intake: intake_answers,
+                attachments: attachment_links.join("\n"),
+            };
+            templates::render(template, &vars).map_err(AppError::Template)?
+        }
+        None => {
+            let mut description = message_body;
+            if !intake_answers.is_empty() {
+                description.push_str("\n\n");
+                description.push_str(&intake_answers);
+            }
+            description.push_str(&format!("{DESCRIPTION_FOOTER}{thread_url})"));
+            if !attachment_links.is_empty() {
+                description.push_str("\n\n**Attachments:**\n");
+                description.push_str(&attachment_links.join("\n"));
+            }
+            description
+        }
+    };
+
+    // Create Linear issue in the routed team
+    let IssueRoute {
+        team_id,
+        project_id,
+        label_ids,
+        priority,
+    } = route;
     let issue = linear
-        .create_issue(&channel_config.linear_team_id, &title, &description, &label_ids, &channel_config.linear_project_id)
+        .create_issue(team_id, &title, &description, &label_ids, project_id)
         .await?;
+    Span::current().record("identifier", issue.identifier.as_str());
 
     info!(
         thread_id,
         identifier = %issue.identifier,
-        team_id = %channel_config.linear_team_id,
+        team_id,
+        project_id,
         "Created Linear issue from Discord thread"
     );
 
     // Store mapping
-    db::create_mapping(
-        pool,
-        &thread_id,
-        &issue.id,
-        &issue.identifier,
-        &channel_config.channel_type,
-    )
+    async {
+        db::create_mapping(
+            pool,
+            &thread_id,
+            &issue.id,
+            &issue.identifier,
+            &channel_config.channel_type,
+            &channel_config.guild_id.to_string(),
+        )
+        .await?;
+        db::record_issue_title(pool, &issue.id, &title).await
+    }
+    .instrument(info_span!("store_mapping"))
     .await?;
+    if let Some(message) = first_message {
+        if let Err(e) = repost::record(pool, thread, message, &issue.id, &issue.identifier).await {
+            warn!(thread_id, error = %e, "Failed to record post content hash");
+        }
+    }
+
+    link_thread_url(linear, &issue.id, &thread_url).await;
+    if let Some(body) = need_body {
+        file_customer_need(linear, &issue.id, thread, &messages, &body, &thread_url).await;
+    }
+
+    // Set before the status embed and triage menu are posted so they show the tagged or routed
+    // values.
+    let (_, estimate) = issue_priority_and_estimate(channel_config, thread);
+    if let Some(priority) = priority {
+        if let Err(e) = linear.update_issue_priority(&issue.id, priority).await {
+            warn!(thread_id, priority, error = %e, "Failed to set routed priority");
+        }
+    }
+    if let Some(estimate) = estimate {
+        if let Err(e) = linear.update_issue_estimate(&issue.id, estimate).await {
+            warn!(thread_id, estimate, error = %e, "Failed to set estimate from forum tags");
+        }
+    }
 
     // Post confirmation in Discord thread
-    let reply = format!(
-        "Tracked as **[{}]({})** in Linear",
-        issue.identifier, issue.url
-    );
-    thread.id.say(http, &reply).await?;
+    let reply = tracked_message(channel_config, &issue.identifier, &issue.url);
+    discord
+        .send_message(thread.id, CreateMessage::new().content(reply))
+        .await?;
 
-    Ok(())
+    if channel_config.status_embed {
+        if let Err(e) = post_status_embed(discord, pool, linear, thread.id, &issue.id).await {
+            warn!(thread_id, error = %e, "Failed to post status embed");
+        }
+    }
+
+    if channel_config.triage_role_id.is_some() {
+        if let Err(e) = triage::post_menu(discord, thread.id, &issue, channel_config).await {
+            warn!(thread_id, error = %e, "Failed to post triage menu");
+        }
+    }
+
+    Ok(issue)
 }
 
-async fn fetch_first_message_with_retry(
-    http: &Http,
+/// Confirmation posted in a thread once it is mapped to a Linear issue.
+pub fn tracked_message(channel_config: &ChannelConfig, identifier: &str, url: &str) -> String {
+    templates::notification(
+        &channel_config.message_templates,
+        Notification::Tracked,
+        &json!({ "identifier": identifier, "url": url }),
+    )
+}
+
+/// Link to a thread in a guild's forum (or text channel) `parent_id`.
+pub fn thread_url(guild_id: u64, parent_id: ChannelId, thread_id: ChannelId) -> String {
+    format!("https://discord.com/channels/{guild_id}/{parent_id}/{thread_id}")
+}
+
+/// Attach the thread's URL to its issue, so the thread shows up among the issue's links and
+/// Linear can find the issue by URL. Failures are only logged; the description links it too.
+pub async fn link_thread_url(linear: &impl LinearApi, issue_id: &str, thread_url: &str) {
+    if let Err(e) = linear
+        .link_url(issue_id, thread_url, "Discord thread")
+        .await
+    {
+        warn!(issue_id, error = %e, "Failed to attach thread link to issue");
+    }
+}
+
+/// Record the post as a customer need on its issue, with the post's author as the customer.
+/// Failures are only logged; the issue itself is filed either way.
+async fn file_customer_need(
+    linear: &impl LinearApi,
+    issue_id: &str,
+    thread: &GuildChannel,
+    messages: &[Message],
+    body: &str,
+    thread_url: &str,
+) {
+    let Some(author_id) = thread.owner_id else {
+        return;
+    };
+    let name = messages
+        .iter()
+        .find(|m| m.author.id == author_id)
+        .map_or_else(
+            || format!("Discord user {author_id}"),
+            |m| m.author.display_name().to_string(),
+        );
+    let customer = Customer {
+        external_id: format!("discord:{author_id}"),
+        name,
+    };
+    if let Err(e) = linear
+        .create_customer_need(issue_id, &customer, body, thread_url)
+        .await
+    {
+        warn!(issue_id, error = %e, "Failed to file customer need");
+    }
+}
+
+/// Whether the thread's author holds one of the channel's `poster_role_ids`. Authors who have
+/// left the guild don't.
+async fn poster_allowed(
+    discord: &impl DiscordPort,
+    channel_config: &ChannelConfig,
+    thread: &GuildChannel,
+) -> Result<bool, AppError> {
+    let Some(owner_id) = thread.owner_id else {
+        return Ok(false);
+    };
+    let member = match discord.member(thread.guild_id, owner_id).await {
+        Ok(member) => member,
+        Err(e) if port::is_not_found(&e) => return Ok(false),
+        Err(e) => return Err(e),
+    };
+    Ok(channel_config
+        .poster_role_ids
+        .iter()
+        .any(|r| member.roles.contains(&RoleId::new(*r))))
+}
+
+/// How long a thread claim holds past the follow-up window, for filing the issue.
+const THREAD_CLAIM_TIMEOUT_SECS: i64 = 300;
+
+/// How often the thread is checked for the author's follow-up messages while waiting for them.
+const FOLLOW_UP_POLL_SECS: i64 = 5;
+
+/// The messages `thread`'s issue is described with: the author's opening messages, up to the
+/// channel's `description_message_limit` and within its `description_window_secs` of the post,
+/// or just `first_message` if it isn't the post's starter or the thread can't be read.
+async fn opening_messages(
+    discord: &impl DiscordPort,
+    channel_config: &ChannelConfig,
+    thread: &GuildChannel,
+    first_message: Option<&Message>,
+) -> Vec<Message> {
+    match author_messages(discord, channel_config, thread, first_message).await {
+        Some(messages) if !messages.is_empty() => messages,
+        _ => first_message.into_iter().cloned().collect(),
+    }
+}
+
+/// Wait until the author has sent `description_message_limit` opening messages or the
+/// channel's `description_window_secs` have passed since the post, whichever is first.
+async fn wait_for_follow_ups(
+    discord: &impl DiscordPort,
+    channel_config: &ChannelConfig,
+    thread: &GuildChannel,
+    first_message: Option<&Message>,
+) {
+    let Some(first) = first_message else {
+        return;
+    };
+    let deadline = first.timestamp.unix_timestamp() + channel_config.description_window_secs;
+    loop {
+        let remaining = deadline - chrono::Utc::now().timestamp();
+        if remaining <= 0 {
+            return;
+        }
+        match author_messages(discord, channel_config, thread, first_message).await {
+            Some(messages) if messages.len() < channel_config.description_message_limit => {}
+            _ => return,
+        }
+        let wait = remaining.min(FOLLOW_UP_POLL_SECS) as u64;
+        tokio::time::sleep(std::time::Duration::from_secs(wait)).await;
+    }
+}
+
+/// The author's opening messages in `thread` that count towards its description, or `None` if
+/// the channel only uses the first message, `first_message` isn't the post's starter message,
+/// or the thread's messages can't be fetched.
+async fn author_messages(
+    discord: &impl DiscordPort,
+    channel_config: &ChannelConfig,
+    thread: &GuildChannel,
+    first_message: Option<&Message>,
+) -> Option<Vec<Message>> {
+    let limit = channel_config.description_message_limit;
+    let first = first_message?;
+    let author_id = thread.owner_id?;
+    if limit <= 1 || first.id.get() != thread.id.get() {
+        return None;
+    }
+
+    let window = channel_config.description_window_secs;
+    let deadline = first.timestamp.unix_timestamp() + window;
+    let messages = match discord.opening_messages(thread.id).await {
+        Ok(messages) => messages,
+        Err(e) => {
+            warn!(thread_id = %thread.id, error = %e, "Failed to fetch follow-up messages");
+            return None;
+        }
+    };
+    Some(
+        messages
+            .into_iter()
+            .filter(|m| m.author.id == author_id)
+            .filter(|m| window <= 0 || m.timestamp.unix_timestamp() <= deadline)
+            .take(limit)
+            .collect(),
+    )
+}
+
+/// Fetch the oldest message in a thread (the forum post body), retrying while Discord catches
+/// up with a just-created post.
+pub async fn fetch_first_message_with_retry(
+    discord: &impl DiscordPort,
     channel_id: ChannelId,
-) -> Option<serenity::model::channel::Message> {
+) -> Option<Message> {
     for attempt in 0..3 {
         if attempt > 0 {
             tokio::time::sleep(std::time::Duration::from_secs(2)).await;
         }
 
-        match channel_id
-            .messages(http, serenity::builder::GetMessages::new().limit(1))
-            .await
-        {
-            Ok(messages) => {
-                if let Some(msg) = messages.into_iter().next() {
-                    return Some(msg);
-                }
+        match discord.first_message(channel_id).await {
+            Ok(Some(msg)) => return Some(msg),
+            Ok(None) => {
                 warn!(attempt, "No messages found in thread yet, retrying");
             }
             Err(e) => {
@@ -140,19 +992,54 @@ async fn fetch_first_message_with_retry(
     None
 }
 
+/// Upload message attachments to Linear, returning markdown links for the ones that succeeded.
+/// Upload to the workspace of `team_or_issue_id`; see [`LinearApi::request_file_upload`].
+#[instrument(skip_all, fields(count = attachments.len()))]
+async fn upload_attachments(
+    linear: &impl LinearApi,
+    team_or_issue_id: &str,
+    attachments: &[Attachment],
+) -> Vec<String> {
+    let mut links = Vec::new();
+    for attachment in attachments {
+        match upload_attachment(linear, team_or_issue_id, attachment).await {
+            Ok(asset_url) => {
+                links.push(format!("![{}]({})", attachment.filename, asset_url));
+            }
+            Err(e) => {
+                warn!(
+                    filename = %attachment.filename,
+                    error = %e,
+                    "Failed to upload attachment, skipping"
+                );
+            }
+        }
+    }
+    links
+}
+
+/// Stream one attachment from Discord into a Linear upload slot, returning the asset URL.
+#[instrument(skip_all, fields(filename = %attachment.filename, size))]
 async fn upload_attachment(
-    linear: &LinearClient,
-    url: &str,
-    filename: &str,
+    linear: &impl LinearApi,
+    team_or_issue_id: &str,
+    attachment: &Attachment,
 ) -> Result<String, AppError> {
-    let (data, content_type) = linear.download_attachment(url).await?;
-    let size = data.len() as u64;
+    let download = linear.download_attachment(&attachment.url).await?;
+    // The slot is reserved for an exact size before any of the file has been read.
+    let size = download.size.unwrap_or(u64::from(attachment.size));
+    Span::current().record("size", size);
 
     let upload = linear
-        .request_file_upload(filename, &content_type, size)
+        .request_file_upload(
+            team_or_issue_id,
+            &attachment.filename,
+            &download.content_type,
+            size,
+        )
         .await?;
 
     linear
-        .upload_file_to_url(&upload, data, &content_type)
+        .upload_file_to_url(&upload, download.body, size, &download.content_type)
         .await
 }
@@ -0,0 +1,120 @@
+//! Discussions started from Linear: an untracked issue given a channel's `discuss_label_id`
+//! gets a forum post in that channel, which is then synced like any other tracked thread.
+
+use serde_json::json;
+use serenity::all::{ChannelId, CreateForumPost, CreateMessage};
+use sqlx::SqlitePool;
+use tracing::{info, warn};
+
+use crate::config::Config;
+use crate::db;
+use crate::discord::port::DiscordPort;
+use crate::error::AppError;
+use crate::format;
+use crate::linear::api::LinearApi;
+use crate::sync::discord_to_linear::{link_thread_url, thread_url};
+use crate::sync::linear_to_discord::split_for_discord;
+use crate::sync::status_embed::post_status_embed;
+use crate::sync::thread::thread_name_for_title;
+use crate::templates::{self, Notification};
+
+/// Open a post for an untracked issue in `team_id` if it has a channel's `discuss_label_id`:
+/// the issue's description under an `opened` header, split across messages if it's long.
+/// Returns whether a post was opened.
+pub async fn open_thread_for_issue(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    config: &Config,
+    linear: &impl LinearApi,
+    team_id: &str,
+    issue_id: &str,
+    label_ids: &[String],
+) -> Result<bool, AppError> {
+    let channel_config = config.channels.iter().find(|c| {
+        c.discuss_label_id
+            .as_ref()
+            .is_some_and(|label| label_ids.contains(label))
+            && c.team_ids().any(|t| t == team_id)
+    });
+    let Some(channel_config) = channel_config else {
+        return Ok(false);
+    };
+    if db::get_mapping_by_linear_issue(pool, issue_id)
+        .await?
+        .is_some()
+    {
+        return Ok(false);
+    }
+
+    let issue = linear.get_issue(issue_id).await?;
+    let description = linear.get_issue_description(issue_id).await?;
+    let header = templates::notification(
+        &channel_config.message_templates,
+        Notification::Opened,
+        &json!({
+            "identifier": issue.identifier,
+            "url": issue.url,
+            "status": issue.status_name,
+        }),
+    );
+    let body = format::linear_to_discord(&description);
+    let text = if body.trim().is_empty() {
+        header
+    } else {
+        format!("{header}\n\n{body}")
+    };
+    let mut chunks = split_for_discord(&text).into_iter();
+    let starter = chunks.next().unwrap_or_default();
+
+    let forum_id = ChannelId::new(channel_config.discord_channel_id);
+    let post = CreateForumPost::new(
+        thread_name_for_title(&issue.title),
+        CreateMessage::new().content(starter),
+    );
+    let thread = discord.create_forum_post(forum_id, post).await?;
+    let thread_id = thread.id.to_string();
+
+    db::create_mapping(
+        pool,
+        &thread_id,
+        &issue.id,
+        &issue.identifier,
+        &channel_config.channel_type,
+        &channel_config.guild_id.to_string(),
+    )
+    .await?;
+    // Prime the cache so the poller doesn't announce the current state as a change.
+    db::upsert_cached_status(pool, &issue.id, &issue.status_name).await?;
+    db::record_issue_title(pool, &issue.id, &issue.title).await?;
+    db::insert_audit_entry(
+        pool,
+        &thread_id,
+        &issue.identifier,
+        "thread_opened",
+        None,
+        &issue.title,
+    )
+    .await?;
+
+    info!(
+        thread_id,
+        identifier = %issue.identifier,
+        channel_id = channel_config.discord_channel_id,
+        "Opened forum post for issue labeled in Linear"
+    );
+
+    for chunk in chunks {
+        discord
+            .send_message(thread.id, CreateMessage::new().content(chunk))
+            .await?;
+    }
+    let url = thread_url(channel_config.guild_id, forum_id, thread.id);
+    link_thread_url(linear, &issue.id, &url).await;
+    if channel_config.status_embed {
+        if let Err(e) = post_status_embed(discord, pool, linear, thread.id, &issue.id).await {
+            warn!(thread_id, error = %e, "Failed to post status embed");
+        }
+    }
+
+    Ok(true)
+}
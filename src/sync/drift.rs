@@ -0,0 +1,329 @@
+//! Periodic cross-check of the mappings against Linear and Discord.
+//!
+//! The live sync paths keep the database in step as events arrive, but events can be missed:
+//! an issue deleted in Linear while the webhook receiver was down, a thread removed while the bot
+//! was offline, a relayed comment deleted by a moderator. Left alone, these cost a failing call
+//! on every poll or, for a missing status cache, a duplicate announcement. The drift check finds
+//! them, reports them to `OPS_CHANNEL_ID`, and with `DRIFT_AUTO_REPAIR` set fixes them:
+//!
+//! - Issue gone from Linear (deleted or archived), or thread deleted in Discord: the mapping is
+//!   tombstoned, as an unlink would.
+//! - No cached status: the current Linear status is cached without being announced.
+//! - A synced comment whose Discord message is gone: the comment's sync rows are dropped. The
+//!   comment cursor is already past it, so it isn't relayed again.
+//!
+//! `/linear admin prune` tombstones the first kind on demand, without waiting for the next check.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serenity::all::{ChannelId, CreateMessage, MessageId};
+use sqlx::SqlitePool;
+use tracing::{error, info, warn};
+
+use crate::config::{Config, SharedConfig};
+use crate::db;
+use crate::discord::port::{is_not_found, DiscordPort};
+use crate::error::AppError;
+use crate::linear::api::LinearApi;
+
+const BATCH_SIZE: usize = 100;
+
+/// Discrepancies listed in one ops channel report; the rest are only counted.
+const MAX_REPORTED: usize = 10;
+
+/// A mapping whose recorded state no longer matches Linear or Discord.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Drift {
+    pub discord_thread_id: String,
+    pub linear_issue_id: String,
+    pub linear_identifier: String,
+    pub kind: DriftKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DriftKind {
+    /// The issue was deleted or archived in Linear.
+    IssueMissing,
+    /// The thread was deleted in Discord.
+    ThreadMissing,
+    /// No status is cached, so the next poll would announce the current one as a change.
+    StatusUncached { status: String },
+    /// A comment is recorded as synced, but its Discord message was deleted.
+    MessageMissing {
+        linear_comment_id: String,
+        discord_message_id: String,
+    },
+}
+
+impl fmt::Display for Drift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (issue, thread) = (&self.linear_identifier, &self.discord_thread_id);
+        match &self.kind {
+            DriftKind::IssueMissing => {
+                write!(
+                    f,
+                    "**{issue}** is deleted or archived in Linear (thread <#{thread}>)"
+                )
+            }
+            DriftKind::ThreadMissing => write!(f, "**{issue}**'s thread {thread} was deleted"),
+            DriftKind::StatusUncached { status } => {
+                write!(f, "**{issue}** has no cached status (currently {status})")
+            }
+            DriftKind::MessageMissing {
+                linear_comment_id,
+                discord_message_id,
+            } => write!(
+                f,
+                "**{issue}** comment {linear_comment_id} was synced to message \
+                 {discord_message_id}, which is gone from <#{thread}>"
+            ),
+        }
+    }
+}
+
+/// Run the drift check every `DRIFT_CHECK_INTERVAL_SECS`. Runs alongside the status poller.
+pub async fn run_drift_checks(
+    discord: impl DiscordPort,
+    pool: SqlitePool,
+    linear: impl LinearApi,
+    config: SharedConfig,
+) {
+    let interval_secs = config.current().drift_check_interval_secs;
+    info!(interval_secs, "Starting drift check task");
+
+    loop {
+        tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
+
+        if let Err(e) = check_drift(&discord, &pool, &config.current(), &linear).await {
+            error!(error = %e, "Drift check failed");
+        }
+    }
+}
+
+/// Find drift, repair it if `DRIFT_AUTO_REPAIR` is set, and report it to the ops channel.
+pub async fn check_drift(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    config: &Config,
+    linear: &impl LinearApi,
+) -> Result<Vec<Drift>, AppError> {
+    let drift = find_drift(discord, pool, linear).await?;
+    if drift.is_empty() {
+        info!("Drift check found nothing");
+        return Ok(drift);
+    }
+
+    let repaired = if config.drift_auto_repair {
+        repair_drift(pool, &drift, None).await?
+    } else {
+        0
+    };
+    for d in &drift {
+        warn!(
+            thread_id = %d.discord_thread_id,
+            identifier = %d.linear_identifier,
+            kind = ?d.kind,
+            repaired = config.drift_auto_repair,
+            "Mapping drifted from Linear or Discord"
+        );
+    }
+    report(discord, config, &drift, repaired).await;
+
+    Ok(drift)
+}
+
+/// Compare every mapping with Linear and Discord. Lookups that fail for any other reason than
+/// the target being gone are logged and skipped, so an outage isn't reported as drift.
+pub async fn find_drift(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    linear: &impl LinearApi,
+) -> Result<Vec<Drift>, AppError> {
+    scan(discord, pool, linear, None, false).await
+}
+
+/// Mappings whose issue is gone from Linear or whose thread is gone from Discord, only those in
+/// `guild_id` if given. Skips the per-comment message lookups `find_drift` makes.
+pub async fn find_orphans(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    linear: &impl LinearApi,
+    guild_id: Option<&str>,
+) -> Result<Vec<Drift>, AppError> {
+    scan(discord, pool, linear, guild_id, true).await
+}
+
+/// Tombstone the mappings `find_orphans` returns, recording `actor` as the one who removed them.
+pub async fn prune_orphans(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    linear: &impl LinearApi,
+    guild_id: Option<&str>,
+    actor: Option<&str>,
+) -> Result<Vec<Drift>, AppError> {
+    let orphans = find_orphans(discord, pool, linear, guild_id).await?;
+    repair_drift(pool, &orphans, actor).await?;
+    Ok(orphans)
+}
+
+async fn scan(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    linear: &impl LinearApi,
+    guild_id: Option<&str>,
+    orphans_only: bool,
+) -> Result<Vec<Drift>, AppError> {
+    let mut mappings = db::get_all_tracked_issues(pool).await?;
+    if let Some(guild_id) = guild_id {
+        mappings.retain(|m| m.guild_id.as_deref() == Some(guild_id));
+    }
+    let mut drift = Vec::new();
+
+    for chunk in mappings.chunks(BATCH_SIZE) {
+        let ids: Vec<String> = chunk.iter().map(|m| m.linear_issue_id.clone()).collect();
+        let statuses: HashMap<String, String> = match linear.get_issues_by_ids(&ids).await {
+            // Deleted and archived issues are left out of the results.
+            Ok(issues) => issues
+                .into_iter()
+                .map(|issue| (issue.id, issue.status_name))
+                .collect(),
+            Err(e) => {
+                warn!(error = %e, "Failed to fetch issue batch for drift check");
+                continue;
+            }
+        };
+
+        for mapping in chunk {
+            let found = |kind| Drift {
+                discord_thread_id: mapping.discord_thread_id.clone(),
+                linear_issue_id: mapping.linear_issue_id.clone(),
+                linear_identifier: mapping.linear_identifier.clone(),
+                kind,
+            };
+
+            let Some(status) = statuses.get(&mapping.linear_issue_id) else {
+                drift.push(found(DriftKind::IssueMissing));
+                continue;
+            };
+
+            let Ok(thread_id) = mapping.discord_thread_id.parse() else {
+                warn!(thread_id = %mapping.discord_thread_id, "Invalid Discord thread id in mapping");
+                continue;
+            };
+            let channel = ChannelId::new(thread_id);
+            match discord.channel(channel).await {
+                Ok(_) => {}
+                Err(e) if is_not_found(&e) => {
+                    drift.push(found(DriftKind::ThreadMissing));
+                    continue;
+                }
+                Err(e) => {
+                    warn!(thread_id, error = %e, "Failed to fetch thread for drift check");
+                    continue;
+                }
+            }
+            if orphans_only {
+                continue;
+            }
+
+            if db::get_cached_status(pool, &mapping.linear_issue_id)
+                .await?
+                .is_none()
+            {
+                drift.push(found(DriftKind::StatusUncached {
+                    status: status.clone(),
+                }));
+            }
+
+            for comment in db::get_synced_comments(pool, &mapping.linear_issue_id).await? {
+                let Ok(message_id) = comment.discord_message_id.parse() else {
+                    continue;
+                };
+                match discord.message(channel, MessageId::new(message_id)).await {
+                    Ok(_) => {}
+                    Err(e) if is_not_found(&e) => {
+                        drift.push(found(DriftKind::MessageMissing {
+                            linear_comment_id: comment.linear_comment_id,
+                            discord_message_id: comment.discord_message_id,
+                        }));
+                    }
+                    Err(e) => {
+                        warn!(thread_id, error = %e, "Failed to fetch message for drift check");
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(drift)
+}
+
+/// Bring the database back in line with Linear and Discord, recording `actor` on tombstoned
+/// mappings. Returns how many discrepancies were repaired; a mapping already removed since it
+/// was checked is skipped.
+pub async fn repair_drift(
+    pool: &SqlitePool,
+    drift: &[Drift],
+    actor: Option<&str>,
+) -> Result<usize, AppError> {
+    let mut repaired = 0;
+    for d in drift {
+        match &d.kind {
+            DriftKind::IssueMissing | DriftKind::ThreadMissing => {
+                let Some(mapping) =
+                    db::get_mapping_by_discord_thread(pool, &d.discord_thread_id).await?
+                else {
+                    continue;
+                };
+                let reason = match d.kind {
+                    DriftKind::IssueMissing => "issue_missing",
+                    _ => "thread_deleted",
+                };
+                db::tombstone_mapping(pool, &mapping, reason, actor).await?;
+            }
+            DriftKind::StatusUncached { status } => {
+                db::upsert_cached_status(pool, &d.linear_issue_id, status).await?;
+            }
+            DriftKind::MessageMissing {
+                linear_comment_id, ..
+            } => {
+                db::delete_relayed_comment(pool, linear_comment_id).await?;
+                db::delete_synced_comment(pool, linear_comment_id).await?;
+            }
+        }
+        repaired += 1;
+    }
+    info!(repaired, "Repaired drifted mappings");
+    Ok(repaired)
+}
+
+/// Post the discrepancies in the ops channel, if one is configured.
+async fn report(discord: &impl DiscordPort, config: &Config, drift: &[Drift], repaired: usize) {
+    let Some(channel_id) = config.ops_channel_id else {
+        return;
+    };
+
+    let mut content = format!("Drift check found {} discrepancies", drift.len());
+    if config.drift_auto_repair {
+        content.push_str(&format!(" and repaired {repaired}"));
+    }
+    content.push(':');
+    for d in drift.iter().take(MAX_REPORTED) {
+        content.push_str(&format!("\n- {d}"));
+    }
+    if drift.len() > MAX_REPORTED {
+        content.push_str(&format!("\n…and {} more", drift.len() - MAX_REPORTED));
+    }
+    if !config.drift_auto_repair {
+        content.push_str("\nSet `DRIFT_AUTO_REPAIR=1` to fix these automatically.");
+    }
+
+    let message = CreateMessage::new().content(content);
+    if let Err(e) = discord
+        .send_message(ChannelId::new(channel_id), message)
+        .await
+    {
+        warn!(error = %e, "Failed to post drift report to ops channel");
+    }
+}
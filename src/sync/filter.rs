@@ -0,0 +1,135 @@
+//! Keeping spam and low-quality posts out of Linear.
+//!
+//! Channels can refuse to file posts whose body is too short, posts from authors who have
+//! already had `user_issue_limit` issues created recently, and posts from blocked users or
+//! containing blocked keywords. A filtered post gets a polite reply instead of an issue and is
+//! recorded in `filtered_threads`, so it stays unfiled; a moderator can still link it by hand.
+
+use serenity::all::{CreateMessage, GuildChannel, Message};
+use sqlx::SqlitePool;
+use tracing::info;
+
+use crate::config::ChannelConfig;
+use crate::db;
+use crate::discord::port::DiscordPort;
+use crate::error::AppError;
+
+/// Why a post wasn't filed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterReason {
+    TooShort,
+    RateLimited,
+    BlockedUser,
+    BlockedKeyword,
+}
+
+impl FilterReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FilterReason::TooShort => "too_short",
+            FilterReason::RateLimited => "rate_limited",
+            FilterReason::BlockedUser => "blocked_user",
+            FilterReason::BlockedKeyword => "blocked_keyword",
+        }
+    }
+
+    fn reply(self, channel_config: &ChannelConfig) -> String {
+        match self {
+            FilterReason::TooShort => format!(
+                "Thanks for posting! This post is too short to be filed as an issue. Please make \
+                 a new post with more detail (at least {} characters) so we can follow up.",
+                channel_config.min_body_length
+            ),
+            FilterReason::RateLimited => "Thanks for posting! You've opened several posts \
+                 recently, so this one wasn't filed automatically. A moderator will take a look."
+                .to_string(),
+            FilterReason::BlockedUser | FilterReason::BlockedKeyword => {
+                "Thanks for posting! This post wasn't filed as an issue. If you think that's a \
+                 mistake, please reach out to a moderator."
+                    .to_string()
+            }
+        }
+    }
+}
+
+/// The reason the channel's filters reject a post, if any, judging its body by the author's
+/// opening `messages`. The body length isn't checked when none could be fetched.
+async fn check(
+    pool: &SqlitePool,
+    channel_config: &ChannelConfig,
+    thread: &GuildChannel,
+    messages: &[Message],
+) -> Result<Option<FilterReason>, AppError> {
+    let body = messages
+        .iter()
+        .map(|m| m.content.as_str())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    if let Some(owner_id) = thread.owner_id {
+        if channel_config.blocked_user_ids.contains(&owner_id.get()) {
+            return Ok(Some(FilterReason::BlockedUser));
+        }
+    }
+
+    let title = thread.name.to_lowercase();
+    let lowered_body = body.to_lowercase();
+    let blocked = channel_config.blocked_keywords.iter().any(|keyword| {
+        let keyword = keyword.to_lowercase();
+        !keyword.is_empty() && (title.contains(&keyword) || lowered_body.contains(&keyword))
+    });
+    if blocked {
+        return Ok(Some(FilterReason::BlockedKeyword));
+    }
+
+    if !messages.is_empty() && body.trim().chars().count() < channel_config.min_body_length {
+        return Ok(Some(FilterReason::TooShort));
+    }
+
+    if let (Some(limit), Some(owner_id)) = (channel_config.user_issue_limit, thread.owner_id) {
+        let recent = db::count_recent_issues_by(
+            pool,
+            &owner_id.to_string(),
+            channel_config.user_issue_window_secs,
+        )
+        .await?;
+        if recent >= limit {
+            return Ok(Some(FilterReason::RateLimited));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Apply the channel's filters to a new post. Returns whether it was filtered, now or on an
+/// earlier sync; if so, no issue should be created.
+pub async fn filter_post(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    channel_config: &ChannelConfig,
+    thread: &GuildChannel,
+    messages: &[Message],
+) -> Result<bool, AppError> {
+    let thread_id = thread.id.to_string();
+    if db::is_thread_filtered(pool, &thread_id).await? {
+        return Ok(true);
+    }
+    let Some(reason) = check(pool, channel_config, thread, messages).await? else {
+        return Ok(false);
+    };
+
+    if db::insert_filtered_thread(pool, &thread_id, reason.as_str()).await? {
+        discord
+            .send_message(
+                thread.id,
+                CreateMessage::new().content(reason.reply(channel_config)),
+            )
+            .await?;
+        info!(
+            thread_id,
+            reason = reason.as_str(),
+            "Filtered post, not filing it"
+        );
+    }
+    Ok(true)
+}
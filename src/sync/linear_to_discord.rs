@@ -1,14 +1,52 @@
-use serenity::all::{ChannelId, EditThread, Http};
+use std::collections::HashMap;
+
+use reqwest::StatusCode;
+use serde_json::json;
+use serenity::all::{
+    ChannelId, CreateAttachment, CreateMessage, CreateWebhook, EditMessage, EditThread,
+    EditWebhookMessage, ExecuteWebhook, MessageId, Webhook,
+};
 use sqlx::SqlitePool;
 use tracing::{info, warn};
 
-use crate::db;
+use crate::config::Config;
+use crate::db::{self, NotificationPrefs, RelayedComment};
+use crate::discord::embeds::issue_embed;
+use crate::discord::port::DiscordPort;
+use crate::discord::subscriptions::{self, Update};
 use crate::error::AppError;
-use crate::linear::client::LinearClient;
+use crate::format;
+use crate::linear::api::LinearApi;
+use crate::linear::client::{LinearAssignee, LinearComment, LinearIssueDetail, StateCategory};
+use crate::sync::digest;
+use crate::sync::status_embed::{post_status_embed, update_status_embed};
+use crate::sync::thread::{
+    channel_config_for_thread, fetch_thread, status_action, tags_for_labels, tags_for_state,
+    thread_name_for_title,
+};
+use crate::templates::{self, Notification};
 
 const DISCORD_MAX_MESSAGE_CHARS: usize = 2000;
 
-fn split_for_discord(message: &str) -> Vec<String> {
+/// Discord's limit on files attached to a single message.
+const DISCORD_MAX_ATTACHMENTS: usize = 10;
+
+/// Discord's attachment size limit for servers without boosts.
+const DISCORD_MAX_UPLOAD_BYTES: usize = 10 * 1024 * 1024;
+
+const LINEAR_UPLOADS_PREFIX: &str = "https://uploads.linear.app/";
+
+/// Discord's limit on webhook usernames.
+const DISCORD_MAX_WEBHOOK_USERNAME_CHARS: usize = 80;
+
+/// Name of the webhook created in each forum channel to relay comments.
+const COMMENT_WEBHOOK_NAME: &str = "Linear";
+
+/// Discord allows two renames per thread every ten minutes; spacing them five minutes apart
+/// keeps us under the limit without tripping the client-side rate limiter.
+const THREAD_RENAME_INTERVAL_SECS: i64 = 300;
+
+pub fn split_for_discord(message: &str) -> Vec<String> {
     if message.chars().count() <= DISCORD_MAX_MESSAGE_CHARS {
         return vec![message.to_string()];
     }
@@ -60,17 +98,22 @@ fn split_for_discord(message: &str) -> Vec<String> {
 }
 
 pub async fn sync_linear_to_discord(
-    http: &Http,
+    discord: &impl DiscordPort,
     pool: &SqlitePool,
-    linear_issue_id: &str,
-    identifier: &str,
-    new_status: &str,
-    new_status_type: &str,
+    config: &Config,
+    linear: &impl LinearApi,
+    issue: &LinearIssueDetail,
 ) -> Result<(), AppError> {
+    let linear_issue_id = issue.id.as_str();
+    let new_status = issue.status_name.as_str();
+    let new_status_type = issue.status_type.as_str();
+    let category = issue.category();
+
     // Look up Discord thread from mapping
     let mapping = db::get_mapping_by_linear_issue(pool, linear_issue_id)
         .await?
-        .ok_or_else(|| AppError::Internal(format!("No mapping for issue {identifier}")))?;
+        .ok_or_else(|| AppError::Internal(format!("No mapping for issue {linear_issue_id}")))?;
+    let identifier = mapping.linear_identifier.as_str();
 
     // Post status update in Discord thread
     let thread_id: u64 = mapping
@@ -79,28 +122,152 @@ pub async fn sync_linear_to_discord(
         .map_err(|_| AppError::Internal("Invalid discord thread id".into()))?;
 
     let channel = ChannelId::new(thread_id);
-    let message = format!("**{identifier}** status changed to **{new_status}**");
+    let thread = match fetch_thread(discord, channel).await {
+        Ok(thread) => Some(thread),
+        Err(e) => {
+            warn!(linear_issue_id, identifier, error = %e, "Failed to fetch thread for state sync");
+            None
+        }
+    };
+    let channel_config = thread
+        .as_ref()
+        .and_then(|t| t.parent_id)
+        .and_then(|p| config.channel_config(p.get()));
 
-    channel.say(http, &message).await?;
+    let action = channel_config.and_then(|c| status_action(c, new_status, category));
+    let status_embed = channel_config.is_some_and(|c| c.status_embed);
+    if status_embed {
+        if db::get_status_embed(pool, linear_issue_id).await?.is_some() {
+            update_status_embed(discord, pool, linear, channel, linear_issue_id).await?;
+        } else {
+            post_status_embed(discord, pool, linear, channel, linear_issue_id).await?;
+        }
+    }
+    let action_notice = action.is_some_and(|a| a.message.is_some() || a.ping_role_id.is_some());
+    if !status_embed || action_notice {
+        let vars = json!({
+            "identifier": identifier,
+            "status": new_status,
+            "status_type": new_status_type,
+            "url": issue.url,
+        });
+        // Reaching a completed state gets its own, celebratory notice.
+        let notification = if category == StateCategory::Completed {
+            Notification::Completed
+        } else {
+            Notification::StatusChanged
+        };
+        let default_message = || {
+            templates::notification(
+                channel_config.map_or(&config.message_templates, |c| &c.message_templates),
+                notification,
+                &vars,
+            )
+        };
+        let mut message = match action.and_then(|a| a.message.as_deref()) {
+            Some(source) => templates::render(source, &vars).unwrap_or_else(|e| {
+                warn!(
+                    identifier,
+                    status = new_status,
+                    error = %e,
+                    "Failed to render status action message"
+                );
+                default_message()
+            }),
+            None => default_message(),
+        };
+        if let Some(role_id) = action.and_then(|a| a.ping_role_id) {
+            message = format!("<@&{role_id}> {message}");
+        }
+        digest::notify_with_embed(discord, pool, config, channel, &message, issue_embed(issue))
+            .await?;
+    }
+    db::insert_audit_entry(
+        pool,
+        &mapping.discord_thread_id,
+        identifier,
+        "status_announced",
+        None,
+        new_status,
+    )
+    .await?;
+    let thread_id = &mapping.discord_thread_id;
+    let update = subscriptions::status_message(
+        channel_config.map_or(&config.message_templates, |c| &c.message_templates),
+        thread_id,
+        identifier,
+        new_status,
+    );
+    let notified =
+        subscriptions::notify_subscribers(discord, pool, thread_id, Update::StatusChanged, &update);
+    if let Err(e) = notified.await {
+        warn!(identifier, error = %e, "Failed to notify subscribers of status change");
+    }
 
-    // Mirror Linear completion state to Discord thread: archive when completed,
-    // unarchive on any other state so reopens in Linear bring the post back.
-    let should_archive = new_status_type == "completed";
-    if let Err(e) = channel
-        .edit_thread(http, EditThread::new().archived(should_archive))
-        .await
-    {
-        warn!(
+    // Mirror Linear closure onto the thread: archive (and optionally lock) when the issue is
+    // completed or canceled, after the channel's grace period if one is set; reopen on any other
+    // state so reopens in Linear bring the post back. A status action can override either.
+    let archive_on_close = channel_config.is_none_or(|c| c.archive_on_close);
+    let lock_on_close = channel_config.is_some_and(|c| c.lock_on_close);
+    let grace_secs = channel_config.map_or(0, |c| c.archive_grace_secs);
+    let action_archive = action.and_then(|a| a.archive);
+    let action_lock = action.and_then(|a| a.lock);
+    let archive = action_archive.unwrap_or(archive_on_close && category.is_terminal());
+
+    let mut edit = EditThread::new();
+    let mut should_archive = false;
+    let mut locked = None;
+    if archive && grace_secs == 0 {
+        should_archive = true;
+        edit = edit.archived(true);
+        locked = action_lock.or(lock_on_close.then_some(true));
+    } else if archive {
+        let lock = action_lock.unwrap_or(lock_on_close);
+        db::upsert_pending_archive(pool, &mapping.discord_thread_id, grace_secs, lock).await?;
+    } else if action_archive.is_some() || !category.is_terminal() {
+        db::delete_pending_archive(pool, &mapping.discord_thread_id).await?;
+        edit = edit.archived(false);
+        locked = action_lock.or((!category.is_terminal() && lock_on_close).then_some(false));
+    } else {
+        locked = action_lock;
+    }
+    if let Some(locked) = locked {
+        edit = edit.locked(locked);
+    }
+
+    // Swap the forum tag for the new workflow state, if the channel maps states to tags.
+    if let (Some(thread), Some(c)) = (&thread, channel_config) {
+        if let Some(tags) = tags_for_state(&thread.applied_tags, c, new_status, category) {
+            edit = edit.applied_tags(tags);
+        }
+    }
+
+    match discord.edit_thread(channel, edit).await {
+        Ok(_) if should_archive => {
+            let detail = if locked == Some(true) { "locked" } else { "" };
+            db::insert_audit_entry(
+                pool,
+                &mapping.discord_thread_id,
+                identifier,
+                "thread_archived",
+                None,
+                detail,
+            )
+            .await?;
+        }
+        Ok(_) => {}
+        Err(e) => warn!(
             linear_issue_id,
             identifier,
             archived = should_archive,
             error = %e,
-            "Failed to update Discord thread archive state"
-        );
+            "Failed to update Discord thread state"
+        ),
     }
 
     // Update status cache
     db::upsert_cached_status(pool, linear_issue_id, new_status).await?;
+    db::insert_status_history(pool, linear_issue_id, new_status, new_status_type).await?;
 
     info!(
         linear_issue_id,
@@ -114,10 +281,301 @@ pub async fn sync_linear_to_discord(
     Ok(())
 }
 
+/// Apply the forum tags mapped (through `tag_label_map`) to the issue's current Linear labels to
+/// its thread, and remove mapped tags whose label was taken off.
+pub async fn sync_labels_to_tags(
+    discord: &impl DiscordPort,
+    config: &Config,
+    discord_thread_id: &str,
+    identifier: &str,
+    label_ids: &[String],
+) -> Result<(), AppError> {
+    if config.channels.iter().all(|c| c.tag_label_map.is_empty()) {
+        return Ok(());
+    }
+    let thread_id: u64 = discord_thread_id
+        .parse()
+        .map_err(|_| AppError::Internal("Invalid discord thread id".into()))?;
+
+    let channel = ChannelId::new(thread_id);
+    let thread = fetch_thread(discord, channel).await?;
+    let Some(channel_config) = thread
+        .parent_id
+        .and_then(|p| config.channel_config(p.get()))
+    else {
+        return Ok(());
+    };
+    let Some(tags) = tags_for_labels(&thread.applied_tags, channel_config, label_ids) else {
+        return Ok(());
+    };
+
+    discord
+        .edit_thread(channel, EditThread::new().applied_tags(tags))
+        .await?;
+    info!(identifier, "Synced Linear labels to forum tags");
+    Ok(())
+}
+
+/// Announce an assignee change in the issue's thread, mentioning the Discord user when the
+/// assignee has connected their account. The first sighting of an issue only records the
+/// current assignee.
+pub async fn sync_assignee_to_discord(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    config: &Config,
+    linear_issue_id: &str,
+    identifier: &str,
+    assignee: Option<&LinearAssignee>,
+) -> Result<(), AppError> {
+    let assignee_id = assignee.map(|a| a.id.as_str());
+    match db::get_cached_assignee(pool, linear_issue_id).await? {
+        None => {
+            db::upsert_cached_assignee(pool, linear_issue_id, assignee_id).await?;
+            return Ok(());
+        }
+        Some(previous) if previous.as_deref() == assignee_id => return Ok(()),
+        Some(_) => {}
+    }
+
+    let mapping = match db::get_mapping_by_linear_issue(pool, linear_issue_id).await? {
+        Some(m) => m,
+        None => return Ok(()),
+    };
+    let thread_id: u64 = mapping
+        .discord_thread_id
+        .parse()
+        .map_err(|_| AppError::Internal("Invalid discord thread id".into()))?;
+
+    let channel = ChannelId::new(thread_id);
+    let overrides = match channel_config_for_thread(discord, config, channel).await {
+        Some(c) => &c.message_templates,
+        None => &config.message_templates,
+    };
+    let mut assigned_user = None;
+    let message = match assignee {
+        Some(a) => {
+            let link = db::get_user_link_by_linear_user(pool, &a.id).await?;
+            let prefs = match &link {
+                Some(link) => db::get_notification_prefs(pool, &link.discord_user_id).await?,
+                None => NotificationPrefs::default(),
+            };
+            // Muted users are named rather than pinged.
+            let mention = match link {
+                Some(link) if !prefs.muted => {
+                    let mention = format!("<@{}>", link.discord_user_id);
+                    assigned_user = Some((link.discord_user_id, prefs));
+                    mention
+                }
+                _ => format!("**{}**", a.name),
+            };
+            templates::notification(
+                overrides,
+                Notification::Assigned,
+                &json!({ "identifier": identifier, "assignee": mention, "assignee_name": a.name }),
+            )
+        }
+        None => templates::notification(
+            overrides,
+            Notification::Unassigned,
+            &json!({ "identifier": identifier }),
+        ),
+    };
+    digest::notify(discord, pool, config, channel, &message).await?;
+    db::upsert_cached_assignee(pool, linear_issue_id, assignee_id).await?;
+    if let Some((discord_user_id, prefs)) = assigned_user {
+        subscriptions::notify_assignee(
+            discord,
+            overrides,
+            &prefs,
+            &discord_user_id,
+            &mapping.discord_thread_id,
+            identifier,
+        )
+        .await;
+    }
+
+    info!(
+        linear_issue_id,
+        identifier,
+        assignee = ?assignee.map(|a| &a.name),
+        "Posted assignee change to Discord"
+    );
+
+    Ok(())
+}
+
+/// Re-upload `uploads.linear.app` files referenced in `body` as Discord attachments; viewing
+/// them on Linear requires a login. Returns the body with mirrored references replaced by their
+/// filename. Files that fail to download, or exceed Discord's upload limit, keep their link.
+async fn mirror_linear_uploads(
+    linear: &impl LinearApi,
+    issue_id: &str,
+    body: &str,
+) -> (String, Vec<CreateAttachment>) {
+    let mut body = body.to_string();
+    let mut files = Vec::new();
+
+    for url in linear_upload_urls(&body) {
+        let (data, content_type) = match linear.download_linear_upload(issue_id, &url).await {
+            Ok(file) => file,
+            Err(e) => {
+                warn!(url, error = %e, "Failed to download Linear upload, leaving link");
+                continue;
+            }
+        };
+        if data.len() > DISCORD_MAX_UPLOAD_BYTES {
+            continue;
+        }
+
+        let filename = upload_filename(&url, &content_type);
+        body = replace_upload_reference(&body, &url, &format!("[attachment: {filename}]"));
+        files.push(CreateAttachment::bytes(data, filename));
+    }
+
+    (body, files)
+}
+
+/// Distinct `uploads.linear.app` URLs in a markdown body, in order of appearance.
+fn linear_upload_urls(body: &str) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    for (start, _) in body.match_indices(LINEAR_UPLOADS_PREFIX) {
+        let end = body[start..]
+            .find(|c: char| c.is_whitespace() || matches!(c, ')' | ']' | '>' | '"'))
+            .map_or(body.len(), |len| start + len);
+        let url = &body[start..end];
+        if !urls.iter().any(|u| u == url) {
+            urls.push(url.to_string());
+        }
+    }
+    urls
+}
+
+/// Replace markdown images/links pointing at `url` (or the bare URL) with `replacement`.
+fn replace_upload_reference(body: &str, url: &str, replacement: &str) -> String {
+    let mut out = body.to_string();
+    while let Some(pos) = out.find(&format!("]({url})")) {
+        let open = out[..pos].rfind('[').unwrap_or(pos);
+        let start = if out[..open].ends_with('!') {
+            open - 1
+        } else {
+            open
+        };
+        out.replace_range(start..pos + url.len() + 3, replacement);
+    }
+    out.replace(url, replacement)
+}
+
+/// Last path segment of the upload URL, with an extension from the content type if it has none.
+fn upload_filename(url: &str, content_type: &str) -> String {
+    let name = url
+        .split(['?', '#'])
+        .next()
+        .and_then(|path| path.rsplit('/').next())
+        .filter(|s| !s.is_empty())
+        .unwrap_or("attachment");
+    if name.contains('.') {
+        return name.to_string();
+    }
+    let extension = content_type
+        .split(';')
+        .next()
+        .and_then(|mime| mime.split('/').nth(1))
+        .unwrap_or("bin");
+    format!("{name}.{extension}")
+}
+
+/// Archive threads whose close grace period has elapsed. Reopening the issue during the grace
+/// period clears its pending row, and unlinked threads are skipped.
+pub async fn archive_due_threads(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+) -> Result<(), AppError> {
+    for pending in db::get_due_pending_archives(pool).await? {
+        let thread_id = pending.discord_thread_id.as_str();
+        db::delete_pending_archive(pool, thread_id).await?;
+
+        let Some(mapping) = db::get_mapping_by_discord_thread(pool, thread_id).await? else {
+            continue;
+        };
+
+        let channel = match thread_id.parse() {
+            Ok(id) => ChannelId::new(id),
+            Err(_) => {
+                warn!(thread_id, "Invalid Discord thread id in pending archive");
+                continue;
+            }
+        };
+        let mut edit = EditThread::new().archived(true);
+        if pending.lock_thread {
+            edit = edit.locked(true);
+        }
+        match discord.edit_thread(channel, edit).await {
+            Ok(_) => {
+                let detail = if pending.lock_thread { "locked" } else { "" };
+                db::insert_audit_entry(
+                    pool,
+                    thread_id,
+                    &mapping.linear_identifier,
+                    "thread_archived",
+                    None,
+                    detail,
+                )
+                .await?;
+                info!(
+                    thread_id,
+                    locked = pending.lock_thread,
+                    "Archived closed thread"
+                );
+            }
+            Err(e) => warn!(thread_id, error = %e, "Failed to archive closed thread"),
+        }
+    }
+
+    Ok(())
+}
+
+/// Rename threads whose Linear issue title changed, skipping any renamed too recently. Skipped
+/// renames stay pending and are retried on a later pass.
+pub async fn sync_title_renames(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+) -> Result<(), AppError> {
+    for rename in db::get_pending_renames(pool, THREAD_RENAME_INTERVAL_SECS).await? {
+        let channel = match rename.discord_thread_id.parse() {
+            Ok(id) => ChannelId::new(id),
+            Err(_) => {
+                warn!(thread_id = %rename.discord_thread_id, "Invalid Discord thread id in mapping");
+                continue;
+            }
+        };
+
+        let name = thread_name_for_title(&rename.title);
+        match discord
+            .edit_thread(channel, EditThread::new().name(name))
+            .await
+        {
+            Ok(_) => {
+                db::mark_title_applied(pool, &rename.linear_issue_id, &rename.title).await?;
+                info!(
+                    thread_id = %rename.discord_thread_id,
+                    title = %rename.title,
+                    "Renamed Discord thread to match Linear title"
+                );
+            }
+            Err(e) => {
+                warn!(thread_id = %rename.discord_thread_id, error = %e, "Failed to rename thread");
+            }
+        }
+    }
+
+    Ok(())
+}
+
 pub async fn sync_linear_comments_to_discord(
-    http: &Http,
+    discord: &impl DiscordPort,
     pool: &SqlitePool,
-    linear: &LinearClient,
+    config: &Config,
+    linear: &impl LinearApi,
     linear_issue_id: &str,
     identifier: &str,
 ) -> Result<(), AppError> {
@@ -132,7 +590,36 @@ pub async fn sync_linear_comments_to_discord(
         .map_err(|_| AppError::Internal("Invalid discord thread id".into()))?;
     let channel = ChannelId::new(thread_id);
 
-    let comments = linear.get_issue_comments(linear_issue_id).await?;
+    let since = db::get_comment_cursor(pool, linear_issue_id).await?;
+    let comments = linear
+        .get_issue_comments(linear_issue_id, since.as_deref())
+        .await?;
+    if comments.is_empty() {
+        return Ok(());
+    }
+    // Only move the cursor past this batch if every comment in it was handled; otherwise the
+    // next pass fetches them again and `synced_comments` skips the ones already posted.
+    let newest = comments.iter().map(|c| c.created_at.as_str()).max();
+    let mut advance_cursor = true;
+    let overrides = match channel_config_for_thread(discord, config, channel).await {
+        Some(c) => &c.message_templates,
+        None => &config.message_templates,
+    };
+
+    let webhook = match comment_webhook(discord, pool, config, channel).await {
+        Ok(webhook) => webhook,
+        Err(e) => {
+            warn!(identifier, error = %e, "Failed to set up comment webhook, posting as the bot");
+            None
+        }
+    };
+
+    // With debouncing on, comments posted by the bot in one pass are combined into a single
+    // message (split only at Discord's size and attachment limits).
+    let batch_comments = webhook.is_none() && config.digest_window_secs > 0;
+    let mut batch: Vec<(&LinearComment, String)> = Vec::new();
+    let mut batch_files: Vec<CreateAttachment> = Vec::new();
+    let mut relayed: Vec<&LinearComment> = Vec::new();
 
     for comment in &comments {
         match db::is_comment_synced(pool, &comment.id).await {
@@ -144,30 +631,87 @@ pub async fn sync_linear_comments_to_discord(
                     error = %e,
                     "Failed to check comment sync status"
                 );
+                advance_cursor = false;
                 continue;
             }
         }
+        if !relays_comment_author(config, comment) {
+            continue;
+        }
 
-        let message = format!(
-            "**{}** commented on **{}**:\n> {}",
-            comment.author_name,
-            identifier,
-            comment.body.replace('\n', "\n> ")
-        );
+        let (body, files) = mirror_linear_uploads(linear, linear_issue_id, &comment.body).await;
+        let body = format::linear_to_discord(&body);
 
-        let chunks = split_for_discord(&message);
-        let mut first_message_id: Option<String> = None;
-        for chunk in &chunks {
-            let sent = channel.say(http, chunk).await?;
-            if first_message_id.is_none() {
-                first_message_id = Some(sent.id.to_string());
+        if batch_comments {
+            if !batch.is_empty() && batch_files.len() + files.len() > DISCORD_MAX_ATTACHMENTS {
+                let files = std::mem::take(&mut batch_files);
+                post_comment_batch(
+                    discord,
+                    pool,
+                    channel,
+                    linear_issue_id,
+                    identifier,
+                    &batch,
+                    files,
+                )
+                .await?;
+                batch.clear();
             }
+            let text = bot_comment_text(overrides, identifier, &comment.author_name, &body);
+            batch.push((comment, text));
+            batch_files.extend(files);
+            relayed.push(comment);
+            continue;
         }
 
+        let first_message_id = match &webhook {
+            Some(webhook) => {
+                match post_comment_as_author(discord, webhook, channel, comment, &body, files).await
+                {
+                    Ok(id) => id,
+                    Err(e) => {
+                        // Most likely the webhook was deleted; recreate it on the next pass.
+                        if let Some(parent) = webhook.channel_id {
+                            db::delete_channel_webhook(pool, &parent.to_string()).await?;
+                        }
+                        return Err(e);
+                    }
+                }
+            }
+            None => {
+                let text = bot_comment_text(overrides, identifier, &comment.author_name, &body);
+                send_chunked(discord, channel, &text, files).await?
+            }
+        };
+
         let discord_message_id = first_message_id
             .ok_or_else(|| AppError::Internal("Comment produced no Discord messages".into()))?;
 
         db::insert_synced_comment(pool, &comment.id, linear_issue_id, &discord_message_id).await?;
+        db::insert_audit_entry(
+            pool,
+            &mapping.discord_thread_id,
+            identifier,
+            "comment_relayed",
+            None,
+            &comment.author_name,
+        )
+        .await?;
+
+        // Edits and deletions are only mirrored onto comments that fit in one message.
+        let via_webhook = webhook.is_some();
+        let text = relay_text(overrides, via_webhook, identifier, comment, &body);
+        if split_for_discord(&text).len() == 1 {
+            db::insert_relayed_comment(
+                pool,
+                &comment.id,
+                linear_issue_id,
+                &discord_message_id,
+                via_webhook,
+                &comment.updated_at,
+            )
+            .await?;
+        }
 
         info!(
             comment_id = %comment.id,
@@ -175,7 +719,388 @@ pub async fn sync_linear_comments_to_discord(
             author = %comment.author_name,
             "Synced Linear comment to Discord"
         );
+        relayed.push(comment);
+    }
+
+    if !batch.is_empty() {
+        post_comment_batch(
+            discord,
+            pool,
+            channel,
+            linear_issue_id,
+            identifier,
+            &batch,
+            batch_files,
+        )
+        .await?;
+    }
+
+    if let Some(newest) = newest.filter(|_| advance_cursor) {
+        db::upsert_comment_cursor(pool, linear_issue_id, newest).await?;
+    }
+
+    let thread_id = &mapping.discord_thread_id;
+    if let Some(update) =
+        subscriptions::comments_message(overrides, thread_id, identifier, &relayed)
+    {
+        let notified =
+            subscriptions::notify_subscribers(discord, pool, thread_id, Update::Comments, &update);
+        if let Err(e) = notified.await {
+            warn!(identifier, error = %e, "Failed to notify subscribers of comments");
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether the comment's author passes the configured bot, deny, and allow filters. Entries match
+/// the author's Linear user ID or (case-insensitively) their email.
+fn relays_comment_author(config: &Config, comment: &LinearComment) -> bool {
+    let Some(author_id) = &comment.author_id else {
+        return !config.skip_bot_comments && config.comment_author_allowlist.is_empty();
+    };
+    let matches = |entry: &String| {
+        entry == author_id
+            || comment
+                .author_email
+                .as_deref()
+                .is_some_and(|email| entry.eq_ignore_ascii_case(email))
+    };
+    !config.comment_author_denylist.iter().any(matches)
+        && (config.comment_author_allowlist.is_empty()
+            || config.comment_author_allowlist.iter().any(matches))
+}
+
+/// The message text a relayed comment is posted with: the body as is through the webhook (which
+/// carries the author's name), or quoted under an attribution line by the bot.
+fn relay_text(
+    overrides: &HashMap<String, String>,
+    via_webhook: bool,
+    identifier: &str,
+    comment: &LinearComment,
+    body: &str,
+) -> String {
+    if via_webhook {
+        body.to_string()
+    } else {
+        bot_comment_text(overrides, identifier, &comment.author_name, body)
+    }
+}
+
+/// Mirror edits and deletions of relayed Linear comments onto their Discord messages: edited
+/// comments are re-rendered in place and deleted ones have their message removed.
+pub async fn sync_linear_comment_changes(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    config: &Config,
+    linear: &impl LinearApi,
+    linear_issue_id: &str,
+    identifier: &str,
+) -> Result<(), AppError> {
+    let relayed = db::get_relayed_comments(pool, linear_issue_id).await?;
+    if relayed.is_empty() {
+        return Ok(());
+    }
+    let mapping = match db::get_mapping_by_linear_issue(pool, linear_issue_id).await? {
+        Some(m) => m,
+        None => return Ok(()),
+    };
+    let channel = ChannelId::new(
+        mapping
+            .discord_thread_id
+            .parse()
+            .map_err(|_| AppError::Internal("Invalid discord thread id".into()))?,
+    );
+
+    let ids: Vec<String> = relayed
+        .iter()
+        .map(|r| r.linear_comment_id.clone())
+        .collect();
+    let current = linear.get_comments_by_ids(&ids).await?;
+    let overrides = match channel_config_for_thread(discord, config, channel).await {
+        Some(c) => &c.message_templates,
+        None => &config.message_templates,
+    };
+
+    let mut webhook = None;
+    for row in &relayed {
+        let comment = current.iter().find(|c| c.id == row.linear_comment_id);
+        if comment.is_some_and(|c| c.updated_at == row.linear_updated_at) {
+            continue;
+        }
+        if row.via_webhook && webhook.is_none() {
+            webhook = comment_webhook(discord, pool, config, channel).await?;
+        }
+        let message_id = match row.discord_message_id.parse() {
+            Ok(id) => MessageId::new(id),
+            Err(_) => {
+                db::delete_relayed_comment(pool, &row.linear_comment_id).await?;
+                continue;
+            }
+        };
+
+        let result = match comment {
+            Some(comment) => {
+                // Uploads are already attached to the message; editing the text leaves them.
+                let (body, _) = mirror_linear_uploads(linear, linear_issue_id, &comment.body).await;
+                let body = format::linear_to_discord(&body);
+                let text = relay_text(overrides, row.via_webhook, identifier, comment, &body);
+                if split_for_discord(&text).len() > 1 {
+                    warn!(
+                        comment_id = %comment.id,
+                        identifier,
+                        "Edited comment no longer fits in one message, not updating"
+                    );
+                    db::set_relayed_comment_updated_at(pool, &comment.id, &comment.updated_at)
+                        .await?;
+                    continue;
+                }
+                let webhook = webhook.as_ref();
+                edit_relayed_message(discord, webhook, row, channel, message_id, text).await
+            }
+            None => {
+                delete_relayed_message(discord, webhook.as_ref(), row, channel, message_id).await
+            }
+        };
+
+        match result {
+            Ok(()) => {}
+            // The message was already removed in Discord; stop tracking it.
+            Err(AppError::Discord(serenity::Error::Http(e)))
+                if e.status_code() == Some(StatusCode::NOT_FOUND) =>
+            {
+                db::delete_relayed_comment(pool, &row.linear_comment_id).await?;
+                continue;
+            }
+            Err(e) => return Err(e),
+        }
+
+        match comment {
+            Some(comment) => {
+                db::set_relayed_comment_updated_at(pool, &comment.id, &comment.updated_at).await?;
+                info!(
+                    comment_id = %comment.id,
+                    identifier,
+                    "Synced Linear comment edit to Discord"
+                );
+            }
+            None => {
+                db::delete_relayed_comment(pool, &row.linear_comment_id).await?;
+                info!(
+                    comment_id = %row.linear_comment_id,
+                    identifier,
+                    "Removed Discord message for deleted Linear comment"
+                );
+            }
+        }
     }
 
     Ok(())
 }
+
+async fn edit_relayed_message(
+    discord: &impl DiscordPort,
+    webhook: Option<&Webhook>,
+    row: &RelayedComment,
+    channel: ChannelId,
+    message_id: MessageId,
+    text: String,
+) -> Result<(), AppError> {
+    match webhook.filter(|_| row.via_webhook) {
+        Some(webhook) => {
+            let edit = EditWebhookMessage::new().content(text);
+            discord
+                .edit_webhook_message(webhook, channel, message_id, edit)
+                .await?;
+        }
+        None => {
+            discord
+                .edit_message(channel, message_id, EditMessage::new().content(text))
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+async fn delete_relayed_message(
+    discord: &impl DiscordPort,
+    webhook: Option<&Webhook>,
+    row: &RelayedComment,
+    channel: ChannelId,
+    message_id: MessageId,
+) -> Result<(), AppError> {
+    match webhook.filter(|_| row.via_webhook) {
+        Some(webhook) => {
+            discord
+                .delete_webhook_message(webhook, channel, message_id)
+                .await
+        }
+        None => discord.delete_message(channel, message_id).await,
+    }
+}
+
+/// A comment quoted under an attribution line, as the bot posts it.
+pub fn bot_comment_text(
+    overrides: &HashMap<String, String>,
+    identifier: &str,
+    author: &str,
+    body: &str,
+) -> String {
+    templates::notification(
+        overrides,
+        Notification::Comment,
+        &json!({
+            "identifier": identifier,
+            "author": author,
+            "body": body,
+            "quoted": format!("> {}", body.replace('\n', "\n> ")),
+        }),
+    )
+}
+
+/// Post several comments as one bot message and record them all as synced against it.
+async fn post_comment_batch(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    channel: ChannelId,
+    linear_issue_id: &str,
+    identifier: &str,
+    batch: &[(&LinearComment, String)],
+    files: Vec<CreateAttachment>,
+) -> Result<(), AppError> {
+    let message = batch
+        .iter()
+        .map(|(_, text)| text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let discord_message_id = send_chunked(discord, channel, &message, files)
+        .await?
+        .ok_or_else(|| AppError::Internal("Comment produced no Discord messages".into()))?;
+
+    let thread_id = channel.to_string();
+    for (comment, _) in batch {
+        db::insert_synced_comment(pool, &comment.id, linear_issue_id, &discord_message_id).await?;
+        db::insert_audit_entry(
+            pool,
+            &thread_id,
+            identifier,
+            "comment_relayed",
+            None,
+            &comment.author_name,
+        )
+        .await?;
+    }
+
+    info!(
+        identifier,
+        comments = batch.len(),
+        "Synced batched Linear comments to Discord"
+    );
+    Ok(())
+}
+
+/// Send `message` split at Discord's length limit. Returns the first message's ID.
+async fn send_chunked(
+    discord: &impl DiscordPort,
+    channel: ChannelId,
+    message: &str,
+    files: Vec<CreateAttachment>,
+) -> Result<Option<String>, AppError> {
+    // Mirrored uploads ride along with the last chunk so they render below the text.
+    let chunks = split_for_discord(message);
+    let last = chunks.len().saturating_sub(1);
+    let mut files = Some(files);
+    let mut first_message_id: Option<String> = None;
+    for (i, chunk) in chunks.iter().enumerate() {
+        let mut builder = CreateMessage::new().content(chunk);
+        if i == last {
+            builder = builder.add_files(files.take().unwrap_or_default());
+        }
+        let sent = discord.send_message(channel, builder).await?;
+        if first_message_id.is_none() {
+            first_message_id = Some(sent.id.to_string());
+        }
+    }
+    Ok(first_message_id)
+}
+
+/// Post a comment through the channel webhook under the Linear author's name and avatar.
+/// Returns the first message's ID.
+async fn post_comment_as_author(
+    discord: &impl DiscordPort,
+    webhook: &Webhook,
+    thread: ChannelId,
+    comment: &LinearComment,
+    body: &str,
+    files: Vec<CreateAttachment>,
+) -> Result<Option<String>, AppError> {
+    let username: String = format!("{} (Linear)", comment.author_name)
+        .chars()
+        .take(DISCORD_MAX_WEBHOOK_USERNAME_CHARS)
+        .collect();
+
+    let chunks = split_for_discord(body);
+    let last = chunks.len().saturating_sub(1);
+    let mut files = Some(files);
+    let mut first_message_id: Option<String> = None;
+    for (i, chunk) in chunks.iter().enumerate() {
+        let mut builder = ExecuteWebhook::new().content(chunk).username(&username);
+        if let Some(avatar) = &comment.author_avatar_url {
+            builder = builder.avatar_url(avatar);
+        }
+        if i == last {
+            builder = builder.add_files(files.take().unwrap_or_default());
+        }
+        let sent = discord.execute_webhook(webhook, thread, builder).await?;
+        if first_message_id.is_none() {
+            first_message_id = Some(sent.id.to_string());
+        }
+    }
+    Ok(first_message_id)
+}
+
+/// The webhook used to relay comments into `thread`, if its forum channel has
+/// `comment_webhooks` enabled. Created on first use and cached in the database; a webhook the
+/// bot created earlier is reused if the cache was lost.
+async fn comment_webhook(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    config: &Config,
+    thread: ChannelId,
+) -> Result<Option<Webhook>, AppError> {
+    let parent = match fetch_thread(discord, thread).await?.parent_id {
+        Some(p) => p,
+        None => return Ok(None),
+    };
+    if !config
+        .channel_config(parent.get())
+        .is_some_and(|c| c.comment_webhooks)
+    {
+        return Ok(None);
+    }
+
+    let parent_key = parent.to_string();
+    if let Some(json) = db::get_channel_webhook(pool, &parent_key).await? {
+        return Ok(Some(serde_json::from_str(&json)?));
+    }
+
+    let existing = discord.webhooks(parent).await?.into_iter().find(|w| {
+        w.name.as_deref() == Some(COMMENT_WEBHOOK_NAME)
+            && w.token.is_some()
+            && w.application_id.is_some()
+            && w.application_id == discord.application_id()
+    });
+    let webhook = match existing {
+        Some(w) => w,
+        None => {
+            discord
+                .create_webhook(parent, CreateWebhook::new(COMMENT_WEBHOOK_NAME))
+                .await?
+        }
+    };
+
+    db::upsert_channel_webhook(pool, &parent_key, &serde_json::to_string(&webhook)?).await?;
+    info!(channel_id = %parent, webhook_id = %webhook.id, "Set up comment relay webhook");
+
+    Ok(Some(webhook))
+}
@@ -1,4 +1,17 @@
 pub mod backfill;
+pub mod digest;
 pub mod discord_to_linear;
+pub mod discuss;
+pub mod drift;
+pub mod filter;
 pub mod linear_to_discord;
+pub mod outbox;
 pub mod reconcile;
+pub mod reminders;
+pub mod repost;
+pub mod routing;
+pub mod sla;
+pub mod stale;
+pub mod status_embed;
+pub mod thread;
+pub mod votes;
@@ -0,0 +1,248 @@
+//! Durable retry queue for sync operations that failed.
+//!
+//! When creating an issue for a new thread (or from its intake form), mirroring a Discord reply
+//! into Linear, or posting a status change to Discord fails, the operation is written to
+//! `pending_operations` and the poller retries it every pass (backing off after repeated
+//! failures) until it succeeds. Queued operations survive restarts, so a transient failure never
+//! leaves a thread untracked.
+//!
+//! An operation that still fails after `OUTBOX_MAX_ATTEMPTS` retries is moved to `dead_letters`
+//! and reported in the ops channel. `/linear retry-failed` puts dead letters back in the queue.
+
+use serde::{Deserialize, Serialize};
+use serenity::all::{ChannelId, CreateMessage};
+use sqlx::SqlitePool;
+use tracing::{error, info, warn};
+
+use crate::config::Config;
+use crate::db;
+use crate::discord::intake;
+use crate::discord::port::DiscordPort;
+use crate::error::AppError;
+use crate::linear::api::LinearApi;
+use crate::linear::client::CommentAuthor;
+use crate::sync::discord_to_linear::{
+    create_linear_comment, sync_discord_to_linear, IntakeAnswers,
+};
+use crate::sync::linear_to_discord::sync_linear_to_discord;
+use crate::sync::thread::fetch_thread;
+
+/// Longest gap between retries of one operation.
+const MAX_RETRY_DELAY_SECS: u64 = 3600;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Operation {
+    /// Create the Linear issue for a forum thread.
+    CreateIssue { thread_id: u64 },
+    /// Post a Discord reply, already formatted, as a Linear comment.
+    CreateComment {
+        issue_id: String,
+        body: String,
+        /// Who the comment is posted as; see `reply_author`.
+        #[serde(default)]
+        author: Option<CommentAuthor>,
+        discord_message_id: String,
+    },
+    /// Bring a thread in line with its issue's current workflow state.
+    SyncStatus { issue_id: String },
+    /// Create the issue for an intake form that was submitted or skipped.
+    FileIntake {
+        thread_id: u64,
+        answers: Option<IntakeAnswers>,
+    },
+}
+
+impl Operation {
+    /// Identifies the operation's target; a target has at most one queued operation.
+    fn dedupe_key(&self) -> String {
+        match self {
+            Operation::CreateIssue { thread_id } => format!("create_issue:{thread_id}"),
+            Operation::CreateComment {
+                discord_message_id, ..
+            } => format!("create_comment:{discord_message_id}"),
+            Operation::SyncStatus { issue_id } => format!("sync_status:{issue_id}"),
+            Operation::FileIntake { thread_id, .. } => format!("file_intake:{thread_id}"),
+        }
+    }
+}
+
+/// Queue `operation` after it failed with `error`. Failing to queue is logged, not returned:
+/// callers are already handling the original error.
+pub async fn enqueue(pool: &SqlitePool, operation: &Operation, error: &AppError) {
+    let key = operation.dedupe_key();
+    let payload = match serde_json::to_string(operation) {
+        Ok(payload) => payload,
+        Err(e) => {
+            error!(operation = %key, error = %e, "Failed to serialize operation for retry");
+            return;
+        }
+    };
+    match db::insert_pending_operation(pool, &key, &payload, &error.to_string()).await {
+        Ok(()) => warn!(operation = %key, error = %error, "Queued failed operation for retry"),
+        Err(e) => error!(operation = %key, error = %e, "Failed to queue operation for retry"),
+    }
+}
+
+/// Retry every queued operation that is due. Successes are removed from the queue; failures are
+/// rescheduled with exponential backoff, or dead-lettered once they run out of attempts.
+pub async fn process_outbox(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    config: &Config,
+    linear: &impl LinearApi,
+) -> Result<(), AppError> {
+    for pending in db::get_due_pending_operations(pool).await? {
+        let operation: Operation = match serde_json::from_str(&pending.payload) {
+            Ok(operation) => operation,
+            Err(e) => {
+                error!(id = pending.id, error = %e, "Dropping unreadable queued operation");
+                db::delete_pending_operation(pool, pending.id).await?;
+                continue;
+            }
+        };
+        let key = operation.dedupe_key();
+        let attempts = pending.attempts + 1;
+
+        match run(discord, pool, config, linear, &operation).await {
+            Ok(()) => {
+                db::delete_pending_operation(pool, pending.id).await?;
+                info!(operation = %key, attempts, "Queued operation succeeded");
+            }
+            Err(e) if attempts >= config.outbox_max_attempts => {
+                db::dead_letter_pending_operation(pool, pending.id, &e.to_string()).await?;
+                error!(operation = %key, attempts, error = %e, "Queued operation gave up");
+                alert_ops(discord, config, &key, attempts, &e).await;
+            }
+            Err(e) => {
+                let delay_secs = retry_delay_secs(attempts);
+                db::reschedule_pending_operation(pool, pending.id, delay_secs, &e.to_string())
+                    .await?;
+                warn!(
+                    operation = %key,
+                    attempts,
+                    delay_secs,
+                    error = %e,
+                    "Queued operation failed again"
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn run(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    config: &Config,
+    linear: &impl LinearApi,
+    operation: &Operation,
+) -> Result<(), AppError> {
+    match operation {
+        Operation::CreateIssue { thread_id } => {
+            let thread = fetch_thread(discord, ChannelId::new(*thread_id)).await?;
+            // The forum may have been removed from the config since.
+            let Some(channel_config) = thread
+                .parent_id
+                .and_then(|p| config.channel_config(p.get()))
+            else {
+                return Ok(());
+            };
+            // Skips threads that were mapped in the meantime (e.g. by the reconcile pass).
+            sync_discord_to_linear(discord, pool, channel_config, linear, &thread).await
+        }
+        Operation::CreateComment {
+            issue_id,
+            body,
+            author,
+            discord_message_id,
+        } => {
+            let comment_id = create_linear_comment(
+                pool,
+                linear,
+                issue_id,
+                body,
+                author.as_ref(),
+                discord_message_id,
+            )
+            .await?;
+            if let Some(mapping) = db::get_mapping_by_linear_issue(pool, issue_id).await? {
+                db::insert_audit_entry(
+                    pool,
+                    &mapping.discord_thread_id,
+                    &mapping.linear_identifier,
+                    "comment_posted",
+                    None,
+                    &comment_id,
+                )
+                .await?;
+            }
+            Ok(())
+        }
+        Operation::SyncStatus { issue_id } => {
+            let issue = linear.get_issue(issue_id).await?;
+            // A later poll or webhook may have synced the state already.
+            if db::get_cached_status(pool, issue_id).await?.as_deref() == Some(&issue.status_name) {
+                return Ok(());
+            }
+            sync_linear_to_discord(discord, pool, config, linear, &issue).await
+        }
+        Operation::FileIntake { thread_id, answers } => {
+            let thread = fetch_thread(discord, ChannelId::new(*thread_id)).await?;
+            let Some(channel_config) = thread
+                .parent_id
+                .and_then(|p| config.channel_config(p.get()))
+            else {
+                // The forum is no longer monitored, so there is nothing left to file.
+                db::delete_pending_intake(pool, &thread.id.to_string()).await?;
+                return Ok(());
+            };
+            if db::get_mapping_by_discord_thread(pool, &thread.id.to_string())
+                .await?
+                .is_some()
+            {
+                db::delete_pending_intake(pool, &thread.id.to_string()).await?;
+                return Ok(());
+            }
+            intake::file_intake(
+                discord,
+                pool,
+                channel_config,
+                linear,
+                &thread,
+                answers.as_ref(),
+            )
+            .await
+        }
+    }
+}
+
+/// Tell the ops channel, if one is configured, that an operation was dead-lettered.
+async fn alert_ops(
+    discord: &impl DiscordPort,
+    config: &Config,
+    key: &str,
+    attempts: i64,
+    error: &AppError,
+) {
+    let Some(channel_id) = config.ops_channel_id else {
+        return;
+    };
+    let content = format!(
+        "Gave up on sync operation `{key}` after {attempts} attempts: {error}\n\
+         Run `/linear retry-failed` once the problem is fixed to queue it again."
+    );
+    let message = CreateMessage::new().content(content);
+    if let Err(e) = discord
+        .send_message(ChannelId::new(channel_id), message)
+        .await
+    {
+        warn!(operation = %key, error = %e, "Failed to alert ops channel");
+    }
+}
+
+/// Delay before the retry after `attempts` failed ones: a minute, doubling up to an hour.
+fn retry_delay_secs(attempts: i64) -> u64 {
+    let doublings = attempts.clamp(1, 7) as u32 - 1;
+    (60u64 << doublings).min(MAX_RETRY_DELAY_SECS)
+}
@@ -1,13 +1,15 @@
 use std::collections::HashMap;
 
-use serenity::all::{Channel, ChannelId, EditThread, GuildId, Http};
+use serenity::all::{ChannelId, EditThread, GuildId};
 use sqlx::SqlitePool;
 use tracing::{info, warn};
 
 use crate::config::Config;
 use crate::db;
+use crate::discord::port::DiscordPort;
 use crate::error::AppError;
-use crate::linear::client::LinearClient;
+use crate::linear::api::LinearApi;
+use crate::linear::client::StateCategory;
 use crate::sync::discord_to_linear::sync_discord_to_linear;
 
 const BATCH_SIZE: usize = 100;
@@ -23,17 +25,17 @@ const BATCH_SIZE: usize = 100;
 /// archived in Discord before its issue is created won't be picked up; the reconcile interval is
 /// expected to be well under Discord's forum auto-archive duration.
 pub async fn reconcile_discord_to_linear(
-    http: &Http,
+    discord: &impl DiscordPort,
     pool: &SqlitePool,
     config: &Config,
-    linear: &LinearClient,
+    linear: &impl LinearApi,
 ) -> Result<(), AppError> {
     let mut created = 0usize;
     let mut failed = 0usize;
 
     for guild_id in config.unique_guild_ids() {
         let guild = GuildId::new(guild_id);
-        let active = match guild.get_active_threads(http).await {
+        let active = match discord.active_threads(guild).await {
             Ok(a) => a,
             Err(e) => {
                 warn!(guild_id, error = %e, "Failed to fetch active threads for reconcile");
@@ -41,7 +43,7 @@ pub async fn reconcile_discord_to_linear(
             }
         };
 
-        for thread in &active.threads {
+        for thread in &active {
             // Only threads in a monitored forum channel.
             let parent_id = match thread.parent_id {
                 Some(p) => p.get(),
@@ -62,7 +64,7 @@ pub async fn reconcile_discord_to_linear(
                 }
             }
 
-            match sync_discord_to_linear(http, pool, channel_config, linear, thread).await {
+            match sync_discord_to_linear(discord, pool, channel_config, linear, thread).await {
                 Ok(()) => {
                     created += 1;
                     info!(
@@ -99,9 +101,10 @@ pub async fn reconcile_discord_to_linear(
 /// past completions don't require manual cleanup. Also primes the status cache so the
 /// poller doesn't fire spurious transitions immediately after.
 pub async fn reconcile_archive_state(
-    http: &Http,
+    discord: &impl DiscordPort,
     pool: &SqlitePool,
-    linear: &LinearClient,
+    config: &Config,
+    linear: &impl LinearApi,
 ) -> Result<(), AppError> {
     let mappings = db::get_all_tracked_issues(pool).await?;
     if mappings.is_empty() {
@@ -112,13 +115,14 @@ pub async fn reconcile_archive_state(
     info!(count = mappings.len(), "Reconciling archive state");
 
     // Fetch current Linear state for all tracked issues in batches.
-    let mut status_by_id: HashMap<String, (String, String)> = HashMap::new();
+    let mut status_by_id: HashMap<String, (String, StateCategory)> = HashMap::new();
     for chunk in mappings.chunks(BATCH_SIZE) {
         let ids: Vec<String> = chunk.iter().map(|m| m.linear_issue_id.clone()).collect();
         match linear.get_issues_by_ids(&ids).await {
             Ok(issues) => {
                 for issue in issues {
-                    status_by_id.insert(issue.id, (issue.status_name, issue.status_type));
+                    let category = issue.category();
+                    status_by_id.insert(issue.id, (issue.status_name, category));
                 }
             }
             Err(e) => {
@@ -133,7 +137,7 @@ pub async fn reconcile_archive_state(
     let mut missing_in_linear = 0usize;
 
     for mapping in &mappings {
-        let (status_name, status_type) = match status_by_id.get(&mapping.linear_issue_id) {
+        let (status_name, category) = match status_by_id.get(&mapping.linear_issue_id) {
             Some(s) => s.clone(),
             None => {
                 missing_in_linear += 1;
@@ -141,8 +145,6 @@ pub async fn reconcile_archive_state(
             }
         };
 
-        let desired_archived = status_type == "completed";
-
         let thread_id: u64 = match mapping.discord_thread_id.parse() {
             Ok(id) => id,
             Err(_) => {
@@ -157,16 +159,13 @@ pub async fn reconcile_archive_state(
         let channel = ChannelId::new(thread_id);
 
         // Read the thread's current archive state so we only write when it differs.
-        let current_archived = match channel.to_channel(http).await {
-            Ok(Channel::Guild(gc)) => gc.thread_metadata.map(|m| m.archived).unwrap_or(false),
-            Ok(_) => {
-                warn!(
-                    identifier = %mapping.linear_identifier,
-                    thread_id = %mapping.discord_thread_id,
-                    "Mapped channel is not a guild channel"
-                );
-                continue;
-            }
+        let (current_archived, archive_on_close) = match discord.channel(channel).await {
+            Ok(gc) => (
+                gc.thread_metadata.map(|m| m.archived).unwrap_or(false),
+                gc.parent_id
+                    .and_then(|p| config.channel_config(p.get()))
+                    .is_none_or(|c| c.archive_on_close),
+            ),
             Err(e) => {
                 warn!(
                     identifier = %mapping.linear_identifier,
@@ -188,13 +187,21 @@ pub async fn reconcile_archive_state(
             );
         }
 
+        // Closed issues in channels that opt out of archiving are left as they are.
+        let closed = category.is_terminal();
+        if closed && !archive_on_close {
+            already_correct += 1;
+            continue;
+        }
+        let desired_archived = closed;
+
         if current_archived == desired_archived {
             already_correct += 1;
             continue;
         }
 
-        if let Err(e) = channel
-            .edit_thread(http, EditThread::new().archived(desired_archived))
+        if let Err(e) = discord
+            .edit_thread(channel, EditThread::new().archived(desired_archived))
             .await
         {
             warn!(
@@ -0,0 +1,138 @@
+use chrono::{NaiveDate, Utc};
+use serde_json::json;
+use serenity::all::ChannelId;
+use sqlx::SqlitePool;
+use tracing::{error, info, warn};
+
+use crate::config::{Config, SharedConfig};
+use crate::db;
+use crate::discord::port::DiscordPort;
+use crate::error::AppError;
+use crate::linear::api::LinearApi;
+use crate::sync::digest;
+use crate::sync::thread::channel_config_for_thread;
+use crate::templates::{self, Notification};
+
+const BATCH_SIZE: usize = 100;
+
+const REMINDER_UPCOMING: &str = "upcoming";
+const REMINDER_OVERDUE: &str = "overdue";
+
+/// Periodically post due date reminders in threads whose channel has `due_date_reminders`
+/// enabled. Runs alongside the status poller on its own, much longer, interval.
+pub async fn run_reminders(
+    discord: impl DiscordPort,
+    pool: SqlitePool,
+    linear: impl LinearApi,
+    config: SharedConfig,
+) {
+    let interval_secs = config.current().due_reminder_interval_secs;
+    info!(interval_secs, "Starting due date reminder task");
+
+    loop {
+        tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
+
+        if let Err(e) = send_due_date_reminders(&discord, &pool, &linear, &config.current()).await {
+            error!(error = %e, "Due date reminder pass failed");
+        }
+    }
+}
+
+/// Remind threads of open issues that are due within their channel's lead time, and again once
+/// they are overdue.
+async fn send_due_date_reminders(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    linear: &impl LinearApi,
+    config: &Config,
+) -> Result<(), AppError> {
+    if !config.channels.iter().any(|c| c.due_date_reminders) {
+        return Ok(());
+    }
+
+    let mappings = db::get_all_tracked_issues(pool).await?;
+    let today = Utc::now().date_naive();
+    let mut sent = 0usize;
+
+    for chunk in mappings.chunks(BATCH_SIZE) {
+        let ids: Vec<String> = chunk.iter().map(|m| m.linear_issue_id.clone()).collect();
+        let issues = linear.get_issues_by_ids(&ids).await?;
+
+        for issue in issues {
+            if issue.category().is_terminal() {
+                continue;
+            }
+            let due_date = match issue
+                .due_date
+                .as_deref()
+                .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+            {
+                Some(d) => d,
+                None => continue,
+            };
+            let mapping = match chunk.iter().find(|m| m.linear_issue_id == issue.id) {
+                Some(m) => m,
+                None => continue,
+            };
+            let thread_id = match mapping.discord_thread_id.parse() {
+                Ok(id) => ChannelId::new(id),
+                Err(_) => continue,
+            };
+
+            let days_left = (due_date - today).num_days();
+            let channel_config = match channel_config_for_thread(discord, config, thread_id).await {
+                Some(c) if c.due_date_reminders => c,
+                _ => continue,
+            };
+            let (kind, message) = if days_left < 0 {
+                (
+                    REMINDER_OVERDUE,
+                    templates::notification(
+                        &channel_config.message_templates,
+                        Notification::Overdue,
+                        &json!({
+                            "identifier": issue.identifier,
+                            "due_date": due_date.to_string(),
+                        }),
+                    ),
+                )
+            } else if days_left <= channel_config.due_reminder_lead_days {
+                let when = match days_left {
+                    0 => "today".to_string(),
+                    1 => "tomorrow".to_string(),
+                    n => format!("in {n} days"),
+                };
+                (
+                    REMINDER_UPCOMING,
+                    templates::notification(
+                        &channel_config.message_templates,
+                        Notification::DueSoon,
+                        &json!({
+                            "identifier": issue.identifier,
+                            "due_date": due_date.to_string(),
+                            "when": when,
+                        }),
+                    ),
+                )
+            } else {
+                continue;
+            };
+
+            let due = due_date.to_string();
+            if db::is_reminder_sent(pool, &issue.id, &due, kind).await? {
+                continue;
+            }
+            if let Err(e) = digest::notify(discord, pool, config, thread_id, &message).await {
+                warn!(identifier = %issue.identifier, error = %e, "Failed to post due date reminder");
+                continue;
+            }
+            db::insert_reminder_sent(pool, &issue.id, &due, kind).await?;
+            sent += 1;
+        }
+    }
+
+    if sent > 0 {
+        info!(sent, "Posted due date reminders");
+    }
+    Ok(())
+}
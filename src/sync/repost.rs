@@ -0,0 +1,158 @@
+//! Recognizing forum posts that were deleted and posted again.
+//!
+//! Each post an issue is created for has a hash of its title and body recorded per forum. When a
+//! new post hashes the same, it isn't filed again:
+//!
+//! - If the original thread was deleted, the new thread takes over the issue, as with
+//!   `/linear link`, unless the issue has since been completed or canceled.
+//! - If the original thread still exists, the author is pointed to it once and no issue is
+//!   created.
+
+use serenity::all::{CreateMessage, GuildChannel, Message};
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use tracing::{info, warn};
+
+use crate::config::ChannelConfig;
+use crate::db;
+use crate::discord::port::DiscordPort;
+use crate::error::AppError;
+use crate::linear::api::LinearApi;
+use crate::sync::discord_to_linear::{link_thread_url, thread_url, tracked_message};
+use crate::sync::status_embed::post_status_embed;
+
+/// Hash of a post's title and body, ignoring case and whitespace differences.
+pub fn content_hash(title: &str, body: &str) -> String {
+    let normalize = |text: &str| text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let content = format!("{}\n{}", normalize(title), normalize(body)).to_lowercase();
+    hex::encode(Sha256::digest(content.as_bytes()))
+}
+
+/// Record the post an issue was just created for.
+pub async fn record(
+    pool: &SqlitePool,
+    thread: &GuildChannel,
+    first_message: &Message,
+    linear_issue_id: &str,
+    linear_identifier: &str,
+) -> Result<(), AppError> {
+    let Some(parent_id) = thread.parent_id else {
+        return Ok(());
+    };
+    let hash = content_hash(&thread.name, &first_message.content);
+    db::record_content_hash(
+        pool,
+        &parent_id.to_string(),
+        &hash,
+        linear_issue_id,
+        linear_identifier,
+    )
+    .await?;
+    Ok(())
+}
+
+/// Handle a new post that repeats an earlier one. Returns whether it did; if not, an issue
+/// should be created as usual.
+pub async fn handle_repost(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    channel_config: &ChannelConfig,
+    linear: &impl LinearApi,
+    thread: &GuildChannel,
+    first_message: &Message,
+) -> Result<bool, AppError> {
+    let thread_id = thread.id.to_string();
+    let hash = content_hash(&thread.name, &first_message.content);
+    let channel_id = channel_config.discord_channel_id.to_string();
+    let Some(previous) = db::get_content_hash(pool, &channel_id, &hash).await? else {
+        return Ok(false);
+    };
+
+    if let Some(original) = db::get_mapping_by_linear_issue(pool, &previous.linear_issue_id).await?
+    {
+        if db::insert_repost(
+            pool,
+            &thread_id,
+            &original.discord_thread_id,
+            &original.linear_identifier,
+        )
+        .await?
+        {
+            let notice = format!(
+                "This looks like a repost of <#{}>, which is tracked as **{}**. Follow along \
+                 there; no new issue was filed.",
+                original.discord_thread_id, original.linear_identifier
+            );
+            discord
+                .send_message(thread.id, CreateMessage::new().content(notice))
+                .await?;
+            info!(
+                thread_id,
+                original_thread_id = %original.discord_thread_id,
+                identifier = %original.linear_identifier,
+                "Pointed repost to the original thread"
+            );
+        }
+        return Ok(true);
+    }
+
+    let issue = match linear.get_issue(&previous.linear_issue_id).await {
+        Ok(issue) => issue,
+        Err(e) => {
+            warn!(
+                thread_id,
+                identifier = %previous.linear_identifier,
+                error = %e,
+                "Failed to fetch reposted thread's issue, creating a new one"
+            );
+            return Ok(false);
+        }
+    };
+    if issue.category().is_terminal() {
+        return Ok(false);
+    }
+
+    db::create_mapping(
+        pool,
+        &thread_id,
+        &issue.id,
+        &issue.identifier,
+        &channel_config.channel_type,
+        &channel_config.guild_id.to_string(),
+    )
+    .await?;
+    // Prime the cache so the poller doesn't announce the current state as a change.
+    db::upsert_cached_status(pool, &issue.id, &issue.status_name).await?;
+    if let Some(parent_id) = thread.parent_id {
+        let url = thread_url(channel_config.guild_id, parent_id, thread.id);
+        link_thread_url(linear, &issue.id, &url).await;
+    }
+    let author = thread.owner_id.map(|id| id.to_string());
+    db::insert_audit_entry(
+        pool,
+        &thread_id,
+        &issue.identifier,
+        "issue_linked",
+        author.as_deref(),
+        "repost",
+    )
+    .await?;
+
+    info!(
+        thread_id,
+        identifier = %issue.identifier,
+        "Linked repost of a deleted thread to its issue"
+    );
+
+    let reply = tracked_message(channel_config, &issue.identifier, &issue.url);
+    discord
+        .send_message(thread.id, CreateMessage::new().content(reply))
+        .await?;
+    if channel_config.status_embed {
+        if let Err(e) = post_status_embed(discord, pool, linear, thread.id, &issue.id).await {
+            warn!(thread_id, error = %e, "Failed to post status embed");
+        }
+    }
+
+    Ok(true)
+}
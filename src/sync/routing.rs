@@ -0,0 +1,141 @@
+//! Channel routing rules: which Linear team, project, labels, and priority a new post's issue
+//! gets.
+
+use serenity::all::{GuildChannel, RoleId};
+use tracing::warn;
+
+use crate::config::{ChannelConfig, RoutingRule};
+use crate::discord::port::DiscordPort;
+use crate::sync::discord_to_linear::{issue_labels_and_project, issue_priority_and_estimate};
+
+/// Where a new post's issue is filed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IssueRoute<'a> {
+    pub team_id: &'a str,
+    pub project_id: &'a str,
+    pub label_ids: Vec<String>,
+    pub priority: Option<i64>,
+}
+
+/// Route `thread`'s issue with the channel's first matching rule, or with the channel's team,
+/// labels, and project (and tag- and keyword-mapped labels and priority) if none matches.
+/// `body` is the post's first message and `author_roles` its author's roles.
+pub fn route<'a>(
+    channel_config: &'a ChannelConfig,
+    thread: &GuildChannel,
+    body: &str,
+    author_roles: &[RoleId],
+) -> IssueRoute<'a> {
+    let (mut label_ids, project_id) = issue_labels_and_project(channel_config, thread);
+    let (priority, _) = issue_priority_and_estimate(channel_config, thread);
+
+    let title = thread.name.to_lowercase();
+    let body = body.to_lowercase();
+    for label_id in keyword_labels(channel_config, &title, &body) {
+        if !label_ids.contains(&label_id) {
+            label_ids.push(label_id);
+        }
+    }
+    let rule = channel_config
+        .routing_rules
+        .iter()
+        .find(|rule| matches(rule, thread, &title, &body, author_roles));
+    let Some(rule) = rule else {
+        return IssueRoute {
+            team_id: &channel_config.linear_team_id,
+            project_id,
+            label_ids,
+            priority,
+        };
+    };
+
+    let priority = rule.priority.or(priority);
+    match &rule.linear_team_id {
+        // Tag- and keyword-mapped labels and projects belong to the channel's team.
+        Some(team_id) => IssueRoute {
+            team_id,
+            project_id: rule.linear_project_id.as_deref().unwrap_or(project_id),
+            label_ids: rule.linear_label_ids.clone(),
+            priority,
+        },
+        None => {
+            if !rule.linear_label_ids.is_empty() {
+                // The channel's label comes first; tag-mapped ones follow it.
+                label_ids.splice(..1, rule.linear_label_ids.iter().cloned());
+            }
+            IssueRoute {
+                team_id: &channel_config.linear_team_id,
+                project_id: rule.linear_project_id.as_deref().unwrap_or(project_id),
+                label_ids,
+                priority,
+            }
+        }
+    }
+}
+
+/// The labels of the channel's `keyword_label_map` entries mentioned in the post, sorted.
+/// `title` and `body` are lowercased.
+fn keyword_labels(channel_config: &ChannelConfig, title: &str, body: &str) -> Vec<String> {
+    let mut label_ids: Vec<String> = channel_config
+        .keyword_label_map
+        .iter()
+        .filter(|(keywords, _)| mentions_any(keywords.split('|'), title, body))
+        .map(|(_, label_id)| label_id.clone())
+        .collect();
+    label_ids.sort();
+    label_ids.dedup();
+    label_ids
+}
+
+/// Whether the lowercased `title` or `body` contains one of `keywords` (ignoring case).
+fn mentions_any<'k>(keywords: impl IntoIterator<Item = &'k str>, title: &str, body: &str) -> bool {
+    keywords.into_iter().any(|keyword| {
+        let keyword = keyword.trim().to_lowercase();
+        !keyword.is_empty() && (title.contains(&keyword) || body.contains(&keyword))
+    })
+}
+
+/// Whether every condition `rule` sets holds for the post. `title` and `body` are lowercased.
+fn matches(
+    rule: &RoutingRule,
+    thread: &GuildChannel,
+    title: &str,
+    body: &str,
+    author_roles: &[RoleId],
+) -> bool {
+    let tagged = rule.tag_ids.is_empty()
+        || thread
+            .applied_tags
+            .iter()
+            .any(|tag| rule.tag_ids.contains(&tag.get()));
+    let mentions = rule.keywords.is_empty()
+        || mentions_any(rule.keywords.iter().map(String::as_str), title, body);
+    let has_role = rule.author_role_ids.is_empty()
+        || author_roles
+            .iter()
+            .any(|role| rule.author_role_ids.contains(&role.get()));
+    tagged && mentions && has_role
+}
+
+/// The roles of `thread`'s author, fetched only if one of the channel's rules matches on them.
+/// Authors who can't be fetched (e.g. because they left the guild) have none.
+pub async fn author_roles(
+    discord: &impl DiscordPort,
+    channel_config: &ChannelConfig,
+    thread: &GuildChannel,
+) -> Vec<RoleId> {
+    let by_role = channel_config
+        .routing_rules
+        .iter()
+        .any(|r| !r.author_role_ids.is_empty());
+    let Some(owner_id) = thread.owner_id.filter(|_| by_role) else {
+        return Vec::new();
+    };
+    match discord.member(thread.guild_id, owner_id).await {
+        Ok(member) => member.roles,
+        Err(e) => {
+            warn!(thread_id = %thread.id, error = %e, "Failed to fetch author roles for routing");
+            Vec::new()
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! Escalation pings for issues nobody has picked up in Linear.
+//!
+//! Channels with `sla_hours` expect some Linear activity (a status change or a comment) on the
+//! issues filed from their posts within that many hours. An issue still without any is escalated
+//! in its thread, mentioning `sla_role_id`. Unanswered escalations repeat with a doubling gap, at
+//! one, three, then seven times `sla_hours` after filing and so on, up to `sla_max_pings`.
+//! Activity in Linear ends the escalation.
+
+use serde_json::json;
+use serenity::all::ChannelId;
+use sqlx::SqlitePool;
+use tracing::{error, info, warn};
+
+use crate::config::{ChannelConfig, Config, SharedConfig};
+use crate::db::{self, SlaCandidate};
+use crate::discord::port::DiscordPort;
+use crate::error::AppError;
+use crate::sync::digest;
+use crate::sync::thread::channel_config_for_thread;
+use crate::templates::{self, Notification};
+
+/// Periodically escalate issues past their channel's SLA.
+pub async fn run_sla_checks(discord: impl DiscordPort, pool: SqlitePool, config: SharedConfig) {
+    let interval_secs = config.current().sla_check_interval_secs;
+    info!(interval_secs, "Starting SLA check task");
+
+    loop {
+        tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
+
+        if let Err(e) = check_slas(&discord, &pool, &config.current()).await {
+            error!(error = %e, "SLA check pass failed");
+        }
+    }
+}
+
+/// Escalate every issue whose next ping is due. Returns how many were posted.
+pub async fn check_slas(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    config: &Config,
+) -> Result<usize, AppError> {
+    if !config.channels.iter().any(|c| c.sla_hours.is_some()) {
+        return Ok(0);
+    }
+
+    let mut pinged = 0usize;
+    for candidate in db::get_sla_candidates(pool).await? {
+        let thread_id = match candidate.discord_thread_id.parse() {
+            Ok(id) => ChannelId::new(id),
+            Err(_) => continue,
+        };
+        let channel_config = match channel_config_for_thread(discord, config, thread_id).await {
+            Some(c) if c.sla_hours.is_some() => c,
+            _ => continue,
+        };
+        if !ping_due(channel_config, &candidate) {
+            continue;
+        }
+
+        let message = templates::notification(
+            &channel_config.message_templates,
+            Notification::SlaBreached,
+            &json!({
+                "identifier": candidate.linear_identifier,
+                "hours": candidate.age_secs / 3600,
+                "role": channel_config
+                    .sla_role_id
+                    .map(|r| format!("<@&{r}>"))
+                    .unwrap_or_default(),
+            }),
+        );
+        if let Err(e) = digest::notify(discord, pool, config, thread_id, message.trim()).await {
+            warn!(
+                identifier = %candidate.linear_identifier,
+                error = %e,
+                "Failed to post SLA escalation"
+            );
+            continue;
+        }
+        db::record_sla_ping(pool, &candidate.linear_issue_id).await?;
+        pinged += 1;
+    }
+
+    if pinged > 0 {
+        info!(pinged, "Escalated issues past their SLA");
+    }
+    Ok(pinged)
+}
+
+/// Whether the candidate's next escalation is due: the first after `sla_hours`, then each after
+/// twice the previous gap.
+fn ping_due(channel_config: &ChannelConfig, candidate: &SlaCandidate) -> bool {
+    let Some(sla_hours) = channel_config.sla_hours else {
+        return false;
+    };
+    if candidate.pings >= i64::from(channel_config.sla_max_pings) {
+        return false;
+    }
+    let Ok(pings) = u32::try_from(candidate.pings) else {
+        return false;
+    };
+    let periods = 2u64.checked_pow(pings + 1).map_or(u64::MAX, |p| p - 1);
+    let due_secs = sla_hours.saturating_mul(3600).saturating_mul(periods);
+    u64::try_from(candidate.age_secs).is_ok_and(|age| age >= due_secs)
+}
@@ -0,0 +1,113 @@
+//! "Still tracked" nudges for open issues that have gone quiet.
+//!
+//! In channels with `stale_nudge_days`, an open issue whose last update in Linear is at least
+//! that many days old gets a short message in its thread, so the reporter knows it hasn't been
+//! lost. While it stays quiet the nudge repeats at most once per `stale_nudge_days`.
+
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use serenity::all::ChannelId;
+use sqlx::SqlitePool;
+use tracing::{error, info, warn};
+
+use crate::config::{Config, SharedConfig};
+use crate::db;
+use crate::discord::port::DiscordPort;
+use crate::error::AppError;
+use crate::linear::api::LinearApi;
+use crate::sync::digest;
+use crate::sync::thread::channel_config_for_thread;
+use crate::templates::{self, Notification};
+
+const BATCH_SIZE: usize = 100;
+
+/// Periodically nudge the threads of stale issues in channels with `stale_nudge_days`.
+pub async fn run_stale_nudges(
+    discord: impl DiscordPort,
+    pool: SqlitePool,
+    linear: impl LinearApi,
+    config: SharedConfig,
+) {
+    let interval_secs = config.current().stale_nudge_interval_secs;
+    info!(interval_secs, "Starting stale issue nudge task");
+
+    loop {
+        tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
+
+        if let Err(e) = nudge_stale_issues(&discord, &pool, &linear, &config.current()).await {
+            error!(error = %e, "Stale issue nudge pass failed");
+        }
+    }
+}
+
+/// Nudge every open issue that hasn't been updated within its channel's `stale_nudge_days` and
+/// wasn't nudged within them either. Returns how many nudges were posted.
+pub async fn nudge_stale_issues(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    linear: &impl LinearApi,
+    config: &Config,
+) -> Result<usize, AppError> {
+    if !config.channels.iter().any(|c| c.stale_nudge_days.is_some()) {
+        return Ok(0);
+    }
+
+    let mappings = db::get_all_tracked_issues(pool).await?;
+    let now = Utc::now();
+    let mut sent = 0usize;
+
+    for chunk in mappings.chunks(BATCH_SIZE) {
+        let ids: Vec<String> = chunk.iter().map(|m| m.linear_issue_id.clone()).collect();
+        let issues = linear.get_issues_by_ids(&ids).await?;
+
+        for issue in issues {
+            if issue.category().is_terminal() {
+                continue;
+            }
+            let Ok(updated_at) = DateTime::parse_from_rfc3339(&issue.updated_at) else {
+                continue;
+            };
+            let days = (now - updated_at.with_timezone(&Utc)).num_days();
+            let mapping = match chunk.iter().find(|m| m.linear_issue_id == issue.id) {
+                Some(m) => m,
+                None => continue,
+            };
+            let thread_id = match mapping.discord_thread_id.parse() {
+                Ok(id) => ChannelId::new(id),
+                Err(_) => continue,
+            };
+            let channel_config = match channel_config_for_thread(discord, config, thread_id).await {
+                Some(c) => c,
+                None => continue,
+            };
+            let period = match channel_config.stale_nudge_days {
+                Some(period) if days >= period => period,
+                _ => continue,
+            };
+            if db::was_nudged_within(pool, &issue.id, period).await? {
+                continue;
+            }
+
+            let message = templates::notification(
+                &channel_config.message_templates,
+                Notification::Stale,
+                &json!({
+                    "identifier": issue.identifier,
+                    "status": issue.status_name,
+                    "days": days,
+                }),
+            );
+            if let Err(e) = digest::notify(discord, pool, config, thread_id, &message).await {
+                warn!(identifier = %issue.identifier, error = %e, "Failed to post stale issue nudge");
+                continue;
+            }
+            db::upsert_stale_nudge(pool, &issue.id).await?;
+            sent += 1;
+        }
+    }
+
+    if sent > 0 {
+        info!(sent, "Nudged threads of stale issues");
+    }
+    Ok(sent)
+}
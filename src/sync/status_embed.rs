@@ -0,0 +1,67 @@
+//! The pinned status embed kept in each tracked thread for channels with `status_embed` on.
+//!
+//! One message per thread is posted when the issue is linked and edited in place as the issue
+//! changes, instead of a new message per status change. If someone deletes it, the next update
+//! posts (and pins) a replacement.
+
+use serenity::all::{ChannelId, CreateMessage, EditMessage, MessageId};
+use sqlx::SqlitePool;
+use tracing::{info, warn};
+
+use crate::db;
+use crate::discord::embeds::issue_embed;
+use crate::discord::port::{is_not_found, DiscordPort};
+use crate::error::AppError;
+use crate::linear::api::LinearApi;
+
+/// Post the status embed in `thread_id` and pin it, replacing any previously recorded one.
+pub async fn post_status_embed(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    linear: &impl LinearApi,
+    thread_id: ChannelId,
+    linear_issue_id: &str,
+) -> Result<(), AppError> {
+    let issue = linear.get_issue(linear_issue_id).await?;
+    let message = discord
+        .send_message(thread_id, CreateMessage::new().embed(issue_embed(&issue)))
+        .await?;
+
+    // Pinning needs Manage Messages; an unpinned embed is still worth keeping.
+    if let Err(e) = discord.pin_message(thread_id, message.id).await {
+        warn!(identifier = %issue.identifier, error = %e, "Failed to pin status embed");
+    }
+
+    db::upsert_status_embed(pool, linear_issue_id, &message.id.to_string()).await?;
+    info!(identifier = %issue.identifier, thread_id = %thread_id, "Posted status embed");
+    Ok(())
+}
+
+/// Refresh the thread's status embed with the issue's current state. Does nothing if no embed
+/// has been posted for the issue; reposts it if the message has been deleted.
+pub async fn update_status_embed(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    linear: &impl LinearApi,
+    thread_id: ChannelId,
+    linear_issue_id: &str,
+) -> Result<(), AppError> {
+    let message_id = match db::get_status_embed(pool, linear_issue_id)
+        .await?
+        .and_then(|id| id.parse().ok())
+    {
+        Some(id) => MessageId::new(id),
+        None => return Ok(()),
+    };
+
+    let issue = linear.get_issue(linear_issue_id).await?;
+    let edit = EditMessage::new().embed(issue_embed(&issue));
+    match discord.edit_message(thread_id, message_id, edit).await {
+        Ok(_) => Ok(()),
+        Err(e) if is_not_found(&e) => {
+            info!(identifier = %issue.identifier, "Status embed was deleted, reposting");
+            post_status_embed(discord, pool, linear, thread_id, linear_issue_id).await
+        }
+        Err(e) => Err(e),
+    }
+}
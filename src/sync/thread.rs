@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use serenity::all::{ChannelId, ForumTagId, GuildChannel};
+use tracing::warn;
+
+use crate::config::{ChannelConfig, Config, StatusAction};
+use crate::discord::port::DiscordPort;
+use crate::error::AppError;
+use crate::linear::client::StateCategory;
+
+/// Fetch a mapped thread as a guild channel.
+pub async fn fetch_thread(
+    discord: &impl DiscordPort,
+    thread_id: ChannelId,
+) -> Result<GuildChannel, AppError> {
+    discord.channel(thread_id).await
+}
+
+/// Config for the forum a mapped thread lives in. `None` if the thread can't be fetched or its
+/// forum isn't configured.
+pub async fn channel_config_for_thread<'a>(
+    discord: &impl DiscordPort,
+    config: &'a Config,
+    thread_id: ChannelId,
+) -> Option<&'a ChannelConfig> {
+    match fetch_thread(discord, thread_id).await {
+        Ok(thread) => thread
+            .parent_id
+            .and_then(|p| config.channel_config(p.get())),
+        Err(e) => {
+            warn!(thread_id = %thread_id, error = %e, "Failed to fetch thread for channel config");
+            None
+        }
+    }
+}
+
+/// Discord's limit on thread names.
+const MAX_THREAD_NAME_CHARS: usize = 100;
+
+/// Thread name for a Linear issue title, trimmed to Discord's limit.
+pub fn thread_name_for_title(title: &str) -> String {
+    title.chars().take(MAX_THREAD_NAME_CHARS).collect()
+}
+
+/// The channel's `status_actions` entry for a workflow state: the one for `status_name`, or
+/// failing that, for its category.
+pub fn status_action<'a>(
+    channel_config: &'a ChannelConfig,
+    status_name: &str,
+    category: StateCategory,
+) -> Option<&'a StatusAction> {
+    let actions = &channel_config.status_actions;
+    actions
+        .get(status_name)
+        .or_else(|| actions.get(category.as_str()))
+}
+
+/// Compute the thread's applied tags after swapping in the tag for `status_name` (or, failing
+/// that, its state category) from `status_actions`, then `state_tag_map`. Tags for other states
+/// are removed; tags neither manages are left alone. Returns `None` when nothing would change.
+pub fn tags_for_state(
+    current: &[ForumTagId],
+    channel_config: &ChannelConfig,
+    status_name: &str,
+    category: StateCategory,
+) -> Option<Vec<ForumTagId>> {
+    let mapped = parse_tag_ids(&channel_config.state_tag_map);
+    let managed: Vec<ForumTagId> = mapped
+        .values()
+        .copied()
+        .chain(
+            channel_config
+                .status_actions
+                .values()
+                .filter_map(|a| a.tag_id)
+                .map(ForumTagId::new),
+        )
+        .collect();
+    if managed.is_empty() {
+        return None;
+    }
+
+    let wanted = status_action(channel_config, status_name, category)
+        .and_then(|a| a.tag_id)
+        .map(ForumTagId::new)
+        .or_else(|| {
+            mapped
+                .get(status_name)
+                .or_else(|| mapped.get(category.as_str()))
+                .copied()
+        });
+
+    let mut tags: Vec<ForumTagId> = current
+        .iter()
+        .filter(|t| !managed.contains(t))
+        .copied()
+        .collect();
+    if let Some(tag) = wanted {
+        tags.push(tag);
+    }
+
+    let mut before = current.to_vec();
+    let mut after = tags.clone();
+    before.sort();
+    after.sort();
+    (before != after).then_some(tags)
+}
+
+/// Discord's limit on tags applied to a forum post.
+const MAX_APPLIED_TAGS: usize = 5;
+
+/// Compute the thread's applied tags after matching the tags in `tag_label_map` to the issue's
+/// `label_ids`: mapped tags whose label is on the issue are applied, the other mapped tags are
+/// removed, and unmapped tags are left alone. Returns `None` when nothing would change.
+pub fn tags_for_labels(
+    current: &[ForumTagId],
+    channel_config: &ChannelConfig,
+    label_ids: &[String],
+) -> Option<Vec<ForumTagId>> {
+    let mapped: Vec<(ForumTagId, &String)> = channel_config
+        .tag_label_map
+        .iter()
+        .filter_map(|(tag, label)| {
+            tag.parse::<u64>()
+                .ok()
+                .map(|id| (ForumTagId::new(id), label))
+        })
+        .collect();
+    if mapped.is_empty() {
+        return None;
+    }
+
+    let mut tags: Vec<ForumTagId> = current
+        .iter()
+        .filter(|t| {
+            mapped
+                .iter()
+                .all(|(tag, label)| tag != *t || label_ids.contains(label))
+        })
+        .copied()
+        .collect();
+    let mut wanted: Vec<ForumTagId> = mapped
+        .iter()
+        .filter(|(tag, label)| label_ids.contains(label) && !tags.contains(tag))
+        .map(|(tag, _)| *tag)
+        .collect();
+    // Map iteration order is arbitrary; keep which tags fit under the limit stable.
+    wanted.sort();
+    for tag in wanted {
+        if tags.len() >= MAX_APPLIED_TAGS {
+            break;
+        }
+        tags.push(tag);
+    }
+
+    let mut before = current.to_vec();
+    let mut after = tags.clone();
+    before.sort();
+    after.sort();
+    (before != after).then_some(tags)
+}
+
+fn parse_tag_ids(map: &HashMap<String, String>) -> HashMap<&str, ForumTagId> {
+    map.iter()
+        .filter_map(|(state, tag)| {
+            tag.parse::<u64>()
+                .ok()
+                .map(|id| (state.as_str(), ForumTagId::new(id)))
+        })
+        .collect()
+}
@@ -0,0 +1,151 @@
+//! Reaction votes on forum posts, written to their Linear issues.
+//!
+//! For channels with `vote_sync`, the `vote_emoji` reactions on each tracked post are counted on
+//! a schedule. A changed count is written as the last line of the issue description, and once
+//! it reaches `vote_label_threshold` the channel's `vote_label_id` is added to the issue. The
+//! label is only added once; it isn't removed if votes drop again.
+
+use serenity::all::{ChannelId, Message, MessageId, ReactionType};
+use sqlx::SqlitePool;
+use tracing::{error, info, warn};
+
+use crate::config::{ChannelConfig, Config, SharedConfig};
+use crate::db::{self, SyncMapping};
+use crate::discord::port::DiscordPort;
+use crate::error::AppError;
+use crate::linear::api::LinearApi;
+use crate::sync::thread::channel_config_for_thread;
+
+const VOTE_FOOTER: &str = "\n\n**Discord votes:** ";
+
+/// Periodically sync vote counts for channels with `vote_sync` enabled.
+pub async fn run_vote_sync(
+    discord: impl DiscordPort,
+    pool: SqlitePool,
+    linear: impl LinearApi,
+    config: SharedConfig,
+) {
+    let interval_secs = config.current().vote_sync_interval_secs;
+    info!(interval_secs, "Starting vote sync task");
+
+    loop {
+        tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
+
+        if let Err(e) = sync_votes(&discord, &pool, &linear, &config.current()).await {
+            error!(error = %e, "Vote sync pass failed");
+        }
+    }
+}
+
+/// Recount the votes on every tracked post in a channel with `vote_sync`. Returns how many
+/// issues were updated.
+pub async fn sync_votes(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    linear: &impl LinearApi,
+    config: &Config,
+) -> Result<usize, AppError> {
+    if !config.channels.iter().any(|c| c.vote_sync) {
+        return Ok(0);
+    }
+
+    let mut updated = 0usize;
+    for mapping in db::get_all_tracked_issues(pool).await? {
+        let thread_id = match mapping.discord_thread_id.parse() {
+            Ok(id) => ChannelId::new(id),
+            Err(_) => continue,
+        };
+        let channel_config = match channel_config_for_thread(discord, config, thread_id).await {
+            Some(c) if c.vote_sync => c,
+            _ => continue,
+        };
+        match sync_issue_votes(discord, pool, linear, channel_config, &mapping, thread_id).await {
+            Ok(true) => updated += 1,
+            Ok(false) => {}
+            Err(e) => warn!(
+                identifier = %mapping.linear_identifier,
+                error = %e,
+                "Failed to sync votes"
+            ),
+        }
+    }
+
+    if updated > 0 {
+        info!(updated, "Synced vote counts to Linear");
+    }
+    Ok(updated)
+}
+
+/// Write one post's vote count to its issue if it changed. Returns whether the issue was
+/// updated.
+async fn sync_issue_votes(
+    discord: &impl DiscordPort,
+    pool: &SqlitePool,
+    linear: &impl LinearApi,
+    channel_config: &ChannelConfig,
+    mapping: &SyncMapping,
+    thread_id: ChannelId,
+) -> Result<bool, AppError> {
+    let issue_id = &mapping.linear_issue_id;
+    // The starter message shares its ID with the thread.
+    let starter = discord
+        .message(thread_id, MessageId::new(thread_id.get()))
+        .await?;
+    let votes = count_votes(&starter, &channel_config.vote_emoji);
+
+    let previous = db::get_vote_count(pool, issue_id).await?;
+    let previous_votes = previous.as_ref().map_or(0, |p| p.vote_count);
+    let mut label_added = previous.as_ref().is_some_and(|p| p.label_added);
+    let add_label = channel_config
+        .vote_label_id
+        .as_deref()
+        .filter(|_| !label_added && votes >= channel_config.vote_label_threshold as i64);
+    if votes == previous_votes && add_label.is_none() {
+        return Ok(false);
+    }
+
+    if votes != previous_votes {
+        let description = linear.get_issue_description(issue_id).await?;
+        linear
+            .update_issue_description(issue_id, &with_vote_footer(&description, votes))
+            .await?;
+    }
+    if let Some(label_id) = add_label {
+        linear.add_issue_label(issue_id, label_id).await?;
+        label_added = true;
+        info!(
+            identifier = %mapping.linear_identifier,
+            votes,
+            "Issue reached the vote threshold, added label"
+        );
+    }
+    db::upsert_vote_count(pool, issue_id, votes, label_added).await?;
+
+    Ok(true)
+}
+
+/// Reactions on `message` with `emoji`, a Unicode emoji or a custom emoji's name.
+fn count_votes(message: &Message, emoji: &str) -> i64 {
+    let votes: u64 = message
+        .reactions
+        .iter()
+        .filter(|r| match &r.reaction_type {
+            ReactionType::Unicode(unicode) => unicode == emoji,
+            ReactionType::Custom { name, .. } => name.as_deref() == Some(emoji),
+            _ => false,
+        })
+        .map(|r| r.count)
+        .sum();
+    i64::try_from(votes).unwrap_or(i64::MAX)
+}
+
+/// `description` with its vote line replaced, or appended if it has none.
+fn with_vote_footer(description: &str, votes: i64) -> String {
+    let body = match description.rfind(VOTE_FOOTER) {
+        Some(start) if !description[start + VOTE_FOOTER.len()..].contains('\n') => {
+            &description[..start]
+        }
+        _ => description,
+    };
+    format!("{body}{VOTE_FOOTER}{votes}")
+}
@@ -0,0 +1,111 @@
+//! Export of `tracing` spans to an OpenTelemetry collector over OTLP/HTTP, JSON-encoded.
+//!
+//! Enabled by `OTEL_EXPORTER_OTLP_ENDPOINT` (or `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT`). Only this
+//! crate's spans are exported, so a slow issue creation shows up as one trace: `thread_create`,
+//! the Linear requests, attachment uploads, and database writes beneath it. Events logged inside
+//! a span are attached to it, and an error event marks the span as failed. Finished spans are
+//! sent in batches every few seconds; if the collector is down, batches are dropped, not retried.
+
+use std::collections::HashMap;
+use std::env;
+use std::time::Duration;
+
+use opentelemetry::trace::TracerProvider;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig, WithHttpConfig};
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+use tracing::{warn, Metadata, Subscriber};
+use tracing_subscriber::filter::filter_fn;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+/// How long a single export request may take.
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Where and how to export, from the standard `OTEL_*` variables.
+#[derive(Debug, Clone)]
+pub struct OtlpSettings {
+    /// Full URL of the traces endpoint, e.g. `http://localhost:4318/v1/traces`.
+    pub endpoint: String,
+    /// Extra request headers, e.g. an API key for a hosted collector.
+    pub headers: HashMap<String, String>,
+    pub service_name: String,
+}
+
+impl OtlpSettings {
+    /// `None` unless an OTLP endpoint is configured.
+    pub fn from_env() -> Option<Self> {
+        let endpoint = match env::var("OTEL_EXPORTER_OTLP_TRACES_ENDPOINT") {
+            Ok(endpoint) => endpoint,
+            Err(_) => {
+                let base = env::var("OTEL_EXPORTER_OTLP_ENDPOINT").ok()?;
+                format!("{}/v1/traces", base.trim_end_matches('/'))
+            }
+        };
+        let headers = env::var("OTEL_EXPORTER_OTLP_HEADERS")
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+            .collect();
+        Some(Self {
+            endpoint,
+            headers,
+            service_name: env::var("OTEL_SERVICE_NAME")
+                .unwrap_or_else(|_| env!("CARGO_PKG_NAME").to_string()),
+        })
+    }
+}
+
+/// Start the exporter. Add the returned layer to the subscriber, and call
+/// [`Exporter::shutdown`] before exiting so the last spans are sent.
+pub fn init<S>(
+    settings: OtlpSettings,
+) -> Result<(impl Layer<S>, Exporter), opentelemetry_otlp::ExporterBuildError>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_protocol(opentelemetry_otlp::Protocol::HttpJson)
+        .with_endpoint(settings.endpoint)
+        .with_headers(settings.headers)
+        .with_timeout(EXPORT_TIMEOUT)
+        .build()?;
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(
+            Resource::builder()
+                .with_service_name(settings.service_name)
+                .build(),
+        )
+        .build();
+    // Spans from other crates aren't exported; spans beneath them join the nearest one that is.
+    let layer = tracing_opentelemetry::layer()
+        .with_tracer(provider.tracer(env!("CARGO_PKG_NAME")))
+        .with_location(false)
+        .with_threads(false)
+        .with_target(false)
+        .with_tracked_inactivity(false)
+        .with_filter(filter_fn(exported));
+    Ok((layer, Exporter { provider }))
+}
+
+pub struct Exporter {
+    provider: SdkTracerProvider,
+}
+
+impl Exporter {
+    /// Send the spans that have finished so far, then stop.
+    pub async fn shutdown(self) {
+        let provider = self.provider;
+        let result = tokio::task::spawn_blocking(move || provider.shutdown()).await;
+        if let Ok(Err(e)) = result {
+            warn!(error = %e, "Failed to flush trace spans");
+        }
+    }
+}
+
+fn exported(metadata: &Metadata<'_>) -> bool {
+    metadata.target().starts_with(env!("CARGO_CRATE_NAME"))
+}
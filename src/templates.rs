@@ -0,0 +1,248 @@
+//! Handlebars templates for text the bot writes on a team's behalf.
+//!
+//! Output goes to Discord and Linear markdown, not HTML, so values are inserted without escaping.
+
+use std::collections::HashMap;
+
+use handlebars::{Handlebars, Template};
+use serde::Serialize;
+use tracing::warn;
+
+/// Check that `source` parses, so a bad template is reported at startup rather than the first
+/// time it is rendered.
+pub fn validate(source: &str) -> Result<(), String> {
+    Template::compile(source)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Render `source` with `data`. Variables missing from `data` render as empty strings.
+pub fn render<T: Serialize>(source: &str, data: &T) -> Result<String, String> {
+    let mut registry = Handlebars::new();
+    registry.register_escape_fn(handlebars::no_escape);
+    registry
+        .render_template(source, data)
+        .map_err(|e| e.to_string())
+}
+
+/// Variables available to a channel's `description_template`.
+#[derive(Debug, Serialize)]
+pub struct DescriptionVars<'a> {
+    pub title: &'a str,
+    pub message_body: &'a str,
+    pub author: &'a str,
+    pub thread_url: &'a str,
+    /// Names of the forum tags applied to the thread, comma-separated.
+    pub tags: String,
+    /// Intake form answers, already formatted as markdown (empty without an intake form).
+    pub intake: String,
+    /// Uploaded attachment links, one per line.
+    pub attachments: String,
+}
+
+/// Messages the bot writes: notices in issue threads, relayed comments, and subscriber DMs. Each
+/// can be overridden globally through `MESSAGE_TEMPLATES` or per channel through
+/// `message_templates`, keyed by [`Notification::key`], and is rendered with the variables
+/// listed by [`Notification::variables`].
+#[derive(Debug, Clone, Copy)]
+pub enum Notification {
+    /// Confirmation when a thread is linked to an issue. Variables: `identifier`, `url`.
+    Tracked,
+    /// Variables: `identifier`, `status`, `status_type`, `url`.
+    StatusChanged,
+    /// Sent instead of `StatusChanged` when the issue reaches a completed state. Variables:
+    /// `identifier`, `status`, `status_type`, `url`.
+    Completed,
+    /// Variables: `identifier`, `assignee` (a mention if the user is connected, otherwise their
+    /// bold Linear name), `assignee_name`.
+    Assigned,
+    /// Variables: `identifier`.
+    Unassigned,
+    /// Variables: `identifier`, `due_date`, `when` ("today", "tomorrow", "in 3 days").
+    DueSoon,
+    /// Variables: `identifier`, `due_date`.
+    Overdue,
+    /// Escalation for an issue with no Linear activity within the channel's SLA. Variables:
+    /// `identifier`, `hours` (since it was filed), `role` (a mention, or empty without
+    /// `sla_role_id`).
+    SlaBreached,
+    /// Reassurance in the thread of an open issue without recent updates. Variables:
+    /// `identifier`, `status`, `days` (since the last update in Linear).
+    Stale,
+    /// Starter message of a post opened for an issue labeled in Linear, above the issue's
+    /// description. Variables: `identifier`, `url`, `status`.
+    Opened,
+    /// A Linear comment posted in the thread by the bot (rather than through the channel
+    /// webhook). Variables: `identifier`, `author`, `body`, `quoted` (the body as a quote).
+    Comment,
+    /// The Linear comment made from a Discord reply. Variables: `author` (Discord display name),
+    /// `linear_name` (empty unless the author ran `/linear connect`), `content`.
+    Reply,
+    /// Notices queued within `DIGEST_WINDOW_SECS`, posted as one message. Variables: `notices`
+    /// (one per line), `count`.
+    Digest,
+    /// DM to a subscriber. Variables: `identifier`, `status`, `thread` (a channel mention).
+    DmStatusChanged,
+    /// DM to a subscriber about one new comment. Variables: `identifier`, `author`, `excerpt`,
+    /// `thread`.
+    DmComment,
+    /// DM to a subscriber about several new comments. Variables: `identifier`, `count`,
+    /// `thread`.
+    DmComments,
+    /// DM to a user who was assigned the issue. Variables: `identifier`, `thread`.
+    DmAssigned,
+}
+
+impl Notification {
+    const ALL: [Notification; 17] = [
+        Notification::Tracked,
+        Notification::StatusChanged,
+        Notification::Completed,
+        Notification::Assigned,
+        Notification::Unassigned,
+        Notification::DueSoon,
+        Notification::Overdue,
+        Notification::SlaBreached,
+        Notification::Stale,
+        Notification::Opened,
+        Notification::Comment,
+        Notification::Reply,
+        Notification::Digest,
+        Notification::DmStatusChanged,
+        Notification::DmComment,
+        Notification::DmComments,
+        Notification::DmAssigned,
+    ];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            Notification::Tracked => "tracked",
+            Notification::StatusChanged => "status_changed",
+            Notification::Completed => "completed",
+            Notification::Assigned => "assigned",
+            Notification::Unassigned => "unassigned",
+            Notification::DueSoon => "due_soon",
+            Notification::Overdue => "overdue",
+            Notification::SlaBreached => "sla_breached",
+            Notification::Stale => "stale",
+            Notification::Opened => "opened",
+            Notification::Comment => "comment",
+            Notification::Reply => "reply",
+            Notification::Digest => "digest",
+            Notification::DmStatusChanged => "dm_status_changed",
+            Notification::DmComment => "dm_comment",
+            Notification::DmComments => "dm_comments",
+            Notification::DmAssigned => "dm_assigned",
+        }
+    }
+
+    /// Variables the message is rendered with; overrides may use no others.
+    pub fn variables(self) -> &'static [&'static str] {
+        match self {
+            Notification::Tracked => &["identifier", "url"],
+            Notification::Opened => &["identifier", "url", "status"],
+            Notification::StatusChanged | Notification::Completed => {
+                &["identifier", "status", "status_type", "url"]
+            }
+            Notification::Assigned => &["identifier", "assignee", "assignee_name"],
+            Notification::Unassigned => &["identifier"],
+            Notification::DueSoon => &["identifier", "due_date", "when"],
+            Notification::Overdue => &["identifier", "due_date"],
+            Notification::SlaBreached => &["identifier", "hours", "role"],
+            Notification::Stale => &["identifier", "status", "days"],
+            Notification::Comment => &["identifier", "author", "body", "quoted"],
+            Notification::Reply => &["author", "linear_name", "content"],
+            Notification::Digest => &["notices", "count"],
+            Notification::DmStatusChanged => &["identifier", "status", "thread"],
+            Notification::DmComment => &["identifier", "author", "excerpt", "thread"],
+            Notification::DmComments => &["identifier", "count", "thread"],
+            Notification::DmAssigned => &["identifier", "thread"],
+        }
+    }
+
+    fn default_template(self) -> &'static str {
+        match self {
+            Notification::Tracked => "Tracked as **[{{identifier}}]({{url}})** in Linear",
+            Notification::StatusChanged => "**{{identifier}}** status changed to **{{status}}**",
+            Notification::Completed => "🎉 **{{identifier}}** is done (**{{status}}**), thanks!",
+            Notification::Assigned => "**{{identifier}}** assigned to {{assignee}}",
+            Notification::Unassigned => "**{{identifier}}** is no longer assigned",
+            Notification::DueSoon => "**{{identifier}}** is due {{when}} ({{due_date}})",
+            Notification::Overdue => "**{{identifier}}** is overdue (was due {{due_date}})",
+            Notification::SlaBreached => {
+                "{{role}} **{{identifier}}** has had no activity in Linear for {{hours}} hours"
+            }
+            Notification::Stale => {
+                "Still tracked as **{{identifier}}** ({{status}}), last update {{days}} days ago"
+            }
+            Notification::Opened => "Discussion of **[{{identifier}}]({{url}})** ({{status}})",
+            Notification::Comment => "**{{author}}** commented on **{{identifier}}**:\n{{quoted}}",
+            Notification::Reply => {
+                "{{#if linear_name}}**{{linear_name}}** (via Discord as {{author}})\
+                 {{else}}**{{author}}** (via Discord){{/if}}:\n\n{{content}}"
+            }
+            Notification::Digest => "{{notices}}",
+            Notification::DmStatusChanged => {
+                "**{{identifier}}** moved to **{{status}}** ({{thread}})."
+            }
+            Notification::DmComment => {
+                "New comment on **{{identifier}}** from {{author}} ({{thread}}):\n>>> {{excerpt}}"
+            }
+            Notification::DmComments => {
+                "{{count}} new comments on **{{identifier}}** ({{thread}})."
+            }
+            Notification::DmAssigned => "You were assigned **{{identifier}}** ({{thread}}).",
+        }
+    }
+}
+
+/// Check a set of notification overrides: every key must name a [`Notification`] and every
+/// template must pass [`validate_message`].
+pub fn validate_messages(overrides: &HashMap<String, String>) -> Result<(), String> {
+    for (key, source) in overrides {
+        let notification = Notification::ALL
+            .into_iter()
+            .find(|m| m.key() == key)
+            .ok_or_else(|| format!("unknown message template \"{key}\""))?;
+        validate_message(notification, source)
+            .map_err(|e| format!("message template \"{key}\": {e}"))?;
+    }
+    Ok(())
+}
+
+/// Check that `source` parses and uses only `notification`'s variables, by rendering it in
+/// strict mode with each of them set.
+pub fn validate_message(notification: Notification, source: &str) -> Result<(), String> {
+    validate(source)?;
+    let vars: HashMap<&str, &str> = notification
+        .variables()
+        .iter()
+        .map(|&name| (name, "x"))
+        .collect();
+    let mut registry = Handlebars::new();
+    registry.set_strict_mode(true);
+    registry
+        .render_template(source, &vars)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Render `notification` using the override in `overrides` if there is one, falling back to the
+/// built-in wording if the override fails to render.
+pub fn notification<T: Serialize>(
+    overrides: &HashMap<String, String>,
+    notification: Notification,
+    vars: &T,
+) -> String {
+    if let Some(source) = overrides.get(notification.key()) {
+        match render(source, vars) {
+            Ok(text) => return text,
+            Err(e) => warn!(
+                notification = notification.key(),
+                error = %e,
+                "Failed to render message template, using default"
+            ),
+        }
+    }
+    render(notification.default_template(), vars).unwrap_or_default()
+}
@@ -0,0 +1,213 @@
+//! Export and import of thread ↔ issue mappings, for moving the bot to another host or merging
+//! two databases without losing links.
+//!
+//! An export carries each mapping with the state that keeps a restored bot from repeating
+//! itself: the last announced status (so it isn't announced again), the comment cursor, and the
+//! comments already mirrored in either direction (so they aren't relayed twice). JSON holds all
+//! of it. CSV has one row per mapping and leaves out the per-comment rows; the cursor alone
+//! keeps comments older than it from being fetched again, but edits and deletions of comments
+//! relayed before the move are no longer mirrored.
+
+use std::io::{Read, Write};
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tracing::warn;
+
+use crate::db;
+use crate::error::AppError;
+
+#[derive(Debug, thiserror::Error)]
+pub enum TransferError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Csv,
+}
+
+/// A mapping and its sync state, as written by `export-mappings`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedMapping {
+    pub discord_thread_id: String,
+    pub linear_issue_id: String,
+    pub linear_identifier: String,
+    pub channel_type: String,
+    pub created_at: String,
+    /// Guild of the thread's forum, if it was recorded.
+    #[serde(default)]
+    pub guild_id: Option<String>,
+    /// Workflow state last announced in the thread.
+    #[serde(default)]
+    pub status: Option<String>,
+    /// `createdAt` of the newest Linear comment handled.
+    #[serde(default)]
+    pub comment_cursor: Option<String>,
+    #[serde(default)]
+    pub synced_comments: Vec<ExportedSyncedComment>,
+    #[serde(default)]
+    pub relayed_comments: Vec<ExportedRelayedComment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedSyncedComment {
+    pub linear_comment_id: String,
+    pub discord_message_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedRelayedComment {
+    pub linear_comment_id: String,
+    pub discord_message_id: String,
+    pub via_webhook: bool,
+    pub linear_updated_at: String,
+}
+
+/// `ExportedMapping` without the comment lists, which don't fit in a CSV row.
+#[derive(Serialize, Deserialize)]
+struct CsvRow {
+    discord_thread_id: String,
+    linear_issue_id: String,
+    linear_identifier: String,
+    channel_type: String,
+    created_at: String,
+    #[serde(default)]
+    guild_id: Option<String>,
+    status: Option<String>,
+    comment_cursor: Option<String>,
+}
+
+/// Counts from an import.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub imported: usize,
+    /// Mappings whose thread or issue was already mapped in this database.
+    pub skipped: usize,
+}
+
+/// Every mapping in the database with its sync state, oldest first.
+pub async fn export(pool: &SqlitePool) -> Result<Vec<ExportedMapping>, AppError> {
+    let mut mappings = db::get_all_tracked_issues(pool).await?;
+    mappings.sort_by_key(|m| m.id);
+
+    let mut exported = Vec::with_capacity(mappings.len());
+    for m in mappings {
+        let issue_id = m.linear_issue_id.as_str();
+        let synced_comments = db::get_synced_comments(pool, issue_id)
+            .await?
+            .into_iter()
+            .map(|c| ExportedSyncedComment {
+                linear_comment_id: c.linear_comment_id,
+                discord_message_id: c.discord_message_id,
+            })
+            .collect();
+        let relayed_comments = db::get_relayed_comments(pool, issue_id)
+            .await?
+            .into_iter()
+            .map(|c| ExportedRelayedComment {
+                linear_comment_id: c.linear_comment_id,
+                discord_message_id: c.discord_message_id,
+                via_webhook: c.via_webhook,
+                linear_updated_at: c.linear_updated_at,
+            })
+            .collect();
+        exported.push(ExportedMapping {
+            status: db::get_cached_status(pool, issue_id).await?,
+            comment_cursor: db::get_comment_cursor(pool, issue_id).await?,
+            synced_comments,
+            relayed_comments,
+            discord_thread_id: m.discord_thread_id,
+            linear_issue_id: m.linear_issue_id,
+            linear_identifier: m.linear_identifier,
+            channel_type: m.channel_type,
+            created_at: m.created_at,
+            guild_id: m.guild_id,
+        });
+    }
+    Ok(exported)
+}
+
+/// Insert the mappings, skipping any whose thread or issue is already mapped. Each mapping is
+/// imported in its own transaction, so rerunning an import picks up where a failed one stopped.
+pub async fn import(
+    pool: &SqlitePool,
+    mappings: &[ExportedMapping],
+) -> Result<ImportSummary, AppError> {
+    let mut summary = ImportSummary::default();
+    for mapping in mappings {
+        if db::import_mapping(pool, mapping).await? {
+            summary.imported += 1;
+        } else {
+            warn!(
+                thread_id = %mapping.discord_thread_id,
+                identifier = %mapping.linear_identifier,
+                "Thread or issue is already mapped, skipping"
+            );
+            summary.skipped += 1;
+        }
+    }
+    Ok(summary)
+}
+
+pub fn write(
+    format: Format,
+    mappings: &[ExportedMapping],
+    mut writer: impl Write,
+) -> Result<(), TransferError> {
+    match format {
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut writer, mappings)?;
+            writeln!(writer)?;
+        }
+        Format::Csv => {
+            let mut csv = csv::Writer::from_writer(writer);
+            for m in mappings {
+                csv.serialize(CsvRow {
+                    discord_thread_id: m.discord_thread_id.clone(),
+                    linear_issue_id: m.linear_issue_id.clone(),
+                    linear_identifier: m.linear_identifier.clone(),
+                    channel_type: m.channel_type.clone(),
+                    created_at: m.created_at.clone(),
+                    guild_id: m.guild_id.clone(),
+                    status: m.status.clone(),
+                    comment_cursor: m.comment_cursor.clone(),
+                })?;
+            }
+            csv.flush()?;
+        }
+    }
+    Ok(())
+}
+
+pub fn read(format: Format, reader: impl Read) -> Result<Vec<ExportedMapping>, TransferError> {
+    match format {
+        Format::Json => Ok(serde_json::from_reader(reader)?),
+        Format::Csv => csv::Reader::from_reader(reader)
+            .deserialize::<CsvRow>()
+            .map(|row| {
+                let row = row?;
+                Ok(ExportedMapping {
+                    discord_thread_id: row.discord_thread_id,
+                    linear_issue_id: row.linear_issue_id,
+                    linear_identifier: row.linear_identifier,
+                    channel_type: row.channel_type,
+                    created_at: row.created_at,
+                    guild_id: row.guild_id,
+                    status: row.status,
+                    comment_cursor: row.comment_cursor,
+                    synced_comments: Vec::new(),
+                    relayed_comments: Vec::new(),
+                })
+            })
+            .collect(),
+    }
+}
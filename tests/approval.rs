@@ -0,0 +1,99 @@
+//! Threads held for moderator approval before their issue is created.
+
+mod common;
+
+use serenity::all::UserId;
+
+use discord_linear_bot::config::ChannelConfig;
+use discord_linear_bot::db;
+use discord_linear_bot::discord::approval::approve;
+use discord_linear_bot::sync::discord_to_linear::sync_discord_to_linear;
+
+use common::fake_discord::FakeDiscord;
+use common::mock_linear::MockLinear;
+use common::{channel_config, test_pool, FORUM_ID};
+
+const MODERATOR_ID: u64 = 4100;
+
+fn gated_config() -> ChannelConfig {
+    let mut config = channel_config();
+    config.approval_role_id = Some(777);
+    config
+}
+
+#[tokio::test]
+async fn thread_waits_for_approval_then_is_filed_once() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    let channel = gated_config();
+    let thread = discord.add_thread(FORUM_ID, "Crash on login", "It crashes");
+
+    sync_discord_to_linear(&discord, &pool, &channel, &linear, &thread)
+        .await
+        .unwrap();
+    // The reconcile pass leaves it waiting.
+    sync_discord_to_linear(&discord, &pool, &channel, &linear, &thread)
+        .await
+        .unwrap();
+
+    assert_eq!(linear.calls("create_issue"), 0);
+    let sent = discord.sent(thread.id);
+    assert_eq!(sent.len(), 1);
+    assert!(sent[0].content().starts_with("Pending triage"));
+    assert!(sent[0].content().contains("<@&777>"));
+
+    let moderator = UserId::new(MODERATOR_ID);
+    assert!(
+        approve(&discord, &pool, &channel, &linear, &thread, moderator)
+            .await
+            .unwrap()
+    );
+    assert!(
+        !approve(&discord, &pool, &channel, &linear, &thread, moderator)
+            .await
+            .unwrap()
+    );
+
+    assert_eq!(linear.calls("create_issue"), 1);
+    assert!(
+        db::get_mapping_by_discord_thread(&pool, &thread.id.to_string())
+            .await
+            .unwrap()
+            .is_some()
+    );
+    let approval = db::get_thread_approval(&pool, &thread.id.to_string())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        approval.approved_by_discord_user_id,
+        Some(MODERATOR_ID.to_string())
+    );
+    let edits = discord.edits(thread.id);
+    assert_eq!(edits.len(), 1);
+    assert_eq!(
+        edits[0].content(),
+        format!("Approved by <@{MODERATOR_ID}>.")
+    );
+}
+
+#[tokio::test]
+async fn channels_without_an_approval_role_file_immediately() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    let thread = discord.add_thread(FORUM_ID, "Crash on login", "It crashes");
+
+    sync_discord_to_linear(&discord, &pool, &channel_config(), &linear, &thread)
+        .await
+        .unwrap();
+
+    assert_eq!(linear.calls("create_issue"), 1);
+    assert!(db::get_thread_approval(&pool, &thread.id.to_string())
+        .await
+        .unwrap()
+        .is_none());
+}
@@ -0,0 +1,79 @@
+//! Attachments streamed from their download straight into a Linear upload slot.
+
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use discord_linear_bot::linear::api::LinearApi;
+use discord_linear_bot::linear::client::{LinearClient, UploadFile, UploadHeader};
+
+fn upload_slot(server: &MockServer) -> UploadFile {
+    UploadFile {
+        upload_url: format!("{}/upload", server.uri()),
+        asset_url: "https://uploads.linear.app/asset/video.mp4".to_string(),
+        headers: vec![UploadHeader {
+            key: "x-goog-meta-source".to_string(),
+            value: "discord".to_string(),
+        }],
+    }
+}
+
+#[tokio::test]
+async fn download_is_streamed_into_the_upload() {
+    let server = MockServer::start().await;
+    let video: Vec<u8> = (0..3 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+    Mock::given(method("GET"))
+        .and(path("/attachments/video.mp4"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(video.clone(), "video/mp4"))
+        .mount(&server)
+        .await;
+    Mock::given(method("PUT"))
+        .and(path("/upload"))
+        .and(header("content-length", video.len().to_string().as_str()))
+        .and(header("content-type", "video/mp4"))
+        .and(header("x-goog-meta-source", "discord"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let linear = LinearClient::new("key".to_string());
+
+    let download = linear
+        .download_attachment(&format!("{}/attachments/video.mp4", server.uri()))
+        .await
+        .unwrap();
+    assert_eq!(download.content_type, "video/mp4");
+    assert_eq!(download.size, Some(video.len() as u64));
+    let asset_url = linear
+        .upload_file_to_url(
+            &upload_slot(&server),
+            download.body,
+            download.size.unwrap(),
+            &download.content_type,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(asset_url, "https://uploads.linear.app/asset/video.mp4");
+    let requests = server.received_requests().await.unwrap();
+    let put = requests
+        .iter()
+        .find(|r| r.method.as_str() == "PUT")
+        .unwrap();
+    assert!(put.body == video, "uploaded bytes match the download");
+}
+
+#[tokio::test]
+async fn failed_download_is_not_uploaded() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(404).set_body_string("gone"))
+        .mount(&server)
+        .await;
+    let linear = LinearClient::new("key".to_string());
+
+    let result = linear
+        .download_attachment(&format!("{}/attachments/gone.png", server.uri()))
+        .await;
+
+    assert!(result.is_err());
+}
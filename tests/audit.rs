@@ -0,0 +1,83 @@
+//! Audit log entries written by the sync paths, as `/linear audit` lists them.
+
+mod common;
+
+use discord_linear_bot::db;
+use discord_linear_bot::sync::discord_to_linear::sync_discord_to_linear;
+use discord_linear_bot::sync::linear_to_discord::sync_linear_to_discord;
+
+use common::fake_discord::{FakeDiscord, REPORTER_ID};
+use common::mock_linear::MockLinear;
+use common::{channel_config, config, test_pool, FORUM_ID};
+
+#[tokio::test]
+async fn issue_lifecycle_is_recorded_newest_first() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    let config = config(vec![channel_config()]);
+    let thread = discord.add_thread(FORUM_ID, "Crash on login", "It crashes when I log in");
+    let thread_id = thread.id.to_string();
+
+    sync_discord_to_linear(&discord, &pool, &channel_config(), &linear, &thread)
+        .await
+        .unwrap();
+    let mapping = db::get_mapping_by_discord_thread(&pool, &thread_id)
+        .await
+        .unwrap()
+        .unwrap();
+    sync_linear_to_discord(
+        &discord,
+        &pool,
+        &config,
+        &linear,
+        &linear.move_to(&mapping.linear_issue_id, "Done"),
+    )
+    .await
+    .unwrap();
+    db::tombstone_mapping(&pool, &mapping, "unlinked", Some("42"))
+        .await
+        .unwrap();
+
+    let entries = db::get_audit_entries(&pool, &thread_id, 10).await.unwrap();
+    let actions: Vec<_> = entries.iter().map(|e| e.action.as_str()).collect();
+    assert_eq!(
+        actions,
+        [
+            "mapping_removed",
+            "thread_archived",
+            "status_announced",
+            "issue_created"
+        ]
+    );
+    assert!(entries
+        .iter()
+        .all(|e| e.linear_identifier == mapping.linear_identifier));
+
+    let unlinked = &entries[0];
+    assert_eq!(unlinked.actor_discord_user_id.as_deref(), Some("42"));
+    assert_eq!(unlinked.detail, "unlinked");
+    assert_eq!(entries[2].actor_discord_user_id, None);
+    assert_eq!(entries[2].detail, "Done");
+    let created = &entries[3];
+    assert_eq!(created.actor_discord_user_id, Some(REPORTER_ID.to_string()));
+    assert_eq!(created.detail, "Crash on login");
+}
+
+#[tokio::test]
+async fn entries_are_limited_to_the_thread() {
+    let pool = test_pool().await;
+    for n in 0..3 {
+        db::insert_audit_entry(&pool, "1", "ENG-1", "comment_posted", None, &n.to_string())
+            .await
+            .unwrap();
+    }
+    db::insert_audit_entry(&pool, "2", "ENG-2", "comment_posted", None, "other")
+        .await
+        .unwrap();
+
+    let entries = db::get_audit_entries(&pool, "1", 2).await.unwrap();
+    let details: Vec<_> = entries.iter().map(|e| e.detail.as_str()).collect();
+    assert_eq!(details, ["2", "1"]);
+}
@@ -0,0 +1,640 @@
+//! In-memory stand-in for Discord.
+//!
+//! Channels and messages live in a mutex. Everything the sync code sends is recorded, and thread
+//! edits are applied to the stored channel, so a test can assert on both "what was posted" and
+//! "what state is the thread in now".
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use serenity::all::{
+    ApplicationId, ChannelId, ChannelType, CreateForumPost, CreateMessage, CreateWebhook,
+    EditMessage, EditThread, EditWebhookMessage, ExecuteWebhook, ForumTagId, GuildChannel, GuildId,
+    Member, Message, MessageId, MessageReaction, ReactionType, Role, RoleId, ThreadMetadata,
+    Timestamp, User, UserId, Webhook,
+};
+use serenity::http::{ErrorResponse, HttpError};
+
+use discord_linear_bot::discord::port::DiscordPort;
+use discord_linear_bot::error::AppError;
+
+pub const GUILD_ID: u64 = 1000;
+pub const APPLICATION_ID: u64 = 3000;
+/// Author of every forum post created by `add_thread`.
+pub const REPORTER_ID: u64 = 4000;
+
+/// A message the sync code posted or edited, as the JSON body Discord would have received.
+#[derive(Debug, Clone)]
+pub struct Sent {
+    pub channel_id: ChannelId,
+    pub message_id: MessageId,
+    pub body: Value,
+}
+
+impl Sent {
+    pub fn content(&self) -> &str {
+        self.body["content"].as_str().unwrap_or_default()
+    }
+
+    pub fn has_embed(&self) -> bool {
+        self.body["embeds"]
+            .as_array()
+            .is_some_and(|e| !e.is_empty())
+    }
+}
+
+#[derive(Default)]
+struct FakeState {
+    channels: HashMap<ChannelId, GuildChannel>,
+    messages: HashMap<ChannelId, Vec<Message>>,
+    sent: Vec<Sent>,
+    edits: Vec<Sent>,
+    deleted: HashSet<MessageId>,
+    pinned: HashSet<MessageId>,
+    webhooks: HashMap<ChannelId, Vec<Webhook>>,
+    members: HashMap<UserId, Vec<RoleId>>,
+    dms: Vec<(UserId, Value)>,
+    closed_dms: HashSet<UserId>,
+    next_id: u64,
+}
+
+impl FakeState {
+    fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        // Clear of the IDs tests pick for channels.
+        1_000_000 + self.next_id
+    }
+}
+
+#[derive(Default)]
+pub struct FakeDiscord {
+    state: Mutex<FakeState>,
+}
+
+/// Metadata for an open, unlocked thread. `ThreadMetadata` has no public constructor.
+fn open_thread_metadata() -> ThreadMetadata {
+    serde_json::from_value(json!({
+        "archived": false,
+        "auto_archive_duration": 10080,
+        "archive_timestamp": null,
+    }))
+    .expect("valid thread metadata")
+}
+
+fn not_found(what: impl std::fmt::Display) -> AppError {
+    AppError::Internal(format!("Unknown {what} (fake Discord)"))
+}
+
+/// The 404 Discord answers a read of a deleted channel or message with, as `is_not_found`
+/// recognises it.
+async fn discord_not_found() -> AppError {
+    let response = http::Response::builder()
+        .status(404)
+        .body(r#"{"code": 10003, "message": "Unknown Channel"}"#)
+        .unwrap();
+    let error = ErrorResponse::from_response(response.into(), reqwest::Method::GET).await;
+    AppError::Discord(serenity::Error::Http(HttpError::UnsuccessfulRequest(error)))
+}
+
+impl FakeDiscord {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_forum(&self, forum_id: u64) {
+        let mut forum = GuildChannel::default();
+        forum.id = ChannelId::new(forum_id);
+        forum.guild_id = GuildId::new(GUILD_ID);
+        forum.kind = ChannelType::Forum;
+        forum.name = format!("forum-{forum_id}");
+        self.state.lock().unwrap().channels.insert(forum.id, forum);
+    }
+
+    pub fn add_text_channel(&self, channel_id: u64) {
+        let mut channel = GuildChannel::default();
+        channel.id = ChannelId::new(channel_id);
+        channel.guild_id = GuildId::new(GUILD_ID);
+        channel.kind = ChannelType::Text;
+        channel.name = format!("channel-{channel_id}");
+        self.state
+            .lock()
+            .unwrap()
+            .channels
+            .insert(channel.id, channel);
+    }
+
+    /// Create a forum post: a thread in `forum_id` whose starter message is `content`.
+    pub fn add_thread(&self, forum_id: u64, name: &str, content: &str) -> GuildChannel {
+        let mut state = self.state.lock().unwrap();
+        let mut thread = GuildChannel::default();
+        thread.id = ChannelId::new(state.next_id());
+        thread.guild_id = GuildId::new(GUILD_ID);
+        thread.parent_id = Some(ChannelId::new(forum_id));
+        thread.kind = ChannelType::PublicThread;
+        thread.name = name.to_string();
+        thread.owner_id = Some(UserId::new(REPORTER_ID));
+        thread.thread_metadata = Some(open_thread_metadata());
+
+        // Forum starter messages share the thread's ID.
+        let mut starter = Message::default();
+        starter.id = MessageId::new(thread.id.get());
+        starter.channel_id = thread.id;
+        starter.content = content.to_string();
+        starter.timestamp = Timestamp::now();
+        starter.author = User::default();
+        starter.author.id = UserId::new(REPORTER_ID);
+        starter.author.name = "reporter".to_string();
+
+        state.messages.insert(thread.id, vec![starter]);
+        state.channels.insert(thread.id, thread.clone());
+        thread
+    }
+
+    /// Post `content` in `thread_id` as `author_id`, `after_secs` after the thread's starter
+    /// message.
+    pub fn add_reply(&self, thread_id: ChannelId, author_id: u64, content: &str, after_secs: i64) {
+        let mut state = self.state.lock().unwrap();
+        let mut reply = Message::default();
+        reply.id = MessageId::new(state.next_id());
+        reply.channel_id = thread_id;
+        reply.content = content.to_string();
+        reply.author = User::default();
+        reply.author.id = UserId::new(author_id);
+        let messages = state.messages.entry(thread_id).or_default();
+        let posted = messages.first().map_or(0, |m| m.timestamp.unix_timestamp());
+        reply.timestamp = Timestamp::from_unix_timestamp(posted + after_secs).unwrap();
+        messages.push(reply);
+    }
+
+    /// Move a forum post's starter message `secs` into the past.
+    pub fn backdate_post(&self, thread_id: ChannelId, secs: i64) {
+        let mut state = self.state.lock().unwrap();
+        let starter = state
+            .messages
+            .get_mut(&thread_id)
+            .and_then(|messages| messages.first_mut())
+            .expect("post exists");
+        let posted = starter.timestamp.unix_timestamp() - secs;
+        starter.timestamp = Timestamp::from_unix_timestamp(posted).unwrap();
+    }
+
+    /// Make `user_id` a guild member holding `roles`. Other users aren't members.
+    pub fn add_member(&self, user_id: u64, roles: &[u64]) {
+        let roles = roles.iter().map(|r| RoleId::new(*r)).collect();
+        self.state
+            .lock()
+            .unwrap()
+            .members
+            .insert(UserId::new(user_id), roles);
+    }
+
+    /// Direct messages sent to `user_id`, oldest first.
+    pub fn dms(&self, user_id: u64) -> Vec<String> {
+        self.state
+            .lock()
+            .unwrap()
+            .dms
+            .iter()
+            .filter(|(user, _)| *user == UserId::new(user_id))
+            .map(|(_, body)| body["content"].as_str().unwrap_or_default().to_string())
+            .collect()
+    }
+
+    /// Make DMs to `user_id` fail, as for a user who doesn't accept them.
+    pub fn close_dms(&self, user_id: u64) {
+        self.state
+            .lock()
+            .unwrap()
+            .closed_dms
+            .insert(UserId::new(user_id));
+    }
+
+    /// Set the number of `emoji` reactions on a thread's starter message.
+    pub fn set_reactions(&self, thread_id: ChannelId, emoji: &str, count: u64) {
+        let reaction: MessageReaction = serde_json::from_value(json!({
+            "count": count,
+            "count_details": { "burst": 0, "normal": count },
+            "me": false,
+            "me_burst": false,
+            "emoji": { "id": null, "name": emoji },
+            "burst_colors": [],
+        }))
+        .expect("valid reaction");
+        let mut state = self.state.lock().unwrap();
+        let starter = state
+            .messages
+            .get_mut(&thread_id)
+            .and_then(|messages| messages.first_mut())
+            .expect("thread has a starter message");
+        starter
+            .reactions
+            .retain(|r| !matches!(&r.reaction_type, ReactionType::Unicode(e) if e == emoji));
+        starter.reactions.push(reaction);
+    }
+
+    /// Current state of a channel, including any edits applied by the sync code.
+    pub fn thread(&self, channel_id: ChannelId) -> GuildChannel {
+        self.state.lock().unwrap().channels[&channel_id].clone()
+    }
+
+    /// Delete a thread and its messages, as a moderator removing a post would.
+    pub fn delete_thread(&self, channel_id: ChannelId) {
+        let mut state = self.state.lock().unwrap();
+        state.channels.remove(&channel_id);
+        state.messages.remove(&channel_id);
+    }
+
+    pub fn is_archived(&self, channel_id: ChannelId) -> bool {
+        self.thread(channel_id)
+            .thread_metadata
+            .is_some_and(|m| m.archived)
+    }
+
+    pub fn is_locked(&self, channel_id: ChannelId) -> bool {
+        self.thread(channel_id)
+            .thread_metadata
+            .is_some_and(|m| m.locked)
+    }
+
+    /// Messages posted in `channel_id`, oldest first.
+    pub fn sent(&self, channel_id: ChannelId) -> Vec<Sent> {
+        self.state
+            .lock()
+            .unwrap()
+            .sent
+            .iter()
+            .filter(|s| s.channel_id == channel_id)
+            .cloned()
+            .collect()
+    }
+
+    /// Message edits made in `channel_id`, oldest first.
+    pub fn edits(&self, channel_id: ChannelId) -> Vec<Sent> {
+        self.state
+            .lock()
+            .unwrap()
+            .edits
+            .iter()
+            .filter(|s| s.channel_id == channel_id)
+            .cloned()
+            .collect()
+    }
+
+    pub fn is_deleted(&self, message_id: MessageId) -> bool {
+        self.state.lock().unwrap().deleted.contains(&message_id)
+    }
+
+    pub fn is_pinned(&self, message_id: MessageId) -> bool {
+        self.state.lock().unwrap().pinned.contains(&message_id)
+    }
+
+    /// Webhooks created in `channel_id`.
+    pub fn webhooks_in(&self, channel_id: ChannelId) -> Vec<Webhook> {
+        self.state
+            .lock()
+            .unwrap()
+            .webhooks
+            .get(&channel_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Record a message in `channel_id`, returning it as Discord would. `None` if the channel
+    /// doesn't exist.
+    fn post(&self, channel_id: ChannelId, body: Value) -> Option<Message> {
+        let mut state = self.state.lock().unwrap();
+        if !state.channels.contains_key(&channel_id) {
+            return None;
+        }
+
+        let mut sent = Message::default();
+        sent.id = MessageId::new(state.next_id());
+        sent.channel_id = channel_id;
+        sent.content = body["content"].as_str().unwrap_or_default().to_string();
+        sent.author = User::default();
+        sent.author.bot = true;
+        if let Some(username) = body["username"].as_str() {
+            sent.author.name = username.to_string();
+        }
+
+        state.sent.push(Sent {
+            channel_id,
+            message_id: sent.id,
+            body,
+        });
+        state
+            .messages
+            .entry(channel_id)
+            .or_default()
+            .push(sent.clone());
+        Some(sent)
+    }
+
+    /// Apply an edit to a stored message and record it.
+    async fn edit(
+        &self,
+        channel_id: ChannelId,
+        message_id: MessageId,
+        body: Value,
+    ) -> Result<Message, AppError> {
+        let mut state = self.state.lock().unwrap();
+        let message = state
+            .messages
+            .get_mut(&channel_id)
+            .and_then(|messages| messages.iter_mut().find(|m| m.id == message_id))
+            .ok_or_else(|| not_found(format_args!("message {message_id}")))?;
+        if let Some(content) = body["content"].as_str() {
+            message.content = content.to_string();
+        }
+        let edited = message.clone();
+        state.edits.push(Sent {
+            channel_id,
+            message_id,
+            body,
+        });
+        Ok(edited)
+    }
+
+    async fn delete(&self, channel_id: ChannelId, message_id: MessageId) -> Result<(), AppError> {
+        let mut state = self.state.lock().unwrap();
+        let messages = state
+            .messages
+            .get_mut(&channel_id)
+            .ok_or_else(|| not_found(format_args!("channel {channel_id}")))?;
+        let before = messages.len();
+        messages.retain(|m| m.id != message_id);
+        if messages.len() == before {
+            return Err(not_found(format_args!("message {message_id}")));
+        }
+        state.deleted.insert(message_id);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl DiscordPort for FakeDiscord {
+    async fn channel(&self, channel_id: ChannelId) -> Result<GuildChannel, AppError> {
+        let channel = self
+            .state
+            .lock()
+            .unwrap()
+            .channels
+            .get(&channel_id)
+            .cloned();
+        match channel {
+            Some(channel) => Ok(channel),
+            None => Err(discord_not_found().await),
+        }
+    }
+
+    async fn roles(&self, _guild_id: GuildId) -> Result<HashMap<RoleId, Role>, AppError> {
+        Ok(HashMap::new())
+    }
+
+    async fn member(&self, guild_id: GuildId, user_id: UserId) -> Result<Member, AppError> {
+        let roles = self.state.lock().unwrap().members.get(&user_id).cloned();
+        match roles {
+            Some(roles) => {
+                let mut member = Member::default();
+                member.guild_id = guild_id;
+                member.user.id = user_id;
+                member.roles = roles;
+                Ok(member)
+            }
+            None => Err(discord_not_found().await),
+        }
+    }
+
+    async fn active_threads(&self, guild_id: GuildId) -> Result<Vec<GuildChannel>, AppError> {
+        Ok(self
+            .state
+            .lock()
+            .unwrap()
+            .channels
+            .values()
+            .filter(|c| c.guild_id == guild_id)
+            .filter(|c| c.thread_metadata.is_some_and(|m| !m.archived))
+            .cloned()
+            .collect())
+    }
+
+    async fn first_message(&self, channel_id: ChannelId) -> Result<Option<Message>, AppError> {
+        Ok(self
+            .state
+            .lock()
+            .unwrap()
+            .messages
+            .get(&channel_id)
+            .and_then(|messages| messages.first().cloned()))
+    }
+
+    async fn opening_messages(&self, channel_id: ChannelId) -> Result<Vec<Message>, AppError> {
+        let state = self.state.lock().unwrap();
+        let messages = state.messages.get(&channel_id).cloned().unwrap_or_default();
+        Ok(messages.into_iter().take(100).collect())
+    }
+
+    async fn message(
+        &self,
+        channel_id: ChannelId,
+        message_id: MessageId,
+    ) -> Result<Message, AppError> {
+        let message = self
+            .state
+            .lock()
+            .unwrap()
+            .messages
+            .get(&channel_id)
+            .and_then(|messages| messages.iter().find(|m| m.id == message_id).cloned());
+        match message {
+            Some(message) => Ok(message),
+            None => Err(discord_not_found().await),
+        }
+    }
+
+    async fn send_message(
+        &self,
+        channel_id: ChannelId,
+        message: CreateMessage,
+    ) -> Result<Message, AppError> {
+        self.post(channel_id, serde_json::to_value(&message)?)
+            .ok_or_else(|| not_found(format_args!("channel {channel_id}")))
+    }
+
+    async fn send_dm(&self, user_id: UserId, message: CreateMessage) -> Result<(), AppError> {
+        let mut state = self.state.lock().unwrap();
+        if state.closed_dms.contains(&user_id) {
+            return Err(AppError::Internal(format!(
+                "Cannot send messages to {user_id} (fake Discord)"
+            )));
+        }
+        state.dms.push((user_id, serde_json::to_value(&message)?));
+        Ok(())
+    }
+
+    async fn edit_message(
+        &self,
+        channel_id: ChannelId,
+        message_id: MessageId,
+        edit: EditMessage,
+    ) -> Result<Message, AppError> {
+        self.edit(channel_id, message_id, serde_json::to_value(&edit)?)
+            .await
+    }
+
+    async fn delete_message(
+        &self,
+        channel_id: ChannelId,
+        message_id: MessageId,
+    ) -> Result<(), AppError> {
+        self.delete(channel_id, message_id).await
+    }
+
+    async fn pin_message(
+        &self,
+        _channel_id: ChannelId,
+        message_id: MessageId,
+    ) -> Result<(), AppError> {
+        self.state.lock().unwrap().pinned.insert(message_id);
+        Ok(())
+    }
+
+    async fn edit_thread(
+        &self,
+        channel_id: ChannelId,
+        edit: EditThread<'_>,
+    ) -> Result<GuildChannel, AppError> {
+        let body = serde_json::to_value(&edit)?;
+        let mut state = self.state.lock().unwrap();
+        let thread = state
+            .channels
+            .get_mut(&channel_id)
+            .ok_or_else(|| not_found(format_args!("channel {channel_id}")))?;
+        let metadata = thread
+            .thread_metadata
+            .get_or_insert_with(open_thread_metadata);
+        if let Some(archived) = body["archived"].as_bool() {
+            metadata.archived = archived;
+        }
+        if let Some(locked) = body["locked"].as_bool() {
+            metadata.locked = locked;
+        }
+        if let Some(name) = body["name"].as_str() {
+            thread.name = name.to_string();
+        }
+        if let Some(tags) = body["applied_tags"].as_array() {
+            thread.applied_tags = tags
+                .iter()
+                .filter_map(|t| t.as_str()?.parse().ok().map(ForumTagId::new))
+                .collect();
+        }
+        Ok(thread.clone())
+    }
+
+    async fn create_forum_post(
+        &self,
+        forum_id: ChannelId,
+        post: CreateForumPost<'_>,
+    ) -> Result<GuildChannel, AppError> {
+        let body = serde_json::to_value(&post)?;
+        let mut state = self.state.lock().unwrap();
+        if !state.channels.contains_key(&forum_id) {
+            return Err(not_found(format_args!("forum {forum_id}")));
+        }
+        let mut thread = GuildChannel::default();
+        thread.id = ChannelId::new(state.next_id());
+        thread.guild_id = GuildId::new(GUILD_ID);
+        thread.parent_id = Some(forum_id);
+        thread.kind = ChannelType::PublicThread;
+        thread.name = body["name"].as_str().unwrap_or_default().to_string();
+        thread.owner_id = Some(UserId::new(APPLICATION_ID));
+        thread.thread_metadata = Some(open_thread_metadata());
+
+        let mut starter = Message::default();
+        starter.id = MessageId::new(thread.id.get());
+        starter.channel_id = thread.id;
+        starter.content = body["message"]["content"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        starter.timestamp = Timestamp::now();
+        starter.author = User::default();
+        starter.author.id = UserId::new(APPLICATION_ID);
+        starter.author.bot = true;
+
+        state.sent.push(Sent {
+            channel_id: thread.id,
+            message_id: starter.id,
+            body: body["message"].clone(),
+        });
+        state.messages.insert(thread.id, vec![starter]);
+        state.channels.insert(thread.id, thread.clone());
+        Ok(thread)
+    }
+
+    fn application_id(&self) -> Option<ApplicationId> {
+        Some(ApplicationId::new(APPLICATION_ID))
+    }
+
+    async fn webhooks(&self, channel_id: ChannelId) -> Result<Vec<Webhook>, AppError> {
+        Ok(self.webhooks_in(channel_id))
+    }
+
+    async fn create_webhook(
+        &self,
+        channel_id: ChannelId,
+        builder: CreateWebhook<'_>,
+    ) -> Result<Webhook, AppError> {
+        let body = serde_json::to_value(&builder)?;
+        let mut state = self.state.lock().unwrap();
+        let id = state.next_id();
+        let webhook: Webhook = serde_json::from_value(json!({
+            "id": id.to_string(),
+            "type": 1,
+            "guild_id": GUILD_ID.to_string(),
+            "channel_id": channel_id.to_string(),
+            "name": body["name"],
+            "avatar": null,
+            "token": format!("token-{id}"),
+            "application_id": APPLICATION_ID.to_string(),
+        }))?;
+        state
+            .webhooks
+            .entry(channel_id)
+            .or_default()
+            .push(webhook.clone());
+        Ok(webhook)
+    }
+
+    async fn execute_webhook(
+        &self,
+        _webhook: &Webhook,
+        thread: ChannelId,
+        builder: ExecuteWebhook,
+    ) -> Result<Message, AppError> {
+        self.post(thread, serde_json::to_value(&builder)?)
+            .ok_or_else(|| not_found(format_args!("channel {thread}")))
+    }
+
+    async fn edit_webhook_message(
+        &self,
+        _webhook: &Webhook,
+        thread: ChannelId,
+        message_id: MessageId,
+        edit: EditWebhookMessage,
+    ) -> Result<Message, AppError> {
+        self.edit(thread, message_id, serde_json::to_value(&edit)?)
+            .await
+    }
+
+    async fn delete_webhook_message(
+        &self,
+        _webhook: &Webhook,
+        thread: ChannelId,
+        message_id: MessageId,
+    ) -> Result<(), AppError> {
+        self.delete(thread, message_id).await
+    }
+}
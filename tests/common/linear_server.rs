@@ -0,0 +1,437 @@
+//! Linear's GraphQL endpoint, served by wiremock.
+//!
+//! Where [`super::mock_linear::MockLinear`] replaces the client, this keeps the real
+//! `LinearClient` and answers its HTTP requests, so query building and response parsing are
+//! exercised too. Requests are dispatched on the operation name in the query and answered from a
+//! small in-memory model of issues and comments; an operation the model doesn't know gets a
+//! GraphQL error, which fails the sync call that sent it.
+
+use std::sync::{Arc, Mutex};
+
+use serde_json::{json, Value};
+use wiremock::matchers::{header, method};
+use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+use discord_linear_bot::linear::client::{LinearClient, LinearUser};
+
+use super::mock_linear::{now, BOT_USER_ID, STATES};
+
+/// The API key the client is built with; requests without it are rejected.
+pub const API_KEY: &str = "lin_api_test";
+
+#[derive(Debug, Clone)]
+pub struct ServerIssue {
+    pub id: String,
+    pub identifier: String,
+    pub team_id: String,
+    pub title: String,
+    pub description: String,
+    pub label_ids: Vec<String>,
+    pub project_id: String,
+    pub state_name: String,
+    pub state_type: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ServerComment {
+    pub id: String,
+    pub issue_id: String,
+    pub body: String,
+    pub user: LinearUser,
+    pub created_at: String,
+}
+
+/// A request the server answered: its operation name and variables.
+#[derive(Debug, Clone)]
+pub struct Received {
+    pub operation: String,
+    pub variables: Value,
+}
+
+#[derive(Default)]
+struct ServerState {
+    issues: Vec<ServerIssue>,
+    comments: Vec<ServerComment>,
+    received: Vec<Received>,
+    rate_limit: Option<(u64, i64)>,
+}
+
+pub struct LinearServer {
+    server: MockServer,
+    state: Arc<Mutex<ServerState>>,
+}
+
+impl LinearServer {
+    pub async fn start() -> Self {
+        let server = MockServer::start().await;
+        let state = Arc::new(Mutex::new(ServerState::default()));
+        Mock::given(method("POST"))
+            .and(header("Authorization", API_KEY))
+            .respond_with(GraphQl(state.clone()))
+            .mount(&server)
+            .await;
+        Self { server, state }
+    }
+
+    /// Answer the next `times` requests with HTTP `status` before they reach the model, as an
+    /// outage at Linear's edge would.
+    pub async fn fail_next(&self, status: u16, times: u64) {
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(status))
+            .up_to_n_times(times)
+            .with_priority(1)
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Report `remaining` requests left until `reset_at_ms` (epoch milliseconds) in the rate
+    /// limit headers of every response from now on.
+    pub fn set_rate_limit(&self, remaining: u64, reset_at_ms: i64) {
+        self.state.lock().unwrap().rate_limit = Some((remaining, reset_at_ms));
+    }
+
+    /// Every HTTP request received, including ones answered by [`Self::fail_next`].
+    pub async fn request_count(&self) -> usize {
+        self.server
+            .received_requests()
+            .await
+            .unwrap_or_default()
+            .len()
+    }
+
+    /// A client pointed at this server.
+    pub fn client(&self) -> LinearClient {
+        LinearClient::with_endpoint(API_KEY.to_string(), self.server.uri())
+    }
+
+    /// Operation names of every request received, oldest first.
+    pub fn operations(&self) -> Vec<String> {
+        self.state
+            .lock()
+            .unwrap()
+            .received
+            .iter()
+            .map(|r| r.operation.clone())
+            .collect()
+    }
+
+    /// Variables of every `operation` request received, oldest first.
+    pub fn variables(&self, operation: &str) -> Vec<Value> {
+        self.state
+            .lock()
+            .unwrap()
+            .received
+            .iter()
+            .filter(|r| r.operation == operation)
+            .map(|r| r.variables.clone())
+            .collect()
+    }
+
+    /// An issue by ID or identifier.
+    pub fn issue(&self, id_or_identifier: &str) -> Option<ServerIssue> {
+        self.state
+            .lock()
+            .unwrap()
+            .find_issue(id_or_identifier)
+            .cloned()
+    }
+
+    /// Comments on an issue, oldest first.
+    pub fn comments(&self, issue_id: &str) -> Vec<ServerComment> {
+        self.state
+            .lock()
+            .unwrap()
+            .comments
+            .iter()
+            .filter(|c| c.issue_id == issue_id)
+            .cloned()
+            .collect()
+    }
+
+    /// Move an issue to the workflow state named `name`, as if changed in Linear.
+    pub fn set_status(&self, issue_id: &str, name: &str) {
+        let (_, name, state_type) = STATES
+            .iter()
+            .copied()
+            .find(|(_, n, _)| *n == name)
+            .expect("known state");
+        let mut state = self.state.lock().unwrap();
+        let issue = state
+            .issues
+            .iter_mut()
+            .find(|i| i.id == issue_id)
+            .expect("known issue");
+        issue.state_name = name.to_string();
+        issue.state_type = state_type.to_string();
+        issue.updated_at = now();
+    }
+
+    /// Write a comment in Linear as `user`. Returns the comment ID.
+    pub fn add_comment(&self, issue_id: &str, user: &LinearUser, body: &str) -> String {
+        let mut state = self.state.lock().unwrap();
+        let id = format!("comment-{}", state.comments.len() + 1);
+        state.comments.push(ServerComment {
+            id: id.clone(),
+            issue_id: issue_id.to_string(),
+            body: body.to_string(),
+            user: user.clone(),
+            created_at: now(),
+        });
+        id
+    }
+}
+
+impl ServerState {
+    fn find_issue(&self, id_or_identifier: &str) -> Option<&ServerIssue> {
+        self.issues
+            .iter()
+            .find(|i| i.id == id_or_identifier || i.identifier == id_or_identifier)
+    }
+
+    fn issue_create(&mut self, input: &Value) -> Result<Value, String> {
+        let n = self.issues.len() + 1;
+        let (_, state_name, state_type) = STATES[0];
+        let issue = ServerIssue {
+            id: format!("issue-{n}"),
+            identifier: format!("ENG-{n}"),
+            team_id: string(&input["teamId"])?,
+            title: string(&input["title"])?,
+            description: string(&input["description"])?,
+            label_ids: serde_json::from_value(input["labelIds"].clone())
+                .map_err(|e| format!("labelIds: {e}"))?,
+            project_id: string(&input["projectId"])?,
+            state_name: state_name.to_string(),
+            state_type: state_type.to_string(),
+            updated_at: now(),
+        };
+        let created = json!({
+            "issueCreate": {
+                "success": true,
+                "issue": {
+                    "id": issue.id,
+                    "identifier": issue.identifier,
+                    "title": issue.title,
+                    "url": issue_url(&issue),
+                },
+            },
+        });
+        self.issues.push(issue);
+        Ok(created)
+    }
+
+    fn attachment_link_url(&self, variables: &Value) -> Result<Value, String> {
+        let issue_id = string(&variables["issueId"])?;
+        string(&variables["url"])?;
+        self.find_issue(&issue_id)
+            .ok_or_else(|| format!("Entity not found: {issue_id}"))?;
+        Ok(json!({ "attachmentLinkURL": { "success": true } }))
+    }
+
+    fn issue(&self, variables: &Value) -> Result<Value, String> {
+        let id = string(&variables["id"])?;
+        let issue = self.find_issue(&id).ok_or_else(|| not_found(&id))?;
+        let issue = json!({
+            "id": issue.id,
+            "identifier": issue.identifier,
+            "title": issue.title,
+            "url": issue_url(issue),
+            "priorityLabel": "No priority",
+            "updatedAt": issue.updated_at,
+            "state": {
+                "name": issue.state_name,
+                "type": issue.state_type,
+                "color": "#5e6ad2",
+            },
+            "assignee": null,
+            "project": null,
+            "labels": { "nodes": [] },
+        });
+        Ok(json!({ "issue": issue }))
+    }
+
+    fn updated_issues(&self, variables: &Value) -> Result<Value, String> {
+        let team_id = string(&variables["teamId"])?;
+        let since = string(&variables["since"])?;
+        let nodes: Vec<Value> = self
+            .issues
+            .iter()
+            .filter(|i| i.team_id == team_id && i.updated_at > since)
+            .map(issue_status)
+            .collect();
+        Ok(json!({
+            "issues": {
+                "pageInfo": { "hasNextPage": false, "endCursor": null },
+                "nodes": nodes,
+            },
+        }))
+    }
+
+    fn issues_by_ids(&self, variables: &Value) -> Result<Value, String> {
+        let ids: Vec<&str> = variables["ids"]
+            .as_array()
+            .ok_or("missing ids")?
+            .iter()
+            .filter_map(Value::as_str)
+            .collect();
+        let nodes: Vec<Value> = self
+            .issues
+            .iter()
+            .filter(|i| ids.contains(&i.id.as_str()))
+            .map(issue_status)
+            .collect();
+        Ok(json!({ "issues": { "nodes": nodes } }))
+    }
+
+    fn issue_comments(&self, variables: &Value) -> Result<Value, String> {
+        let issue_id = string(&variables["issueId"])?;
+        self.find_issue(&issue_id)
+            .ok_or_else(|| not_found(&issue_id))?;
+        let since = variables["filter"]["createdAt"]["gt"].as_str();
+        let nodes: Vec<Value> = self
+            .comments
+            .iter()
+            .filter(|c| c.issue_id == issue_id && since.is_none_or(|s| c.created_at.as_str() > s))
+            .map(|comment| {
+                json!({
+                    "id": comment.id,
+                    "body": comment.body,
+                    "createdAt": comment.created_at,
+                    "updatedAt": comment.created_at,
+                    "user": {
+                        "id": comment.user.id,
+                        "displayName": comment.user.display_name,
+                        "email": comment.user.email,
+                        "avatarUrl": null,
+                    },
+                    "botActor": null,
+                })
+            })
+            .collect();
+        Ok(json!({ "issue": { "comments": { "nodes": nodes } } }))
+    }
+
+    fn comment_create(&mut self, input: &Value) -> Result<Value, String> {
+        let issue_id = string(&input["issueId"])?;
+        if self.find_issue(&issue_id).is_none() {
+            return Err(format!("Entity not found: issue {issue_id}"));
+        }
+        let id = match input["id"].as_str() {
+            Some(id) => id.to_string(),
+            None => format!("comment-{}", self.comments.len() + 1),
+        };
+        self.comments.push(ServerComment {
+            id: id.clone(),
+            issue_id,
+            body: string(&input["body"])?,
+            user: bot_user(),
+            created_at: now(),
+        });
+        Ok(json!({ "commentCreate": { "success": true, "comment": { "id": id } } }))
+    }
+}
+
+fn string(value: &Value) -> Result<String, String> {
+    value
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| format!("expected a string, got {value}"))
+}
+
+/// Linear's error for an `issue(id:)` that doesn't exist; the field is non-null.
+fn not_found(id: &str) -> String {
+    format!("Entity not found: Issue - Could not find referenced Issue {id}.")
+}
+
+fn issue_url(issue: &ServerIssue) -> String {
+    format!("https://linear.app/test/issue/{}", issue.identifier)
+}
+
+/// `issue` as the `IssueStatusFields` fragment.
+fn issue_status(issue: &ServerIssue) -> Value {
+    let labels: Vec<Value> = issue
+        .label_ids
+        .iter()
+        .map(|id| json!({ "id": id, "name": id }))
+        .collect();
+    json!({
+        "id": issue.id,
+        "identifier": issue.identifier,
+        "title": issue.title,
+        "url": issue_url(issue),
+        "priorityLabel": "No priority",
+        "assignee": null,
+        "dueDate": null,
+        "state": {
+            "name": issue.state_name,
+            "type": issue.state_type,
+            "color": "#5e6ad2",
+        },
+        "project": null,
+        "labels": { "nodes": labels },
+        "updatedAt": issue.updated_at,
+    })
+}
+
+fn bot_user() -> LinearUser {
+    LinearUser {
+        id: BOT_USER_ID.to_string(),
+        name: "bot".to_string(),
+        display_name: "Bot".to_string(),
+        email: "bot@example.com".to_string(),
+    }
+}
+
+/// `query Name(...)` / `mutation Name(...)` → `Name`.
+fn operation_name(query: &str) -> String {
+    query
+        .split_whitespace()
+        .skip_while(|word| *word != "query" && *word != "mutation")
+        .nth(1)
+        .unwrap_or_default()
+        .split(['(', '{'])
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+struct GraphQl(Arc<Mutex<ServerState>>);
+
+impl Respond for GraphQl {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let body: Value = match request.body_json() {
+            Ok(body) => body,
+            Err(e) => return ResponseTemplate::new(400).set_body_string(e.to_string()),
+        };
+        let operation = operation_name(body["query"].as_str().unwrap_or_default());
+        let variables = body["variables"].clone();
+
+        let mut state = self.0.lock().unwrap();
+        state.received.push(Received {
+            operation: operation.clone(),
+            variables: variables.clone(),
+        });
+        let result = match operation.as_str() {
+            "CreateIssue" => state.issue_create(&variables["input"]),
+            "Issue" => state.issue(&variables),
+            "UpdatedIssues" => state.updated_issues(&variables),
+            "IssuesByIds" => state.issues_by_ids(&variables),
+            "IssueComments" => state.issue_comments(&variables),
+            "CreateComment" => state.comment_create(&variables["input"]),
+            "AttachmentLinkUrl" => state.attachment_link_url(&variables),
+            other => Err(format!("Unhandled operation {other:?}")),
+        };
+
+        let response = match result {
+            Ok(data) => json!({ "data": data }),
+            Err(message) => json!({ "errors": [{ "message": message }] }),
+        };
+        let mut template = ResponseTemplate::new(200).set_body_json(response);
+        if let Some((remaining, reset_at_ms)) = state.rate_limit {
+            template = template
+                .insert_header("X-RateLimit-Requests-Remaining", remaining.to_string())
+                .insert_header("X-RateLimit-Requests-Reset", reset_at_ms.to_string());
+        }
+        template
+    }
+}
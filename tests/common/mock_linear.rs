@@ -0,0 +1,753 @@
+//! In-memory stand-in for Linear.
+//!
+//! Holds issues, comments, and team members in a mutex and answers [`LinearApi`] calls from
+//! them, so sync code can be exercised without the real API. Tests arrange "Linear-side"
+//! changes (a status change, a comment written in Linear) through the helper methods, and can
+//! make any operation fail with [`MockLinear::fail`].
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use chrono::Utc;
+
+use discord_linear_bot::error::AppError;
+use discord_linear_bot::linear::api::LinearApi;
+use discord_linear_bot::linear::client::{
+    priority_label, AttachmentDownload, CommentAuthor, Customer, LinearAssignee, LinearComment,
+    LinearIssue, LinearIssueDetail, LinearIssueStatus, LinearSearchResult, LinearUser,
+    LinearWorkflowState, TeamMetadata, UploadFile,
+};
+
+/// Color of every mock workflow state.
+const STATE_COLOR: &str = "#5e6ad2";
+
+/// Workflow states every mock team has: (id, name, type).
+pub const STATES: [(&str, &str, &str); 6] = [
+    ("state-triage", "Triage", "triage"),
+    ("state-backlog", "Backlog", "backlog"),
+    ("state-todo", "Todo", "unstarted"),
+    ("state-in-progress", "In Progress", "started"),
+    ("state-done", "Done", "completed"),
+    ("state-canceled", "Canceled", "canceled"),
+];
+
+/// The user the bot's API key belongs to; authors every comment created through the API.
+pub const BOT_USER_ID: &str = "user-bot";
+
+#[derive(Debug, Clone)]
+pub struct MockIssue {
+    pub id: String,
+    pub identifier: String,
+    pub team_id: String,
+    pub title: String,
+    pub description: String,
+    pub url: String,
+    pub label_ids: Vec<String>,
+    pub project_id: String,
+    pub state_id: String,
+    pub assignee: Option<LinearUser>,
+    pub priority: i64,
+    pub estimate: Option<i64>,
+    pub due_date: Option<String>,
+    pub updated_at: String,
+    /// URLs attached with `link_url`
+    pub links: Vec<String>,
+}
+
+impl MockIssue {
+    fn state(&self) -> (&'static str, &'static str, &'static str) {
+        STATES
+            .iter()
+            .copied()
+            .find(|(id, _, _)| *id == self.state_id)
+            .expect("mock issue in unknown state")
+    }
+
+    fn status(&self) -> LinearIssueStatus {
+        let (_, name, state_type) = self.state();
+        LinearIssueStatus {
+            id: self.id.clone(),
+            identifier: self.identifier.clone(),
+            title: self.title.clone(),
+            url: self.url.clone(),
+            assignee: self.assignee.as_ref().map(|user| LinearAssignee {
+                id: user.id.clone(),
+                name: user.display_name.clone(),
+            }),
+            due_date: self.due_date.clone(),
+            status_name: name.to_string(),
+            status_type: state_type.to_string(),
+            status_color: STATE_COLOR.to_string(),
+            priority_label: priority_label(self.priority).to_string(),
+            label_ids: self.label_ids.clone(),
+            // Mock labels are named after their IDs.
+            labels: self.label_ids.clone(),
+            project_name: None,
+            updated_at: self.updated_at.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MockComment {
+    issue_id: String,
+    comment: LinearComment,
+}
+
+/// A customer need recorded with `create_customer_need`.
+#[derive(Debug, Clone)]
+pub struct MockCustomerNeed {
+    pub issue_id: String,
+    pub customer_external_id: String,
+    pub customer_name: String,
+    pub body: String,
+    pub url: String,
+}
+
+#[derive(Default)]
+struct MockState {
+    issues: Vec<MockIssue>,
+    comments: Vec<MockComment>,
+    members: HashMap<String, Vec<LinearUser>>,
+    /// `(id, name)` labels and projects per team.
+    labels: HashMap<String, Vec<(String, String)>>,
+    projects: HashMap<String, Vec<(String, String)>>,
+    uploads: HashMap<String, Vec<u8>>,
+    customer_needs: Vec<MockCustomerNeed>,
+    failing: HashSet<&'static str>,
+    calls: HashMap<&'static str, usize>,
+}
+
+#[derive(Default)]
+pub struct MockLinear {
+    state: Mutex<MockState>,
+}
+
+/// Linear-style timestamp, so string order is time order. Never returns the same value twice:
+/// calls within one millisecond would otherwise tie and break `since` filtering.
+pub fn now() -> String {
+    static LAST: Mutex<String> = Mutex::new(String::new());
+    let mut last = LAST.lock().unwrap();
+    let mut stamp = Utc::now();
+    loop {
+        let formatted = stamp.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
+        if formatted > *last {
+            *last = formatted.clone();
+            return formatted;
+        }
+        stamp += chrono::Duration::milliseconds(1);
+    }
+}
+
+fn not_found(what: &str) -> AppError {
+    AppError::LinearApi(format!("Entity not found: {what}"))
+}
+
+impl MockLinear {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make every subsequent call to `operation` (a [`LinearApi`] method name) fail.
+    pub fn customer_needs(&self) -> Vec<MockCustomerNeed> {
+        self.state.lock().unwrap().customer_needs.clone()
+    }
+
+    pub fn fail(&self, operation: &'static str) {
+        self.state.lock().unwrap().failing.insert(operation);
+    }
+
+    /// Let `operation` succeed again after [`MockLinear::fail`].
+    pub fn recover(&self, operation: &'static str) {
+        self.state.lock().unwrap().failing.remove(operation);
+    }
+
+    /// Number of times `operation` has been called, including failed calls.
+    pub fn calls(&self, operation: &'static str) -> usize {
+        self.state
+            .lock()
+            .unwrap()
+            .calls
+            .get(operation)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub fn add_member(&self, team_id: &str, user: LinearUser) {
+        self.state
+            .lock()
+            .unwrap()
+            .members
+            .entry(team_id.to_string())
+            .or_default()
+            .push(user);
+    }
+
+    pub fn add_label(&self, team_id: &str, label_id: &str, name: &str) {
+        self.state
+            .lock()
+            .unwrap()
+            .labels
+            .entry(team_id.to_string())
+            .or_default()
+            .push((label_id.to_string(), name.to_string()));
+    }
+
+    pub fn add_project(&self, team_id: &str, project_id: &str, name: &str) {
+        self.state
+            .lock()
+            .unwrap()
+            .projects
+            .entry(team_id.to_string())
+            .or_default()
+            .push((project_id.to_string(), name.to_string()));
+    }
+
+    /// Snapshot of an issue by ID or identifier.
+    pub fn issue(&self, id_or_identifier: &str) -> Option<MockIssue> {
+        self.state
+            .lock()
+            .unwrap()
+            .issues
+            .iter()
+            .find(|issue| issue.id == id_or_identifier || issue.identifier == id_or_identifier)
+            .cloned()
+    }
+
+    /// Comments on an issue in creation order.
+    pub fn comments(&self, issue_id: &str) -> Vec<LinearComment> {
+        self.state
+            .lock()
+            .unwrap()
+            .comments
+            .iter()
+            .filter(|c| c.issue_id == issue_id)
+            .map(|c| c.comment.clone())
+            .collect()
+    }
+
+    /// Move an issue to the state named `status_name`, as if someone changed it in Linear.
+    pub fn set_status(&self, issue_id: &str, status_name: &str) {
+        let (state_id, _, _) = STATES
+            .iter()
+            .find(|(_, name, _)| *name == status_name)
+            .expect("unknown mock status");
+        self.with_issue(issue_id, |issue| issue.state_id = state_id.to_string())
+            .expect("unknown mock issue");
+    }
+
+    /// [`set_status`](Self::set_status), returning the issue as a poll would then see it.
+    pub fn move_to(&self, issue_id: &str, status_name: &str) -> LinearIssueDetail {
+        self.set_status(issue_id, status_name);
+        self.issue(issue_id).unwrap().status().detail()
+    }
+
+    /// Replace an issue's labels, as if someone edited them in Linear.
+    pub fn set_labels(&self, issue_id: &str, label_ids: &[&str]) {
+        self.with_issue(issue_id, |issue| {
+            issue.label_ids = label_ids.iter().map(|l| l.to_string()).collect();
+        })
+        .expect("unknown mock issue");
+    }
+
+    /// Backdate an issue's last update, as if nothing has happened to it since `days` ago.
+    pub fn set_last_update(&self, issue_id: &str, days: i64) {
+        let mut state = self.state.lock().unwrap();
+        let issue = state
+            .issues
+            .iter_mut()
+            .find(|issue| issue.id == issue_id)
+            .expect("unknown mock issue");
+        issue.updated_at = (Utc::now() - chrono::Duration::days(days))
+            .format("%Y-%m-%dT%H:%M:%S%.3fZ")
+            .to_string();
+    }
+
+    /// Add a comment written in Linear by someone other than the bot. Returns its ID.
+    pub fn add_comment(&self, issue_id: &str, author: &LinearUser, body: &str) -> String {
+        let id = uuid::Uuid::new_v4().to_string();
+        let created_at = now();
+        self.state.lock().unwrap().comments.push(MockComment {
+            issue_id: issue_id.to_string(),
+            comment: LinearComment {
+                id: id.clone(),
+                body: body.to_string(),
+                created_at: created_at.clone(),
+                updated_at: created_at,
+                author_name: author.display_name.clone(),
+                author_avatar_url: None,
+                author_id: Some(author.id.clone()),
+                author_email: Some(author.email.clone()),
+            },
+        });
+        id
+    }
+
+    /// Record a call to `operation`, failing it if the test asked for that.
+    fn enter(&self, operation: &'static str) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        *state.calls.entry(operation).or_default() += 1;
+        if state.failing.contains(operation) {
+            return Err(format!("{operation} failed (mock)"));
+        }
+        Ok(())
+    }
+
+    /// Apply `update` to an issue and bump its `updatedAt`.
+    fn with_issue(
+        &self,
+        id_or_identifier: &str,
+        update: impl FnOnce(&mut MockIssue),
+    ) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        let issue = state
+            .issues
+            .iter_mut()
+            .find(|issue| issue.id == id_or_identifier || issue.identifier == id_or_identifier)
+            .ok_or_else(|| format!("Entity not found: {id_or_identifier}"))?;
+        update(issue);
+        issue.updated_at = now();
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl LinearApi for MockLinear {
+    async fn create_issue(
+        &self,
+        team_id: &str,
+        title: &str,
+        description: &str,
+        label_ids: &[String],
+        project_id: &str,
+    ) -> Result<LinearIssue, AppError> {
+        self.enter("create_issue").map_err(AppError::LinearApi)?;
+        let mut state = self.state.lock().unwrap();
+        let identifier = format!("MOCK-{}", state.issues.len() + 1);
+        let issue = MockIssue {
+            id: uuid::Uuid::new_v4().to_string(),
+            url: format!("https://linear.app/mock/issue/{identifier}"),
+            identifier,
+            team_id: team_id.to_string(),
+            title: title.to_string(),
+            description: description.to_string(),
+            label_ids: label_ids.to_vec(),
+            project_id: project_id.to_string(),
+            state_id: STATES[0].0.to_string(),
+            assignee: None,
+            priority: 0,
+            estimate: None,
+            due_date: None,
+            updated_at: now(),
+            links: Vec::new(),
+        };
+        let created = LinearIssue {
+            id: issue.id.clone(),
+            identifier: issue.identifier.clone(),
+            title: issue.title.clone(),
+            url: issue.url.clone(),
+        };
+        state.issues.push(issue);
+        Ok(created)
+    }
+
+    async fn get_issue(&self, id_or_identifier: &str) -> Result<LinearIssueDetail, AppError> {
+        self.enter("get_issue").map_err(AppError::LinearApi)?;
+        let issue = self
+            .issue(id_or_identifier)
+            .ok_or_else(|| not_found(id_or_identifier))?;
+        Ok(issue.status().detail())
+    }
+
+    async fn get_updated_issues(
+        &self,
+        team_id: &str,
+        since: &str,
+    ) -> Result<Vec<LinearIssueStatus>, AppError> {
+        self.enter("get_updated_issues")
+            .map_err(AppError::LinearApi)?;
+        Ok(self
+            .state
+            .lock()
+            .unwrap()
+            .issues
+            .iter()
+            .filter(|issue| issue.team_id == team_id && issue.updated_at.as_str() > since)
+            .map(MockIssue::status)
+            .collect())
+    }
+
+    async fn get_issues_by_ids(&self, ids: &[String]) -> Result<Vec<LinearIssueStatus>, AppError> {
+        self.enter("get_issues_by_ids")
+            .map_err(AppError::LinearApi)?;
+        Ok(self
+            .state
+            .lock()
+            .unwrap()
+            .issues
+            .iter()
+            .filter(|issue| ids.contains(&issue.id))
+            .map(MockIssue::status)
+            .collect())
+    }
+
+    async fn get_issue_comments(
+        &self,
+        issue_id: &str,
+        since: Option<&str>,
+    ) -> Result<Vec<LinearComment>, AppError> {
+        self.enter("get_issue_comments")
+            .map_err(AppError::LinearApi)?;
+        if self.issue(issue_id).is_none() {
+            return Err(not_found(issue_id));
+        }
+        Ok(self
+            .comments(issue_id)
+            .into_iter()
+            .filter(|c| since.is_none_or(|since| c.created_at.as_str() > since))
+            .collect())
+    }
+
+    async fn get_comments_by_ids(
+        &self,
+        comment_ids: &[String],
+    ) -> Result<Vec<LinearComment>, AppError> {
+        self.enter("get_comments_by_ids")
+            .map_err(AppError::LinearApi)?;
+        Ok(self
+            .state
+            .lock()
+            .unwrap()
+            .comments
+            .iter()
+            .filter(|c| comment_ids.contains(&c.comment.id))
+            .map(|c| c.comment.clone())
+            .collect())
+    }
+
+    async fn create_comment(
+        &self,
+        comment_id: Option<&str>,
+        issue_id: &str,
+        body: &str,
+        author: Option<&CommentAuthor>,
+    ) -> Result<String, AppError> {
+        self.enter("create_comment").map_err(AppError::LinearApi)?;
+        if self.issue(issue_id).is_none() {
+            return Err(not_found(issue_id));
+        }
+        let id = comment_id
+            .map(String::from)
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let created_at = now();
+        self.state.lock().unwrap().comments.push(MockComment {
+            issue_id: issue_id.to_string(),
+            comment: LinearComment {
+                id: id.clone(),
+                body: body.to_string(),
+                created_at: created_at.clone(),
+                updated_at: created_at,
+                author_name: author.map_or("Discord Bot", |a| &a.name).to_string(),
+                author_avatar_url: author.and_then(|a| a.avatar_url.clone()),
+                author_id: Some(BOT_USER_ID.to_string()),
+                author_email: None,
+            },
+        });
+        Ok(id)
+    }
+
+    async fn search_issues(
+        &self,
+        term: &str,
+        team_ids: &[String],
+        limit: usize,
+    ) -> Result<Vec<LinearSearchResult>, AppError> {
+        self.enter("search_issues").map_err(AppError::LinearApi)?;
+        let term = term.to_lowercase();
+        Ok(self
+            .state
+            .lock()
+            .unwrap()
+            .issues
+            .iter()
+            .filter(|issue| team_ids.contains(&issue.team_id))
+            .filter(|issue| issue.title.to_lowercase().contains(&term))
+            .take(limit)
+            .map(|issue| LinearSearchResult {
+                identifier: issue.identifier.clone(),
+                title: issue.title.clone(),
+                url: issue.url.clone(),
+                status_name: issue.state().1.to_string(),
+            })
+            .collect())
+    }
+
+    async fn get_team_members(&self, team_id: &str) -> Result<Vec<LinearUser>, AppError> {
+        self.enter("get_team_members")
+            .map_err(AppError::LinearApi)?;
+        Ok(self
+            .state
+            .lock()
+            .unwrap()
+            .members
+            .get(team_id)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    async fn get_team_metadata(&self, team_id: &str) -> Result<TeamMetadata, AppError> {
+        self.enter("get_team_metadata")
+            .map_err(AppError::LinearApi)?;
+        let state = self.state.lock().unwrap();
+        Ok(TeamMetadata {
+            key: "MOCK".to_string(),
+            name: team_id.to_string(),
+            states: STATES
+                .iter()
+                .map(|(id, name, state_type)| LinearWorkflowState {
+                    id: id.to_string(),
+                    name: name.to_string(),
+                    state_type: state_type.to_string(),
+                })
+                .collect(),
+            labels: state.labels.get(team_id).cloned().unwrap_or_default(),
+            projects: state.projects.get(team_id).cloned().unwrap_or_default(),
+            members: state.members.get(team_id).cloned().unwrap_or_default(),
+        })
+    }
+
+    async fn find_user_by_email(&self, email: &str) -> Result<Option<LinearUser>, AppError> {
+        self.enter("find_user_by_email")
+            .map_err(AppError::LinearApi)?;
+        Ok(self
+            .state
+            .lock()
+            .unwrap()
+            .members
+            .values()
+            .flatten()
+            .find(|user| user.email.eq_ignore_ascii_case(email))
+            .cloned())
+    }
+
+    async fn user_commented(&self, user_id: &str, text: &str) -> Result<bool, AppError> {
+        self.enter("user_commented").map_err(AppError::LinearApi)?;
+        Ok(self.state.lock().unwrap().comments.iter().any(|c| {
+            c.comment.author_id.as_deref() == Some(user_id) && c.comment.body.contains(text)
+        }))
+    }
+
+    async fn viewer(&self) -> Result<LinearUser, AppError> {
+        self.enter("viewer").map_err(AppError::LinearApi)?;
+        Ok(LinearUser {
+            id: BOT_USER_ID.to_string(),
+            name: "bot".to_string(),
+            display_name: "Bot".to_string(),
+            email: "bot@example.com".to_string(),
+        })
+    }
+
+    async fn update_issue_assignee(
+        &self,
+        issue_id: &str,
+        assignee_id: Option<&str>,
+    ) -> Result<(), AppError> {
+        self.enter("update_issue_assignee")
+            .map_err(AppError::LinearApi)?;
+        let assignee = match assignee_id {
+            Some(id) => Some(
+                self.state
+                    .lock()
+                    .unwrap()
+                    .members
+                    .values()
+                    .flatten()
+                    .find(|user| user.id == id)
+                    .cloned()
+                    .ok_or_else(|| not_found(id))?,
+            ),
+            None => None,
+        };
+        self.with_issue(issue_id, |issue| issue.assignee = assignee)
+            .map_err(AppError::LinearApi)
+    }
+
+    async fn update_issue_priority(&self, issue_id: &str, priority: i64) -> Result<(), AppError> {
+        self.enter("update_issue_priority")
+            .map_err(AppError::LinearApi)?;
+        self.with_issue(issue_id, |issue| issue.priority = priority)
+            .map_err(AppError::LinearApi)
+    }
+
+    async fn update_issue_project(&self, issue_id: &str, project_id: &str) -> Result<(), AppError> {
+        self.enter("update_issue_project")
+            .map_err(AppError::LinearApi)?;
+        self.with_issue(issue_id, |issue| issue.project_id = project_id.to_string())
+            .map_err(AppError::LinearApi)
+    }
+
+    async fn get_canceled_state_id(&self, _team_id: &str) -> Result<Option<String>, AppError> {
+        self.enter("get_canceled_state_id")
+            .map_err(AppError::LinearApi)?;
+        Ok(STATES
+            .iter()
+            .find(|(_, _, state_type)| *state_type == "canceled")
+            .map(|(id, _, _)| id.to_string()))
+    }
+
+    async fn update_issue_state(&self, issue_id: &str, state_id: &str) -> Result<(), AppError> {
+        self.enter("update_issue_state")
+            .map_err(AppError::LinearApi)?;
+        if !STATES.iter().any(|(id, _, _)| *id == state_id) {
+            return Err(not_found(state_id));
+        }
+        self.with_issue(issue_id, |issue| issue.state_id = state_id.to_string())
+            .map_err(AppError::LinearApi)
+    }
+
+    async fn get_issue_description(&self, issue_id: &str) -> Result<String, AppError> {
+        self.enter("get_issue_description")
+            .map_err(AppError::LinearApi)?;
+        self.issue(issue_id)
+            .map(|issue| issue.description)
+            .ok_or_else(|| not_found(issue_id))
+    }
+
+    async fn update_issue_description(
+        &self,
+        issue_id: &str,
+        description: &str,
+    ) -> Result<(), AppError> {
+        self.enter("update_issue_description")
+            .map_err(AppError::LinearApi)?;
+        self.with_issue(issue_id, |issue| {
+            issue.description = description.to_string()
+        })
+        .map_err(AppError::LinearApi)
+    }
+
+    async fn update_issue_title(&self, issue_id: &str, title: &str) -> Result<(), AppError> {
+        self.enter("update_issue_title")
+            .map_err(AppError::LinearApi)?;
+        self.with_issue(issue_id, |issue| issue.title = title.to_string())
+            .map_err(AppError::LinearApi)
+    }
+
+    async fn update_issue_estimate(&self, issue_id: &str, estimate: i64) -> Result<(), AppError> {
+        self.enter("update_issue_estimate")
+            .map_err(AppError::LinearApi)?;
+        self.with_issue(issue_id, |issue| issue.estimate = Some(estimate))
+            .map_err(AppError::LinearApi)
+    }
+
+    async fn add_issue_label(&self, issue_id: &str, label_id: &str) -> Result<(), AppError> {
+        self.enter("add_issue_label").map_err(AppError::LinearApi)?;
+        self.with_issue(issue_id, |issue| {
+            if !issue.label_ids.iter().any(|l| l == label_id) {
+                issue.label_ids.push(label_id.to_string());
+            }
+        })
+        .map_err(AppError::LinearApi)
+    }
+
+    async fn create_customer_need(
+        &self,
+        issue_id: &str,
+        customer: &Customer,
+        body: &str,
+        url: &str,
+    ) -> Result<(), AppError> {
+        self.enter("create_customer_need")
+            .map_err(AppError::LinearApi)?;
+        self.with_issue(issue_id, |_| {})
+            .map_err(AppError::LinearApi)?;
+        self.state
+            .lock()
+            .unwrap()
+            .customer_needs
+            .push(MockCustomerNeed {
+                issue_id: issue_id.to_string(),
+                customer_external_id: customer.external_id.clone(),
+                customer_name: customer.name.clone(),
+                body: body.to_string(),
+                url: url.to_string(),
+            });
+        Ok(())
+    }
+
+    async fn link_url(&self, issue_id: &str, url: &str, _title: &str) -> Result<(), AppError> {
+        self.enter("link_url").map_err(AppError::LinearApi)?;
+        self.with_issue(issue_id, |issue| {
+            if !issue.links.iter().any(|l| l == url) {
+                issue.links.push(url.to_string());
+            }
+        })
+        .map_err(AppError::LinearApi)
+    }
+
+    async fn request_file_upload(
+        &self,
+        _team_or_issue_id: &str,
+        filename: &str,
+        _content_type: &str,
+        _size: u64,
+    ) -> Result<UploadFile, AppError> {
+        self.enter("request_file_upload")
+            .map_err(AppError::LinearApi)?;
+        let key = uuid::Uuid::new_v4();
+        Ok(UploadFile {
+            upload_url: format!("https://storage.mock/{key}/{filename}"),
+            asset_url: format!("https://uploads.linear.app/mock/{key}/{filename}"),
+            headers: Vec::new(),
+        })
+    }
+
+    async fn upload_file_to_url(
+        &self,
+        upload: &UploadFile,
+        body: reqwest::Body,
+        size: u64,
+        _content_type: &str,
+    ) -> Result<String, AppError> {
+        self.enter("upload_file_to_url")
+            .map_err(AppError::LinearApi)?;
+        // Downloads from the mock are never streamed, so the body is in memory.
+        let data = body.as_bytes().unwrap_or_default().to_vec();
+        assert_eq!(data.len() as u64, size, "upload size matches the body");
+        self.state
+            .lock()
+            .unwrap()
+            .uploads
+            .insert(upload.asset_url.clone(), data);
+        Ok(upload.asset_url.clone())
+    }
+
+    async fn download_attachment(&self, url: &str) -> Result<AttachmentDownload, AppError> {
+        self.enter("download_attachment")
+            .map_err(AppError::LinearApi)?;
+        // Anything outside Linear's own uploads stands in for a Discord CDN attachment.
+        Ok(AttachmentDownload {
+            content_type: "application/octet-stream".to_string(),
+            size: Some(url.len() as u64),
+            body: url.as_bytes().to_vec().into(),
+        })
+    }
+
+    async fn download_linear_upload(
+        &self,
+        _issue_id: &str,
+        url: &str,
+    ) -> Result<(Vec<u8>, String), AppError> {
+        self.enter("download_linear_upload")
+            .map_err(AppError::LinearApi)?;
+        self.state
+            .lock()
+            .unwrap()
+            .uploads
+            .get(url)
+            .cloned()
+            .map(|data| (data, "application/octet-stream".to_string()))
+            .ok_or_else(|| AppError::AttachmentUpload(format!("No upload at {url}")))
+    }
+}
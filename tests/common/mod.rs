@@ -0,0 +1,104 @@
+//! Shared fixtures for the integration tests. Each test binary uses a different subset.
+#![allow(dead_code)]
+
+pub mod fake_discord;
+pub mod linear_server;
+pub mod mock_linear;
+
+use std::collections::HashMap;
+
+use serde_json::json;
+use sqlx::sqlite::{SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous};
+use sqlx::SqlitePool;
+
+use discord_linear_bot::config::{ChannelConfig, Config};
+use discord_linear_bot::db;
+use discord_linear_bot::linear::client::LinearUser;
+
+/// A fresh in-memory database with every migration applied. One connection, since each
+/// in-memory SQLite connection is its own database.
+pub async fn test_pool() -> SqlitePool {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("open in-memory database");
+    db::run_migrations(&pool).await.expect("run migrations");
+    pool
+}
+
+pub fn user(id: &str, name: &str) -> LinearUser {
+    LinearUser {
+        id: id.to_string(),
+        name: name.to_string(),
+        display_name: name.to_string(),
+        email: format!("{}@example.com", name.to_lowercase()),
+    }
+}
+
+pub const FORUM_ID: u64 = 2000;
+pub const TEAM_ID: &str = "team-1";
+
+/// A forum channel config with every optional feature at its default.
+pub fn channel_config() -> ChannelConfig {
+    serde_json::from_value(json!({
+        "discord_channel_id": FORUM_ID,
+        "guild_id": fake_discord::GUILD_ID,
+        "channel_type": "bug",
+        "linear_team_id": TEAM_ID,
+        "linear_label_id": "label-bug",
+        "linear_project_id": "project-1",
+    }))
+    .expect("valid channel config")
+}
+
+/// Bot config monitoring the given channels, with notifications posted immediately.
+pub fn config(channels: Vec<ChannelConfig>) -> Config {
+    Config {
+        discord_token: "discord-token".to_string(),
+        linear_api_key: "linear-key".to_string(),
+        linear_api_keys: HashMap::new(),
+        credential_key: None,
+        credential_previous_keys: Vec::new(),
+        multi_tenant: false,
+        channels,
+        database_url: "sqlite::memory:".to_string(),
+        database_max_connections: 1,
+        sqlite_journal_mode: SqliteJournalMode::Memory,
+        sqlite_synchronous: SqliteSynchronous::Normal,
+        sqlite_busy_timeout_ms: 5000,
+        poll_interval_secs: 30,
+        poll_interval_max_secs: 300,
+        poll_cursor_overlap_secs: 10,
+        comment_poll_interval_secs: 30,
+        thread_reconcile_interval_secs: 300,
+        due_reminder_interval_secs: 3600,
+        stale_nudge_interval_secs: 3600,
+        vote_sync_interval_secs: 900,
+        sla_check_interval_secs: 900,
+        metadata_refresh_secs: 300,
+        drift_check_interval_secs: 86400,
+        drift_auto_repair: false,
+        digest_window_secs: 0,
+        context_menu_channel_id: None,
+        webhook_listen_addr: None,
+        linear_webhook_secret: None,
+        webhook_max_age_secs: 60,
+        comment_author_denylist: Vec::new(),
+        comment_author_allowlist: Vec::new(),
+        skip_bot_comments: false,
+        linear_comment_as_author: false,
+        command_roles: HashMap::new(),
+        message_templates: HashMap::new(),
+        linear_max_attempts: 3,
+        breaker_threshold: 5,
+        breaker_cooldown_secs: 60,
+        outbox_max_attempts: 10,
+        ops_channel_id: None,
+        shutdown_timeout_secs: 25,
+        leader_election: false,
+        leader_lease_secs: 30,
+        shadow_mode: false,
+        shadow_record: false,
+    }
+}
@@ -0,0 +1,201 @@
+//! Reloading `CHANNELS` and `MESSAGE_TEMPLATES` into a running configuration, and overlaying the
+//! channels and Linear API keys stored with `/linear admin`.
+
+mod common;
+
+use std::collections::HashMap;
+
+use serde_json::json;
+
+use discord_linear_bot::config::SharedConfig;
+use discord_linear_bot::credentials::CredentialKeys;
+use discord_linear_bot::db;
+
+use common::{channel_config, config, FORUM_ID};
+
+fn vars(pairs: &[(&str, String)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.clone()))
+        .collect()
+}
+
+#[test]
+fn reload_replaces_channels_and_merges_templates() {
+    let shared = SharedConfig::new(config(vec![channel_config()]));
+    let channels = json!([
+        {
+            "discord_channel_id": FORUM_ID,
+            "guild_id": 1,
+            "channel_type": "bug",
+            "linear_team_id": "team-1",
+            "linear_label_id": "label-bug",
+            "linear_project_id": "project-1",
+        },
+        {
+            "discord_channel_id": 3000,
+            "guild_id": 2,
+            "channel_type": "feature",
+            "linear_team_id": "team-2",
+            "linear_label_id": "label-feature",
+            "linear_project_id": "project-2",
+        },
+    ]);
+    let env = vars(&[
+        ("CHANNELS", channels.to_string()),
+        (
+            "MESSAGE_TEMPLATES",
+            json!({"tracked": "Filed {{identifier}}"}).to_string(),
+        ),
+    ]);
+
+    let reloaded = shared
+        .reload_from(|name| env.get(name).cloned())
+        .expect("valid config");
+
+    assert_eq!(reloaded.channels.len(), 2);
+    assert_eq!(reloaded.unique_team_ids(), vec!["team-1", "team-2"]);
+    let added = reloaded.channel_config(3000).expect("new channel");
+    assert_eq!(added.message_templates["tracked"], "Filed {{identifier}}");
+    // Settings outside CHANNELS and MESSAGE_TEMPLATES are kept.
+    assert_eq!(reloaded.discord_token, "discord-token");
+    assert_eq!(shared.current().channels.len(), 2);
+}
+
+#[test]
+fn invalid_reload_keeps_running_config() {
+    let shared = SharedConfig::new(config(vec![channel_config()]));
+
+    let empty = vars(&[("CHANNELS", "[]".to_string())]);
+    assert!(shared.reload_from(|name| empty.get(name).cloned()).is_err());
+
+    let bad_template = vars(&[
+        (
+            "CHANNELS",
+            json!([{
+                "discord_channel_id": FORUM_ID,
+                "guild_id": 1,
+                "channel_type": "bug",
+                "linear_team_id": "team-1",
+                "linear_label_id": "label-bug",
+                "linear_project_id": "project-1",
+            }])
+            .to_string(),
+        ),
+        (
+            "MESSAGE_TEMPLATES",
+            json!({"tracked": "{{#if}}"}).to_string(),
+        ),
+    ]);
+    assert!(shared
+        .reload_from(|name| bad_template.get(name).cloned())
+        .is_err());
+
+    assert!(shared.reload_from(|_| None).is_err());
+
+    let current = shared.current();
+    assert_eq!(current.channels.len(), 1);
+    assert!(current.channel_config(FORUM_ID).is_some());
+}
+
+#[tokio::test]
+async fn stored_channels_override_and_hide_env_channels() {
+    let pool = common::test_pool().await;
+    let shared = SharedConfig::new(config(vec![channel_config()]));
+    let added = json!({
+        "discord_channel_id": 3000,
+        "guild_id": 1,
+        "channel_type": "feature",
+        "linear_team_id": "team-2",
+        "linear_label_id": "label-feature",
+        "linear_project_id": "project-2",
+    });
+    db::upsert_channel_config(&pool, "3000", "1", Some(&added.to_string()), "42")
+        .await
+        .unwrap();
+    db::upsert_channel_config(&pool, &FORUM_ID.to_string(), "1", None, "42")
+        .await
+        .unwrap();
+
+    let loaded = shared.load_stored_channels(&pool).await.unwrap();
+    assert!(loaded.channel_config(FORUM_ID).is_none());
+    assert_eq!(loaded.unique_team_ids(), vec!["team-2"]);
+    assert!(shared.in_env(FORUM_ID));
+    assert!(!shared.in_env(3000));
+
+    // Reloading CHANNELS keeps the stored channels on top.
+    let env = vars(&[(
+        "CHANNELS",
+        json!([{
+            "discord_channel_id": FORUM_ID,
+            "guild_id": 1,
+            "channel_type": "bug",
+            "linear_team_id": "team-1",
+            "linear_label_id": "label-bug",
+            "linear_project_id": "project-1",
+        }])
+        .to_string(),
+    )]);
+    let reloaded = shared.reload_from(|name| env.get(name).cloned()).unwrap();
+    assert_eq!(reloaded.channels.len(), 1);
+    assert!(reloaded.channel_config(3000).is_some());
+
+    // Dropping the tombstone brings the CHANNELS entry back.
+    db::delete_channel_config(&pool, &FORUM_ID.to_string())
+        .await
+        .unwrap();
+    let restored = shared.load_stored_channels(&pool).await.unwrap();
+    assert_eq!(restored.unique_team_ids(), vec!["team-1", "team-2"]);
+}
+
+#[tokio::test]
+async fn stored_channels_can_only_use_their_guilds_stored_keys() {
+    let pool = common::test_pool().await;
+    let shared = SharedConfig::new(config(vec![channel_config()]));
+    let keys = CredentialKeys::new(&"07".repeat(32), &[]).unwrap();
+    db::upsert_linear_credential(
+        &pool,
+        "partner",
+        "1",
+        &keys.seal("partner", "lin_api_x"),
+        "42",
+    )
+    .await
+    .unwrap();
+    for (channel_id, guild_id) in [(3000, 1), (3001, 2)] {
+        let channel = json!({
+            "discord_channel_id": channel_id,
+            "guild_id": guild_id,
+            "channel_type": "bug",
+            "linear_team_id": "team-2",
+            "linear_label_id": "label-bug",
+            "linear_project_id": "project-2",
+            "linear_credential": "partner",
+        });
+        db::upsert_channel_config(
+            &pool,
+            &channel_id.to_string(),
+            &guild_id.to_string(),
+            Some(&channel.to_string()),
+            "42",
+        )
+        .await
+        .unwrap();
+    }
+
+    let loaded = shared.load_stored_channels(&pool).await.unwrap();
+    assert!(loaded.channel_config(3000).is_some());
+    assert!(loaded.channel_config(3001).is_none());
+}
+
+#[test]
+fn multi_tenant_mode_runs_without_channels() {
+    let mut base = config(vec![channel_config()]);
+    base.multi_tenant = true;
+    let shared = SharedConfig::new(base);
+
+    let reloaded = shared.reload_from(|_| None).expect("CHANNELS is optional");
+    assert!(reloaded.channels.is_empty());
+    let empty = vars(&[("CHANNELS", "[]".to_string())]);
+    assert!(shared.reload_from(|name| empty.get(name).cloned()).is_ok());
+}
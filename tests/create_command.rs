@@ -0,0 +1,103 @@
+//! Issues filed with `/linear create`, with and without a forum post to follow them.
+
+mod common;
+
+use serenity::all::{ChannelId, User, UserId};
+
+use discord_linear_bot::db;
+use discord_linear_bot::discord::commands::create::{file_issue, NewIssue};
+use discord_linear_bot::sync::discord_to_linear::sync_discord_to_linear;
+
+use common::fake_discord::FakeDiscord;
+use common::mock_linear::MockLinear;
+use common::{channel_config, test_pool, FORUM_ID, TEAM_ID};
+
+fn author() -> User {
+    let mut user = User::default();
+    user.id = UserId::new(42);
+    user.name = "ada".to_string();
+    user
+}
+
+#[tokio::test]
+async fn files_in_the_forums_team_and_project() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    let author = author();
+
+    let (issue, thread_id) = file_issue(
+        &discord,
+        &pool,
+        &channel_config(),
+        &linear,
+        NewIssue {
+            author: &author,
+            title: "Export times out",
+            description: "Large workspaces never finish exporting.",
+            open_thread: false,
+        },
+    )
+    .await
+    .unwrap();
+
+    assert!(thread_id.is_none());
+    let filed = linear.issue(&issue.id).unwrap();
+    assert_eq!(filed.team_id, TEAM_ID);
+    assert_eq!(filed.project_id, "project-1");
+    assert_eq!(filed.label_ids, vec!["label-bug".to_string()]);
+    assert_eq!(filed.title, "Export times out");
+    assert!(filed
+        .description
+        .starts_with("Large workspaces never finish exporting."));
+    assert!(filed.description.contains("by ada"));
+    assert!(db::get_mapping_by_linear_issue(&pool, &issue.id)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn opens_a_tracked_post_when_asked() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    let author = author();
+    let channel = channel_config();
+
+    let (issue, thread_id) = file_issue(
+        &discord,
+        &pool,
+        &channel,
+        &linear,
+        NewIssue {
+            author: &author,
+            title: "Export times out",
+            description: "",
+            open_thread: true,
+        },
+    )
+    .await
+    .unwrap();
+
+    let thread_id = thread_id.unwrap();
+    let thread = discord.thread(thread_id);
+    assert_eq!(thread.parent_id, Some(ChannelId::new(FORUM_ID)));
+    assert_eq!(thread.name, "Export times out");
+    let mapping = db::get_mapping_by_discord_thread(&pool, &thread_id.to_string())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(mapping.linear_issue_id, issue.id);
+    let sent = discord.sent(thread_id);
+    assert!(sent[0].content().contains("<@42>"));
+    assert!(sent.last().unwrap().content().contains(&issue.identifier));
+    assert_eq!(linear.issue(&issue.id).unwrap().links.len(), 1);
+
+    // The post's own thread-create event doesn't file it again.
+    sync_discord_to_linear(&discord, &pool, &channel, &linear, &thread)
+        .await
+        .unwrap();
+    assert_eq!(linear.calls("create_issue"), 1);
+}
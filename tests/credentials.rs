@@ -0,0 +1,137 @@
+//! Encrypting stored Linear API keys, and rotating the key they're encrypted with.
+
+mod common;
+
+use discord_linear_bot::credentials::{self, CredentialError, CredentialKeys};
+use discord_linear_bot::db;
+use discord_linear_bot::error::AppError;
+
+const OLD_KEY: &str = "0101010101010101010101010101010101010101010101010101010101010101";
+const NEW_KEY: &str = "0202020202020202020202020202020202020202020202020202020202020202";
+
+#[tokio::test]
+async fn stored_keys_are_encrypted_and_load_back() {
+    let pool = common::test_pool().await;
+    let keys = CredentialKeys::new(NEW_KEY, &[]).unwrap();
+    let sealed = keys.seal("partner", "lin_api_secret");
+    db::upsert_linear_credential(&pool, "partner", "1", &sealed, "42")
+        .await
+        .unwrap();
+
+    let rows = db::get_linear_credentials(&pool).await.unwrap();
+    assert_eq!(rows[0].key_id, keys.current_key_id());
+    assert!(!String::from_utf8_lossy(&rows[0].ciphertext).contains("lin_api_secret"));
+
+    let loaded = credentials::load(&pool, Some(&keys)).await.unwrap();
+    assert_eq!(loaded.len(), 1);
+    assert_eq!(loaded[0].api_key, "lin_api_secret");
+    assert_eq!(loaded[0].guild_id, "1");
+
+    // Without CREDENTIAL_KEY the stored keys can't be used.
+    assert!(matches!(
+        credentials::load(&pool, None).await,
+        Err(AppError::Credential(CredentialError::NoKey))
+    ));
+}
+
+#[tokio::test]
+async fn ciphertext_only_opens_for_its_own_name() {
+    let pool = common::test_pool().await;
+    let keys = CredentialKeys::new(NEW_KEY, &[]).unwrap();
+    db::upsert_linear_credential(
+        &pool,
+        "other",
+        "1",
+        &keys.seal("partner", "lin_api_secret"),
+        "42",
+    )
+    .await
+    .unwrap();
+
+    assert!(matches!(
+        credentials::load(&pool, Some(&keys)).await,
+        Err(AppError::Credential(CredentialError::Corrupt(name))) if name == "other"
+    ));
+}
+
+#[tokio::test]
+async fn rotation_reseals_rows_under_the_new_key() {
+    let pool = common::test_pool().await;
+    let old = CredentialKeys::new(OLD_KEY, &[]).unwrap();
+    db::upsert_linear_credential(
+        &pool,
+        "partner",
+        "1",
+        &old.seal("partner", "lin_api_secret"),
+        "42",
+    )
+    .await
+    .unwrap();
+
+    // A new key alone can't open rows sealed with the old one.
+    let new_only = CredentialKeys::new(NEW_KEY, &[]).unwrap();
+    assert!(matches!(
+        credentials::load(&pool, Some(&new_only)).await,
+        Err(AppError::Credential(CredentialError::UnknownKey(..)))
+    ));
+
+    let rotating = CredentialKeys::new(NEW_KEY, &[OLD_KEY.to_string()]).unwrap();
+    assert_eq!(credentials::rotate(&pool, &rotating).await.unwrap(), 1);
+    assert_eq!(credentials::rotate(&pool, &rotating).await.unwrap(), 0);
+
+    // Once rotated, the old key is no longer needed.
+    let loaded = credentials::load(&pool, Some(&new_only)).await.unwrap();
+    assert_eq!(loaded[0].api_key, "lin_api_secret");
+}
+
+#[tokio::test]
+async fn a_server_cant_overwrite_another_servers_key() {
+    let pool = common::test_pool().await;
+    let keys = CredentialKeys::new(NEW_KEY, &[]).unwrap();
+    // Server 1 stores a key under the name server 2's `/linear setup` would use.
+    let stored = db::upsert_linear_credential(
+        &pool,
+        "guild-2",
+        "1",
+        &keys.seal("guild-2", "lin_api_first"),
+        "42",
+    )
+    .await
+    .unwrap();
+    assert!(stored);
+
+    let stored = db::upsert_linear_credential(
+        &pool,
+        "guild-2",
+        "2",
+        &keys.seal("guild-2", "lin_api_second"),
+        "43",
+    )
+    .await
+    .unwrap();
+    assert!(!stored);
+    let loaded = credentials::load(&pool, Some(&keys)).await.unwrap();
+    assert_eq!(loaded.len(), 1);
+    assert_eq!(loaded[0].guild_id, "1");
+    assert_eq!(loaded[0].api_key, "lin_api_first");
+
+    // The owning server can still replace its own key.
+    let stored = db::upsert_linear_credential(
+        &pool,
+        "guild-2",
+        "1",
+        &keys.seal("guild-2", "lin_api_third"),
+        "42",
+    )
+    .await
+    .unwrap();
+    assert!(stored);
+    let loaded = credentials::load(&pool, Some(&keys)).await.unwrap();
+    assert_eq!(loaded[0].api_key, "lin_api_third");
+}
+
+#[test]
+fn keys_must_be_32_bytes_of_hex() {
+    assert!(CredentialKeys::new("abcd", &[]).is_err());
+    assert!(CredentialKeys::new(NEW_KEY, &["not-hex".to_string()]).is_err());
+}
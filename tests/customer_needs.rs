@@ -0,0 +1,76 @@
+//! Posts in support channels filed as customer needs on their issues.
+
+mod common;
+
+use discord_linear_bot::config::ChannelConfig;
+use discord_linear_bot::db;
+use discord_linear_bot::sync::discord_to_linear::sync_discord_to_linear;
+
+use common::fake_discord::{FakeDiscord, GUILD_ID, REPORTER_ID};
+use common::mock_linear::MockLinear;
+use common::{channel_config, test_pool, FORUM_ID};
+
+fn support_config() -> ChannelConfig {
+    let mut channel = channel_config();
+    channel.customer_needs = true;
+    channel
+}
+
+/// File "Crash on login" in `channel` and return the thread ID and its issue ID.
+async fn file(linear: &MockLinear, channel: &ChannelConfig) -> (u64, String) {
+    let pool = test_pool().await;
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    let thread = discord.add_thread(FORUM_ID, "Crash on login", "It crashes when I log in");
+
+    sync_discord_to_linear(&discord, &pool, channel, linear, &thread)
+        .await
+        .unwrap();
+
+    let mapping = db::get_mapping_by_discord_thread(&pool, &thread.id.to_string())
+        .await
+        .unwrap()
+        .unwrap();
+    (thread.id.get(), mapping.linear_issue_id)
+}
+
+#[tokio::test]
+async fn post_is_filed_as_a_need_from_its_author() {
+    let linear = MockLinear::new();
+
+    let (thread_id, issue_id) = file(&linear, &support_config()).await;
+
+    let needs = linear.customer_needs();
+    assert_eq!(needs.len(), 1);
+    assert_eq!(needs[0].issue_id, issue_id);
+    assert_eq!(
+        needs[0].customer_external_id,
+        format!("discord:{REPORTER_ID}")
+    );
+    assert_eq!(needs[0].customer_name, "reporter");
+    assert_eq!(needs[0].body, "It crashes when I log in");
+    assert_eq!(
+        needs[0].url,
+        format!("https://discord.com/channels/{GUILD_ID}/{FORUM_ID}/{thread_id}")
+    );
+}
+
+#[tokio::test]
+async fn other_channels_file_bare_issues() {
+    let linear = MockLinear::new();
+
+    file(&linear, &channel_config()).await;
+
+    assert!(linear.customer_needs().is_empty());
+}
+
+#[tokio::test]
+async fn failed_need_still_files_the_issue() {
+    let linear = MockLinear::new();
+    linear.fail("create_customer_need");
+
+    let (_, issue_id) = file(&linear, &support_config()).await;
+
+    assert!(linear.issue(&issue_id).is_some());
+    assert!(linear.customer_needs().is_empty());
+}
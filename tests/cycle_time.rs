@@ -0,0 +1,156 @@
+//! Status history recorded for tracked issues and the `/linear cycle-time` report built on it.
+
+mod common;
+
+use discord_linear_bot::db::{self, TrackedStatusChange};
+use discord_linear_bot::discord::commands::cycle_time::{summarize, Average};
+use discord_linear_bot::sync::discord_to_linear::sync_discord_to_linear;
+use discord_linear_bot::sync::linear_to_discord::sync_linear_to_discord;
+
+use common::fake_discord::FakeDiscord;
+use common::mock_linear::MockLinear;
+use common::{channel_config, config, test_pool, FORUM_ID};
+
+/// A thread posted at 2026-01-01 00:00:00 UTC.
+const THREAD_ID: u64 = (1_767_225_600_000 - 1_420_070_400_000) << 22;
+
+fn change(issue: &str, status: &str, status_type: &str, at: &str) -> TrackedStatusChange {
+    TrackedStatusChange {
+        discord_thread_id: THREAD_ID.to_string(),
+        linear_issue_id: issue.to_string(),
+        status_name: status.to_string(),
+        status_type: status_type.to_string(),
+        changed_at: at.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn announced_status_changes_are_appended_to_the_history() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    let config = config(vec![channel_config()]);
+    let thread = discord.add_thread(FORUM_ID, "Crash on login", "It crashes when I log in");
+    sync_discord_to_linear(&discord, &pool, &channel_config(), &linear, &thread)
+        .await
+        .unwrap();
+    let mapping = db::get_mapping_by_discord_thread(&pool, &thread.id.to_string())
+        .await
+        .unwrap()
+        .unwrap();
+
+    for status in ["In Progress", "Done"] {
+        sync_linear_to_discord(
+            &discord,
+            &pool,
+            &config,
+            &linear,
+            &linear.move_to(&mapping.linear_issue_id, status),
+        )
+        .await
+        .unwrap();
+    }
+
+    let history = db::get_tracked_status_history(&pool, None).await.unwrap();
+    let statuses: Vec<_> = history
+        .iter()
+        .map(|c| (c.status_name.as_str(), c.status_type.as_str()))
+        .collect();
+    assert_eq!(
+        statuses,
+        [("In Progress", "started"), ("Done", "completed")]
+    );
+    assert_eq!(
+        db::get_cached_status(&pool, &mapping.linear_issue_id)
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("Done")
+    );
+
+    // Unlinking drops the history along with the rest of the issue's sync state.
+    db::tombstone_mapping(&pool, &mapping, "unlinked", None)
+        .await
+        .unwrap();
+    assert!(db::get_tracked_status_history(&pool, None)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn history_can_be_limited_to_one_server() {
+    let pool = test_pool().await;
+    for (n, guild, status) in [(1, "1", "In Progress"), (2, "2", "Secret Review")] {
+        db::create_mapping(
+            &pool,
+            &n.to_string(),
+            &format!("issue-{n}"),
+            &format!("ENG-{n}"),
+            "bug",
+            guild,
+        )
+        .await
+        .unwrap();
+        db::insert_status_history(&pool, &format!("issue-{n}"), status, "started")
+            .await
+            .unwrap();
+    }
+
+    let history = db::get_tracked_status_history(&pool, Some("1"))
+        .await
+        .unwrap();
+    let statuses: Vec<_> = history.iter().map(|c| c.status_name.as_str()).collect();
+    assert_eq!(statuses, ["In Progress"]);
+    assert_eq!(
+        db::get_tracked_status_history(&pool, None)
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+}
+
+#[test]
+fn averages_time_from_post_to_started_and_completed() {
+    let history = [
+        change("a", "Todo", "unstarted", "2026-01-01 01:00:00"),
+        change("a", "In Progress", "started", "2026-01-01 02:00:00"),
+        change("a", "Done", "completed", "2026-01-02 00:00:00"),
+        change("b", "Doing", "started", "2026-01-01 04:00:00"),
+        change("b", "Blocked", "started", "2026-01-01 05:00:00"),
+    ];
+
+    let times = summarize(&history);
+
+    assert_eq!(
+        times.to_started,
+        Average {
+            total_secs: 2 * 3_600 + 4 * 3_600,
+            count: 2
+        }
+    );
+    assert_eq!(times.to_started.mean_secs(), Some(3 * 3_600));
+    assert_eq!(
+        times.to_completed,
+        Average {
+            total_secs: 24 * 3_600,
+            count: 1
+        }
+    );
+    let in_status: Vec<_> = times
+        .in_status
+        .iter()
+        .map(|(status, average)| (status.as_str(), average.mean_secs().unwrap()))
+        .collect();
+    // Each issue's current status hasn't ended, so it isn't counted.
+    assert_eq!(
+        in_status,
+        [
+            ("Doing", 3_600),
+            ("In Progress", 22 * 3_600),
+            ("Todo", 3_600)
+        ]
+    );
+}
@@ -0,0 +1,108 @@
+//! Issue descriptions built from several of the author's opening messages.
+
+mod common;
+
+use serenity::all::GuildChannel;
+
+use discord_linear_bot::config::ChannelConfig;
+use discord_linear_bot::db;
+use discord_linear_bot::sync::discord_to_linear::sync_discord_to_linear;
+
+use common::fake_discord::{FakeDiscord, REPORTER_ID};
+use common::mock_linear::MockLinear;
+use common::{channel_config, test_pool, FORUM_ID};
+
+const OTHER_USER_ID: u64 = 4001;
+
+fn config(limit: usize, window_secs: i64) -> ChannelConfig {
+    let mut channel = channel_config();
+    channel.description_message_limit = limit;
+    channel.description_window_secs = window_secs;
+    channel
+}
+
+fn post(discord: &FakeDiscord) -> GuildChannel {
+    discord.add_forum(FORUM_ID);
+    discord.add_thread(FORUM_ID, "Crash on login", "It crashes when I log in")
+}
+
+/// File `thread` and return its issue's description.
+async fn file(discord: &FakeDiscord, channel: &ChannelConfig, thread: &GuildChannel) -> String {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    sync_discord_to_linear(discord, &pool, channel, &linear, thread)
+        .await
+        .unwrap();
+
+    let mapping = db::get_mapping_by_discord_thread(&pool, &thread.id.to_string())
+        .await
+        .unwrap()
+        .unwrap();
+    linear.issue(&mapping.linear_issue_id).unwrap().description
+}
+
+#[tokio::test]
+async fn the_authors_first_messages_are_included() {
+    let discord = FakeDiscord::new();
+    let thread = post(&discord);
+    discord.add_reply(thread.id, REPORTER_ID, "Only on Android", 5);
+    discord.add_reply(thread.id, OTHER_USER_ID, "Same here", 10);
+    discord.add_reply(thread.id, REPORTER_ID, "Version 2.3.1", 15);
+    discord.add_reply(thread.id, REPORTER_ID, "Any update?", 20);
+
+    let description = file(&discord, &config(3, 0), &thread).await;
+
+    assert!(description
+        .starts_with("It crashes when I log in\n\nOnly on Android\n\nVersion 2.3.1\n\n---"));
+    assert!(!description.contains("Same here"));
+    assert!(!description.contains("Any update?"));
+}
+
+#[tokio::test]
+async fn messages_after_the_window_are_left_out() {
+    let discord = FakeDiscord::new();
+    let thread = post(&discord);
+    discord.backdate_post(thread.id, 3600);
+    discord.add_reply(thread.id, REPORTER_ID, "Only on Android", 30);
+    discord.add_reply(thread.id, REPORTER_ID, "Any update?", 900);
+
+    let description = file(&discord, &config(5, 600), &thread).await;
+
+    assert!(description.starts_with("It crashes when I log in\n\nOnly on Android\n\n---"));
+}
+
+#[tokio::test]
+async fn only_the_first_message_by_default() {
+    let discord = FakeDiscord::new();
+    let thread = post(&discord);
+    discord.add_reply(thread.id, REPORTER_ID, "Only on Android", 5);
+
+    let description = file(&discord, &channel_config(), &thread).await;
+
+    assert!(description.starts_with("It crashes when I log in\n\n---"));
+}
+
+#[tokio::test]
+async fn a_claimed_post_is_left_to_its_claimant() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    let thread = post(&discord);
+    // Another sync is waiting for the author's follow-ups.
+    assert!(db::claim_thread(&pool, &thread.id.to_string(), 600)
+        .await
+        .unwrap());
+
+    sync_discord_to_linear(&discord, &pool, &channel_config(), &linear, &thread)
+        .await
+        .unwrap();
+    assert_eq!(linear.calls("create_issue"), 0);
+
+    db::release_thread_claim(&pool, &thread.id.to_string())
+        .await
+        .unwrap();
+    sync_discord_to_linear(&discord, &pool, &channel_config(), &linear, &thread)
+        .await
+        .unwrap();
+    assert_eq!(linear.calls("create_issue"), 1);
+}
@@ -0,0 +1,153 @@
+//! The two sync directions driven through `FakeDiscord` and `MockLinear`.
+
+mod common;
+
+use discord_linear_bot::db;
+use discord_linear_bot::sync::discord_to_linear::sync_discord_to_linear;
+use discord_linear_bot::sync::linear_to_discord::sync_linear_to_discord;
+
+use common::fake_discord::FakeDiscord;
+use common::mock_linear::MockLinear;
+use common::{channel_config, config, test_pool, FORUM_ID, TEAM_ID};
+
+#[tokio::test]
+async fn new_thread_creates_issue_and_confirms_in_thread() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    let thread = discord.add_thread(FORUM_ID, "Crash on login", "It crashes when I log in");
+
+    sync_discord_to_linear(&discord, &pool, &channel_config(), &linear, &thread)
+        .await
+        .unwrap();
+
+    let mapping = db::get_mapping_by_discord_thread(&pool, &thread.id.to_string())
+        .await
+        .unwrap()
+        .expect("thread is mapped");
+    let issue = linear.issue(&mapping.linear_issue_id).unwrap();
+    assert_eq!(issue.team_id, TEAM_ID);
+    assert_eq!(issue.title, "Crash on login");
+    assert!(issue.description.starts_with("It crashes when I log in"));
+
+    let sent = discord.sent(thread.id);
+    assert_eq!(sent.len(), 1);
+    assert!(sent[0].content().contains(&issue.identifier));
+}
+
+#[tokio::test]
+async fn already_mapped_thread_is_skipped() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    let thread = discord.add_thread(FORUM_ID, "Crash on login", "It crashes");
+
+    for _ in 0..2 {
+        sync_discord_to_linear(&discord, &pool, &channel_config(), &linear, &thread)
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(linear.calls("create_issue"), 1);
+    assert_eq!(discord.sent(thread.id).len(), 1);
+}
+
+#[tokio::test]
+async fn closing_the_issue_posts_status_and_archives_thread() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    let config = config(vec![channel_config()]);
+    discord.add_forum(FORUM_ID);
+    let thread = discord.add_thread(FORUM_ID, "Crash on login", "It crashes");
+    sync_discord_to_linear(&discord, &pool, &config.channels[0], &linear, &thread)
+        .await
+        .unwrap();
+    let mapping = db::get_mapping_by_discord_thread(&pool, &thread.id.to_string())
+        .await
+        .unwrap()
+        .unwrap();
+
+    sync_linear_to_discord(
+        &discord,
+        &pool,
+        &config,
+        &linear,
+        &linear.move_to(&mapping.linear_issue_id, "Done"),
+    )
+    .await
+    .unwrap();
+
+    let sent = discord.sent(thread.id);
+    let notice = sent.last().unwrap();
+    assert!(notice.content().contains(&mapping.linear_identifier));
+    assert!(notice.content().contains("Done"));
+    let embed = &notice.body["embeds"][0];
+    assert_eq!(embed["title"], "MOCK-1: Crash on login");
+    assert!(embed["fields"].to_string().contains("Done"));
+    assert!(discord.is_archived(thread.id));
+    assert!(!discord.is_locked(thread.id));
+    assert_eq!(
+        db::get_cached_status(&pool, &mapping.linear_issue_id)
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("Done")
+    );
+
+    // Reopening in Linear brings the thread back.
+    sync_linear_to_discord(
+        &discord,
+        &pool,
+        &config,
+        &linear,
+        &linear.move_to(&mapping.linear_issue_id, "In Progress"),
+    )
+    .await
+    .unwrap();
+    assert!(!discord.is_archived(thread.id));
+}
+
+#[tokio::test]
+async fn status_embed_is_edited_instead_of_posting() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    let mut channel = channel_config();
+    channel.status_embed = true;
+    let config = config(vec![channel]);
+    discord.add_forum(FORUM_ID);
+    let thread = discord.add_thread(FORUM_ID, "Crash on login", "It crashes");
+    sync_discord_to_linear(&discord, &pool, &config.channels[0], &linear, &thread)
+        .await
+        .unwrap();
+    let mapping = db::get_mapping_by_discord_thread(&pool, &thread.id.to_string())
+        .await
+        .unwrap()
+        .unwrap();
+
+    // Tracked confirmation plus the pinned embed.
+    let sent = discord.sent(thread.id);
+    assert_eq!(sent.len(), 2);
+    let embed = &sent[1];
+    assert!(embed.has_embed());
+    assert!(discord.is_pinned(embed.message_id));
+
+    sync_linear_to_discord(
+        &discord,
+        &pool,
+        &config,
+        &linear,
+        &linear.move_to(&mapping.linear_issue_id, "In Progress"),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(discord.sent(thread.id).len(), 2);
+    let edits = discord.edits(thread.id);
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].message_id, embed.message_id);
+    assert!(edits[0].body.to_string().contains("In Progress"));
+}
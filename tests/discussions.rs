@@ -0,0 +1,129 @@
+//! Forum posts opened for untracked issues labeled for a discussion in Linear.
+
+mod common;
+
+use serenity::all::ChannelId;
+use sqlx::SqlitePool;
+
+use discord_linear_bot::config::Config;
+use discord_linear_bot::db;
+use discord_linear_bot::linear::api::LinearApi;
+use discord_linear_bot::sync::discord_to_linear::sync_discord_to_linear;
+use discord_linear_bot::sync::discuss::open_thread_for_issue;
+
+use common::fake_discord::FakeDiscord;
+use common::mock_linear::MockLinear;
+use common::{channel_config, config, test_pool, FORUM_ID, TEAM_ID};
+
+const DISCUSS_LABEL: &str = "label-discuss";
+
+fn discuss_config() -> Config {
+    let mut channel = channel_config();
+    channel.discuss_label_id = Some(DISCUSS_LABEL.to_string());
+    config(vec![channel])
+}
+
+/// An issue in `TEAM_ID` carrying `label_ids`. Returns its ID.
+async fn issue(linear: &MockLinear, label_ids: &[&str]) -> String {
+    let issue = linear
+        .create_issue(
+            TEAM_ID,
+            "Rethink onboarding",
+            "What should **new users** see?",
+            &[],
+            "",
+        )
+        .await
+        .unwrap();
+    linear.set_labels(&issue.id, label_ids);
+    issue.id
+}
+
+async fn open(
+    discord: &FakeDiscord,
+    pool: &SqlitePool,
+    linear: &MockLinear,
+    team_id: &str,
+    issue_id: &str,
+) -> bool {
+    let label_ids = linear.issue(issue_id).unwrap().label_ids;
+    open_thread_for_issue(
+        discord,
+        pool,
+        &discuss_config(),
+        linear,
+        team_id,
+        issue_id,
+        &label_ids,
+    )
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn labeled_issue_opens_a_tracked_post() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    let issue_id = issue(&linear, &[DISCUSS_LABEL]).await;
+
+    assert!(open(&discord, &pool, &linear, TEAM_ID, &issue_id).await);
+
+    let mapping = db::get_mapping_by_linear_issue(&pool, &issue_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let thread_id = ChannelId::new(mapping.discord_thread_id.parse().unwrap());
+    let thread = discord.thread(thread_id);
+    assert_eq!(thread.parent_id, Some(ChannelId::new(FORUM_ID)));
+    assert_eq!(thread.name, "Rethink onboarding");
+    let sent = discord.sent(thread_id);
+    assert_eq!(sent.len(), 1);
+    assert!(sent[0].content().contains(&mapping.linear_identifier));
+    assert!(sent[0].content().contains("What should **new users** see?"));
+    assert_eq!(
+        db::get_cached_status(&pool, &issue_id)
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("Triage")
+    );
+    assert_eq!(linear.issue(&issue_id).unwrap().links.len(), 1);
+
+    // The post's own thread-create event doesn't file it again.
+    let channel = channel_config();
+    sync_discord_to_linear(&discord, &pool, &channel, &linear, &thread)
+        .await
+        .unwrap();
+    assert_eq!(linear.calls("create_issue"), 1);
+}
+
+#[tokio::test]
+async fn tracked_issues_are_left_alone() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    let issue_id = issue(&linear, &[DISCUSS_LABEL]).await;
+
+    assert!(open(&discord, &pool, &linear, TEAM_ID, &issue_id).await);
+    assert!(!open(&discord, &pool, &linear, TEAM_ID, &issue_id).await);
+}
+
+#[tokio::test]
+async fn issues_without_the_label_or_in_other_teams_are_skipped() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    let unlabeled = issue(&linear, &["label-bug"]).await;
+    let labeled = issue(&linear, &[DISCUSS_LABEL]).await;
+
+    assert!(!open(&discord, &pool, &linear, TEAM_ID, &unlabeled).await);
+    assert!(!open(&discord, &pool, &linear, "team-other", &labeled).await);
+    assert!(db::get_mapping_by_linear_issue(&pool, &labeled)
+        .await
+        .unwrap()
+        .is_none());
+}
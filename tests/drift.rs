@@ -0,0 +1,252 @@
+//! The drift check: finding mappings out of step with Linear and Discord, and repairing them.
+
+mod common;
+
+use serenity::all::{ChannelId, GuildChannel, MessageId};
+use sqlx::SqlitePool;
+
+use discord_linear_bot::db;
+use discord_linear_bot::discord::port::DiscordPort;
+use discord_linear_bot::sync::discord_to_linear::sync_discord_to_linear;
+use discord_linear_bot::sync::drift::{check_drift, find_drift, prune_orphans, DriftKind};
+use discord_linear_bot::sync::linear_to_discord::sync_linear_comments_to_discord;
+
+use common::fake_discord::{FakeDiscord, GUILD_ID};
+use common::mock_linear::MockLinear;
+use common::{channel_config, config, test_pool, user, FORUM_ID};
+
+const OPS_CHANNEL_ID: u64 = 5000;
+
+/// File an issue for a new post and cache its status, as the poller would on its first pass.
+async fn synced_thread(
+    discord: &FakeDiscord,
+    pool: &SqlitePool,
+    linear: &MockLinear,
+    name: &str,
+) -> (GuildChannel, String) {
+    let thread = discord.add_thread(FORUM_ID, name, "Details");
+    sync_discord_to_linear(discord, pool, &channel_config(), linear, &thread)
+        .await
+        .unwrap();
+    let mapping = db::get_mapping_by_discord_thread(pool, &thread.id.to_string())
+        .await
+        .unwrap()
+        .unwrap();
+    db::upsert_cached_status(pool, &mapping.linear_issue_id, "Triage")
+        .await
+        .unwrap();
+    (thread, mapping.linear_issue_id)
+}
+
+#[tokio::test]
+async fn drift_is_found_reported_and_repaired() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    discord.add_text_channel(OPS_CHANNEL_ID);
+
+    let (healthy, healthy_issue) = synced_thread(&discord, &pool, &linear, "Healthy").await;
+    let (deleted, _) = synced_thread(&discord, &pool, &linear, "Deleted thread").await;
+    discord.delete_thread(deleted.id);
+    // Filed, but the poller never got to it.
+    let uncached = discord.add_thread(FORUM_ID, "Uncached", "Details");
+    sync_discord_to_linear(&discord, &pool, &channel_config(), &linear, &uncached)
+        .await
+        .unwrap();
+    let uncached_issue = db::get_mapping_by_discord_thread(&pool, &uncached.id.to_string())
+        .await
+        .unwrap()
+        .unwrap()
+        .linear_issue_id;
+    let orphan = discord.add_thread(FORUM_ID, "Issue deleted in Linear", "Details");
+    db::create_mapping(&pool, &orphan.id.to_string(), "gone", "ENG-404", "bug", "1")
+        .await
+        .unwrap();
+
+    // A relayed comment whose message a moderator deleted.
+    let comment_id = linear.add_comment(&healthy_issue, &user("u1", "Alice"), "Looking");
+    sync_linear_comments_to_discord(
+        &discord,
+        &pool,
+        &config(vec![channel_config()]),
+        &linear,
+        &healthy_issue,
+        "ENG-1",
+    )
+    .await
+    .unwrap();
+    let relayed = db::get_synced_comments(&pool, &healthy_issue)
+        .await
+        .unwrap();
+    let message_id = MessageId::new(relayed[0].discord_message_id.parse().unwrap());
+    discord
+        .delete_message(healthy.id, message_id)
+        .await
+        .unwrap();
+
+    let drift = find_drift(&discord, &pool, &linear).await.unwrap();
+    let found: Vec<_> = drift
+        .iter()
+        .map(|d| (d.discord_thread_id.clone(), d.kind.clone()))
+        .collect();
+    assert_eq!(found.len(), 4);
+    assert!(found.contains(&(
+        healthy.id.to_string(),
+        DriftKind::MessageMissing {
+            linear_comment_id: comment_id.clone(),
+            discord_message_id: message_id.to_string(),
+        }
+    )));
+    assert!(found.contains(&(deleted.id.to_string(), DriftKind::ThreadMissing)));
+    assert!(found.contains(&(
+        uncached.id.to_string(),
+        DriftKind::StatusUncached {
+            status: "Triage".into()
+        }
+    )));
+    assert!(found.contains(&(orphan.id.to_string(), DriftKind::IssueMissing)));
+
+    let mut config = config(vec![channel_config()]);
+    config.ops_channel_id = Some(OPS_CHANNEL_ID);
+    config.drift_auto_repair = true;
+    check_drift(&discord, &pool, &config, &linear)
+        .await
+        .unwrap();
+
+    let report = discord.sent(ChannelId::new(OPS_CHANNEL_ID));
+    assert_eq!(report.len(), 1);
+    assert!(report[0]
+        .content()
+        .starts_with("Drift check found 4 discrepancies and repaired 4:"));
+    assert!(report[0].content().contains("ENG-404"));
+
+    for thread in [&deleted, &orphan] {
+        assert!(
+            db::get_mapping_by_discord_thread(&pool, &thread.id.to_string())
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+    assert_eq!(
+        db::get_cached_status(&pool, &uncached_issue)
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("Triage")
+    );
+    assert!(!db::is_comment_synced(&pool, &comment_id).await.unwrap());
+    assert!(find_drift(&discord, &pool, &linear)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn drift_is_only_reported_without_auto_repair() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    discord.add_text_channel(OPS_CHANNEL_ID);
+    let (thread, _) = synced_thread(&discord, &pool, &linear, "Deleted thread").await;
+    discord.delete_thread(thread.id);
+
+    let mut config = config(vec![channel_config()]);
+    config.ops_channel_id = Some(OPS_CHANNEL_ID);
+    let drift = check_drift(&discord, &pool, &config, &linear)
+        .await
+        .unwrap();
+
+    assert_eq!(drift.len(), 1);
+    let report = discord.sent(ChannelId::new(OPS_CHANNEL_ID));
+    assert!(report[0].content().contains("DRIFT_AUTO_REPAIR=1"));
+    assert!(
+        db::get_mapping_by_discord_thread(&pool, &thread.id.to_string())
+            .await
+            .unwrap()
+            .is_some()
+    );
+}
+
+#[tokio::test]
+async fn prune_removes_only_orphaned_mappings() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    let (deleted, _) = synced_thread(&discord, &pool, &linear, "Deleted thread").await;
+    discord.delete_thread(deleted.id);
+    // Not an orphan, so prune leaves it for the drift check.
+    let uncached = discord.add_thread(FORUM_ID, "Uncached", "Details");
+    sync_discord_to_linear(&discord, &pool, &channel_config(), &linear, &uncached)
+        .await
+        .unwrap();
+
+    let pruned = prune_orphans(&discord, &pool, &linear, None, Some("42"))
+        .await
+        .unwrap();
+
+    assert_eq!(pruned.len(), 1);
+    assert_eq!(pruned[0].kind, DriftKind::ThreadMissing);
+    assert!(
+        db::get_mapping_by_discord_thread(&pool, &deleted.id.to_string())
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(
+        db::get_mapping_by_discord_thread(&pool, &uncached.id.to_string())
+            .await
+            .unwrap()
+            .is_some()
+    );
+    let entries = db::get_audit_entries(&pool, &deleted.id.to_string(), 1)
+        .await
+        .unwrap();
+    assert_eq!(entries[0].action, "mapping_removed");
+    assert_eq!(entries[0].actor_discord_user_id.as_deref(), Some("42"));
+    assert_eq!(entries[0].detail, "thread_deleted");
+}
+
+#[tokio::test]
+async fn prune_in_a_server_leaves_other_servers_mappings() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    let (deleted, _) = synced_thread(&discord, &pool, &linear, "Deleted thread").await;
+    discord.delete_thread(deleted.id);
+    // Another server's thread, also gone.
+    db::create_mapping(&pool, "9999", "elsewhere", "OTHER-1", "bug", "2000")
+        .await
+        .unwrap();
+
+    let pruned = prune_orphans(&discord, &pool, &linear, Some(&GUILD_ID.to_string()), None)
+        .await
+        .unwrap();
+
+    assert_eq!(pruned.len(), 1);
+    assert_eq!(pruned[0].discord_thread_id, deleted.id.to_string());
+    assert!(db::get_mapping_by_discord_thread(&pool, "9999")
+        .await
+        .unwrap()
+        .is_some());
+}
+
+#[tokio::test]
+async fn linear_outage_is_not_reported_as_drift() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    synced_thread(&discord, &pool, &linear, "Healthy").await;
+
+    linear.fail("get_issues_by_ids");
+
+    assert!(find_drift(&discord, &pool, &linear)
+        .await
+        .unwrap()
+        .is_empty());
+}
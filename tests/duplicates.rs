@@ -0,0 +1,143 @@
+//! Similar issues suggested before a new thread's issue is created.
+
+mod common;
+
+use serenity::all::GuildChannel;
+
+use discord_linear_bot::config::ChannelConfig;
+use discord_linear_bot::db;
+use discord_linear_bot::sync::discord_to_linear::sync_discord_to_linear;
+
+use common::fake_discord::FakeDiscord;
+use common::mock_linear::MockLinear;
+use common::{channel_config, test_pool, FORUM_ID};
+
+fn suggesting_config() -> ChannelConfig {
+    let mut config = channel_config();
+    config.suggest_duplicates = true;
+    config
+}
+
+/// A forum where "Crash on login" is already filed.
+async fn forum_with_issue(
+    discord: &FakeDiscord,
+    pool: &sqlx::SqlitePool,
+    linear: &MockLinear,
+    channel: &ChannelConfig,
+) {
+    discord.add_forum(FORUM_ID);
+    let first = discord.add_thread(FORUM_ID, "Crash on login", "It crashes");
+    sync_discord_to_linear(discord, pool, channel, linear, &first)
+        .await
+        .unwrap();
+    assert_eq!(linear.calls("create_issue"), 1);
+}
+
+async fn is_mapped(pool: &sqlx::SqlitePool, thread: &GuildChannel) -> bool {
+    db::get_mapping_by_discord_thread(pool, &thread.id.to_string())
+        .await
+        .unwrap()
+        .is_some()
+}
+
+#[tokio::test]
+async fn similar_issue_is_suggested_instead_of_created() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    let channel = suggesting_config();
+    forum_with_issue(&discord, &pool, &linear, &channel).await;
+
+    let thread = discord.add_thread(FORUM_ID, "Crash on login", "Same here");
+    sync_discord_to_linear(&discord, &pool, &channel, &linear, &thread)
+        .await
+        .unwrap();
+
+    assert_eq!(linear.calls("create_issue"), 1);
+    let sent = discord.sent(thread.id);
+    assert_eq!(sent.len(), 1);
+    assert!(sent[0].content().contains("may already be tracked"));
+    assert!(sent[0].has_embed());
+    let check = db::get_duplicate_check(&pool, &thread.id.to_string())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(check.outcome, None);
+
+    // Reconciling before the author answers leaves the prompt waiting.
+    sync_discord_to_linear(&discord, &pool, &channel, &linear, &thread)
+        .await
+        .unwrap();
+    assert_eq!(linear.calls("create_issue"), 1);
+    assert_eq!(discord.sent(thread.id).len(), 1);
+}
+
+#[tokio::test]
+async fn create_anyway_and_timeout_create_the_issue_but_link_does_not() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    let mut channel = suggesting_config();
+    forum_with_issue(&discord, &pool, &linear, &channel).await;
+    let mut threads = Vec::new();
+    for n in 0..3 {
+        let body = format!("Same here, attempt {n}");
+        let thread = discord.add_thread(FORUM_ID, "Crash on login", &body);
+        sync_discord_to_linear(&discord, &pool, &channel, &linear, &thread)
+            .await
+            .unwrap();
+        threads.push(thread);
+    }
+    let [created, linked, timed_out] = &threads[..] else {
+        unreachable!()
+    };
+
+    assert!(
+        db::resolve_duplicate_check(&pool, &created.id.to_string(), "create")
+            .await
+            .unwrap()
+    );
+    assert!(
+        db::resolve_duplicate_check(&pool, &linked.id.to_string(), "link")
+            .await
+            .unwrap()
+    );
+    channel.duplicate_timeout_secs = 0;
+    for thread in &threads {
+        sync_discord_to_linear(&discord, &pool, &channel, &linear, thread)
+            .await
+            .unwrap();
+    }
+
+    assert!(is_mapped(&pool, created).await);
+    assert!(!is_mapped(&pool, linked).await);
+    assert!(is_mapped(&pool, timed_out).await);
+    let check = db::get_duplicate_check(&pool, &timed_out.id.to_string())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(check.outcome.as_deref(), Some("timeout"));
+    // An answer arriving after the timeout is ignored.
+    assert!(
+        !db::resolve_duplicate_check(&pool, &timed_out.id.to_string(), "create")
+            .await
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn failed_search_files_the_issue() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    let channel = suggesting_config();
+    forum_with_issue(&discord, &pool, &linear, &channel).await;
+
+    linear.fail("search_issues");
+    let thread = discord.add_thread(FORUM_ID, "Crash on login", "Same here");
+    sync_discord_to_linear(&discord, &pool, &channel, &linear, &thread)
+        .await
+        .unwrap();
+
+    assert_eq!(linear.calls("create_issue"), 2);
+}
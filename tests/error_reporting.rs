@@ -0,0 +1,98 @@
+//! Reporting failures to Sentry, captured with an in-memory transport.
+
+#![cfg(feature = "sentry")]
+
+use std::sync::{Arc, Mutex};
+
+use sentry::protocol::{Context, Event, Value};
+use sentry::{ClientOptions, Envelope, Hub, Transport};
+use tracing::{error, info, info_span, warn};
+use tracing_subscriber::layer::SubscriberExt;
+
+use discord_linear_bot::error_reporting;
+
+#[derive(Default)]
+struct Captured(Mutex<Vec<Event<'static>>>);
+
+impl Transport for Captured {
+    fn send_envelope(&self, envelope: Envelope) {
+        if let Some(event) = envelope.event() {
+            self.0.lock().unwrap().push(event.clone());
+        }
+    }
+}
+
+/// Events reported while running `f` with the error reporting layer installed.
+fn capture(f: impl FnOnce()) -> Vec<Event<'static>> {
+    let captured = Arc::new(Captured::default());
+    let transport = captured.clone();
+    let client = sentry::Client::from(ClientOptions {
+        dsn: Some("https://public@sentry.invalid/1".parse().unwrap()),
+        transport: Some(Arc::new(move |_: &ClientOptions| {
+            transport.clone() as Arc<dyn Transport>
+        })),
+        ..Default::default()
+    });
+    let hub = Arc::new(Hub::new_from_top(Hub::main()));
+    hub.bind_client(Some(Arc::new(client)));
+    Hub::run(hub, || {
+        let subscriber = tracing_subscriber::registry().with(error_reporting::layer());
+        tracing::subscriber::with_default(subscriber, f);
+    });
+    let events = captured.0.lock().unwrap().clone();
+    events
+}
+
+fn field<'a>(event: &'a Event<'static>, key: &str) -> Option<&'a Value> {
+    match event.contexts.get("Rust Tracing Fields") {
+        Some(Context::Other(fields)) => fields.get(key),
+        _ => None,
+    }
+}
+
+#[test]
+fn failures_are_reported_with_the_sync_context() {
+    let events = capture(|| {
+        info_span!(target: "discord_linear_bot::discord", "thread_create").in_scope(|| {
+            let span = info_span!(
+                target: "discord_linear_bot::sync",
+                "create_issue_for_thread",
+                thread_id = 42u64,
+                team_id = "team-1",
+            );
+            span.in_scope(|| {
+                info!(target: "discord_linear_bot::sync", "Creating issue");
+                warn!(target: "discord_linear_bot::sync", error = "timeout", "Upload failed");
+            });
+        });
+    });
+
+    assert_eq!(events.len(), 1);
+    let event = &events[0];
+    assert_eq!(event.message.as_deref(), Some("Upload failed"));
+    assert_eq!(event.level, sentry::Level::Warning);
+    assert_eq!(field(event, "error"), Some(&Value::from("timeout")));
+    assert_eq!(
+        field(event, "create_issue_for_thread:thread_id"),
+        Some(&Value::from(42))
+    );
+    assert_eq!(
+        field(event, "create_issue_for_thread:team_id"),
+        Some(&Value::from("team-1"))
+    );
+}
+
+#[test]
+fn only_this_crates_failures_are_reported() {
+    let events = capture(|| {
+        error!(target: "discord_linear_bot::linear", "Linear webhook receiver stopped");
+        warn!(target: "discord_linear_bot::sync", attempt = 1, "No messages found in thread yet");
+        error!(target: "serenity::gateway", "Shard disconnected");
+    });
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        events[0].message.as_deref(),
+        Some("Linear webhook receiver stopped")
+    );
+}
@@ -0,0 +1,157 @@
+//! Spam and low-quality posts answered with a reply instead of an issue.
+
+mod common;
+
+use serenity::all::GuildChannel;
+use sqlx::SqlitePool;
+
+use discord_linear_bot::config::ChannelConfig;
+use discord_linear_bot::db;
+use discord_linear_bot::sync::discord_to_linear::sync_discord_to_linear;
+
+use common::fake_discord::{FakeDiscord, REPORTER_ID};
+use common::mock_linear::MockLinear;
+use common::{channel_config, test_pool, FORUM_ID};
+
+async fn post(
+    discord: &FakeDiscord,
+    pool: &SqlitePool,
+    linear: &MockLinear,
+    channel: &ChannelConfig,
+    title: &str,
+    body: &str,
+) -> GuildChannel {
+    let thread = discord.add_thread(FORUM_ID, title, body);
+    sync_discord_to_linear(discord, pool, channel, linear, &thread)
+        .await
+        .unwrap();
+    thread
+}
+
+async fn filter_reason(pool: &SqlitePool, thread: &GuildChannel) -> Option<String> {
+    sqlx::query_scalar("SELECT reason FROM filtered_threads WHERE discord_thread_id = ?")
+        .bind(thread.id.to_string())
+        .fetch_optional(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn short_post_gets_one_reply_and_no_issue() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    let mut channel = channel_config();
+    channel.min_body_length = 20;
+
+    let thread = post(&discord, &pool, &linear, &channel, "Broken", "  it broke  ").await;
+    // The reconcile pass sees the unmapped thread again.
+    sync_discord_to_linear(&discord, &pool, &channel, &linear, &thread)
+        .await
+        .unwrap();
+
+    assert_eq!(linear.calls("create_issue"), 0);
+    assert_eq!(
+        filter_reason(&pool, &thread).await.as_deref(),
+        Some("too_short")
+    );
+    let sent = discord.sent(thread.id);
+    assert_eq!(sent.len(), 1);
+    assert!(sent[0].content().contains("at least 20 characters"));
+
+    let long = post(
+        &discord,
+        &pool,
+        &linear,
+        &channel,
+        "Broken",
+        "It broke after the 2.3 update on Windows",
+    )
+    .await;
+    assert_eq!(linear.calls("create_issue"), 1);
+    assert_eq!(filter_reason(&pool, &long).await, None);
+}
+
+#[tokio::test]
+async fn authors_over_the_limit_are_not_filed() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    let mut channel = channel_config();
+    channel.user_issue_limit = Some(2);
+
+    let mut threads = Vec::new();
+    for n in 0..3 {
+        let body = format!("Problem number {n}");
+        threads.push(post(&discord, &pool, &linear, &channel, "Problem", &body).await);
+    }
+
+    assert_eq!(linear.calls("create_issue"), 2);
+    assert_eq!(
+        filter_reason(&pool, &threads[2]).await.as_deref(),
+        Some("rate_limited")
+    );
+    assert_eq!(discord.sent(threads[2].id).len(), 1);
+    assert_eq!(
+        db::count_recent_issues_by(&pool, &REPORTER_ID.to_string(), 3600)
+            .await
+            .unwrap(),
+        2
+    );
+}
+
+#[tokio::test]
+async fn blocked_users_and_keywords_are_not_filed() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    let mut channel = channel_config();
+    channel.blocked_keywords = vec!["Free Nitro".to_string()];
+
+    let spam = post(
+        &discord,
+        &pool,
+        &linear,
+        &channel,
+        "Hi",
+        "get FREE nitro here",
+    )
+    .await;
+    assert_eq!(
+        filter_reason(&pool, &spam).await.as_deref(),
+        Some("blocked_keyword")
+    );
+
+    channel.blocked_user_ids = vec![REPORTER_ID];
+    let blocked = post(&discord, &pool, &linear, &channel, "Crash", "It crashes").await;
+    assert_eq!(
+        filter_reason(&pool, &blocked).await.as_deref(),
+        Some("blocked_user")
+    );
+
+    assert_eq!(linear.calls("create_issue"), 0);
+    assert!(!discord.sent(blocked.id)[0].content().contains("nitro"));
+}
+
+#[tokio::test]
+async fn follow_up_messages_count_towards_the_length() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    let mut channel = channel_config();
+    channel.min_body_length = 20;
+    channel.description_message_limit = 3;
+
+    let thread = discord.add_thread(FORUM_ID, "Broken", "it broke");
+    discord.add_reply(thread.id, REPORTER_ID, "Only when logging in on Android", 5);
+    sync_discord_to_linear(&discord, &pool, &channel, &linear, &thread)
+        .await
+        .unwrap();
+
+    assert_eq!(linear.calls("create_issue"), 1);
+    assert_eq!(filter_reason(&pool, &thread).await, None);
+}
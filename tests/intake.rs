@@ -0,0 +1,142 @@
+//! Intake forms are only dropped once their issue exists.
+
+mod common;
+
+use discord_linear_bot::db;
+use discord_linear_bot::error::AppError;
+use discord_linear_bot::sync::discord_to_linear::{sync_discord_to_linear, IntakeAnswers};
+use discord_linear_bot::sync::outbox::{self, process_outbox, Operation};
+
+use common::fake_discord::FakeDiscord;
+use common::mock_linear::MockLinear;
+use common::{channel_config, config, test_pool, FORUM_ID};
+
+#[tokio::test]
+async fn timed_out_intake_is_kept_when_filing_fails() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    let mut channel = channel_config();
+    channel.intake_form = true;
+    channel.intake_timeout_secs = 0;
+    discord.add_forum(FORUM_ID);
+    let thread = discord.add_thread(FORUM_ID, "Crash on login", "It crashes");
+    let thread_id = thread.id.to_string();
+    sync_discord_to_linear(&discord, &pool, &channel, &linear, &thread)
+        .await
+        .unwrap();
+    assert!(db::get_pending_intake(&pool, &thread_id)
+        .await
+        .unwrap()
+        .is_some());
+
+    linear.fail("create_issue");
+    assert!(
+        sync_discord_to_linear(&discord, &pool, &channel, &linear, &thread)
+            .await
+            .is_err()
+    );
+    assert!(db::get_pending_intake(&pool, &thread_id)
+        .await
+        .unwrap()
+        .is_some());
+
+    // The retry can claim it again.
+    linear.recover("create_issue");
+    sync_discord_to_linear(&discord, &pool, &channel, &linear, &thread)
+        .await
+        .unwrap();
+    assert!(db::get_mapping_by_discord_thread(&pool, &thread_id)
+        .await
+        .unwrap()
+        .is_some());
+    assert!(db::get_pending_intake(&pool, &thread_id)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn failed_form_submission_is_filed_with_its_answers_from_the_outbox() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    let mut channel = channel_config();
+    channel.intake_form = true;
+    channel.intake_timeout_secs = 0;
+    let config = config(vec![channel]);
+    discord.add_forum(FORUM_ID);
+    let thread = discord.add_thread(FORUM_ID, "Crash on login", "It crashes");
+    let thread_id = thread.id.to_string();
+    sync_discord_to_linear(&discord, &pool, &config.channels[0], &linear, &thread)
+        .await
+        .unwrap();
+    // What a submit does before filing fails.
+    assert!(db::claim_pending_intake(&pool, &thread_id).await.unwrap());
+    let operation = Operation::FileIntake {
+        thread_id: thread.id.get(),
+        answers: Some(IntakeAnswers {
+            repro_steps: "Tap login twice".to_string(),
+            severity: "High".to_string(),
+            affected_version: "2.3.1".to_string(),
+        }),
+    };
+    outbox::enqueue(&pool, &operation, &AppError::Internal("boom".into())).await;
+
+    // The claim keeps the timeout from filing it without the answers.
+    sync_discord_to_linear(&discord, &pool, &config.channels[0], &linear, &thread)
+        .await
+        .unwrap();
+    assert_eq!(linear.calls("create_issue"), 0);
+
+    process_outbox(&discord, &pool, &config, &linear)
+        .await
+        .unwrap();
+
+    let mapping = db::get_mapping_by_discord_thread(&pool, &thread_id)
+        .await
+        .unwrap()
+        .expect("issue filed from the outbox");
+    let issue = linear.issue(&mapping.linear_issue_id).unwrap();
+    assert!(issue.description.contains("Tap login twice"));
+    assert!(issue.description.contains("2.3.1"));
+    assert!(db::get_pending_intake(&pool, &thread_id)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn abandoned_claim_expires_so_the_timeout_can_file() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    let mut channel = channel_config();
+    channel.intake_form = true;
+    channel.intake_timeout_secs = 0;
+    discord.add_forum(FORUM_ID);
+    let thread = discord.add_thread(FORUM_ID, "Crash on login", "It crashes");
+    let thread_id = thread.id.to_string();
+    sync_discord_to_linear(&discord, &pool, &channel, &linear, &thread)
+        .await
+        .unwrap();
+    // A submit claimed it, then the process died before filing.
+    assert!(db::claim_pending_intake(&pool, &thread_id).await.unwrap());
+    assert!(!db::claim_pending_intake(&pool, &thread_id).await.unwrap());
+
+    sqlx::query(
+        "UPDATE pending_intakes SET claimed_at = datetime('now', '-10 minutes')
+         WHERE discord_thread_id = ?",
+    )
+    .bind(&thread_id)
+    .execute(&pool)
+    .await
+    .unwrap();
+    sync_discord_to_linear(&discord, &pool, &channel, &linear, &thread)
+        .await
+        .unwrap();
+    assert!(db::get_mapping_by_discord_thread(&pool, &thread_id)
+        .await
+        .unwrap()
+        .is_some());
+}
@@ -0,0 +1,66 @@
+//! Labels added to new issues from keywords in the post's title or body.
+
+mod common;
+
+use discord_linear_bot::config::ChannelConfig;
+use discord_linear_bot::db;
+use discord_linear_bot::sync::discord_to_linear::sync_discord_to_linear;
+
+use common::fake_discord::FakeDiscord;
+use common::mock_linear::MockLinear;
+use common::{channel_config, test_pool, FORUM_ID};
+
+fn keyword_config() -> ChannelConfig {
+    let mut channel = channel_config();
+    channel
+        .keyword_label_map
+        .insert("android|apk".to_string(), "label-android".to_string());
+    channel
+        .keyword_label_map
+        .insert("iOS".to_string(), "label-ios".to_string());
+    channel
+}
+
+/// File a post and return its issue's labels.
+async fn file(title: &str, body: &str) -> Vec<String> {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    let thread = discord.add_thread(FORUM_ID, title, body);
+
+    sync_discord_to_linear(&discord, &pool, &keyword_config(), &linear, &thread)
+        .await
+        .unwrap();
+
+    let mapping = db::get_mapping_by_discord_thread(&pool, &thread.id.to_string())
+        .await
+        .unwrap()
+        .unwrap();
+    linear.issue(&mapping.linear_issue_id).unwrap().label_ids
+}
+
+#[tokio::test]
+async fn any_alternative_in_the_title_or_body_adds_the_label() {
+    assert_eq!(
+        file("Crash on Android", "It crashes").await,
+        ["label-bug", "label-android"]
+    );
+    assert_eq!(
+        file("Crash on login", "Installed the latest APK").await,
+        ["label-bug", "label-android"]
+    );
+}
+
+#[tokio::test]
+async fn every_matching_keyword_adds_its_label() {
+    assert_eq!(
+        file("Sync broken", "Both the ios and android apps lose data").await,
+        ["label-bug", "label-android", "label-ios"]
+    );
+}
+
+#[tokio::test]
+async fn posts_without_keywords_keep_the_channel_label() {
+    assert_eq!(file("Crash on login", "It crashes").await, ["label-bug"]);
+}
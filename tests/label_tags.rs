@@ -0,0 +1,114 @@
+//! Linear label changes mirrored onto the thread's forum tags through `tag_label_map`.
+
+mod common;
+
+use serenity::all::{EditThread, ForumTagId};
+
+use discord_linear_bot::config::ChannelConfig;
+use discord_linear_bot::db;
+use discord_linear_bot::discord::port::DiscordPort;
+use discord_linear_bot::linear::api::LinearApi;
+use discord_linear_bot::sync::discord_to_linear::sync_discord_to_linear;
+use discord_linear_bot::sync::linear_to_discord::sync_labels_to_tags;
+use discord_linear_bot::sync::thread::tags_for_labels;
+
+use common::fake_discord::FakeDiscord;
+use common::mock_linear::MockLinear;
+use common::{channel_config, config, test_pool, FORUM_ID};
+
+const BUG_TAG: u64 = 901;
+const UI_TAG: u64 = 902;
+const UNMAPPED_TAG: u64 = 500;
+
+fn tagged_config() -> ChannelConfig {
+    let mut channel = channel_config();
+    channel
+        .tag_label_map
+        .insert(BUG_TAG.to_string(), "label-crash".to_string());
+    channel
+        .tag_label_map
+        .insert(UI_TAG.to_string(), "label-ui".to_string());
+    channel
+}
+
+fn tags(ids: &[u64]) -> Vec<ForumTagId> {
+    ids.iter().map(|id| ForumTagId::new(*id)).collect()
+}
+
+fn sorted(mut tags: Vec<ForumTagId>) -> Vec<ForumTagId> {
+    tags.sort();
+    tags
+}
+
+#[tokio::test]
+async fn label_changes_in_linear_update_the_mapped_tags() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    let channel = tagged_config();
+    let config = config(vec![channel.clone()]);
+    let thread = discord.add_thread(FORUM_ID, "Button is misaligned", "On the settings page");
+    discord
+        .edit_thread(
+            thread.id,
+            EditThread::new().applied_tags(tags(&[BUG_TAG, UNMAPPED_TAG])),
+        )
+        .await
+        .unwrap();
+    let thread = discord.thread(thread.id);
+    sync_discord_to_linear(&discord, &pool, &channel, &linear, &thread)
+        .await
+        .unwrap();
+    let mapping = db::get_mapping_by_discord_thread(&pool, &thread.id.to_string())
+        .await
+        .unwrap()
+        .unwrap();
+
+    // Someone in Linear swaps the bug label for the UI one.
+    linear.set_labels(&mapping.linear_issue_id, &["label-bug", "label-ui"]);
+    let issue = linear
+        .get_issues_by_ids(std::slice::from_ref(&mapping.linear_issue_id))
+        .await
+        .unwrap()
+        .remove(0);
+    sync_labels_to_tags(
+        &discord,
+        &config,
+        &mapping.discord_thread_id,
+        &mapping.linear_identifier,
+        &issue.label_ids,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        sorted(discord.thread(thread.id).applied_tags),
+        tags(&[UNMAPPED_TAG, UI_TAG])
+    );
+}
+
+#[test]
+fn unchanged_tags_need_no_edit() {
+    let channel = tagged_config();
+    let labels = vec!["label-crash".to_string()];
+
+    assert_eq!(
+        tags_for_labels(&tags(&[BUG_TAG, UNMAPPED_TAG]), &channel, &labels),
+        None
+    );
+    assert_eq!(
+        tags_for_labels(&tags(&[BUG_TAG]), &channel_config(), &[]),
+        None
+    );
+}
+
+#[test]
+fn added_tags_stop_at_the_discord_limit() {
+    let channel = tagged_config();
+    let labels = vec!["label-crash".to_string(), "label-ui".to_string()];
+
+    let updated = tags_for_labels(&tags(&[1, 2, 3, 4]), &channel, &labels).unwrap();
+
+    assert_eq!(updated, tags(&[1, 2, 3, 4, BUG_TAG]));
+}
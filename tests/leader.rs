@@ -0,0 +1,61 @@
+//! Leader election between replicas sharing a database.
+
+mod common;
+
+use std::time::Duration;
+
+use discord_linear_bot::leader::LeaderLease;
+use discord_linear_bot::shutdown::Shutdown;
+
+const TTL: Duration = Duration::from_secs(30);
+
+#[tokio::test]
+async fn only_one_replica_holds_the_lease_until_it_releases_it() {
+    let pool = common::test_pool().await;
+    let first = LeaderLease::new(pool.clone(), TTL);
+    let second = LeaderLease::new(pool.clone(), TTL);
+    assert_ne!(first.holder(), second.holder());
+
+    assert!(first.try_acquire().await.unwrap());
+    assert!(!second.try_acquire().await.unwrap());
+    // Renewing keeps it.
+    assert!(first.try_acquire().await.unwrap());
+    assert!(!second.try_acquire().await.unwrap());
+
+    first.release().await.unwrap();
+    assert!(second.try_acquire().await.unwrap());
+    assert!(!first.try_acquire().await.unwrap());
+}
+
+#[tokio::test]
+async fn an_expired_lease_is_taken_over_and_its_old_holder_steps_down() {
+    let pool = common::test_pool().await;
+    // A lease that expires at once, as if its holder had stopped renewing long ago.
+    let stale = LeaderLease::new(pool.clone(), Duration::ZERO);
+    let standby = LeaderLease::new(pool.clone(), TTL);
+    assert!(stale.try_acquire().await.unwrap());
+
+    let shutdown = Shutdown::new();
+    assert!(standby.acquire(&shutdown).await);
+    assert!(!standby.is_lost());
+
+    // The old leader notices on its next renewal and shuts down.
+    let stale_shutdown = Shutdown::new();
+    tokio::time::timeout(Duration::from_secs(5), stale.hold(&stale_shutdown))
+        .await
+        .expect("the old leader stops renewing");
+    assert!(stale_shutdown.is_requested());
+    assert!(stale.is_lost());
+}
+
+#[tokio::test]
+async fn a_standby_stops_waiting_on_shutdown() {
+    let pool = common::test_pool().await;
+    let leader = LeaderLease::new(pool.clone(), TTL);
+    let standby = LeaderLease::new(pool, TTL);
+    assert!(leader.try_acquire().await.unwrap());
+
+    let shutdown = Shutdown::new();
+    shutdown.request();
+    assert!(!standby.acquire(&shutdown).await);
+}
@@ -0,0 +1,52 @@
+//! Sync paths that only touch Linear and the database, driven against `MockLinear`.
+
+mod common;
+
+use discord_linear_bot::db;
+use discord_linear_bot::linear::api::LinearApi;
+use discord_linear_bot::sync::discord_to_linear::create_linear_comment;
+
+use common::mock_linear::{MockLinear, BOT_USER_ID};
+use common::test_pool;
+
+#[tokio::test]
+async fn created_comment_is_recorded_as_synced() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let issue = linear
+        .create_issue("team", "Crash on login", "", &[], "")
+        .await
+        .unwrap();
+
+    let comment_id = create_linear_comment(&pool, &linear, &issue.id, "Still happening", None, "1")
+        .await
+        .unwrap();
+
+    let comments = linear.comments(&issue.id);
+    assert_eq!(comments.len(), 1);
+    assert_eq!(comments[0].id, comment_id);
+    assert_eq!(comments[0].author_id.as_deref(), Some(BOT_USER_ID));
+    assert!(db::is_comment_synced(&pool, &comment_id).await.unwrap());
+}
+
+#[tokio::test]
+async fn failed_comment_leaves_no_synced_record() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let issue = linear
+        .create_issue("team", "Crash on login", "", &[], "")
+        .await
+        .unwrap();
+    linear.fail("create_comment");
+
+    let result =
+        create_linear_comment(&pool, &linear, &issue.id, "Still happening", None, "1").await;
+
+    assert!(result.is_err());
+    assert!(linear.comments(&issue.id).is_empty());
+    let (synced,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM synced_comments")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(synced, 0);
+}
@@ -0,0 +1,103 @@
+//! Routing Linear calls between workspaces, each served by its own `LinearServer`.
+
+mod common;
+
+use std::collections::BTreeMap;
+
+use discord_linear_bot::config::SharedConfig;
+use discord_linear_bot::linear::api::LinearApi;
+use discord_linear_bot::linear::workspaces::LinearWorkspaces;
+
+use common::linear_server::LinearServer;
+use common::{channel_config, config};
+
+const SECOND_TEAM_ID: &str = "team-2";
+
+/// Workspaces for `channel_config()`'s team on `first` and `SECOND_TEAM_ID` on `second`, whose
+/// channel uses the "second" credential.
+fn workspaces(first: &LinearServer, second: &LinearServer) -> LinearWorkspaces {
+    let mut other = channel_config();
+    other.discord_channel_id += 1;
+    other.linear_team_id = SECOND_TEAM_ID.to_string();
+    other.linear_credential = Some("second".to_string());
+    let shared = SharedConfig::new(config(vec![channel_config(), other]));
+    LinearWorkspaces::new(
+        first.client(),
+        BTreeMap::from([("second".to_string(), second.client())]),
+        shared,
+    )
+}
+
+#[tokio::test]
+async fn team_calls_go_to_the_teams_workspace() {
+    let first = LinearServer::start().await;
+    let second = LinearServer::start().await;
+    let linear = workspaces(&first, &second);
+
+    let issue = linear
+        .create_issue(SECOND_TEAM_ID, "Crash on login", "", &[], "project-2")
+        .await
+        .unwrap();
+    linear
+        .create_comment(None, &issue.id, "Still happening", None)
+        .await
+        .unwrap();
+
+    assert!(first.operations().is_empty());
+    assert_eq!(second.operations(), vec!["CreateIssue", "CreateComment"]);
+    assert_eq!(second.comments(&issue.id).len(), 1);
+}
+
+#[tokio::test]
+async fn unseen_issue_is_looked_up_in_each_workspace_once() {
+    let first = LinearServer::start().await;
+    let second = LinearServer::start().await;
+    let issue = second
+        .client()
+        .create_issue(SECOND_TEAM_ID, "Crash on login", "", &[], "project-2")
+        .await
+        .unwrap();
+    let linear = workspaces(&first, &second);
+
+    let found = linear.get_issue(&issue.id).await.unwrap();
+    assert_eq!(found.identifier, issue.identifier);
+    linear
+        .create_comment(None, &issue.id, "Still happening", None)
+        .await
+        .unwrap();
+
+    // The first workspace was asked once; after that the issue's workspace is known.
+    assert_eq!(first.operations(), vec!["Issue"]);
+    assert_eq!(
+        second.operations(),
+        vec!["CreateIssue", "Issue", "CreateComment"]
+    );
+}
+
+#[tokio::test]
+async fn a_failing_workspace_doesnt_hide_the_others_issues() {
+    let first = LinearServer::start().await;
+    let second = LinearServer::start().await;
+    let issue = second
+        .client()
+        .create_issue(SECOND_TEAM_ID, "Crash on login", "", &[], "project-2")
+        .await
+        .unwrap();
+    let linear = workspaces(&first, &second);
+
+    first.fail_next(400, 1).await;
+    let found = linear
+        .get_issues_by_ids(std::slice::from_ref(&issue.id))
+        .await
+        .unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].identifier, issue.identifier);
+
+    // With no workspace answering, there's nothing to go on.
+    first.fail_next(400, 1).await;
+    second.fail_next(400, 1).await;
+    assert!(linear
+        .get_issues_by_ids(std::slice::from_ref(&issue.id))
+        .await
+        .is_err());
+}
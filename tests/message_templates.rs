@@ -0,0 +1,105 @@
+//! Message template overrides: startup validation and the messages they reword.
+
+mod common;
+
+use std::collections::HashMap;
+
+use discord_linear_bot::db;
+use discord_linear_bot::discord::subscriptions;
+use discord_linear_bot::sync::discord_to_linear::sync_discord_to_linear;
+use discord_linear_bot::sync::linear_to_discord::sync_linear_comments_to_discord;
+use discord_linear_bot::templates::{validate_message, validate_messages, Notification};
+
+use common::fake_discord::FakeDiscord;
+use common::mock_linear::MockLinear;
+use common::{channel_config, config, test_pool, user, FORUM_ID};
+
+fn overrides(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(key, source)| (key.to_string(), source.to_string()))
+        .collect()
+}
+
+#[test]
+fn overrides_may_only_use_their_messages_variables() {
+    assert!(validate_messages(&overrides(&[
+        (
+            "dm_comments",
+            "{{count}} comments on {{identifier}} in {{thread}}"
+        ),
+        ("digest", "Updates ({{count}}):\n{{notices}}"),
+    ]))
+    .is_ok());
+
+    let err = validate_messages(&overrides(&[(
+        "dm_assigned",
+        "{{identifier}} is {{status}}",
+    )]))
+    .unwrap_err();
+    assert!(err.contains("dm_assigned"), "{err}");
+
+    assert!(validate_messages(&overrides(&[("comments", "{{body}}")])).is_err());
+}
+
+#[test]
+fn conditionals_on_optional_variables_validate() {
+    let reply = "{{#if linear_name}}{{linear_name}}{{else}}{{author}}{{/if}}: {{content}}";
+
+    assert!(validate_message(Notification::Reply, reply).is_ok());
+    assert!(validate_message(Notification::Comment, reply).is_err());
+}
+
+#[test]
+fn subscriber_dms_use_overrides() {
+    let custom = overrides(&[("dm_status_changed", "{{identifier}} → {{status}}")]);
+
+    assert_eq!(
+        subscriptions::status_message(&custom, "55", "ENG-1", "Done"),
+        "ENG-1 → Done"
+    );
+    assert_eq!(
+        subscriptions::status_message(&HashMap::new(), "55", "ENG-1", "Done"),
+        "**ENG-1** moved to **Done** (<#55>)."
+    );
+}
+
+#[tokio::test]
+async fn relayed_comments_use_the_channels_template() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    let mut channel = channel_config();
+    channel.message_templates = overrides(&[("comment", "{{author}} on {{identifier}}: {{body}}")]);
+    discord.add_forum(FORUM_ID);
+    let thread = discord.add_thread(FORUM_ID, "Crash on login", "It crashes");
+    sync_discord_to_linear(&discord, &pool, &channel, &linear, &thread)
+        .await
+        .unwrap();
+    let mapping = db::get_mapping_by_discord_thread(&pool, &thread.id.to_string())
+        .await
+        .unwrap()
+        .unwrap();
+
+    linear.add_comment(
+        &mapping.linear_issue_id,
+        &user("u1", "Alice"),
+        "Looking into it",
+    );
+    sync_linear_comments_to_discord(
+        &discord,
+        &pool,
+        &config(vec![channel]),
+        &linear,
+        &mapping.linear_issue_id,
+        &mapping.linear_identifier,
+    )
+    .await
+    .unwrap();
+
+    let sent = discord.sent(thread.id);
+    assert_eq!(
+        sent.last().unwrap().content(),
+        format!("Alice on {}: Looking into it", mapping.linear_identifier)
+    );
+}
@@ -0,0 +1,75 @@
+//! The Linear metadata cache behind slash command autocomplete.
+
+mod common;
+
+use discord_linear_bot::discord::commands::{name_choices, resolve_name};
+use discord_linear_bot::linear::cache::MetadataCache;
+
+use common::mock_linear::MockLinear;
+use common::user;
+
+#[tokio::test]
+async fn lookups_are_served_from_the_cache() {
+    let linear = MockLinear::new();
+    linear.add_member("team", user("user-1", "Ada"));
+    linear.add_label("team", "label-bug", "Bug");
+    let cache = MetadataCache::default();
+
+    let first = cache.team(&linear, "team").await.unwrap();
+    let second = cache.team(&linear, "team").await.unwrap();
+
+    assert_eq!(first.members.len(), 1);
+    assert_eq!(second.members[0].id, "user-1");
+    assert_eq!(second.labels, vec![("label-bug".into(), "Bug".into())]);
+    assert_eq!(linear.calls("get_team_metadata"), 1);
+}
+
+#[tokio::test]
+async fn refresh_refetches_configured_teams_and_drops_the_rest() {
+    let linear = MockLinear::new();
+    let cache = MetadataCache::default();
+    cache.team(&linear, "old-team").await.unwrap();
+    linear.add_project("team", "project-1", "Onboarding");
+
+    let refreshed = cache.refresh(&linear, &["team".to_string()]).await;
+
+    assert_eq!(refreshed, 1);
+    assert_eq!(linear.calls("get_team_metadata"), 2);
+    let team = cache.team(&linear, "team").await.unwrap();
+    assert_eq!(team.projects[0].1, "Onboarding");
+    assert_eq!(linear.calls("get_team_metadata"), 2);
+    // Dropped, so looking it up fetches it again.
+    cache.team(&linear, "old-team").await.unwrap();
+    assert_eq!(linear.calls("get_team_metadata"), 3);
+}
+
+#[tokio::test]
+async fn failed_refresh_keeps_the_previous_metadata() {
+    let linear = MockLinear::new();
+    linear.add_label("team", "label-bug", "Bug");
+    let cache = MetadataCache::default();
+    cache.team(&linear, "team").await.unwrap();
+
+    linear.fail("get_team_metadata");
+    let refreshed = cache.refresh(&linear, &["team".to_string()]).await;
+
+    assert_eq!(refreshed, 0);
+    let team = cache.team(&linear, "team").await.unwrap();
+    assert_eq!(team.labels.len(), 1);
+}
+
+#[test]
+fn choices_match_names_and_resolve_ids_or_names() {
+    let labels = vec![
+        ("label-bug".to_string(), "Bug".to_string()),
+        ("label-debt".to_string(), "Tech debt".to_string()),
+        ("label-feature".to_string(), "Feature".to_string()),
+    ];
+
+    let choices = name_choices(&labels, "DE");
+    assert_eq!(choices.len(), 1);
+
+    assert_eq!(resolve_name(&labels, "label-feature").unwrap().1, "Feature");
+    assert_eq!(resolve_name(&labels, "tech DEBT").unwrap().0, "label-debt");
+    assert!(resolve_name(&labels, "Docs").is_none());
+}
@@ -0,0 +1,95 @@
+//! Schema migrations on fresh and pre-existing databases.
+
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::SqlitePool;
+
+use discord_linear_bot::db;
+
+async fn empty_pool() -> SqlitePool {
+    SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("open in-memory database")
+}
+
+async fn applied_versions(pool: &SqlitePool) -> Vec<i64> {
+    sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success ORDER BY version")
+        .fetch_all(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn migrations_apply_once() {
+    let pool = empty_pool().await;
+    db::run_migrations(&pool).await.unwrap();
+    let versions = applied_versions(&pool).await;
+    assert_eq!(versions.first(), Some(&1));
+    assert_eq!(versions.len() as i64, *versions.last().unwrap());
+
+    db::run_migrations(&pool).await.unwrap();
+    assert_eq!(applied_versions(&pool).await, versions);
+    assert!(db::check_integrity(&pool).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn untracked_database_keeps_its_data() {
+    // A database set up before migrations were tracked: the schema exists and holds rows, but
+    // there is no `_sqlx_migrations` table.
+    let pool = empty_pool().await;
+    sqlx::raw_sql(include_str!("../migrations/001_initial_schema.sql"))
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query(
+        "INSERT INTO sync_mappings (discord_thread_id, linear_issue_id, linear_identifier, channel_type)
+         VALUES ('100', 'issue-1', 'ENG-1', 'bug')",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    db::run_migrations(&pool).await.unwrap();
+
+    assert!(!applied_versions(&pool).await.is_empty());
+    let mapping = db::get_mapping_by_discord_thread(&pool, "100")
+        .await
+        .unwrap()
+        .expect("mapping survives");
+    assert_eq!(mapping.linear_identifier, "ENG-1");
+}
+
+/// `EXPLAIN QUERY PLAN` detail lines for `query`.
+async fn query_plan(pool: &SqlitePool, query: &str) -> Vec<String> {
+    let rows: Vec<(i64, i64, i64, String)> = sqlx::query_as(&format!("EXPLAIN QUERY PLAN {query}"))
+        .fetch_all(pool)
+        .await
+        .unwrap();
+    rows.into_iter().map(|r| r.3).collect()
+}
+
+#[tokio::test]
+async fn per_pass_lookups_use_indexes() {
+    let pool = empty_pool().await;
+    db::run_migrations(&pool).await.unwrap();
+
+    for query in [
+        "SELECT * FROM sync_mappings WHERE linear_issue_id = 'x'",
+        "SELECT * FROM sync_mappings WHERE discord_thread_id = 'x'",
+        "SELECT 1 FROM synced_comments WHERE linear_comment_id = 'x'",
+        "SELECT status_name FROM linear_status_cache WHERE linear_issue_id = 'x'",
+        "SELECT * FROM relayed_comments WHERE linear_issue_id = 'x'",
+        "SELECT * FROM pending_operations WHERE next_attempt_at <= datetime('now')",
+        "SELECT * FROM pending_archives WHERE archive_at <= datetime('now')",
+        "SELECT * FROM issue_titles WHERE title != applied_title",
+    ] {
+        let plan = query_plan(&pool, query).await;
+        assert!(
+            // Scanning a partial index only reads the rows it covers.
+            plan.iter()
+                .all(|step| !step.starts_with("SCAN") || step.contains("USING")),
+            "{query}: {plan:?}"
+        );
+    }
+}
@@ -0,0 +1,185 @@
+//! Per-user notification settings applied to subscription and assignment DMs.
+
+mod common;
+
+use serenity::all::ChannelId;
+use sqlx::SqlitePool;
+
+use discord_linear_bot::db::{self, NotificationPrefs, SyncMapping};
+use discord_linear_bot::linear::client::LinearAssignee;
+use discord_linear_bot::sync::discord_to_linear::sync_discord_to_linear;
+use discord_linear_bot::sync::linear_to_discord::{
+    sync_assignee_to_discord, sync_linear_comments_to_discord, sync_linear_to_discord,
+};
+
+use common::fake_discord::FakeDiscord;
+use common::mock_linear::MockLinear;
+use common::{channel_config, config, test_pool, user, FORUM_ID};
+
+const MEMBER: u64 = 4300;
+
+async fn tracked_thread(
+    discord: &FakeDiscord,
+    pool: &SqlitePool,
+    linear: &MockLinear,
+) -> SyncMapping {
+    discord.add_forum(FORUM_ID);
+    let thread = discord.add_thread(FORUM_ID, "Crash on login", "It crashes when I log in");
+    sync_discord_to_linear(discord, pool, &channel_config(), linear, &thread)
+        .await
+        .unwrap();
+    db::get_mapping_by_discord_thread(pool, &thread.id.to_string())
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+async fn set_prefs(pool: &SqlitePool, prefs: NotificationPrefs) {
+    db::upsert_notification_prefs(pool, &MEMBER.to_string(), &prefs)
+        .await
+        .unwrap();
+}
+
+/// Announce a status change and a new comment on the issue.
+async fn status_change_and_comment(
+    discord: &FakeDiscord,
+    pool: &SqlitePool,
+    linear: &MockLinear,
+    mapping: &SyncMapping,
+) {
+    let config = config(vec![channel_config()]);
+    let issue_id = &mapping.linear_issue_id;
+    sync_linear_to_discord(
+        discord,
+        pool,
+        &config,
+        linear,
+        &linear.move_to(issue_id, "In Progress"),
+    )
+    .await
+    .unwrap();
+    linear.add_comment(issue_id, &user("u1", "Alice"), "Found the cause");
+    sync_linear_comments_to_discord(
+        discord,
+        pool,
+        &config,
+        linear,
+        issue_id,
+        &mapping.linear_identifier,
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn subscription_dms_follow_the_subscribers_settings() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    let mapping = tracked_thread(&discord, &pool, &linear).await;
+    db::insert_subscription(&pool, &mapping.discord_thread_id, &MEMBER.to_string())
+        .await
+        .unwrap();
+
+    set_prefs(
+        &pool,
+        NotificationPrefs {
+            status_changes_only: true,
+            ..Default::default()
+        },
+    )
+    .await;
+    status_change_and_comment(&discord, &pool, &linear, &mapping).await;
+    let dms = discord.dms(MEMBER);
+    assert_eq!(dms.len(), 1);
+    assert!(dms[0].contains("moved to **In Progress**"));
+
+    set_prefs(
+        &pool,
+        NotificationPrefs {
+            muted: true,
+            ..Default::default()
+        },
+    )
+    .await;
+    status_change_and_comment(&discord, &pool, &linear, &mapping).await;
+    assert_eq!(discord.dms(MEMBER).len(), 1);
+}
+
+/// Reassign the issue to the Linear user `id`.
+async fn assign(discord: &FakeDiscord, pool: &SqlitePool, mapping: &SyncMapping, id: &str) {
+    let assignee = LinearAssignee {
+        id: id.to_string(),
+        name: if id == "u1" { "Alice" } else { "Bob" }.to_string(),
+    };
+    sync_assignee_to_discord(
+        discord,
+        pool,
+        &config(vec![channel_config()]),
+        &mapping.linear_issue_id,
+        &mapping.linear_identifier,
+        Some(&assignee),
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn assignment_dm_is_opt_in_and_muted_users_are_not_mentioned() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    let mapping = tracked_thread(&discord, &pool, &linear).await;
+    db::upsert_user_link(
+        &pool,
+        &MEMBER.to_string(),
+        "u1",
+        "Alice",
+        "alice@example.com",
+    )
+    .await
+    .unwrap();
+
+    // The first sighting only records the assignee.
+    assign(&discord, &pool, &mapping, "u2").await;
+    assign(&discord, &pool, &mapping, "u1").await;
+    assert!(discord.dms(MEMBER).is_empty());
+
+    set_prefs(
+        &pool,
+        NotificationPrefs {
+            dm_on_assignment: true,
+            ..Default::default()
+        },
+    )
+    .await;
+    assign(&discord, &pool, &mapping, "u2").await;
+    assign(&discord, &pool, &mapping, "u1").await;
+    let dms = discord.dms(MEMBER);
+    assert_eq!(dms.len(), 1);
+    assert!(dms[0].starts_with(&format!(
+        "You were assigned **{}**",
+        mapping.linear_identifier
+    )));
+
+    set_prefs(
+        &pool,
+        NotificationPrefs {
+            dm_on_assignment: true,
+            muted: true,
+            ..Default::default()
+        },
+    )
+    .await;
+    assign(&discord, &pool, &mapping, "u2").await;
+    assign(&discord, &pool, &mapping, "u1").await;
+    assert_eq!(discord.dms(MEMBER).len(), 1);
+    let sent = discord.sent(ChannelId::new(mapping.discord_thread_id.parse().unwrap()));
+    let mentions = sent
+        .iter()
+        .filter(|s| s.content().contains(&format!("<@{MEMBER}>")))
+        .count();
+    // Only the two assignments before muting mentioned the member.
+    assert_eq!(mentions, 2);
+    assert!(sent.last().unwrap().content().contains("**Alice**"));
+}
@@ -0,0 +1,68 @@
+//! Channels where only posts by members with certain roles get an issue automatically.
+
+mod common;
+
+use discord_linear_bot::config::ChannelConfig;
+use discord_linear_bot::db;
+use discord_linear_bot::sync::discord_to_linear::sync_discord_to_linear;
+
+use common::fake_discord::{FakeDiscord, REPORTER_ID};
+use common::mock_linear::MockLinear;
+use common::{channel_config, test_pool, FORUM_ID};
+
+const POSTER_ROLE: u64 = 888;
+
+fn restricted_config() -> ChannelConfig {
+    let mut config = channel_config();
+    config.poster_role_ids = vec![POSTER_ROLE];
+    config
+}
+
+/// Sync a new post by the reporter and return whether an issue was created for it.
+async fn post_is_filed(discord: &FakeDiscord, channel: &ChannelConfig) -> bool {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    discord.add_forum(FORUM_ID);
+    let thread = discord.add_thread(FORUM_ID, "Crash on login", "It crashes");
+
+    sync_discord_to_linear(discord, &pool, channel, &linear, &thread)
+        .await
+        .unwrap();
+
+    let mapped = db::get_mapping_by_discord_thread(&pool, &thread.id.to_string())
+        .await
+        .unwrap()
+        .is_some();
+    assert_eq!(linear.calls("create_issue"), usize::from(mapped));
+    mapped
+}
+
+#[tokio::test]
+async fn posts_by_members_with_a_poster_role_are_filed() {
+    let discord = FakeDiscord::new();
+    discord.add_member(REPORTER_ID, &[1, POSTER_ROLE]);
+
+    assert!(post_is_filed(&discord, &restricted_config()).await);
+}
+
+#[tokio::test]
+async fn posts_by_other_members_are_left_alone() {
+    let discord = FakeDiscord::new();
+    discord.add_member(REPORTER_ID, &[1]);
+
+    assert!(!post_is_filed(&discord, &restricted_config()).await);
+}
+
+#[tokio::test]
+async fn posts_by_authors_who_left_are_left_alone() {
+    let discord = FakeDiscord::new();
+
+    assert!(!post_is_filed(&discord, &restricted_config()).await);
+}
+
+#[tokio::test]
+async fn unrestricted_channels_file_every_post() {
+    let discord = FakeDiscord::new();
+
+    assert!(post_is_filed(&discord, &channel_config()).await);
+}
@@ -0,0 +1,46 @@
+use std::time::Duration;
+
+use discord_linear_bot::linear::poll_interval::PollInterval;
+
+const MIN: Duration = Duration::from_secs(30);
+const MAX: Duration = Duration::from_secs(300);
+
+#[test]
+fn quiet_polls_lengthen_the_interval_up_to_the_max() {
+    let mut interval = PollInterval::new(MIN, MAX);
+    let mut seen = vec![interval.current()];
+    for _ in 0..15 {
+        interval.record(false);
+        seen.push(interval.current());
+    }
+
+    let secs: Vec<u64> = seen.iter().step_by(3).map(Duration::as_secs).collect();
+    assert_eq!(secs, [30, 60, 120, 240, 300, 300]);
+}
+
+#[test]
+fn an_update_snaps_back_to_the_min() {
+    let mut interval = PollInterval::new(MIN, MAX);
+    for _ in 0..6 {
+        interval.record(false);
+    }
+    assert_eq!(interval.current(), MIN * 4);
+
+    // A quiet streak interrupted by an update starts over.
+    interval.record(false);
+    interval.record(false);
+    interval.record(true);
+    assert_eq!(interval.current(), MIN);
+    interval.record(false);
+    interval.record(false);
+    assert_eq!(interval.current(), MIN);
+}
+
+#[test]
+fn max_below_min_keeps_the_interval_fixed() {
+    let mut interval = PollInterval::new(MIN, Duration::from_secs(10));
+    for _ in 0..10 {
+        interval.record(false);
+    }
+    assert_eq!(interval.current(), MIN);
+}
@@ -0,0 +1,124 @@
+//! Posts deleted and posted again, recognized by their title and body.
+
+mod common;
+
+use serenity::all::{ChannelId, GuildChannel};
+use sqlx::SqlitePool;
+
+use discord_linear_bot::db;
+use discord_linear_bot::sync::discord_to_linear::{sync_discord_to_linear, sync_thread_delete};
+
+use common::fake_discord::FakeDiscord;
+use common::mock_linear::MockLinear;
+use common::{channel_config, config, test_pool, FORUM_ID};
+
+/// Post "Crash on login" and file it. Returns the thread and its issue ID.
+async fn original(
+    discord: &FakeDiscord,
+    pool: &SqlitePool,
+    linear: &MockLinear,
+) -> (GuildChannel, String) {
+    discord.add_forum(FORUM_ID);
+    let thread = discord.add_thread(FORUM_ID, "Crash on login", "It crashes when I log in");
+    sync_discord_to_linear(discord, pool, &channel_config(), linear, &thread)
+        .await
+        .unwrap();
+    let mapping = db::get_mapping_by_discord_thread(pool, &thread.id.to_string())
+        .await
+        .unwrap()
+        .unwrap();
+    (thread, mapping.linear_issue_id)
+}
+
+async fn delete(pool: &SqlitePool, linear: &MockLinear, thread: &GuildChannel) {
+    let config = config(vec![channel_config()]);
+    sync_thread_delete(pool, &config, linear, thread.id, ChannelId::new(FORUM_ID))
+        .await
+        .unwrap();
+}
+
+/// Post the same content again, with different case and spacing.
+async fn repost(discord: &FakeDiscord, pool: &SqlitePool, linear: &MockLinear) -> GuildChannel {
+    let thread = discord.add_thread(FORUM_ID, "crash on  login", "It crashes when I log in\n");
+    sync_discord_to_linear(discord, pool, &channel_config(), linear, &thread)
+        .await
+        .unwrap();
+    thread
+}
+
+#[tokio::test]
+async fn repost_of_a_deleted_thread_takes_over_its_issue() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    let (first, issue_id) = original(&discord, &pool, &linear).await;
+    delete(&pool, &linear, &first).await;
+
+    let second = repost(&discord, &pool, &linear).await;
+
+    assert_eq!(linear.calls("create_issue"), 1);
+    let mapping = db::get_mapping_by_discord_thread(&pool, &second.id.to_string())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(mapping.linear_issue_id, issue_id);
+    assert_eq!(
+        db::get_cached_status(&pool, &issue_id)
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("Triage")
+    );
+    let sent = discord.sent(second.id);
+    assert_eq!(sent.len(), 1);
+    assert!(sent[0].content().contains(&mapping.linear_identifier));
+    let links = linear.issue(&issue_id).unwrap().links;
+    assert_eq!(links.len(), 2);
+    assert!(links[1].ends_with(&format!("/{FORUM_ID}/{}", second.id)));
+}
+
+#[tokio::test]
+async fn repost_of_a_live_thread_points_to_it_once() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    let (first, _) = original(&discord, &pool, &linear).await;
+
+    let second = repost(&discord, &pool, &linear).await;
+    // The reconcile pass sees the unmapped thread again.
+    sync_discord_to_linear(&discord, &pool, &channel_config(), &linear, &second)
+        .await
+        .unwrap();
+
+    assert_eq!(linear.calls("create_issue"), 1);
+    assert!(
+        db::get_mapping_by_discord_thread(&pool, &second.id.to_string())
+            .await
+            .unwrap()
+            .is_none()
+    );
+    let sent = discord.sent(second.id);
+    assert_eq!(sent.len(), 1);
+    assert!(sent[0]
+        .content()
+        .contains(&format!("repost of <#{}>", first.id)));
+}
+
+#[tokio::test]
+async fn repost_of_a_closed_issue_is_filed_again() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    let (first, issue_id) = original(&discord, &pool, &linear).await;
+    linear.set_status(&issue_id, "Done");
+    delete(&pool, &linear, &first).await;
+
+    let second = repost(&discord, &pool, &linear).await;
+
+    assert_eq!(linear.calls("create_issue"), 2);
+    let mapping = db::get_mapping_by_discord_thread(&pool, &second.id.to_string())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_ne!(mapping.linear_issue_id, issue_id);
+}
@@ -0,0 +1,134 @@
+//! Channel routing rules picking the team, project, labels, and priority of new issues.
+
+mod common;
+
+use serde_json::json;
+use serenity::all::{EditThread, ForumTagId};
+
+use discord_linear_bot::config::{ChannelConfig, RoutingRule};
+use discord_linear_bot::db;
+use discord_linear_bot::discord::port::DiscordPort;
+use discord_linear_bot::sync::discord_to_linear::sync_discord_to_linear;
+
+use common::fake_discord::{FakeDiscord, REPORTER_ID};
+use common::mock_linear::{MockIssue, MockLinear};
+use common::{channel_config, test_pool, FORUM_ID, TEAM_ID};
+
+const CRASH_TAG: u64 = 901;
+const IOS_TAG: u64 = 902;
+const STAFF_ROLE: u64 = 7000;
+
+fn rule(value: serde_json::Value) -> RoutingRule {
+    serde_json::from_value(value).expect("valid routing rule")
+}
+
+fn routed_config() -> ChannelConfig {
+    let mut channel = channel_config();
+    channel
+        .tag_label_map
+        .insert(CRASH_TAG.to_string(), "label-crash".to_string());
+    channel.routing_rules = vec![
+        rule(json!({
+            "tag_ids": [IOS_TAG],
+            "linear_team_id": "team-mobile",
+            "linear_project_id": "project-ios",
+            "linear_label_ids": ["label-ios"],
+        })),
+        rule(json!({
+            "keywords": ["Billing"],
+            "linear_label_ids": ["label-billing"],
+            "priority": 2,
+        })),
+        rule(json!({
+            "author_role_ids": [STAFF_ROLE],
+            "linear_project_id": "project-internal",
+        })),
+    ];
+    channel
+}
+
+/// File a post with `tags` applied and return its issue.
+async fn file(discord: &FakeDiscord, title: &str, body: &str, tags: &[u64]) -> MockIssue {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    discord.add_forum(FORUM_ID);
+    let thread = discord.add_thread(FORUM_ID, title, body);
+    let applied: Vec<_> = tags.iter().map(|id| ForumTagId::new(*id)).collect();
+    discord
+        .edit_thread(thread.id, EditThread::new().applied_tags(applied))
+        .await
+        .unwrap();
+    let thread = discord.thread(thread.id);
+
+    sync_discord_to_linear(discord, &pool, &routed_config(), &linear, &thread)
+        .await
+        .unwrap();
+
+    let mapping = db::get_mapping_by_discord_thread(&pool, &thread.id.to_string())
+        .await
+        .unwrap()
+        .unwrap();
+    linear.issue(&mapping.linear_issue_id).unwrap()
+}
+
+#[tokio::test]
+async fn unmatched_posts_use_the_channel_settings() {
+    let issue = file(
+        &FakeDiscord::new(),
+        "Crash on login",
+        "It crashes",
+        &[CRASH_TAG],
+    )
+    .await;
+
+    assert_eq!(issue.team_id, TEAM_ID);
+    assert_eq!(issue.project_id, "project-1");
+    assert_eq!(issue.label_ids, ["label-bug", "label-crash"]);
+    assert_eq!(issue.priority, 0);
+}
+
+#[tokio::test]
+async fn rules_setting_a_team_drop_tag_mapped_labels() {
+    let tags = [CRASH_TAG, IOS_TAG];
+    let issue = file(&FakeDiscord::new(), "Crash on login", "It crashes", &tags).await;
+
+    assert_eq!(issue.team_id, "team-mobile");
+    assert_eq!(issue.project_id, "project-ios");
+    assert_eq!(issue.label_ids, ["label-ios"]);
+}
+
+#[tokio::test]
+async fn keywords_match_the_body_ignoring_case() {
+    let discord = FakeDiscord::new();
+    let issue = file(&discord, "Crash", "The billing page crashes", &[CRASH_TAG]).await;
+
+    assert_eq!(issue.team_id, TEAM_ID);
+    assert_eq!(issue.label_ids, ["label-billing", "label-crash"]);
+    assert_eq!(issue.priority, 2);
+}
+
+#[tokio::test]
+async fn the_first_matching_rule_wins() {
+    let discord = FakeDiscord::new();
+    discord.add_member(REPORTER_ID, &[STAFF_ROLE]);
+    let issue = file(&discord, "Billing is down", "", &[IOS_TAG]).await;
+
+    assert_eq!(issue.team_id, "team-mobile");
+}
+
+#[tokio::test]
+async fn rules_match_the_authors_roles() {
+    let discord = FakeDiscord::new();
+    discord.add_member(REPORTER_ID, &[STAFF_ROLE]);
+    let issue = file(&discord, "Slow search", "Search takes a minute", &[]).await;
+    assert_eq!(issue.project_id, "project-internal");
+
+    let issue = file(
+        &FakeDiscord::new(),
+        "Slow search",
+        "Search takes a minute",
+        &[],
+    )
+    .await;
+    assert_eq!(issue.project_id, "project-1");
+}
@@ -0,0 +1,95 @@
+//! Shadow mode: syncs run as usual, but their Linear and Discord writes are only recorded.
+
+mod common;
+
+use std::sync::Arc;
+
+use serde_json::Value;
+use serenity::all::EditThread;
+
+use common::fake_discord::FakeDiscord;
+use common::mock_linear::MockLinear;
+use common::{channel_config, test_pool, FORUM_ID};
+use discord_linear_bot::db;
+use discord_linear_bot::discord::port::DiscordPort;
+use discord_linear_bot::shadow::{ShadowLog, Shadowed};
+use discord_linear_bot::sync::discord_to_linear::sync_discord_to_linear;
+
+#[tokio::test]
+async fn a_new_post_is_mapped_without_filing_an_issue_or_replying() {
+    let pool = test_pool().await;
+    let log = ShadowLog::new(Some(pool.clone()));
+    let fake_discord = Arc::new(FakeDiscord::new());
+    fake_discord.add_forum(FORUM_ID);
+    let mock_linear = Arc::new(MockLinear::new());
+    let discord = Shadowed::new(fake_discord.clone(), Some(log.clone()));
+    let linear = Shadowed::new(mock_linear.clone(), Some(log));
+
+    let thread = fake_discord.add_thread(FORUM_ID, "Export crashes", "It crashes.");
+    sync_discord_to_linear(&discord, &pool, &channel_config(), &linear, &thread)
+        .await
+        .unwrap();
+
+    assert_eq!(mock_linear.calls("create_issue"), 0);
+    assert!(fake_discord.sent(thread.id).is_empty());
+    let mapping = db::get_mapping_by_discord_thread(&pool, &thread.id.to_string())
+        .await
+        .unwrap()
+        .expect("thread is mapped to the stand-in issue");
+    assert!(mapping.linear_identifier.starts_with("SHADOW-"));
+
+    let actions = db::get_shadow_actions(&pool).await.unwrap();
+    let kinds: Vec<_> = actions
+        .iter()
+        .map(|a| (a.service.as_str(), a.action.as_str()))
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            ("linear", "create_issue"),
+            ("linear", "link_url"),
+            ("discord", "send_message")
+        ]
+    );
+    let issue: Value = serde_json::from_str(&actions[0].detail).unwrap();
+    assert_eq!(issue["title"], "Export crashes");
+    assert_eq!(issue["label_ids"][0], "label-bug");
+    assert!(actions[2].detail.contains(&mapping.linear_identifier));
+}
+
+#[tokio::test]
+async fn thread_edits_are_skipped_and_only_logged_without_a_pool() {
+    let pool = test_pool().await;
+    let fake_discord = Arc::new(FakeDiscord::new());
+    fake_discord.add_forum(FORUM_ID);
+    let thread = fake_discord.add_thread(FORUM_ID, "Slow sync", "Takes minutes.");
+    let discord = Shadowed::new(fake_discord.clone(), Some(ShadowLog::new(None)));
+
+    let returned = discord
+        .edit_thread(thread.id, EditThread::new().archived(true).locked(true))
+        .await
+        .unwrap();
+
+    assert!(!returned.thread_metadata.unwrap().archived);
+    assert!(!fake_discord.is_archived(thread.id));
+    assert!(db::get_shadow_actions(&pool).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn without_a_log_writes_go_through() {
+    let pool = test_pool().await;
+    let fake_discord = Arc::new(FakeDiscord::new());
+    fake_discord.add_forum(FORUM_ID);
+    let mock_linear = Arc::new(MockLinear::new());
+    let discord = Shadowed::new(fake_discord.clone(), None);
+    let linear = Shadowed::new(mock_linear.clone(), None);
+
+    let thread = fake_discord.add_thread(FORUM_ID, "Export crashes", "It crashes.");
+    sync_discord_to_linear(&discord, &pool, &channel_config(), &linear, &thread)
+        .await
+        .unwrap();
+
+    assert_eq!(mock_linear.calls("create_issue"), 1);
+    assert_eq!(fake_discord.sent(thread.id).len(), 1);
+    assert!(db::get_shadow_actions(&pool).await.unwrap().is_empty());
+}
@@ -0,0 +1,51 @@
+//! Graceful shutdown: in-flight work is waited for, new work is turned away.
+
+use std::time::Duration;
+
+use discord_linear_bot::shutdown::Shutdown;
+
+#[tokio::test]
+async fn drain_waits_for_work_started_before_the_request() {
+    let shutdown = Shutdown::new();
+    let task = shutdown.start_task().expect("not shutting down yet");
+
+    shutdown.request();
+    assert!(shutdown.start_task().is_none());
+    assert_eq!(shutdown.in_flight(), 1);
+
+    let finish = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        drop(task);
+    });
+    assert!(shutdown.drain(Duration::from_secs(5)).await);
+    assert_eq!(shutdown.in_flight(), 0);
+    finish.await.unwrap();
+}
+
+#[tokio::test]
+async fn drain_gives_up_after_the_timeout() {
+    let shutdown = Shutdown::new();
+    let _stuck = shutdown.start_task().unwrap();
+    shutdown.request();
+
+    assert!(!shutdown.drain(Duration::from_millis(50)).await);
+}
+
+#[tokio::test]
+async fn waiters_wake_on_request_and_after_it() {
+    let shutdown = Shutdown::new();
+    let waiter = tokio::spawn({
+        let shutdown = shutdown.clone();
+        async move { shutdown.requested().await }
+    });
+    tokio::task::yield_now().await;
+    assert!(!waiter.is_finished());
+
+    shutdown.request();
+    tokio::time::timeout(Duration::from_secs(5), waiter)
+        .await
+        .expect("woken by the request")
+        .unwrap();
+    // Already requested: resolves straight away.
+    shutdown.requested().await;
+}
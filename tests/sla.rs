@@ -0,0 +1,120 @@
+//! Escalation pings for filed issues with no Linear activity within the channel's SLA.
+
+mod common;
+
+use sqlx::SqlitePool;
+
+use discord_linear_bot::config::ChannelConfig;
+use discord_linear_bot::db;
+use discord_linear_bot::sync::discord_to_linear::sync_discord_to_linear;
+use discord_linear_bot::sync::linear_to_discord::sync_linear_to_discord;
+use discord_linear_bot::sync::sla::check_slas;
+
+use common::fake_discord::FakeDiscord;
+use common::mock_linear::MockLinear;
+use common::{channel_config, config, test_pool, FORUM_ID};
+
+fn sla_config() -> ChannelConfig {
+    let mut channel = channel_config();
+    channel.sla_hours = Some(48);
+    channel.sla_role_id = Some(222);
+    channel
+}
+
+/// Pretend the thread's issue was filed `hours` ago.
+async fn filed_hours_ago(pool: &SqlitePool, thread_id: &str, hours: i64) {
+    sqlx::query(
+        "UPDATE sync_mappings SET created_at = datetime('now', '-' || ? || ' hours')
+         WHERE discord_thread_id = ?",
+    )
+    .bind(hours)
+    .bind(thread_id)
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn quiet_issues_are_escalated_on_a_backoff_until_linear_activity() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    let channel = sla_config();
+    let config = config(vec![channel.clone()]);
+    let thread = discord.add_thread(FORUM_ID, "Crash on login", "It crashes when I log in");
+    let thread_id = thread.id.to_string();
+    sync_discord_to_linear(&discord, &pool, &channel, &linear, &thread)
+        .await
+        .unwrap();
+    let sent_before = discord.sent(thread.id).len();
+
+    assert_eq!(check_slas(&discord, &pool, &config).await.unwrap(), 0);
+
+    filed_hours_ago(&pool, &thread_id, 50).await;
+    assert_eq!(check_slas(&discord, &pool, &config).await.unwrap(), 1);
+    let sent = discord.sent(thread.id);
+    assert_eq!(sent.len(), sent_before + 1);
+    let ping = sent.last().unwrap().content();
+    assert!(ping.starts_with("<@&222> **"), "{ping}");
+    assert!(
+        ping.ends_with("has had no activity in Linear for 50 hours"),
+        "{ping}"
+    );
+
+    // The next escalation waits for three times the SLA.
+    assert_eq!(check_slas(&discord, &pool, &config).await.unwrap(), 0);
+    filed_hours_ago(&pool, &thread_id, 150).await;
+    assert_eq!(check_slas(&discord, &pool, &config).await.unwrap(), 1);
+
+    let mapping = db::get_mapping_by_discord_thread(&pool, &thread_id)
+        .await
+        .unwrap()
+        .unwrap();
+    sync_linear_to_discord(
+        &discord,
+        &pool,
+        &config,
+        &linear,
+        &linear.move_to(&mapping.linear_issue_id, "In Progress"),
+    )
+    .await
+    .unwrap();
+    filed_hours_ago(&pool, &thread_id, 400).await;
+    assert_eq!(check_slas(&discord, &pool, &config).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn escalations_stop_at_the_channel_limit() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    let mut channel = sla_config();
+    channel.sla_max_pings = 1;
+    let config = config(vec![channel.clone()]);
+    let thread = discord.add_thread(FORUM_ID, "Crash on login", "It crashes when I log in");
+    sync_discord_to_linear(&discord, &pool, &channel, &linear, &thread)
+        .await
+        .unwrap();
+
+    filed_hours_ago(&pool, &thread.id.to_string(), 1000).await;
+    assert_eq!(check_slas(&discord, &pool, &config).await.unwrap(), 1);
+    assert_eq!(check_slas(&discord, &pool, &config).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn channels_without_an_sla_are_not_escalated() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    let config = config(vec![channel_config()]);
+    let thread = discord.add_thread(FORUM_ID, "Crash on login", "It crashes when I log in");
+    sync_discord_to_linear(&discord, &pool, &channel_config(), &linear, &thread)
+        .await
+        .unwrap();
+
+    filed_hours_ago(&pool, &thread.id.to_string(), 1000).await;
+    assert_eq!(check_slas(&discord, &pool, &config).await.unwrap(), 0);
+}
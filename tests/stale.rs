@@ -0,0 +1,123 @@
+//! "Still tracked" nudges in the threads of open issues without recent updates.
+
+mod common;
+
+use sqlx::SqlitePool;
+
+use discord_linear_bot::config::ChannelConfig;
+use discord_linear_bot::db;
+use discord_linear_bot::sync::discord_to_linear::sync_discord_to_linear;
+use discord_linear_bot::sync::stale::nudge_stale_issues;
+
+use common::fake_discord::FakeDiscord;
+use common::mock_linear::MockLinear;
+use common::{channel_config, config, test_pool, FORUM_ID};
+
+fn nudging_config() -> ChannelConfig {
+    let mut channel = channel_config();
+    channel.stale_nudge_days = Some(14);
+    channel
+}
+
+/// Pretend the issue's last nudge was posted `days` ago.
+async fn nudged_days_ago(pool: &SqlitePool, issue_id: &str, days: i64) {
+    sqlx::query(
+        "UPDATE stale_nudges SET nudged_at = datetime('now', '-' || ? || ' days')
+         WHERE linear_issue_id = ?",
+    )
+    .bind(days)
+    .bind(issue_id)
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn quiet_open_issues_are_nudged_once_per_period() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    let channel = nudging_config();
+    let config = config(vec![channel.clone()]);
+    let thread = discord.add_thread(FORUM_ID, "Crash on login", "It crashes when I log in");
+    sync_discord_to_linear(&discord, &pool, &channel, &linear, &thread)
+        .await
+        .unwrap();
+    let mapping = db::get_mapping_by_discord_thread(&pool, &thread.id.to_string())
+        .await
+        .unwrap()
+        .unwrap();
+    let issue_id = &mapping.linear_issue_id;
+
+    linear.set_last_update(issue_id, 3);
+    assert_eq!(
+        nudge_stale_issues(&discord, &pool, &linear, &config)
+            .await
+            .unwrap(),
+        0
+    );
+
+    linear.set_last_update(issue_id, 20);
+    assert_eq!(
+        nudge_stale_issues(&discord, &pool, &linear, &config)
+            .await
+            .unwrap(),
+        1
+    );
+    let nudge = discord
+        .sent(thread.id)
+        .last()
+        .unwrap()
+        .content()
+        .to_string();
+    assert_eq!(
+        nudge,
+        format!(
+            "Still tracked as **{}** (Triage), last update 20 days ago",
+            mapping.linear_identifier
+        )
+    );
+
+    // Not again until another period has passed.
+    assert_eq!(
+        nudge_stale_issues(&discord, &pool, &linear, &config)
+            .await
+            .unwrap(),
+        0
+    );
+    nudged_days_ago(&pool, issue_id, 15).await;
+    assert_eq!(
+        nudge_stale_issues(&discord, &pool, &linear, &config)
+            .await
+            .unwrap(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn closed_issues_are_not_nudged() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    let channel = nudging_config();
+    let config = config(vec![channel.clone()]);
+    let thread = discord.add_thread(FORUM_ID, "Crash on login", "It crashes when I log in");
+    sync_discord_to_linear(&discord, &pool, &channel, &linear, &thread)
+        .await
+        .unwrap();
+    let mapping = db::get_mapping_by_discord_thread(&pool, &thread.id.to_string())
+        .await
+        .unwrap()
+        .unwrap();
+
+    linear.set_status(&mapping.linear_issue_id, "Done");
+    linear.set_last_update(&mapping.linear_issue_id, 60);
+    assert_eq!(
+        nudge_stale_issues(&discord, &pool, &linear, &config)
+            .await
+            .unwrap(),
+        0
+    );
+}
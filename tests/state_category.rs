@@ -0,0 +1,110 @@
+//! Behavior keyed off the workflow state category rather than the state's name.
+
+mod common;
+
+use serde_json::json;
+use serenity::all::ForumTagId;
+
+use discord_linear_bot::config::ChannelConfig;
+use discord_linear_bot::db;
+use discord_linear_bot::linear::client::StateCategory;
+use discord_linear_bot::sync::discord_to_linear::sync_discord_to_linear;
+use discord_linear_bot::sync::linear_to_discord::sync_linear_to_discord;
+use discord_linear_bot::sync::thread::tags_for_state;
+
+use common::fake_discord::FakeDiscord;
+use common::mock_linear::MockLinear;
+use common::{channel_config, config, test_pool, FORUM_ID};
+
+/// Post a thread, then move its issue to `status` in Linear; returns the notice posted.
+async fn notice_for(channel: ChannelConfig, status: &str) -> String {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    let config = config(vec![channel]);
+    discord.add_forum(FORUM_ID);
+    let thread = discord.add_thread(FORUM_ID, "Crash on login", "It crashes");
+    sync_discord_to_linear(&discord, &pool, &config.channels[0], &linear, &thread)
+        .await
+        .unwrap();
+    let mapping = db::get_mapping_by_discord_thread(&pool, &thread.id.to_string())
+        .await
+        .unwrap()
+        .unwrap();
+
+    sync_linear_to_discord(
+        &discord,
+        &pool,
+        &config,
+        &linear,
+        &linear.move_to(&mapping.linear_issue_id, status),
+    )
+    .await
+    .unwrap();
+
+    discord
+        .sent(thread.id)
+        .last()
+        .unwrap()
+        .content()
+        .to_string()
+}
+
+#[tokio::test]
+async fn completing_an_issue_celebrates() {
+    let notice = notice_for(channel_config(), "Done").await;
+
+    assert!(notice.contains("🎉"));
+    assert!(notice.contains("MOCK-1"));
+    assert!(notice.contains("Done"));
+}
+
+#[tokio::test]
+async fn canceling_an_issue_is_a_plain_status_change() {
+    let notice = notice_for(channel_config(), "Canceled").await;
+
+    assert_eq!(notice, "**MOCK-1** status changed to **Canceled**");
+}
+
+#[tokio::test]
+async fn completed_notice_can_be_overridden() {
+    let mut channel = channel_config();
+    channel.message_templates.insert(
+        "completed".to_string(),
+        "{{identifier}} shipped as {{status}}".to_string(),
+    );
+
+    let notice = notice_for(channel, "Done").await;
+
+    assert_eq!(notice, "MOCK-1 shipped as Done");
+}
+
+#[test]
+fn state_tags_fall_back_to_the_category() {
+    let channel: ChannelConfig = serde_json::from_value(json!({
+        "discord_channel_id": FORUM_ID,
+        "guild_id": 1,
+        "channel_type": "bug",
+        "linear_team_id": "team",
+        "linear_label_id": "label",
+        "linear_project_id": "project",
+        "state_tag_map": {"In Review": "10", "started": "20", "completed": "30"},
+    }))
+    .unwrap();
+    let current = [ForumTagId::new(20), ForumTagId::new(99)];
+
+    let named = tags_for_state(&current, &channel, "In Review", StateCategory::Started);
+    assert_eq!(named, Some(vec![ForumTagId::new(99), ForumTagId::new(10)]));
+
+    let by_category = tags_for_state(&current, &channel, "Shipped", StateCategory::Completed);
+    assert_eq!(
+        by_category,
+        Some(vec![ForumTagId::new(99), ForumTagId::new(30)])
+    );
+
+    // Already tagged for its category: nothing to change.
+    assert_eq!(
+        tags_for_state(&current, &channel, "In Progress", StateCategory::Started),
+        None
+    );
+}
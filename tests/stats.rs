@@ -0,0 +1,164 @@
+//! The database aggregates `/linear stats` reports.
+
+mod common;
+
+use discord_linear_bot::db;
+
+use common::test_pool;
+
+#[tokio::test]
+async fn issues_are_counted_by_cached_status() {
+    let pool = test_pool().await;
+    for (n, status) in [
+        (1, Some("Todo")),
+        (2, Some("Done")),
+        (3, Some("Todo")),
+        (4, None),
+    ] {
+        db::create_mapping(
+            &pool,
+            &n.to_string(),
+            &format!("issue-{n}"),
+            &format!("ENG-{n}"),
+            "bug",
+            "1",
+        )
+        .await
+        .unwrap();
+        if let Some(status) = status {
+            db::upsert_cached_status(&pool, &format!("issue-{n}"), status)
+                .await
+                .unwrap();
+        }
+    }
+    // Cached statuses of issues that are no longer tracked don't count.
+    db::upsert_cached_status(&pool, "untracked", "Done")
+        .await
+        .unwrap();
+
+    assert_eq!(db::count_mappings(&pool, None).await.unwrap(), 4);
+    assert_eq!(
+        db::count_mappings_by_status(&pool, None).await.unwrap(),
+        vec![
+            (Some("Todo".to_string()), 2),
+            (None, 1),
+            (Some("Done".to_string()), 1),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn comments_are_counted_per_direction() {
+    let pool = test_pool().await;
+    for action in [
+        "comment_posted",
+        "comment_relayed",
+        "comment_relayed",
+        "issue_created",
+    ] {
+        db::insert_audit_entry(&pool, "1", "ENG-1", action, None, "")
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(
+        db::count_audit_actions(&pool, "comment_posted", None)
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        db::count_audit_actions(&pool, "comment_relayed", None)
+            .await
+            .unwrap(),
+        2
+    );
+}
+
+#[tokio::test]
+async fn failures_include_queued_and_dead_lettered_operations_in_the_window() {
+    let pool = test_pool().await;
+    db::insert_pending_operation(&pool, "a", "{}", "timeout")
+        .await
+        .unwrap();
+    db::insert_pending_operation(&pool, "b", "{}", "timeout")
+        .await
+        .unwrap();
+    db::insert_pending_operation(&pool, "old", "{}", "timeout")
+        .await
+        .unwrap();
+    sqlx::query(
+        "UPDATE pending_operations SET created_at = datetime('now', '-2 days')
+         WHERE dedupe_key = 'old'",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    let (id,): (i64,) = sqlx::query_as("SELECT id FROM pending_operations WHERE dedupe_key = 'b'")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    db::dead_letter_pending_operation(&pool, id, "gave up")
+        .await
+        .unwrap();
+
+    assert_eq!(
+        db::count_sync_failures_since(&pool, 24 * 60 * 60)
+            .await
+            .unwrap(),
+        2
+    );
+}
+
+#[tokio::test]
+async fn poll_cursors_are_listed_per_team() {
+    let pool = test_pool().await;
+    db::upsert_poll_cursor(&pool, "team-b", "2026-01-02T00:00:00Z")
+        .await
+        .unwrap();
+    db::upsert_poll_cursor(&pool, "team-a", "2026-01-01T00:00:00Z")
+        .await
+        .unwrap();
+
+    let cursors = db::get_poll_cursors(&pool).await.unwrap();
+    let teams: Vec<_> = cursors.iter().map(|c| c.linear_team_id.as_str()).collect();
+    assert_eq!(teams, ["team-a", "team-b"]);
+    assert_eq!(cursors[0].last_polled_at, "2026-01-01T00:00:00Z");
+}
+
+#[tokio::test]
+async fn counts_can_be_limited_to_one_server() {
+    let pool = test_pool().await;
+    for (n, guild) in [(1, "1"), (2, "1"), (3, "2")] {
+        db::create_mapping(
+            &pool,
+            &n.to_string(),
+            &format!("issue-{n}"),
+            &format!("ENG-{n}"),
+            "bug",
+            guild,
+        )
+        .await
+        .unwrap();
+        db::upsert_cached_status(&pool, &format!("issue-{n}"), "Todo")
+            .await
+            .unwrap();
+        db::insert_audit_entry(&pool, &n.to_string(), "ENG-1", "comment_posted", None, "")
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(db::count_mappings(&pool, Some("1")).await.unwrap(), 2);
+    assert_eq!(
+        db::count_mappings_by_status(&pool, Some("2"))
+            .await
+            .unwrap(),
+        vec![(Some("Todo".to_string()), 1)]
+    );
+    assert_eq!(
+        db::count_audit_actions(&pool, "comment_posted", Some("1"))
+            .await
+            .unwrap(),
+        2
+    );
+}
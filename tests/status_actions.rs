@@ -0,0 +1,182 @@
+//! Per-state `status_actions`: messages, role pings, tags, archiving, and locking.
+
+mod common;
+
+use serde_json::json;
+use serenity::all::{ChannelId, ForumTagId};
+use sqlx::SqlitePool;
+
+use discord_linear_bot::config::ChannelConfig;
+use discord_linear_bot::db;
+use discord_linear_bot::sync::discord_to_linear::sync_discord_to_linear;
+use discord_linear_bot::sync::linear_to_discord::sync_linear_to_discord;
+
+use common::fake_discord::{self, FakeDiscord};
+use common::mock_linear::MockLinear;
+use common::{config, test_pool, FORUM_ID, TEAM_ID};
+
+struct Tracked {
+    pool: SqlitePool,
+    linear: MockLinear,
+    discord: FakeDiscord,
+    thread_id: ChannelId,
+    issue_id: String,
+}
+
+/// Post a thread in a forum configured like `common::channel_config()`, with `extra` merged in.
+async fn tracked(extra: serde_json::Value) -> (Tracked, ChannelConfig) {
+    let mut value = json!({
+        "discord_channel_id": FORUM_ID,
+        "guild_id": fake_discord::GUILD_ID,
+        "channel_type": "bug",
+        "linear_team_id": TEAM_ID,
+        "linear_label_id": "label-bug",
+        "linear_project_id": "project-1",
+    });
+    value
+        .as_object_mut()
+        .unwrap()
+        .extend(extra.as_object().unwrap().clone());
+    let channel: ChannelConfig = serde_json::from_value(value).unwrap();
+
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    let thread = discord.add_thread(FORUM_ID, "Crash on login", "It crashes");
+    sync_discord_to_linear(&discord, &pool, &channel, &linear, &thread)
+        .await
+        .unwrap();
+    let mapping = db::get_mapping_by_discord_thread(&pool, &thread.id.to_string())
+        .await
+        .unwrap()
+        .unwrap();
+
+    let tracked = Tracked {
+        pool,
+        linear,
+        discord,
+        thread_id: thread.id,
+        issue_id: mapping.linear_issue_id,
+    };
+    (tracked, channel)
+}
+
+impl Tracked {
+    async fn move_to(&self, channel: &ChannelConfig, status: &str) {
+        let config = config(vec![channel.clone()]);
+        let issue = self.linear.move_to(&self.issue_id, status);
+        sync_linear_to_discord(&self.discord, &self.pool, &config, &self.linear, &issue)
+            .await
+            .unwrap();
+    }
+
+    fn last_notice(&self) -> String {
+        let sent = self.discord.sent(self.thread_id);
+        sent.last().unwrap().content().to_string()
+    }
+}
+
+#[tokio::test]
+async fn state_name_action_posts_its_message_and_pings() {
+    let (t, channel) = tracked(json!({
+        "status_actions": {
+            "In Progress": {
+                "message": "{{identifier}} is being worked on ({{status_type}})",
+                "ping_role_id": 555,
+            },
+        },
+    }))
+    .await;
+
+    t.move_to(&channel, "In Progress").await;
+
+    assert_eq!(
+        t.last_notice(),
+        "<@&555> MOCK-1 is being worked on (started)"
+    );
+    assert!(!t.discord.is_archived(t.thread_id));
+}
+
+#[tokio::test]
+async fn category_action_overrides_archiving_and_locks() {
+    let (t, channel) = tracked(json!({
+        "status_actions": {
+            "canceled": {"archive": false, "lock": true},
+        },
+    }))
+    .await;
+
+    t.move_to(&channel, "Canceled").await;
+
+    assert!(!t.discord.is_archived(t.thread_id));
+    assert!(t.discord.is_locked(t.thread_id));
+    assert!(t.last_notice().contains("Canceled"));
+
+    // States without an action keep the channel's behavior.
+    t.move_to(&channel, "Done").await;
+    assert!(t.discord.is_archived(t.thread_id));
+}
+
+#[tokio::test]
+async fn action_archives_a_state_that_isnt_closed() {
+    let (t, channel) = tracked(json!({
+        "archive_on_close": false,
+        "status_actions": {
+            "Backlog": {"archive": true},
+        },
+    }))
+    .await;
+
+    t.move_to(&channel, "Done").await;
+    assert!(!t.discord.is_archived(t.thread_id));
+
+    t.move_to(&channel, "Backlog").await;
+    assert!(t.discord.is_archived(t.thread_id));
+    assert!(!t.discord.is_locked(t.thread_id));
+}
+
+#[tokio::test]
+async fn action_tags_replace_state_tags() {
+    let (t, channel) = tracked(json!({
+        "state_tag_map": {"started": "10", "completed": "20"},
+        "status_actions": {
+            "Done": {"tag_id": 30},
+        },
+    }))
+    .await;
+
+    t.move_to(&channel, "In Progress").await;
+    assert_eq!(
+        t.discord.thread(t.thread_id).applied_tags,
+        vec![ForumTagId::new(10)]
+    );
+
+    t.move_to(&channel, "Done").await;
+    assert_eq!(
+        t.discord.thread(t.thread_id).applied_tags,
+        vec![ForumTagId::new(30)]
+    );
+}
+
+#[tokio::test]
+async fn pings_are_posted_alongside_the_status_embed() {
+    let (t, channel) = tracked(json!({
+        "status_embed": true,
+        "status_actions": {
+            "completed": {"ping_role_id": 777},
+        },
+    }))
+    .await;
+    let before = t.discord.sent(t.thread_id).len();
+
+    t.move_to(&channel, "In Progress").await;
+    let after_progress = t.discord.sent(t.thread_id).len();
+    t.move_to(&channel, "Done").await;
+
+    // The state without an action only updated the status embed.
+    assert_eq!(after_progress, before);
+    assert_eq!(t.discord.sent(t.thread_id).len(), before + 1);
+    assert!(t.last_notice().starts_with("<@&777> "));
+    assert!(t.last_notice().contains("🎉"));
+}
@@ -0,0 +1,92 @@
+//! DMs to members subscribed to a thread's issue.
+
+mod common;
+
+use discord_linear_bot::db;
+use discord_linear_bot::sync::discord_to_linear::sync_discord_to_linear;
+use discord_linear_bot::sync::linear_to_discord::{
+    sync_linear_comments_to_discord, sync_linear_to_discord,
+};
+
+use common::fake_discord::FakeDiscord;
+use common::mock_linear::MockLinear;
+use common::{channel_config, config, test_pool, user, FORUM_ID};
+
+const SUBSCRIBER: u64 = 4200;
+const NO_DMS: u64 = 4201;
+
+#[tokio::test]
+async fn subscribers_are_told_about_status_changes_and_comments() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    let config = config(vec![channel_config()]);
+    let thread = discord.add_thread(FORUM_ID, "Crash on login", "It crashes when I log in");
+    let thread_id = thread.id.to_string();
+    sync_discord_to_linear(&discord, &pool, &channel_config(), &linear, &thread)
+        .await
+        .unwrap();
+    let mapping = db::get_mapping_by_discord_thread(&pool, &thread_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let issue_id = &mapping.linear_issue_id;
+    let identifier = &mapping.linear_identifier;
+
+    // The member who doesn't accept DMs subscribed first; the other still hears about changes.
+    discord.close_dms(NO_DMS);
+    for member in [NO_DMS, SUBSCRIBER] {
+        assert!(
+            db::insert_subscription(&pool, &thread_id, &member.to_string())
+                .await
+                .unwrap()
+        );
+    }
+    assert!(
+        !db::insert_subscription(&pool, &thread_id, &SUBSCRIBER.to_string())
+            .await
+            .unwrap()
+    );
+
+    sync_linear_to_discord(
+        &discord,
+        &pool,
+        &config,
+        &linear,
+        &linear.move_to(issue_id, "In Progress"),
+    )
+    .await
+    .unwrap();
+    linear.add_comment(issue_id, &user("u1", "Alice"), "Found the cause");
+    sync_linear_comments_to_discord(&discord, &pool, &config, &linear, issue_id, identifier)
+        .await
+        .unwrap();
+
+    let dms = discord.dms(SUBSCRIBER);
+    assert_eq!(dms.len(), 2);
+    assert_eq!(
+        dms[0],
+        format!("**{identifier}** moved to **In Progress** (<#{thread_id}>).")
+    );
+    assert!(dms[1].starts_with(&format!("New comment on **{identifier}** from Alice")));
+    assert!(dms[1].ends_with("Found the cause"));
+    assert!(discord.dms(NO_DMS).is_empty());
+
+    // Unsubscribed members hear nothing more.
+    assert!(
+        db::delete_subscription(&pool, &thread_id, &SUBSCRIBER.to_string())
+            .await
+            .unwrap()
+    );
+    sync_linear_to_discord(
+        &discord,
+        &pool,
+        &config,
+        &linear,
+        &linear.move_to(issue_id, "Done"),
+    )
+    .await
+    .unwrap();
+    assert_eq!(discord.dms(SUBSCRIBER).len(), 2);
+}
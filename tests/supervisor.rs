@@ -0,0 +1,64 @@
+//! Restarting background tasks that die, and alerting the ops channel about it.
+
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serenity::all::ChannelId;
+
+use common::fake_discord::FakeDiscord;
+use discord_linear_bot::shutdown::Shutdown;
+use discord_linear_bot::supervisor::supervise;
+
+const OPS_CHANNEL_ID: u64 = 900;
+
+#[tokio::test]
+async fn a_panicking_task_is_restarted_and_alerted() {
+    let discord = FakeDiscord::new();
+    discord.add_text_channel(OPS_CHANNEL_ID);
+    let shutdown = Shutdown::new();
+    let runs = Arc::new(AtomicUsize::new(0));
+
+    let start = || {
+        let runs = runs.clone();
+        let shutdown = shutdown.clone();
+        async move {
+            if runs.fetch_add(1, Ordering::SeqCst) == 0 {
+                panic!("poll cursor table missing");
+            }
+            // The restarted task ends the run as a shutdown would; that isn't restarted.
+            shutdown.request();
+        }
+    };
+    tokio::time::timeout(
+        Duration::from_secs(10),
+        supervise("poller", &discord, Some(OPS_CHANNEL_ID), &shutdown, start),
+    )
+    .await
+    .expect("supervisor returns on shutdown");
+
+    assert_eq!(runs.load(Ordering::SeqCst), 2);
+    let alerts = discord.sent(ChannelId::new(OPS_CHANNEL_ID));
+    assert_eq!(alerts.len(), 1);
+    assert!(alerts[0].content().contains("poller"));
+    assert!(alerts[0].content().contains("poll cursor table missing"));
+}
+
+#[tokio::test]
+async fn shutdown_during_the_restart_delay_stops_the_supervisor() {
+    let discord = FakeDiscord::new();
+    let shutdown = Shutdown::new();
+
+    let supervisor = supervise("poller", &discord, None, &shutdown, || async {});
+    let stop = async {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        shutdown.request();
+    };
+    tokio::time::timeout(Duration::from_secs(5), async {
+        tokio::join!(supervisor, stop)
+    })
+    .await
+    .expect("supervisor returns instead of restarting");
+}
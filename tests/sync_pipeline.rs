@@ -0,0 +1,448 @@
+//! The full sync pipeline against real infrastructure where it's cheap: a real `LinearClient`
+//! talking to a wiremock GraphQL server, real migrations on in-memory SQLite, and `FakeDiscord`
+//! in place of the gateway. Each test walks a thread through several sync steps in order.
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use serenity::all::{GuildChannel, Message, MessageId, User, UserId};
+use sqlx::SqlitePool;
+
+use discord_linear_bot::config::Config;
+use discord_linear_bot::db;
+use discord_linear_bot::error::AppError;
+use discord_linear_bot::linear::api::LinearApi;
+use discord_linear_bot::linear::client::LinearClient;
+use discord_linear_bot::sync::discord_to_linear::{sync_discord_to_linear, sync_reply_to_linear};
+use discord_linear_bot::sync::linear_to_discord::{
+    sync_linear_comments_to_discord, sync_linear_to_discord,
+};
+use discord_linear_bot::sync::outbox::{self, process_outbox, Operation};
+
+use common::fake_discord::FakeDiscord;
+use common::linear_server::LinearServer;
+use common::mock_linear::now;
+use common::{channel_config, config, test_pool, user, FORUM_ID, TEAM_ID};
+
+struct Pipeline {
+    pool: SqlitePool,
+    server: LinearServer,
+    linear: LinearClient,
+    discord: FakeDiscord,
+    config: Config,
+}
+
+impl Pipeline {
+    async fn new(config: Config) -> Self {
+        let server = LinearServer::start().await;
+        let discord = FakeDiscord::new();
+        discord.add_forum(FORUM_ID);
+        Self {
+            pool: test_pool().await,
+            linear: server.client(),
+            server,
+            discord,
+            config,
+        }
+    }
+
+    /// Post a forum thread and run it through issue creation. Returns the thread and the ID and
+    /// identifier of its Linear issue.
+    async fn open_thread(&self, name: &str, content: &str) -> (GuildChannel, String, String) {
+        let thread = self.discord.add_thread(FORUM_ID, name, content);
+        sync_discord_to_linear(
+            &self.discord,
+            &self.pool,
+            &self.config.channels[0],
+            &self.linear,
+            &thread,
+        )
+        .await
+        .unwrap();
+        let mapping = db::get_mapping_by_discord_thread(&self.pool, &thread.id.to_string())
+            .await
+            .unwrap()
+            .expect("thread is mapped");
+        (thread, mapping.linear_issue_id, mapping.linear_identifier)
+    }
+
+    async fn sync_comments(&self, issue_id: &str, identifier: &str) {
+        sync_linear_comments_to_discord(
+            &self.discord,
+            &self.pool,
+            &self.config,
+            &self.linear,
+            issue_id,
+            identifier,
+        )
+        .await
+        .unwrap();
+    }
+}
+
+/// A reply from a person (not the bot) in `thread`.
+fn reply(thread: &GuildChannel, id: u64, content: &str) -> Message {
+    let mut msg = Message::default();
+    msg.id = MessageId::new(id);
+    msg.channel_id = thread.id;
+    msg.guild_id = Some(thread.guild_id);
+    msg.content = content.to_string();
+    msg.author = User::default();
+    msg.author.id = UserId::new(42);
+    msg.author.name = "reporter".to_string();
+    msg
+}
+
+#[tokio::test]
+async fn thread_becomes_issue_and_follows_its_status() {
+    let p = Pipeline::new(config(vec![channel_config()])).await;
+
+    let (thread, issue_id, identifier) = p
+        .open_thread("Crash on login", "It crashes when I log in")
+        .await;
+
+    assert_eq!(p.server.operations(), ["CreateIssue", "AttachmentLinkUrl"]);
+    let input = &p.server.variables("CreateIssue")[0]["input"];
+    assert_eq!(input["teamId"], TEAM_ID);
+    assert_eq!(input["title"], "Crash on login");
+    assert_eq!(input["labelIds"][0], "label-bug");
+    assert_eq!(input["projectId"], "project-1");
+    let issue = p.server.issue(&issue_id).unwrap();
+    assert!(issue.description.starts_with("It crashes when I log in"));
+    assert_eq!(identifier, issue.identifier);
+    let sent = p.discord.sent(thread.id);
+    assert_eq!(sent.len(), 1);
+    assert!(sent[0].content().contains(&identifier));
+
+    // One poller pass: fetch what changed since the last poll and sync its status.
+    let since = now();
+    p.server.set_status(&issue_id, "Done");
+    let updated = p.linear.get_updated_issues(TEAM_ID, &since).await.unwrap();
+    assert_eq!(updated.len(), 1);
+    assert_eq!(updated[0].status_type, "completed");
+    sync_linear_to_discord(
+        &p.discord,
+        &p.pool,
+        &p.config,
+        &p.linear,
+        &updated[0].detail(),
+    )
+    .await
+    .unwrap();
+
+    let notice = p
+        .discord
+        .sent(thread.id)
+        .last()
+        .unwrap()
+        .content()
+        .to_string();
+    assert!(notice.contains(&identifier));
+    assert!(notice.contains("Done"));
+    assert!(p.discord.is_archived(thread.id));
+    assert_eq!(
+        db::get_cached_status(&p.pool, &issue_id)
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("Done")
+    );
+
+    // Nothing changed since: the next pass sees no updates.
+    let since = now();
+    assert!(p
+        .linear
+        .get_updated_issues(TEAM_ID, &since)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn transient_linear_error_during_issue_creation_is_retried() {
+    let p = Pipeline::new(config(vec![channel_config()])).await;
+    p.server.fail_next(503, 1).await;
+
+    let (thread, _, identifier) = p.open_thread("Crash on login", "It crashes").await;
+
+    // The 503 never reached the model, so exactly one issue was created.
+    assert_eq!(p.server.operations(), ["CreateIssue", "AttachmentLinkUrl"]);
+    assert!(p.discord.sent(thread.id)[0].content().contains(&identifier));
+}
+
+#[tokio::test]
+async fn requests_wait_for_reset_once_rate_limit_is_spent() {
+    let p = Pipeline::new(config(vec![channel_config()])).await;
+    let reset_in = Duration::from_millis(800);
+    let reset_at = Utc::now().timestamp_millis() + reset_in.as_millis() as i64;
+    p.server.set_rate_limit(0, reset_at);
+
+    let since = now();
+    p.linear.get_updated_issues(TEAM_ID, &since).await.unwrap();
+    let started = Instant::now();
+    p.linear.get_updated_issues(TEAM_ID, &since).await.unwrap();
+
+    // Allow for the first request's round trip, which ate into the window.
+    assert!(started.elapsed() >= reset_in / 2);
+    assert_eq!(p.server.operations().len(), 2);
+}
+
+#[tokio::test]
+async fn repeated_linear_outages_open_the_breaker() {
+    let p = Pipeline::new(config(vec![channel_config()])).await;
+    let linear = p
+        .server
+        .client()
+        .with_max_attempts(1)
+        .with_circuit_breaker(2, Duration::from_secs(60));
+    p.server.fail_next(503, 2).await;
+
+    let since = now();
+    for _ in 0..2 {
+        assert!(linear.get_updated_issues(TEAM_ID, &since).await.is_err());
+    }
+    assert!(!linear.is_available());
+
+    // Refused without a request reaching Linear.
+    let err = linear
+        .get_updated_issues(TEAM_ID, &since)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, AppError::Unavailable("Linear")));
+    assert_eq!(p.server.request_count().await, 2);
+}
+
+#[tokio::test]
+async fn failed_issue_creation_is_retried_from_the_queue() {
+    let p = Pipeline::new(config(vec![channel_config()])).await;
+    let thread = p
+        .discord
+        .add_thread(FORUM_ID, "Crash on login", "It crashes");
+    p.server.fail_next(400, 1).await;
+
+    // What the thread_create handler does.
+    let err = sync_discord_to_linear(
+        &p.discord,
+        &p.pool,
+        &p.config.channels[0],
+        &p.linear,
+        &thread,
+    )
+    .await
+    .unwrap_err();
+    let operation = Operation::CreateIssue {
+        thread_id: thread.id.get(),
+    };
+    outbox::enqueue(&p.pool, &operation, &err).await;
+
+    process_outbox(&p.discord, &p.pool, &p.config, &p.linear)
+        .await
+        .unwrap();
+
+    let mapping = db::get_mapping_by_discord_thread(&p.pool, &thread.id.to_string())
+        .await
+        .unwrap()
+        .expect("thread is mapped on retry");
+    assert!(p.discord.sent(thread.id)[0]
+        .content()
+        .contains(&mapping.linear_identifier));
+    assert!(db::get_due_pending_operations(&p.pool)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn failed_reply_is_queued_and_posted_on_the_next_pass() {
+    let mut channel = channel_config();
+    channel.sync_replies = true;
+    let p = Pipeline::new(config(vec![channel])).await;
+    let (thread, issue_id, _) = p.open_thread("Crash on login", "It crashes").await;
+    p.server.fail_next(400, 1).await;
+
+    let msg = reply(&thread, 5_000_000, "Happens on Android too");
+    assert!(
+        sync_reply_to_linear(&p.discord, &p.pool, &p.config, &p.linear, &msg)
+            .await
+            .is_err()
+    );
+    assert!(p.server.comments(&issue_id).is_empty());
+
+    process_outbox(&p.discord, &p.pool, &p.config, &p.linear)
+        .await
+        .unwrap();
+
+    let comments = p.server.comments(&issue_id);
+    assert_eq!(comments.len(), 1);
+    assert!(comments[0].body.contains("Happens on Android too"));
+    assert!(db::is_comment_synced(&p.pool, &comments[0].id)
+        .await
+        .unwrap());
+}
+
+#[tokio::test]
+async fn comments_sync_in_both_directions_without_echo() {
+    let mut channel = channel_config();
+    channel.sync_replies = true;
+    let p = Pipeline::new(config(vec![channel])).await;
+    let (thread, issue_id, identifier) = p.open_thread("Crash on login", "It crashes").await;
+
+    // Linear → Discord.
+    let alice = user("user-alice", "Alice");
+    p.server.add_comment(&issue_id, &alice, "Looking into it");
+    p.sync_comments(&issue_id, &identifier).await;
+
+    let sent = p.discord.sent(thread.id);
+    assert_eq!(sent.len(), 2);
+    assert!(sent[1].content().contains("Alice"));
+    assert!(sent[1].content().contains("Looking into it"));
+
+    // A second pass asks only for newer comments and posts nothing.
+    p.sync_comments(&issue_id, &identifier).await;
+    assert_eq!(p.discord.sent(thread.id).len(), 2);
+    let requests = p.server.variables("IssueComments");
+    assert!(requests[0]["filter"].is_null());
+    assert!(requests[1]["filter"]["createdAt"]["gt"].is_string());
+
+    // Discord → Linear.
+    let msg = reply(&thread, 5_000_000, "Thanks, it happens on Android too");
+    sync_reply_to_linear(&p.discord, &p.pool, &p.config, &p.linear, &msg)
+        .await
+        .unwrap();
+
+    let comments = p.server.comments(&issue_id);
+    assert_eq!(comments.len(), 2);
+    assert!(comments[1].body.contains("reporter"));
+    assert!(p.server.variables("CreateComment")[0]["input"]["createAsUser"].is_null());
+    assert!(comments[1].body.contains("it happens on Android too"));
+
+    // The mirrored reply shows up in the next poll but isn't relayed back into the thread.
+    p.sync_comments(&issue_id, &identifier).await;
+    assert_eq!(p.discord.sent(thread.id).len(), 2);
+}
+
+#[tokio::test]
+async fn replies_are_posted_as_their_author_when_enabled() {
+    let mut channel = channel_config();
+    channel.sync_replies = true;
+    let mut config = config(vec![channel]);
+    config.linear_comment_as_author = true;
+    let p = Pipeline::new(config).await;
+    let (thread, _, _) = p.open_thread("Crash on login", "It crashes").await;
+
+    let msg = reply(&thread, 5_000_000, "Happens on Android too");
+    sync_reply_to_linear(&p.discord, &p.pool, &p.config, &p.linear, &msg)
+        .await
+        .unwrap();
+    // Users who ran `/linear connect` are named by their Linear account.
+    db::upsert_user_link(&p.pool, "42", "user-alice", "Alice", "alice@example.com")
+        .await
+        .unwrap();
+    let msg = reply(&thread, 5_000_001, "And on iOS");
+    sync_reply_to_linear(&p.discord, &p.pool, &p.config, &p.linear, &msg)
+        .await
+        .unwrap();
+
+    let inputs: Vec<_> = p
+        .server
+        .variables("CreateComment")
+        .into_iter()
+        .map(|v| v["input"].clone())
+        .collect();
+    assert_eq!(inputs[0]["createAsUser"], "reporter");
+    assert_eq!(inputs[1]["createAsUser"], "Alice");
+    assert!(inputs[1]["displayIconUrl"]
+        .as_str()
+        .is_some_and(|url| url.starts_with("https://cdn.discordapp.com/")));
+}
+
+#[tokio::test]
+async fn comment_webhook_is_created_once_and_posts_as_the_author() {
+    let mut channel = channel_config();
+    channel.comment_webhooks = true;
+    let p = Pipeline::new(config(vec![channel])).await;
+    let (thread, issue_id, identifier) = p.open_thread("Crash on login", "It crashes").await;
+
+    let alice = user("user-alice", "Alice");
+    p.server.add_comment(&issue_id, &alice, "First");
+    p.sync_comments(&issue_id, &identifier).await;
+    p.server.add_comment(&issue_id, &alice, "Second");
+    p.sync_comments(&issue_id, &identifier).await;
+
+    let webhooks = p.discord.webhooks_in(FORUM_ID.into());
+    assert_eq!(webhooks.len(), 1);
+    assert_eq!(webhooks[0].name.as_deref(), Some("Linear"));
+
+    let relayed = &p.discord.sent(thread.id)[1..];
+    assert_eq!(relayed.len(), 2);
+    for (sent, body) in relayed.iter().zip(["First", "Second"]) {
+        assert_eq!(sent.content(), body);
+        assert_eq!(sent.body["username"], "Alice (Linear)");
+    }
+}
+
+#[tokio::test]
+async fn operation_out_of_retries_is_dead_lettered_and_requeued_on_request() {
+    const OPS_CHANNEL_ID: u64 = 4000;
+    let mut config = config(vec![channel_config()]);
+    config.outbox_max_attempts = 2;
+    config.ops_channel_id = Some(OPS_CHANNEL_ID);
+    let p = Pipeline::new(config).await;
+    p.discord.add_text_channel(OPS_CHANNEL_ID);
+    let thread = p
+        .discord
+        .add_thread(FORUM_ID, "Crash on login", "It crashes");
+    let operation = Operation::CreateIssue {
+        thread_id: thread.id.get(),
+    };
+    outbox::enqueue(&p.pool, &operation, &AppError::Internal("boom".into())).await;
+
+    p.server.fail_next(400, 2).await;
+    process_outbox(&p.discord, &p.pool, &p.config, &p.linear)
+        .await
+        .unwrap();
+    assert!(p.discord.sent(OPS_CHANNEL_ID.into()).is_empty());
+    // Backed off after the first failure; make it due again.
+    sqlx::query("UPDATE pending_operations SET next_attempt_at = datetime('now')")
+        .execute(&p.pool)
+        .await
+        .unwrap();
+    process_outbox(&p.discord, &p.pool, &p.config, &p.linear)
+        .await
+        .unwrap();
+
+    let alerts = p.discord.sent(OPS_CHANNEL_ID.into());
+    assert_eq!(alerts.len(), 1);
+    assert!(alerts[0]
+        .content()
+        .contains(&format!("create_issue:{}", thread.id)));
+    assert!(alerts[0].content().contains("/linear retry-failed"));
+    let (attempts, last_error): (i64, String) =
+        sqlx::query_as("SELECT attempts, last_error FROM dead_letters")
+            .fetch_one(&p.pool)
+            .await
+            .unwrap();
+    assert_eq!(attempts, 2);
+    assert!(last_error.contains("400"));
+    assert!(db::get_due_pending_operations(&p.pool)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // Linear is back; an admin requeues and the next pass files the issue.
+    assert_eq!(db::requeue_dead_letters(&p.pool).await.unwrap(), 1);
+    assert_eq!(db::requeue_dead_letters(&p.pool).await.unwrap(), 0);
+    process_outbox(&p.discord, &p.pool, &p.config, &p.linear)
+        .await
+        .unwrap();
+
+    assert!(
+        db::get_mapping_by_discord_thread(&p.pool, &thread.id.to_string())
+            .await
+            .unwrap()
+            .is_some()
+    );
+    assert_eq!(p.discord.sent(OPS_CHANNEL_ID.into()).len(), 1);
+}
@@ -0,0 +1,63 @@
+//! Priority and estimate set on new issues from the post's forum tags.
+
+mod common;
+
+use serenity::all::{EditThread, ForumTagId};
+
+use discord_linear_bot::config::ChannelConfig;
+use discord_linear_bot::db;
+use discord_linear_bot::discord::port::DiscordPort;
+use discord_linear_bot::sync::discord_to_linear::sync_discord_to_linear;
+
+use common::fake_discord::FakeDiscord;
+use common::mock_linear::MockLinear;
+use common::{channel_config, test_pool, FORUM_ID};
+
+const CRASH_TAG: u64 = 901;
+const MINOR_TAG: u64 = 902;
+
+fn tagged_config() -> ChannelConfig {
+    let mut channel = channel_config();
+    channel.tag_priority_map.insert(CRASH_TAG.to_string(), 1);
+    channel.tag_priority_map.insert(MINOR_TAG.to_string(), 4);
+    channel.tag_estimate_map.insert(CRASH_TAG.to_string(), 5);
+    channel.tag_estimate_map.insert(MINOR_TAG.to_string(), 1);
+    channel
+}
+
+/// File a post with `tags` applied and return its issue's (priority, estimate).
+async fn file_with_tags(tags: &[u64]) -> (i64, Option<i64>) {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    let thread = discord.add_thread(FORUM_ID, "Crash on login", "It crashes when I log in");
+    let applied: Vec<_> = tags.iter().map(|id| ForumTagId::new(*id)).collect();
+    discord
+        .edit_thread(thread.id, EditThread::new().applied_tags(applied))
+        .await
+        .unwrap();
+    let thread = discord.thread(thread.id);
+
+    sync_discord_to_linear(&discord, &pool, &tagged_config(), &linear, &thread)
+        .await
+        .unwrap();
+
+    let mapping = db::get_mapping_by_discord_thread(&pool, &thread.id.to_string())
+        .await
+        .unwrap()
+        .unwrap();
+    let issue = linear.issue(&mapping.linear_issue_id).unwrap();
+    (issue.priority, issue.estimate)
+}
+
+#[tokio::test]
+async fn most_urgent_priority_and_largest_estimate_win() {
+    assert_eq!(file_with_tags(&[MINOR_TAG, CRASH_TAG]).await, (1, Some(5)));
+    assert_eq!(file_with_tags(&[MINOR_TAG]).await, (4, Some(1)));
+}
+
+#[tokio::test]
+async fn untagged_posts_keep_the_defaults() {
+    assert_eq!(file_with_tags(&[]).await, (0, None));
+}
@@ -0,0 +1,90 @@
+//! Exporting spans to an OTLP collector, here a wiremock server.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+use tracing::{error, info_span};
+use tracing_subscriber::layer::SubscriberExt;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use discord_linear_bot::telemetry::{self, OtlpSettings};
+
+fn attribute<'a>(attributes: &'a Value, key: &str) -> &'a Value {
+    attributes
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|a| a["key"] == key)
+        .map(|a| &a["value"])
+        .unwrap_or_else(|| panic!("no {key} attribute"))
+}
+
+fn span<'a>(spans: &'a [Value], name: &str) -> &'a Value {
+    spans
+        .iter()
+        .find(|s| s["name"] == name)
+        .unwrap_or_else(|| panic!("no {name} span"))
+}
+
+#[tokio::test]
+async fn nested_spans_are_exported_as_one_trace() {
+    let collector = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/traces"))
+        .and(header("x-api-key", "secret"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&collector)
+        .await;
+    let (layer, exporter) = telemetry::init(OtlpSettings {
+        endpoint: format!("{}/v1/traces", collector.uri()),
+        headers: HashMap::from([("x-api-key".to_string(), "secret".to_string())]),
+        service_name: "bot-test".to_string(),
+    })
+    .unwrap();
+
+    {
+        let _subscriber =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(layer));
+        let root =
+            info_span!(target: "discord_linear_bot::discord", "thread_create", thread_id = 42u64);
+        let _root = root.enter();
+        let request = info_span!(target: "discord_linear_bot::linear", "linear_graphql");
+        request.in_scope(|| error!(target: "discord_linear_bot::linear", "Linear request failed"));
+        drop(request);
+        // Other crates' spans aren't exported; spans beneath them still join the trace.
+        info_span!(target: "hyper", "connect").in_scope(|| {
+            info_span!(target: "discord_linear_bot::sync", "store_mapping").in_scope(|| {});
+        });
+    }
+    exporter.shutdown().await;
+
+    let requests = collector.received_requests().await.unwrap();
+    let body: Value = requests[0].body_json().unwrap();
+    let resource = &body["resourceSpans"][0];
+    assert_eq!(
+        attribute(&resource["resource"]["attributes"], "service.name")["stringValue"],
+        "bot-test"
+    );
+    let spans = resource["scopeSpans"][0]["spans"].as_array().unwrap();
+    assert_eq!(spans.len(), 3);
+
+    let root = span(spans, "thread_create");
+    assert_eq!(root["parentSpanId"], "");
+    assert_eq!(
+        attribute(&root["attributes"], "thread_id")["stringValue"],
+        "42"
+    );
+    for name in ["linear_graphql", "store_mapping"] {
+        let child = span(spans, name);
+        assert_eq!(child["traceId"], root["traceId"]);
+        assert_eq!(child["parentSpanId"], root["spanId"]);
+    }
+
+    let request = span(spans, "linear_graphql");
+    assert_eq!(request["status"]["code"], 2);
+    assert_eq!(request["events"][0]["name"], "Linear request failed");
+    // STATUS_CODE_UNSET
+    assert_eq!(root["status"]["code"], 0);
+}
@@ -0,0 +1,49 @@
+//! Deleting a mapped thread retires its mapping even when canceling the issue fails.
+
+mod common;
+
+use serenity::all::ChannelId;
+
+use discord_linear_bot::config::ThreadDeleteAction;
+use discord_linear_bot::db;
+use discord_linear_bot::sync::discord_to_linear::{sync_discord_to_linear, sync_thread_delete};
+
+use common::fake_discord::FakeDiscord;
+use common::mock_linear::MockLinear;
+use common::{channel_config, config, test_pool, FORUM_ID};
+
+#[tokio::test]
+async fn failed_cancel_still_tombstones_the_mapping() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    let mut channel = channel_config();
+    channel.on_thread_delete = ThreadDeleteAction::Cancel;
+    discord.add_forum(FORUM_ID);
+    let thread = discord.add_thread(FORUM_ID, "Crash on login", "It crashes");
+    sync_discord_to_linear(&discord, &pool, &channel, &linear, &thread)
+        .await
+        .unwrap();
+    let issue_id = db::get_mapping_by_discord_thread(&pool, &thread.id.to_string())
+        .await
+        .unwrap()
+        .unwrap()
+        .linear_issue_id;
+
+    linear.fail("update_issue_state");
+    let config = config(vec![channel]);
+    for _ in 0..2 {
+        sync_thread_delete(&pool, &config, &linear, thread.id, ChannelId::new(FORUM_ID))
+            .await
+            .unwrap();
+    }
+
+    assert!(
+        db::get_mapping_by_discord_thread(&pool, &thread.id.to_string())
+            .await
+            .unwrap()
+            .is_none()
+    );
+    // The second delete event found no mapping, so the note was posted once.
+    assert_eq!(linear.comments(&issue_id).len(), 1);
+}
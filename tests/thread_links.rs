@@ -0,0 +1,60 @@
+//! Discord threads attached to their issues as links.
+
+mod common;
+
+use discord_linear_bot::db;
+use discord_linear_bot::sync::discord_to_linear::sync_discord_to_linear;
+
+use common::fake_discord::{FakeDiscord, GUILD_ID};
+use common::mock_linear::MockLinear;
+use common::{channel_config, test_pool, FORUM_ID};
+
+#[tokio::test]
+async fn new_issue_links_its_thread() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    let thread = discord.add_thread(FORUM_ID, "Crash on login", "It crashes when I log in");
+
+    sync_discord_to_linear(&discord, &pool, &channel_config(), &linear, &thread)
+        .await
+        .unwrap();
+
+    let mapping = db::get_mapping_by_discord_thread(&pool, &thread.id.to_string())
+        .await
+        .unwrap()
+        .unwrap();
+    let issue = linear.issue(&mapping.linear_issue_id).unwrap();
+    let url = format!(
+        "https://discord.com/channels/{GUILD_ID}/{FORUM_ID}/{}",
+        thread.id
+    );
+    assert_eq!(issue.links, std::slice::from_ref(&url));
+    assert!(issue.description.contains(&url));
+}
+
+#[tokio::test]
+async fn failed_link_still_files_the_issue() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    linear.fail("link_url");
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    let thread = discord.add_thread(FORUM_ID, "Crash on login", "It crashes when I log in");
+
+    sync_discord_to_linear(&discord, &pool, &channel_config(), &linear, &thread)
+        .await
+        .unwrap();
+
+    let mapping = db::get_mapping_by_discord_thread(&pool, &thread.id.to_string())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(linear
+        .issue(&mapping.linear_issue_id)
+        .unwrap()
+        .links
+        .is_empty());
+    assert_eq!(discord.sent(thread.id).len(), 1);
+}
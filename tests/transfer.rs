@@ -0,0 +1,166 @@
+//! Moving mappings and their sync state between databases with export-mappings/import-mappings.
+
+mod common;
+
+use discord_linear_bot::db;
+use discord_linear_bot::transfer::{self, Format, ImportSummary};
+use sqlx::SqlitePool;
+
+use common::test_pool;
+
+/// A database with one mapping whose status and comments have been synced.
+async fn populated_pool() -> SqlitePool {
+    let pool = test_pool().await;
+    db::create_mapping(&pool, "100", "issue-1", "ENG-1", "bug", "1")
+        .await
+        .unwrap();
+    db::upsert_cached_status(&pool, "issue-1", "In Progress")
+        .await
+        .unwrap();
+    db::upsert_comment_cursor(&pool, "issue-1", "2024-05-01T10:00:00.000Z")
+        .await
+        .unwrap();
+    db::insert_synced_comment(&pool, "comment-1", "issue-1", "500")
+        .await
+        .unwrap();
+    db::insert_relayed_comment(
+        &pool,
+        "comment-1",
+        "issue-1",
+        "500",
+        true,
+        "2024-05-01T10:00:00.000Z",
+    )
+    .await
+    .unwrap();
+    pool
+}
+
+async fn round_trip(format: Format) -> (SqlitePool, Vec<transfer::ExportedMapping>) {
+    let source = populated_pool().await;
+    let mut file = Vec::new();
+    transfer::write(format, &transfer::export(&source).await.unwrap(), &mut file).unwrap();
+    let mappings = transfer::read(format, file.as_slice()).unwrap();
+
+    let target = test_pool().await;
+    let summary = transfer::import(&target, &mappings).await.unwrap();
+    assert_eq!(
+        summary,
+        ImportSummary {
+            imported: 1,
+            skipped: 0
+        }
+    );
+    (target, mappings)
+}
+
+#[tokio::test]
+async fn json_round_trip_keeps_status_and_comment_state() {
+    let (pool, _) = round_trip(Format::Json).await;
+
+    let mapping = db::get_mapping_by_discord_thread(&pool, "100")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(mapping.linear_issue_id, "issue-1");
+    assert_eq!(mapping.linear_identifier, "ENG-1");
+    assert_eq!(
+        db::get_cached_status(&pool, "issue-1")
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("In Progress")
+    );
+    assert_eq!(
+        db::get_comment_cursor(&pool, "issue-1")
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("2024-05-01T10:00:00.000Z")
+    );
+    assert!(db::is_comment_synced(&pool, "comment-1").await.unwrap());
+    let relayed = db::get_relayed_comments(&pool, "issue-1").await.unwrap();
+    assert_eq!(relayed.len(), 1);
+    assert!(relayed[0].via_webhook);
+}
+
+#[tokio::test]
+async fn csv_round_trip_keeps_the_mapping_and_cursor() {
+    let (pool, mappings) = round_trip(Format::Csv).await;
+
+    assert!(mappings[0].synced_comments.is_empty());
+    assert!(db::get_mapping_by_linear_issue(&pool, "issue-1")
+        .await
+        .unwrap()
+        .is_some());
+    assert_eq!(
+        db::get_comment_cursor(&pool, "issue-1")
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("2024-05-01T10:00:00.000Z")
+    );
+    assert!(!db::is_comment_synced(&pool, "comment-1").await.unwrap());
+}
+
+#[tokio::test]
+async fn import_skips_threads_and_issues_already_mapped() {
+    let target = test_pool().await;
+    db::create_mapping(&target, "100", "issue-9", "ENG-9", "bug", "1")
+        .await
+        .unwrap();
+    db::create_mapping(&target, "200", "issue-2", "ENG-2", "bug", "1")
+        .await
+        .unwrap();
+
+    let mut mappings = transfer::export(&populated_pool().await).await.unwrap();
+    let mut same_issue = mappings[0].clone();
+    same_issue.discord_thread_id = "300".into();
+    same_issue.linear_issue_id = "issue-2".into();
+    let mut fresh = mappings[0].clone();
+    fresh.discord_thread_id = "400".into();
+    fresh.linear_issue_id = "issue-4".into();
+    mappings.extend([same_issue, fresh]);
+
+    let summary = transfer::import(&target, &mappings).await.unwrap();
+
+    assert_eq!(
+        summary,
+        ImportSummary {
+            imported: 1,
+            skipped: 2
+        }
+    );
+    // The skipped mapping's state didn't land on the existing one.
+    assert_eq!(
+        db::get_cached_status(&target, "issue-9").await.unwrap(),
+        None
+    );
+    assert!(db::get_mapping_by_linear_issue(&target, "issue-4")
+        .await
+        .unwrap()
+        .is_some());
+}
+
+#[tokio::test]
+async fn exports_from_before_sync_state_was_included_still_import() {
+    let legacy = r#"[{
+        "id": 7,
+        "discord_thread_id": "100",
+        "linear_issue_id": "issue-1",
+        "linear_identifier": "ENG-1",
+        "channel_type": "feature",
+        "created_at": "2024-01-01 00:00:00"
+    }]"#;
+    let mappings = transfer::read(Format::Json, legacy.as_bytes()).unwrap();
+
+    let pool = test_pool().await;
+    transfer::import(&pool, &mappings).await.unwrap();
+
+    let mapping = db::get_mapping_by_discord_thread(&pool, "100")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(mapping.created_at, "2024-01-01 00:00:00");
+    assert_eq!(db::get_cached_status(&pool, "issue-1").await.unwrap(), None);
+}
@@ -0,0 +1,52 @@
+//! `/linear connect` codes: one per Discord user, and only good for a limited time.
+
+mod common;
+
+use discord_linear_bot::db;
+
+use common::test_pool;
+
+const DISCORD_USER: &str = "4400";
+
+#[tokio::test]
+async fn new_code_replaces_the_earlier_one() {
+    let pool = test_pool().await;
+    db::upsert_user_link_code(&pool, DISCORD_USER, "user-a", "link-first")
+        .await
+        .unwrap();
+    db::upsert_user_link_code(&pool, DISCORD_USER, "user-b", "link-second")
+        .await
+        .unwrap();
+
+    let pending = db::get_user_link_code(&pool, DISCORD_USER)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(pending.linear_user_id, "user-b");
+    assert_eq!(pending.code, "link-second");
+
+    db::delete_user_link_code(&pool, DISCORD_USER)
+        .await
+        .unwrap();
+    assert!(db::get_user_link_code(&pool, DISCORD_USER)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn expired_code_is_not_returned() {
+    let pool = test_pool().await;
+    db::upsert_user_link_code(&pool, DISCORD_USER, "user-a", "link-old")
+        .await
+        .unwrap();
+    sqlx::query("UPDATE user_link_codes SET created_at = datetime('now', '-31 minutes')")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    assert!(db::get_user_link_code(&pool, DISCORD_USER)
+        .await
+        .unwrap()
+        .is_none());
+}
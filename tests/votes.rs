@@ -0,0 +1,99 @@
+//! Reaction votes on posts written to their Linear issues.
+
+mod common;
+
+use discord_linear_bot::config::ChannelConfig;
+use discord_linear_bot::db;
+use discord_linear_bot::sync::discord_to_linear::sync_discord_to_linear;
+use discord_linear_bot::sync::votes::sync_votes;
+
+use common::fake_discord::FakeDiscord;
+use common::mock_linear::MockLinear;
+use common::{channel_config, config, test_pool, FORUM_ID};
+
+fn voting_config() -> ChannelConfig {
+    let mut channel = channel_config();
+    channel.vote_sync = true;
+    channel.vote_label_id = Some("label-high-demand".to_string());
+    channel.vote_label_threshold = 3;
+    channel
+}
+
+#[tokio::test]
+async fn vote_counts_update_the_description_and_add_the_label_once() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    let channel = voting_config();
+    let config = config(vec![channel.clone()]);
+    let thread = discord.add_thread(FORUM_ID, "Dark mode", "Please add dark mode");
+    sync_discord_to_linear(&discord, &pool, &channel, &linear, &thread)
+        .await
+        .unwrap();
+    let mapping = db::get_mapping_by_discord_thread(&pool, &thread.id.to_string())
+        .await
+        .unwrap()
+        .unwrap();
+    let original = linear.issue(&mapping.linear_issue_id).unwrap().description;
+
+    // No votes yet: nothing to write.
+    assert_eq!(
+        sync_votes(&discord, &pool, &linear, &config).await.unwrap(),
+        0
+    );
+
+    discord.set_reactions(thread.id, "👍", 2);
+    discord.set_reactions(thread.id, "🎉", 5);
+    assert_eq!(
+        sync_votes(&discord, &pool, &linear, &config).await.unwrap(),
+        1
+    );
+    let issue = linear.issue(&mapping.linear_issue_id).unwrap();
+    assert_eq!(
+        issue.description,
+        format!("{original}\n\n**Discord votes:** 2")
+    );
+    assert!(!issue.label_ids.contains(&"label-high-demand".to_string()));
+
+    // Unchanged counts aren't rewritten.
+    assert_eq!(
+        sync_votes(&discord, &pool, &linear, &config).await.unwrap(),
+        0
+    );
+    assert_eq!(linear.calls("update_issue_description"), 1);
+
+    discord.set_reactions(thread.id, "👍", 3);
+    sync_votes(&discord, &pool, &linear, &config).await.unwrap();
+    let issue = linear.issue(&mapping.linear_issue_id).unwrap();
+    assert_eq!(
+        issue.description,
+        format!("{original}\n\n**Discord votes:** 3")
+    );
+    assert!(issue.label_ids.contains(&"label-high-demand".to_string()));
+
+    discord.set_reactions(thread.id, "👍", 4);
+    sync_votes(&discord, &pool, &linear, &config).await.unwrap();
+    assert_eq!(linear.calls("add_issue_label"), 1);
+}
+
+#[tokio::test]
+async fn channels_without_vote_sync_are_skipped() {
+    let pool = test_pool().await;
+    let linear = MockLinear::new();
+    let discord = FakeDiscord::new();
+    discord.add_forum(FORUM_ID);
+    let channel = channel_config();
+    let thread = discord.add_thread(FORUM_ID, "Dark mode", "Please add dark mode");
+    sync_discord_to_linear(&discord, &pool, &channel, &linear, &thread)
+        .await
+        .unwrap();
+    discord.set_reactions(thread.id, "👍", 30);
+
+    let updated = sync_votes(&discord, &pool, &linear, &config(vec![channel]))
+        .await
+        .unwrap();
+
+    assert_eq!(updated, 0);
+    assert_eq!(linear.calls("update_issue_description"), 0);
+}